@@ -0,0 +1,54 @@
+//! Check that the PETSCII maps in a configuration are bijective
+//! cargo run --example check_config
+//! cargo run --example check_config -- data/config.json
+//! cargo run --example check_config -- --named lossless-escape
+//!
+//! Prints every PETSCII code and Unicode character that doesn't map
+//! back to itself, and exits with status 1 if there are any.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{env, process::exit};
+
+use forbidden_bands::{Config, Configuration};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let config_result = match args.as_slice() {
+        [] => Config::load(),
+        [flag, name] if flag == "--named" => Config::load_named(name),
+        [filename] => Config::load_from_file(filename),
+        _ => {
+            println!("Usage: check_config [CONFIG_FILE | --named NAME]");
+            exit(2);
+        }
+    };
+
+    let config = match config_result {
+        Ok(c) => c,
+        Err(e) => {
+            println!("Error loading config: {:?}", e);
+            exit(2);
+        }
+    };
+
+    let report = match config.check_bijectivity() {
+        Ok(r) => r,
+        Err(e) => {
+            println!("Error checking config: {:?}", e);
+            exit(2);
+        }
+    };
+
+    print!("{}", report);
+    println!(
+        "{} PETSCII mismatches, {} Unicode mismatches",
+        report.petscii_mismatches.len(),
+        report.unicode_mismatches.len()
+    );
+
+    if !report.is_bijective() {
+        exit(1);
+    }
+}
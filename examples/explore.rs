@@ -0,0 +1,179 @@
+//! Explore the PETSCII mappings interactively
+//! cargo run --example explore
+//! cargo run --example explore -- --named lossless-escape
+//!
+//! Type a hex byte like $B0 or 0xB0, a single character like ┌, or
+//! part of a name like "down and right", and see the whole mapping
+//! chain: PETSCII to screen code to Unicode, and back again.  Chains
+//! that don't come back to where they started are marked, which is
+//! usually a bug in the configuration.  Press CTRL-D to quit.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{
+    env,
+    io::{stdin, stdout, BufRead, Write},
+    process::exit,
+};
+
+use forbidden_bands::{
+    petscii::{screen_code_to_petscii, unicode_to_screen_code, PetsciiConfig},
+    Config, Configuration, SystemConfig,
+};
+
+/// What was typed at the prompt
+enum Query {
+    /// A PETSCII code
+    Code(u8),
+    /// A Unicode character
+    Char(char),
+    /// Part of a code name
+    Name(String),
+}
+
+/// Work out what a line of input is asking about
+fn parse_query(line: &str) -> Option<Query> {
+    let hex = line
+        .strip_prefix('$')
+        .or_else(|| line.strip_prefix("0x"))
+        .or_else(|| line.strip_prefix("0X"))
+        .or((line.len() == 2).then_some(line));
+    if let Some(code) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+        return Some(Query::Code(code));
+    }
+
+    let mut chars = line.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => None,
+        (Some(c), None) => Some(Query::Char(c)),
+        _ => Some(Query::Name(line.to_uppercase())),
+    }
+}
+
+/// Print the chain from a PETSCII code to Unicode and back
+fn explore_code(map: &PetsciiConfig, code: u8, shifted: bool) {
+    let info = map.describe_code(code, shifted);
+    let set = if shifted { "shifted" } else { "unshifted" };
+    println!("{} ({})", info, set);
+
+    if let Some(duplicate_of) = info.duplicate_of {
+        println!("  duplicate of ${:02X}", duplicate_of.0);
+    }
+    if let Some(quoted) = info.quoted_glyph {
+        println!("  shown as {} in quote mode", quoted);
+    }
+    let Some(screen_code) = info.screen_code else {
+        println!("  no screen code");
+        return;
+    };
+    println!(
+        "  screen code set {} value {}",
+        u8::from(screen_code.set),
+        screen_code.value
+    );
+    let Some(c) = info.unicode else {
+        println!("  no Unicode character");
+        return;
+    };
+    println!("  U+{:04X} {:?}", u32::from(c), c);
+
+    // And back again
+    match unicode_to_screen_code(c, map).and_then(|s| screen_code_to_petscii(s, map)) {
+        Some(back) if back.value.0 == code && back.is_shifted() == shifted => {}
+        Some(back) if back.value.0 == info.duplicate_of.map_or(code, |d| d.0) => {}
+        Some(back) => println!(
+            "  ! encodes back to ${:02X}{}",
+            back.value.0,
+            if back.is_shifted() { " shifted" } else { "" }
+        ),
+        None => println!("  ! doesn't encode back to PETSCII"),
+    }
+}
+
+/// Print the chain from a Unicode character to PETSCII and back
+fn explore_char(map: &PetsciiConfig, c: char) {
+    println!("U+{:04X} {:?}", u32::from(c), c);
+    let Some(screen_code) = unicode_to_screen_code(c, map) else {
+        println!("  no screen code");
+        return;
+    };
+    println!(
+        "  screen code set {} value {}",
+        u8::from(screen_code.set),
+        screen_code.value
+    );
+    if screen_code.is_reversed() {
+        println!("  reversed");
+    }
+    match screen_code_to_petscii(screen_code, map) {
+        Some(petscii_code) => explore_code(map, petscii_code.value.0, petscii_code.is_shifted()),
+        None => println!("  ! no PETSCII code"),
+    }
+}
+
+/// Print every code with a name containing the text
+fn explore_name(map: &PetsciiConfig, name: &str) {
+    let mut found = false;
+    for shifted in [false, true] {
+        for code in 0..=255u8 {
+            let matches = map
+                .code_name(code, shifted)
+                .is_some_and(|n| n.contains(name));
+            // Shifted codes with the unshifted name were already shown
+            let own_name = !shifted || map.code_name(code, false) != map.code_name(code, true);
+            if matches && own_name {
+                explore_code(map, code, shifted);
+                found = true;
+            }
+        }
+    }
+    if !found {
+        println!("No code names contain {}", name);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let config_result = match args.as_slice() {
+        [] => PetsciiConfig::load(),
+        [flag, name] if flag == "--named" => Config::load_named(name),
+        _ => {
+            println!("Usage: explore [--named NAME]");
+            exit(2);
+        }
+    };
+    let petscii = match config_result.and_then(|config| config.system::<SystemConfig>()) {
+        Ok(p) => p,
+        Err(e) => {
+            println!("Error loading config: {:?}", e);
+            exit(2);
+        }
+    };
+    let map = &petscii.character_set_map;
+
+    let mut lines = stdin().lock().lines();
+    loop {
+        print!("> ");
+        stdout().flush().expect("Error writing prompt");
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+
+        match parse_query(line.trim()) {
+            Some(Query::Code(code)) => {
+                explore_code(map, code, false);
+                let (unshifted, shifted) = (
+                    map.describe_code(code, false),
+                    map.describe_code(code, true),
+                );
+                if (shifted.screen_code, shifted.name) != (unshifted.screen_code, unshifted.name) {
+                    explore_code(map, code, true);
+                }
+            }
+            Some(Query::Char(c)) => explore_char(map, c),
+            Some(Query::Name(name)) => explore_name(map, &name),
+            None => {}
+        }
+    }
+    println!();
+}
@@ -2,7 +2,7 @@
 #![warn(missing_docs)]
 #![warn(unsafe_code)]
 
-use forbidden_bands::{petscii::PetsciiString, Config, Configuration};
+use forbidden_bands::{petscii::PetsciiString, Config, Configuration, SystemConfig};
 
 fn main() {
     let config_fn = String::from("data/config.json");
@@ -16,7 +16,11 @@ fn main() {
         0x0a,
     ];
 
-    let ps = PetsciiString::new_with_config(61, hello_world_data, &config.petscii);
+    let petscii = config
+        .system::<SystemConfig>()
+        .expect("Error loading PETSCII config");
+
+    let ps = PetsciiString::new_with_config(61, hello_world_data, &petscii);
 
     println!("debugging PETSCII string: {:?}", ps);
 
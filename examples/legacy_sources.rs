@@ -0,0 +1,77 @@
+//! Check the C64 screen code tables against the Legacy Computing
+//! Sources data
+//! cargo run --features legacy-sources --example legacy_sources -- sources.txt
+//! cargo run --features legacy-sources --example legacy_sources -- sources.txt --fix
+//!
+//! Prints every screen code where the configuration and the sources
+//! disagree, and exits with status 1 if there are any.  With --fix
+//! the corrected PETSCII configuration is printed as JSON instead.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{env, fs, process::exit};
+
+use forbidden_bands::{
+    petscii::{legacy_sources::parse_sources, PetsciiConfig},
+    Configuration, SystemConfig,
+};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (filename, fix) = match args.as_slice() {
+        [filename] => (filename, false),
+        [filename, flag] if flag == "--fix" => (filename, true),
+        _ => {
+            println!("Usage: legacy_sources SOURCES_FILE [--fix]");
+            exit(2);
+        }
+    };
+
+    let mappings = match fs::read_to_string(filename)
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse_sources(&text).map_err(|e| e.to_string()))
+    {
+        Ok(m) => m,
+        Err(e) => {
+            println!("Error reading sources: {}", e);
+            exit(2);
+        }
+    };
+
+    let config = PetsciiConfig::load().expect("Error loading config");
+    let mut petscii = config
+        .system::<SystemConfig>()
+        .expect("Error loading PETSCII config");
+
+    if fix {
+        petscii.character_set_map.apply_sources(&mappings);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&petscii.character_set_map)
+                .expect("Error serializing config")
+        );
+        return;
+    }
+
+    let divergences = petscii.character_set_map.cross_check(&mappings);
+    for divergence in &divergences {
+        println!(
+            "Set {} screen code {}: sources have U+{:04X}, config has {}",
+            u8::from(divergence.screen_code.set),
+            divergence.screen_code.value,
+            u32::from(divergence.expected),
+            divergence
+                .found
+                .map_or(String::from("nothing"), |c| format!(
+                    "U+{:04X}",
+                    u32::from(c)
+                ))
+        );
+    }
+    println!("{} divergences", divergences.len());
+
+    if !divergences.is_empty() {
+        exit(1);
+    }
+}
@@ -14,7 +14,7 @@ use std::{
 
 use forbidden_bands::{
     petscii::{PetsciiConfig, PetsciiString},
-    Config, Configuration,
+    Config, Configuration, SystemConfig,
 };
 
 /// The forbidden-bands configuration for the image-rider application
@@ -31,6 +31,10 @@ fn main() {
         }
     };
 
+    let petscii = config
+        .system::<SystemConfig>()
+        .expect("Error loading PETSCII config");
+
     let mut stdin = stdin();
     let mut input: Vec<u8> = Vec::new();
 
@@ -46,7 +50,7 @@ fn main() {
     // but others might want it.
     let ps = PetsciiString::<256>::from_byte_slice_strip_shifted_space_with_config(
         input.as_slice(),
-        &config.petscii,
+        &petscii,
     );
 
     let s: String = ps.into();
@@ -3,23 +3,25 @@
 #![warn(missing_docs)]
 #![warn(unsafe_code)]
 
-use forbidden_bands::{petscii::PetsciiString, Config, Configuration};
+use forbidden_bands::{petscii::PetsciiString, Config, Configuration, SystemConfig};
 
 fn main() {
     let config_fn = String::from("data/config.json");
     let config = Config::load_from_file(&config_fn).expect("Error loading config file");
 
+    let petscii = config
+        .system::<SystemConfig>()
+        .expect("Error loading PETSCII config");
+
     // Test config
     let key: String = 84.to_string();
-    let res = config
-        .petscii
+    let res = petscii
         .character_set_map
         .c64_petscii_unshifted_codes_to_screen_codes
         .get(&key);
     println!("res: {:?}", res);
 
-    let ps =
-        PetsciiString::new_with_config(6, [0x41, 0x42, 0x43, 0x5c, 0x5e, 0x5f], &config.petscii);
+    let ps = PetsciiString::new_with_config(6, [0x41, 0x42, 0x43, 0x5c, 0x5e, 0x5f], &petscii);
 
     println!("debugging PETSCII string: {:?}", ps);
     println!("printing  PETSCII string: {}", ps);
@@ -28,7 +30,7 @@ fn main() {
 
     println!("PETSCII string as String string: {:?}", s);
 
-    let ps_shifted = PetsciiString::new_with_config(3, [0x41, 0x42, 0x43], &config.petscii);
+    let ps_shifted = PetsciiString::new_with_config(3, [0x41, 0x42, 0x43], &petscii);
 
     println!("debugging PETSCII string: {:?}", ps_shifted);
     println!("printing  PETSCII string: {}", ps_shifted);
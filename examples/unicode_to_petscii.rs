@@ -13,7 +13,7 @@ use std::{
 
 use forbidden_bands::{
     petscii::{PetsciiConfig, PetsciiString},
-    Configuration,
+    Configuration, SystemConfig,
 };
 
 /// The forbidden-bands configuration for the image-rider application
@@ -34,7 +34,11 @@ fn main() {
         panic!("Can't read in more than 256 bytes, {bytes_read} read in");
     }
 
-    let ps = PetsciiString::<256>::from_str_with_config(input.as_str(), &config.petscii);
+    let petscii = config
+        .system::<SystemConfig>()
+        .expect("Error loading PETSCII config");
+
+    let ps = PetsciiString::<256>::from_str_with_config(input.as_str(), &petscii);
 
     let s: String = ps.into();
     println!("{}", s);
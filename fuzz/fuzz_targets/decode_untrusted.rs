@@ -0,0 +1,21 @@
+//! Decode arbitrary bytes with the default PETSCII configuration
+#![no_main]
+
+use std::sync::OnceLock;
+
+use forbidden_bands::{petscii::PetsciiConfig, Configuration, SystemConfig};
+use libfuzzer_sys::fuzz_target;
+
+fn petscii() -> &'static SystemConfig {
+    static PETSCII: OnceLock<SystemConfig> = OnceLock::new();
+    PETSCII.get_or_init(|| {
+        PetsciiConfig::load()
+            .expect("Error loading config")
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config")
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = petscii().decode_untrusted(data);
+});
@@ -0,0 +1,23 @@
+//! Decode arbitrary bytes with an arbitrary configuration
+//!
+//! The input is a JSON configuration, a zero byte and the bytes to
+//! decode.  Inputs that aren't a valid configuration are skipped.
+#![no_main]
+
+use forbidden_bands::{Config, SystemConfig};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(split) = data.iter().position(|b| *b == 0) else {
+        return;
+    };
+    let (json, bytes) = (&data[..split], &data[split + 1..]);
+
+    let Ok(config) = serde_json::from_slice::<Config>(json) else {
+        return;
+    };
+    let Ok(petscii) = config.system::<SystemConfig>() else {
+        return;
+    };
+    let _ = petscii.decode_untrusted(bytes);
+});
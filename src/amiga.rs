@@ -0,0 +1,321 @@
+//!
+//! Amiga console text library
+//!
+//! The Amiga uses ISO-8859-1 (Latin-1) for text.  The Topaz ROM font
+//! has glyphs for the printable Latin-1 characters, so decoding the
+//! characters themselves is a direct mapping.
+//!
+//! The Amiga console device also understands control sequences.
+//! They start with the Control Sequence Introducer (CSI), either the
+//! single byte 0x9B or the two byte sequence ESC [.  The CSI is
+//! followed by parameter bytes (digits and semicolons), optional
+//! intermediate bytes and a final byte from 0x40 to 0x7E.  Most of
+//! them are the same as ANSI X3.64, which is what ANSI and ASCII art
+//! from Amiga BBSs uses to set colors and move the cursor.
+//!
+//! Modern terminals don't understand the single byte CSI, and a few
+//! sequences are Amiga-specific, like the window and cursor
+//! visibility controls.  When decoding, the sequences can be
+//! stripped, preserved as they are or translated to the ANSI
+//! sequences modern terminals understand.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::CharacterSet;
+
+/// The single byte Control Sequence Introducer
+pub const CSI: u8 = 0x9B;
+
+/// The escape character
+pub const ESC: u8 = 0x1B;
+
+/// What to do with control sequences when decoding
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CsiMode {
+    /// Remove control sequences from the output
+    Strip,
+    /// Keep control sequences as they are, with the single byte CSI
+    /// decoded as U+009B and ESC [ kept as ESC [
+    Preserve,
+    /// Translate control sequences to ANSI sequences using ESC [
+    ///
+    /// Amiga-specific sequences without an ANSI equivalent are
+    /// removed.
+    #[default]
+    Translate,
+}
+
+/// How a control sequence was introduced
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Introducer {
+    /// The single byte CSI, 0x9B
+    #[default]
+    Csi,
+    /// The two byte sequence ESC [
+    EscBracket,
+}
+
+/// A control sequence split into its parts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControlSequence {
+    /// How the sequence was introduced
+    pub introducer: Introducer,
+    /// The parameter bytes
+    pub parameters: String,
+    /// The intermediate bytes
+    pub intermediates: String,
+    /// The final byte
+    pub final_byte: char,
+}
+
+impl ControlSequence {
+    /// Translate an Amiga control sequence to an ANSI sequence
+    ///
+    /// Returns None if there isn't an ANSI equivalent.
+    pub fn to_ansi(&self) -> Option<String> {
+        match (self.intermediates.as_str(), self.final_byte) {
+            // Cursor visibility, CSI 0 SPACE p hides the cursor
+            (" ", 'p') => {
+                if self.parameters == "0" {
+                    Some(String::from("\x1b[?25l"))
+                } else {
+                    Some(String::from("\x1b[?25h"))
+                }
+            }
+            // Page length, line length, left offset, top offset,
+            // raw events and window status requests
+            ("", 't' | 'u' | 'x' | 'y' | '{' | '}' | 'q') => None,
+            _ => Some(format!(
+                "\x1b[{}{}{}",
+                self.parameters, self.intermediates, self.final_byte
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for ControlSequence {
+    /// Format the sequence with the introducer it was read with
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let introducer = match self.introducer {
+            Introducer::Csi => "\u{9b}",
+            Introducer::EscBracket => "\x1b[",
+        };
+        write!(
+            f,
+            "{}{}{}{}",
+            introducer, self.parameters, self.intermediates, self.final_byte
+        )
+    }
+}
+
+/// Parse a control sequence from the bytes after the introducer
+///
+/// Returns the sequence and the number of bytes used, or None if the
+/// sequence isn't complete.
+fn parse_sequence(introducer: Introducer, bytes: &[u8]) -> Option<(ControlSequence, usize)> {
+    let mut parameters = String::new();
+    let mut intermediates = String::new();
+
+    for (i, b) in bytes.iter().enumerate() {
+        match b {
+            0x30..=0x3F if intermediates.is_empty() => parameters.push(char::from(*b)),
+            0x20..=0x2F => intermediates.push(char::from(*b)),
+            0x40..=0x7E => {
+                return Some((
+                    ControlSequence {
+                        introducer,
+                        parameters,
+                        intermediates,
+                        final_byte: char::from(*b),
+                    },
+                    i + 1,
+                ))
+            }
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+/// Amiga console text
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     amiga::{AmigaText, CsiMode},
+///     CharacterSet,
+/// };
+///
+/// let amiga = AmigaText::new(CsiMode::Translate);
+///
+/// // CSI 3 3 m sets the text color to pen 3
+/// assert_eq!(amiga.decode(b"\x9b33mHej d\xe5"), "\x1b[33mHej då");
+///
+/// let amiga = AmigaText::new(CsiMode::Strip);
+/// assert_eq!(amiga.decode(b"\x9b33mHej d\xe5"), "Hej då");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AmigaText {
+    /// What to do with control sequences when decoding
+    pub csi_mode: CsiMode,
+}
+
+impl AmigaText {
+    /// Create a new Amiga text character set
+    pub fn new(csi_mode: CsiMode) -> AmigaText {
+        AmigaText { csi_mode }
+    }
+
+    /// Split Amiga console text into text and control sequences
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::amiga::{AmigaText, ConsoleItem};
+    ///
+    /// let items = AmigaText::default().parse(b"A\x1b[1mB");
+    ///
+    /// assert_eq!(items.len(), 3);
+    /// assert!(matches!(&items[1], ConsoleItem::Sequence(s) if s.final_byte == 'm'));
+    /// ```
+    pub fn parse(&self, bytes: &[u8]) -> Vec<ConsoleItem> {
+        let mut items: Vec<ConsoleItem> = Vec::new();
+        let mut text = String::new();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let start = match bytes[i] {
+                CSI => Some((Introducer::Csi, i + 1)),
+                ESC if bytes.get(i + 1) == Some(&b'[') => Some((Introducer::EscBracket, i + 2)),
+                _ => None,
+            };
+
+            if let Some((introducer, start)) = start {
+                if let Some((sequence, used)) = parse_sequence(introducer, &bytes[start..]) {
+                    if !text.is_empty() {
+                        items.push(ConsoleItem::Text(std::mem::take(&mut text)));
+                    }
+                    items.push(ConsoleItem::Sequence(sequence));
+                    i = start + used;
+                    continue;
+                }
+            }
+
+            // Latin-1 maps directly to the first 256 Unicode code points
+            text.push(char::from(bytes[i]));
+            i += 1;
+        }
+
+        if !text.is_empty() {
+            items.push(ConsoleItem::Text(text));
+        }
+
+        items
+    }
+}
+
+/// A piece of Amiga console text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsoleItem {
+    /// Printable text and single byte control characters
+    Text(String),
+    /// A control sequence
+    Sequence(ControlSequence),
+}
+
+impl CharacterSet for AmigaText {
+    /// Decode Amiga console text, handling control sequences using
+    /// the CSI mode
+    ///
+    /// In the Translate mode a form feed, which clears the console
+    /// window on the Amiga, is translated to the ANSI clear screen
+    /// and cursor home sequences.
+    fn decode(&self, bytes: &[u8]) -> String {
+        self.parse(bytes)
+            .iter()
+            .map(|item| match (item, self.csi_mode) {
+                (ConsoleItem::Text(t), CsiMode::Translate) => t.replace('\x0c', "\x1b[2J\x1b[H"),
+                (ConsoleItem::Text(t), _) => t.clone(),
+                (ConsoleItem::Sequence(_), CsiMode::Strip) => String::new(),
+                (ConsoleItem::Sequence(s), CsiMode::Preserve) => s.to_string(),
+                (ConsoleItem::Sequence(s), CsiMode::Translate) => s.to_ansi().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Encode a Unicode string as Latin-1
+    ///
+    /// Characters outside Latin-1 are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{amiga::AmigaText, CharacterSet};
+    ///
+    /// assert_eq!(AmigaText::default().encode("Café €"), b"Caf\xe9 ");
+    /// ```
+    fn encode(&self, s: &str) -> Vec<u8> {
+        s.chars().filter_map(|c| u8::try_from(c).ok()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        amiga::{AmigaText, ConsoleItem, ControlSequence, CsiMode, Introducer},
+        CharacterSet,
+    };
+
+    #[test]
+    fn amiga_preserve_mode_works() {
+        let amiga = AmigaText::new(CsiMode::Preserve);
+
+        assert_eq!(amiga.decode(b"\x9b1;31mX"), "\u{9b}1;31mX");
+        // The seven bit form keeps its ESC [
+        assert_eq!(amiga.decode(b"\x1b[0mX"), "\x1b[0mX");
+        assert_eq!(amiga.decode(b"\x9b1m\x1b[33mX"), "\u{9b}1m\x1b[33mX");
+    }
+
+    #[test]
+    fn amiga_translate_amiga_specific_sequences_works() {
+        let amiga = AmigaText::new(CsiMode::Translate);
+
+        // Hide the cursor, set the page length and clear the window
+        assert_eq!(
+            amiga.decode(b"\x9b0 p\x9b25t\x0cHi"),
+            "\x1b[?25l\x1b[2J\x1b[HHi"
+        );
+    }
+
+    #[test]
+    fn amiga_incomplete_sequence_is_text() {
+        let amiga = AmigaText::new(CsiMode::Strip);
+
+        assert_eq!(amiga.decode(b"A\x1b[12"), "A\x1b[12");
+    }
+
+    #[test]
+    fn amiga_parse_sequence_parts_work() {
+        let items = AmigaText::default().parse(b"\x9b>1 p");
+
+        assert_eq!(
+            items,
+            vec![ConsoleItem::Sequence(ControlSequence {
+                introducer: Introducer::Csi,
+                parameters: String::from(">1"),
+                intermediates: String::from(" "),
+                final_byte: 'p',
+            })]
+        );
+    }
+
+    #[test]
+    fn amiga_latin1_round_trip_works() {
+        let amiga = AmigaText::new(CsiMode::Strip);
+        let data: Vec<u8> = (0x20..=0x7E).chain(0xA0..=0xFF).collect();
+
+        assert_eq!(amiga.encode(&amiga.decode(&data)), data);
+    }
+}
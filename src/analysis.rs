@@ -0,0 +1,357 @@
+//!
+//! Statistics over PETSCII data
+//!
+//! Raw disk sectors and memory dumps mix machine code, text and
+//! screen art.  The statistics in this module help tell them apart.
+//! Text is mostly letters, digits and punctuation, art is mostly
+//! graphics characters with color and reverse video codes, and
+//! machine code has a lot of control codes and no long printable
+//! runs.
+//!
+//! The PETSCII codes are classified without a configuration, so the
+//! statistics can be gathered quickly over large amounts of data.
+//! The data is assumed to start in the unshifted (uppercase and
+//! graphics) character set.
+//!
+//! extract_strings works like the Unix strings command, finding runs
+//! of text in ROMs, disk images and other binaries in any of the
+//! character sets.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::ops::Range;
+
+use crate::CharacterSet;
+
+/// Switch to the shifted (lowercase and uppercase) character set
+const SHIFT_ON: u8 = 0x0E;
+/// Switch to the unshifted (uppercase and graphics) character set
+const SHIFT_OFF: u8 = 0x8E;
+/// Turn reverse video on
+const REVERSE_ON: u8 = 0x12;
+/// Turn reverse video off
+const REVERSE_OFF: u8 = 0x92;
+
+/// The kind of character a PETSCII code is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeClass {
+    /// A control code, like a color, cursor movement or carriage
+    /// return
+    Control,
+    /// A letter, digit, punctuation character or space
+    Text,
+    /// A graphics character
+    Graphics,
+}
+
+/// Classify a PETSCII code
+///
+/// Some codes are letters in the shifted character set and graphics
+/// characters in the unshifted character set.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::analysis::{classify, CodeClass};
+///
+/// assert_eq!(classify(0x05, false), CodeClass::Control);
+/// assert_eq!(classify(0x41, false), CodeClass::Text);
+/// assert_eq!(classify(0xC1, false), CodeClass::Graphics);
+/// assert_eq!(classify(0xC1, true), CodeClass::Text);
+/// ```
+pub fn classify(code: u8, shifted: bool) -> CodeClass {
+    match code {
+        0x00..=0x1F | 0x80..=0x9F => CodeClass::Control,
+        // Space, punctuation, digits and the unshifted letters
+        0x20..=0x5F | 0xA0 => CodeClass::Text,
+        // The uppercase letters in the shifted character set
+        0x61..=0x7A | 0xC1..=0xDA if shifted => CodeClass::Text,
+        _ => CodeClass::Graphics,
+    }
+}
+
+/// Statistics over a sequence of PETSCII codes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PetsciiStats {
+    /// The number of times each code appears
+    pub frequencies: [usize; 256],
+    /// The number of control codes
+    pub control_codes: usize,
+    /// The number of letters, digits, punctuation characters and
+    /// spaces
+    pub text_characters: usize,
+    /// The number of graphics characters
+    pub graphics_characters: usize,
+    /// The number of codes that switch between the shifted and
+    /// unshifted character sets
+    pub charset_switches: usize,
+    /// The number of codes that turn reverse video on or off
+    pub reverse_switches: usize,
+    /// The position of the longest run of text and graphics
+    /// characters without any control codes
+    pub longest_printable_run: Range<usize>,
+}
+
+impl PetsciiStats {
+    /// The total number of codes
+    pub fn len(&self) -> usize {
+        self.control_codes + self.text_characters + self.graphics_characters
+    }
+
+    /// True if there weren't any codes
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of times a code appears
+    pub fn count(&self, code: u8) -> usize {
+        self.frequencies[usize::from(code)]
+    }
+
+    /// The proportion of control codes, between 0 and 1
+    pub fn control_ratio(&self) -> f64 {
+        self.ratio(self.control_codes)
+    }
+
+    /// The proportion of text characters, between 0 and 1
+    pub fn text_ratio(&self) -> f64 {
+        self.ratio(self.text_characters)
+    }
+
+    /// The proportion of graphics characters, between 0 and 1
+    pub fn graphics_ratio(&self) -> f64 {
+        self.ratio(self.graphics_characters)
+    }
+
+    fn ratio(&self, count: usize) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            count as f64 / self.len() as f64
+        }
+    }
+}
+
+/// Gather statistics over a sequence of PETSCII codes
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::analysis::analyze;
+///
+/// // HELLO, a carriage return, then a reversed ball
+/// let stats = analyze(&[0x48, 0x45, 0x4C, 0x4C, 0x4F, 0x0D, 0x12, 0xD1, 0x92]);
+///
+/// assert_eq!(stats.text_characters, 5);
+/// assert_eq!(stats.graphics_characters, 1);
+/// assert_eq!(stats.control_codes, 3);
+/// assert_eq!(stats.reverse_switches, 2);
+/// assert_eq!(stats.longest_printable_run, 0..5);
+/// ```
+pub fn analyze(bytes: &[u8]) -> PetsciiStats {
+    let mut stats = PetsciiStats {
+        frequencies: [0; 256],
+        control_codes: 0,
+        text_characters: 0,
+        graphics_characters: 0,
+        charset_switches: 0,
+        reverse_switches: 0,
+        longest_printable_run: 0..0,
+    };
+
+    let mut shifted = false;
+    let mut run_start = 0;
+
+    for (offset, code) in bytes.iter().copied().enumerate() {
+        stats.frequencies[usize::from(code)] += 1;
+
+        match classify(code, shifted) {
+            CodeClass::Control => {
+                stats.control_codes += 1;
+                run_start = offset + 1;
+            }
+            CodeClass::Text => stats.text_characters += 1,
+            CodeClass::Graphics => stats.graphics_characters += 1,
+        }
+
+        if offset + 1 - run_start > stats.longest_printable_run.len() {
+            stats.longest_printable_run = run_start..offset + 1;
+        }
+
+        match code {
+            SHIFT_ON | SHIFT_OFF => {
+                shifted = code == SHIFT_ON;
+                stats.charset_switches += 1;
+            }
+            REVERSE_ON | REVERSE_OFF => stats.reverse_switches += 1,
+            _ => (),
+        }
+    }
+
+    stats
+}
+
+/// A run of text found in binary data
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtractedString<'a> {
+    /// The offset of the run in the data
+    pub offset: usize,
+    /// The character codes in the run
+    pub bytes: &'a [u8],
+    /// The decoded text
+    pub text: String,
+}
+
+/// Check if a character looks like part of a text string
+///
+/// Letters, digits, spaces, punctuation and the Latin-1 symbols are
+/// text.  The arrows are included because they're in the text range
+/// of PETSCII.  Control characters, box drawing and the other
+/// graphics characters aren't.
+fn is_text_char(c: char) -> bool {
+    c == ' '
+        || c.is_ascii_graphic()
+        || c.is_alphanumeric()
+        || ('\u{a0}'..='\u{ff}').contains(&c)
+        || c == '↑'
+        || c == '←'
+}
+
+/// Find the runs of text in binary data
+///
+/// Each byte is decoded on its own with the character set, and runs of
+/// at least min_len bytes that decode to text characters are
+/// returned.  Because the bytes are decoded one at a time, character
+/// sets with state are decoded in their initial state.  For PETSCII
+/// that's the unshifted character set, so the uppercase letters of the
+/// shifted character set end a run.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     analysis::extract_strings, petscii::PetsciiConfig, Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let data = [0xA9, 0x00, 0x48, 0x49, 0x20, 0x4D, 0x4F, 0x4D, 0x00, 0x4F, 0x4B, 0x60];
+/// let strings = extract_strings(&data, 4, &petscii);
+///
+/// assert_eq!(strings.len(), 1);
+/// assert_eq!(strings[0].offset, 2);
+/// assert_eq!(strings[0].text, "HI MOM");
+/// ```
+pub fn extract_strings<'a, C: CharacterSet + ?Sized>(
+    bytes: &'a [u8],
+    min_len: usize,
+    charset: &C,
+) -> Vec<ExtractedString<'a>> {
+    let mut strings = Vec::new();
+    let mut run_start = 0;
+
+    for offset in 0..=bytes.len() {
+        let is_text = bytes.get(offset).is_some_and(|code| {
+            let decoded = charset.decode(&[*code]);
+            let mut chars = decoded.chars();
+            matches!((chars.next(), chars.next()), (Some(c), None) if is_text_char(c))
+        });
+
+        if is_text {
+            continue;
+        }
+
+        // Empty runs are skipped even if min_len is zero
+        if offset > run_start && offset - run_start >= min_len {
+            let run = &bytes[run_start..offset];
+            strings.push(ExtractedString {
+                offset: run_start,
+                bytes: run,
+                text: charset.decode(run),
+            });
+        }
+        run_start = offset + 1;
+    }
+
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::{analyze, classify, extract_strings, CodeClass},
+        custom::TableEncoding,
+        petscii::PetsciiConfig,
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn analysis_shift_state_changes_classes() {
+        // The same code is a graphics character, then a letter
+        let stats = analyze(&[0xC1, 0x0E, 0xC1, 0x8E, 0xC1]);
+
+        assert_eq!(stats.graphics_characters, 2);
+        assert_eq!(stats.text_characters, 1);
+        assert_eq!(stats.charset_switches, 2);
+        assert_eq!(stats.count(0xC1), 3);
+    }
+
+    #[test]
+    fn analysis_longest_printable_run_works() {
+        let stats = analyze(&[0x41, 0x0D, 0x41, 0x42, 0xA6, 0x43, 0x05, 0x41]);
+
+        assert_eq!(stats.longest_printable_run, 2..6);
+    }
+
+    #[test]
+    fn analysis_ratios_work() {
+        let empty = analyze(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.text_ratio(), 0.0);
+
+        let stats = analyze(&[0x41, 0x42, 0xA6, 0x0D]);
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats.text_ratio(), 0.5);
+        assert_eq!(stats.graphics_ratio(), 0.25);
+        assert_eq!(stats.control_ratio(), 0.25);
+        assert_eq!(classify(0xFF, true), CodeClass::Graphics);
+    }
+
+    #[test]
+    fn extract_strings_min_len_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // A run at the end of the data is found too
+        let data = [0x41, 0x42, 0x0D, 0x43, 0x44, 0x45, 0xB0, 0x46, 0x47, 0x48];
+
+        let strings = extract_strings(&data, 3, &petscii);
+        let found: Vec<(usize, &str)> = strings
+            .iter()
+            .map(|s| (s.offset, s.text.as_str()))
+            .collect();
+        assert_eq!(found, vec![(3, "CDE"), (7, "FGH")]);
+
+        assert_eq!(extract_strings(&data, 0, &petscii).len(), 3);
+        assert!(extract_strings(&data, 4, &petscii).is_empty());
+    }
+
+    #[test]
+    fn extract_strings_custom_table_works() {
+        let table = std::array::from_fn(|code| match code {
+            0x01..=0x1A => char::from_u32(0x40 + code as u32),
+            0x20 => Some(' '),
+            0x80 => Some('┼'),
+            _ => None,
+        });
+        let encoding = TableEncoding::new("letters", table);
+
+        let data = [0x08, 0x09, 0x20, 0x80, 0x01, 0x02, 0x03];
+        let strings = extract_strings(&data, 3, &encoding);
+
+        assert_eq!(strings.len(), 2);
+        assert_eq!(strings[0].text, "HI ");
+        assert_eq!(strings[1].bytes, &[0x01, 0x02, 0x03]);
+    }
+}
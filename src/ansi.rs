@@ -0,0 +1,788 @@
+//!
+//! ANSI art conversion
+//!
+//! ANSI art from PC BBSs is code page 437 text with ANSI escape
+//! sequences that move the cursor and set colors (SGR, Select Graphic
+//! Rendition).  PETSCII art is a stream of PETSCII characters and
+//! control codes that do the same things on a Commodore 64.
+//!
+//! The two don't have the same characters or the same colors, so
+//! converting between them is an approximation.  The 16 ANSI colors
+//! are matched to the nearest color in the other palette, and 256
+//! color and 24-bit color sequences are matched to the nearest color
+//! in a C64 palette.  Block and box drawing
+//! characters are matched to similar characters, using reverse video
+//! for blocks PETSCII only has the inverse of.  Anything else is
+//! replaced with its ASCII approximation.
+//!
+//! A C64 has one background color for the whole screen, which can't
+//! be changed from a PETSCII stream.  ANSI cells with a different
+//! background color are drawn in reverse video when that keeps the
+//! shape of the cell.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ascii::AsciiConfig,
+    config_data,
+    ebcdic::CodePage,
+    error::Error,
+    load_cached,
+    palette::{Palette, Rgb},
+    petscii::Color,
+    CharacterSet, SystemConfig,
+};
+
+/// Configuration data with the code page 437 table
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AnsiConfig {
+    /// Version of the ANSI config
+    pub version: String,
+
+    /// Code page 437 to Unicode codes
+    pub cp437_to_unicode_codes: Vec<u32>,
+}
+
+/// Configuration data for the ANSI module
+pub static CONFIG: RwLock<Option<AnsiConfig>> = RwLock::new(None);
+
+impl AnsiConfig {
+    /// Load the embedded ANSI configuration
+    pub fn load() -> std::result::Result<AnsiConfig, Error> {
+        load_cached(&CONFIG, config_data::CP437_CHARACTER_MAP)
+    }
+
+    /// Build the code page 437 character set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{ansi::AnsiConfig, CharacterSet};
+    ///
+    /// let config = AnsiConfig::load().expect("Error loading config");
+    /// let cp437 = config.cp437().expect("Error building code page");
+    ///
+    /// assert_eq!(cp437.decode(&[0xC9, 0xCD, 0xBB, 0xDB]), "╔═╗█");
+    /// ```
+    pub fn cp437(&self) -> std::result::Result<CodePage, Error> {
+        CodePage::new(&self.cp437_to_unicode_codes)
+    }
+}
+
+/// The nearest C64 color to each of the 16 ANSI colors
+const ANSI_TO_C64: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Brown,
+    Color::Blue,
+    Color::Purple,
+    Color::Cyan,
+    Color::LightGrey,
+    Color::DarkGrey,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::Yellow,
+    Color::LightBlue,
+    Color::Purple,
+    Color::Cyan,
+    Color::White,
+];
+
+/// The nearest ANSI color to each C64 color, in color number order
+const C64_TO_ANSI: [u8; 16] = [0, 15, 1, 14, 5, 2, 4, 11, 3, 3, 9, 8, 8, 10, 12, 7];
+
+/// The nearest C64 color to one of the 16 ANSI colors
+///
+/// Colors 8 to 15 are the bright colors.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{ansi::c64_color, petscii::Color};
+///
+/// assert_eq!(c64_color(4), Color::Blue);
+/// assert_eq!(c64_color(12), Color::LightBlue);
+/// ```
+pub fn c64_color(ansi: u8) -> Color {
+    ANSI_TO_C64[usize::from(ansi & 0x0F)]
+}
+
+/// The nearest of the 16 ANSI colors to a C64 color
+pub fn ansi_color(color: Color) -> u8 {
+    C64_TO_ANSI[usize::from(color.index())]
+}
+
+/// Block and box drawing characters without a PETSCII character,
+/// with the character to use instead and whether it's reversed
+const PETSCII_SUBSTITUTES: [(char, char, bool); 30] = [
+    ('█', ' ', true),
+    ('▀', '▄', true),
+    ('▐', '▌', true),
+    ('░', '▒', false),
+    ('▓', '▒', true),
+    ('─', '🭹', false),
+    ('═', '🭹', false),
+    ('│', '🭲', false),
+    ('║', '🭲', false),
+    ('╒', '┌', false),
+    ('╓', '┌', false),
+    ('╔', '┌', false),
+    ('╕', '┐', false),
+    ('╖', '┐', false),
+    ('╗', '┐', false),
+    ('╘', '└', false),
+    ('╙', '└', false),
+    ('╚', '└', false),
+    ('╛', '┘', false),
+    ('╜', '┘', false),
+    ('╝', '┘', false),
+    ('╞', '├', false),
+    ('╟', '├', false),
+    ('╠', '├', false),
+    ('╡', '┤', false),
+    ('╢', '┤', false),
+    ('╣', '┤', false),
+    ('╪', '┼', false),
+    ('╫', '┼', false),
+    ('╬', '┼', false),
+];
+
+/// PETSCII graphics characters without a code page 437 character,
+/// with the character to use instead
+const CP437_SUBSTITUTES: [(char, char); 6] = [
+    ('🭹', '─'),
+    ('🭲', '│'),
+    ('▔', '▀'),
+    ('▁', '_'),
+    ('▎', '▌'),
+    ('▍', '▌'),
+];
+
+/// Options for converting between ANSI art and PETSCII
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArtOptions {
+    /// The width of the ANSI screen
+    pub ansi_columns: usize,
+    /// The width of the C64 screen
+    ///
+    /// ANSI rows are cut to this width, and PETSCII text wraps at it.
+    pub petscii_columns: usize,
+    /// The background color of the C64 screen
+    pub background: Color,
+    /// The text color at the start of a PETSCII stream
+    pub foreground: Color,
+    /// The palette 256 color and 24-bit ANSI colors are matched
+    /// against
+    pub palette: Palette,
+}
+
+impl Default for ArtOptions {
+    fn default() -> Self {
+        ArtOptions {
+            ansi_columns: 80,
+            petscii_columns: 40,
+            background: Color::Black,
+            foreground: Color::LightBlue,
+            palette: Palette::default(),
+        }
+    }
+}
+
+/// A color set by an SGR sequence
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AnsiColor {
+    /// One of the 16 ANSI colors
+    Indexed(u8),
+    /// A color from the 256 color cube or a 24-bit color
+    Rgb(Rgb),
+}
+
+/// The RGB value of a color in the xterm 256 color palette, above
+/// the 16 ANSI colors
+fn xterm_rgb(index: u8) -> Rgb {
+    if index >= 232 {
+        let level = 8 + 10 * (index - 232);
+        return Rgb::new(level, level, level);
+    }
+
+    let index = index - 16;
+    let level = |v: u8| if v == 0 { 0 } else { 55 + 40 * v };
+    Rgb::new(level(index / 36), level(index / 6 % 6), level(index % 6))
+}
+
+/// A character cell on an ANSI screen, with the colors matched to
+/// C64 colors
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    c: char,
+    foreground: Color,
+    background: Color,
+}
+
+const BLANK: Cell = Cell {
+    c: ' ',
+    foreground: Color::LightGrey,
+    background: Color::Black,
+};
+
+/// The most rows an ANSI screen can have
+///
+/// Cursor movements are clamped to the screen, so a hostile sequence
+/// like ESC[999999999H can't make the screen allocate billions of
+/// rows.  Text past the last row is drawn over it.
+const MAX_ROWS: usize = 10_000;
+
+/// The most columns an ANSI screen can have
+const MAX_COLUMNS: usize = 1_000;
+
+/// An ANSI screen being drawn on
+struct AnsiScreen {
+    rows: Vec<Vec<Cell>>,
+    columns: usize,
+    palette: Palette,
+    row: usize,
+    column: usize,
+    saved: (usize, usize),
+    foreground: AnsiColor,
+    background: AnsiColor,
+    bold: bool,
+    reversed: bool,
+}
+
+impl AnsiScreen {
+    fn new(columns: usize, palette: Palette) -> AnsiScreen {
+        AnsiScreen {
+            rows: Vec::new(),
+            columns: columns.clamp(1, MAX_COLUMNS),
+            palette,
+            row: 0,
+            column: 0,
+            saved: (0, 0),
+            foreground: AnsiColor::Indexed(7),
+            background: AnsiColor::Indexed(0),
+            bold: false,
+            reversed: false,
+        }
+    }
+
+    fn c64_color(&self, color: AnsiColor) -> Color {
+        match color {
+            AnsiColor::Indexed(ansi) => c64_color(ansi),
+            AnsiColor::Rgb(rgb) => self.palette.nearest(rgb),
+        }
+    }
+
+    /// Move the cursor down, staying on the screen
+    fn move_down(&mut self, count: usize) {
+        self.row = self.row.saturating_add(count).min(MAX_ROWS - 1);
+    }
+
+    fn put(&mut self, c: char) {
+        if self.column >= self.columns {
+            self.move_down(1);
+            self.column = 0;
+        }
+        if self.rows.len() <= self.row {
+            self.rows.resize(self.row + 1, Vec::new());
+        }
+
+        // Bold makes the first eight colors bright
+        let foreground = match self.foreground {
+            AnsiColor::Indexed(ansi) if self.bold => AnsiColor::Indexed(ansi | 0x08),
+            foreground => foreground,
+        };
+        let foreground = self.c64_color(foreground);
+        let background = self.c64_color(self.background);
+        let (foreground, background) = if self.reversed {
+            (background, foreground)
+        } else {
+            (foreground, background)
+        };
+
+        let row = &mut self.rows[self.row];
+        if row.len() <= self.column {
+            row.resize(self.column + 1, BLANK);
+        }
+        row[self.column] = Cell {
+            c,
+            foreground,
+            background,
+        };
+        self.column += 1;
+    }
+
+    /// Run a control sequence
+    fn control(&mut self, parameters: &str, final_byte: u8) {
+        let numbers: Vec<usize> = parameters
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let count = numbers[0].max(1);
+
+        match final_byte {
+            b'A' => self.row = self.row.saturating_sub(count),
+            b'B' => self.move_down(count),
+            b'C' => self.column = self.column.saturating_add(count).min(self.columns - 1),
+            b'D' => self.column = self.column.saturating_sub(count),
+            b'H' | b'f' => {
+                self.row = numbers[0].saturating_sub(1).min(MAX_ROWS - 1);
+                self.column = numbers
+                    .get(1)
+                    .map_or(0, |column| column.saturating_sub(1))
+                    .min(self.columns - 1);
+            }
+            b'J' if numbers[0] == 2 => {
+                self.rows.clear();
+                self.row = 0;
+                self.column = 0;
+            }
+            b'K' => {
+                if let Some(row) = self.rows.get_mut(self.row) {
+                    row.truncate(self.column);
+                }
+            }
+            b'm' => self.select_graphic_rendition(&numbers),
+            b's' => self.saved = (self.row, self.column),
+            b'u' => (self.row, self.column) = self.saved,
+            _ => (),
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, numbers: &[usize]) {
+        let mut numbers = numbers.iter().copied();
+
+        while let Some(n) = numbers.next() {
+            match n {
+                0 => {
+                    self.foreground = AnsiColor::Indexed(7);
+                    self.background = AnsiColor::Indexed(0);
+                    self.bold = false;
+                    self.reversed = false;
+                }
+                1 => self.bold = true,
+                7 => self.reversed = true,
+                22 => self.bold = false,
+                27 => self.reversed = false,
+                30..=37 => self.foreground = AnsiColor::Indexed((n - 30) as u8),
+                39 => self.foreground = AnsiColor::Indexed(7),
+                40..=47 => self.background = AnsiColor::Indexed((n - 40) as u8),
+                49 => self.background = AnsiColor::Indexed(0),
+                90..=97 => self.foreground = AnsiColor::Indexed((n - 90) as u8 | 0x08),
+                100..=107 => self.background = AnsiColor::Indexed((n - 100) as u8 | 0x08),
+                38 | 48 => {
+                    // 5;n selects a 256 color palette entry and
+                    // 2;r;g;b a 24-bit color
+                    let mut next = || numbers.next().unwrap_or(0).min(255) as u8;
+                    let color = match next() {
+                        5 => match next() {
+                            index @ 0..=15 => AnsiColor::Indexed(index),
+                            index => AnsiColor::Rgb(xterm_rgb(index)),
+                        },
+                        2 => AnsiColor::Rgb(Rgb::new(next(), next(), next())),
+                        _ => continue,
+                    };
+                    if n == 38 {
+                        self.foreground = color;
+                    } else {
+                        self.background = color;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Convert ANSI art to a PETSCII stream
+///
+/// The ANSI art is drawn on a screen of options.ansi_columns columns
+/// and then written out a row at a time, starting with a clear
+/// screen.  The stream uses the uppercase and graphics character set,
+/// so lowercase letters are written as uppercase.  A SAUCE record at
+/// the end of the file is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     ansi::{ansi_to_petscii, ArtOptions},
+///     petscii::PetsciiConfig,
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// // A red "Hi" and a full block in CP437
+/// let bytes = ansi_to_petscii(b"\x1b[31mHi\xdb", &petscii, &ArtOptions::default())
+///     .expect("Error converting art");
+///
+/// assert_eq!(bytes, vec![0x93, 0x8e, 0x1c, 0x48, 0x49, 0x12, 0xa0, 0x0d]);
+/// ```
+pub fn ansi_to_petscii(
+    bytes: &[u8],
+    petscii: &SystemConfig,
+    options: &ArtOptions,
+) -> std::result::Result<Vec<u8>, Error> {
+    let cp437 = AnsiConfig::load()?.cp437()?;
+    let approximations = AsciiConfig::load()?;
+
+    // Code page 437 doesn't have codes for every character, so the
+    // codes are looked up in the screen by character
+    let encode_char = |c: char| -> Option<(u8, bool)> {
+        let (c, reversed) = PETSCII_SUBSTITUTES
+            .iter()
+            .find(|(from, _, _)| *from == c)
+            .map_or((c, false), |(_, to, reversed)| (*to, *reversed));
+
+        let candidates = [
+            Some(c),
+            Some(c.to_ascii_uppercase()),
+            approximations.approximate_char(c),
+        ];
+        candidates.into_iter().flatten().find_map(|c| {
+            match petscii.encode(c.encode_utf8(&mut [0; 4])).as_slice() {
+                [code] => Some((*code, reversed)),
+                _ => None,
+            }
+        })
+    };
+
+    let mut screen = AnsiScreen::new(options.ansi_columns, options.palette);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // The end of the file, a SAUCE record may follow
+            0x1A => break,
+            0x1B if bytes.get(i + 1) == Some(&b'[') => {
+                let start = i + 2;
+                let end = bytes[start..]
+                    .iter()
+                    .position(|b| (0x40..=0x7E).contains(b))
+                    .map(|len| start + len);
+                match end {
+                    Some(end) => {
+                        let parameters = String::from_utf8_lossy(&bytes[start..end]);
+                        screen.control(&parameters, bytes[end]);
+                        i = end + 1;
+                    }
+                    None => i = bytes.len(),
+                }
+                continue;
+            }
+            b'\r' => screen.column = 0,
+            b'\n' => {
+                screen.move_down(1);
+                screen.column = 0;
+            }
+            0x00..=0x1F => (),
+            b => {
+                let c = cp437.decode(&[b]).chars().next().unwrap_or(' ');
+                screen.put(c);
+            }
+        }
+        i += 1;
+    }
+
+    let mut output = vec![0x93, 0x8E];
+    let mut color: Option<Color> = None;
+    let mut reversed = false;
+
+    for row in &screen.rows {
+        let row = &row[..row.len().min(options.petscii_columns)];
+        let len = row
+            .iter()
+            .rposition(|cell| cell.c != ' ' || cell.background != options.background)
+            .map_or(0, |position| position + 1);
+
+        for cell in &row[..len] {
+            let foreground = cell.foreground;
+            let background = cell.background;
+            let (code, glyph_reversed) = encode_char(cell.c).unwrap_or((0xA0, false));
+
+            let (cell_color, cell_reversed) = if background == options.background {
+                (foreground, glyph_reversed)
+            } else if code == 0xA0 && !glyph_reversed {
+                // A blank cell is filled with its background color
+                (background, true)
+            } else if foreground == options.background {
+                // The character is cut out of its background, like
+                // reverse video
+                (background, !glyph_reversed)
+            } else {
+                (foreground, glyph_reversed)
+            };
+
+            if color != Some(cell_color) {
+                output.push(cell_color.control_code());
+                color = Some(cell_color);
+            }
+            if reversed != cell_reversed {
+                output.push(if cell_reversed { 0x12 } else { 0x92 });
+                reversed = cell_reversed;
+            }
+            output.push(code);
+        }
+
+        if len == options.petscii_columns {
+            // The C64 moves to the next line after the last column,
+            // so a carriage return would leave a blank line
+            if reversed {
+                output.push(0x92);
+                reversed = false;
+            }
+        } else {
+            // A carriage return also turns off reverse video
+            output.push(0x0D);
+            reversed = false;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Write an SGR sequence selecting a color and reverse video
+pub(crate) fn push_sgr(output: &mut Vec<u8>, color: Color, background: Color, reversed: bool) {
+    let foreground = ansi_color(color);
+    let background = ansi_color(background) & 0x07;
+
+    let mut sgr = String::from("\x1b[0;");
+    if foreground & 0x08 != 0 {
+        sgr.push_str("1;");
+    }
+    sgr.push_str(&format!("{};{}", 30 + (foreground & 0x07), 40 + background));
+    if reversed {
+        sgr.push_str(";7");
+    }
+    sgr.push('m');
+    output.extend(sgr.bytes());
+}
+
+/// Convert a PETSCII stream to ANSI art
+///
+/// The output is code page 437 text with ANSI escape sequences.
+/// Lines wrap at options.petscii_columns, like they do on the C64.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     ansi::{petscii_to_ansi, ArtOptions},
+///     petscii::PetsciiConfig,
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// // Clear the screen, then a white "HI" and a reversed space
+/// let bytes = petscii_to_ansi(&[0x93, 0x05, 0x48, 0x49, 0x12, 0x20], &petscii, &ArtOptions::default())
+///     .expect("Error converting art");
+///
+/// assert_eq!(bytes, b"\x1b[2J\x1b[H\x1b[0;1;37;40mHI\x1b[0;1;37;40;7m ");
+/// ```
+pub fn petscii_to_ansi(
+    bytes: &[u8],
+    petscii: &SystemConfig,
+    options: &ArtOptions,
+) -> std::result::Result<Vec<u8>, Error> {
+    let cp437 = AnsiConfig::load()?.cp437()?;
+    let approximations = AsciiConfig::load()?;
+
+    let encode_char = |c: char| -> u8 {
+        let c = CP437_SUBSTITUTES
+            .iter()
+            .find(|(from, _)| *from == c)
+            .map_or(c, |(_, to)| *to);
+
+        [Some(c), approximations.approximate_char(c)]
+            .into_iter()
+            .flatten()
+            .find_map(
+                |c| match cp437.encode(c.encode_utf8(&mut [0; 4])).as_slice() {
+                    [code] if *code >= 0x20 => Some(*code),
+                    _ => None,
+                },
+            )
+            .unwrap_or(b' ')
+    };
+
+    let mut output = Vec::new();
+    let mut color = options.foreground;
+    let mut reversed = false;
+    let mut shifted = false;
+    let mut column: usize = 0;
+    // The color and reverse video of the last SGR sequence written
+    let mut written: Option<(Color, bool)> = None;
+
+    for b in bytes {
+        if let Some(c) = Color::from_control_code(*b) {
+            color = c;
+            continue;
+        }
+
+        match b {
+            0x0D | 0x8D => {
+                reversed = false;
+                column = 0;
+                output.extend_from_slice(b"\r\n");
+            }
+            0x0E => shifted = true,
+            0x8E => shifted = false,
+            0x11 => output.extend_from_slice(b"\x1b[B"),
+            0x91 => output.extend_from_slice(b"\x1b[A"),
+            0x12 => reversed = true,
+            0x92 => reversed = false,
+            0x13 => {
+                column = 0;
+                output.extend_from_slice(b"\x1b[H");
+            }
+            0x93 => {
+                column = 0;
+                output.extend_from_slice(b"\x1b[2J\x1b[H");
+            }
+            0x1D => {
+                column += 1;
+                output.extend_from_slice(b"\x1b[C");
+            }
+            0x9D => {
+                column = column.saturating_sub(1);
+                output.extend_from_slice(b"\x1b[D");
+            }
+            0x00..=0x1F | 0x80..=0x9F => (),
+            b => {
+                let decoded = if shifted {
+                    petscii.decode(&[0x0E, *b])
+                } else {
+                    petscii.decode(&[*b])
+                };
+                let code = decoded.chars().next().map_or(b' ', encode_char);
+
+                if written != Some((color, reversed)) {
+                    push_sgr(&mut output, color, options.background, reversed);
+                    written = Some((color, reversed));
+                }
+                output.push(code);
+
+                column += 1;
+                if column >= options.petscii_columns {
+                    column = 0;
+                    output.extend_from_slice(b"\r\n");
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ansi::{ansi_color, ansi_to_petscii, c64_color, petscii_to_ansi, ArtOptions},
+        petscii::{Color, PetsciiConfig},
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn ansi_colors_round_trip() {
+        for ansi in [0, 1, 2, 4, 5, 7, 8, 9, 10, 11, 12, 15] {
+            assert_eq!(ansi_color(c64_color(ansi)), ansi);
+        }
+        for color in [Color::Black, Color::White, Color::Red, Color::LightBlue] {
+            assert_eq!(c64_color(ansi_color(color)), color);
+        }
+    }
+
+    #[test]
+    fn ansi_cursor_movement_and_background_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Move right two columns, then a blue background space, a
+        // black on cyan A and a black on cyan double line
+        let art = b"\x1b[2C\x1b[44m \x1b[30;46mA\r\n\xcd\x1a SAUCE";
+        let bytes = ansi_to_petscii(art, &petscii, &ArtOptions::default()).unwrap();
+
+        assert_eq!(
+            bytes,
+            vec![
+                0x93, 0x8e, 0x9b, 0xa0, 0xa0, 0x1f, 0x12, 0xa0, 0x9f, 0x41, 0x0d, 0x12, 0x60, 0x0d
+            ]
+        );
+    }
+
+    #[test]
+    fn ansi_full_width_rows_skip_return() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let options = ArtOptions {
+            petscii_columns: 2,
+            ..ArtOptions::default()
+        };
+        let bytes = ansi_to_petscii(b"ABC\r\nD", &petscii, &options).unwrap();
+
+        assert_eq!(bytes, vec![0x93, 0x8e, 0x9b, 0x41, 0x42, 0x44, 0x0d]);
+    }
+
+    #[test]
+    fn ansi_petscii_wraps_at_screen_width() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let options = ArtOptions {
+            petscii_columns: 2,
+            ..ArtOptions::default()
+        };
+        // Shifted A, a horizontal line and a return
+        let bytes =
+            petscii_to_ansi(&[0x0e, 0x41, 0x8e, 0x60, 0x41, 0x0d], &petscii, &options).unwrap();
+
+        assert_eq!(bytes, b"\x1b[0;1;34;40ma\xc4\r\nA\r\n");
+    }
+
+    #[test]
+    fn ansi_extended_colors_use_palette() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // 24-bit orange, 256 color bright yellow and 256 color dark red
+        let art = b"\x1b[38;2;142;80;41mA\x1b[38;5;11mB\x1b[38;5;88mC";
+        let bytes = ansi_to_petscii(art, &petscii, &ArtOptions::default()).unwrap();
+
+        assert_eq!(
+            bytes,
+            vec![0x93, 0x8e, 0x81, 0x41, 0x9e, 0x42, 0x1c, 0x43, 0x0d]
+        );
+    }
+
+    #[test]
+    fn ansi_hostile_cursor_movement_stays_on_screen() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let options = ArtOptions::default();
+
+        // Moves as far as a usize goes, and past it
+        for art in [
+            &b"\x1b[18446744073709551615BA\x1b[18446744073709551615CB"[..],
+            b"\x1b[99999999999999999999999BA",
+            b"\x1b[999999999;999999999HA\nB",
+        ] {
+            let bytes = ansi_to_petscii(art, &petscii, &options).unwrap();
+            assert!(bytes.len() < 20_000, "{} bytes", bytes.len());
+        }
+
+        let huge = ArtOptions {
+            ansi_columns: usize::MAX,
+            ..ArtOptions::default()
+        };
+        let bytes = ansi_to_petscii(b"\x1b[999999999CA", &petscii, &huge).unwrap();
+        assert_eq!(bytes, vec![0x93, 0x8e, 0x0d]);
+    }
+}
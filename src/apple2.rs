@@ -0,0 +1,320 @@
+//!
+//! Apple II string library
+//!
+//! The Apple II text screen stores one byte per character.  The
+//! screen codes are split into four ranges:
+//!
+//! 0x00-0x3F: inverse uppercase letters and symbols
+//! 0x40-0x7F: flashing uppercase letters and symbols
+//! 0x80-0xFF: normal characters, lowercase from 0xE0 on the Apple IIe
+//!
+//! The enhanced Apple IIe and the Apple IIc have an alternate
+//! character set that can be switched on with the ALTCHARSET soft
+//! switch.  It replaces the flashing characters with inverse
+//! lowercase characters and the MouseText graphics characters from
+//! 0x40 to 0x5F.  MouseText was used to draw windows, menus and
+//! icons in programs like AppleWorks and the Apple IIc system
+//! utilities.  Most of the MouseText characters were added to Unicode
+//! in Symbols for Legacy Computing.
+//!
+//! Text files stored by DOS 3.3 and ProDOS are ASCII with the high
+//! bit set, with a carriage return (0x8D) at the end of each line.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{config_data, error::Error, load_cached, lookup_char, CharacterSet, SystemModule};
+
+/// The number of columns on the 40-column text screen
+pub const SCREEN_COLUMNS: usize = 40;
+
+/// The number of rows on the text screen
+pub const SCREEN_ROWS: usize = 24;
+
+/// The size of a text page in memory
+pub const TEXT_PAGE_SIZE: usize = 1024;
+
+/// The character set the screen is displayed with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharacterSetMode {
+    /// The primary character set with flashing characters
+    Primary,
+    /// The alternate character set with MouseText characters
+    MouseText,
+}
+
+/// A decoded character from the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Apple2Cell {
+    /// The Unicode character for the cell
+    pub character: char,
+    /// True if the character is displayed in inverse video
+    pub inverse: bool,
+    /// True if the character is flashing
+    pub flashing: bool,
+}
+
+/// Configuration data including character maps for the Apple II
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Apple2Config {
+    /// Version of the Apple II config
+    pub version: String,
+
+    /// Primary character set screen codes to Unicode codes
+    pub apple2_screen_codes_to_unicode_codes: Map<String, Value>,
+
+    /// Alternate character set screen codes that are different from
+    /// the primary character set, to Unicode codes
+    pub mousetext_screen_codes_to_unicode_codes: Map<String, Value>,
+}
+
+/// Configuration data for the Apple II module
+pub static CONFIG: RwLock<Option<Apple2Config>> = RwLock::new(None);
+
+impl SystemModule for Apple2Config {
+    const NAMESPACE: &'static str = "apple2";
+}
+
+impl Apple2Config {
+    /// Load the embedded Apple II configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::apple2::Apple2Config;
+    ///
+    /// let config = Apple2Config::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.apple2_screen_codes_to_unicode_codes.len(), 256);
+    /// ```
+    pub fn load() -> std::result::Result<Apple2Config, Error> {
+        load_cached(&CONFIG, config_data::APPLE2_CHARACTER_MAP)
+    }
+
+    /// Decode a single screen code
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::apple2::{Apple2Config, CharacterSetMode};
+    ///
+    /// let config = Apple2Config::load().expect("Error loading config");
+    ///
+    /// let cell = config.decode_cell(0x44, CharacterSetMode::Primary);
+    /// assert_eq!(cell.character, 'D');
+    /// assert!(cell.flashing);
+    ///
+    /// let cell = config.decode_cell(0x44, CharacterSetMode::MouseText);
+    /// assert_eq!(cell.character, '✓');
+    /// assert!(!cell.flashing);
+    /// ```
+    pub fn decode_cell(&self, code: u8, mode: CharacterSetMode) -> Apple2Cell {
+        let alternate = match mode {
+            CharacterSetMode::Primary => None,
+            CharacterSetMode::MouseText => {
+                lookup_char(&self.mousetext_screen_codes_to_unicode_codes, code)
+            }
+        };
+
+        let character = alternate
+            .or_else(|| lookup_char(&self.apple2_screen_codes_to_unicode_codes, code))
+            .unwrap_or(' ');
+
+        let (inverse, flashing) = match (code, mode) {
+            (0x00..=0x3F, _) => (true, false),
+            (0x40..=0x7F, CharacterSetMode::Primary) => (false, true),
+            (0x60..=0x7F, CharacterSetMode::MouseText) => (true, false),
+            _ => (false, false),
+        };
+
+        Apple2Cell {
+            character,
+            inverse,
+            flashing,
+        }
+    }
+
+    /// Decode a 40-column text page into lines of text
+    ///
+    /// Text page memory isn't stored in row order.  Each group of
+    /// three rows (0, 8 and 16, then 1, 9 and 17 and so on) is stored
+    /// in 128 bytes, with the last eight bytes unused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::apple2::{Apple2Config, CharacterSetMode, TEXT_PAGE_SIZE};
+    ///
+    /// let config = Apple2Config::load().expect("Error loading config");
+    ///
+    /// let mut page = [0xA0u8; TEXT_PAGE_SIZE];
+    /// // Row 8 starts 40 bytes into the page
+    /// page[40..42].copy_from_slice(&[0xC8, 0xC9]);
+    ///
+    /// let screen = config.decode_text_page(&page, CharacterSetMode::Primary);
+    ///
+    /// assert_eq!(screen.lines().nth(8).unwrap().trim_end(), "HI");
+    /// ```
+    pub fn decode_text_page(&self, page: &[u8], mode: CharacterSetMode) -> String {
+        (0..SCREEN_ROWS)
+            .map(|row| {
+                let start = 0x80 * (row % 8) + SCREEN_COLUMNS * (row / 8);
+                page.get(start..start + SCREEN_COLUMNS)
+                    .unwrap_or(&[])
+                    .iter()
+                    .map(|code| self.decode_cell(*code, mode).character)
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl CharacterSet for Apple2Config {
+    /// Decode high-bit ASCII text, such as a DOS 3.3 text file
+    ///
+    /// Carriage returns are decoded as newlines and other control
+    /// characters are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{apple2::Apple2Config, CharacterSet};
+    ///
+    /// let config = Apple2Config::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.decode(&[0xC8, 0xE9, 0x8D]), "Hi\n");
+    /// ```
+    fn decode(&self, bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .filter_map(|c| match c & 0x7F {
+                0x0D => Some('\n'),
+                0x20..=0x7E => Some(char::from(c & 0x7F)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Encode a Unicode string as high-bit ASCII text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{apple2::Apple2Config, CharacterSet};
+    ///
+    /// let config = Apple2Config::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.encode("Hi\n"), vec![0xC8, 0xE9, 0x8D]);
+    /// ```
+    fn encode(&self, s: &str) -> Vec<u8> {
+        s.chars()
+            .filter_map(|c| match c {
+                '\n' => Some(0x8D),
+                ' '..='~' => Some(c as u8 | 0x80),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        apple2::{Apple2Config, CharacterSetMode, TEXT_PAGE_SIZE},
+        CharacterSet,
+    };
+
+    #[test]
+    fn apple2_primary_character_set_works() {
+        let config = Apple2Config::load().expect("Error loading config");
+
+        let inverse = config.decode_cell(0x01, CharacterSetMode::Primary);
+        assert_eq!(inverse.character, 'A');
+        assert!(inverse.inverse);
+
+        let normal = config.decode_cell(0xC1, CharacterSetMode::Primary);
+        assert_eq!(normal.character, 'A');
+        assert!(!normal.inverse && !normal.flashing);
+
+        assert_eq!(
+            config
+                .decode_cell(0xE1, CharacterSetMode::Primary)
+                .character,
+            'a'
+        );
+        assert_eq!(
+            config
+                .decode_cell(0xA1, CharacterSetMode::Primary)
+                .character,
+            '!'
+        );
+    }
+
+    #[test]
+    fn apple2_mousetext_characters_work() {
+        let config = Apple2Config::load().expect("Error loading config");
+
+        let expected = [
+            (0x42, '\u{1FBB0}'),
+            (0x43, '⌛'),
+            (0x46, '\u{1FBB2}'),
+            (0x47, '\u{1FBB3}'),
+            (0x48, '←'),
+            (0x4E, '█'),
+            (0x58, '\u{1FBB9}'),
+            (0x5B, '◆'),
+        ];
+
+        for (code, c) in expected {
+            let cell = config.decode_cell(code, CharacterSetMode::MouseText);
+            assert_eq!(cell.character, c);
+            assert!(!cell.inverse && !cell.flashing);
+        }
+    }
+
+    #[test]
+    fn apple2_alternate_inverse_lowercase_works() {
+        let config = Apple2Config::load().expect("Error loading config");
+
+        let cell = config.decode_cell(0x61, CharacterSetMode::MouseText);
+        assert_eq!(cell.character, 'a');
+        assert!(cell.inverse);
+
+        let cell = config.decode_cell(0x61, CharacterSetMode::Primary);
+        assert_eq!(cell.character, '!');
+        assert!(cell.flashing);
+    }
+
+    #[test]
+    fn apple2_text_page_rows_work() {
+        let config = Apple2Config::load().expect("Error loading config");
+
+        let mut page = [0xA0u8; TEXT_PAGE_SIZE];
+        page[0] = 0xB0;
+        page[0x80] = 0xB1;
+        page[0x50] = 0xB2;
+        page[0x3D0] = 0xB3;
+
+        let screen = config.decode_text_page(&page, CharacterSetMode::Primary);
+        let rows: Vec<&str> = screen.lines().collect();
+
+        assert_eq!(rows.len(), 24);
+        assert!(rows[0].starts_with('0'));
+        assert!(rows[1].starts_with('1'));
+        assert!(rows[16].starts_with('2'));
+        assert!(rows[23].starts_with('3'));
+    }
+
+    #[test]
+    fn apple2_high_ascii_round_trip_works() {
+        let config = Apple2Config::load().expect("Error loading config");
+        let text = "10 PRINT \"Hello\"\n";
+
+        assert_eq!(config.decode(&config.encode(text)), text);
+    }
+}
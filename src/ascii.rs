@@ -0,0 +1,153 @@
+//!
+//! ASCII approximation library
+//!
+//! Most of the graphics characters in 8-bit character sets are
+//! mapped to the Box Drawing, Block Elements and Symbols for Legacy
+//! Computing blocks.  A lot of terminals, fonts and log viewers can't
+//! display the Legacy Computing characters and show empty boxes
+//! instead.
+//!
+//! This module replaces graphics characters with the ASCII character
+//! that looks the most like them, using a table in the configuration
+//! data.  It works on decoded Unicode text, so it can be used with
+//! any of the character sets.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{config_data, error::Error, load_cached};
+
+/// Configuration data with the ASCII approximation table
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AsciiConfig {
+    /// Version of the ASCII approximation config
+    pub version: String,
+
+    /// Unicode codes to the ASCII codes of their approximations
+    pub unicode_codes_to_ascii_codes: Map<String, Value>,
+}
+
+/// Configuration data for the ASCII approximation module
+pub static CONFIG: RwLock<Option<AsciiConfig>> = RwLock::new(None);
+
+impl AsciiConfig {
+    /// Load the embedded ASCII approximation configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::ascii::AsciiConfig;
+    ///
+    /// let config = AsciiConfig::load().expect("Error loading config");
+    ///
+    /// assert!(!config.unicode_codes_to_ascii_codes.is_empty());
+    /// ```
+    pub fn load() -> std::result::Result<AsciiConfig, Error> {
+        load_cached(&CONFIG, config_data::ASCII_APPROXIMATION_MAP)
+    }
+
+    /// Get the ASCII approximation for a character
+    ///
+    /// Returns None if the character doesn't have an approximation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::ascii::AsciiConfig;
+    ///
+    /// let config = AsciiConfig::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.approximate_char('┼'), Some('+'));
+    /// assert_eq!(config.approximate_char('é'), None);
+    /// ```
+    pub fn approximate_char(&self, c: char) -> Option<char> {
+        match self
+            .unicode_codes_to_ascii_codes
+            .get(&u32::from(c).to_string())
+        {
+            Some(Value::Number(v)) => v
+                .as_u64()
+                .and_then(|v| u8::try_from(v).ok())
+                .map(char::from),
+            _ => None,
+        }
+    }
+
+    /// Replace the graphics characters in a string with their ASCII
+    /// approximations
+    ///
+    /// Characters without an approximation aren't changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::ascii::AsciiConfig;
+    ///
+    /// let config = AsciiConfig::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.approximate("╭─╮\n│█│\n╰─╯"), "+-+\n|#|\n+-+");
+    /// ```
+    pub fn approximate(&self, s: &str) -> String {
+        s.chars()
+            .map(|c| self.approximate_char(c).unwrap_or(c))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        apple2::{Apple2Config, CharacterSetMode},
+        ascii::AsciiConfig,
+        petscii::PetsciiConfig,
+        CharacterSet, Configuration, DecodeOptions, DecodeTarget, SystemConfig,
+    };
+
+    #[test]
+    fn ascii_approximations_are_ascii() {
+        let config = AsciiConfig::load().expect("Error loading config");
+
+        for (key, _) in config.unicode_codes_to_ascii_codes.iter() {
+            let c = char::from_u32(key.parse().unwrap()).unwrap();
+            let approximation = config.approximate_char(c).unwrap();
+            assert!(approximation.is_ascii_graphic(), "{c}");
+        }
+    }
+
+    #[test]
+    fn ascii_decode_target_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // A box with a heart suit inside
+        let data = [0xb0, 0xc0, 0xae, 0x0d, 0xdd, 0xd3, 0xdd];
+        let options = DecodeOptions::default().with_target(DecodeTarget::AsciiApproximation);
+
+        assert_eq!(petscii.decode_with_options(&data, &options), "+-+\r|*|");
+        assert_eq!(
+            petscii.decode_with_options(&data, &DecodeOptions::default()),
+            petscii.decode(&data)
+        );
+    }
+
+    #[test]
+    fn ascii_approximation_mousetext_works() {
+        let config = AsciiConfig::load().expect("Error loading config");
+        let apple2 = Apple2Config::load().expect("Error loading config");
+
+        let s: String = [0x48, 0x4a, 0x4b, 0x44]
+            .iter()
+            .map(|code| {
+                apple2
+                    .decode_cell(*code, CharacterSetMode::MouseText)
+                    .character
+            })
+            .collect();
+
+        assert_eq!(config.approximate(&s), "<v^v");
+    }
+}
@@ -0,0 +1,539 @@
+//!
+//! BBC Micro, BBC Master and Acorn Electron string library
+//!
+//! Acorn's 8-bit machines use ASCII with a couple of changes.  In
+//! screen modes 0 to 6 character 96 (backtick) shows a pound sign.
+//! MODE 7 on the BBC Micro and Master is a teletext mode with its own
+//! character set, where # shows a pound sign and the brackets and
+//! braces show arrows and fractions.
+//!
+//! Text output goes through the VDU drivers.  Control codes 0 to 31
+//! and 127 are VDU commands, and some of them are followed by
+//! parameter bytes.  For example VDU 17 sets the text colour from the
+//! next byte and VDU 31 moves the text cursor to the column and row in
+//! the next two bytes.
+//!
+//! Files captured with *SPOOL contain everything sent to the VDU
+//! drivers, so they include these commands and their parameter bytes.
+//! Decoding them as plain text shows the parameter bytes as garbage
+//! characters.  The VduDecoder in this module runs the commands
+//! against a text screen so the output ends up where it was shown.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{config_data, error::Error, load_cached, lookup_char, CharacterSet, SystemModule};
+
+/// The character set used to display text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScreenCharacterSet {
+    /// The character set for screen modes 0 to 6
+    Standard,
+    /// The MODE 7 teletext character set
+    Teletext,
+}
+
+/// A character cell on the text screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VduCell {
+    /// The character in the cell
+    pub character: char,
+    /// The logical foreground colour set with VDU 17
+    pub foreground: u8,
+    /// The logical background colour set with VDU 17
+    pub background: u8,
+}
+
+impl Default for VduCell {
+    fn default() -> VduCell {
+        VduCell {
+            character: ' ',
+            foreground: 7,
+            background: 0,
+        }
+    }
+}
+
+/// Configuration data including character maps for Acorn machines
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BbcConfig {
+    /// Version of the BBC config
+    pub version: String,
+
+    /// Character codes to Unicode codes for screen modes 0 to 6
+    pub bbc_character_codes_to_unicode_codes: Map<String, Value>,
+
+    /// Unicode codes to character codes for screen modes 0 to 6
+    pub unicode_codes_to_bbc_character_codes: Map<String, Value>,
+
+    /// Character codes to Unicode codes for MODE 7
+    pub teletext_character_codes_to_unicode_codes: Map<String, Value>,
+
+    /// The number of parameter bytes following each VDU code
+    pub vdu_parameter_counts: Map<String, Value>,
+}
+
+/// Configuration data for the BBC module
+pub static CONFIG: RwLock<Option<BbcConfig>> = RwLock::new(None);
+
+impl SystemModule for BbcConfig {
+    const NAMESPACE: &'static str = "bbc";
+}
+
+impl BbcConfig {
+    /// Load the embedded BBC configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::bbc::BbcConfig;
+    ///
+    /// let config = BbcConfig::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.parameter_count(31), 2);
+    /// ```
+    pub fn load() -> std::result::Result<BbcConfig, Error> {
+        load_cached(&CONFIG, config_data::BBC_CHARACTER_MAP)
+    }
+
+    /// The number of parameter bytes that follow a VDU code
+    pub fn parameter_count(&self, code: u8) -> usize {
+        match self.vdu_parameter_counts.get(&code.to_string()) {
+            Some(Value::Number(v)) => v.as_u64().unwrap_or(0) as usize,
+            _ => 0,
+        }
+    }
+
+    /// Look up the Unicode character for a printable character code
+    pub fn to_unicode(&self, code: u8, character_set: ScreenCharacterSet) -> Option<char> {
+        let map = match character_set {
+            ScreenCharacterSet::Standard => &self.bbc_character_codes_to_unicode_codes,
+            ScreenCharacterSet::Teletext => &self.teletext_character_codes_to_unicode_codes,
+        };
+        lookup_char(map, code)
+    }
+}
+
+impl CharacterSet for BbcConfig {
+    /// Decode a VDU byte stream as plain text
+    ///
+    /// VDU commands and their parameters are skipped, except for
+    /// carriage return which is decoded as a newline.  Use VduDecoder
+    /// to get the text as it was positioned on the screen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{bbc::BbcConfig, CharacterSet};
+    ///
+    /// let config = BbcConfig::load().expect("Error loading config");
+    ///
+    /// // VDU 17,1 sets the text colour to red
+    /// assert_eq!(config.decode(&[17, 1, b'`', b'5', 10, 13]), "£5\n");
+    /// ```
+    fn decode(&self, bytes: &[u8]) -> String {
+        let mut skip = 0;
+
+        bytes
+            .iter()
+            .filter_map(|c| {
+                if skip > 0 {
+                    skip -= 1;
+                    return None;
+                }
+                match c {
+                    13 => Some('\n'),
+                    0..=31 => {
+                        skip = self.parameter_count(*c);
+                        None
+                    }
+                    _ => self.to_unicode(*c, ScreenCharacterSet::Standard),
+                }
+            })
+            .collect()
+    }
+
+    /// Encode a Unicode string for screen modes 0 to 6
+    ///
+    /// Newlines are encoded as a line feed and carriage return, the
+    /// order the BBC Micro outputs them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{bbc::BbcConfig, CharacterSet};
+    ///
+    /// let config = BbcConfig::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.encode("£5\n"), vec![b'`', b'5', 10, 13]);
+    /// ```
+    fn encode(&self, s: &str) -> Vec<u8> {
+        s.chars()
+            .flat_map(|c| {
+                if c == '\n' {
+                    return vec![10, 13];
+                }
+                match self
+                    .unicode_codes_to_bbc_character_codes
+                    .get(&u32::from(c).to_string())
+                {
+                    Some(Value::Number(v)) => v
+                        .as_u64()
+                        .and_then(|v| u8::try_from(v).ok())
+                        .into_iter()
+                        .collect(),
+                    _ => vec![],
+                }
+            })
+            .collect()
+    }
+}
+
+/// Return the text screen size for a screen mode
+///
+/// The size is returned as (columns, rows).
+pub fn mode_size(mode: u8) -> (usize, usize) {
+    match mode & 0x07 {
+        0 => (80, 32),
+        1 | 4 => (40, 32),
+        2 | 5 => (20, 32),
+        3 => (80, 25),
+        _ => (40, 25),
+    }
+}
+
+/// Runs a VDU byte stream against a text screen
+///
+/// The decoder keeps track of partial commands, so a stream can be
+/// fed in pieces.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::bbc::{BbcConfig, VduDecoder};
+///
+/// let config = BbcConfig::load().expect("Error loading config");
+/// let mut decoder = VduDecoder::new(&config, 7);
+///
+/// // Print at column 2, row 1 with VDU 31,2,1
+/// decoder.feed(&[31, 2, 1, b'H', b'I']);
+///
+/// assert_eq!(decoder.text(), "\n  HI");
+/// ```
+pub struct VduDecoder<'a> {
+    config: &'a BbcConfig,
+    mode: u8,
+    columns: usize,
+    rows: usize,
+    cells: Vec<VduCell>,
+    x: usize,
+    y: usize,
+    foreground: u8,
+    background: u8,
+    command: Option<u8>,
+    parameters: Vec<u8>,
+}
+
+impl<'a> VduDecoder<'a> {
+    /// Create a decoder with a cleared screen in the given mode
+    pub fn new(config: &'a BbcConfig, mode: u8) -> VduDecoder<'a> {
+        let (columns, rows) = mode_size(mode);
+        VduDecoder {
+            config,
+            mode: mode & 0x07,
+            columns,
+            rows,
+            cells: vec![VduCell::default(); columns * rows],
+            x: 0,
+            y: 0,
+            foreground: 7,
+            background: 0,
+            command: None,
+            parameters: Vec::new(),
+        }
+    }
+
+    /// The current screen mode
+    pub fn mode(&self) -> u8 {
+        self.mode
+    }
+
+    /// The text cursor position as (column, row)
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// The screen contents as rows of cells
+    pub fn rows(&self) -> std::slice::Chunks<'_, VduCell> {
+        self.cells.chunks(self.columns)
+    }
+
+    /// Feed bytes to the VDU drivers
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.feed_byte(*byte);
+        }
+    }
+
+    fn feed_byte(&mut self, byte: u8) {
+        if let Some(command) = self.command {
+            self.parameters.push(byte);
+            if self.parameters.len() >= self.config.parameter_count(command) {
+                self.command = None;
+                let parameters = std::mem::take(&mut self.parameters);
+                self.execute(command, &parameters);
+            }
+            return;
+        }
+
+        if byte < 32 || byte == 127 {
+            if self.config.parameter_count(byte) > 0 {
+                self.command = Some(byte);
+            } else {
+                self.execute(byte, &[]);
+            }
+            return;
+        }
+
+        let character_set = if self.mode == 7 {
+            ScreenCharacterSet::Teletext
+        } else {
+            ScreenCharacterSet::Standard
+        };
+        let character = self.config.to_unicode(byte, character_set).unwrap_or(' ');
+        self.put(character);
+    }
+
+    fn put(&mut self, character: char) {
+        let index = self.y * self.columns + self.x;
+        self.cells[index] = VduCell {
+            character,
+            foreground: self.foreground,
+            background: self.background,
+        };
+        self.x += 1;
+        if self.x >= self.columns {
+            self.x = 0;
+            self.line_feed();
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.y + 1 < self.rows {
+            self.y += 1;
+        } else {
+            // Scroll the screen up a line
+            self.cells.drain(..self.columns);
+            self.cells
+                .extend(std::iter::repeat_n(VduCell::default(), self.columns));
+        }
+    }
+
+    fn clear(&mut self) {
+        self.cells = vec![VduCell::default(); self.columns * self.rows];
+        self.x = 0;
+        self.y = 0;
+    }
+
+    fn execute(&mut self, command: u8, parameters: &[u8]) {
+        match command {
+            8 => {
+                if self.x > 0 {
+                    self.x -= 1;
+                } else if self.y > 0 {
+                    self.x = self.columns - 1;
+                    self.y -= 1;
+                }
+            }
+            9 => {
+                self.x += 1;
+                if self.x >= self.columns {
+                    self.x = 0;
+                    self.line_feed();
+                }
+            }
+            10 => self.line_feed(),
+            11 => self.y = self.y.saturating_sub(1),
+            12 => self.clear(),
+            13 => self.x = 0,
+            // The parameter counts come from the configuration, so
+            // skip commands that didn't get the parameters they use
+            17 => {
+                if let [colour, ..] = *parameters {
+                    if colour < 128 {
+                        self.foreground = colour;
+                    } else {
+                        self.background = colour - 128;
+                    }
+                }
+            }
+            22 => {
+                if let [mode, ..] = *parameters {
+                    self.mode = mode & 0x07;
+                    (self.columns, self.rows) = mode_size(self.mode);
+                    self.clear();
+                }
+            }
+            30 => {
+                self.x = 0;
+                self.y = 0;
+            }
+            31 => {
+                if let [x, y, ..] = *parameters {
+                    let (x, y) = (usize::from(x), usize::from(y));
+                    // The VDU drivers ignore positions outside the window
+                    if x < self.columns && y < self.rows {
+                        self.x = x;
+                        self.y = y;
+                    }
+                }
+            }
+            127 => {
+                self.execute(8, &[]);
+                let index = self.y * self.columns + self.x;
+                self.cells[index] = VduCell::default();
+            }
+            _ => {}
+        }
+    }
+
+    /// The screen contents as text
+    ///
+    /// Trailing spaces on each line and trailing blank lines are
+    /// removed.
+    pub fn text(&self) -> String {
+        let mut lines: Vec<String> = self
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.character)
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bbc::{BbcConfig, ScreenCharacterSet, VduDecoder},
+        CharacterSet,
+    };
+
+    #[test]
+    fn bbc_decode_skips_vdu_parameters() {
+        let config = BbcConfig::load().expect("Error loading config");
+
+        // VDU 31,65,66 would show "AB" if the parameters weren't
+        // skipped, VDU 19 has five parameters
+        let data = [31, 65, 66, b'X', 19, 1, 2, 0, 0, 0, b'Y'];
+
+        assert_eq!(config.decode(&data), "XY");
+    }
+
+    #[test]
+    fn bbc_teletext_characters_work() {
+        let config = BbcConfig::load().expect("Error loading config");
+
+        assert_eq!(
+            config.to_unicode(b'#', ScreenCharacterSet::Teletext),
+            Some('£')
+        );
+        assert_eq!(
+            config.to_unicode(b'#', ScreenCharacterSet::Standard),
+            Some('#')
+        );
+        assert_eq!(
+            config.to_unicode(b'{', ScreenCharacterSet::Teletext),
+            Some('¼')
+        );
+    }
+
+    #[test]
+    fn vdu_decoder_spool_lines_work() {
+        let config = BbcConfig::load().expect("Error loading config");
+        let mut decoder = VduDecoder::new(&config, 7);
+
+        decoder.feed(b">LIST\n\r   10 PRINT \"HELLO\"\n\r>");
+
+        assert_eq!(decoder.text(), ">LIST\n   10 PRINT \"HELLO\"\n>");
+    }
+
+    #[test]
+    fn vdu_decoder_split_parameters_work() {
+        let config = BbcConfig::load().expect("Error loading config");
+        let mut decoder = VduDecoder::new(&config, 7);
+
+        // Split VDU 31,5,2 and VDU 17,1 across feeds
+        decoder.feed(&[31, 5]);
+        decoder.feed(&[2, 17]);
+        decoder.feed(&[1, b'R']);
+
+        assert_eq!(decoder.cursor(), (6, 2));
+        let cell = decoder.rows().nth(2).unwrap()[5];
+        assert_eq!(cell.character, 'R');
+        assert_eq!(cell.foreground, 1);
+    }
+
+    #[test]
+    fn vdu_decoder_mode_change_and_scroll_work() {
+        let config = BbcConfig::load().expect("Error loading config");
+        let mut decoder = VduDecoder::new(&config, 7);
+
+        // MODE 2 is 20 columns by 32 rows
+        decoder.feed(&[22, 2]);
+        assert_eq!(decoder.mode(), 2);
+
+        decoder.feed(b"TOP");
+        for _ in 0..32 {
+            decoder.feed(&[10]);
+        }
+        decoder.feed(b"BOTTOM");
+
+        let text = decoder.text();
+        assert!(!text.contains("TOP"));
+        assert!(text.ends_with("BOTTOM"));
+        assert_eq!(decoder.rows().count(), 32);
+    }
+
+    #[test]
+    fn vdu_decoder_delete_works() {
+        let config = BbcConfig::load().expect("Error loading config");
+        let mut decoder = VduDecoder::new(&config, 3);
+
+        decoder.feed(b"ABX");
+        decoder.feed(&[127]);
+        decoder.feed(b"C");
+
+        assert_eq!(decoder.text(), "ABC");
+    }
+
+    #[test]
+    fn vdu_decoder_short_parameter_counts_work() {
+        let mut config = BbcConfig::load().expect("Error loading config");
+        for (code, count) in [(17, 0), (22, 0), (31, 1)] {
+            config
+                .vdu_parameter_counts
+                .insert(code.to_string(), serde_json::Value::from(count));
+        }
+        let mut decoder = VduDecoder::new(&config, 7);
+
+        // Commands without the parameters they use are skipped
+        decoder.feed(&[31, 5, b'A', 17, 22, b'B']);
+
+        assert_eq!(decoder.mode(), 7);
+        assert_eq!(decoder.text(), "AB");
+    }
+}
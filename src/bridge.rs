@@ -0,0 +1,346 @@
+//!
+//! Bridging PETSCII BBSs and modern terminals
+//!
+//! C64 BBSs reachable over telnet, and devices like the Ultimate 64,
+//! talk in PETSCII: characters mixed with control codes for colors,
+//! reverse video, cursor movement and the character set.  A modern
+//! terminal talks in UTF-8 with ANSI escape sequences.  A Bridge sits
+//! between the two and translates both directions.
+//!
+//! The directions share state.  Keys typed on the terminal become
+//! different PETSCII codes depending on the character set the BBS has
+//! selected, so the bridge watches the PETSCII stream to know which
+//! one is showing.  Both directions also keep partial input between
+//! calls, since a network read can end in the middle of a UTF-8
+//! character or an escape sequence.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    ansi::push_sgr,
+    petscii::{
+        decoder::{Dangling, PetsciiEvent, PetsciiEventDecoder},
+        petscii_to_screen_code,
+        screen::Charset,
+        screen_code_to_petscii, unicode_to_screen_code, Color, PetsciiCode,
+    },
+    SystemConfig,
+};
+
+/// ANSI key sequences and the PETSCII codes for the same keys
+const KEY_SEQUENCES: [(&[u8], u8); 18] = [
+    (b"\x1b[A", 0x91),
+    (b"\x1b[B", 0x11),
+    (b"\x1b[C", 0x1D),
+    (b"\x1b[D", 0x9D),
+    (b"\x1b[H", 0x13),
+    (b"\x1b[1~", 0x13),
+    (b"\x1b[2~", 0x94),
+    (b"\x1b[3~", 0x14),
+    (b"\x1bOP", 0x85),
+    (b"\x1bOQ", 0x89),
+    (b"\x1bOR", 0x86),
+    (b"\x1bOS", 0x8A),
+    (b"\x1b[15~", 0x87),
+    (b"\x1b[17~", 0x8B),
+    (b"\x1b[18~", 0x88),
+    (b"\x1b[19~", 0x8C),
+    (b"\x1b[11~", 0x85),
+    (b"\x1b[12~", 0x89),
+];
+
+/// Translates between a PETSCII stream and a UTF-8 terminal
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     bridge::Bridge, petscii::PetsciiConfig, Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let mut bridge = Bridge::new(&petscii);
+///
+/// // The BBS clears the screen and switches to lowercase
+/// let shown = bridge.petscii_to_terminal(b"\x93\x0e\x05\x48\x49");
+/// assert_eq!(shown, b"\x1b[2J\x1b[H\x1b[0;1;37;40mhi");
+///
+/// // So a typed uppercase letter is sent as the shifted code
+/// assert_eq!(bridge.terminal_to_petscii(b"Ok\r\n"), [0xCF, 0x4B, 0x0D]);
+/// ```
+#[derive(Clone)]
+pub struct Bridge<'a> {
+    petscii: &'a SystemConfig,
+    decoder: PetsciiEventDecoder<'a>,
+    background: Color,
+    /// The color and reverse video of the last SGR sequence sent to
+    /// the terminal
+    written: Option<(Color, bool)>,
+    /// Terminal input that ended in the middle of a character or
+    /// escape sequence
+    pending: Vec<u8>,
+    last_was_cr: bool,
+}
+
+impl<'a> Bridge<'a> {
+    /// Create a bridge, starting in the state the C64 starts in
+    pub fn new(petscii: &'a SystemConfig) -> Bridge<'a> {
+        Bridge {
+            petscii,
+            decoder: PetsciiEventDecoder::new(petscii),
+            background: Color::Black,
+            written: None,
+            pending: Vec::new(),
+            last_was_cr: false,
+        }
+    }
+
+    /// Set the background color used in SGR sequences
+    pub fn with_background(mut self, background: Color) -> Bridge<'a> {
+        self.background = background;
+        self
+    }
+
+    /// The character set the PETSCII side has selected
+    pub fn charset(&self) -> Charset {
+        if self.decoder.state().is_shifted() {
+            Charset::Lowercase
+        } else {
+            Charset::Uppercase
+        }
+    }
+
+    /// End the session, returning the PETSCII state that was left on
+    /// and the terminal input that ended in the middle of a character
+    /// or escape sequence
+    ///
+    /// The incomplete input is dropped, and the PETSCII side goes back
+    /// to the start state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{bridge::Bridge, petscii::PetsciiConfig, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut bridge = Bridge::new(&petscii);
+    /// bridge.petscii_to_terminal(b"\x12");
+    /// bridge.terminal_to_petscii(b"\x1b[");
+    ///
+    /// let dangling = bridge.finish();
+    /// assert!(dangling.reversed);
+    /// assert_eq!(dangling.incomplete, b"\x1b[");
+    /// ```
+    pub fn finish(&mut self) -> Dangling {
+        let mut dangling = self.decoder.finish();
+        dangling.incomplete = std::mem::take(&mut self.pending);
+        self.last_was_cr = false;
+        dangling
+    }
+
+    /// Decode PETSCII from the BBS into screen events
+    pub fn petscii_events(&mut self, bytes: &[u8]) -> Vec<PetsciiEvent> {
+        bytes.iter().filter_map(|b| self.decoder.feed(*b)).collect()
+    }
+
+    /// The character for a code without reverse video
+    fn unreversed_char(&self, b: u8) -> Option<char> {
+        let map = &self.petscii.character_set_map;
+        // In quote mode control codes show the character 0x40 above
+        let code = if PetsciiCode(b).is_control() {
+            b.wrapping_add(0x40)
+        } else {
+            b
+        };
+        petscii_to_screen_code(code, self.decoder.state().is_shifted(), map)?.to_unicode(map)
+    }
+
+    /// Translate PETSCII from the BBS into UTF-8 and ANSI escape
+    /// sequences for the terminal
+    ///
+    /// Reverse video is sent as SGR 7 on the plain character, so it
+    /// looks the same whether or not the terminal's font has the
+    /// reversed graphics characters.
+    pub fn petscii_to_terminal(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+
+        for b in bytes {
+            // The character set needed to look up the plain character
+            // is the one before the byte
+            let unreversed = self.unreversed_char(*b);
+
+            match self.decoder.feed(*b) {
+                Some(PetsciiEvent::PrintChar { c, color, reversed }) => {
+                    let (c, reversed) = match (reversed, unreversed) {
+                        (true, Some(plain)) => (plain, true),
+                        _ => (c, false),
+                    };
+                    if self.written != Some((color, reversed)) {
+                        push_sgr(&mut output, color, self.background, reversed);
+                        self.written = Some((color, reversed));
+                    }
+                    output.extend(c.encode_utf8(&mut [0; 4]).bytes());
+                }
+                Some(PetsciiEvent::ClearScreen) => output.extend_from_slice(b"\x1b[2J\x1b[H"),
+                Some(PetsciiEvent::Home) => output.extend_from_slice(b"\x1b[H"),
+                Some(PetsciiEvent::CursorMove { dx, dy }) => {
+                    let sequence: &[u8] = match (dx, dy) {
+                        (0, -1) => b"\x1b[A",
+                        (0, 1) => b"\x1b[B",
+                        (1, 0) => b"\x1b[C",
+                        _ => b"\x1b[D",
+                    };
+                    output.extend_from_slice(sequence);
+                }
+                Some(PetsciiEvent::NewLine) => output.extend_from_slice(b"\r\n"),
+                Some(PetsciiEvent::Delete) => output.extend_from_slice(b"\x08\x1b[P"),
+                Some(PetsciiEvent::Insert) => output.extend_from_slice(b"\x1b[@"),
+                Some(PetsciiEvent::Control(0x07)) => output.push(0x07),
+                // Colors and reverse video are sent with the next
+                // character
+                _ => (),
+            }
+        }
+
+        output
+    }
+
+    /// The PETSCII code for a typed character
+    fn key_code(&self, c: char) -> Option<u8> {
+        let lowercase = self.decoder.state().is_shifted();
+        match c {
+            // Unshifted letter keys send 0x41 to 0x5A, shown as
+            // lowercase in the lowercase character set
+            'a'..='z' => Some(c.to_ascii_uppercase() as u8),
+            'A'..='Z' if lowercase => Some(c as u8 | 0x80),
+            ' ' => Some(0x20),
+            _ => {
+                let map = &self.petscii.character_set_map;
+                let code = screen_code_to_petscii(unicode_to_screen_code(c, map)?, map)?;
+                Some(code.value.into())
+            }
+        }
+    }
+
+    /// Translate keys typed on the terminal into PETSCII for the BBS
+    ///
+    /// Arrow keys, Home, Insert, Delete and the function keys F1 to
+    /// F8 are translated to their C64 keys.  Enter is sent as a
+    /// carriage return and Backspace as DEL.  Input that ends in the
+    /// middle of a character or escape sequence is kept until the
+    /// next call.
+    pub fn terminal_to_petscii(&mut self, input: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(input);
+
+        let mut output = Vec::new();
+        let mut i = 0;
+        while i < self.pending.len() {
+            let b = self.pending[i];
+            let was_cr = std::mem::replace(&mut self.last_was_cr, b == b'\r');
+
+            let len = match b {
+                0x1B => {
+                    let rest = &self.pending[i..];
+                    let end = match rest.get(1) {
+                        Some(b'[' | b'O') => rest
+                            .iter()
+                            .skip(2)
+                            .position(|b| (0x40..=0x7E).contains(b))
+                            .map(|position| position + 3),
+                        Some(_) => Some(1),
+                        None => None,
+                    };
+                    let Some(end) = end else { break };
+
+                    if let Some((_, code)) = KEY_SEQUENCES
+                        .iter()
+                        .find(|(sequence, _)| *sequence == &rest[..end])
+                    {
+                        output.push(*code);
+                    }
+                    end
+                }
+                b'\r' => {
+                    output.push(0x0D);
+                    1
+                }
+                // A line feed after a carriage return is the same
+                // Enter key
+                b'\n' => {
+                    if !was_cr {
+                        output.push(0x0D);
+                    }
+                    1
+                }
+                0x08 | 0x7F => {
+                    output.push(0x14);
+                    1
+                }
+                0x00..=0x1F => 1,
+                _ => {
+                    let len = match b {
+                        0xF0..=0xF7 => 4,
+                        0xE0..=0xEF => 3,
+                        0xC0..=0xDF => 2,
+                        _ => 1,
+                    };
+                    let Some(bytes) = self.pending.get(i..i + len) else {
+                        break;
+                    };
+                    if let Some(code) = std::str::from_utf8(bytes)
+                        .ok()
+                        .and_then(|s| s.chars().next())
+                        .and_then(|c| self.key_code(c))
+                    {
+                        output.push(code);
+                    }
+                    len
+                }
+            };
+            i += len;
+        }
+
+        self.pending.drain(..i);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bridge::Bridge,
+        petscii::{decoder::PetsciiEvent, screen::Charset, PetsciiConfig},
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn bridge_keeps_partial_input() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut bridge = Bridge::new(&petscii);
+        // An up arrow and a pound sign split across reads
+        assert!(bridge.terminal_to_petscii(b"\x1b[").is_empty());
+        assert_eq!(bridge.terminal_to_petscii(b"A\xc2"), [0x91]);
+        assert_eq!(bridge.terminal_to_petscii(b"\xa3\x1b[15~"), [0x5C, 0x87]);
+        assert_eq!(bridge.terminal_to_petscii(b"a\x7f\n"), [0x41, 0x14, 0x0D]);
+    }
+
+    #[test]
+    fn bridge_reverse_uses_sgr() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut bridge = Bridge::new(&petscii);
+        let shown = bridge.petscii_to_terminal(b"\x1c\x12A\x92\x0d");
+        assert_eq!(shown, b"\x1b[0;31;40;7mA\r\n");
+
+        let events = bridge.petscii_events(b"\x0e");
+        assert_eq!(events, [PetsciiEvent::CharsetSwitch(Charset::Lowercase)]);
+        assert_eq!(bridge.charset(), Charset::Lowercase);
+    }
+}
@@ -0,0 +1,137 @@
+//!
+//! Converting many buffers in parallel
+//!
+//! Indexing a collection of disk images means decoding thousands of
+//! directory entries and file names, and the conversion can take
+//! most of the time.  The functions here convert a slice of buffers
+//! on the rayon thread pool.  Every thread borrows the same character
+//! set, which isn't changed while they run, so the tables are loaded
+//! once and aren't copied or locked.
+//!
+//! In a program that replaces its configuration with a ConfigManager,
+//! take the SystemConfig from one snapshot and convert the whole batch
+//! with it, so every buffer in the batch is converted with the same
+//! tables.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use rayon::prelude::*;
+
+use crate::{
+    petscii::decoder::{DecodeEvent, PetsciiDecoderState},
+    CharacterSet, SystemConfig,
+};
+
+/// Decode each buffer into a Unicode string, in parallel
+///
+/// The strings are in the same order as the buffers.  Any character
+/// set that can be shared between threads can be used.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{bulk::decode_all, petscii::PetsciiConfig, Configuration, SystemConfig};
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let buffers = [vec![0x48, 0x49], vec![0x0e, 0x48, 0x49]];
+///
+/// assert_eq!(decode_all(&petscii, &buffers), ["HI", "hi"]);
+/// ```
+pub fn decode_all<C, B>(character_set: &C, buffers: &[B]) -> Vec<String>
+where
+    C: CharacterSet + Sync,
+    B: AsRef<[u8]> + Sync,
+{
+    buffers
+        .par_iter()
+        .map(|bytes| character_set.decode(bytes.as_ref()))
+        .collect()
+}
+
+/// Encode each string into 8-bit character codes, in parallel
+///
+/// The buffers are in the same order as the strings.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{bulk::encode_all, petscii::PetsciiConfig, Configuration, SystemConfig};
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// assert_eq!(
+///     encode_all(&petscii, &["HI", "hi"]),
+///     [vec![0x48, 0x49], vec![0x0e, 0x48, 0x49, 0x8e]]
+/// );
+/// ```
+pub fn encode_all<C, S>(character_set: &C, strings: &[S]) -> Vec<Vec<u8>>
+where
+    C: CharacterSet + Sync,
+    S: AsRef<str> + Sync,
+{
+    strings
+        .par_iter()
+        .map(|s| character_set.encode(s.as_ref()))
+        .collect()
+}
+
+/// Decode PETSCII file and disk names, in parallel
+///
+/// The names are fields from directory entries and disk headers,
+/// padded with shifted spaces (0xA0).  The padding is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{bulk::decode_names, petscii::PetsciiConfig, Configuration, SystemConfig};
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let names = [[0x47, 0x41, 0x4d, 0x45, 0xa0, 0xa0], [0x44, 0x45, 0x4d, 0x4f, 0x32, 0xa0]];
+///
+/// assert_eq!(decode_names(&petscii, &names), ["GAME", "DEMO2"]);
+/// ```
+pub fn decode_names<B>(petscii: &SystemConfig, names: &[B]) -> Vec<String>
+where
+    B: AsRef<[u8]> + Sync,
+{
+    names
+        .par_iter()
+        .map(|name| {
+            let mut state = PetsciiDecoderState::new(Some(petscii)).with_strip_shifted_space(true);
+            name.as_ref()
+                .iter()
+                .filter_map(|b| match state.feed(*b) {
+                    DecodeEvent::Char(c) => Some(c),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bulk::{decode_all, encode_all},
+        petscii::PetsciiConfig,
+        CharacterSet, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn bulk_matches_single_conversions() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let strings: Vec<String> = (0..1000).map(|i| format!("FILE {i} £")).collect();
+        let buffers = encode_all(&petscii, &strings);
+        for (s, bytes) in strings.iter().zip(&buffers) {
+            assert_eq!(*bytes, petscii.encode(s));
+        }
+        assert_eq!(decode_all(&petscii, &buffers), strings);
+    }
+}
@@ -0,0 +1,140 @@
+//!
+//! Commodore file formats
+//!
+//! Commodore DOS and the tape routines in the KERNAL store names in
+//! fixed-length fields of PETSCII codes.  Names shorter than the
+//! field are padded, and the padding byte depends on where the name
+//! is stored.  Disk directories pad with shifted spaces (0xA0) and
+//! tape headers pad with ordinary spaces (0x20).
+//!
+//! The submodules parse the structures that hold these names.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    error::{Error, ErrorKind},
+    petscii::PetsciiString,
+    SystemConfig,
+};
+
+pub mod basic;
+pub mod collate;
+pub mod dialect;
+pub mod directory;
+pub mod iec;
+pub mod opencbm;
+pub mod prg;
+pub mod prg_studio;
+pub mod rel;
+pub mod seq;
+pub mod status;
+pub mod tape;
+pub mod write;
+
+/// The byte used to pad names in disk directories
+pub const DISK_PADDING: u8 = 0xA0;
+
+/// The byte used to pad names in tape headers
+pub const TAPE_PADDING: u8 = 0x20;
+
+/// Remove trailing padding from a fixed-length field
+///
+/// Any of the bytes in padding are removed from the end of the field.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::{trim_padding, TAPE_PADDING};
+///
+/// assert_eq!(trim_padding(b"GAME    ", &[TAPE_PADDING]), b"GAME");
+/// assert_eq!(trim_padding(b"A B ", &[TAPE_PADDING]), b"A B");
+/// ```
+pub fn trim_padding<'b>(field: &'b [u8], padding: &[u8]) -> &'b [u8] {
+    let len = field
+        .iter()
+        .rposition(|b| !padding.contains(b))
+        .map_or(0, |position| position + 1);
+    &field[..len]
+}
+
+/// True if a name matches a CBM DOS filename pattern
+///
+/// A question mark in the pattern matches any one character, and a
+/// star matches the rest of the name.  Like the DOS, anything after a
+/// star is ignored, so "G*E" matches every name starting with G.  The
+/// name and the pattern are compared as PETSCII codes, after removing
+/// the shifted space padding from both.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::matches_pattern;
+///
+/// assert!(matches_pattern(b"GAME", b"GA*"));
+/// assert!(matches_pattern(b"GAME", b"GAME*"));
+/// assert!(matches_pattern(b"GAME\xa0\xa0", b"G?ME"));
+/// assert!(!matches_pattern(b"GAMES", b"GAME"));
+/// assert!(!matches_pattern(b"GAM", b"GAM?"));
+/// ```
+pub fn matches_pattern(name: &[u8], pattern: &[u8]) -> bool {
+    let name = trim_padding(name, &[DISK_PADDING]);
+    let pattern = trim_padding(pattern, &[DISK_PADDING]);
+
+    for (i, p) in pattern.iter().enumerate() {
+        match (*p, name.get(i)) {
+            (b'*', _) => return true,
+            (b'?', Some(_)) => {}
+            (p, Some(c)) if p == *c => {}
+            _ => return false,
+        }
+    }
+    pattern.len() == name.len()
+}
+
+/// Create a PetsciiString from a padded fixed-length field
+///
+/// The string keeps all of the field data, but its length only
+/// covers the name before the padding.  Returns an error if the field
+/// is longer than L.
+pub fn padded_string<'a, const L: usize>(
+    field: &[u8],
+    padding: &[u8],
+    character_map: Option<&'a SystemConfig>,
+) -> std::result::Result<PetsciiString<'a, L>, Error> {
+    if field.len() > L {
+        return Err(Error::new(ErrorKind::Message(format!(
+            "Field of {} bytes doesn't fit in a string of {} bytes",
+            field.len(),
+            L
+        ))));
+    }
+
+    let mut data = [0; L];
+    data[..field.len()].copy_from_slice(field);
+
+    Ok(PetsciiString {
+        len: trim_padding(field, padding).len() as u32,
+        data,
+        character_map,
+        strip_shifted_space: false,
+    })
+}
+
+/// Read a little-endian 16-bit word at an offset
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+/// Check that a structure has at least len bytes
+pub(crate) fn check_len(data: &[u8], len: usize, name: &str) -> std::result::Result<(), Error> {
+    if data.len() < len {
+        Err(Error::new(ErrorKind::Message(format!(
+            "{} needs {} bytes, only {} available",
+            name,
+            len,
+            data.len()
+        ))))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,470 @@
+//!
+//! Commodore BASIC V2 programs
+//!
+//! BASIC programs are stored as a linked list of lines.  Each line
+//! starts with the address of the next line and the line number,
+//! followed by the tokenized text of the line and a zero byte.  The
+//! list ends with a next line address of zero.
+//!
+//! Keywords are stored as single byte tokens from 0x80 to 0xCB.
+//! Detokenizing replaces the tokens with the PETSCII text of the
+//! keywords, the same way LIST does.  Like LIST, tokens are expanded
+//! everywhere except inside string literals, including after REM.
+//!
+//! For syntax highlighting, listing_tokens splits a line into
+//! keywords, string literals, remarks and the text between them
+//! instead, and ansi_listing uses them to list a program in color.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    cbm::read_u16,
+    error::{Error, ErrorKind},
+    CharacterSet, SystemConfig,
+};
+
+/// The first keyword token
+pub const FIRST_TOKEN: u8 = 0x80;
+
+/// The DATA keyword token
+pub const DATA_TOKEN: u8 = 0x83;
+
+/// The REM keyword token
+pub const REM_TOKEN: u8 = 0x8F;
+
+/// The BASIC V2 keywords, starting at token 0x80
+pub const KEYWORDS: [&str; 76] = [
+    "END", "FOR", "NEXT", "DATA", "INPUT#", "INPUT", "DIM", "READ", "LET", "GOTO", "RUN", "IF",
+    "RESTORE", "GOSUB", "RETURN", "REM", "STOP", "ON", "WAIT", "LOAD", "SAVE", "VERIFY", "DEF",
+    "POKE", "PRINT#", "PRINT", "CONT", "LIST", "CLR", "CMD", "SYS", "OPEN", "CLOSE", "GET", "NEW",
+    "TAB(", "TO", "FN", "SPC(", "THEN", "NOT", "STEP", "+", "-", "*", "/", "^", "AND", "OR", ">",
+    "=", "<", "SGN", "INT", "ABS", "USR", "FRE", "POS", "SQR", "RND", "LOG", "EXP", "COS", "SIN",
+    "TAN", "ATN", "PEEK", "LEN", "STR$", "VAL", "ASC", "CHR$", "LEFT$", "RIGHT$", "MID$", "GO",
+];
+
+/// The keyword for a token
+///
+/// Returns None if the byte isn't a BASIC V2 token.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::basic::keyword;
+///
+/// assert_eq!(keyword(0x99), Some("PRINT"));
+/// assert_eq!(keyword(0x41), None);
+/// ```
+pub fn keyword(token: u8) -> Option<&'static str> {
+    token
+        .checked_sub(FIRST_TOKEN)
+        .and_then(|index| KEYWORDS.get(usize::from(index)))
+        .copied()
+}
+
+/// Look up the keyword a BASIC V2 token starts with
+fn v2_keyword_at(tokens: &[u8]) -> Option<(&'static str, usize)> {
+    tokens
+        .first()
+        .and_then(|b| keyword(*b))
+        .map(|keyword| (keyword, 1))
+}
+
+/// Finds the keyword the tokens start with, and the number of bytes
+/// in its token
+pub(crate) type KeywordLookup<'k> = dyn Fn(&[u8]) -> Option<(&'k str, usize)> + 'k;
+
+/// A line of a tokenized BASIC program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BasicLine<'b> {
+    /// The offset of the line in the program data
+    pub offset: usize,
+    /// The line number
+    pub line_number: u16,
+    /// The tokenized text of the line, without the zero terminator
+    pub tokens: &'b [u8],
+}
+
+impl<'b> BasicLine<'b> {
+    /// The offset of the tokenized text in the program data
+    pub fn tokens_offset(&self) -> usize {
+        // The next line address and the line number come first
+        self.offset + 4
+    }
+
+    /// Detokenize the line into PETSCII text, without the line
+    /// number
+    pub fn detokenize(&self) -> Vec<u8> {
+        detokenize(self.tokens)
+    }
+
+    /// Split the line into listing tokens, starting with the line
+    /// number
+    pub fn listing_tokens(&self) -> Vec<ListingToken<'static>> {
+        self.listing_tokens_with(&v2_keyword_at)
+    }
+
+    /// Split the line into listing tokens with a keyword lookup
+    pub(crate) fn listing_tokens_with<'k>(
+        &self,
+        keyword_at: &KeywordLookup<'k>,
+    ) -> Vec<ListingToken<'k>> {
+        let mut tokens = vec![ListingToken::LineNumber(self.line_number)];
+        tokens.extend(listing_tokens_with(self.tokens, keyword_at));
+        tokens
+    }
+}
+
+/// A piece of a listed BASIC line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListingToken<'k> {
+    /// The line number
+    LineNumber(u16),
+    /// A keyword
+    Keyword(&'k str),
+    /// A string literal in PETSCII, with its quotes.  A string that
+    /// isn't closed runs to the end of the line and only has the
+    /// opening quote.
+    StringLiteral(Vec<u8>),
+    /// The detokenized PETSCII text of a remark, after the REM keyword
+    Remark(Vec<u8>),
+    /// Anything else, like variables, numbers and punctuation, in
+    /// PETSCII
+    Text(Vec<u8>),
+}
+
+impl ListingToken<'_> {
+    /// The PETSCII text of the token, as LIST prints it
+    pub fn to_petscii(&self) -> Vec<u8> {
+        match self {
+            ListingToken::LineNumber(line_number) => line_number.to_string().into_bytes(),
+            ListingToken::Keyword(keyword) => keyword.as_bytes().to_vec(),
+            ListingToken::StringLiteral(bytes)
+            | ListingToken::Remark(bytes)
+            | ListingToken::Text(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// Split a tokenized BASIC program into lines
+///
+/// data is the program without the load address.  The lines are
+/// found by scanning for the zero terminators, not by following the
+/// next line addresses, so programs saved from a different address
+/// can still be read.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::basic::basic_lines;
+///
+/// // 10 PRINT "HI"
+/// let data = [
+///     0x0c, 0x08, 0x0a, 0x00, 0x99, 0x20, 0x22, 0x48, 0x49, 0x22, 0x00, 0x00, 0x00,
+/// ];
+///
+/// let lines = basic_lines(&data).expect("Error reading program");
+///
+/// assert_eq!(lines.len(), 1);
+/// assert_eq!(lines[0].line_number, 10);
+/// assert_eq!(lines[0].detokenize(), b"PRINT \"HI\"");
+/// ```
+pub fn basic_lines(data: &[u8]) -> std::result::Result<Vec<BasicLine<'_>>, Error> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+
+    loop {
+        if data.len() < offset + 2 {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "BASIC program ends without an end marker at offset {}",
+                offset
+            ))));
+        }
+        if read_u16(data, offset) == 0 {
+            return Ok(lines);
+        }
+        if data.len() < offset + 4 {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "BASIC line header cut off at offset {}",
+                offset
+            ))));
+        }
+
+        let text = &data[offset + 4..];
+        let len = text.iter().position(|b| *b == 0).ok_or_else(|| {
+            Error::new(ErrorKind::Message(format!(
+                "BASIC line at offset {} isn't terminated",
+                offset
+            )))
+        })?;
+
+        lines.push(BasicLine {
+            offset,
+            line_number: read_u16(data, offset + 2),
+            tokens: &text[..len],
+        });
+        offset += 4 + len + 1;
+    }
+}
+
+/// Detokenize the text of a BASIC line into PETSCII
+///
+/// Bytes that aren't tokens, like the π character 0xFF, are copied
+/// as they are.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::basic::detokenize;
+///
+/// // Tokens inside quotes aren't expanded
+/// assert_eq!(detokenize(&[0x8f, 0x20, 0x22, 0x8f, 0x22]), b"REM \"\x8f\"");
+/// ```
+pub fn detokenize(tokens: &[u8]) -> Vec<u8> {
+    detokenize_with(tokens, &v2_keyword_at)
+}
+
+/// Detokenize the text of a BASIC line with a keyword lookup
+pub(crate) fn detokenize_with(tokens: &[u8], keyword_at: &KeywordLookup<'_>) -> Vec<u8> {
+    let mut text = Vec::with_capacity(tokens.len() * 2);
+    let mut quoted = false;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let b = tokens[i];
+        if b == b'"' {
+            quoted = !quoted;
+        }
+
+        match keyword_at(&tokens[i..]) {
+            Some((keyword, len)) if !quoted => {
+                text.extend_from_slice(keyword.as_bytes());
+                i += len;
+            }
+            _ => {
+                text.push(b);
+                i += 1;
+            }
+        }
+    }
+
+    text
+}
+
+/// Split the text of a BASIC line into listing tokens
+///
+/// Concatenating the PETSCII of the tokens gives the same text as
+/// detokenize.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::basic::{listing_tokens, ListingToken};
+///
+/// // PRINT "HI":REM GOTO
+/// let tokens = [0x99, 0x20, 0x22, 0x48, 0x49, 0x22, 0x3a, 0x8f, 0x20, 0x89];
+///
+/// assert_eq!(
+///     listing_tokens(&tokens),
+///     [
+///         ListingToken::Keyword("PRINT"),
+///         ListingToken::Text(b" ".to_vec()),
+///         ListingToken::StringLiteral(b"\"HI\"".to_vec()),
+///         ListingToken::Text(b":".to_vec()),
+///         ListingToken::Keyword("REM"),
+///         ListingToken::Remark(b" GOTO".to_vec()),
+///     ]
+/// );
+/// ```
+pub fn listing_tokens(tokens: &[u8]) -> Vec<ListingToken<'static>> {
+    listing_tokens_with(tokens, &v2_keyword_at)
+}
+
+/// Split the text of a BASIC line into listing tokens with a keyword
+/// lookup
+pub(crate) fn listing_tokens_with<'k>(
+    tokens: &[u8],
+    keyword_at: &KeywordLookup<'k>,
+) -> Vec<ListingToken<'k>> {
+    let mut listing = Vec::new();
+    let mut text = Vec::new();
+    let mut i = 0;
+
+    let flush = |listing: &mut Vec<ListingToken<'k>>, text: &mut Vec<u8>| {
+        if !text.is_empty() {
+            listing.push(ListingToken::Text(std::mem::take(text)));
+        }
+    };
+
+    while i < tokens.len() {
+        let b = tokens[i];
+        if b == b'"' {
+            flush(&mut listing, &mut text);
+            let end = tokens[i + 1..]
+                .iter()
+                .position(|b| *b == b'"')
+                .map_or(tokens.len(), |len| i + len + 2);
+            listing.push(ListingToken::StringLiteral(tokens[i..end].to_vec()));
+            i = end;
+        } else if let Some((keyword, len)) = keyword_at(&tokens[i..]) {
+            flush(&mut listing, &mut text);
+            listing.push(ListingToken::Keyword(keyword));
+            i += len;
+            if b == REM_TOKEN {
+                // LIST still expands the tokens in a remark
+                let remark = detokenize_with(&tokens[i..], keyword_at);
+                if !remark.is_empty() {
+                    listing.push(ListingToken::Remark(remark));
+                }
+                return listing;
+            }
+        } else {
+            text.push(b);
+            i += 1;
+        }
+    }
+    flush(&mut listing, &mut text);
+
+    listing
+}
+
+/// The color of each kind of listing token
+const LINE_NUMBER_STYLE: &str = "\x1b[33m";
+const KEYWORD_STYLE: &str = "\x1b[1;36m";
+const STRING_STYLE: &str = "\x1b[32m";
+const REMARK_STYLE: &str = "\x1b[2m";
+const RESET_STYLE: &str = "\x1b[0m";
+
+/// List a BASIC program with ANSI colors
+///
+/// Line numbers are yellow, keywords bold cyan, strings green and
+/// remarks dim.  The text is decoded with the character set, and
+/// control codes in strings show as the reversed characters quote
+/// mode prints for them.  It's a simple example of highlighting with
+/// listing_tokens.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     cbm::basic::{ansi_listing, basic_lines},
+///     petscii::PetsciiConfig,
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// // 10 PRINT "HI"
+/// let data = [
+///     0x0c, 0x08, 0x0a, 0x00, 0x99, 0x20, 0x22, 0x48, 0x49, 0x22, 0x00, 0x00, 0x00,
+/// ];
+/// let lines = basic_lines(&data).expect("Error reading program");
+///
+/// assert_eq!(
+///     ansi_listing(&lines, &petscii),
+///     "\x1b[33m10\x1b[0m \x1b[1;36mPRINT\x1b[0m \x1b[32m\"HI\"\x1b[0m\n"
+/// );
+/// ```
+pub fn ansi_listing(lines: &[BasicLine<'_>], charset: &SystemConfig) -> String {
+    ansi_listing_with(lines, &v2_keyword_at, charset)
+}
+
+/// List a BASIC program with ANSI colors and a keyword lookup
+pub(crate) fn ansi_listing_with(
+    lines: &[BasicLine<'_>],
+    keyword_at: &KeywordLookup<'_>,
+    charset: &SystemConfig,
+) -> String {
+    let mut listing = String::new();
+
+    for line in lines {
+        for token in line.listing_tokens_with(keyword_at) {
+            let (style, text) = match &token {
+                ListingToken::LineNumber(line_number) => {
+                    (LINE_NUMBER_STYLE, format!("{line_number}"))
+                }
+                ListingToken::Keyword(keyword) => (KEYWORD_STYLE, String::from(*keyword)),
+                ListingToken::StringLiteral(bytes) => (STRING_STYLE, charset.decode(bytes)),
+                ListingToken::Remark(bytes) => (REMARK_STYLE, charset.decode(bytes)),
+                ListingToken::Text(bytes) => {
+                    listing.push_str(&charset.decode(bytes));
+                    continue;
+                }
+            };
+            listing.push_str(style);
+            listing.push_str(&text);
+            listing.push_str(RESET_STYLE);
+            if matches!(token, ListingToken::LineNumber(_)) {
+                listing.push(' ');
+            }
+        }
+        listing.push('\n');
+    }
+
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cbm::basic::{
+        basic_lines, detokenize, keyword, listing_tokens, ListingToken, DATA_TOKEN, REM_TOKEN,
+    };
+
+    #[test]
+    fn basic_keywords_work() {
+        assert_eq!(keyword(0x80), Some("END"));
+        assert_eq!(keyword(DATA_TOKEN), Some("DATA"));
+        assert_eq!(keyword(REM_TOKEN), Some("REM"));
+        assert_eq!(keyword(0xCB), Some("GO"));
+        assert_eq!(keyword(0xCC), None);
+        assert_eq!(keyword(0xFF), None);
+    }
+
+    #[test]
+    fn basic_detokenize_works() {
+        // FOR I=1 TO 10:REM A FOR LOOP
+        let tokens = [
+            0x81, 0x49, 0xb2, 0x31, 0x20, 0xa4, 0x20, 0x31, 0x30, 0x3a, 0x8f, 0x20, 0x41, 0x20,
+            0x81, 0x20, 0x4c, 0x4f, 0x4f, 0x50,
+        ];
+
+        assert_eq!(detokenize(&tokens), b"FORI=1 TO 10:REM A FOR LOOP");
+    }
+
+    #[test]
+    fn basic_lines_errors_work() {
+        // The end marker is missing
+        let data = [0x07, 0x08, 0x0a, 0x00, 0x80, 0x00];
+        assert!(basic_lines(&data).is_err());
+
+        // The line isn't terminated
+        let data = [0x07, 0x08, 0x0a, 0x00, 0x80];
+        assert!(basic_lines(&data).is_err());
+
+        let lines = basic_lines(&[0x00, 0x00]).unwrap();
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn basic_listing_tokens_match_detokenize() {
+        // A$="{CLR}{CYN}":IF A$<>"" THEN PRINT"UNCLOSED
+        let tokens = [
+            0x41, 0x24, 0xb2, 0x22, 0x93, 0x9f, 0x22, 0x3a, 0x8b, 0x20, 0x41, 0x24, 0xb3, 0xb1,
+            0x22, 0x22, 0x20, 0xa7, 0x20, 0x99, 0x22, 0x55, 0x4e, 0x43, 0x4c, 0x4f, 0x53, 0x45,
+            0x44,
+        ];
+        let listing = listing_tokens(&tokens);
+
+        assert_eq!(listing[1], ListingToken::Keyword("="));
+        assert_eq!(
+            listing[2],
+            ListingToken::StringLiteral(vec![0x22, 0x93, 0x9f, 0x22])
+        );
+        assert_eq!(
+            listing.last(),
+            Some(&ListingToken::StringLiteral(b"\"UNCLOSED".to_vec()))
+        );
+        let text: Vec<u8> = listing.iter().flat_map(ListingToken::to_petscii).collect();
+        assert_eq!(text, detokenize(&tokens));
+    }
+}
@@ -0,0 +1,235 @@
+//!
+//! Sorting file names
+//!
+//! The 1541 lists a directory in the order of its entries, and never
+//! sorts it.  Tools that do sort it compare the PETSCII codes of the
+//! names, which puts uppercase letters before the graphics
+//! characters and, in the lowercase set, lowercase before uppercase.
+//! Where they differ is the padding.  A tool that sorts the 16 byte
+//! name fields as they're stored compares the shifted space padding
+//! too, and since 0xA0 is above the letters and digits, ABC sorts
+//! before AB.  A tool that removes the padding first sorts AB first.
+//!
+//! A Collation picks one of those, or a natural order for catalogs,
+//! where the digits in a name are compared by their value so PART 2
+//! sorts before PART 10.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::cmp::Ordering;
+
+use crate::{
+    cbm::{
+        directory::DirectoryListing,
+        trim_padding,
+        write::{encode_name, NAME_LEN},
+        DISK_PADDING,
+    },
+    error::Error,
+    SystemConfig,
+};
+
+/// How names are compared
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Collation {
+    /// Compare the names padded to 16 bytes with shifted spaces, the
+    /// way they're stored in the directory
+    Padded,
+    /// Compare the names without padding, so a name sorts before the
+    /// longer names it starts
+    #[default]
+    Trimmed,
+    /// Compare the names without padding, with runs of digits
+    /// compared by their value
+    Natural,
+}
+
+/// Compare two PETSCII file names
+///
+/// Padding with shifted spaces at the end of either name is handled
+/// by the collation, so names can be passed with or without it.
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Ordering;
+///
+/// use forbidden_bands::cbm::collate::{compare_names, Collation};
+///
+/// assert_eq!(compare_names(b"ABC", b"AB", Collation::Padded), Ordering::Less);
+/// assert_eq!(compare_names(b"ABC", b"AB\xa0\xa0", Collation::Trimmed), Ordering::Greater);
+/// assert_eq!(compare_names(b"PART 2", b"PART 10", Collation::Trimmed), Ordering::Greater);
+/// assert_eq!(compare_names(b"PART 2", b"PART 10", Collation::Natural), Ordering::Less);
+/// ```
+pub fn compare_names(a: &[u8], b: &[u8], collation: Collation) -> Ordering {
+    let a = trim_padding(a, &[DISK_PADDING]);
+    let b = trim_padding(b, &[DISK_PADDING]);
+
+    match collation {
+        Collation::Padded => {
+            let len = a.len().max(b.len()).max(NAME_LEN);
+            let padded = |name: &[u8], i: usize| name.get(i).copied().unwrap_or(DISK_PADDING);
+            (0..len)
+                .map(|i| padded(a, i).cmp(&padded(b, i)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        }
+        Collation::Trimmed => a.cmp(b),
+        Collation::Natural => compare_natural(a, b),
+    }
+}
+
+/// Split the leading digits off a name
+fn split_digits(name: &[u8]) -> (&[u8], &[u8]) {
+    let len = name.iter().take_while(|b| b.is_ascii_digit()).count();
+    name.split_at(len)
+}
+
+/// Remove the leading zeros from a run of digits
+fn without_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits.iter().take_while(|b| **b == b'0').count();
+    &digits[start..]
+}
+
+/// Compare names with runs of digits compared by their value
+///
+/// Numbers with the same value and more leading zeros sort after the
+/// shorter ones, so the order is still total.
+fn compare_natural(mut a: &[u8], mut b: &[u8]) -> Ordering {
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (digits_a, rest_a) = split_digits(a);
+                let (digits_b, rest_b) = split_digits(b);
+                let (value_a, value_b) = (without_zeros(digits_a), without_zeros(digits_b));
+
+                let ordering = value_a
+                    .len()
+                    .cmp(&value_b.len())
+                    .then_with(|| value_a.cmp(value_b))
+                    .then_with(|| digits_a.len().cmp(&digits_b.len()));
+                if ordering.is_ne() {
+                    return ordering;
+                }
+                (a, b) = (rest_a, rest_b);
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                (a, b) = (&a[1..], &b[1..]);
+            }
+        }
+    }
+}
+
+impl DirectoryListing {
+    /// Sort the files by name
+    ///
+    /// The names are encoded to PETSCII and compared with the
+    /// collation.  Files with the same name stay in directory order.
+    /// Returns an error, leaving the files as they were, if a name
+    /// can't be encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     cbm::{
+    ///         collate::Collation,
+    ///         directory::{DirectoryEntry, DirectoryListing, FileType},
+    ///         write::DiskHeader,
+    ///     },
+    ///     petscii::PetsciiConfig,
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut listing = DirectoryListing {
+    ///     header: DiskHeader::new("DEMOS", "01"),
+    ///     entries: vec![
+    ///         DirectoryEntry::new("INTRO", 12, FileType::Prg),
+    ///         DirectoryEntry::new("INTRO2", 12, FileType::Prg),
+    ///         DirectoryEntry::new("GAME", 80, FileType::Prg),
+    ///     ],
+    ///     blocks_free: 560,
+    /// };
+    ///
+    /// listing.sort_by_name(Collation::Padded, &petscii).expect("Error encoding names");
+    /// let names: Vec<&str> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+    /// assert_eq!(names, ["GAME", "INTRO2", "INTRO"]);
+    /// ```
+    pub fn sort_by_name(
+        &mut self,
+        collation: Collation,
+        charset: &SystemConfig,
+    ) -> std::result::Result<(), Error> {
+        let names = self
+            .entries
+            .iter()
+            .map(|entry| encode_name(&entry.name, charset))
+            .collect::<std::result::Result<Vec<_>, Error>>()?;
+
+        let mut entries: Vec<_> = names.into_iter().zip(self.entries.drain(..)).collect();
+        entries.sort_by(|(a, _), (b, _)| compare_names(a, b, collation));
+        self.entries = entries.into_iter().map(|(_, entry)| entry).collect();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cbm::collate::{compare_names, Collation};
+
+    #[test]
+    fn collate_sorts_names_with_each_collation() {
+        let names: [&[u8]; 6] = [
+            b"DISK 10", b"DISK 2", b"DISK", b"DISK 02", b"\xc1BC", b"ABC",
+        ];
+        let sorted = |collation| {
+            let mut names = names.to_vec();
+            names.sort_by(|a, b| compare_names(a, b, collation));
+            names
+        };
+
+        assert_eq!(
+            sorted(Collation::Padded),
+            [
+                &b"ABC"[..],
+                b"DISK 02",
+                b"DISK 10",
+                b"DISK 2",
+                b"DISK",
+                b"\xc1BC"
+            ]
+        );
+        assert_eq!(
+            sorted(Collation::Trimmed),
+            [
+                &b"ABC"[..],
+                b"DISK",
+                b"DISK 02",
+                b"DISK 10",
+                b"DISK 2",
+                b"\xc1BC"
+            ]
+        );
+        assert_eq!(
+            sorted(Collation::Natural),
+            [
+                &b"ABC"[..],
+                b"DISK",
+                b"DISK 2",
+                b"DISK 02",
+                b"DISK 10",
+                b"\xc1BC"
+            ]
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//!
+//! BASIC dialects
+//!
+//! BASIC extensions add their own keywords, tokenized with the codes
+//! BASIC V2 doesn't use.  BASIC 7.0 on the C128 uses the single byte
+//! codes from 0xCC and two byte tokens after 0xCE and 0xFE, and
+//! Simons' BASIC puts all of its keywords after a 0x64 prefix.
+//! Listed with the V2 table, those programs come out as graphics
+//! characters and stray letters.
+//!
+//! The keyword tables, V2's included, are configuration data in
+//! [crate::config_data::BASIC_KEYWORDS], versioned like the character
+//! maps.  BasicTokenConfig::load_with_overlay merges an overlay over
+//! them the way [crate::Config::load_named] does, so a wrong keyword
+//! can be fixed or a dialect added without changing the code.
+//!
+//! A Dialect is built from the V2 table with an extension's keywords
+//! over it, and lists programs the way the extension's LIST does.
+//! Extensions that aren't included, like the Final Cartridge, can be
+//! loaded from a table definition with the same layout.
+//! BasicTokenConfig::detect guesses the dialect of a program from the
+//! tokens it uses.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::RwLock,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    cbm::basic::{ansi_listing_with, detokenize_with, keyword, BasicLine, ListingToken},
+    config_data,
+    custom::parse_code,
+    error::{Error, ErrorKind},
+    load_cached, merge_json, SystemConfig, SystemModule,
+};
+
+/// The key of the BASIC V2 table, the base of the other dialects
+pub const V2_DIALECT: &str = "v2";
+
+/// The keywords of a BASIC dialect, as stored in the configuration
+///
+/// Codes are written in decimal or in hexadecimal with a 0x prefix.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DialectDefinition {
+    /// The name of the dialect
+    pub name: String,
+    /// Single byte tokens to keywords
+    #[serde(default)]
+    pub tokens: BTreeMap<String, String>,
+    /// Two byte tokens to keywords, keyed by the first byte
+    #[serde(default)]
+    pub prefixed_tokens: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Configuration data with the BASIC keyword tables
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BasicTokenConfig {
+    /// Version of the keyword tables
+    pub version: String,
+    /// The dialects, by key
+    pub dialects: BTreeMap<String, DialectDefinition>,
+}
+
+/// Configuration data for the dialect module
+pub static CONFIG: RwLock<Option<BasicTokenConfig>> = RwLock::new(None);
+
+impl SystemModule for BasicTokenConfig {
+    const NAMESPACE: &'static str = "basic";
+}
+
+impl BasicTokenConfig {
+    /// Load the embedded keyword tables
+    pub fn load() -> std::result::Result<BasicTokenConfig, Error> {
+        load_cached(&CONFIG, config_data::BASIC_KEYWORDS)
+    }
+
+    /// Load the embedded keyword tables with a JSON overlay merged
+    /// over them
+    ///
+    /// Objects in the overlay are merged key by key, so it only needs
+    /// the entries it changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::cbm::dialect::BasicTokenConfig;
+    ///
+    /// let overlay = r#"{ "dialects": { "tiny": { "name": "Tiny", "tokens": { "0xCC": "BEEP" } } } }"#;
+    /// let config = BasicTokenConfig::load_with_overlay(overlay).expect("Error loading keyword tables");
+    /// let tiny = config.dialect("tiny").expect("Error building dialect");
+    ///
+    /// assert_eq!(tiny.detokenize(&[0xcc, 0x3a, 0x99]), b"BEEP:PRINT");
+    /// ```
+    pub fn load_with_overlay(overlay: &str) -> std::result::Result<BasicTokenConfig, Error> {
+        let mut value: Value = serde_json::from_str(config_data::BASIC_KEYWORDS)?;
+        merge_json(&mut value, serde_json::from_str(overlay)?);
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// The definition of a dialect
+    fn definition(&self, key: &str) -> std::result::Result<&DialectDefinition, Error> {
+        self.dialects
+            .get(key)
+            .ok_or_else(|| Error::new(ErrorKind::Message(format!("No BASIC dialect named {key}"))))
+    }
+
+    /// Build the dialect with a key, like "simons" or "basic7"
+    ///
+    /// The dialect's keywords are put over the V2 table.  Returns an
+    /// error if there's no dialect with the key or a table has an
+    /// invalid code.
+    pub fn dialect(&self, key: &str) -> std::result::Result<Dialect, Error> {
+        let mut dialect = Dialect::from_definition(self.definition(V2_DIALECT)?)?;
+        if key != V2_DIALECT {
+            let extension = Dialect::from_definition(self.definition(key)?)?;
+            dialect.name = extension.name;
+            dialect.tokens.extend(extension.tokens);
+            dialect.prefixed_tokens.extend(extension.prefixed_tokens);
+        }
+        Ok(dialect)
+    }
+
+    /// Guess the dialect of a program
+    ///
+    /// Each dialect is scored by the number of its extension tokens
+    /// found outside strings and remarks, and the key of the one with
+    /// the most is returned.  Returns None if no extension tokens are
+    /// found, for a plain BASIC V2 program.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::cbm::{basic::basic_lines, dialect::BasicTokenConfig};
+    ///
+    /// let config = BasicTokenConfig::load().expect("Error loading keyword tables");
+    ///
+    /// // 10 FAST:GRAPHIC 1
+    /// let data = [
+    ///     0x0c, 0x1c, 0x0a, 0x00, 0xfe, 0x25, 0x3a, 0xde, 0x20, 0x31, 0x00, 0x00, 0x00,
+    /// ];
+    /// let lines = basic_lines(&data).expect("Error reading program");
+    ///
+    /// assert_eq!(config.detect(&lines).expect("Error building dialects"), Some("basic7"));
+    /// ```
+    pub fn detect(&self, lines: &[BasicLine<'_>]) -> std::result::Result<Option<&str>, Error> {
+        let mut best: Option<(&str, usize)> = None;
+
+        for (key, definition) in &self.dialects {
+            if key == V2_DIALECT {
+                continue;
+            }
+            let dialect = self.dialect(key)?;
+            let extension: BTreeSet<&str> = definition
+                .tokens
+                .values()
+                .chain(definition.prefixed_tokens.values().flat_map(|t| t.values()))
+                .map(String::as_str)
+                .collect();
+
+            let count = lines
+                .iter()
+                .flat_map(|line| dialect.listing_tokens(line))
+                .filter(|token| match token {
+                    ListingToken::Keyword(keyword) => extension.contains(keyword),
+                    _ => false,
+                })
+                .count();
+            if count > best.map_or(0, |(_, best)| best) {
+                best = Some((key, count));
+            }
+        }
+
+        Ok(best.map(|(key, _)| key))
+    }
+}
+
+/// A BASIC dialect's keyword tables
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::dialect::BasicTokenConfig;
+///
+/// let config = BasicTokenConfig::load().expect("Error loading keyword tables");
+/// let simons = config.dialect("simons").expect("Error building dialect");
+///
+/// // HIRES 0,1:PRINT
+/// let tokens = [0x64, 0x01, 0x20, 0x30, 0x2c, 0x31, 0x3a, 0x99];
+///
+/// assert_eq!(simons.detokenize(&tokens), b"HIRES 0,1:PRINT");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dialect {
+    name: String,
+    tokens: BTreeMap<u8, String>,
+    prefixed_tokens: BTreeMap<u8, BTreeMap<u8, String>>,
+}
+
+impl Dialect {
+    /// Plain BASIC V2, without extension keywords
+    pub fn v2() -> Dialect {
+        Dialect {
+            name: String::from("BASIC V2"),
+            ..Dialect::default()
+        }
+    }
+
+    /// Build a dialect from a table definition
+    ///
+    /// Tokens the definition doesn't have fall back to the built-in
+    /// V2 keywords.  Returns an error if a code isn't a byte.
+    pub fn from_definition(definition: &DialectDefinition) -> std::result::Result<Dialect, Error> {
+        let table = |entries: &BTreeMap<String, String>| {
+            entries
+                .iter()
+                .map(|(code, keyword)| Ok((parse_code(code)?, keyword.clone())))
+                .collect::<std::result::Result<BTreeMap<u8, String>, Error>>()
+        };
+
+        Ok(Dialect {
+            name: definition.name.clone(),
+            tokens: table(&definition.tokens)?,
+            prefixed_tokens: definition
+                .prefixed_tokens
+                .iter()
+                .map(|(prefix, entries)| Ok((parse_code(prefix)?, table(entries)?)))
+                .collect::<std::result::Result<_, Error>>()?,
+        })
+    }
+
+    /// Load a dialect from a JSON table definition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::cbm::dialect::Dialect;
+    ///
+    /// let json = r#"{ "name": "tiny", "tokens": { "0xCC": "BEEP" } }"#;
+    /// let dialect = Dialect::from_json(json).expect("Error loading table");
+    ///
+    /// assert_eq!(dialect.detokenize(&[0xcc, 0x3a, 0x80]), b"BEEP:END");
+    /// ```
+    pub fn from_json(json_str: &str) -> std::result::Result<Dialect, Error> {
+        let definition: DialectDefinition = serde_json::from_str(json_str)?;
+        Dialect::from_definition(&definition)
+    }
+
+    /// The name of the dialect
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The extension keyword the tokens start with, and the number
+    /// of bytes in its token
+    fn extension_keyword_at(&self, tokens: &[u8]) -> Option<(&str, usize)> {
+        let first = *tokens.first()?;
+        match self.prefixed_tokens.get(&first) {
+            Some(table) => tokens
+                .get(1)
+                .and_then(|second| table.get(second))
+                .map(|keyword| (keyword.as_str(), 2)),
+            None => self.tokens.get(&first).map(|keyword| (keyword.as_str(), 1)),
+        }
+    }
+
+    /// The keyword the tokens start with, and the number of bytes in
+    /// its token
+    ///
+    /// Extension keywords are looked up first, then the V2 keywords.
+    pub fn keyword_at(&self, tokens: &[u8]) -> Option<(&str, usize)> {
+        self.extension_keyword_at(tokens).or_else(|| {
+            tokens
+                .first()
+                .and_then(|b| keyword(*b))
+                .map(|keyword| (keyword, 1))
+        })
+    }
+
+    /// Detokenize the text of a BASIC line into PETSCII
+    pub fn detokenize(&self, tokens: &[u8]) -> Vec<u8> {
+        detokenize_with(tokens, &|tokens| self.keyword_at(tokens))
+    }
+
+    /// Split a line into listing tokens, starting with the line
+    /// number
+    pub fn listing_tokens(&self, line: &BasicLine<'_>) -> Vec<ListingToken<'_>> {
+        line.listing_tokens_with(&|tokens| self.keyword_at(tokens))
+    }
+
+    /// List a BASIC program with ANSI colors, see
+    /// [crate::cbm::basic::ansi_listing]
+    pub fn ansi_listing(&self, lines: &[BasicLine<'_>], charset: &SystemConfig) -> String {
+        ansi_listing_with(lines, &|tokens| self.keyword_at(tokens), charset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cbm::{
+        basic::{keyword, KEYWORDS},
+        dialect::{BasicTokenConfig, Dialect, V2_DIALECT},
+    };
+
+    #[test]
+    fn dialect_lists_extension_tokens() {
+        let config = BasicTokenConfig::load().expect("Error loading keyword tables");
+        let basic7 = config.dialect("basic7").unwrap();
+        assert_eq!(basic7.name(), "BASIC 7.0");
+
+        // DO:SLEEP 1:LOOP UNTIL "A"=A$ with a token in the string
+        let tokens = [
+            0xeb, 0x3a, 0xfe, 0x0b, 0x20, 0x31, 0x3a, 0xec, 0x20, 0xfc, 0x20, 0x22, 0xeb, 0x22,
+            0xb2, 0x41, 0x24,
+        ];
+        assert_eq!(
+            basic7.detokenize(&tokens),
+            b"DO:SLEEP 1:LOOP UNTIL \"\xeb\"=A$"
+        );
+        assert_eq!(basic7.keyword_at(&[0x99]), Some(("PRINT", 1)));
+        // An unknown second byte isn't a token
+        assert_eq!(basic7.keyword_at(&[0xfe, 0x20]), None);
+
+        assert_eq!(Dialect::v2().detokenize(&tokens)[..2], [0xeb, 0x3a]);
+        assert!(config.dialect("final-cartridge").is_err());
+    }
+
+    #[test]
+    fn dialect_v2_table_matches_the_keywords() {
+        let config = BasicTokenConfig::load().expect("Error loading keyword tables");
+        let v2 = config.dialect(V2_DIALECT).unwrap();
+        for token in 0x80..=0xFF {
+            assert_eq!(
+                v2.keyword_at(&[token]).map(|(keyword, _)| keyword),
+                keyword(token),
+                "{token:#04x}"
+            );
+        }
+        assert_eq!(config.dialects[V2_DIALECT].tokens.len(), KEYWORDS.len());
+
+        // An overlay fixes a keyword without touching the others
+        let overlay = r#"{ "dialects": { "v2": { "tokens": { "0xCB": "GO " } } } }"#;
+        let config = BasicTokenConfig::load_with_overlay(overlay).unwrap();
+        let basic7 = config.dialect("basic7").unwrap();
+        assert_eq!(basic7.detokenize(&[0xcb, 0xa4, 0xfe, 0x25]), b"GO TOFAST");
+    }
+}
@@ -0,0 +1,365 @@
+//!
+//! Directory listings
+//!
+//! LOAD"$",8 followed by LIST shows the directory of a disk as a
+//! BASIC program.  The header line has the disk name in reverse
+//! video, each file is a line numbered with its size in blocks, and
+//! the last line has the number of free blocks:
+//!
+//! ```text
+//! 0 "DEMOS           " 01 2A
+//! 12   "INTRO"            PRG
+//! 3    "NOTES"           *SEQ<
+//! 649 BLOCKS FREE.
+//! ```
+//!
+//! The DOS pads the line after the block count so the names line up,
+//! and pads each name to 16 characters.  Files that weren't closed
+//! have a star before the type, and locked files have a less-than
+//! sign after it.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    cbm::{
+        matches_pattern,
+        write::{encode_name, DiskHeader, NAME_LEN},
+    },
+    error::Error,
+    SystemConfig,
+};
+
+/// The type of a file in a directory entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileType {
+    /// A deleted file
+    Del,
+    /// A sequential file
+    Seq,
+    /// A program file
+    Prg,
+    /// A user file
+    Usr,
+    /// A relative file
+    Rel,
+    /// A type the DOS doesn't define
+    Unknown(u8),
+}
+
+impl FileType {
+    /// The three letter name shown in listings
+    pub fn name(self) -> &'static str {
+        match self {
+            FileType::Del => "DEL",
+            FileType::Seq => "SEQ",
+            FileType::Prg => "PRG",
+            FileType::Usr => "USR",
+            FileType::Rel => "REL",
+            FileType::Unknown(_) => "???",
+        }
+    }
+}
+
+impl From<u8> for FileType {
+    /// The file type in the low bits of a directory entry's type byte
+    fn from(value: u8) -> Self {
+        match value & 0x07 {
+            0 => FileType::Del,
+            1 => FileType::Seq,
+            2 => FileType::Prg,
+            3 => FileType::Usr,
+            4 => FileType::Rel,
+            other => FileType::Unknown(other),
+        }
+    }
+}
+
+/// A file in a directory listing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    /// The decoded filename, without padding
+    pub name: String,
+    /// The size of the file in blocks
+    pub blocks: u16,
+    /// The file type
+    pub file_type: FileType,
+    /// False for files that weren't closed, shown with a star
+    pub closed: bool,
+    /// True for locked files, shown with a less-than sign
+    pub locked: bool,
+}
+
+impl DirectoryEntry {
+    /// Create an entry for a closed, unlocked file
+    pub fn new(name: &str, blocks: u16, file_type: FileType) -> DirectoryEntry {
+        DirectoryEntry {
+            name: String::from(name),
+            blocks,
+            file_type,
+            closed: true,
+            locked: false,
+        }
+    }
+
+    /// Create an entry from the type byte of a directory entry, which
+    /// also has the closed and locked flags
+    pub fn from_type_byte(name: &str, blocks: u16, type_byte: u8) -> DirectoryEntry {
+        DirectoryEntry {
+            name: String::from(name),
+            blocks,
+            file_type: FileType::from(type_byte),
+            closed: type_byte & 0x80 != 0,
+            locked: type_byte & 0x40 != 0,
+        }
+    }
+}
+
+/// A disk directory, listed the way the 1541 lists it
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::{
+///     directory::{DirectoryEntry, DirectoryListing, FileType},
+///     write::DiskHeader,
+/// };
+///
+/// let listing = DirectoryListing {
+///     header: DiskHeader::new("DEMOS", "01"),
+///     entries: vec![DirectoryEntry::new("INTRO", 12, FileType::Prg)],
+///     blocks_free: 652,
+/// };
+///
+/// assert_eq!(
+///     listing.to_string(),
+///     "0 \"DEMOS           \" 01 2A\n\
+///      12   \"INTRO\"            PRG\n\
+///      652 BLOCKS FREE."
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirectoryListing {
+    /// The disk name, ID and DOS type
+    pub header: DiskHeader,
+    /// The files, in directory order
+    pub entries: Vec<DirectoryEntry>,
+    /// The number of free blocks on the disk
+    pub blocks_free: u16,
+}
+
+/// Pad a name to the width of a name field
+fn pad_name(name: &str) -> String {
+    format!("{:<width$}", name, width = NAME_LEN)
+}
+
+impl DirectoryListing {
+    /// The header line, after the line number
+    fn header_line(&self) -> String {
+        format!(
+            "\"{}\" {} {}",
+            pad_name(&self.header.name),
+            self.header.id,
+            self.header.dos_type
+        )
+    }
+
+    /// The line for a file
+    fn entry_line(entry: &DirectoryEntry) -> String {
+        // The DOS pads short block counts so names start in the same
+        // column
+        let indent = match entry.blocks {
+            0..=9 => 3,
+            10..=99 => 2,
+            100..=999 => 1,
+            _ => 0,
+        };
+        let quoted = format!("\"{}\"", entry.name);
+
+        format!(
+            "{} {:indent$}{:<width$}{}{}{}",
+            entry.blocks,
+            "",
+            quoted,
+            if entry.closed { ' ' } else { '*' },
+            entry.file_type.name(),
+            if entry.locked { "<" } else { "" },
+            indent = indent,
+            width = NAME_LEN + 2,
+        )
+    }
+
+    /// The lines of the listing, without the line number of the
+    /// header
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![self.header_line()];
+        lines.extend(self.entries.iter().map(DirectoryListing::entry_line));
+        lines.push(format!("{} BLOCKS FREE.", self.blocks_free));
+        lines
+    }
+
+    /// The first file matching a CBM DOS filename pattern, the file
+    /// LOAD would load
+    ///
+    /// The pattern and the names are encoded and compared as PETSCII,
+    /// see cbm::matches_pattern.  Returns an error if the pattern or
+    /// a name can't be encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     cbm::{
+    ///         directory::{DirectoryEntry, DirectoryListing, FileType},
+    ///         write::DiskHeader,
+    ///     },
+    ///     petscii::PetsciiConfig,
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let listing = DirectoryListing {
+    ///     header: DiskHeader::new("DEMOS", "01"),
+    ///     entries: vec![
+    ///         DirectoryEntry::new("INTRO", 12, FileType::Prg),
+    ///         DirectoryEntry::new("GAME", 80, FileType::Prg),
+    ///     ],
+    ///     blocks_free: 572,
+    /// };
+    ///
+    /// let entry = listing.find("G*", &petscii).expect("Error encoding names");
+    /// assert_eq!(entry.map(|e| e.name.as_str()), Some("GAME"));
+    /// ```
+    pub fn find(
+        &self,
+        pattern: &str,
+        charset: &SystemConfig,
+    ) -> std::result::Result<Option<&DirectoryEntry>, Error> {
+        let pattern = encode_name(pattern, charset)?;
+        for entry in &self.entries {
+            if matches_pattern(&encode_name(&entry.name, charset)?, &pattern) {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Encode the listing into the PETSCII a C64 prints for it
+    ///
+    /// Every line ends with a carriage return, and the header is in
+    /// reverse video.  Returns an error if a name can't be encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     cbm::{directory::DirectoryListing, write::DiskHeader},
+    ///     petscii::PetsciiConfig,
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let listing = DirectoryListing {
+    ///     header: DiskHeader::new("DEMOS", "01"),
+    ///     entries: vec![],
+    ///     blocks_free: 664,
+    /// };
+    /// let bytes = listing.to_petscii(&petscii).expect("Error encoding listing");
+    ///
+    /// assert_eq!(&bytes[..5], b"0 \x12\"D");
+    /// assert!(bytes.ends_with(b"664 BLOCKS FREE.\r"));
+    /// ```
+    pub fn to_petscii(&self, charset: &SystemConfig) -> std::result::Result<Vec<u8>, Error> {
+        let mut bytes = b"0 \x12".to_vec();
+        for line in self.lines() {
+            bytes.extend(encode_name(&line, charset)?);
+            bytes.push(0x0D);
+        }
+        Ok(bytes)
+    }
+}
+
+impl Display for DirectoryListing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0 {}", self.lines().join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cbm::{
+            directory::{DirectoryEntry, DirectoryListing, FileType},
+            write::DiskHeader,
+        },
+        petscii::PetsciiConfig,
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn directory_entry_flags_work() {
+        let listing = DirectoryListing {
+            header: DiskHeader::new("GAMES", "AB"),
+            entries: vec![
+                DirectoryEntry::from_type_byte("NOTES", 3, 0x41),
+                DirectoryEntry::from_type_byte("BIG", 1000, 0x82),
+                DirectoryEntry::new("SIXTEEN CHARS!!!", 100, FileType::Rel),
+            ],
+            blocks_free: 0,
+        };
+
+        let text = listing.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[1], "3    \"NOTES\"           *SEQ<");
+        assert_eq!(lines[2], "1000 \"BIG\"              PRG");
+        assert_eq!(lines[3], "100  \"SIXTEEN CHARS!!!\" REL");
+        assert_eq!(lines[4], "0 BLOCKS FREE.");
+    }
+
+    #[test]
+    fn directory_unencodable_name_fails() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut listing = DirectoryListing {
+            header: DiskHeader::new("DISK", "01"),
+            entries: vec![DirectoryEntry::new("FILE", 1, FileType::Prg)],
+            blocks_free: 663,
+        };
+        let bytes = listing.to_petscii(&petscii).unwrap();
+        assert_eq!(bytes.iter().filter(|b| **b == 0x0D).count(), 3);
+        assert!(!bytes.contains(&0xA0));
+
+        listing.entries[0].name = String::from("FILE│");
+        assert!(listing.to_petscii(&petscii).is_err());
+    }
+
+    #[test]
+    fn directory_find_matches_patterns() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let listing = DirectoryListing {
+            header: DiskHeader::new("DISK", "01"),
+            entries: vec![
+                DirectoryEntry::new("GAME", 10, FileType::Prg),
+                DirectoryEntry::new("GAMES", 20, FileType::Prg),
+                DirectoryEntry::new("game", 30, FileType::Seq),
+            ],
+            blocks_free: 604,
+        };
+
+        let blocks = |pattern| listing.find(pattern, &petscii).unwrap().map(|e| e.blocks);
+        assert_eq!(blocks("GAME?"), Some(20));
+        assert_eq!(blocks("*"), Some(10));
+        // Names don't keep the character set, so lowercase letters
+        // are the same codes as uppercase ones
+        assert_eq!(blocks("g*"), Some(10));
+        assert_eq!(blocks("GAMES?"), None);
+    }
+}
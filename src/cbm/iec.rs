@@ -0,0 +1,345 @@
+//!
+//! IEC and IEEE-488 bus traces
+//!
+//! Commodore computers talk to drives and printers over a serial IEC
+//! bus, or the parallel IEEE-488 bus on the PET, with the same
+//! protocol.  While ATN is held the computer sends command bytes that
+//! pick a device to listen or talk and a secondary address, the
+//! channel.  Then the data bytes flow, from the computer to the
+//! listener or from the talker to the computer.
+//!
+//! annotate turns a captured byte stream, with the phase each byte
+//! was sent in, into a trace of the commands, file names and data.
+//! File names and channel 15, the DOS command and error channel, are
+//! PETSCII text and are decoded.  Data on other channels is usually
+//! binary and is shown as hex.
+//!
+//! ```text
+//! ATN LISTEN 8
+//! ATN OPEN 2
+//! NAME 8,2 "NOTES,S,W"
+//! ATN UNLISTEN
+//! ```
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Display, Formatter};
+
+use crate::{cbm::trim_padding, CharacterSet, SystemConfig};
+
+/// The channel DOS commands are sent to and status is read from
+pub const COMMAND_CHANNEL: u8 = 15;
+
+/// The phase a byte was sent in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusPhase {
+    /// A command byte, sent while ATN is held
+    Atn,
+    /// A data byte sent by the talker
+    Talk,
+    /// A data byte sent to the listeners
+    Listen,
+}
+
+/// A command byte sent while ATN is held
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusCommand {
+    /// A device starts listening
+    Listen(u8),
+    /// All devices stop listening
+    Unlisten,
+    /// A device starts talking
+    Talk(u8),
+    /// The device stops talking
+    Untalk,
+    /// Data follows for a secondary address
+    Data(u8),
+    /// The file on a secondary address is closed
+    Close(u8),
+    /// A file is opened on a secondary address, its name follows
+    Open(u8),
+    /// A byte that isn't a command
+    Unknown(u8),
+}
+
+impl From<u8> for BusCommand {
+    fn from(byte: u8) -> BusCommand {
+        match byte {
+            0x3F => BusCommand::Unlisten,
+            0x5F => BusCommand::Untalk,
+            0x20..=0x3E => BusCommand::Listen(byte & 0x1F),
+            0x40..=0x5E => BusCommand::Talk(byte & 0x1F),
+            0x60..=0x6F => BusCommand::Data(byte & 0x0F),
+            0xE0..=0xEF => BusCommand::Close(byte & 0x0F),
+            0xF0..=0xFF => BusCommand::Open(byte & 0x0F),
+            _ => BusCommand::Unknown(byte),
+        }
+    }
+}
+
+impl Display for BusCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BusCommand::Listen(device) => write!(f, "LISTEN {device}"),
+            BusCommand::Unlisten => write!(f, "UNLISTEN"),
+            BusCommand::Talk(device) => write!(f, "TALK {device}"),
+            BusCommand::Untalk => write!(f, "UNTALK"),
+            BusCommand::Data(channel) => write!(f, "DATA {channel}"),
+            BusCommand::Close(channel) => write!(f, "CLOSE {channel}"),
+            BusCommand::Open(channel) => write!(f, "OPEN {channel}"),
+            BusCommand::Unknown(byte) => write!(f, "${byte:02X}"),
+        }
+    }
+}
+
+/// A line of a bus trace
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BusEvent {
+    /// A command byte
+    Command(BusCommand),
+    /// The name of a file being opened, or a DOS command sent with
+    /// the open of channel 15
+    Name {
+        /// The listening device, if the trace saw it addressed
+        device: Option<u8>,
+        /// The secondary address
+        channel: u8,
+        /// The decoded name
+        name: String,
+    },
+    /// A run of data bytes sent in the same phase
+    Data {
+        /// Talk or Listen
+        phase: BusPhase,
+        /// The talking or listening device, if the trace saw it
+        /// addressed
+        device: Option<u8>,
+        /// The secondary address, if the trace saw it
+        channel: Option<u8>,
+        /// The bytes
+        bytes: Vec<u8>,
+        /// The decoded text, for the command channel
+        text: Option<String>,
+    },
+}
+
+/// Write a device and channel, with a question mark for the ones
+/// that weren't seen
+fn write_address(
+    f: &mut Formatter<'_>,
+    device: Option<u8>,
+    channel: Option<u8>,
+) -> std::fmt::Result {
+    match device {
+        Some(device) => write!(f, "{device}")?,
+        None => write!(f, "?")?,
+    }
+    match channel {
+        Some(channel) => write!(f, ",{channel}"),
+        None => write!(f, ",?"),
+    }
+}
+
+impl Display for BusEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BusEvent::Command(command) => write!(f, "ATN {command}"),
+            BusEvent::Name {
+                device,
+                channel,
+                name,
+            } => {
+                write!(f, "NAME ")?;
+                write_address(f, *device, Some(*channel))?;
+                write!(f, " {name:?}")
+            }
+            BusEvent::Data {
+                phase,
+                device,
+                channel,
+                bytes,
+                text,
+            } => {
+                let phase = match phase {
+                    BusPhase::Talk => "TALK",
+                    _ => "LISTEN",
+                };
+                write!(f, "{phase} ")?;
+                write_address(f, *device, *channel)?;
+                match text {
+                    Some(text) => write!(f, " {text:?}"),
+                    None => {
+                        write!(f, " {} bytes:", bytes.len())?;
+                        bytes.iter().try_for_each(|b| write!(f, " {b:02X}"))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Annotate a captured bus byte stream
+///
+/// Each byte is tagged with the phase it was sent in.  Runs of data
+/// bytes in the same phase become one event, addressed to the device
+/// and channel the last commands selected.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     cbm::iec::{annotate, BusPhase::{Atn, Listen, Talk}},
+///     petscii::PetsciiConfig,
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let mut capture = vec![(Atn, 0x28), (Atn, 0xF0), (Listen, 0x24), (Atn, 0x3F)];
+/// capture.extend([(Atn, 0x48), (Atn, 0x6F)]);
+/// capture.extend(b"00, OK,00,00\r".iter().map(|b| (Talk, *b)));
+///
+/// let trace: Vec<String> = annotate(capture, &petscii).iter().map(ToString::to_string).collect();
+///
+/// assert_eq!(
+///     trace,
+///     [
+///         "ATN LISTEN 8",
+///         "ATN OPEN 0",
+///         "NAME 8,0 \"$\"",
+///         "ATN UNLISTEN",
+///         "ATN TALK 8",
+///         "ATN DATA 15",
+///         "TALK 8,15 \"00, OK,00,00\"",
+///     ]
+/// );
+/// ```
+pub fn annotate(
+    bytes: impl IntoIterator<Item = (BusPhase, u8)>,
+    petscii: &SystemConfig,
+) -> Vec<BusEvent> {
+    let mut bus = Bus {
+        petscii,
+        events: Vec::new(),
+        listener: None,
+        talker: None,
+        channel: None,
+        opening: false,
+        phase: BusPhase::Atn,
+        run: Vec::new(),
+    };
+
+    for (phase, byte) in bytes {
+        if phase != bus.phase {
+            bus.finish_run();
+            bus.phase = phase;
+        }
+        if phase == BusPhase::Atn {
+            bus.command(BusCommand::from(byte));
+        } else {
+            bus.run.push(byte);
+        }
+    }
+    bus.finish_run();
+
+    bus.events
+}
+
+/// What the commands so far have selected, and the data bytes that
+/// haven't been added to the trace yet
+struct Bus<'a> {
+    petscii: &'a SystemConfig,
+    events: Vec<BusEvent>,
+    listener: Option<u8>,
+    talker: Option<u8>,
+    channel: Option<u8>,
+    /// True after an open command, until the name has been sent
+    opening: bool,
+    phase: BusPhase,
+    run: Vec<u8>,
+}
+
+impl Bus<'_> {
+    fn command(&mut self, command: BusCommand) {
+        match command {
+            BusCommand::Listen(device) => self.listener = Some(device),
+            BusCommand::Talk(device) => self.talker = Some(device),
+            BusCommand::Unlisten | BusCommand::Untalk => {
+                self.channel = None;
+                self.opening = false;
+            }
+            BusCommand::Data(channel) | BusCommand::Close(channel) => {
+                self.channel = Some(channel);
+                self.opening = false;
+            }
+            BusCommand::Open(channel) => {
+                self.channel = Some(channel);
+                self.opening = true;
+            }
+            BusCommand::Unknown(_) => {}
+        }
+        self.events.push(BusEvent::Command(command));
+    }
+
+    /// Add the data bytes to the trace
+    fn finish_run(&mut self) {
+        if self.run.is_empty() {
+            return;
+        }
+        let bytes = std::mem::take(&mut self.run);
+        let text = || self.petscii.decode(trim_padding(&bytes, &[0x0D]));
+
+        let event = match (self.phase, self.channel) {
+            (BusPhase::Listen, Some(channel)) if self.opening => BusEvent::Name {
+                device: self.listener,
+                channel,
+                name: text(),
+            },
+            (phase, channel) => BusEvent::Data {
+                phase,
+                device: match phase {
+                    BusPhase::Talk => self.talker,
+                    _ => self.listener,
+                },
+                channel,
+                text: (channel == Some(COMMAND_CHANNEL)).then(text),
+                bytes,
+            },
+        };
+        self.events.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cbm::iec::{
+            annotate, BusCommand, BusEvent,
+            BusPhase::{Atn, Listen},
+        },
+        petscii::PetsciiConfig,
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn iec_annotates_binary_data_as_hex() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Data without the commands that addressed it, then a save to
+        // channel 1 of device 9
+        let capture = [
+            (Listen, 0x01),
+            (Atn, 0x29),
+            (Atn, 0x61),
+            (Listen, 0x01),
+            (Listen, 0x08),
+        ];
+        let events = annotate(capture, &petscii);
+
+        assert_eq!(events[0].to_string(), "LISTEN ?,? 1 bytes: 01");
+        assert_eq!(events[2], BusEvent::Command(BusCommand::Data(1)));
+        assert_eq!(events[3].to_string(), "LISTEN 9,1 2 bytes: 01 08");
+    }
+}
@@ -0,0 +1,165 @@
+//!
+//! OpenCBM compatible conversions
+//!
+//! OpenCBM, the library behind cbmcopy, d64copy and the other tools
+//! for drives connected to a PC, converts between PETSCII and ASCII
+//! with its own small functions, cbm_petscii2ascii_c and
+//! cbm_ascii2petscii_c.  They only swap the letter cases around and
+//! replace everything else that isn't printable ASCII with a dot.
+//!
+//! The functions here reproduce them byte for byte, so names and files
+//! exchanged with the OpenCBM tools convert the same way in both
+//! directions.  A Compatibility flag picks between them and the
+//! conversions of a character set from this crate.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{CharacterSet, DecodeOptions, DecodeTarget, SystemConfig};
+
+/// Which conversion to use
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Convert exactly the way OpenCBM does
+    #[default]
+    OpenCbm,
+    /// Convert with the character set, approximating graphics
+    /// characters with ASCII
+    Native,
+}
+
+/// Convert a PETSCII code to ASCII the way cbm_petscii2ascii_c does
+///
+/// Line feeds and carriage returns become line feeds, shifted spaces
+/// become spaces and the codes OpenCBM doesn't convert become dots.
+/// Like OpenCBM, the codes from 0x5B to 0x5F are moved with the
+/// letters, so the pound sign becomes a vertical bar and the left
+/// arrow becomes DEL.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::opencbm::petscii_to_ascii_c;
+///
+/// assert_eq!(petscii_to_ascii_c(0x41), b'a');
+/// assert_eq!(petscii_to_ascii_c(0xc1), b'A');
+/// assert_eq!(petscii_to_ascii_c(0x0d), b'\n');
+/// assert_eq!(petscii_to_ascii_c(0x93), b'.');
+/// ```
+pub fn petscii_to_ascii_c(code: u8) -> u8 {
+    match code {
+        0x0A | 0x0D => b'\n',
+        0x40 | 0x60 => code,
+        0xA0 | 0xE0 => b' ',
+        _ => match code & 0xE0 {
+            0x40 | 0x60 => code ^ 0x20,
+            0xC0 => code ^ 0x80,
+            _ if code.is_ascii_graphic() || code == b' ' => code,
+            _ => b'.',
+        },
+    }
+}
+
+/// Convert an ASCII byte to PETSCII the way cbm_ascii2petscii_c does
+///
+/// Only the letters change, everything else is kept.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::opencbm::ascii_to_petscii_c;
+///
+/// assert_eq!(ascii_to_petscii_c(b'a'), 0x41);
+/// assert_eq!(ascii_to_petscii_c(b'A'), 0xc1);
+/// assert_eq!(ascii_to_petscii_c(b'1'), 0x31);
+/// ```
+pub fn ascii_to_petscii_c(c: u8) -> u8 {
+    match c {
+        b'A'..=b'Z' => c | 0x80,
+        b'a'..=b'z' => c ^ 0x20,
+        _ => c,
+    }
+}
+
+impl SystemConfig {
+    /// Convert PETSCII to ASCII
+    ///
+    /// With Compatibility::OpenCbm every byte is converted with
+    /// petscii_to_ascii_c.  With Compatibility::Native the bytes are
+    /// decoded with ASCII approximations of the graphics characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     cbm::opencbm::Compatibility, petscii::PetsciiConfig, Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let name = [0xc7, 0x41, 0x4d, 0x45, 0xa0, 0x5c];
+    ///
+    /// assert_eq!(petscii.petscii_to_ascii(&name, Compatibility::OpenCbm), "Game |");
+    /// ```
+    pub fn petscii_to_ascii(&self, bytes: &[u8], compatibility: Compatibility) -> String {
+        match compatibility {
+            Compatibility::OpenCbm => bytes
+                .iter()
+                .map(|b| char::from(petscii_to_ascii_c(*b)))
+                .collect(),
+            Compatibility::Native => self.decode_with_options(
+                bytes,
+                &DecodeOptions::default().with_target(DecodeTarget::AsciiApproximation),
+            ),
+        }
+    }
+
+    /// Convert ASCII to PETSCII
+    ///
+    /// With Compatibility::OpenCbm every byte of the string is
+    /// converted with ascii_to_petscii_c, the way OpenCBM converts the
+    /// bytes of a UTF-8 file.  With Compatibility::Native the string is
+    /// encoded with the character set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     cbm::opencbm::Compatibility, petscii::PetsciiConfig, Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert_eq!(petscii.ascii_to_petscii("Game", Compatibility::OpenCbm), [0xc7, 0x41, 0x4d, 0x45]);
+    /// assert_eq!(petscii.ascii_to_petscii("GAME", Compatibility::Native), [0x47, 0x41, 0x4d, 0x45]);
+    /// ```
+    pub fn ascii_to_petscii(&self, s: &str, compatibility: Compatibility) -> Vec<u8> {
+        match compatibility {
+            Compatibility::OpenCbm => s.bytes().map(ascii_to_petscii_c).collect(),
+            Compatibility::Native => self.encode(s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cbm::opencbm::{ascii_to_petscii_c, petscii_to_ascii_c};
+
+    #[test]
+    fn opencbm_round_trips_letters_and_digits() {
+        for c in (b'0'..=b'9').chain(b'A'..=b'Z').chain(b'a'..=b'z') {
+            assert_eq!(petscii_to_ascii_c(ascii_to_petscii_c(c)), c);
+        }
+
+        // Every code converts to printable ASCII, a line feed or DEL
+        for code in 0..=255 {
+            let c = petscii_to_ascii_c(code);
+            assert!(
+                c.is_ascii_graphic() || b" \n\x7f".contains(&c),
+                "{code:#04x}"
+            );
+        }
+        assert_eq!(petscii_to_ascii_c(0x5f), 0x7f);
+    }
+}
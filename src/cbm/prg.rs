@@ -0,0 +1,263 @@
+//!
+//! PRG program files
+//!
+//! A PRG file starts with the two byte address the program is loaded
+//! at, followed by the program.  BASIC programs on the C64 are usually
+//! loaded at 0x0801.
+//!
+//! The text in a BASIC program is in its string literals and DATA
+//! statements.  This module finds them and reports the address each
+//! one is loaded at, so they can be translated or patched in place.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    cbm::{
+        basic::{basic_lines, BasicLine, DATA_TOKEN},
+        check_len, read_u16,
+    },
+    error::Error,
+};
+
+/// The usual load address of a BASIC program on the C64
+pub const C64_BASIC_START: u16 = 0x0801;
+
+/// A PRG program file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Prg<'b> {
+    /// The address the program is loaded at
+    pub load_address: u16,
+    /// The program, without the load address
+    pub data: &'b [u8],
+}
+
+/// Where a piece of text in a BASIC program comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiteralKind {
+    /// A string literal in quotes
+    String,
+    /// An item in a DATA statement
+    Data,
+}
+
+/// A piece of text in a BASIC program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BasicLiteral<'b> {
+    /// Where the text comes from
+    pub kind: LiteralKind,
+    /// The number of the line the text is on
+    pub line_number: u16,
+    /// The memory address of the text, once the program is loaded
+    pub address: u16,
+    /// The PETSCII text, without the surrounding quotes
+    pub bytes: &'b [u8],
+}
+
+impl<'b> Prg<'b> {
+    /// Parse a PRG file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::cbm::prg::{Prg, C64_BASIC_START};
+    ///
+    /// let prg = Prg::parse(&[0x01, 0x08, 0x00, 0x00]).expect("Error parsing PRG");
+    ///
+    /// assert_eq!(prg.load_address, C64_BASIC_START);
+    /// assert_eq!(prg.data, &[0x00, 0x00]);
+    /// ```
+    pub fn parse(bytes: &'b [u8]) -> std::result::Result<Prg<'b>, Error> {
+        check_len(bytes, 2, "PRG file")?;
+
+        Ok(Prg {
+            load_address: read_u16(bytes, 0),
+            data: &bytes[2..],
+        })
+    }
+
+    /// The memory address of an offset in the program
+    pub fn address_of(&self, offset: usize) -> u16 {
+        self.load_address.wrapping_add(offset as u16)
+    }
+
+    /// The lines of the program, if it's a BASIC program
+    pub fn basic_lines(&self) -> std::result::Result<Vec<BasicLine<'b>>, Error> {
+        basic_lines(self.data)
+    }
+
+    /// Find the string literals and DATA items in a BASIC program
+    ///
+    /// DATA items are split at commas and colons outside quotes.
+    /// Quoted DATA items are reported as DATA items, without the
+    /// quotes.  Spaces around unquoted DATA items are kept, since
+    /// they're part of the item when the program reads it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     cbm::prg::{LiteralKind, Prg},
+    ///     petscii::PetsciiConfig,
+    ///     CharacterSet, Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// // 10 PRINT "HELLO"
+    /// // 20 DATA 1,TWO
+    /// let bytes = [
+    ///     0x01, 0x08, 0x0f, 0x08, 0x0a, 0x00, 0x99, 0x20, 0x22, 0x48, 0x45, 0x4c, 0x4c, 0x4f,
+    ///     0x22, 0x00, 0x19, 0x08, 0x14, 0x00, 0x83, 0x20, 0x31, 0x2c, 0x54, 0x57, 0x4f, 0x00,
+    ///     0x00, 0x00,
+    /// ];
+    ///
+    /// let prg = Prg::parse(&bytes).expect("Error parsing PRG");
+    /// let literals = prg.basic_literals().expect("Error reading program");
+    ///
+    /// assert_eq!(literals.len(), 3);
+    /// assert_eq!(literals[0].kind, LiteralKind::String);
+    /// assert_eq!(literals[0].address, 0x0808);
+    /// assert_eq!(petscii.decode(literals[0].bytes), "HELLO");
+    /// assert_eq!(literals[2].kind, LiteralKind::Data);
+    /// assert_eq!(petscii.decode(literals[2].bytes), "TWO");
+    /// ```
+    pub fn basic_literals(&self) -> std::result::Result<Vec<BasicLiteral<'b>>, Error> {
+        let mut literals = Vec::new();
+
+        for line in self.basic_lines()? {
+            let tokens_offset = line.tokens_offset();
+            let mut push = |kind, start: usize, end: usize| {
+                literals.push(BasicLiteral {
+                    kind,
+                    line_number: line.line_number,
+                    address: self.address_of(tokens_offset + start),
+                    bytes: &line.tokens[start..end],
+                });
+            };
+
+            let mut index = 0;
+            let mut in_data = false;
+            // The start of the current unquoted DATA item
+            let mut item_start: Option<usize> = None;
+
+            while index < line.tokens.len() {
+                let b = line.tokens[index];
+
+                if b == b'"' {
+                    // A missing closing quote ends the string at the
+                    // end of the line
+                    let start = index + 1;
+                    let end = line.tokens[start..]
+                        .iter()
+                        .position(|b| *b == b'"')
+                        .map_or(line.tokens.len(), |len| start + len);
+
+                    if in_data {
+                        push(LiteralKind::Data, start, end);
+                        item_start = None;
+                    } else {
+                        push(LiteralKind::String, start, end);
+                    }
+                    index = end + 1;
+                    continue;
+                }
+
+                if in_data {
+                    if b == b',' || b == b':' {
+                        if let Some(start) = item_start.take() {
+                            push(LiteralKind::Data, start, index);
+                        }
+                        in_data = b == b',';
+                        if in_data {
+                            item_start = Some(index + 1);
+                        }
+                    }
+                } else if b == DATA_TOKEN {
+                    in_data = true;
+                    item_start = Some(index + 1);
+                }
+                index += 1;
+            }
+
+            if let Some(start) = item_start {
+                push(LiteralKind::Data, start, line.tokens.len());
+            }
+        }
+
+        Ok(literals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cbm::prg::{LiteralKind, Prg};
+
+    /// Build a PRG at 0x0801 from lines of tokens
+    fn program(lines: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut bytes = vec![0x01, 0x08];
+        let mut address: u16 = 0x0801;
+        for (line_number, tokens) in lines {
+            address += 4 + tokens.len() as u16 + 1;
+            bytes.extend_from_slice(&address.to_le_bytes());
+            bytes.extend_from_slice(&line_number.to_le_bytes());
+            bytes.extend_from_slice(tokens);
+            bytes.push(0);
+        }
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    fn literal_text(prg: &Prg) -> Vec<(LiteralKind, u16, Vec<u8>)> {
+        prg.basic_literals()
+            .unwrap()
+            .iter()
+            .map(|l| (l.kind, l.line_number, l.bytes.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn prg_data_statements_work() {
+        // DATA "A,B",C:PRINT "D":DATA E
+        let bytes = program(&[(100, b"\x83\"A,B\",C:\x99\"D\":\x83E")]);
+        let prg = Prg::parse(&bytes).unwrap();
+
+        assert_eq!(
+            literal_text(&prg),
+            vec![
+                (LiteralKind::Data, 100, b"A,B".to_vec()),
+                (LiteralKind::Data, 100, b"C".to_vec()),
+                (LiteralKind::String, 100, b"D".to_vec()),
+                (LiteralKind::Data, 100, b"E".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn prg_unterminated_string_works() {
+        let bytes = program(&[(10, b"\x99\"HI"), (20, b"\x99\"THERE\";")]);
+        let prg = Prg::parse(&bytes).unwrap();
+
+        assert_eq!(
+            literal_text(&prg),
+            vec![
+                (LiteralKind::String, 10, b"HI".to_vec()),
+                (LiteralKind::String, 20, b"THERE".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn prg_addresses_work() {
+        let bytes = program(&[(10, b"\x99\"HI\""), (20, b"\x83 1")]);
+        let prg = Prg::parse(&bytes).unwrap();
+        let literals = prg.basic_literals().unwrap();
+
+        // The second line starts 10 bytes in, and the DATA item
+        // starts after its header and the DATA token
+        assert_eq!(literals[0].address, 0x0807);
+        assert_eq!(literals[1].address, 0x0801 + 10 + 4 + 1);
+        assert_eq!(literals[1].bytes, b" 1");
+        assert!(Prg::parse(&[0x01]).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//!
+//! CBM prg Studio source text
+//!
+//! CBM prg Studio keeps BASIC programs as plain text files, so the
+//! PETSCII codes that don't have an ASCII character are written as
+//! names in braces.  A string that clears the screen and prints a
+//! reversed heart looks like this:
+//!
+//! ```text
+//! 10 PRINT "{CLEAR}{REVERSE ON}{SHIFT-S}"
+//! ```
+//!
+//! Control codes use the names from the character set configuration,
+//! the same ones describe_code and diff show.  Graphics characters
+//! are written as the key combination that types them, {SHIFT-x} or
+//! {CBM-x}, and aliases of those codes are written as the code the
+//! keyboard types.  The pound sign, arrows and pi are written as the
+//! Unicode characters.
+//!
+//! Only the escapes listed here are read and written.  A control code
+//! without a name has no escape, and writing it is an error rather
+//! than a guess at syntax the IDE may not read.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    cbm::basic::{detokenize, BasicLine},
+    error::{Error, ErrorKind},
+    SystemConfig,
+};
+
+/// The graphics characters and the keys that type them
+///
+/// The codes are the ones the keyboard puts in a string.
+const KEY_ESCAPES: [(u8, &str); 64] = [
+    (0xA0, "SHIFT-SPACE"),
+    (0xA1, "CBM-K"),
+    (0xA2, "CBM-I"),
+    (0xA3, "CBM-T"),
+    (0xA4, "CBM-@"),
+    (0xA5, "CBM-G"),
+    (0xA6, "CBM-+"),
+    (0xA7, "CBM-M"),
+    (0xA8, "CBM-£"),
+    (0xA9, "SHIFT-£"),
+    (0xAA, "CBM-N"),
+    (0xAB, "CBM-Q"),
+    (0xAC, "CBM-D"),
+    (0xAD, "CBM-Z"),
+    (0xAE, "CBM-S"),
+    (0xAF, "CBM-P"),
+    (0xB0, "CBM-A"),
+    (0xB1, "CBM-E"),
+    (0xB2, "CBM-R"),
+    (0xB3, "CBM-W"),
+    (0xB4, "CBM-H"),
+    (0xB5, "CBM-J"),
+    (0xB6, "CBM-L"),
+    (0xB7, "CBM-Y"),
+    (0xB8, "CBM-U"),
+    (0xB9, "CBM-O"),
+    (0xBA, "SHIFT-@"),
+    (0xBB, "CBM-F"),
+    (0xBC, "CBM-C"),
+    (0xBD, "CBM-X"),
+    (0xBE, "CBM-V"),
+    (0xBF, "CBM-B"),
+    (0xC0, "SHIFT-*"),
+    (0xC1, "SHIFT-A"),
+    (0xC2, "SHIFT-B"),
+    (0xC3, "SHIFT-C"),
+    (0xC4, "SHIFT-D"),
+    (0xC5, "SHIFT-E"),
+    (0xC6, "SHIFT-F"),
+    (0xC7, "SHIFT-G"),
+    (0xC8, "SHIFT-H"),
+    (0xC9, "SHIFT-I"),
+    (0xCA, "SHIFT-J"),
+    (0xCB, "SHIFT-K"),
+    (0xCC, "SHIFT-L"),
+    (0xCD, "SHIFT-M"),
+    (0xCE, "SHIFT-N"),
+    (0xCF, "SHIFT-O"),
+    (0xD0, "SHIFT-P"),
+    (0xD1, "SHIFT-Q"),
+    (0xD2, "SHIFT-R"),
+    (0xD3, "SHIFT-S"),
+    (0xD4, "SHIFT-T"),
+    (0xD5, "SHIFT-U"),
+    (0xD6, "SHIFT-V"),
+    (0xD7, "SHIFT-W"),
+    (0xD8, "SHIFT-X"),
+    (0xD9, "SHIFT-Y"),
+    (0xDA, "SHIFT-Z"),
+    (0xDB, "SHIFT-+"),
+    (0xDC, "CBM--"),
+    (0xDD, "SHIFT--"),
+    (0xDE, "SHIFT-↑"),
+    (0xDF, "CBM-*"),
+];
+
+/// The characters written as themselves besides the ASCII ones
+const CHARACTERS: [(u8, char); 4] = [(0x5C, '£'), (0x5E, '↑'), (0x5F, '←'), (0xDE, 'π')];
+
+/// True for the codes that are control codes rather than characters
+fn is_control(code: u8) -> bool {
+    matches!(code, 0x00..=0x1F | 0x80..=0x9F)
+}
+
+/// The code the keyboard types for a graphics character
+fn keyboard_code(code: u8) -> u8 {
+    match code {
+        0x60..=0x7F => code + 0x60,
+        0xE0..=0xFE => code - 0x40,
+        0xFF => 0xDE,
+        _ => code,
+    }
+}
+
+/// Write PETSCII as CBM prg Studio text
+///
+/// Returns an error for a code without an escape.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     cbm::prg_studio::escape, petscii::PetsciiConfig, Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let text = escape(b"\x93\x12HI\xd3\xb0", &petscii).expect("Error escaping");
+/// assert_eq!(text, "{CLEAR}{REVERSE ON}HI{SHIFT-S}{CBM-A}");
+/// ```
+pub fn escape(bytes: &[u8], petscii: &SystemConfig) -> std::result::Result<String, Error> {
+    let map = &petscii.character_set_map;
+    let mut s = String::new();
+
+    for (offset, code) in bytes.iter().enumerate() {
+        let code = keyboard_code(*code);
+        if let Some((_, c)) = CHARACTERS.iter().find(|(b, _)| *b == code) {
+            s.push(*c);
+        } else if matches!(code, 0x20..=0x5D) {
+            s.push(char::from(code));
+        } else if let Some(name) = is_control(code)
+            .then(|| map.code_name(code, false))
+            .flatten()
+            .or_else(|| {
+                KEY_ESCAPES
+                    .iter()
+                    .find(|(b, _)| *b == code)
+                    .map(|(_, name)| *name)
+            })
+        {
+            s.push('{');
+            s.push_str(name);
+            s.push('}');
+        } else {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "PETSCII code {:#04x} at byte {} has no CBM prg Studio escape",
+                bytes[offset], offset
+            ))));
+        }
+    }
+
+    Ok(s)
+}
+
+/// Read CBM prg Studio text as PETSCII
+///
+/// Names in braces are matched without regard to case, and lowercase
+/// letters read as the uppercase ones, since the IDE's BASIC source
+/// is written in either.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     cbm::prg_studio::unescape, petscii::PetsciiConfig, Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let bytes = unescape("{clear}Hi{CBM-A}£", &petscii).expect("Error reading escapes");
+/// assert_eq!(bytes, b"\x93HI\xb0\x5c");
+///
+/// assert!(unescape("{NOT A KEY}", &petscii).is_err());
+/// ```
+pub fn unescape(s: &str, petscii: &SystemConfig) -> std::result::Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    let mut offset = 0;
+
+    while let Some(c) = s[offset..].chars().next() {
+        if c == '{' {
+            let end = s[offset..]
+                .find('}')
+                .map(|end| offset + end + 1)
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::Message(format!(
+                        "Unclosed escape at byte {offset}"
+                    )))
+                })?;
+            let escape = &s[offset..end];
+            let code = escape_code(&escape[1..escape.len() - 1], petscii).ok_or_else(|| {
+                Error::new(ErrorKind::Unencodable {
+                    grapheme: String::from(escape),
+                    offset,
+                })
+            })?;
+            bytes.push(code);
+            offset = end;
+            continue;
+        }
+
+        let code = match c {
+            ' '..='[' | ']' => c as u8,
+            'a'..='z' => c.to_ascii_uppercase() as u8,
+            _ => CHARACTERS
+                .iter()
+                .find(|(_, ch)| *ch == c)
+                .map(|(code, _)| *code)
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::Unencodable {
+                        grapheme: String::from(c),
+                        offset,
+                    })
+                })?,
+        };
+        bytes.push(code);
+        offset += c.len_utf8();
+    }
+
+    Ok(bytes)
+}
+
+/// The code for the name in an escape
+pub(crate) fn escape_code(name: &str, petscii: &SystemConfig) -> Option<u8> {
+    (0..=u8::MAX)
+        .filter(|code| is_control(*code))
+        .find(|code| {
+            petscii
+                .character_set_map
+                .code_name(*code, false)
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        })
+        .or_else(|| {
+            KEY_ESCAPES
+                .iter()
+                .find(|(_, n)| n.eq_ignore_ascii_case(name))
+                .map(|(code, _)| *code)
+        })
+}
+
+/// List a BASIC program as CBM prg Studio source
+///
+/// Each line is the line number and the detokenized text, with the
+/// codes that aren't ASCII written as escapes.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     cbm::{basic::basic_lines, prg_studio::listing},
+///     petscii::PetsciiConfig,
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// // 10 PRINT "{CLEAR}HI"
+/// let data = [
+///     0x0d, 0x08, 0x0a, 0x00, 0x99, 0x20, 0x22, 0x93, 0x48, 0x49, 0x22, 0x00, 0x00, 0x00,
+/// ];
+/// let lines = basic_lines(&data).expect("Error reading program");
+///
+/// assert_eq!(
+///     listing(&lines, &petscii).expect("Error escaping"),
+///     "10 PRINT \"{CLEAR}HI\"\n"
+/// );
+/// ```
+pub fn listing(
+    lines: &[BasicLine<'_>],
+    petscii: &SystemConfig,
+) -> std::result::Result<String, Error> {
+    let mut listing = String::new();
+
+    for line in lines {
+        listing.push_str(&format!("{} ", line.line_number));
+        listing.push_str(&escape(&detokenize(line.tokens), petscii)?);
+        listing.push('\n');
+    }
+
+    Ok(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cbm::prg_studio::{escape, unescape},
+        error::ErrorKind,
+        petscii::PetsciiConfig,
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn prg_studio_escapes_round_trip() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let bytes: Vec<u8> = (0x20..=0x5f)
+            .chain([0x05, 0x0d, 0x0e, 0x11, 0x1c, 0x8e, 0x90, 0x9f])
+            .chain(0xa0..=0xdf)
+            .collect();
+        let text = escape(&bytes, &petscii).unwrap();
+        assert_eq!(unescape(&text, &petscii).unwrap(), bytes);
+
+        // Aliases are written as the codes the keyboard types
+        assert_eq!(
+            escape(&[0x61, 0xe1, 0xff], &petscii).unwrap(),
+            "{SHIFT-A}{CBM-K}π"
+        );
+
+        // A code without a name has no escape
+        assert!(escape(&[0x41, 0x02], &petscii).is_err());
+
+        let error = unescape("AB{CBM-A}{WHAT}", &petscii).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::Unencodable { grapheme, offset: 9 } if grapheme == "{WHAT}"
+        ));
+        assert!(unescape("{CLEAR", &petscii).is_err());
+    }
+}
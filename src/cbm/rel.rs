@@ -0,0 +1,149 @@
+//!
+//! REL file records
+//!
+//! Relative files hold fixed-length records, from 1 to 254 bytes
+//! long, that can be read in any order.  The record length is stored
+//! in the directory entry, not in the file data.
+//!
+//! When the DOS creates a record it writes 0xFF followed by zeros, so
+//! records that have never been written start with 0xFF.  Records
+//! that have been written are padded with zeros after the data.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    cbm::{padded_string, seq::RECORD_TERMINATOR, trim_padding},
+    error::{Error, ErrorKind},
+    petscii::PetsciiString,
+    SystemConfig,
+};
+
+/// The longest record a relative file can have
+pub const MAX_RECORD_LEN: usize = 254;
+
+/// The byte at the start of a record that has never been written
+pub const EMPTY_RECORD: u8 = 0xFF;
+
+/// Strip the fill from a REL record
+///
+/// Records that have never been written are empty.  Otherwise the
+/// zero padding and the carriage return written by PRINT# are
+/// removed from the end.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::rel::strip_record_fill;
+///
+/// assert_eq!(strip_record_fill(&[0xFF, 0x00, 0x00, 0x00]), b"");
+/// assert_eq!(strip_record_fill(b"BOB\r\0\0\0"), b"BOB");
+/// ```
+pub fn strip_record_fill(record: &[u8]) -> &[u8] {
+    let record = trim_padding(record, &[0x00]);
+
+    if record == [EMPTY_RECORD] {
+        return &[];
+    }
+
+    record.strip_suffix(&[RECORD_TERMINATOR]).unwrap_or(record)
+}
+
+/// An iterator over the records in a REL file
+///
+/// Each record is returned as a PetsciiString with the fill stripped.
+/// L should be at least the record length.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::rel::RelRecords;
+///
+/// let data = b"ALICE\r\0\0BOB\r\0\0\0\0\xff\0\0\0\0\0\0\0";
+///
+/// let records: Vec<String> = RelRecords::<8>::new(data, 8, None)
+///     .expect("Invalid record length")
+///     .map(|record| String::from(record.expect("Record too long")))
+///     .collect();
+///
+/// assert_eq!(records, vec!["ALICE", "BOB", ""]);
+/// ```
+#[derive(Clone)]
+pub struct RelRecords<'a, 'b, const L: usize> {
+    records: std::slice::Chunks<'b, u8>,
+    character_map: Option<&'a SystemConfig>,
+}
+
+impl<'a, 'b, const L: usize> RelRecords<'a, 'b, L> {
+    /// Read the records in a file with the given record length
+    ///
+    /// Returns an error if the record length isn't between 1 and 254.
+    /// If the data isn't a whole number of records, the last record
+    /// is shorter than the others.
+    pub fn new(
+        data: &'b [u8],
+        record_len: usize,
+        character_map: Option<&'a SystemConfig>,
+    ) -> std::result::Result<Self, Error> {
+        if !(1..=MAX_RECORD_LEN).contains(&record_len) {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "Invalid REL record length {}",
+                record_len
+            ))));
+        }
+
+        Ok(RelRecords {
+            records: data.chunks(record_len),
+            character_map,
+        })
+    }
+}
+
+impl<'a, 'b, const L: usize> Iterator for RelRecords<'a, 'b, L> {
+    type Item = std::result::Result<PetsciiString<'a, L>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records
+            .next()
+            .map(|record| padded_string(strip_record_fill(record), &[], self.character_map))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.records.size_hint()
+    }
+}
+
+impl<'a, 'b, const L: usize> ExactSizeIterator for RelRecords<'a, 'b, L> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::cbm::rel::{strip_record_fill, RelRecords};
+
+    #[test]
+    fn rel_strip_record_fill_keeps_data() {
+        // A full record has no fill
+        assert_eq!(strip_record_fill(b"ABCD"), b"ABCD");
+        // Only a lone 0xFF marks an unused record
+        assert_eq!(strip_record_fill(&[0xFF, 0x41, 0x00]), &[0xFF, 0x41]);
+        assert_eq!(strip_record_fill(&[0x00, 0x00]), b"");
+    }
+
+    #[test]
+    fn rel_invalid_record_lengths_fail() {
+        assert!(RelRecords::<256>::new(b"", 0, None).is_err());
+        assert!(RelRecords::<256>::new(b"", 255, None).is_err());
+        assert!(RelRecords::<256>::new(b"", 254, None).is_ok());
+    }
+
+    #[test]
+    fn rel_records_short_last_record_works() {
+        let records = RelRecords::<4>::new(b"AB\0\0CD", 4, None).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let lengths: Vec<usize> = records.map(|record| record.unwrap().byte_len()).collect();
+        assert_eq!(lengths, vec![2, 2]);
+
+        // The string is too short for the records
+        let mut records = RelRecords::<2>::new(b"ABC\0", 4, None).unwrap();
+        assert!(records.next().unwrap().is_err());
+    }
+}
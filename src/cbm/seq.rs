@@ -0,0 +1,130 @@
+//!
+//! SEQ and USR file records
+//!
+//! Sequential files written with PRINT# are a stream of records, each
+//! ended by a carriage return (0x0D).  The last record may not have a
+//! carriage return, and files copied out of disk images sometimes
+//! have padding after the last record, filling the rest of the last
+//! sector.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    cbm::{padded_string, trim_padding},
+    error::Error,
+    petscii::PetsciiString,
+    SystemConfig,
+};
+
+/// The byte that ends a record
+pub const RECORD_TERMINATOR: u8 = 0x0D;
+
+/// An iterator over the records in a SEQ or USR file
+///
+/// Each record is returned as a PetsciiString without its carriage
+/// return.  Records longer than L bytes are returned as errors, and
+/// reading continues with the next record.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::seq::SeqRecords;
+///
+/// let data = b"HIGH SCORES\r1000\r500\r\0\0\0";
+///
+/// let records: Vec<String> = SeqRecords::<16>::with_padding(data, &[0x00], None)
+///     .map(|record| String::from(record.expect("Record too long")))
+///     .collect();
+///
+/// assert_eq!(records, vec!["HIGH SCORES", "1000", "500"]);
+/// ```
+#[derive(Clone)]
+pub struct SeqRecords<'a, 'b, const L: usize> {
+    data: &'b [u8],
+    character_map: Option<&'a SystemConfig>,
+}
+
+impl<'a, 'b, const L: usize> SeqRecords<'a, 'b, L> {
+    /// Read the records in a file
+    pub fn new(data: &'b [u8], character_map: Option<&'a SystemConfig>) -> Self {
+        SeqRecords {
+            data,
+            character_map,
+        }
+    }
+
+    /// Read the records in a file, ignoring padding at the end of the
+    /// file
+    ///
+    /// Any of the bytes in padding are removed from the end of the
+    /// file before it's split into records.
+    pub fn with_padding(
+        data: &'b [u8],
+        padding: &[u8],
+        character_map: Option<&'a SystemConfig>,
+    ) -> Self {
+        SeqRecords::new(trim_padding(data, padding), character_map)
+    }
+
+    /// The data that hasn't been read yet
+    pub fn remainder(&self) -> &'b [u8] {
+        self.data
+    }
+}
+
+impl<'a, 'b, const L: usize> Iterator for SeqRecords<'a, 'b, L> {
+    type Item = std::result::Result<PetsciiString<'a, L>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let (record, rest) = match self.data.iter().position(|b| *b == RECORD_TERMINATOR) {
+            Some(len) => (&self.data[..len], &self.data[len + 1..]),
+            None => (self.data, &self.data[self.data.len()..]),
+        };
+        self.data = rest;
+
+        Some(padded_string(record, &[], self.character_map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cbm::seq::SeqRecords, petscii::PetsciiConfig, Configuration, SystemConfig};
+
+    #[test]
+    fn seq_records_empty_records_work() {
+        let records: Vec<usize> = SeqRecords::<8>::new(b"A\r\rB", None)
+            .map(|record| record.unwrap().byte_len())
+            .collect();
+
+        assert_eq!(records, vec![1, 0, 1]);
+        assert_eq!(SeqRecords::<8>::new(b"", None).count(), 0);
+    }
+
+    #[test]
+    fn seq_records_long_records_fail() {
+        let mut records = SeqRecords::<4>::new(b"TOO LONG\rOK\r", None);
+
+        assert!(records.next().unwrap().is_err());
+        assert_eq!(records.remainder(), b"OK\r");
+        assert_eq!(records.next().unwrap().unwrap().byte_len(), 2);
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn seq_records_with_config_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Shifted spaces pad the last sector
+        let data = [0x0e, 0x48, 0x49, 0x8e, 0x0d, 0x5c, 0x31, 0xa0, 0xa0];
+        let records: Vec<String> = SeqRecords::<8>::with_padding(&data, &[0xa0], Some(&petscii))
+            .map(|record| String::from(record.unwrap()))
+            .collect();
+
+        assert_eq!(records, vec!["hi", "£1"]);
+    }
+}
@@ -0,0 +1,132 @@
+//!
+//! DOS error channel status
+//!
+//! Reading the command channel of a drive, secondary address 15,
+//! returns the status of the last command as a line of PETSCII:
+//!
+//! ```text
+//! 00, OK,00,00
+//! 62,FILE NOT FOUND,00,00
+//! 23,READ ERROR,18,04
+//! ```
+//!
+//! The line has the error number, the message, and the track and
+//! sector the error happened on, ended by a carriage return.  Numbers
+//! below 20 aren't errors: 00 is OK, 01 counts the files a scratch
+//! command deleted and 73 is the DOS version after a reset.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    cbm::padded_string,
+    error::{Error, ErrorKind},
+    SystemConfig,
+};
+
+/// The longest status line read
+///
+/// The 1541 messages are at most 36 bytes, drives with longer version
+/// strings fit too.
+pub const STATUS_LEN: usize = 64;
+
+/// A status read from the error channel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DosStatus {
+    /// The error number
+    pub code: u8,
+    /// The message, like "FILE NOT FOUND"
+    pub message: String,
+    /// The track the error happened on
+    pub track: u8,
+    /// The sector the error happened on
+    pub sector: u8,
+}
+
+impl DosStatus {
+    /// Parse a status line read from the error channel
+    ///
+    /// A carriage return at the end is ignored, and so are spaces
+    /// around the fields, like the one before "OK".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{cbm::status::DosStatus, petscii::PetsciiConfig, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let status = DosStatus::parse(b"23,READ ERROR,18,04\r", Some(&petscii)).expect("Error parsing status");
+    ///
+    /// assert_eq!(status.code, 23);
+    /// assert_eq!(status.message, "READ ERROR");
+    /// assert_eq!((status.track, status.sector), (18, 4));
+    /// assert!(status.is_error());
+    /// ```
+    pub fn parse(
+        bytes: &[u8],
+        character_map: Option<&SystemConfig>,
+    ) -> std::result::Result<DosStatus, Error> {
+        let line = String::from(padded_string::<STATUS_LEN>(bytes, &[0x0D], character_map)?);
+        let invalid = || Error::new(ErrorKind::Message(format!("\"{line}\" isn't a DOS status")));
+        let number = |field: &str| field.trim().parse::<u8>().map_err(|_| invalid());
+
+        // The message is between the first comma and the last two, so
+        // a message with a comma in it is still read
+        let (code, rest) = line.split_once(',').ok_or_else(invalid)?;
+        let mut fields = rest.rsplitn(3, ',');
+        let (Some(sector), Some(track), Some(message)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+
+        Ok(DosStatus {
+            code: number(code)?,
+            message: String::from(message.trim()),
+            track: number(track)?,
+            sector: number(sector)?,
+        })
+    }
+
+    /// True if the status is an error
+    ///
+    /// Codes below 20 report success, and 73 is the power-on message
+    /// with the DOS version.
+    pub fn is_error(&self) -> bool {
+        self.code >= 20 && self.code != 73
+    }
+}
+
+impl Display for DosStatus {
+    /// The status as a status line, without the carriage return or
+    /// the space the 1541 puts before "OK"
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:02},{},{:02},{:02}",
+            self.code, self.message, self.track, self.sector
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cbm::status::DosStatus;
+
+    #[test]
+    fn status_parses_drive_messages() {
+        let status = DosStatus::parse(b"00, OK,00,00\r", None).unwrap();
+        assert_eq!(status.message, "OK");
+        assert!(!status.is_error());
+
+        let status = DosStatus::parse(b"73,CBM DOS V2.6 1541,00,00\r", None).unwrap();
+        assert_eq!(status.to_string(), "73,CBM DOS V2.6 1541,00,00");
+        assert!(!status.is_error());
+
+        assert!(DosStatus::parse(b"62,FILE NOT FOUND\r", None).is_err());
+        assert!(DosStatus::parse(b"XX,OK,00,00", None).is_err());
+    }
+}
@@ -0,0 +1,322 @@
+//!
+//! Tape archive filenames
+//!
+//! Tape files are stored in two common container formats.  TAP files
+//! hold the raw pulses recorded from a Datasette, and each file on
+//! the tape starts with a header block.  T64 files are an archive
+//! format with a directory of entries instead.
+//!
+//! The KERNAL pads tape filenames with spaces, not the shifted spaces
+//! used on disk.  Some tools that create T64 files pad directory
+//! entries with shifted spaces anyway, so both are removed from T64
+//! names.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    cbm::{check_len, padded_string, read_u16, DISK_PADDING, TAPE_PADDING},
+    error::{Error, ErrorKind},
+    petscii::PetsciiString,
+    SystemConfig,
+};
+
+/// The length of a tape filename
+pub const FILENAME_LEN: usize = 16;
+
+/// The length of the tape name in a T64 header
+pub const T64_TAPE_NAME_LEN: usize = 24;
+
+/// The length of a T64 header
+pub const T64_HEADER_LEN: usize = 64;
+
+/// The length of a T64 directory entry
+pub const T64_ENTRY_LEN: usize = 32;
+
+/// The length of the data in a tape header block, without the
+/// checksum
+pub const TAPE_HEADER_LEN: usize = 192;
+
+/// The kind of tape header block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TapeHeaderType {
+    /// A BASIC program, loaded at the start of BASIC memory
+    RelocatableProgram,
+    /// A block of data in a sequential file
+    DataBlock,
+    /// A machine language program, loaded at its start address
+    Program,
+    /// The header of a sequential file
+    SeqHeader,
+    /// The end of the tape
+    EndOfTape,
+    /// Some other header type
+    Unknown(u8),
+}
+
+impl From<u8> for TapeHeaderType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => TapeHeaderType::RelocatableProgram,
+            2 => TapeHeaderType::DataBlock,
+            3 => TapeHeaderType::Program,
+            4 => TapeHeaderType::SeqHeader,
+            5 => TapeHeaderType::EndOfTape,
+            _ => TapeHeaderType::Unknown(value),
+        }
+    }
+}
+
+/// A header block from a tape
+#[derive(Clone, Copy, Debug)]
+pub struct TapeHeader<'a> {
+    /// The kind of header
+    pub header_type: TapeHeaderType,
+    /// The address the file is loaded at
+    pub start_address: u16,
+    /// The address after the end of the file
+    pub end_address: u16,
+    /// The filename, without the padding
+    pub filename: PetsciiString<'a, FILENAME_LEN>,
+}
+
+impl<'a> TapeHeader<'a> {
+    /// Parse a header block from a tape
+    ///
+    /// The block is the data after the sync bytes, as loaded into the
+    /// tape buffer.  Only the first 21 bytes are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::cbm::tape::{TapeHeader, TapeHeaderType};
+    ///
+    /// let mut block = [0x20; 192];
+    /// block[..5].copy_from_slice(&[0x03, 0x01, 0x08, 0x00, 0x10]);
+    /// block[5..9].copy_from_slice(b"GAME");
+    ///
+    /// let header = TapeHeader::parse(&block, None).expect("Error parsing header");
+    ///
+    /// assert_eq!(header.header_type, TapeHeaderType::Program);
+    /// assert_eq!(header.start_address, 0x0801);
+    /// assert_eq!(String::from(header.filename), "GAME");
+    /// ```
+    pub fn parse(
+        block: &[u8],
+        character_map: Option<&'a SystemConfig>,
+    ) -> std::result::Result<TapeHeader<'a>, Error> {
+        check_len(block, 5 + FILENAME_LEN, "Tape header")?;
+
+        Ok(TapeHeader {
+            header_type: TapeHeaderType::from(block[0]),
+            start_address: read_u16(block, 1),
+            end_address: read_u16(block, 3),
+            filename: padded_string(&block[5..5 + FILENAME_LEN], &[TAPE_PADDING], character_map)?,
+        })
+    }
+}
+
+/// The header of a T64 archive
+#[derive(Clone, Copy, Debug)]
+pub struct T64Header<'a> {
+    /// The T64 format version, usually 0x0100 or 0x0101
+    pub version: u16,
+    /// The number of directory entries
+    pub max_entries: u16,
+    /// The number of directory entries in use
+    pub used_entries: u16,
+    /// The name of the tape, without the padding
+    pub tape_name: PetsciiString<'a, T64_TAPE_NAME_LEN>,
+}
+
+impl<'a> T64Header<'a> {
+    /// Parse the header at the start of a T64 archive
+    pub fn parse(
+        data: &[u8],
+        character_map: Option<&'a SystemConfig>,
+    ) -> std::result::Result<T64Header<'a>, Error> {
+        check_len(data, T64_HEADER_LEN, "T64 header")?;
+
+        if !data.starts_with(b"C64") {
+            return Err(Error::new(ErrorKind::Message(String::from(
+                "Missing T64 signature",
+            ))));
+        }
+
+        Ok(T64Header {
+            version: read_u16(data, 0x20),
+            max_entries: read_u16(data, 0x22),
+            used_entries: read_u16(data, 0x24),
+            tape_name: padded_string(
+                &data[0x28..0x28 + T64_TAPE_NAME_LEN],
+                &[TAPE_PADDING, DISK_PADDING],
+                character_map,
+            )?,
+        })
+    }
+}
+
+/// A directory entry in a T64 archive
+#[derive(Clone, Copy, Debug)]
+pub struct T64Entry<'a> {
+    /// The entry type, 0 for a free entry and 1 for a normal tape file
+    pub entry_type: u8,
+    /// The file type, in the same format as a disk directory
+    pub file_type: u8,
+    /// The address the file is loaded at
+    pub start_address: u16,
+    /// The address after the end of the file
+    pub end_address: u16,
+    /// The offset of the file data in the archive
+    pub offset: u32,
+    /// The filename, without the padding
+    pub filename: PetsciiString<'a, FILENAME_LEN>,
+}
+
+impl<'a> T64Entry<'a> {
+    /// Parse a T64 directory entry
+    pub fn parse(
+        entry: &[u8],
+        character_map: Option<&'a SystemConfig>,
+    ) -> std::result::Result<T64Entry<'a>, Error> {
+        check_len(entry, T64_ENTRY_LEN, "T64 directory entry")?;
+
+        Ok(T64Entry {
+            entry_type: entry[0],
+            file_type: entry[1],
+            start_address: read_u16(entry, 2),
+            end_address: read_u16(entry, 4),
+            offset: u32::from_le_bytes([entry[8], entry[9], entry[10], entry[11]]),
+            filename: padded_string(
+                &entry[0x10..0x10 + FILENAME_LEN],
+                &[TAPE_PADDING, DISK_PADDING],
+                character_map,
+            )?,
+        })
+    }
+
+    /// True if the entry isn't in use
+    pub fn is_free(&self) -> bool {
+        self.entry_type == 0
+    }
+}
+
+/// Read the directory entries of a T64 archive that are in use
+///
+/// Many T64 files declare more directory slots than they contain, so
+/// only the slots that fit in the data are read.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::tape::t64_entries;
+///
+/// let mut data = vec![0; 96];
+/// data[..3].copy_from_slice(b"C64");
+/// data[0x22] = 1;
+/// data[0x24] = 1;
+/// data[0x40] = 1;
+/// data[0x50..0x60].copy_from_slice(b"DEMO            ");
+///
+/// let entries = t64_entries(&data, None).expect("Error reading T64");
+///
+/// assert_eq!(entries.len(), 1);
+/// assert_eq!(String::from(entries[0].filename), "DEMO");
+/// ```
+pub fn t64_entries<'a>(
+    data: &[u8],
+    character_map: Option<&'a SystemConfig>,
+) -> std::result::Result<Vec<T64Entry<'a>>, Error> {
+    let header = T64Header::parse(data, character_map)?;
+
+    let slots = data.len().saturating_sub(T64_HEADER_LEN) / T64_ENTRY_LEN;
+
+    let mut entries = Vec::new();
+    for index in 0..usize::from(header.max_entries).min(slots) {
+        let start = T64_HEADER_LEN + index * T64_ENTRY_LEN;
+        let entry = T64Entry::parse(&data[start..], character_map)?;
+        if !entry.is_free() {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cbm::tape::{t64_entries, T64Header, TapeHeader, TapeHeaderType},
+        petscii::PetsciiConfig,
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn tape_header_keeps_shifted_spaces() {
+        let mut block = [0x20; 21];
+        block[0] = 4;
+        // A shifted space is part of a tape filename, not padding
+        block[5..9].copy_from_slice(&[0x44, 0x41, 0xA0, 0x54]);
+
+        let header = TapeHeader::parse(&block, None).unwrap();
+
+        assert_eq!(header.header_type, TapeHeaderType::SeqHeader);
+        assert_eq!(header.filename.byte_len(), 4);
+        assert!(TapeHeader::parse(&block[..20], None).is_err());
+    }
+
+    #[test]
+    fn t64_header_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut data = [0; 64];
+        data[..19].copy_from_slice(b"C64S tape image fil");
+        data[0x20] = 0x01;
+        data[0x21] = 0x01;
+        data[0x22] = 30;
+        data[0x28..0x40].copy_from_slice(
+            b"MY TAPE\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0",
+        );
+
+        let header = T64Header::parse(&data, Some(&petscii)).unwrap();
+
+        assert_eq!(header.version, 0x0101);
+        assert_eq!(header.max_entries, 30);
+        assert_eq!(String::from(header.tape_name), "MY TAPE");
+
+        data[0] = b'X';
+        assert!(T64Header::parse(&data, None).is_err());
+    }
+
+    #[test]
+    fn t64_entries_skip_free_entries() {
+        let mut data = vec![0; 64 + 3 * 32];
+        data[..3].copy_from_slice(b"C64");
+        data[0x22] = 3;
+        for (index, name) in [(0, b"ONE "), (2, b"TWO ")] {
+            let start = 64 + index * 32;
+            data[start] = 1;
+            data[start + 1] = 0x82;
+            data[start + 2..start + 4].copy_from_slice(&[0x01, 0x08]);
+            data[start + 8] = 0x80;
+            data[start + 0x10..start + 0x14].copy_from_slice(name);
+            data[start + 0x14..start + 0x20].fill(0x20);
+        }
+
+        let entries = t64_entries(&data, None).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(String::from(entries[1].filename), "TWO");
+        assert_eq!(entries[1].start_address, 0x0801);
+        assert_eq!(entries[1].offset, 0x80);
+
+        // Slots past the end of the data aren't read
+        let entries = t64_entries(&data[..100], None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(String::from(entries[0].filename), "ONE");
+
+        data[0x22] = 30;
+        assert_eq!(t64_entries(&data, None).unwrap().len(), 2);
+        assert!(t64_entries(&data[..64], None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,435 @@
+//!
+//! Building CBM DOS structures
+//!
+//! These builders encode Unicode strings into the padded fixed-length
+//! fields used in disk images and tape headers, and into BASIC string
+//! literals.  They're used to write corrected names and text back
+//! into images.
+//!
+//! Encoding a string into PETSCII turns spaces into shifted spaces
+//! (0xA0).  That's the padding byte on disk, so when a character set
+//! decodes 0x20 to a space too, spaces are written as 0x20 instead.
+//! The lossless-escape configuration decodes the two codes to
+//! different characters, so a shifted space read from a name is
+//! written back as one.
+//!
+//! A directory is shown in a single character set, so name fields
+//! can't have shift codes.  Names without lowercase letters are
+//! written in the uppercase and graphics character set, and names
+//! with lowercase letters in the lowercase and uppercase set, where
+//! uppercase letters are 0xC1 to 0xDA.  A name that needs both, like
+//! one with a lowercase letter and a graphics character, is an
+//! error.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    cbm::{DISK_PADDING, TAPE_PADDING},
+    error::{Error, ErrorKind},
+    petscii::PetsciiString,
+    CharacterSet, SystemConfig,
+};
+
+/// The length of a filename or disk name field
+pub const NAME_LEN: usize = 16;
+
+/// The length of the disk header fields in a D64 BAM sector, from the
+/// disk name at 0x90 to the end of the DOS type padding at 0xAA
+pub const DISK_HEADER_LEN: usize = 27;
+
+/// Encode a string, failing on the first character that can't be
+/// encoded
+fn encode_text(s: &str, charset: &SystemConfig) -> std::result::Result<Vec<u8>, Error> {
+    if let Some((offset, c)) = s
+        .char_indices()
+        .find(|(_, c)| charset.encode(c.encode_utf8(&mut [0; 4])).is_empty())
+    {
+        return Err(Error::new(ErrorKind::Unencodable {
+            grapheme: c.to_string(),
+            offset,
+        }));
+    }
+
+    if charset.decode(&[0x20]) != " " {
+        return Ok(charset.encode(s));
+    }
+
+    // The encoder ends every string in the uppercase character set,
+    // so the parts between spaces can be encoded on their own
+    let mut bytes = Vec::with_capacity(s.len());
+    for (i, part) in s.split(' ').enumerate() {
+        if i > 0 {
+            bytes.push(0x20);
+        }
+        bytes.extend(charset.encode(part));
+    }
+    Ok(bytes)
+}
+
+/// Encode a name the way it's shown in a directory, in a single
+/// character set without shift codes
+///
+/// Returns an error if the name needs both character sets.
+pub(crate) fn encode_name(
+    name: &str,
+    charset: &SystemConfig,
+) -> std::result::Result<Vec<u8>, Error> {
+    let bytes = encode_text(name, charset)?;
+    if !bytes.iter().any(|b| *b == 0x0E || *b == 0x8E) {
+        return Ok(bytes);
+    }
+
+    // Move the codes outside the shifted runs to the lowercase
+    // character set
+    let mut shifted = false;
+    let mut name_bytes = Vec::with_capacity(bytes.len());
+    for code in bytes {
+        match code {
+            0x0E => shifted = true,
+            0x8E => shifted = false,
+            code if shifted => name_bytes.push(code),
+            code => {
+                // Codes that print the same in both sets are kept,
+                // and the uppercase letters are 0xC1 to 0xDA rather
+                // than their duplicates at 0x61 to 0x7A
+                let c = charset.decode(&[code]);
+                let shifted_code = std::iter::once(code)
+                    .chain(0x80..=0xFF)
+                    .chain(0x00..=0x7F)
+                    .find(|shifted_code| charset.decode(&[0x0E, *shifted_code]) == c)
+                    .ok_or_else(|| {
+                        Error::new(ErrorKind::Message(format!(
+                            "{name} needs both character sets, which a name can't switch between"
+                        )))
+                    })?;
+                name_bytes.push(shifted_code);
+            }
+        }
+    }
+    Ok(name_bytes)
+}
+
+/// Copy bytes into a padded fixed-length field
+///
+/// Returns an error if the bytes don't fit in the field.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::cbm::{write::pad_field, DISK_PADDING};
+///
+/// let field: [u8; 4] = pad_field(b"AB", DISK_PADDING).expect("Name too long");
+///
+/// assert_eq!(field, [0x41, 0x42, 0xA0, 0xA0]);
+/// assert!(pad_field::<1>(b"AB", DISK_PADDING).is_err());
+/// ```
+pub fn pad_field<const L: usize>(bytes: &[u8], padding: u8) -> std::result::Result<[u8; L], Error> {
+    if bytes.len() > L {
+        return Err(Error::new(ErrorKind::Message(format!(
+            "{} bytes don't fit in a field of {} bytes",
+            bytes.len(),
+            L
+        ))));
+    }
+
+    let mut field = [padding; L];
+    field[..bytes.len()].copy_from_slice(bytes);
+    Ok(field)
+}
+
+/// Encode a name into a field
+fn name_field(
+    name: &str,
+    padding: u8,
+    charset: &SystemConfig,
+) -> std::result::Result<[u8; NAME_LEN], Error> {
+    pad_field(&encode_name(name, charset)?, padding)
+}
+
+/// Encode a filename for a disk directory entry
+///
+/// The name is padded with shifted spaces.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     cbm::write::disk_filename_field, petscii::PetsciiConfig, Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let field = disk_filename_field("MY GAME", &petscii).expect("Error encoding name");
+///
+/// assert_eq!(&field[..8], b"MY GAME\xa0");
+/// ```
+pub fn disk_filename_field(
+    name: &str,
+    charset: &SystemConfig,
+) -> std::result::Result<[u8; NAME_LEN], Error> {
+    name_field(name, DISK_PADDING, charset)
+}
+
+/// Encode a filename for a tape header or T64 directory entry
+///
+/// The name is padded with spaces.
+pub fn tape_filename_field(
+    name: &str,
+    charset: &SystemConfig,
+) -> std::result::Result<[u8; NAME_LEN], Error> {
+    name_field(name, TAPE_PADDING, charset)
+}
+
+/// Write back a name that was read with its shifted space padding
+/// stripped
+///
+/// original is the name as it was read, with
+/// PetsciiString::from_byte_slice_strip_shifted_space_with_config.
+/// Decoding strips every shifted space, not only the padding, and
+/// several PETSCII codes decode to the same character, so the bytes
+/// can't be recovered from the decoded name.  If name is the same as
+/// the decoded original, the original bytes are written back byte
+/// for byte.  An edited name is encoded like disk_filename_field and
+/// padded with shifted spaces.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     cbm::write::restore_padded_name, petscii::{PetsciiConfig, PetsciiString},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let field = *b"GA\xa0ME\xa0\xa0\xa0";
+/// let name = PetsciiString::<8>::from_byte_slice_strip_shifted_space_with_config(&field, &petscii);
+///
+/// let restored = restore_padded_name(&name.to_string(), &name, &petscii)
+///     .expect("Error encoding name");
+/// assert_eq!(restored, field);
+///
+/// let edited = restore_padded_name("GAME 2", &name, &petscii).expect("Error encoding name");
+/// assert_eq!(&edited, b"GAME 2\xa0\xa0");
+/// ```
+pub fn restore_padded_name<const L: usize>(
+    name: &str,
+    original: &PetsciiString<'_, L>,
+    charset: &SystemConfig,
+) -> std::result::Result<[u8; L], Error> {
+    if name == original.to_string() {
+        return pad_field(original.as_ref(), DISK_PADDING);
+    }
+
+    pad_field(&encode_name(name, charset)?, DISK_PADDING)
+}
+
+/// The disk name and ID fields of a disk header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiskHeader {
+    /// The disk name, up to 16 characters
+    pub name: String,
+    /// The two character disk ID
+    pub id: String,
+    /// The two character DOS type, "2A" on 1541 disks
+    pub dos_type: String,
+}
+
+impl DiskHeader {
+    /// Create a 1541 disk header
+    pub fn new(name: &str, id: &str) -> DiskHeader {
+        DiskHeader {
+            name: String::from(name),
+            id: String::from(id),
+            dos_type: String::from("2A"),
+        }
+    }
+
+    /// Encode the header into the layout of a D64 BAM sector
+    ///
+    /// The bytes go at offset 0x90 in the BAM sector: the padded disk
+    /// name, two shifted spaces, the disk ID, a shifted space, the DOS
+    /// type and four shifted spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     cbm::write::DiskHeader, petscii::PetsciiConfig, Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let header = DiskHeader::new("DEMOS", "01");
+    /// let bytes = header.to_bytes(&petscii).expect("Error encoding header");
+    ///
+    /// assert_eq!(&bytes[..6], b"DEMOS\xa0");
+    /// assert_eq!(&bytes[0x12..0x17], b"01\xa02A");
+    /// ```
+    pub fn to_bytes(
+        &self,
+        charset: &SystemConfig,
+    ) -> std::result::Result<[u8; DISK_HEADER_LEN], Error> {
+        let name = disk_filename_field(&self.name, charset)?;
+        let id: [u8; 2] = pad_field(&encode_text(&self.id, charset)?, DISK_PADDING)?;
+        let dos_type: [u8; 2] = pad_field(&encode_text(&self.dos_type, charset)?, DISK_PADDING)?;
+
+        let mut bytes = [DISK_PADDING; DISK_HEADER_LEN];
+        bytes[..NAME_LEN].copy_from_slice(&name);
+        bytes[0x12..0x14].copy_from_slice(&id);
+        bytes[0x15..0x17].copy_from_slice(&dos_type);
+        Ok(bytes)
+    }
+}
+
+/// Encode a BASIC string literal, with the quotes
+///
+/// Shift codes are kept, since printing them switches the character
+/// set.  BASIC doesn't have a way to escape a quote inside a string,
+/// so strings with quotes are an error.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     cbm::write::basic_string_literal, petscii::PetsciiConfig, Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let literal = basic_string_literal("HI bob", &petscii).expect("Error encoding string");
+///
+/// assert_eq!(literal, b"\"HI \x0e\x42\x4f\x42\x8e\"");
+/// ```
+pub fn basic_string_literal(
+    s: &str,
+    charset: &SystemConfig,
+) -> std::result::Result<Vec<u8>, Error> {
+    if let Some(offset) = s.find('"') {
+        return Err(Error::new(ErrorKind::Unencodable {
+            grapheme: String::from("\""),
+            offset,
+        }));
+    }
+
+    let mut literal = vec![b'"'];
+    literal.extend(encode_text(s, charset)?);
+    literal.push(b'"');
+    Ok(literal)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cbm::{
+            tape::TapeHeader,
+            write::{
+                basic_string_literal, disk_filename_field, restore_padded_name, tape_filename_field,
+            },
+        },
+        error::ErrorKind,
+        petscii::{PetsciiConfig, PetsciiString},
+        CharacterSet, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn write_tape_filename_round_trip_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut block = [0x03, 0x01, 0x08, 0x00, 0x10].to_vec();
+        block.extend(tape_filename_field("SPACE GAME", &petscii).unwrap());
+
+        let header = TapeHeader::parse(&block, Some(&petscii)).unwrap();
+        assert_eq!(String::from(header.filename), "SPACE GAME");
+    }
+
+    #[test]
+    fn write_filename_errors_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        assert!(disk_filename_field("A NAME THAT IS TOO LONG", &petscii).is_err());
+
+        let error = disk_filename_field("CAFÉ", &petscii).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::Unencodable { grapheme, offset: 3 } if grapheme == "É"
+        ));
+    }
+
+    #[test]
+    fn write_basic_string_literal_quotes_fail() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        assert!(basic_string_literal("SAY \"HI\"", &petscii).is_err());
+        assert_eq!(basic_string_literal("", &petscii).unwrap(), b"\"\"");
+    }
+
+    #[test]
+    fn write_restore_padded_name_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let field = *b"MY GAME\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0";
+        let name =
+            PetsciiString::<16>::from_byte_slice_strip_shifted_space_with_config(&field, &petscii);
+        assert_eq!(name.padding_len(), 9);
+
+        let restored: [u8; 16] = restore_padded_name(&String::from(name), &name, &petscii).unwrap();
+        assert_eq!(restored, field);
+
+        // An edited name is encoded and padded again
+        let edited = restore_padded_name("MY GAME 2", &name, &petscii).unwrap();
+        assert_eq!(&edited, b"MY GAME 2\xa0\xa0\xa0\xa0\xa0\xa0\xa0");
+        assert!(restore_padded_name("A NAME THAT IS TOO LONG", &name, &petscii).is_err());
+
+        // A mixed case name is written in the lowercase character set
+        // and shows the same in it
+        let empty =
+            PetsciiString::<8>::from_byte_slice_strip_shifted_space_with_config(b"", &petscii);
+        let restored = restore_padded_name("Game", &empty, &petscii).unwrap();
+        assert_eq!(restored, *b"\xc7AME\xa0\xa0\xa0\xa0");
+        assert_eq!(
+            petscii.decode(&[&[0x0e][..], &restored[..4]].concat()),
+            "Game"
+        );
+
+        // A lowercase letter and a heart need both character sets
+        assert!(restore_padded_name("\u{2665}a", &empty, &petscii).is_err());
+    }
+
+    #[test]
+    fn write_restore_padded_name_keeps_unchanged_bytes() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Shifted spaces inside the name are stripped when decoding too
+        let field = *b"A\xa0B\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0";
+        let name =
+            PetsciiString::<16>::from_byte_slice_strip_shifted_space_with_config(&field, &petscii);
+        assert_eq!(
+            restore_padded_name(&name.to_string(), &name, &petscii).unwrap(),
+            field
+        );
+
+        // Codes that duplicate other codes are written back as they were
+        for code in (0x20..=0x7f).chain(0xa0..=0xff) {
+            let mut field = [0xa0; 16];
+            field[0] = code;
+            let name = PetsciiString::<16>::from_byte_slice_strip_shifted_space_with_config(
+                &field, &petscii,
+            );
+            assert_eq!(
+                restore_padded_name(&name.to_string(), &name, &petscii).unwrap(),
+                field,
+                "{code:#04x}"
+            );
+        }
+    }
+}
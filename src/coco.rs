@@ -0,0 +1,521 @@
+//!
+//! Tandy Color Computer (CoCo) string library
+//!
+//! The CoCo uses the Motorola 6847 Video Display Generator (VDG) for
+//! its text screen.  The VDG has a built-in set of 64 characters:
+//! uppercase letters, digits, punctuation and up and left arrows.
+//!
+//! Each byte in screen memory is either an alphanumeric character or
+//! a semigraphics character:
+//!
+//! Bit 7 clear: an alphanumeric character.  Bits 0-5 select the
+//! character and bit 6 selects normal or inverse video.  Values
+//! 0x00-0x3F are inverse and 0x40-0x7F are normal.  Color BASIC uses
+//! inverse uppercase characters to show lowercase letters, because
+//! the VDG doesn't have any.
+//!
+//! Bit 7 set: a semigraphics character.  In semigraphics-4 mode bits
+//! 0-3 turn on the four quadrants of the character cell and bits 4-6
+//! select the color.  In semigraphics-6 mode bits 0-5 turn on six
+//! elements (two columns of three rows) and bits 6-7 select the color.
+//!
+//! The semigraphics characters map to the Unicode quadrant block
+//! elements and the sextant characters in Symbols for Legacy
+//! Computing.
+//!
+//! Text files saved to cassette (CAS) are stored as ASCII.  The
+//! caret and underscore characters show up as the VDG up and left
+//! arrows.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    config_data,
+    error::{Error, ErrorKind},
+    load_cached, lookup_char, CharacterSet, SystemModule,
+};
+
+/// The number of columns on the CoCo text screen
+pub const SCREEN_COLUMNS: usize = 32;
+
+/// The cassette block type for the filename block
+pub const CAS_NAMEFILE_BLOCK: u8 = 0x00;
+/// The cassette block type for data blocks
+pub const CAS_DATA_BLOCK: u8 = 0x01;
+/// The cassette block type for the end of file block
+pub const CAS_EOF_BLOCK: u8 = 0xFF;
+
+/// The eight colors the VDG can show in semigraphics modes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VdgColor {
+    /// Green
+    Green,
+    /// Yellow
+    Yellow,
+    /// Blue
+    Blue,
+    /// Red
+    Red,
+    /// Buff
+    Buff,
+    /// Cyan
+    Cyan,
+    /// Magenta
+    Magenta,
+    /// Orange
+    Orange,
+}
+
+impl From<u8> for VdgColor {
+    /// Create a color from the three color bits of a semigraphics
+    /// character
+    fn from(value: u8) -> VdgColor {
+        match value & 0x07 {
+            0 => VdgColor::Green,
+            1 => VdgColor::Yellow,
+            2 => VdgColor::Blue,
+            3 => VdgColor::Red,
+            4 => VdgColor::Buff,
+            5 => VdgColor::Cyan,
+            6 => VdgColor::Magenta,
+            _ => VdgColor::Orange,
+        }
+    }
+}
+
+/// The semigraphics mode the screen data was displayed in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SemigraphicsMode {
+    /// Semigraphics-4, the default CoCo text screen mode
+    Semigraphics4,
+    /// Semigraphics-6, with the color set select (CSS) line state
+    Semigraphics6 {
+        /// The color set select line, false for the
+        /// green/yellow/blue/red set and true for the
+        /// buff/cyan/magenta/orange set
+        css: bool,
+    },
+}
+
+/// How to decode inverse video alphanumeric characters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InverseVideo {
+    /// Decode inverse letters as lowercase, the Color BASIC convention
+    Lowercase,
+    /// Decode inverse characters the same as normal characters
+    Ignore,
+}
+
+/// A decoded character cell from the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VdgCell {
+    /// The Unicode character for the cell
+    pub character: char,
+    /// True if this is an inverse video alphanumeric character
+    pub inverse: bool,
+    /// The color of a semigraphics character, None for alphanumerics
+    pub color: Option<VdgColor>,
+}
+
+/// Configuration data including character maps for the CoCo
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CocoConfig {
+    /// Version of the CoCo config
+    pub version: String,
+
+    /// VDG character codes to Unicode codes
+    pub vdg_character_codes_to_unicode_codes: Map<String, Value>,
+
+    /// Unicode codes to VDG character codes
+    pub unicode_codes_to_vdg_character_codes: Map<String, Value>,
+
+    /// Semigraphics-4 element bits to Unicode codes
+    pub semigraphics_4_to_unicode_codes: Map<String, Value>,
+
+    /// Semigraphics-6 element bits to Unicode codes
+    pub semigraphics_6_to_unicode_codes: Map<String, Value>,
+}
+
+/// Configuration data for the CoCo module
+pub static CONFIG: RwLock<Option<CocoConfig>> = RwLock::new(None);
+
+impl SystemModule for CocoConfig {
+    const NAMESPACE: &'static str = "coco";
+}
+
+impl CocoConfig {
+    /// Load the embedded CoCo configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::coco::CocoConfig;
+    ///
+    /// let config = CocoConfig::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.vdg_character_codes_to_unicode_codes.len(), 64);
+    /// ```
+    pub fn load() -> std::result::Result<CocoConfig, Error> {
+        load_cached(&CONFIG, config_data::COCO_CHARACTER_MAP)
+    }
+
+    /// Decode a single byte of screen memory
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::coco::{CocoConfig, SemigraphicsMode, VdgColor};
+    ///
+    /// let config = CocoConfig::load().expect("Error loading config");
+    ///
+    /// let cell = config.decode_cell(0x01, SemigraphicsMode::Semigraphics4);
+    /// assert_eq!(cell.character, 'A');
+    /// assert!(cell.inverse);
+    ///
+    /// let cell = config.decode_cell(0xBF, SemigraphicsMode::Semigraphics4);
+    /// assert_eq!(cell.character, '█');
+    /// assert_eq!(cell.color, Some(VdgColor::Red));
+    /// ```
+    pub fn decode_cell(&self, byte: u8, mode: SemigraphicsMode) -> VdgCell {
+        if byte & 0x80 == 0 {
+            return VdgCell {
+                character: lookup_char(&self.vdg_character_codes_to_unicode_codes, byte & 0x3F)
+                    .unwrap_or(' '),
+                inverse: byte & 0x40 == 0,
+                color: None,
+            };
+        }
+
+        let (elements, map, color) = match mode {
+            SemigraphicsMode::Semigraphics4 => (
+                byte & 0x0F,
+                &self.semigraphics_4_to_unicode_codes,
+                (byte >> 4) & 0x07,
+            ),
+            SemigraphicsMode::Semigraphics6 { css } => (
+                byte & 0x3F,
+                &self.semigraphics_6_to_unicode_codes,
+                (u8::from(css) << 2) | (byte >> 6),
+            ),
+        };
+
+        VdgCell {
+            character: lookup_char(map, elements).unwrap_or(' '),
+            inverse: false,
+            color: Some(VdgColor::from(color)),
+        }
+    }
+
+    /// Decode a screen memory dump into lines of text
+    ///
+    /// The screen is split into lines of SCREEN_COLUMNS characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::coco::{CocoConfig, InverseVideo, SemigraphicsMode};
+    ///
+    /// let config = CocoConfig::load().expect("Error loading config");
+    ///
+    /// // "Hi" as Color BASIC shows it, then a semigraphics block
+    /// let screen = config.decode_screen(
+    ///     &[0x48, 0x09, 0x8C],
+    ///     SemigraphicsMode::Semigraphics4,
+    ///     InverseVideo::Lowercase,
+    /// );
+    ///
+    /// assert_eq!(screen, "Hi▀");
+    /// ```
+    pub fn decode_screen(
+        &self,
+        data: &[u8],
+        mode: SemigraphicsMode,
+        inverse: InverseVideo,
+    ) -> String {
+        data.chunks(SCREEN_COLUMNS)
+            .map(|line| {
+                line.iter()
+                    .map(|byte| {
+                        let cell = self.decode_cell(*byte, mode);
+                        if cell.inverse && inverse == InverseVideo::Lowercase {
+                            cell.character.to_ascii_lowercase()
+                        } else {
+                            cell.character
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl CharacterSet for CocoConfig {
+    /// Decode CoCo ASCII text, such as a cassette text file
+    ///
+    /// Carriage returns are decoded as newlines.  Characters that the
+    /// VDG shows differently from ASCII, like the up arrow for caret,
+    /// are decoded to the character the VDG shows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{coco::CocoConfig, CharacterSet};
+    ///
+    /// let config = CocoConfig::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.decode(b"10 PRINT 2^3\r"), "10 PRINT 2↑3\n");
+    /// ```
+    fn decode(&self, bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .filter_map(|c| match c {
+                0x0D => Some('\n'),
+                0x20..=0x3F => lookup_char(&self.vdg_character_codes_to_unicode_codes, *c),
+                0x40..=0x5F => lookup_char(&self.vdg_character_codes_to_unicode_codes, c - 0x40),
+                0x60..=0x7E => Some(char::from(*c)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Encode a Unicode string as CoCo ASCII text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{coco::CocoConfig, CharacterSet};
+    ///
+    /// let config = CocoConfig::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.encode("2↑3\n"), b"2^3\r");
+    /// ```
+    fn encode(&self, s: &str) -> Vec<u8> {
+        s.chars()
+            .filter_map(|c| match c {
+                '\n' => Some(0x0D),
+                '`'..='~' => u8::try_from(c).ok(),
+                _ => match self
+                    .unicode_codes_to_vdg_character_codes
+                    .get(&u32::from(c).to_string())
+                {
+                    Some(Value::Number(v)) => v.as_u64().and_then(|v| {
+                        let code = u8::try_from(v).ok()?;
+                        Some(if code < 0x20 { code + 0x40 } else { code })
+                    }),
+                    _ => None,
+                },
+            })
+            .collect()
+    }
+}
+
+/// A cassette block
+#[derive(Clone, Copy, Debug)]
+pub struct CasBlock<'a> {
+    /// The block type
+    pub block_type: u8,
+    /// The block data
+    pub data: &'a [u8],
+}
+
+/// An iterator over the blocks in a CAS file
+///
+/// Each block starts with a 0x55 leader and a 0x3C sync byte,
+/// followed by the block type, data length, data and a checksum.
+/// Blocks with bad checksums are skipped.
+pub struct CasBlocks<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> CasBlocks<'a> {
+    /// Iterate over the blocks in a CAS file
+    pub fn new(data: &'a [u8]) -> CasBlocks<'a> {
+        CasBlocks { data, position: 0 }
+    }
+}
+
+impl<'a> Iterator for CasBlocks<'a> {
+    type Item = CasBlock<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Find the next leader and sync byte pair
+            let offset = self.data[self.position..]
+                .windows(2)
+                .position(|w| w == [0x55, 0x3C])?;
+            let start = self.position + offset + 2;
+
+            let header = self.data.get(start..start + 2)?;
+            let (block_type, length) = (header[0], usize::from(header[1]));
+            let data = self.data.get(start + 2..start + 2 + length)?;
+            let checksum = *self.data.get(start + 2 + length)?;
+            self.position = start + 3 + length;
+
+            let sum = data
+                .iter()
+                .fold(block_type.wrapping_add(header[1]), |sum, b| {
+                    sum.wrapping_add(*b)
+                });
+            if sum == checksum {
+                return Some(CasBlock { block_type, data });
+            }
+        }
+    }
+}
+
+/// Decode an ASCII text file saved to cassette
+///
+/// Returns the filename from the namefile block and the decoded text.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::coco::{decode_cas_text, CocoConfig};
+///
+/// let config = CocoConfig::load().expect("Error loading config");
+///
+/// let mut cas = vec![0x55, 0x3C, 0x00, 0x0F];
+/// let name = b"HELLO   \x00\xFF\x00\x00\x00\x00\x00";
+/// cas.extend_from_slice(name);
+/// cas.push(name.iter().fold(0x0Fu8, |s, b| s.wrapping_add(*b)));
+/// cas.extend_from_slice(&[0x55, 0x55, 0x3C, 0x01, 0x03, b'H', b'I', 0x0D]);
+/// cas.push(0x01u8 + 0x03 + b'H' + b'I' + 0x0D);
+///
+/// let (filename, text) = decode_cas_text(&cas, &config).expect("Error decoding CAS file");
+///
+/// assert_eq!(filename, "HELLO");
+/// assert_eq!(text, "HI\n");
+/// ```
+pub fn decode_cas_text(
+    data: &[u8],
+    config: &CocoConfig,
+) -> std::result::Result<(String, String), Error> {
+    let mut filename: Option<String> = None;
+    let mut text: Vec<u8> = Vec::new();
+
+    for block in CasBlocks::new(data) {
+        match block.block_type {
+            CAS_NAMEFILE_BLOCK => {
+                if block.data.len() < 10 {
+                    return Err(Error::new(ErrorKind::Message(String::from(
+                        "Namefile block is too short",
+                    ))));
+                }
+                // The file type is the ninth byte and the ASCII flag
+                // is the tenth byte.
+                if block.data[9] != 0xFF {
+                    return Err(Error::new(ErrorKind::Message(String::from(
+                        "Not an ASCII file",
+                    ))));
+                }
+                filename = Some(config.decode(&block.data[..8]).trim_end().to_string());
+            }
+            CAS_DATA_BLOCK => text.extend_from_slice(block.data),
+            CAS_EOF_BLOCK => break,
+            _ => {}
+        }
+    }
+
+    match filename {
+        Some(filename) => Ok((filename, config.decode(&text))),
+        None => Err(Error::new(ErrorKind::Message(String::from(
+            "No namefile block found",
+        )))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        coco::{CocoConfig, InverseVideo, SemigraphicsMode, VdgColor},
+        CharacterSet,
+    };
+
+    #[test]
+    fn coco_alphanumeric_characters_work() {
+        let config = CocoConfig::load().expect("Error loading config");
+
+        let screen = config.decode_screen(
+            &[0x40, 0x41, 0x5E, 0x5F, 0x60, 0x7F],
+            SemigraphicsMode::Semigraphics4,
+            InverseVideo::Ignore,
+        );
+
+        assert_eq!(screen, "@A↑← ?");
+    }
+
+    #[test]
+    fn coco_inverse_video_lowercase_works() {
+        let config = CocoConfig::load().expect("Error loading config");
+        let data = [0x48, 0x05, 0x0C, 0x0C, 0x0F];
+
+        let lowercase = config.decode_screen(
+            &data,
+            SemigraphicsMode::Semigraphics4,
+            InverseVideo::Lowercase,
+        );
+        assert_eq!(lowercase, "Hello");
+
+        let ignored =
+            config.decode_screen(&data, SemigraphicsMode::Semigraphics4, InverseVideo::Ignore);
+        assert_eq!(ignored, "HELLO");
+    }
+
+    #[test]
+    fn coco_semigraphics_4_quadrants_work() {
+        let config = CocoConfig::load().expect("Error loading config");
+
+        let expected = [
+            ' ', '▗', '▖', '▄', '▝', '▐', '▞', '▟', '▘', '▚', '▌', '▙', '▀', '▜', '▛', '█',
+        ];
+        for (bits, c) in expected.iter().enumerate() {
+            let cell = config.decode_cell(0x80 | bits as u8, SemigraphicsMode::Semigraphics4);
+            assert_eq!(cell.character, *c);
+            assert_eq!(cell.color, Some(VdgColor::Green));
+        }
+    }
+
+    #[test]
+    fn coco_semigraphics_6_sextants_work() {
+        let config = CocoConfig::load().expect("Error loading config");
+        let mode = SemigraphicsMode::Semigraphics6 { css: true };
+
+        // Top left element only is sextant-1
+        let cell = config.decode_cell(0xA0, mode);
+        assert_eq!(cell.character, '\u{1FB00}');
+        assert_eq!(cell.color, Some(VdgColor::Magenta));
+
+        // Left column is the left half block
+        assert_eq!(config.decode_cell(0xAA, mode).character, '▌');
+        // Right column is the right half block
+        assert_eq!(config.decode_cell(0x95, mode).character, '▐');
+        // Everything except the top left element
+        assert_eq!(config.decode_cell(0x9F, mode).character, '\u{1FB3B}');
+    }
+
+    #[test]
+    fn coco_screen_lines_work() {
+        let config = CocoConfig::load().expect("Error loading config");
+        let data = [0x60u8; 64];
+
+        let screen =
+            config.decode_screen(&data, SemigraphicsMode::Semigraphics4, InverseVideo::Ignore);
+
+        assert_eq!(screen.lines().count(), 2);
+    }
+
+    #[test]
+    fn coco_ascii_round_trip_works() {
+        let config = CocoConfig::load().expect("Error loading config");
+        let data = b"10 A$=\"lower\"+CHR$(95)\r";
+
+        assert_eq!(config.encode(&config.decode(data)), data);
+    }
+}
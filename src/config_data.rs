@@ -17,6 +17,405 @@ pub static CONFIG_DATA: &str = "
 	{
 	  \"version\": \"0.2.0\",
 
+	  \"c64_petscii_code_aliases\":
+	  {
+	    \"192\": 96,
+	    \"193\": 97,
+	    \"194\": 98,
+	    \"195\": 99,
+	    \"196\": 100,
+	    \"197\": 101,
+	    \"198\": 102,
+	    \"199\": 103,
+	    \"200\": 104,
+	    \"201\": 105,
+	    \"202\": 106,
+	    \"203\": 107,
+	    \"204\": 108,
+	    \"205\": 109,
+	    \"206\": 110,
+	    \"207\": 111,
+	    \"208\": 112,
+	    \"209\": 113,
+	    \"210\": 114,
+	    \"211\": 115,
+	    \"212\": 116,
+	    \"213\": 117,
+	    \"214\": 118,
+	    \"215\": 119,
+	    \"216\": 120,
+	    \"217\": 121,
+	    \"218\": 122,
+	    \"219\": 123,
+	    \"220\": 124,
+	    \"221\": 125,
+	    \"222\": 126,
+	    \"223\": 127,
+	    \"224\": 160,
+	    \"225\": 161,
+	    \"226\": 162,
+	    \"227\": 163,
+	    \"228\": 164,
+	    \"229\": 165,
+	    \"230\": 166,
+	    \"231\": 167,
+	    \"232\": 168,
+	    \"233\": 169,
+	    \"234\": 170,
+	    \"235\": 171,
+	    \"236\": 172,
+	    \"237\": 173,
+	    \"238\": 174,
+	    \"239\": 175,
+	    \"240\": 176,
+	    \"241\": 177,
+	    \"242\": 178,
+	    \"243\": 179,
+	    \"244\": 180,
+	    \"245\": 181,
+	    \"246\": 182,
+	    \"247\": 183,
+	    \"248\": 184,
+	    \"249\": 185,
+	    \"250\": 186,
+	    \"251\": 187,
+	    \"252\": 188,
+	    \"253\": 189,
+	    \"254\": 190,
+	    \"255\": 126
+	  },
+
+	  \"c64_petscii_unshifted_code_names\":
+	  {
+	    \"3\": \"STOP\",
+	    \"5\": \"WHITE\",
+	    \"8\": \"DISABLE SHIFT-COMMODORE\",
+	    \"9\": \"ENABLE SHIFT-COMMODORE\",
+	    \"10\": \"LINE FEED\",
+	    \"13\": \"RETURN\",
+	    \"14\": \"LOWERCASE\",
+	    \"15\": \"FLASH ON\",
+	    \"17\": \"CURSOR DOWN\",
+	    \"18\": \"REVERSE ON\",
+	    \"19\": \"HOME\",
+	    \"20\": \"DELETE\",
+	    \"28\": \"RED\",
+	    \"29\": \"CURSOR RIGHT\",
+	    \"30\": \"GREEN\",
+	    \"31\": \"BLUE\",
+	    \"32\": \"SPACE\",
+	    \"33\": \"EXCLAMATION MARK\",
+	    \"34\": \"QUOTATION MARK\",
+	    \"35\": \"NUMBER SIGN\",
+	    \"36\": \"DOLLAR SIGN\",
+	    \"37\": \"PERCENT SIGN\",
+	    \"38\": \"AMPERSAND\",
+	    \"39\": \"APOSTROPHE\",
+	    \"40\": \"LEFT PARENTHESIS\",
+	    \"41\": \"RIGHT PARENTHESIS\",
+	    \"42\": \"ASTERISK\",
+	    \"43\": \"PLUS SIGN\",
+	    \"44\": \"COMMA\",
+	    \"45\": \"HYPHEN-MINUS\",
+	    \"46\": \"FULL STOP\",
+	    \"47\": \"SOLIDUS\",
+	    \"48\": \"DIGIT ZERO\",
+	    \"49\": \"DIGIT ONE\",
+	    \"50\": \"DIGIT TWO\",
+	    \"51\": \"DIGIT THREE\",
+	    \"52\": \"DIGIT FOUR\",
+	    \"53\": \"DIGIT FIVE\",
+	    \"54\": \"DIGIT SIX\",
+	    \"55\": \"DIGIT SEVEN\",
+	    \"56\": \"DIGIT EIGHT\",
+	    \"57\": \"DIGIT NINE\",
+	    \"58\": \"COLON\",
+	    \"59\": \"SEMICOLON\",
+	    \"60\": \"LESS-THAN SIGN\",
+	    \"61\": \"EQUALS SIGN\",
+	    \"62\": \"GREATER-THAN SIGN\",
+	    \"63\": \"QUESTION MARK\",
+	    \"64\": \"COMMERCIAL AT\",
+	    \"65\": \"LATIN CAPITAL LETTER A\",
+	    \"66\": \"LATIN CAPITAL LETTER B\",
+	    \"67\": \"LATIN CAPITAL LETTER C\",
+	    \"68\": \"LATIN CAPITAL LETTER D\",
+	    \"69\": \"LATIN CAPITAL LETTER E\",
+	    \"70\": \"LATIN CAPITAL LETTER F\",
+	    \"71\": \"LATIN CAPITAL LETTER G\",
+	    \"72\": \"LATIN CAPITAL LETTER H\",
+	    \"73\": \"LATIN CAPITAL LETTER I\",
+	    \"74\": \"LATIN CAPITAL LETTER J\",
+	    \"75\": \"LATIN CAPITAL LETTER K\",
+	    \"76\": \"LATIN CAPITAL LETTER L\",
+	    \"77\": \"LATIN CAPITAL LETTER M\",
+	    \"78\": \"LATIN CAPITAL LETTER N\",
+	    \"79\": \"LATIN CAPITAL LETTER O\",
+	    \"80\": \"LATIN CAPITAL LETTER P\",
+	    \"81\": \"LATIN CAPITAL LETTER Q\",
+	    \"82\": \"LATIN CAPITAL LETTER R\",
+	    \"83\": \"LATIN CAPITAL LETTER S\",
+	    \"84\": \"LATIN CAPITAL LETTER T\",
+	    \"85\": \"LATIN CAPITAL LETTER U\",
+	    \"86\": \"LATIN CAPITAL LETTER V\",
+	    \"87\": \"LATIN CAPITAL LETTER W\",
+	    \"88\": \"LATIN CAPITAL LETTER X\",
+	    \"89\": \"LATIN CAPITAL LETTER Y\",
+	    \"90\": \"LATIN CAPITAL LETTER Z\",
+	    \"91\": \"LEFT SQUARE BRACKET\",
+	    \"92\": \"POUND SIGN\",
+	    \"93\": \"RIGHT SQUARE BRACKET\",
+	    \"94\": \"UPWARDS ARROW\",
+	    \"95\": \"LEFTWARDS ARROW\",
+	    \"96\": \"HORIZONTAL ONE EIGHTH BLOCK-5 (CBM)\",
+	    \"97\": \"BLACK SPADE SUIT (CBM)\",
+	    \"98\": \"VERTICAL ONE EIGHTH BLOCK-4 (CBM)\",
+	    \"99\": \"HORIZONTAL ONE EIGHTH BLOCK-4 (CBM)\",
+	    \"100\": \"HORIZONTAL ONE EIGHTH BLOCK-3 (CBM)\",
+	    \"101\": \"HORIZONTAL ONE EIGHTH BLOCK-2 (CBM)\",
+	    \"102\": \"HORIZONTAL ONE EIGHTH BLOCK-6 (CBM)\",
+	    \"103\": \"VERTICAL ONE EIGHTH BLOCK-3 (CBM)\",
+	    \"104\": \"VERTICAL ONE EIGHTH BLOCK-6 (CBM)\",
+	    \"105\": \"BOX DRAWINGS LIGHT ARC DOWN AND LEFT (CBM)\",
+	    \"106\": \"BOX DRAWINGS LIGHT ARC UP AND RIGHT (CBM)\",
+	    \"107\": \"BOX DRAWINGS LIGHT ARC UP AND LEFT (CBM)\",
+	    \"108\": \"LEFT AND LOWER ONE EIGHTH BLOCK (CBM)\",
+	    \"109\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER LEFT TO LOWER RIGHT (CBM)\",
+	    \"110\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER RIGHT TO LOWER LEFT (CBM)\",
+	    \"111\": \"LEFT AND UPPER ONE EIGHTH BLOCK (CBM)\",
+	    \"112\": \"RIGHT AND UPPER ONE EIGHTH BLOCK (CBM)\",
+	    \"113\": \"BLACK CIRCLE (CBM)\",
+	    \"114\": \"HORIZONTAL ONE EIGHTH BLOCK-7 (CBM)\",
+	    \"115\": \"BLACK HEART SUIT (CBM)\",
+	    \"116\": \"VERTICAL ONE EIGHTH BLOCK-2 (CBM)\",
+	    \"117\": \"BOX DRAWINGS LIGHT ARC DOWN AND RIGHT (CBM)\",
+	    \"118\": \"BOX DRAWINGS LIGHT DIAGONAL CROSS (CBM)\",
+	    \"119\": \"WHITE CIRCLE (CBM)\",
+	    \"120\": \"BLACK CLUB SUIT (CBM)\",
+	    \"121\": \"VERTICAL ONE EIGHTH BLOCK-7 (CBM)\",
+	    \"122\": \"BLACK DIAMOND SUIT (CBM)\",
+	    \"123\": \"BOX DRAWINGS LIGHT VERTICAL AND HORIZONTAL (CBM)\",
+	    \"124\": \"LEFT HALF MEDIUM SHADE (CBM)\",
+	    \"125\": \"VERTICAL ONE EIGHTH BLOCK-5 (CBM)\",
+	    \"126\": \"MEDIUM SHADE (CBM)\",
+	    \"127\": \"BLACK UPPER RIGHT TRIANGLE (CBM)\",
+	    \"129\": \"ORANGE\",
+	    \"133\": \"F1\",
+	    \"134\": \"F3\",
+	    \"135\": \"F5\",
+	    \"136\": \"F7\",
+	    \"137\": \"F2\",
+	    \"138\": \"F4\",
+	    \"139\": \"F6\",
+	    \"140\": \"F8\",
+	    \"141\": \"SHIFT RETURN\",
+	    \"142\": \"UPPERCASE\",
+	    \"143\": \"FLASH OFF\",
+	    \"144\": \"BLACK\",
+	    \"145\": \"CURSOR UP\",
+	    \"146\": \"REVERSE OFF\",
+	    \"147\": \"CLEAR\",
+	    \"148\": \"INSERT\",
+	    \"149\": \"BROWN\",
+	    \"150\": \"LIGHT RED\",
+	    \"151\": \"DARK GREY\",
+	    \"152\": \"GREY\",
+	    \"153\": \"LIGHT GREEN\",
+	    \"154\": \"LIGHT BLUE\",
+	    \"155\": \"LIGHT GREY\",
+	    \"156\": \"PURPLE\",
+	    \"157\": \"CURSOR LEFT\",
+	    \"158\": \"YELLOW\",
+	    \"159\": \"CYAN\",
+	    \"160\": \"SHIFTED SPACE\",
+	    \"161\": \"LEFT HALF BLOCK (CBM)\",
+	    \"162\": \"LOWER HALF BLOCK (CBM)\",
+	    \"163\": \"UPPER ONE EIGHTH BLOCK (CBM)\",
+	    \"164\": \"LOWER ONE EIGHTH BLOCK (CBM)\",
+	    \"165\": \"LEFT ONE EIGHTH BLOCK (CBM)\",
+	    \"166\": \"MEDIUM SHADE (CBM)\",
+	    \"167\": \"RIGHT ONE EIGHTH BLOCK (CBM)\",
+	    \"168\": \"LOWER HALF MEDIUM SHADE (CBM)\",
+	    \"169\": \"BLACK UPPER LEFT TRIANGLE (CBM)\",
+	    \"170\": \"RIGHT ONE QUARTER BLOCK (CBM)\",
+	    \"171\": \"BOX DRAWINGS LIGHT VERTICAL AND RIGHT (CBM)\",
+	    \"172\": \"QUADRANT LOWER RIGHT (CBM)\",
+	    \"173\": \"BOX DRAWINGS LIGHT UP AND RIGHT (CBM)\",
+	    \"174\": \"BOX DRAWINGS LIGHT DOWN AND LEFT (CBM)\",
+	    \"175\": \"LOWER ONE QUARTER BLOCK (CBM)\",
+	    \"176\": \"BOX DRAWINGS LIGHT DOWN AND RIGHT (CBM)\",
+	    \"177\": \"BOX DRAWINGS LIGHT UP AND HORIZONTAL (CBM)\",
+	    \"178\": \"BOX DRAWINGS LIGHT DOWN AND HORIZONTAL (CBM)\",
+	    \"179\": \"BOX DRAWINGS LIGHT VERTICAL AND LEFT (CBM)\",
+	    \"180\": \"LEFT ONE QUARTER BLOCK (CBM)\",
+	    \"181\": \"LEFT THREE EIGHTHS BLOCK (CBM)\",
+	    \"182\": \"RIGHT THREE EIGHTHS BLOCK (CBM)\",
+	    \"183\": \"UPPER ONE QUARTER BLOCK (CBM)\",
+	    \"184\": \"UPPER THREE EIGHTHS BLOCK (CBM)\",
+	    \"185\": \"LOWER THREE EIGHTHS BLOCK (CBM)\",
+	    \"186\": \"RIGHT AND LOWER ONE EIGHTH BLOCK (CBM)\",
+	    \"187\": \"QUADRANT LOWER LEFT (CBM)\",
+	    \"188\": \"QUADRANT UPPER RIGHT (CBM)\",
+	    \"189\": \"BOX DRAWINGS LIGHT UP AND LEFT (CBM)\",
+	    \"190\": \"QUADRANT UPPER LEFT (CBM)\",
+	    \"191\": \"QUADRANT UPPER LEFT AND LOWER RIGHT (CBM)\",
+	    \"192\": \"HORIZONTAL ONE EIGHTH BLOCK-5 (CBM)\",
+	    \"193\": \"BLACK SPADE SUIT (CBM)\",
+	    \"194\": \"VERTICAL ONE EIGHTH BLOCK-4 (CBM)\",
+	    \"195\": \"HORIZONTAL ONE EIGHTH BLOCK-4 (CBM)\",
+	    \"196\": \"HORIZONTAL ONE EIGHTH BLOCK-3 (CBM)\",
+	    \"197\": \"HORIZONTAL ONE EIGHTH BLOCK-2 (CBM)\",
+	    \"198\": \"HORIZONTAL ONE EIGHTH BLOCK-6 (CBM)\",
+	    \"199\": \"VERTICAL ONE EIGHTH BLOCK-3 (CBM)\",
+	    \"200\": \"VERTICAL ONE EIGHTH BLOCK-6 (CBM)\",
+	    \"201\": \"BOX DRAWINGS LIGHT ARC DOWN AND LEFT (CBM)\",
+	    \"202\": \"BOX DRAWINGS LIGHT ARC UP AND RIGHT (CBM)\",
+	    \"203\": \"BOX DRAWINGS LIGHT ARC UP AND LEFT (CBM)\",
+	    \"204\": \"LEFT AND LOWER ONE EIGHTH BLOCK (CBM)\",
+	    \"205\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER LEFT TO LOWER RIGHT (CBM)\",
+	    \"206\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER RIGHT TO LOWER LEFT (CBM)\",
+	    \"207\": \"LEFT AND UPPER ONE EIGHTH BLOCK (CBM)\",
+	    \"208\": \"RIGHT AND UPPER ONE EIGHTH BLOCK (CBM)\",
+	    \"209\": \"BLACK CIRCLE (CBM)\",
+	    \"210\": \"HORIZONTAL ONE EIGHTH BLOCK-7 (CBM)\",
+	    \"211\": \"BLACK HEART SUIT (CBM)\",
+	    \"212\": \"VERTICAL ONE EIGHTH BLOCK-2 (CBM)\",
+	    \"213\": \"BOX DRAWINGS LIGHT ARC DOWN AND RIGHT (CBM)\",
+	    \"214\": \"BOX DRAWINGS LIGHT DIAGONAL CROSS (CBM)\",
+	    \"215\": \"WHITE CIRCLE (CBM)\",
+	    \"216\": \"BLACK CLUB SUIT (CBM)\",
+	    \"217\": \"VERTICAL ONE EIGHTH BLOCK-7 (CBM)\",
+	    \"218\": \"BLACK DIAMOND SUIT (CBM)\",
+	    \"219\": \"BOX DRAWINGS LIGHT VERTICAL AND HORIZONTAL (CBM)\",
+	    \"220\": \"LEFT HALF MEDIUM SHADE (CBM)\",
+	    \"221\": \"VERTICAL ONE EIGHTH BLOCK-5 (CBM)\",
+	    \"222\": \"MEDIUM SHADE (CBM)\",
+	    \"223\": \"BLACK UPPER RIGHT TRIANGLE (CBM)\",
+	    \"224\": \"SHIFTED SPACE\",
+	    \"225\": \"LEFT HALF BLOCK (CBM)\",
+	    \"226\": \"LOWER HALF BLOCK (CBM)\",
+	    \"227\": \"UPPER ONE EIGHTH BLOCK (CBM)\",
+	    \"228\": \"LOWER ONE EIGHTH BLOCK (CBM)\",
+	    \"229\": \"LEFT ONE EIGHTH BLOCK (CBM)\",
+	    \"230\": \"MEDIUM SHADE (CBM)\",
+	    \"231\": \"RIGHT ONE EIGHTH BLOCK (CBM)\",
+	    \"232\": \"LOWER HALF MEDIUM SHADE (CBM)\",
+	    \"233\": \"BLACK UPPER LEFT TRIANGLE (CBM)\",
+	    \"234\": \"RIGHT ONE QUARTER BLOCK (CBM)\",
+	    \"235\": \"BOX DRAWINGS LIGHT VERTICAL AND RIGHT (CBM)\",
+	    \"236\": \"QUADRANT LOWER RIGHT (CBM)\",
+	    \"237\": \"BOX DRAWINGS LIGHT UP AND RIGHT (CBM)\",
+	    \"238\": \"BOX DRAWINGS LIGHT DOWN AND LEFT (CBM)\",
+	    \"239\": \"LOWER ONE QUARTER BLOCK (CBM)\",
+	    \"240\": \"BOX DRAWINGS LIGHT DOWN AND RIGHT (CBM)\",
+	    \"241\": \"BOX DRAWINGS LIGHT UP AND HORIZONTAL (CBM)\",
+	    \"242\": \"BOX DRAWINGS LIGHT DOWN AND HORIZONTAL (CBM)\",
+	    \"243\": \"BOX DRAWINGS LIGHT VERTICAL AND LEFT (CBM)\",
+	    \"244\": \"LEFT ONE QUARTER BLOCK (CBM)\",
+	    \"245\": \"LEFT THREE EIGHTHS BLOCK (CBM)\",
+	    \"246\": \"RIGHT THREE EIGHTHS BLOCK (CBM)\",
+	    \"247\": \"UPPER ONE QUARTER BLOCK (CBM)\",
+	    \"248\": \"UPPER THREE EIGHTHS BLOCK (CBM)\",
+	    \"249\": \"LOWER THREE EIGHTHS BLOCK (CBM)\",
+	    \"250\": \"RIGHT AND LOWER ONE EIGHTH BLOCK (CBM)\",
+	    \"251\": \"QUADRANT LOWER LEFT (CBM)\",
+	    \"252\": \"QUADRANT UPPER RIGHT (CBM)\",
+	    \"253\": \"BOX DRAWINGS LIGHT UP AND LEFT (CBM)\",
+	    \"254\": \"QUADRANT UPPER LEFT (CBM)\",
+	    \"255\": \"MEDIUM SHADE (CBM)\"
+	  },
+
+	  \"c64_petscii_shifted_code_names\":
+	  {
+	    \"65\": \"LATIN SMALL LETTER A\",
+	    \"66\": \"LATIN SMALL LETTER B\",
+	    \"67\": \"LATIN SMALL LETTER C\",
+	    \"68\": \"LATIN SMALL LETTER D\",
+	    \"69\": \"LATIN SMALL LETTER E\",
+	    \"70\": \"LATIN SMALL LETTER F\",
+	    \"71\": \"LATIN SMALL LETTER G\",
+	    \"72\": \"LATIN SMALL LETTER H\",
+	    \"73\": \"LATIN SMALL LETTER I\",
+	    \"74\": \"LATIN SMALL LETTER J\",
+	    \"75\": \"LATIN SMALL LETTER K\",
+	    \"76\": \"LATIN SMALL LETTER L\",
+	    \"77\": \"LATIN SMALL LETTER M\",
+	    \"78\": \"LATIN SMALL LETTER N\",
+	    \"79\": \"LATIN SMALL LETTER O\",
+	    \"80\": \"LATIN SMALL LETTER P\",
+	    \"81\": \"LATIN SMALL LETTER Q\",
+	    \"82\": \"LATIN SMALL LETTER R\",
+	    \"83\": \"LATIN SMALL LETTER S\",
+	    \"84\": \"LATIN SMALL LETTER T\",
+	    \"85\": \"LATIN SMALL LETTER U\",
+	    \"86\": \"LATIN SMALL LETTER V\",
+	    \"87\": \"LATIN SMALL LETTER W\",
+	    \"88\": \"LATIN SMALL LETTER X\",
+	    \"89\": \"LATIN SMALL LETTER Y\",
+	    \"90\": \"LATIN SMALL LETTER Z\",
+	    \"97\": \"LATIN CAPITAL LETTER A\",
+	    \"98\": \"LATIN CAPITAL LETTER B\",
+	    \"99\": \"LATIN CAPITAL LETTER C\",
+	    \"100\": \"LATIN CAPITAL LETTER D\",
+	    \"101\": \"LATIN CAPITAL LETTER E\",
+	    \"102\": \"LATIN CAPITAL LETTER F\",
+	    \"103\": \"LATIN CAPITAL LETTER G\",
+	    \"104\": \"LATIN CAPITAL LETTER H\",
+	    \"105\": \"LATIN CAPITAL LETTER I\",
+	    \"106\": \"LATIN CAPITAL LETTER J\",
+	    \"107\": \"LATIN CAPITAL LETTER K\",
+	    \"108\": \"LATIN CAPITAL LETTER L\",
+	    \"109\": \"LATIN CAPITAL LETTER M\",
+	    \"110\": \"LATIN CAPITAL LETTER N\",
+	    \"111\": \"LATIN CAPITAL LETTER O\",
+	    \"112\": \"LATIN CAPITAL LETTER P\",
+	    \"113\": \"LATIN CAPITAL LETTER Q\",
+	    \"114\": \"LATIN CAPITAL LETTER R\",
+	    \"115\": \"LATIN CAPITAL LETTER S\",
+	    \"116\": \"LATIN CAPITAL LETTER T\",
+	    \"117\": \"LATIN CAPITAL LETTER U\",
+	    \"118\": \"LATIN CAPITAL LETTER V\",
+	    \"119\": \"LATIN CAPITAL LETTER W\",
+	    \"120\": \"LATIN CAPITAL LETTER X\",
+	    \"121\": \"LATIN CAPITAL LETTER Y\",
+	    \"122\": \"LATIN CAPITAL LETTER Z\",
+	    \"126\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE (CBM)\",
+	    \"127\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE LEFT AND MIDDLE RIGHT TO LOWER CENTRE (CBM)\",
+	    \"169\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE TO MIDDLE LEFT (CBM)\",
+	    \"186\": \"CHECK MARK (CBM)\",
+	    \"193\": \"LATIN CAPITAL LETTER A\",
+	    \"194\": \"LATIN CAPITAL LETTER B\",
+	    \"195\": \"LATIN CAPITAL LETTER C\",
+	    \"196\": \"LATIN CAPITAL LETTER D\",
+	    \"197\": \"LATIN CAPITAL LETTER E\",
+	    \"198\": \"LATIN CAPITAL LETTER F\",
+	    \"199\": \"LATIN CAPITAL LETTER G\",
+	    \"200\": \"LATIN CAPITAL LETTER H\",
+	    \"201\": \"LATIN CAPITAL LETTER I\",
+	    \"202\": \"LATIN CAPITAL LETTER J\",
+	    \"203\": \"LATIN CAPITAL LETTER K\",
+	    \"204\": \"LATIN CAPITAL LETTER L\",
+	    \"205\": \"LATIN CAPITAL LETTER M\",
+	    \"206\": \"LATIN CAPITAL LETTER N\",
+	    \"207\": \"LATIN CAPITAL LETTER O\",
+	    \"208\": \"LATIN CAPITAL LETTER P\",
+	    \"209\": \"LATIN CAPITAL LETTER Q\",
+	    \"210\": \"LATIN CAPITAL LETTER R\",
+	    \"211\": \"LATIN CAPITAL LETTER S\",
+	    \"212\": \"LATIN CAPITAL LETTER T\",
+	    \"213\": \"LATIN CAPITAL LETTER U\",
+	    \"214\": \"LATIN CAPITAL LETTER V\",
+	    \"215\": \"LATIN CAPITAL LETTER W\",
+	    \"216\": \"LATIN CAPITAL LETTER X\",
+	    \"217\": \"LATIN CAPITAL LETTER Y\",
+	    \"218\": \"LATIN CAPITAL LETTER Z\",
+	    \"222\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE (CBM)\",
+	    \"223\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE LEFT AND MIDDLE RIGHT TO LOWER CENTRE (CBM)\",
+	    \"233\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE TO MIDDLE LEFT (CBM)\",
+	    \"250\": \"CHECK MARK (CBM)\",
+	    \"255\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE (CBM)\"
+	  },
+
 	  \"c64_screen_codes_set_1_to_petscii_codes\":
 	  {
 	    \"0\": [0, 64],
@@ -511,34 +910,8 @@ pub static CONFIG_DATA: &str = "
 	  },
 
 	  \"c64_petscii_shifted_codes_to_screen_codes\": {
-              \"10\": [3, 10],
-              \"13\": [3, 13],
-	      \"65\": [2, 1],
-	      \"66\": [2, 2],
-	      \"67\": [2, 3],
-	      \"68\": [2, 4],
-	      \"69\": [2, 5],
-	      \"70\": [2, 6],
-	      \"71\": [2, 7],
-	      \"72\": [2, 8],
-	      \"73\": [2, 9],
-	      \"74\": [2, 10],
-	      \"75\": [2, 11],
-	      \"76\": [2, 12],
-	      \"77\": [2, 13],
-	      \"78\": [2, 14],
-	      \"79\": [2, 15],
-	      \"80\": [2, 16],
-	      \"81\": [2, 17],
-	      \"82\": [2, 18],
-	      \"83\": [2, 19],
-	      \"84\": [2, 20],
-	      \"85\": [2, 21],
-	      \"86\": [2, 22],
-	      \"87\": [2, 23],
-	      \"88\": [2, 24],
-	      \"89\": [2, 25],
-	      \"90\": [2, 26],
+	      \"10\": [3, 10],
+	      \"13\": [3, 13],
 	      \"32\": [1, 32],
 	      \"33\": [1, 33],
 	      \"34\": [1, 34],
@@ -570,7 +943,167 @@ pub static CONFIG_DATA: &str = "
 	      \"60\": [1, 60],
 	      \"61\": [1, 61],
 	      \"62\": [1, 62],
-	      \"63\": [1, 63]
+	      \"63\": [1, 63],
+	      \"64\": [1, 0],
+	      \"65\": [2, 1],
+	      \"66\": [2, 2],
+	      \"67\": [2, 3],
+	      \"68\": [2, 4],
+	      \"69\": [2, 5],
+	      \"70\": [2, 6],
+	      \"71\": [2, 7],
+	      \"72\": [2, 8],
+	      \"73\": [2, 9],
+	      \"74\": [2, 10],
+	      \"75\": [2, 11],
+	      \"76\": [2, 12],
+	      \"77\": [2, 13],
+	      \"78\": [2, 14],
+	      \"79\": [2, 15],
+	      \"80\": [2, 16],
+	      \"81\": [2, 17],
+	      \"82\": [2, 18],
+	      \"83\": [2, 19],
+	      \"84\": [2, 20],
+	      \"85\": [2, 21],
+	      \"86\": [2, 22],
+	      \"87\": [2, 23],
+	      \"88\": [2, 24],
+	      \"89\": [2, 25],
+	      \"90\": [2, 26],
+	      \"91\": [1, 27],
+	      \"92\": [1, 28],
+	      \"93\": [1, 29],
+	      \"94\": [1, 30],
+	      \"95\": [1, 31],
+	      \"96\": [1, 64],
+	      \"97\": [2, 65],
+	      \"98\": [2, 66],
+	      \"99\": [2, 67],
+	      \"100\": [2, 68],
+	      \"101\": [2, 69],
+	      \"102\": [2, 70],
+	      \"103\": [2, 71],
+	      \"104\": [2, 72],
+	      \"105\": [2, 73],
+	      \"106\": [2, 74],
+	      \"107\": [2, 75],
+	      \"108\": [2, 76],
+	      \"109\": [2, 77],
+	      \"110\": [2, 78],
+	      \"111\": [2, 79],
+	      \"112\": [2, 80],
+	      \"113\": [2, 81],
+	      \"114\": [2, 82],
+	      \"115\": [2, 83],
+	      \"116\": [2, 84],
+	      \"117\": [2, 85],
+	      \"118\": [2, 86],
+	      \"119\": [2, 87],
+	      \"120\": [2, 88],
+	      \"121\": [2, 89],
+	      \"122\": [2, 90],
+	      \"123\": [1, 91],
+	      \"124\": [1, 92],
+	      \"125\": [1, 93],
+	      \"126\": [2, 94],
+	      \"127\": [2, 95],
+	      \"160\": [1, 96],
+	      \"161\": [1, 97],
+	      \"162\": [1, 98],
+	      \"163\": [1, 99],
+	      \"164\": [1, 100],
+	      \"165\": [1, 101],
+	      \"166\": [1, 102],
+	      \"167\": [1, 103],
+	      \"168\": [1, 104],
+	      \"169\": [2, 105],
+	      \"170\": [1, 106],
+	      \"171\": [1, 107],
+	      \"172\": [1, 108],
+	      \"173\": [1, 109],
+	      \"174\": [1, 110],
+	      \"175\": [1, 111],
+	      \"176\": [1, 112],
+	      \"177\": [1, 113],
+	      \"178\": [1, 114],
+	      \"179\": [1, 115],
+	      \"180\": [1, 116],
+	      \"181\": [1, 117],
+	      \"182\": [1, 118],
+	      \"183\": [1, 119],
+	      \"184\": [1, 120],
+	      \"185\": [1, 121],
+	      \"186\": [2, 122],
+	      \"187\": [1, 123],
+	      \"188\": [1, 124],
+	      \"189\": [1, 125],
+	      \"190\": [1, 126],
+	      \"191\": [1, 127],
+	      \"192\": [1, 64],
+	      \"193\": [2, 65],
+	      \"194\": [2, 66],
+	      \"195\": [2, 67],
+	      \"196\": [2, 68],
+	      \"197\": [2, 69],
+	      \"198\": [2, 70],
+	      \"199\": [2, 71],
+	      \"200\": [2, 72],
+	      \"201\": [2, 73],
+	      \"202\": [2, 74],
+	      \"203\": [2, 75],
+	      \"204\": [2, 76],
+	      \"205\": [2, 77],
+	      \"206\": [2, 78],
+	      \"207\": [2, 79],
+	      \"208\": [2, 80],
+	      \"209\": [2, 81],
+	      \"210\": [2, 82],
+	      \"211\": [2, 83],
+	      \"212\": [2, 84],
+	      \"213\": [2, 85],
+	      \"214\": [2, 86],
+	      \"215\": [2, 87],
+	      \"216\": [2, 88],
+	      \"217\": [2, 89],
+	      \"218\": [2, 90],
+	      \"219\": [1, 91],
+	      \"220\": [1, 92],
+	      \"221\": [1, 93],
+	      \"222\": [2, 94],
+	      \"223\": [2, 95],
+	      \"224\": [1, 96],
+	      \"225\": [1, 97],
+	      \"226\": [1, 98],
+	      \"227\": [1, 99],
+	      \"228\": [1, 100],
+	      \"229\": [1, 101],
+	      \"230\": [1, 102],
+	      \"231\": [1, 103],
+	      \"232\": [1, 104],
+	      \"233\": [2, 105],
+	      \"234\": [1, 106],
+	      \"235\": [1, 107],
+	      \"236\": [1, 108],
+	      \"237\": [1, 109],
+	      \"238\": [1, 110],
+	      \"239\": [1, 111],
+	      \"240\": [1, 112],
+	      \"241\": [1, 113],
+	      \"242\": [1, 114],
+	      \"243\": [1, 115],
+	      \"244\": [1, 116],
+	      \"245\": [1, 117],
+	      \"246\": [1, 118],
+	      \"247\": [1, 119],
+	      \"248\": [1, 120],
+	      \"249\": [1, 121],
+	      \"250\": [2, 122],
+	      \"251\": [1, 123],
+	      \"252\": [1, 124],
+	      \"253\": [1, 125],
+	      \"254\": [1, 126],
+	      \"255\": [2, 94]
 	  },
 
 	  \"c64_screen_codes_set_1_to_unicode_codes\":
@@ -667,16 +1200,21 @@ pub static CONFIG_DATA: &str = "
 	     \"89\": 129909,
 	     \"90\": 9830,
 	     \"91\": 9532,
+	     \"92\": 129932,
 	     \"93\": 129907,
 	     \"94\": 9618,
+	     \"95\": 9701,
 	     \"96\": 32,
 	     \"97\": 9612,
 	     \"98\": 9604,
 	     \"99\": 9620,
 	     \"100\": 9601,
-	     \"101\": 9614,
+	     \"101\": 9615,
 	     \"102\": 9618,
+	     \"103\": 9621,
+	     \"104\": 129935,
 	     \"105\": 9700,
+	     \"106\": 129927,
 	     \"107\": 9500,
 	     \"108\": 9623,
 	     \"109\": 9492,
@@ -688,6 +1226,9 @@ pub static CONFIG_DATA: &str = "
 	     \"115\": 9508,
 	     \"116\": 9614,
 	     \"117\": 9613,
+	     \"118\": 129928,
+	     \"119\": 129922,
+	     \"120\": 129923,
 	     \"121\": 9603,
 	     \"122\": 129919,
 	     \"123\": 9622,
@@ -712,7 +1253,7 @@ pub static CONFIG_DATA: &str = "
 	     \"199\": 129937,
 	     \"194\": 129938,
 	     \"221\": 129939,
-	     \"299\": 129940,
+	     \"220\": 129940,
 	     \"217\": 129941,
 	     \"197\": 129942,
 	     \"196\": 129943,
@@ -749,12 +1290,39 @@ pub static CONFIG_DATA: &str = "
 	    \"24\": 120,
 	    \"25\": 121,
 	    \"26\": 122,
+	    \"65\": 65,
+	    \"66\": 66,
+	    \"67\": 67,
+	    \"68\": 68,
+	    \"69\": 69,
+	    \"70\": 70,
+	    \"71\": 71,
+	    \"72\": 72,
+	    \"73\": 73,
+	    \"74\": 74,
+	    \"75\": 75,
+	    \"76\": 76,
+	    \"77\": 77,
+	    \"78\": 78,
+	    \"79\": 79,
+	    \"80\": 80,
+	    \"81\": 81,
+	    \"82\": 82,
+	    \"83\": 83,
+	    \"84\": 84,
+	    \"85\": 85,
+	    \"86\": 86,
+	    \"87\": 87,
+	    \"88\": 88,
+	    \"89\": 89,
+	    \"90\": 90,
 	    \"94\": 129957,
 	    \"95\": 129960,
 	    \"222\": 129958,
 	    \"223\": 129959,
 	    \"233\": 129961,
 	    \"105\": 129962,
+	    \"122\": 10003,
 	    \"250\": 129963
 	  },
 	  \"c64_screen_codes_set_3_to_unicode_codes\":
@@ -765,6 +1333,7 @@ pub static CONFIG_DATA: &str = "
           \"unicode_codes_to_c64_screen_codes\":
           {
             \"10\": [3, 10],
+            \"13\": [3, 13],
 	    \"32\": [1, 96],
 	    \"33\": [1, 33],
 	    \"34\": [1, 34],
@@ -878,8 +1447,10 @@ pub static CONFIG_DATA: &str = "
 	    \"9612\": [1, 97],
 	    \"9613\": [1, 117],
 	    \"9614\": [1, 116],
+	    \"9615\": [1, 101],
 	    \"9618\": [1, 102],
 	    \"9620\": [1, 99],
+	    \"9621\": [1, 103],
 	    \"9622\": [1, 123],
 	    \"9623\": [1, 108],
 	    \"9624\": [1, 126],
@@ -888,6 +1459,7 @@ pub static CONFIG_DATA: &str = "
 	    \"9675\": [1, 87],
 	    \"9679\": [1, 81],
 	    \"9700\": [1, 105],
+	    \"9701\": [1, 95],
 	    \"9824\": [1, 65],
 	    \"9825\": [1, 211],
 	    \"9826\": [1, 218],
@@ -896,6 +1468,7 @@ pub static CONFIG_DATA: &str = "
 	    \"9829\": [1, 83],
 	    \"9830\": [1, 90],
 	    \"9831\": [1, 216],
+	    \"10003\": [2, 122],
 	    \"129904\": [1, 84],
 	    \"129905\": [1, 71],
 	    \"129906\": [1, 66],
@@ -912,19 +1485,23 @@ pub static CONFIG_DATA: &str = "
 	    \"129917\": [1, 79],
 	    \"129918\": [1, 80],
 	    \"129919\": [1, 122],
+	    \"129922\": [1, 119],
+	    \"129923\": [1, 120],
 	    \"129925\": [1, 239],
 	    \"129926\": [1, 228],
+	    \"129927\": [1, 106],
+	    \"129928\": [1, 118],
 	    \"129929\": [1, 245],
 	    \"129930\": [1, 244],
 	    \"129931\": [1, 229],
-	    \"129932\": [1, 204],
+	    \"129932\": [1, 92],
 	    \"129933\": [1, 207],
 	    \"129934\": [1, 208],
-	    \"129935\": [1, 250],
+	    \"129935\": [1, 104],
 	    \"129936\": [1, 212],
 	    \"129937\": [1, 199],
 	    \"129939\": [1, 221],
-	    \"129940\": [1, 299],
+	    \"129940\": [1, 220],
 	    \"129941\": [1, 217],
 	    \"129942\": [1, 197],
 	    \"129943\": [1, 196],
@@ -994,9 +1571,9 @@ pub static CONFIG_DATA_AS_BLOB: &[u8] = &[
 ];
 
 /// C64 character maps
-
+///
 /// This duplicates the data in the CONFIG_DATA
-
+///
 /// The configuration loading and unloading needs a refactor to be
 /// more dynamic
 /// In the meantime, this gets basic mapping from PETSCII to Unicode
@@ -1005,6 +1582,405 @@ pub static C64_PETSCII_MAP: &str = "
 {
   \"version\": \"0.2.0\",
 
+  \"c64_petscii_code_aliases\":
+  {
+    \"192\": 96,
+    \"193\": 97,
+    \"194\": 98,
+    \"195\": 99,
+    \"196\": 100,
+    \"197\": 101,
+    \"198\": 102,
+    \"199\": 103,
+    \"200\": 104,
+    \"201\": 105,
+    \"202\": 106,
+    \"203\": 107,
+    \"204\": 108,
+    \"205\": 109,
+    \"206\": 110,
+    \"207\": 111,
+    \"208\": 112,
+    \"209\": 113,
+    \"210\": 114,
+    \"211\": 115,
+    \"212\": 116,
+    \"213\": 117,
+    \"214\": 118,
+    \"215\": 119,
+    \"216\": 120,
+    \"217\": 121,
+    \"218\": 122,
+    \"219\": 123,
+    \"220\": 124,
+    \"221\": 125,
+    \"222\": 126,
+    \"223\": 127,
+    \"224\": 160,
+    \"225\": 161,
+    \"226\": 162,
+    \"227\": 163,
+    \"228\": 164,
+    \"229\": 165,
+    \"230\": 166,
+    \"231\": 167,
+    \"232\": 168,
+    \"233\": 169,
+    \"234\": 170,
+    \"235\": 171,
+    \"236\": 172,
+    \"237\": 173,
+    \"238\": 174,
+    \"239\": 175,
+    \"240\": 176,
+    \"241\": 177,
+    \"242\": 178,
+    \"243\": 179,
+    \"244\": 180,
+    \"245\": 181,
+    \"246\": 182,
+    \"247\": 183,
+    \"248\": 184,
+    \"249\": 185,
+    \"250\": 186,
+    \"251\": 187,
+    \"252\": 188,
+    \"253\": 189,
+    \"254\": 190,
+    \"255\": 126
+  },
+
+  \"c64_petscii_unshifted_code_names\":
+  {
+    \"3\": \"STOP\",
+    \"5\": \"WHITE\",
+    \"8\": \"DISABLE SHIFT-COMMODORE\",
+    \"9\": \"ENABLE SHIFT-COMMODORE\",
+    \"10\": \"LINE FEED\",
+    \"13\": \"RETURN\",
+    \"14\": \"LOWERCASE\",
+    \"15\": \"FLASH ON\",
+    \"17\": \"CURSOR DOWN\",
+    \"18\": \"REVERSE ON\",
+    \"19\": \"HOME\",
+    \"20\": \"DELETE\",
+    \"28\": \"RED\",
+    \"29\": \"CURSOR RIGHT\",
+    \"30\": \"GREEN\",
+    \"31\": \"BLUE\",
+    \"32\": \"SPACE\",
+    \"33\": \"EXCLAMATION MARK\",
+    \"34\": \"QUOTATION MARK\",
+    \"35\": \"NUMBER SIGN\",
+    \"36\": \"DOLLAR SIGN\",
+    \"37\": \"PERCENT SIGN\",
+    \"38\": \"AMPERSAND\",
+    \"39\": \"APOSTROPHE\",
+    \"40\": \"LEFT PARENTHESIS\",
+    \"41\": \"RIGHT PARENTHESIS\",
+    \"42\": \"ASTERISK\",
+    \"43\": \"PLUS SIGN\",
+    \"44\": \"COMMA\",
+    \"45\": \"HYPHEN-MINUS\",
+    \"46\": \"FULL STOP\",
+    \"47\": \"SOLIDUS\",
+    \"48\": \"DIGIT ZERO\",
+    \"49\": \"DIGIT ONE\",
+    \"50\": \"DIGIT TWO\",
+    \"51\": \"DIGIT THREE\",
+    \"52\": \"DIGIT FOUR\",
+    \"53\": \"DIGIT FIVE\",
+    \"54\": \"DIGIT SIX\",
+    \"55\": \"DIGIT SEVEN\",
+    \"56\": \"DIGIT EIGHT\",
+    \"57\": \"DIGIT NINE\",
+    \"58\": \"COLON\",
+    \"59\": \"SEMICOLON\",
+    \"60\": \"LESS-THAN SIGN\",
+    \"61\": \"EQUALS SIGN\",
+    \"62\": \"GREATER-THAN SIGN\",
+    \"63\": \"QUESTION MARK\",
+    \"64\": \"COMMERCIAL AT\",
+    \"65\": \"LATIN CAPITAL LETTER A\",
+    \"66\": \"LATIN CAPITAL LETTER B\",
+    \"67\": \"LATIN CAPITAL LETTER C\",
+    \"68\": \"LATIN CAPITAL LETTER D\",
+    \"69\": \"LATIN CAPITAL LETTER E\",
+    \"70\": \"LATIN CAPITAL LETTER F\",
+    \"71\": \"LATIN CAPITAL LETTER G\",
+    \"72\": \"LATIN CAPITAL LETTER H\",
+    \"73\": \"LATIN CAPITAL LETTER I\",
+    \"74\": \"LATIN CAPITAL LETTER J\",
+    \"75\": \"LATIN CAPITAL LETTER K\",
+    \"76\": \"LATIN CAPITAL LETTER L\",
+    \"77\": \"LATIN CAPITAL LETTER M\",
+    \"78\": \"LATIN CAPITAL LETTER N\",
+    \"79\": \"LATIN CAPITAL LETTER O\",
+    \"80\": \"LATIN CAPITAL LETTER P\",
+    \"81\": \"LATIN CAPITAL LETTER Q\",
+    \"82\": \"LATIN CAPITAL LETTER R\",
+    \"83\": \"LATIN CAPITAL LETTER S\",
+    \"84\": \"LATIN CAPITAL LETTER T\",
+    \"85\": \"LATIN CAPITAL LETTER U\",
+    \"86\": \"LATIN CAPITAL LETTER V\",
+    \"87\": \"LATIN CAPITAL LETTER W\",
+    \"88\": \"LATIN CAPITAL LETTER X\",
+    \"89\": \"LATIN CAPITAL LETTER Y\",
+    \"90\": \"LATIN CAPITAL LETTER Z\",
+    \"91\": \"LEFT SQUARE BRACKET\",
+    \"92\": \"POUND SIGN\",
+    \"93\": \"RIGHT SQUARE BRACKET\",
+    \"94\": \"UPWARDS ARROW\",
+    \"95\": \"LEFTWARDS ARROW\",
+    \"96\": \"HORIZONTAL ONE EIGHTH BLOCK-5 (CBM)\",
+    \"97\": \"BLACK SPADE SUIT (CBM)\",
+    \"98\": \"VERTICAL ONE EIGHTH BLOCK-4 (CBM)\",
+    \"99\": \"HORIZONTAL ONE EIGHTH BLOCK-4 (CBM)\",
+    \"100\": \"HORIZONTAL ONE EIGHTH BLOCK-3 (CBM)\",
+    \"101\": \"HORIZONTAL ONE EIGHTH BLOCK-2 (CBM)\",
+    \"102\": \"HORIZONTAL ONE EIGHTH BLOCK-6 (CBM)\",
+    \"103\": \"VERTICAL ONE EIGHTH BLOCK-3 (CBM)\",
+    \"104\": \"VERTICAL ONE EIGHTH BLOCK-6 (CBM)\",
+    \"105\": \"BOX DRAWINGS LIGHT ARC DOWN AND LEFT (CBM)\",
+    \"106\": \"BOX DRAWINGS LIGHT ARC UP AND RIGHT (CBM)\",
+    \"107\": \"BOX DRAWINGS LIGHT ARC UP AND LEFT (CBM)\",
+    \"108\": \"LEFT AND LOWER ONE EIGHTH BLOCK (CBM)\",
+    \"109\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER LEFT TO LOWER RIGHT (CBM)\",
+    \"110\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER RIGHT TO LOWER LEFT (CBM)\",
+    \"111\": \"LEFT AND UPPER ONE EIGHTH BLOCK (CBM)\",
+    \"112\": \"RIGHT AND UPPER ONE EIGHTH BLOCK (CBM)\",
+    \"113\": \"BLACK CIRCLE (CBM)\",
+    \"114\": \"HORIZONTAL ONE EIGHTH BLOCK-7 (CBM)\",
+    \"115\": \"BLACK HEART SUIT (CBM)\",
+    \"116\": \"VERTICAL ONE EIGHTH BLOCK-2 (CBM)\",
+    \"117\": \"BOX DRAWINGS LIGHT ARC DOWN AND RIGHT (CBM)\",
+    \"118\": \"BOX DRAWINGS LIGHT DIAGONAL CROSS (CBM)\",
+    \"119\": \"WHITE CIRCLE (CBM)\",
+    \"120\": \"BLACK CLUB SUIT (CBM)\",
+    \"121\": \"VERTICAL ONE EIGHTH BLOCK-7 (CBM)\",
+    \"122\": \"BLACK DIAMOND SUIT (CBM)\",
+    \"123\": \"BOX DRAWINGS LIGHT VERTICAL AND HORIZONTAL (CBM)\",
+    \"124\": \"LEFT HALF MEDIUM SHADE (CBM)\",
+    \"125\": \"VERTICAL ONE EIGHTH BLOCK-5 (CBM)\",
+    \"126\": \"MEDIUM SHADE (CBM)\",
+    \"127\": \"BLACK UPPER RIGHT TRIANGLE (CBM)\",
+    \"129\": \"ORANGE\",
+    \"133\": \"F1\",
+    \"134\": \"F3\",
+    \"135\": \"F5\",
+    \"136\": \"F7\",
+    \"137\": \"F2\",
+    \"138\": \"F4\",
+    \"139\": \"F6\",
+    \"140\": \"F8\",
+    \"141\": \"SHIFT RETURN\",
+    \"142\": \"UPPERCASE\",
+    \"143\": \"FLASH OFF\",
+    \"144\": \"BLACK\",
+    \"145\": \"CURSOR UP\",
+    \"146\": \"REVERSE OFF\",
+    \"147\": \"CLEAR\",
+    \"148\": \"INSERT\",
+    \"149\": \"BROWN\",
+    \"150\": \"LIGHT RED\",
+    \"151\": \"DARK GREY\",
+    \"152\": \"GREY\",
+    \"153\": \"LIGHT GREEN\",
+    \"154\": \"LIGHT BLUE\",
+    \"155\": \"LIGHT GREY\",
+    \"156\": \"PURPLE\",
+    \"157\": \"CURSOR LEFT\",
+    \"158\": \"YELLOW\",
+    \"159\": \"CYAN\",
+    \"160\": \"SHIFTED SPACE\",
+    \"161\": \"LEFT HALF BLOCK (CBM)\",
+    \"162\": \"LOWER HALF BLOCK (CBM)\",
+    \"163\": \"UPPER ONE EIGHTH BLOCK (CBM)\",
+    \"164\": \"LOWER ONE EIGHTH BLOCK (CBM)\",
+    \"165\": \"LEFT ONE EIGHTH BLOCK (CBM)\",
+    \"166\": \"MEDIUM SHADE (CBM)\",
+    \"167\": \"RIGHT ONE EIGHTH BLOCK (CBM)\",
+    \"168\": \"LOWER HALF MEDIUM SHADE (CBM)\",
+    \"169\": \"BLACK UPPER LEFT TRIANGLE (CBM)\",
+    \"170\": \"RIGHT ONE QUARTER BLOCK (CBM)\",
+    \"171\": \"BOX DRAWINGS LIGHT VERTICAL AND RIGHT (CBM)\",
+    \"172\": \"QUADRANT LOWER RIGHT (CBM)\",
+    \"173\": \"BOX DRAWINGS LIGHT UP AND RIGHT (CBM)\",
+    \"174\": \"BOX DRAWINGS LIGHT DOWN AND LEFT (CBM)\",
+    \"175\": \"LOWER ONE QUARTER BLOCK (CBM)\",
+    \"176\": \"BOX DRAWINGS LIGHT DOWN AND RIGHT (CBM)\",
+    \"177\": \"BOX DRAWINGS LIGHT UP AND HORIZONTAL (CBM)\",
+    \"178\": \"BOX DRAWINGS LIGHT DOWN AND HORIZONTAL (CBM)\",
+    \"179\": \"BOX DRAWINGS LIGHT VERTICAL AND LEFT (CBM)\",
+    \"180\": \"LEFT ONE QUARTER BLOCK (CBM)\",
+    \"181\": \"LEFT THREE EIGHTHS BLOCK (CBM)\",
+    \"182\": \"RIGHT THREE EIGHTHS BLOCK (CBM)\",
+    \"183\": \"UPPER ONE QUARTER BLOCK (CBM)\",
+    \"184\": \"UPPER THREE EIGHTHS BLOCK (CBM)\",
+    \"185\": \"LOWER THREE EIGHTHS BLOCK (CBM)\",
+    \"186\": \"RIGHT AND LOWER ONE EIGHTH BLOCK (CBM)\",
+    \"187\": \"QUADRANT LOWER LEFT (CBM)\",
+    \"188\": \"QUADRANT UPPER RIGHT (CBM)\",
+    \"189\": \"BOX DRAWINGS LIGHT UP AND LEFT (CBM)\",
+    \"190\": \"QUADRANT UPPER LEFT (CBM)\",
+    \"191\": \"QUADRANT UPPER LEFT AND LOWER RIGHT (CBM)\",
+    \"192\": \"HORIZONTAL ONE EIGHTH BLOCK-5 (CBM)\",
+    \"193\": \"BLACK SPADE SUIT (CBM)\",
+    \"194\": \"VERTICAL ONE EIGHTH BLOCK-4 (CBM)\",
+    \"195\": \"HORIZONTAL ONE EIGHTH BLOCK-4 (CBM)\",
+    \"196\": \"HORIZONTAL ONE EIGHTH BLOCK-3 (CBM)\",
+    \"197\": \"HORIZONTAL ONE EIGHTH BLOCK-2 (CBM)\",
+    \"198\": \"HORIZONTAL ONE EIGHTH BLOCK-6 (CBM)\",
+    \"199\": \"VERTICAL ONE EIGHTH BLOCK-3 (CBM)\",
+    \"200\": \"VERTICAL ONE EIGHTH BLOCK-6 (CBM)\",
+    \"201\": \"BOX DRAWINGS LIGHT ARC DOWN AND LEFT (CBM)\",
+    \"202\": \"BOX DRAWINGS LIGHT ARC UP AND RIGHT (CBM)\",
+    \"203\": \"BOX DRAWINGS LIGHT ARC UP AND LEFT (CBM)\",
+    \"204\": \"LEFT AND LOWER ONE EIGHTH BLOCK (CBM)\",
+    \"205\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER LEFT TO LOWER RIGHT (CBM)\",
+    \"206\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER RIGHT TO LOWER LEFT (CBM)\",
+    \"207\": \"LEFT AND UPPER ONE EIGHTH BLOCK (CBM)\",
+    \"208\": \"RIGHT AND UPPER ONE EIGHTH BLOCK (CBM)\",
+    \"209\": \"BLACK CIRCLE (CBM)\",
+    \"210\": \"HORIZONTAL ONE EIGHTH BLOCK-7 (CBM)\",
+    \"211\": \"BLACK HEART SUIT (CBM)\",
+    \"212\": \"VERTICAL ONE EIGHTH BLOCK-2 (CBM)\",
+    \"213\": \"BOX DRAWINGS LIGHT ARC DOWN AND RIGHT (CBM)\",
+    \"214\": \"BOX DRAWINGS LIGHT DIAGONAL CROSS (CBM)\",
+    \"215\": \"WHITE CIRCLE (CBM)\",
+    \"216\": \"BLACK CLUB SUIT (CBM)\",
+    \"217\": \"VERTICAL ONE EIGHTH BLOCK-7 (CBM)\",
+    \"218\": \"BLACK DIAMOND SUIT (CBM)\",
+    \"219\": \"BOX DRAWINGS LIGHT VERTICAL AND HORIZONTAL (CBM)\",
+    \"220\": \"LEFT HALF MEDIUM SHADE (CBM)\",
+    \"221\": \"VERTICAL ONE EIGHTH BLOCK-5 (CBM)\",
+    \"222\": \"MEDIUM SHADE (CBM)\",
+    \"223\": \"BLACK UPPER RIGHT TRIANGLE (CBM)\",
+    \"224\": \"SHIFTED SPACE\",
+    \"225\": \"LEFT HALF BLOCK (CBM)\",
+    \"226\": \"LOWER HALF BLOCK (CBM)\",
+    \"227\": \"UPPER ONE EIGHTH BLOCK (CBM)\",
+    \"228\": \"LOWER ONE EIGHTH BLOCK (CBM)\",
+    \"229\": \"LEFT ONE EIGHTH BLOCK (CBM)\",
+    \"230\": \"MEDIUM SHADE (CBM)\",
+    \"231\": \"RIGHT ONE EIGHTH BLOCK (CBM)\",
+    \"232\": \"LOWER HALF MEDIUM SHADE (CBM)\",
+    \"233\": \"BLACK UPPER LEFT TRIANGLE (CBM)\",
+    \"234\": \"RIGHT ONE QUARTER BLOCK (CBM)\",
+    \"235\": \"BOX DRAWINGS LIGHT VERTICAL AND RIGHT (CBM)\",
+    \"236\": \"QUADRANT LOWER RIGHT (CBM)\",
+    \"237\": \"BOX DRAWINGS LIGHT UP AND RIGHT (CBM)\",
+    \"238\": \"BOX DRAWINGS LIGHT DOWN AND LEFT (CBM)\",
+    \"239\": \"LOWER ONE QUARTER BLOCK (CBM)\",
+    \"240\": \"BOX DRAWINGS LIGHT DOWN AND RIGHT (CBM)\",
+    \"241\": \"BOX DRAWINGS LIGHT UP AND HORIZONTAL (CBM)\",
+    \"242\": \"BOX DRAWINGS LIGHT DOWN AND HORIZONTAL (CBM)\",
+    \"243\": \"BOX DRAWINGS LIGHT VERTICAL AND LEFT (CBM)\",
+    \"244\": \"LEFT ONE QUARTER BLOCK (CBM)\",
+    \"245\": \"LEFT THREE EIGHTHS BLOCK (CBM)\",
+    \"246\": \"RIGHT THREE EIGHTHS BLOCK (CBM)\",
+    \"247\": \"UPPER ONE QUARTER BLOCK (CBM)\",
+    \"248\": \"UPPER THREE EIGHTHS BLOCK (CBM)\",
+    \"249\": \"LOWER THREE EIGHTHS BLOCK (CBM)\",
+    \"250\": \"RIGHT AND LOWER ONE EIGHTH BLOCK (CBM)\",
+    \"251\": \"QUADRANT LOWER LEFT (CBM)\",
+    \"252\": \"QUADRANT UPPER RIGHT (CBM)\",
+    \"253\": \"BOX DRAWINGS LIGHT UP AND LEFT (CBM)\",
+    \"254\": \"QUADRANT UPPER LEFT (CBM)\",
+    \"255\": \"MEDIUM SHADE (CBM)\"
+  },
+
+  \"c64_petscii_shifted_code_names\":
+  {
+    \"65\": \"LATIN SMALL LETTER A\",
+    \"66\": \"LATIN SMALL LETTER B\",
+    \"67\": \"LATIN SMALL LETTER C\",
+    \"68\": \"LATIN SMALL LETTER D\",
+    \"69\": \"LATIN SMALL LETTER E\",
+    \"70\": \"LATIN SMALL LETTER F\",
+    \"71\": \"LATIN SMALL LETTER G\",
+    \"72\": \"LATIN SMALL LETTER H\",
+    \"73\": \"LATIN SMALL LETTER I\",
+    \"74\": \"LATIN SMALL LETTER J\",
+    \"75\": \"LATIN SMALL LETTER K\",
+    \"76\": \"LATIN SMALL LETTER L\",
+    \"77\": \"LATIN SMALL LETTER M\",
+    \"78\": \"LATIN SMALL LETTER N\",
+    \"79\": \"LATIN SMALL LETTER O\",
+    \"80\": \"LATIN SMALL LETTER P\",
+    \"81\": \"LATIN SMALL LETTER Q\",
+    \"82\": \"LATIN SMALL LETTER R\",
+    \"83\": \"LATIN SMALL LETTER S\",
+    \"84\": \"LATIN SMALL LETTER T\",
+    \"85\": \"LATIN SMALL LETTER U\",
+    \"86\": \"LATIN SMALL LETTER V\",
+    \"87\": \"LATIN SMALL LETTER W\",
+    \"88\": \"LATIN SMALL LETTER X\",
+    \"89\": \"LATIN SMALL LETTER Y\",
+    \"90\": \"LATIN SMALL LETTER Z\",
+    \"97\": \"LATIN CAPITAL LETTER A\",
+    \"98\": \"LATIN CAPITAL LETTER B\",
+    \"99\": \"LATIN CAPITAL LETTER C\",
+    \"100\": \"LATIN CAPITAL LETTER D\",
+    \"101\": \"LATIN CAPITAL LETTER E\",
+    \"102\": \"LATIN CAPITAL LETTER F\",
+    \"103\": \"LATIN CAPITAL LETTER G\",
+    \"104\": \"LATIN CAPITAL LETTER H\",
+    \"105\": \"LATIN CAPITAL LETTER I\",
+    \"106\": \"LATIN CAPITAL LETTER J\",
+    \"107\": \"LATIN CAPITAL LETTER K\",
+    \"108\": \"LATIN CAPITAL LETTER L\",
+    \"109\": \"LATIN CAPITAL LETTER M\",
+    \"110\": \"LATIN CAPITAL LETTER N\",
+    \"111\": \"LATIN CAPITAL LETTER O\",
+    \"112\": \"LATIN CAPITAL LETTER P\",
+    \"113\": \"LATIN CAPITAL LETTER Q\",
+    \"114\": \"LATIN CAPITAL LETTER R\",
+    \"115\": \"LATIN CAPITAL LETTER S\",
+    \"116\": \"LATIN CAPITAL LETTER T\",
+    \"117\": \"LATIN CAPITAL LETTER U\",
+    \"118\": \"LATIN CAPITAL LETTER V\",
+    \"119\": \"LATIN CAPITAL LETTER W\",
+    \"120\": \"LATIN CAPITAL LETTER X\",
+    \"121\": \"LATIN CAPITAL LETTER Y\",
+    \"122\": \"LATIN CAPITAL LETTER Z\",
+    \"126\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE (CBM)\",
+    \"127\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE LEFT AND MIDDLE RIGHT TO LOWER CENTRE (CBM)\",
+    \"169\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE TO MIDDLE LEFT (CBM)\",
+    \"186\": \"CHECK MARK (CBM)\",
+    \"193\": \"LATIN CAPITAL LETTER A\",
+    \"194\": \"LATIN CAPITAL LETTER B\",
+    \"195\": \"LATIN CAPITAL LETTER C\",
+    \"196\": \"LATIN CAPITAL LETTER D\",
+    \"197\": \"LATIN CAPITAL LETTER E\",
+    \"198\": \"LATIN CAPITAL LETTER F\",
+    \"199\": \"LATIN CAPITAL LETTER G\",
+    \"200\": \"LATIN CAPITAL LETTER H\",
+    \"201\": \"LATIN CAPITAL LETTER I\",
+    \"202\": \"LATIN CAPITAL LETTER J\",
+    \"203\": \"LATIN CAPITAL LETTER K\",
+    \"204\": \"LATIN CAPITAL LETTER L\",
+    \"205\": \"LATIN CAPITAL LETTER M\",
+    \"206\": \"LATIN CAPITAL LETTER N\",
+    \"207\": \"LATIN CAPITAL LETTER O\",
+    \"208\": \"LATIN CAPITAL LETTER P\",
+    \"209\": \"LATIN CAPITAL LETTER Q\",
+    \"210\": \"LATIN CAPITAL LETTER R\",
+    \"211\": \"LATIN CAPITAL LETTER S\",
+    \"212\": \"LATIN CAPITAL LETTER T\",
+    \"213\": \"LATIN CAPITAL LETTER U\",
+    \"214\": \"LATIN CAPITAL LETTER V\",
+    \"215\": \"LATIN CAPITAL LETTER W\",
+    \"216\": \"LATIN CAPITAL LETTER X\",
+    \"217\": \"LATIN CAPITAL LETTER Y\",
+    \"218\": \"LATIN CAPITAL LETTER Z\",
+    \"222\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE (CBM)\",
+    \"223\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE LEFT AND MIDDLE RIGHT TO LOWER CENTRE (CBM)\",
+    \"233\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE TO MIDDLE LEFT (CBM)\",
+    \"250\": \"CHECK MARK (CBM)\",
+    \"255\": \"BOX DRAWINGS LIGHT DIAGONAL UPPER CENTRE TO MIDDLE RIGHT TO LOWER CENTRE (CBM)\"
+  },
+
   \"c64_screen_codes_set_1_to_petscii_codes\":
   {
     \"0\": [0, 64],
@@ -1501,32 +2477,6 @@ pub static C64_PETSCII_MAP: &str = "
   \"c64_petscii_shifted_codes_to_screen_codes\": {
     \"10\": [3, 10],
     \"13\": [3, 13],
-    \"65\": [2, 1],
-    \"66\": [2, 2],
-    \"67\": [2, 3],
-    \"68\": [2, 4],
-    \"69\": [2, 5],
-    \"70\": [2, 6],
-    \"71\": [2, 7],
-    \"72\": [2, 8],
-    \"73\": [2, 9],
-    \"74\": [2, 10],
-    \"75\": [2, 11],
-    \"76\": [2, 12],
-    \"77\": [2, 13],
-    \"78\": [2, 14],
-    \"79\": [2, 15],
-    \"80\": [2, 16],
-    \"81\": [2, 17],
-    \"82\": [2, 18],
-    \"83\": [2, 19],
-    \"84\": [2, 20],
-    \"85\": [2, 21],
-    \"86\": [2, 22],
-    \"87\": [2, 23],
-    \"88\": [2, 24],
-    \"89\": [2, 25],
-    \"90\": [2, 26],
     \"32\": [1, 32],
     \"33\": [1, 33],
     \"34\": [1, 34],
@@ -1558,7 +2508,167 @@ pub static C64_PETSCII_MAP: &str = "
     \"60\": [1, 60],
     \"61\": [1, 61],
     \"62\": [1, 62],
-    \"63\": [1, 63]
+    \"63\": [1, 63],
+    \"64\": [1, 0],
+    \"65\": [2, 1],
+    \"66\": [2, 2],
+    \"67\": [2, 3],
+    \"68\": [2, 4],
+    \"69\": [2, 5],
+    \"70\": [2, 6],
+    \"71\": [2, 7],
+    \"72\": [2, 8],
+    \"73\": [2, 9],
+    \"74\": [2, 10],
+    \"75\": [2, 11],
+    \"76\": [2, 12],
+    \"77\": [2, 13],
+    \"78\": [2, 14],
+    \"79\": [2, 15],
+    \"80\": [2, 16],
+    \"81\": [2, 17],
+    \"82\": [2, 18],
+    \"83\": [2, 19],
+    \"84\": [2, 20],
+    \"85\": [2, 21],
+    \"86\": [2, 22],
+    \"87\": [2, 23],
+    \"88\": [2, 24],
+    \"89\": [2, 25],
+    \"90\": [2, 26],
+    \"91\": [1, 27],
+    \"92\": [1, 28],
+    \"93\": [1, 29],
+    \"94\": [1, 30],
+    \"95\": [1, 31],
+    \"96\": [1, 64],
+    \"97\": [2, 65],
+    \"98\": [2, 66],
+    \"99\": [2, 67],
+    \"100\": [2, 68],
+    \"101\": [2, 69],
+    \"102\": [2, 70],
+    \"103\": [2, 71],
+    \"104\": [2, 72],
+    \"105\": [2, 73],
+    \"106\": [2, 74],
+    \"107\": [2, 75],
+    \"108\": [2, 76],
+    \"109\": [2, 77],
+    \"110\": [2, 78],
+    \"111\": [2, 79],
+    \"112\": [2, 80],
+    \"113\": [2, 81],
+    \"114\": [2, 82],
+    \"115\": [2, 83],
+    \"116\": [2, 84],
+    \"117\": [2, 85],
+    \"118\": [2, 86],
+    \"119\": [2, 87],
+    \"120\": [2, 88],
+    \"121\": [2, 89],
+    \"122\": [2, 90],
+    \"123\": [1, 91],
+    \"124\": [1, 92],
+    \"125\": [1, 93],
+    \"126\": [2, 94],
+    \"127\": [2, 95],
+    \"160\": [1, 96],
+    \"161\": [1, 97],
+    \"162\": [1, 98],
+    \"163\": [1, 99],
+    \"164\": [1, 100],
+    \"165\": [1, 101],
+    \"166\": [1, 102],
+    \"167\": [1, 103],
+    \"168\": [1, 104],
+    \"169\": [2, 105],
+    \"170\": [1, 106],
+    \"171\": [1, 107],
+    \"172\": [1, 108],
+    \"173\": [1, 109],
+    \"174\": [1, 110],
+    \"175\": [1, 111],
+    \"176\": [1, 112],
+    \"177\": [1, 113],
+    \"178\": [1, 114],
+    \"179\": [1, 115],
+    \"180\": [1, 116],
+    \"181\": [1, 117],
+    \"182\": [1, 118],
+    \"183\": [1, 119],
+    \"184\": [1, 120],
+    \"185\": [1, 121],
+    \"186\": [2, 122],
+    \"187\": [1, 123],
+    \"188\": [1, 124],
+    \"189\": [1, 125],
+    \"190\": [1, 126],
+    \"191\": [1, 127],
+    \"192\": [1, 64],
+    \"193\": [2, 65],
+    \"194\": [2, 66],
+    \"195\": [2, 67],
+    \"196\": [2, 68],
+    \"197\": [2, 69],
+    \"198\": [2, 70],
+    \"199\": [2, 71],
+    \"200\": [2, 72],
+    \"201\": [2, 73],
+    \"202\": [2, 74],
+    \"203\": [2, 75],
+    \"204\": [2, 76],
+    \"205\": [2, 77],
+    \"206\": [2, 78],
+    \"207\": [2, 79],
+    \"208\": [2, 80],
+    \"209\": [2, 81],
+    \"210\": [2, 82],
+    \"211\": [2, 83],
+    \"212\": [2, 84],
+    \"213\": [2, 85],
+    \"214\": [2, 86],
+    \"215\": [2, 87],
+    \"216\": [2, 88],
+    \"217\": [2, 89],
+    \"218\": [2, 90],
+    \"219\": [1, 91],
+    \"220\": [1, 92],
+    \"221\": [1, 93],
+    \"222\": [2, 94],
+    \"223\": [2, 95],
+    \"224\": [1, 96],
+    \"225\": [1, 97],
+    \"226\": [1, 98],
+    \"227\": [1, 99],
+    \"228\": [1, 100],
+    \"229\": [1, 101],
+    \"230\": [1, 102],
+    \"231\": [1, 103],
+    \"232\": [1, 104],
+    \"233\": [2, 105],
+    \"234\": [1, 106],
+    \"235\": [1, 107],
+    \"236\": [1, 108],
+    \"237\": [1, 109],
+    \"238\": [1, 110],
+    \"239\": [1, 111],
+    \"240\": [1, 112],
+    \"241\": [1, 113],
+    \"242\": [1, 114],
+    \"243\": [1, 115],
+    \"244\": [1, 116],
+    \"245\": [1, 117],
+    \"246\": [1, 118],
+    \"247\": [1, 119],
+    \"248\": [1, 120],
+    \"249\": [1, 121],
+    \"250\": [2, 122],
+    \"251\": [1, 123],
+    \"252\": [1, 124],
+    \"253\": [1, 125],
+    \"254\": [1, 126],
+    \"255\": [2, 94]
   },
 
   \"c64_screen_codes_set_1_to_unicode_codes\":
@@ -1655,16 +2765,21 @@ pub static C64_PETSCII_MAP: &str = "
      \"89\": 129909,
      \"90\": 9830,
      \"91\": 9532,
+     \"92\": 129932,
      \"93\": 129907,
      \"94\": 9618,
+     \"95\": 9701,
      \"96\": 32,
      \"97\": 9612,
      \"98\": 9604,
      \"99\": 9620,
      \"100\": 9601,
-     \"101\": 9614,
+     \"101\": 9615,
      \"102\": 9618,
+     \"103\": 9621,
+     \"104\": 129935,
      \"105\": 9700,
+     \"106\": 129927,
      \"107\": 9500,
      \"108\": 9623,
      \"109\": 9492,
@@ -1676,6 +2791,9 @@ pub static C64_PETSCII_MAP: &str = "
      \"115\": 9508,
      \"116\": 9614,
      \"117\": 9613,
+     \"118\": 129928,
+     \"119\": 129922,
+     \"120\": 129923,
      \"121\": 9603,
      \"122\": 129919,
      \"123\": 9622,
@@ -1700,7 +2818,7 @@ pub static C64_PETSCII_MAP: &str = "
      \"199\": 129937,
      \"194\": 129938,
      \"221\": 129939,
-     \"299\": 129940,
+     \"220\": 129940,
      \"217\": 129941,
      \"197\": 129942,
      \"196\": 129943,
@@ -1737,12 +2855,39 @@ pub static C64_PETSCII_MAP: &str = "
     \"24\": 120,
     \"25\": 121,
     \"26\": 122,
+    \"65\": 65,
+    \"66\": 66,
+    \"67\": 67,
+    \"68\": 68,
+    \"69\": 69,
+    \"70\": 70,
+    \"71\": 71,
+    \"72\": 72,
+    \"73\": 73,
+    \"74\": 74,
+    \"75\": 75,
+    \"76\": 76,
+    \"77\": 77,
+    \"78\": 78,
+    \"79\": 79,
+    \"80\": 80,
+    \"81\": 81,
+    \"82\": 82,
+    \"83\": 83,
+    \"84\": 84,
+    \"85\": 85,
+    \"86\": 86,
+    \"87\": 87,
+    \"88\": 88,
+    \"89\": 89,
+    \"90\": 90,
     \"94\": 129957,
     \"95\": 129960,
     \"222\": 129958,
     \"223\": 129959,
     \"233\": 129961,
     \"105\": 129962,
+    \"122\": 10003,
     \"250\": 129963
   },
   \"c64_screen_codes_set_3_to_unicode_codes\":
@@ -1753,6 +2898,7 @@ pub static C64_PETSCII_MAP: &str = "
   \"unicode_codes_to_c64_screen_codes\":
   {
     \"10\": [3, 10],
+    \"13\": [3, 13],
     \"32\": [1, 96],
     \"33\": [1, 33],
     \"34\": [1, 34],
@@ -1866,8 +3012,10 @@ pub static C64_PETSCII_MAP: &str = "
     \"9612\": [1, 97],
     \"9613\": [1, 117],
     \"9614\": [1, 116],
+    \"9615\": [1, 101],
     \"9618\": [1, 102],
     \"9620\": [1, 99],
+    \"9621\": [1, 103],
     \"9622\": [1, 123],
     \"9623\": [1, 108],
     \"9624\": [1, 126],
@@ -1876,6 +3024,7 @@ pub static C64_PETSCII_MAP: &str = "
     \"9675\": [1, 87],
     \"9679\": [1, 81],
     \"9700\": [1, 105],
+    \"9701\": [1, 95],
     \"9824\": [1, 65],
     \"9825\": [1, 211],
     \"9826\": [1, 218],
@@ -1884,6 +3033,7 @@ pub static C64_PETSCII_MAP: &str = "
     \"9829\": [1, 83],
     \"9830\": [1, 90],
     \"9831\": [1, 216],
+    \"10003\": [2, 122],
     \"129904\": [1, 84],
     \"129905\": [1, 71],
     \"129906\": [1, 66],
@@ -1900,19 +3050,23 @@ pub static C64_PETSCII_MAP: &str = "
     \"129917\": [1, 79],
     \"129918\": [1, 80],
     \"129919\": [1, 122],
+    \"129922\": [1, 119],
+    \"129923\": [1, 120],
     \"129925\": [1, 239],
     \"129926\": [1, 228],
+    \"129927\": [1, 106],
+    \"129928\": [1, 118],
     \"129929\": [1, 245],
     \"129930\": [1, 244],
     \"129931\": [1, 229],
-    \"129932\": [1, 204],
+    \"129932\": [1, 92],
     \"129933\": [1, 207],
     \"129934\": [1, 208],
-    \"129935\": [1, 250],
+    \"129935\": [1, 104],
     \"129936\": [1, 212],
     \"129937\": [1, 199],
     \"129939\": [1, 221],
-    \"129940\": [1, 299],
+    \"129940\": [1, 220],
     \"129941\": [1, 217],
     \"129942\": [1, 197],
     \"129943\": [1, 196],
@@ -1930,3 +3084,2443 @@ pub static C64_PETSCII_MAP: &str = "
   }
 }
 ";
+
+/// TI-99/4A character maps
+///
+/// The TI-99/4A character codes from 32 to 126 match ASCII.  Code 30
+/// is the cursor and code 31 is the "edge" character used to fill
+/// the screen border.
+///
+/// custom_character_area is the first character code, last character
+/// code and first Unicode Private Use Area code point used for the
+/// user-definable characters.
+pub static TI99_CHARACTER_MAP: &str = "
+{
+  \"version\": \"0.1.0\",
+
+  \"ti99_character_codes_to_unicode_codes\":
+  {
+    \"13\": 13,
+    \"30\": 9608,
+    \"31\": 32,
+    \"32\": 32,
+    \"33\": 33,
+    \"34\": 34,
+    \"35\": 35,
+    \"36\": 36,
+    \"37\": 37,
+    \"38\": 38,
+    \"39\": 39,
+    \"40\": 40,
+    \"41\": 41,
+    \"42\": 42,
+    \"43\": 43,
+    \"44\": 44,
+    \"45\": 45,
+    \"46\": 46,
+    \"47\": 47,
+    \"48\": 48,
+    \"49\": 49,
+    \"50\": 50,
+    \"51\": 51,
+    \"52\": 52,
+    \"53\": 53,
+    \"54\": 54,
+    \"55\": 55,
+    \"56\": 56,
+    \"57\": 57,
+    \"58\": 58,
+    \"59\": 59,
+    \"60\": 60,
+    \"61\": 61,
+    \"62\": 62,
+    \"63\": 63,
+    \"64\": 64,
+    \"65\": 65,
+    \"66\": 66,
+    \"67\": 67,
+    \"68\": 68,
+    \"69\": 69,
+    \"70\": 70,
+    \"71\": 71,
+    \"72\": 72,
+    \"73\": 73,
+    \"74\": 74,
+    \"75\": 75,
+    \"76\": 76,
+    \"77\": 77,
+    \"78\": 78,
+    \"79\": 79,
+    \"80\": 80,
+    \"81\": 81,
+    \"82\": 82,
+    \"83\": 83,
+    \"84\": 84,
+    \"85\": 85,
+    \"86\": 86,
+    \"87\": 87,
+    \"88\": 88,
+    \"89\": 89,
+    \"90\": 90,
+    \"91\": 91,
+    \"92\": 92,
+    \"93\": 93,
+    \"94\": 94,
+    \"95\": 95,
+    \"96\": 96,
+    \"97\": 97,
+    \"98\": 98,
+    \"99\": 99,
+    \"100\": 100,
+    \"101\": 101,
+    \"102\": 102,
+    \"103\": 103,
+    \"104\": 104,
+    \"105\": 105,
+    \"106\": 106,
+    \"107\": 107,
+    \"108\": 108,
+    \"109\": 109,
+    \"110\": 110,
+    \"111\": 111,
+    \"112\": 112,
+    \"113\": 113,
+    \"114\": 114,
+    \"115\": 115,
+    \"116\": 116,
+    \"117\": 117,
+    \"118\": 118,
+    \"119\": 119,
+    \"120\": 120,
+    \"121\": 121,
+    \"122\": 122,
+    \"123\": 123,
+    \"124\": 124,
+    \"125\": 125,
+    \"126\": 126
+  },
+
+  \"unicode_codes_to_ti99_character_codes\":
+  {
+    \"13\": 13,
+    \"32\": 32,
+    \"33\": 33,
+    \"34\": 34,
+    \"35\": 35,
+    \"36\": 36,
+    \"37\": 37,
+    \"38\": 38,
+    \"39\": 39,
+    \"40\": 40,
+    \"41\": 41,
+    \"42\": 42,
+    \"43\": 43,
+    \"44\": 44,
+    \"45\": 45,
+    \"46\": 46,
+    \"47\": 47,
+    \"48\": 48,
+    \"49\": 49,
+    \"50\": 50,
+    \"51\": 51,
+    \"52\": 52,
+    \"53\": 53,
+    \"54\": 54,
+    \"55\": 55,
+    \"56\": 56,
+    \"57\": 57,
+    \"58\": 58,
+    \"59\": 59,
+    \"60\": 60,
+    \"61\": 61,
+    \"62\": 62,
+    \"63\": 63,
+    \"64\": 64,
+    \"65\": 65,
+    \"66\": 66,
+    \"67\": 67,
+    \"68\": 68,
+    \"69\": 69,
+    \"70\": 70,
+    \"71\": 71,
+    \"72\": 72,
+    \"73\": 73,
+    \"74\": 74,
+    \"75\": 75,
+    \"76\": 76,
+    \"77\": 77,
+    \"78\": 78,
+    \"79\": 79,
+    \"80\": 80,
+    \"81\": 81,
+    \"82\": 82,
+    \"83\": 83,
+    \"84\": 84,
+    \"85\": 85,
+    \"86\": 86,
+    \"87\": 87,
+    \"88\": 88,
+    \"89\": 89,
+    \"90\": 90,
+    \"91\": 91,
+    \"92\": 92,
+    \"93\": 93,
+    \"94\": 94,
+    \"95\": 95,
+    \"96\": 96,
+    \"97\": 97,
+    \"98\": 98,
+    \"99\": 99,
+    \"100\": 100,
+    \"101\": 101,
+    \"102\": 102,
+    \"103\": 103,
+    \"104\": 104,
+    \"105\": 105,
+    \"106\": 106,
+    \"107\": 107,
+    \"108\": 108,
+    \"109\": 109,
+    \"110\": 110,
+    \"111\": 111,
+    \"112\": 112,
+    \"113\": 113,
+    \"114\": 114,
+    \"115\": 115,
+    \"116\": 116,
+    \"117\": 117,
+    \"118\": 118,
+    \"119\": 119,
+    \"120\": 120,
+    \"121\": 121,
+    \"122\": 122,
+    \"123\": 123,
+    \"124\": 124,
+    \"125\": 125,
+    \"126\": 126,
+    \"9608\": 30
+  },
+
+  \"custom_character_area\": [128, 255, 57344]
+}
+";
+
+/// Tandy Color Computer (CoCo) character maps
+///
+/// The Motorola 6847 Video Display Generator (VDG) has a built-in
+/// set of 64 characters.  The semigraphics tables map the element
+/// bits of semigraphics-4 and semigraphics-6 characters to Unicode
+/// quadrant and sextant block characters.
+pub static COCO_CHARACTER_MAP: &str = "
+{
+  \"version\": \"0.1.0\",
+
+  \"vdg_character_codes_to_unicode_codes\":
+  {
+    \"0\": 64,
+    \"1\": 65,
+    \"2\": 66,
+    \"3\": 67,
+    \"4\": 68,
+    \"5\": 69,
+    \"6\": 70,
+    \"7\": 71,
+    \"8\": 72,
+    \"9\": 73,
+    \"10\": 74,
+    \"11\": 75,
+    \"12\": 76,
+    \"13\": 77,
+    \"14\": 78,
+    \"15\": 79,
+    \"16\": 80,
+    \"17\": 81,
+    \"18\": 82,
+    \"19\": 83,
+    \"20\": 84,
+    \"21\": 85,
+    \"22\": 86,
+    \"23\": 87,
+    \"24\": 88,
+    \"25\": 89,
+    \"26\": 90,
+    \"27\": 91,
+    \"28\": 92,
+    \"29\": 93,
+    \"30\": 8593,
+    \"31\": 8592,
+    \"32\": 32,
+    \"33\": 33,
+    \"34\": 34,
+    \"35\": 35,
+    \"36\": 36,
+    \"37\": 37,
+    \"38\": 38,
+    \"39\": 39,
+    \"40\": 40,
+    \"41\": 41,
+    \"42\": 42,
+    \"43\": 43,
+    \"44\": 44,
+    \"45\": 45,
+    \"46\": 46,
+    \"47\": 47,
+    \"48\": 48,
+    \"49\": 49,
+    \"50\": 50,
+    \"51\": 51,
+    \"52\": 52,
+    \"53\": 53,
+    \"54\": 54,
+    \"55\": 55,
+    \"56\": 56,
+    \"57\": 57,
+    \"58\": 58,
+    \"59\": 59,
+    \"60\": 60,
+    \"61\": 61,
+    \"62\": 62,
+    \"63\": 63
+  },
+
+  \"unicode_codes_to_vdg_character_codes\":
+  {
+    \"32\": 32,
+    \"33\": 33,
+    \"34\": 34,
+    \"35\": 35,
+    \"36\": 36,
+    \"37\": 37,
+    \"38\": 38,
+    \"39\": 39,
+    \"40\": 40,
+    \"41\": 41,
+    \"42\": 42,
+    \"43\": 43,
+    \"44\": 44,
+    \"45\": 45,
+    \"46\": 46,
+    \"47\": 47,
+    \"48\": 48,
+    \"49\": 49,
+    \"50\": 50,
+    \"51\": 51,
+    \"52\": 52,
+    \"53\": 53,
+    \"54\": 54,
+    \"55\": 55,
+    \"56\": 56,
+    \"57\": 57,
+    \"58\": 58,
+    \"59\": 59,
+    \"60\": 60,
+    \"61\": 61,
+    \"62\": 62,
+    \"63\": 63,
+    \"64\": 0,
+    \"65\": 1,
+    \"66\": 2,
+    \"67\": 3,
+    \"68\": 4,
+    \"69\": 5,
+    \"70\": 6,
+    \"71\": 7,
+    \"72\": 8,
+    \"73\": 9,
+    \"74\": 10,
+    \"75\": 11,
+    \"76\": 12,
+    \"77\": 13,
+    \"78\": 14,
+    \"79\": 15,
+    \"80\": 16,
+    \"81\": 17,
+    \"82\": 18,
+    \"83\": 19,
+    \"84\": 20,
+    \"85\": 21,
+    \"86\": 22,
+    \"87\": 23,
+    \"88\": 24,
+    \"89\": 25,
+    \"90\": 26,
+    \"91\": 27,
+    \"92\": 28,
+    \"93\": 29,
+    \"8592\": 31,
+    \"8593\": 30
+  },
+
+  \"semigraphics_4_to_unicode_codes\":
+  {
+    \"0\": 32,
+    \"1\": 9623,
+    \"2\": 9622,
+    \"3\": 9604,
+    \"4\": 9629,
+    \"5\": 9616,
+    \"6\": 9630,
+    \"7\": 9631,
+    \"8\": 9624,
+    \"9\": 9626,
+    \"10\": 9612,
+    \"11\": 9625,
+    \"12\": 9600,
+    \"13\": 9628,
+    \"14\": 9627,
+    \"15\": 9608
+  },
+
+  \"semigraphics_6_to_unicode_codes\":
+  {
+    \"0\": 32,
+    \"1\": 129822,
+    \"2\": 129807,
+    \"3\": 129837,
+    \"4\": 129799,
+    \"5\": 129830,
+    \"6\": 129814,
+    \"7\": 129845,
+    \"8\": 129795,
+    \"9\": 129826,
+    \"10\": 129811,
+    \"11\": 129841,
+    \"12\": 129803,
+    \"13\": 129833,
+    \"14\": 129818,
+    \"15\": 129849,
+    \"16\": 129793,
+    \"17\": 129824,
+    \"18\": 129809,
+    \"19\": 129839,
+    \"20\": 129801,
+    \"21\": 9616,
+    \"22\": 129816,
+    \"23\": 129847,
+    \"24\": 129797,
+    \"25\": 129828,
+    \"26\": 129812,
+    \"27\": 129843,
+    \"28\": 129805,
+    \"29\": 129835,
+    \"30\": 129820,
+    \"31\": 129851,
+    \"32\": 129792,
+    \"33\": 129823,
+    \"34\": 129808,
+    \"35\": 129838,
+    \"36\": 129800,
+    \"37\": 129831,
+    \"38\": 129815,
+    \"39\": 129846,
+    \"40\": 129796,
+    \"41\": 129827,
+    \"42\": 9612,
+    \"43\": 129842,
+    \"44\": 129804,
+    \"45\": 129834,
+    \"46\": 129819,
+    \"47\": 129850,
+    \"48\": 129794,
+    \"49\": 129825,
+    \"50\": 129810,
+    \"51\": 129840,
+    \"52\": 129802,
+    \"53\": 129832,
+    \"54\": 129817,
+    \"55\": 129848,
+    \"56\": 129798,
+    \"57\": 129829,
+    \"58\": 129813,
+    \"59\": 129844,
+    \"60\": 129806,
+    \"61\": 129836,
+    \"62\": 129821,
+    \"63\": 9608
+  }
+}
+";
+
+/// Apple II character maps
+///
+/// apple2_screen_codes_to_unicode_codes is the primary character set
+/// of the Apple IIe.  mousetext_screen_codes_to_unicode_codes holds
+/// the screen codes that are different in the alternate character
+/// set of the enhanced Apple IIe and Apple IIc.
+///
+/// Closed Apple and Open Apple don't have Unicode characters.  They
+/// are mapped to U+F8FF, the Apple logo in Apple's Private Use Area
+/// convention, and U+F8FE.
+pub static APPLE2_CHARACTER_MAP: &str = "
+{
+  \"version\": \"0.1.0\",
+
+  \"apple2_screen_codes_to_unicode_codes\":
+  {
+    \"0\": 64,
+    \"1\": 65,
+    \"2\": 66,
+    \"3\": 67,
+    \"4\": 68,
+    \"5\": 69,
+    \"6\": 70,
+    \"7\": 71,
+    \"8\": 72,
+    \"9\": 73,
+    \"10\": 74,
+    \"11\": 75,
+    \"12\": 76,
+    \"13\": 77,
+    \"14\": 78,
+    \"15\": 79,
+    \"16\": 80,
+    \"17\": 81,
+    \"18\": 82,
+    \"19\": 83,
+    \"20\": 84,
+    \"21\": 85,
+    \"22\": 86,
+    \"23\": 87,
+    \"24\": 88,
+    \"25\": 89,
+    \"26\": 90,
+    \"27\": 91,
+    \"28\": 92,
+    \"29\": 93,
+    \"30\": 94,
+    \"31\": 95,
+    \"32\": 32,
+    \"33\": 33,
+    \"34\": 34,
+    \"35\": 35,
+    \"36\": 36,
+    \"37\": 37,
+    \"38\": 38,
+    \"39\": 39,
+    \"40\": 40,
+    \"41\": 41,
+    \"42\": 42,
+    \"43\": 43,
+    \"44\": 44,
+    \"45\": 45,
+    \"46\": 46,
+    \"47\": 47,
+    \"48\": 48,
+    \"49\": 49,
+    \"50\": 50,
+    \"51\": 51,
+    \"52\": 52,
+    \"53\": 53,
+    \"54\": 54,
+    \"55\": 55,
+    \"56\": 56,
+    \"57\": 57,
+    \"58\": 58,
+    \"59\": 59,
+    \"60\": 60,
+    \"61\": 61,
+    \"62\": 62,
+    \"63\": 63,
+    \"64\": 64,
+    \"65\": 65,
+    \"66\": 66,
+    \"67\": 67,
+    \"68\": 68,
+    \"69\": 69,
+    \"70\": 70,
+    \"71\": 71,
+    \"72\": 72,
+    \"73\": 73,
+    \"74\": 74,
+    \"75\": 75,
+    \"76\": 76,
+    \"77\": 77,
+    \"78\": 78,
+    \"79\": 79,
+    \"80\": 80,
+    \"81\": 81,
+    \"82\": 82,
+    \"83\": 83,
+    \"84\": 84,
+    \"85\": 85,
+    \"86\": 86,
+    \"87\": 87,
+    \"88\": 88,
+    \"89\": 89,
+    \"90\": 90,
+    \"91\": 91,
+    \"92\": 92,
+    \"93\": 93,
+    \"94\": 94,
+    \"95\": 95,
+    \"96\": 32,
+    \"97\": 33,
+    \"98\": 34,
+    \"99\": 35,
+    \"100\": 36,
+    \"101\": 37,
+    \"102\": 38,
+    \"103\": 39,
+    \"104\": 40,
+    \"105\": 41,
+    \"106\": 42,
+    \"107\": 43,
+    \"108\": 44,
+    \"109\": 45,
+    \"110\": 46,
+    \"111\": 47,
+    \"112\": 48,
+    \"113\": 49,
+    \"114\": 50,
+    \"115\": 51,
+    \"116\": 52,
+    \"117\": 53,
+    \"118\": 54,
+    \"119\": 55,
+    \"120\": 56,
+    \"121\": 57,
+    \"122\": 58,
+    \"123\": 59,
+    \"124\": 60,
+    \"125\": 61,
+    \"126\": 62,
+    \"127\": 63,
+    \"128\": 64,
+    \"129\": 65,
+    \"130\": 66,
+    \"131\": 67,
+    \"132\": 68,
+    \"133\": 69,
+    \"134\": 70,
+    \"135\": 71,
+    \"136\": 72,
+    \"137\": 73,
+    \"138\": 74,
+    \"139\": 75,
+    \"140\": 76,
+    \"141\": 77,
+    \"142\": 78,
+    \"143\": 79,
+    \"144\": 80,
+    \"145\": 81,
+    \"146\": 82,
+    \"147\": 83,
+    \"148\": 84,
+    \"149\": 85,
+    \"150\": 86,
+    \"151\": 87,
+    \"152\": 88,
+    \"153\": 89,
+    \"154\": 90,
+    \"155\": 91,
+    \"156\": 92,
+    \"157\": 93,
+    \"158\": 94,
+    \"159\": 95,
+    \"160\": 32,
+    \"161\": 33,
+    \"162\": 34,
+    \"163\": 35,
+    \"164\": 36,
+    \"165\": 37,
+    \"166\": 38,
+    \"167\": 39,
+    \"168\": 40,
+    \"169\": 41,
+    \"170\": 42,
+    \"171\": 43,
+    \"172\": 44,
+    \"173\": 45,
+    \"174\": 46,
+    \"175\": 47,
+    \"176\": 48,
+    \"177\": 49,
+    \"178\": 50,
+    \"179\": 51,
+    \"180\": 52,
+    \"181\": 53,
+    \"182\": 54,
+    \"183\": 55,
+    \"184\": 56,
+    \"185\": 57,
+    \"186\": 58,
+    \"187\": 59,
+    \"188\": 60,
+    \"189\": 61,
+    \"190\": 62,
+    \"191\": 63,
+    \"192\": 64,
+    \"193\": 65,
+    \"194\": 66,
+    \"195\": 67,
+    \"196\": 68,
+    \"197\": 69,
+    \"198\": 70,
+    \"199\": 71,
+    \"200\": 72,
+    \"201\": 73,
+    \"202\": 74,
+    \"203\": 75,
+    \"204\": 76,
+    \"205\": 77,
+    \"206\": 78,
+    \"207\": 79,
+    \"208\": 80,
+    \"209\": 81,
+    \"210\": 82,
+    \"211\": 83,
+    \"212\": 84,
+    \"213\": 85,
+    \"214\": 86,
+    \"215\": 87,
+    \"216\": 88,
+    \"217\": 89,
+    \"218\": 90,
+    \"219\": 91,
+    \"220\": 92,
+    \"221\": 93,
+    \"222\": 94,
+    \"223\": 95,
+    \"224\": 96,
+    \"225\": 97,
+    \"226\": 98,
+    \"227\": 99,
+    \"228\": 100,
+    \"229\": 101,
+    \"230\": 102,
+    \"231\": 103,
+    \"232\": 104,
+    \"233\": 105,
+    \"234\": 106,
+    \"235\": 107,
+    \"236\": 108,
+    \"237\": 109,
+    \"238\": 110,
+    \"239\": 111,
+    \"240\": 112,
+    \"241\": 113,
+    \"242\": 114,
+    \"243\": 115,
+    \"244\": 116,
+    \"245\": 117,
+    \"246\": 118,
+    \"247\": 119,
+    \"248\": 120,
+    \"249\": 121,
+    \"250\": 122,
+    \"251\": 123,
+    \"252\": 124,
+    \"253\": 125,
+    \"254\": 126,
+    \"255\": 9618
+  },
+
+  \"mousetext_screen_codes_to_unicode_codes\":
+  {
+    \"64\": 63743,
+    \"65\": 63742,
+    \"66\": 129968,
+    \"67\": 8987,
+    \"68\": 10003,
+    \"69\": 129969,
+    \"70\": 129970,
+    \"71\": 129971,
+    \"72\": 8592,
+    \"73\": 8230,
+    \"74\": 8595,
+    \"75\": 8593,
+    \"76\": 9620,
+    \"77\": 8626,
+    \"78\": 9608,
+    \"79\": 129973,
+    \"80\": 129974,
+    \"81\": 129975,
+    \"82\": 129976,
+    \"83\": 9472,
+    \"84\": 8990,
+    \"85\": 8594,
+    \"86\": 9618,
+    \"87\": 129936,
+    \"88\": 129977,
+    \"89\": 129978,
+    \"90\": 9621,
+    \"91\": 9670,
+    \"92\": 129920,
+    \"93\": 129979,
+    \"94\": 129980,
+    \"95\": 9615,
+    \"96\": 96,
+    \"97\": 97,
+    \"98\": 98,
+    \"99\": 99,
+    \"100\": 100,
+    \"101\": 101,
+    \"102\": 102,
+    \"103\": 103,
+    \"104\": 104,
+    \"105\": 105,
+    \"106\": 106,
+    \"107\": 107,
+    \"108\": 108,
+    \"109\": 109,
+    \"110\": 110,
+    \"111\": 111,
+    \"112\": 112,
+    \"113\": 113,
+    \"114\": 114,
+    \"115\": 115,
+    \"116\": 116,
+    \"117\": 117,
+    \"118\": 118,
+    \"119\": 119,
+    \"120\": 120,
+    \"121\": 121,
+    \"122\": 122,
+    \"123\": 123,
+    \"124\": 124,
+    \"125\": 125,
+    \"126\": 126,
+    \"127\": 9618
+  }
+}
+";
+
+/// BBC Micro and Acorn Electron character maps
+///
+/// bbc_character_codes_to_unicode_codes is the character set for
+/// screen modes 0 to 6.  teletext_character_codes_to_unicode_codes
+/// is the character set for the MODE 7 teletext screen.
+///
+/// vdu_parameter_counts is the number of parameter bytes that follow
+/// each VDU control code.  Control codes that aren't listed don't
+/// have any parameters.
+pub static BBC_CHARACTER_MAP: &str = "
+{
+  \"version\": \"0.1.0\",
+
+  \"bbc_character_codes_to_unicode_codes\":
+  {
+    \"32\": 32,
+    \"33\": 33,
+    \"34\": 34,
+    \"35\": 35,
+    \"36\": 36,
+    \"37\": 37,
+    \"38\": 38,
+    \"39\": 39,
+    \"40\": 40,
+    \"41\": 41,
+    \"42\": 42,
+    \"43\": 43,
+    \"44\": 44,
+    \"45\": 45,
+    \"46\": 46,
+    \"47\": 47,
+    \"48\": 48,
+    \"49\": 49,
+    \"50\": 50,
+    \"51\": 51,
+    \"52\": 52,
+    \"53\": 53,
+    \"54\": 54,
+    \"55\": 55,
+    \"56\": 56,
+    \"57\": 57,
+    \"58\": 58,
+    \"59\": 59,
+    \"60\": 60,
+    \"61\": 61,
+    \"62\": 62,
+    \"63\": 63,
+    \"64\": 64,
+    \"65\": 65,
+    \"66\": 66,
+    \"67\": 67,
+    \"68\": 68,
+    \"69\": 69,
+    \"70\": 70,
+    \"71\": 71,
+    \"72\": 72,
+    \"73\": 73,
+    \"74\": 74,
+    \"75\": 75,
+    \"76\": 76,
+    \"77\": 77,
+    \"78\": 78,
+    \"79\": 79,
+    \"80\": 80,
+    \"81\": 81,
+    \"82\": 82,
+    \"83\": 83,
+    \"84\": 84,
+    \"85\": 85,
+    \"86\": 86,
+    \"87\": 87,
+    \"88\": 88,
+    \"89\": 89,
+    \"90\": 90,
+    \"91\": 91,
+    \"92\": 92,
+    \"93\": 93,
+    \"94\": 94,
+    \"95\": 95,
+    \"96\": 163,
+    \"97\": 97,
+    \"98\": 98,
+    \"99\": 99,
+    \"100\": 100,
+    \"101\": 101,
+    \"102\": 102,
+    \"103\": 103,
+    \"104\": 104,
+    \"105\": 105,
+    \"106\": 106,
+    \"107\": 107,
+    \"108\": 108,
+    \"109\": 109,
+    \"110\": 110,
+    \"111\": 111,
+    \"112\": 112,
+    \"113\": 113,
+    \"114\": 114,
+    \"115\": 115,
+    \"116\": 116,
+    \"117\": 117,
+    \"118\": 118,
+    \"119\": 119,
+    \"120\": 120,
+    \"121\": 121,
+    \"122\": 122,
+    \"123\": 123,
+    \"124\": 124,
+    \"125\": 125,
+    \"126\": 126
+  },
+
+  \"unicode_codes_to_bbc_character_codes\":
+  {
+    \"32\": 32,
+    \"33\": 33,
+    \"34\": 34,
+    \"35\": 35,
+    \"36\": 36,
+    \"37\": 37,
+    \"38\": 38,
+    \"39\": 39,
+    \"40\": 40,
+    \"41\": 41,
+    \"42\": 42,
+    \"43\": 43,
+    \"44\": 44,
+    \"45\": 45,
+    \"46\": 46,
+    \"47\": 47,
+    \"48\": 48,
+    \"49\": 49,
+    \"50\": 50,
+    \"51\": 51,
+    \"52\": 52,
+    \"53\": 53,
+    \"54\": 54,
+    \"55\": 55,
+    \"56\": 56,
+    \"57\": 57,
+    \"58\": 58,
+    \"59\": 59,
+    \"60\": 60,
+    \"61\": 61,
+    \"62\": 62,
+    \"63\": 63,
+    \"64\": 64,
+    \"65\": 65,
+    \"66\": 66,
+    \"67\": 67,
+    \"68\": 68,
+    \"69\": 69,
+    \"70\": 70,
+    \"71\": 71,
+    \"72\": 72,
+    \"73\": 73,
+    \"74\": 74,
+    \"75\": 75,
+    \"76\": 76,
+    \"77\": 77,
+    \"78\": 78,
+    \"79\": 79,
+    \"80\": 80,
+    \"81\": 81,
+    \"82\": 82,
+    \"83\": 83,
+    \"84\": 84,
+    \"85\": 85,
+    \"86\": 86,
+    \"87\": 87,
+    \"88\": 88,
+    \"89\": 89,
+    \"90\": 90,
+    \"91\": 91,
+    \"92\": 92,
+    \"93\": 93,
+    \"94\": 94,
+    \"95\": 95,
+    \"97\": 97,
+    \"98\": 98,
+    \"99\": 99,
+    \"100\": 100,
+    \"101\": 101,
+    \"102\": 102,
+    \"103\": 103,
+    \"104\": 104,
+    \"105\": 105,
+    \"106\": 106,
+    \"107\": 107,
+    \"108\": 108,
+    \"109\": 109,
+    \"110\": 110,
+    \"111\": 111,
+    \"112\": 112,
+    \"113\": 113,
+    \"114\": 114,
+    \"115\": 115,
+    \"116\": 116,
+    \"117\": 117,
+    \"118\": 118,
+    \"119\": 119,
+    \"120\": 120,
+    \"121\": 121,
+    \"122\": 122,
+    \"123\": 123,
+    \"124\": 124,
+    \"125\": 125,
+    \"126\": 126,
+    \"163\": 96
+  },
+
+  \"teletext_character_codes_to_unicode_codes\":
+  {
+    \"32\": 32,
+    \"33\": 33,
+    \"34\": 34,
+    \"35\": 163,
+    \"36\": 36,
+    \"37\": 37,
+    \"38\": 38,
+    \"39\": 39,
+    \"40\": 40,
+    \"41\": 41,
+    \"42\": 42,
+    \"43\": 43,
+    \"44\": 44,
+    \"45\": 45,
+    \"46\": 46,
+    \"47\": 47,
+    \"48\": 48,
+    \"49\": 49,
+    \"50\": 50,
+    \"51\": 51,
+    \"52\": 52,
+    \"53\": 53,
+    \"54\": 54,
+    \"55\": 55,
+    \"56\": 56,
+    \"57\": 57,
+    \"58\": 58,
+    \"59\": 59,
+    \"60\": 60,
+    \"61\": 61,
+    \"62\": 62,
+    \"63\": 63,
+    \"64\": 64,
+    \"65\": 65,
+    \"66\": 66,
+    \"67\": 67,
+    \"68\": 68,
+    \"69\": 69,
+    \"70\": 70,
+    \"71\": 71,
+    \"72\": 72,
+    \"73\": 73,
+    \"74\": 74,
+    \"75\": 75,
+    \"76\": 76,
+    \"77\": 77,
+    \"78\": 78,
+    \"79\": 79,
+    \"80\": 80,
+    \"81\": 81,
+    \"82\": 82,
+    \"83\": 83,
+    \"84\": 84,
+    \"85\": 85,
+    \"86\": 86,
+    \"87\": 87,
+    \"88\": 88,
+    \"89\": 89,
+    \"90\": 90,
+    \"91\": 8592,
+    \"92\": 189,
+    \"93\": 8594,
+    \"94\": 8593,
+    \"95\": 35,
+    \"96\": 8212,
+    \"97\": 97,
+    \"98\": 98,
+    \"99\": 99,
+    \"100\": 100,
+    \"101\": 101,
+    \"102\": 102,
+    \"103\": 103,
+    \"104\": 104,
+    \"105\": 105,
+    \"106\": 106,
+    \"107\": 107,
+    \"108\": 108,
+    \"109\": 109,
+    \"110\": 110,
+    \"111\": 111,
+    \"112\": 112,
+    \"113\": 113,
+    \"114\": 114,
+    \"115\": 115,
+    \"116\": 116,
+    \"117\": 117,
+    \"118\": 118,
+    \"119\": 119,
+    \"120\": 120,
+    \"121\": 121,
+    \"122\": 122,
+    \"123\": 188,
+    \"124\": 8214,
+    \"125\": 190,
+    \"126\": 247
+  },
+
+  \"vdu_parameter_counts\":
+  {
+    \"1\": 1,
+    \"17\": 1,
+    \"18\": 2,
+    \"19\": 5,
+    \"22\": 1,
+    \"23\": 9,
+    \"24\": 8,
+    \"25\": 5,
+    \"28\": 4,
+    \"29\": 4,
+    \"31\": 2
+  }
+}
+";
+
+/// EBCDIC code page maps
+///
+/// Each table has the Unicode code point for all 256 EBCDIC codes.
+/// Code page 037 is US/Canada EBCDIC and code page 500 is
+/// International EBCDIC.  They differ in the positions of a few
+/// punctuation characters.
+pub static EBCDIC_CHARACTER_MAP: &str = "
+{
+  \"version\": \"0.1.0\",
+
+  \"cp037_to_unicode_codes\":
+  [
+    0, 1, 2, 3, 156, 9, 134, 127, 151, 141, 142, 11, 12, 13, 14, 15,
+    16, 17, 18, 19, 157, 133, 8, 135, 24, 25, 146, 143, 28, 29, 30, 31,
+    128, 129, 130, 131, 132, 10, 23, 27, 136, 137, 138, 139, 140, 5, 6, 7,
+    144, 145, 22, 147, 148, 149, 150, 4, 152, 153, 154, 155, 20, 21, 158, 26,
+    32, 160, 226, 228, 224, 225, 227, 229, 231, 241, 162, 46, 60, 40, 43, 124,
+    38, 233, 234, 235, 232, 237, 238, 239, 236, 223, 33, 36, 42, 41, 59, 172,
+    45, 47, 194, 196, 192, 193, 195, 197, 199, 209, 166, 44, 37, 95, 62, 63,
+    248, 201, 202, 203, 200, 205, 206, 207, 204, 96, 58, 35, 64, 39, 61, 34,
+    216, 97, 98, 99, 100, 101, 102, 103, 104, 105, 171, 187, 240, 253, 254, 177,
+    176, 106, 107, 108, 109, 110, 111, 112, 113, 114, 170, 186, 230, 184, 198, 164,
+    181, 126, 115, 116, 117, 118, 119, 120, 121, 122, 161, 191, 208, 221, 222, 174,
+    94, 163, 165, 183, 169, 167, 182, 188, 189, 190, 91, 93, 175, 168, 180, 215,
+    123, 65, 66, 67, 68, 69, 70, 71, 72, 73, 173, 244, 246, 242, 243, 245,
+    125, 74, 75, 76, 77, 78, 79, 80, 81, 82, 185, 251, 252, 249, 250, 255,
+    92, 247, 83, 84, 85, 86, 87, 88, 89, 90, 178, 212, 214, 210, 211, 213,
+    48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 179, 219, 220, 217, 218, 159
+  ],
+
+  \"cp500_to_unicode_codes\":
+  [
+    0, 1, 2, 3, 156, 9, 134, 127, 151, 141, 142, 11, 12, 13, 14, 15,
+    16, 17, 18, 19, 157, 133, 8, 135, 24, 25, 146, 143, 28, 29, 30, 31,
+    128, 129, 130, 131, 132, 10, 23, 27, 136, 137, 138, 139, 140, 5, 6, 7,
+    144, 145, 22, 147, 148, 149, 150, 4, 152, 153, 154, 155, 20, 21, 158, 26,
+    32, 160, 226, 228, 224, 225, 227, 229, 231, 241, 91, 46, 60, 40, 43, 33,
+    38, 233, 234, 235, 232, 237, 238, 239, 236, 223, 93, 36, 42, 41, 59, 94,
+    45, 47, 194, 196, 192, 193, 195, 197, 199, 209, 166, 44, 37, 95, 62, 63,
+    248, 201, 202, 203, 200, 205, 206, 207, 204, 96, 58, 35, 64, 39, 61, 34,
+    216, 97, 98, 99, 100, 101, 102, 103, 104, 105, 171, 187, 240, 253, 254, 177,
+    176, 106, 107, 108, 109, 110, 111, 112, 113, 114, 170, 186, 230, 184, 198, 164,
+    181, 126, 115, 116, 117, 118, 119, 120, 121, 122, 161, 191, 208, 221, 222, 174,
+    162, 163, 165, 183, 169, 167, 182, 188, 189, 190, 172, 124, 175, 168, 180, 215,
+    123, 65, 66, 67, 68, 69, 70, 71, 72, 73, 173, 244, 246, 242, 243, 245,
+    125, 74, 75, 76, 77, 78, 79, 80, 81, 82, 185, 251, 252, 249, 250, 255,
+    92, 247, 83, 84, 85, 86, 87, 88, 89, 90, 178, 212, 214, 210, 211, 213,
+    48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 179, 219, 220, 217, 218, 159
+  ]
+}
+";
+
+/// IBM PC code page 437, the character set used by ANSI art
+///
+/// The codes below 0x20 are left as control characters, since ANSI
+/// art uses them for line breaks and escape sequences.
+pub static CP437_CHARACTER_MAP: &str = "
+{
+  \"version\": \"0.1.0\",
+
+  \"cp437_to_unicode_codes\":
+  [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+    16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+    32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47,
+    48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63,
+    64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79,
+    80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95,
+    96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111,
+    112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125, 126, 127,
+    199, 252, 233, 226, 228, 224, 229, 231, 234, 235, 232, 239, 238, 236, 196, 197,
+    201, 230, 198, 244, 246, 242, 251, 249, 255, 214, 220, 162, 163, 165, 8359, 402,
+    225, 237, 243, 250, 241, 209, 170, 186, 191, 8976, 172, 189, 188, 161, 171, 187,
+    9617, 9618, 9619, 9474, 9508, 9569, 9570, 9558, 9557, 9571, 9553, 9559, 9565, 9564, 9563, 9488,
+    9492, 9524, 9516, 9500, 9472, 9532, 9566, 9567, 9562, 9556, 9577, 9574, 9568, 9552, 9580, 9575,
+    9576, 9572, 9573, 9561, 9560, 9554, 9555, 9579, 9578, 9496, 9484, 9608, 9604, 9612, 9616, 9600,
+    945, 223, 915, 960, 931, 963, 181, 964, 934, 920, 937, 948, 8734, 966, 949, 8745,
+    8801, 177, 8805, 8804, 8992, 8993, 247, 8776, 176, 8729, 183, 8730, 8319, 178, 9632, 160
+  ]
+}
+";
+
+/// Overlay for the lossless-escape configuration
+///
+/// Screen codes without a Unicode character, or sharing a character
+/// with another screen code, are mapped to the Private Use Area:
+/// U+E000 plus the screen code for set 1 and U+E100 plus the screen
+/// code for set 2.  Every screen code decodes to a different
+/// character, and the unreversed ones encode back to a PETSCII code
+/// for the same screen code.  Duplicate PETSCII codes, like 0xDE
+/// for 0x7E, come back as the code they duplicate.  The overlay is
+/// merged over CONFIG_DATA.
+pub static LOSSLESS_ESCAPE_OVERLAY: &str = "
+{
+  \"petscii\": {
+    \"character_set_map\":
+    {
+      \"c64_screen_codes_set_1_to_unicode_codes\":
+      {
+        \"32\": 57376,
+        \"94\": 57438,
+        \"128\": 57472,
+        \"129\": 57473,
+        \"130\": 57474,
+        \"131\": 57475,
+        \"132\": 57476,
+        \"133\": 57477,
+        \"134\": 57478,
+        \"135\": 57479,
+        \"136\": 57480,
+        \"137\": 57481,
+        \"138\": 57482,
+        \"139\": 57483,
+        \"140\": 57484,
+        \"141\": 57485,
+        \"142\": 57486,
+        \"143\": 57487,
+        \"144\": 57488,
+        \"145\": 57489,
+        \"146\": 57490,
+        \"147\": 57491,
+        \"148\": 57492,
+        \"149\": 57493,
+        \"150\": 57494,
+        \"151\": 57495,
+        \"152\": 57496,
+        \"153\": 57497,
+        \"154\": 57498,
+        \"155\": 57499,
+        \"156\": 57500,
+        \"157\": 57501,
+        \"158\": 57502,
+        \"159\": 57503,
+        \"160\": 57504,
+        \"161\": 57505,
+        \"162\": 57506,
+        \"163\": 57507,
+        \"164\": 57508,
+        \"165\": 57509,
+        \"166\": 57510,
+        \"167\": 57511,
+        \"168\": 57512,
+        \"169\": 57513,
+        \"170\": 57514,
+        \"171\": 57515,
+        \"172\": 57516,
+        \"173\": 57517,
+        \"174\": 57518,
+        \"175\": 57519,
+        \"176\": 57520,
+        \"177\": 57521,
+        \"178\": 57522,
+        \"179\": 57523,
+        \"180\": 57524,
+        \"181\": 57525,
+        \"182\": 57526,
+        \"183\": 57527,
+        \"184\": 57528,
+        \"185\": 57529,
+        \"186\": 57530,
+        \"187\": 57531,
+        \"188\": 57532,
+        \"189\": 57533,
+        \"190\": 57534,
+        \"191\": 57535,
+        \"200\": 57544,
+        \"201\": 57545,
+        \"202\": 57546,
+        \"203\": 57547,
+        \"205\": 57549,
+        \"206\": 57550,
+        \"209\": 57553,
+        \"210\": 57554,
+        \"213\": 57557,
+        \"214\": 57558,
+        \"215\": 57559,
+        \"219\": 57563,
+        \"222\": 57566,
+        \"223\": 57567,
+        \"224\": 57568,
+        \"225\": 57569,
+        \"226\": 57570,
+        \"227\": 57571,
+        \"230\": 57574,
+        \"231\": 57575,
+        \"232\": 57576,
+        \"233\": 57577,
+        \"234\": 57578,
+        \"235\": 57579,
+        \"236\": 57580,
+        \"237\": 57581,
+        \"238\": 57582,
+        \"240\": 57584,
+        \"241\": 57585,
+        \"242\": 57586,
+        \"243\": 57587,
+        \"246\": 57590,
+        \"247\": 57591,
+        \"248\": 57592,
+        \"249\": 57593,
+        \"251\": 57595,
+        \"252\": 57596,
+        \"253\": 57597,
+        \"254\": 57598,
+        \"255\": 57599
+      },
+
+      \"c64_screen_codes_set_2_to_unicode_codes\":
+      {
+        \"0\": 57600,
+        \"27\": 57627,
+        \"28\": 57628,
+        \"29\": 57629,
+        \"30\": 57630,
+        \"31\": 57631,
+        \"32\": 57632,
+        \"33\": 57633,
+        \"34\": 57634,
+        \"35\": 57635,
+        \"36\": 57636,
+        \"37\": 57637,
+        \"38\": 57638,
+        \"39\": 57639,
+        \"40\": 57640,
+        \"41\": 57641,
+        \"42\": 57642,
+        \"43\": 57643,
+        \"44\": 57644,
+        \"45\": 57645,
+        \"46\": 57646,
+        \"47\": 57647,
+        \"48\": 57648,
+        \"49\": 57649,
+        \"50\": 57650,
+        \"51\": 57651,
+        \"52\": 57652,
+        \"53\": 57653,
+        \"54\": 57654,
+        \"55\": 57655,
+        \"56\": 57656,
+        \"57\": 57657,
+        \"58\": 57658,
+        \"59\": 57659,
+        \"60\": 57660,
+        \"61\": 57661,
+        \"62\": 57662,
+        \"63\": 57663,
+        \"64\": 57664,
+        \"65\": 57665,
+        \"66\": 57666,
+        \"67\": 57667,
+        \"68\": 57668,
+        \"69\": 57669,
+        \"70\": 57670,
+        \"71\": 57671,
+        \"72\": 57672,
+        \"73\": 57673,
+        \"74\": 57674,
+        \"75\": 57675,
+        \"76\": 57676,
+        \"77\": 57677,
+        \"78\": 57678,
+        \"79\": 57679,
+        \"80\": 57680,
+        \"81\": 57681,
+        \"82\": 57682,
+        \"83\": 57683,
+        \"84\": 57684,
+        \"85\": 57685,
+        \"86\": 57686,
+        \"87\": 57687,
+        \"88\": 57688,
+        \"89\": 57689,
+        \"90\": 57690,
+        \"91\": 57691,
+        \"92\": 57692,
+        \"93\": 57693,
+        \"96\": 57696,
+        \"97\": 57697,
+        \"98\": 57698,
+        \"99\": 57699,
+        \"100\": 57700,
+        \"101\": 57701,
+        \"102\": 57702,
+        \"103\": 57703,
+        \"104\": 57704,
+        \"106\": 57706,
+        \"107\": 57707,
+        \"108\": 57708,
+        \"109\": 57709,
+        \"110\": 57710,
+        \"111\": 57711,
+        \"112\": 57712,
+        \"113\": 57713,
+        \"114\": 57714,
+        \"115\": 57715,
+        \"116\": 57716,
+        \"117\": 57717,
+        \"118\": 57718,
+        \"119\": 57719,
+        \"120\": 57720,
+        \"121\": 57721,
+        \"123\": 57723,
+        \"124\": 57724,
+        \"125\": 57725,
+        \"126\": 57726,
+        \"127\": 57727,
+        \"128\": 57728,
+        \"129\": 57729,
+        \"130\": 57730,
+        \"131\": 57731,
+        \"132\": 57732,
+        \"133\": 57733,
+        \"134\": 57734,
+        \"135\": 57735,
+        \"136\": 57736,
+        \"137\": 57737,
+        \"138\": 57738,
+        \"139\": 57739,
+        \"140\": 57740,
+        \"141\": 57741,
+        \"142\": 57742,
+        \"143\": 57743,
+        \"144\": 57744,
+        \"145\": 57745,
+        \"146\": 57746,
+        \"147\": 57747,
+        \"148\": 57748,
+        \"149\": 57749,
+        \"150\": 57750,
+        \"151\": 57751,
+        \"152\": 57752,
+        \"153\": 57753,
+        \"154\": 57754,
+        \"155\": 57755,
+        \"156\": 57756,
+        \"157\": 57757,
+        \"158\": 57758,
+        \"159\": 57759,
+        \"160\": 57760,
+        \"161\": 57761,
+        \"162\": 57762,
+        \"163\": 57763,
+        \"164\": 57764,
+        \"165\": 57765,
+        \"166\": 57766,
+        \"167\": 57767,
+        \"168\": 57768,
+        \"169\": 57769,
+        \"170\": 57770,
+        \"171\": 57771,
+        \"172\": 57772,
+        \"173\": 57773,
+        \"174\": 57774,
+        \"175\": 57775,
+        \"176\": 57776,
+        \"177\": 57777,
+        \"178\": 57778,
+        \"179\": 57779,
+        \"180\": 57780,
+        \"181\": 57781,
+        \"182\": 57782,
+        \"183\": 57783,
+        \"184\": 57784,
+        \"185\": 57785,
+        \"186\": 57786,
+        \"187\": 57787,
+        \"188\": 57788,
+        \"189\": 57789,
+        \"190\": 57790,
+        \"191\": 57791,
+        \"192\": 57792,
+        \"193\": 57793,
+        \"194\": 57794,
+        \"195\": 57795,
+        \"196\": 57796,
+        \"197\": 57797,
+        \"198\": 57798,
+        \"199\": 57799,
+        \"200\": 57800,
+        \"201\": 57801,
+        \"202\": 57802,
+        \"203\": 57803,
+        \"204\": 57804,
+        \"205\": 57805,
+        \"206\": 57806,
+        \"207\": 57807,
+        \"208\": 57808,
+        \"209\": 57809,
+        \"210\": 57810,
+        \"211\": 57811,
+        \"212\": 57812,
+        \"213\": 57813,
+        \"214\": 57814,
+        \"215\": 57815,
+        \"216\": 57816,
+        \"217\": 57817,
+        \"218\": 57818,
+        \"219\": 57819,
+        \"220\": 57820,
+        \"221\": 57821,
+        \"224\": 57824,
+        \"225\": 57825,
+        \"226\": 57826,
+        \"227\": 57827,
+        \"228\": 57828,
+        \"229\": 57829,
+        \"230\": 57830,
+        \"231\": 57831,
+        \"232\": 57832,
+        \"234\": 57834,
+        \"235\": 57835,
+        \"236\": 57836,
+        \"237\": 57837,
+        \"238\": 57838,
+        \"239\": 57839,
+        \"240\": 57840,
+        \"241\": 57841,
+        \"242\": 57842,
+        \"243\": 57843,
+        \"244\": 57844,
+        \"245\": 57845,
+        \"246\": 57846,
+        \"247\": 57847,
+        \"248\": 57848,
+        \"249\": 57849,
+        \"251\": 57851,
+        \"252\": 57852,
+        \"253\": 57853,
+        \"254\": 57854,
+        \"255\": 57855
+      },
+
+      \"unicode_codes_to_c64_screen_codes\":
+      {
+        \"57376\": [1, 32],
+        \"57438\": [1, 94],
+        \"57665\": [2, 65],
+        \"57666\": [2, 66],
+        \"57667\": [2, 67],
+        \"57668\": [2, 68],
+        \"57669\": [2, 69],
+        \"57670\": [2, 70],
+        \"57671\": [2, 71],
+        \"57672\": [2, 72],
+        \"57673\": [2, 73],
+        \"57674\": [2, 74],
+        \"57675\": [2, 75],
+        \"57676\": [2, 76],
+        \"57677\": [2, 77],
+        \"57678\": [2, 78],
+        \"57679\": [2, 79],
+        \"57680\": [2, 80],
+        \"57681\": [2, 81],
+        \"57682\": [2, 82],
+        \"57683\": [2, 83],
+        \"57684\": [2, 84],
+        \"57685\": [2, 85],
+        \"57686\": [2, 86],
+        \"57687\": [2, 87],
+        \"57688\": [2, 88],
+        \"57689\": [2, 89],
+        \"57690\": [2, 90]
+      }
+    }
+  }
+}
+";
+
+/// Overlay for the strict-unicode mapping profile
+///
+/// Screen code 94 of the uppercase set, printed by PETSCII 126, 222
+/// and 255, is drawn as π on the C64 and decodes to U+03C0 GREEK SMALL
+/// LETTER PI instead of the shade of the default tables.
+pub static STRICT_UNICODE_PROFILE_OVERLAY: &str = "
+{
+  \"petscii\": {
+    \"character_set_map\":
+    {
+      \"c64_screen_codes_set_1_to_unicode_codes\":
+      {
+        \"94\": 960
+      },
+      \"unicode_codes_to_c64_screen_codes\":
+      {
+        \"960\": [1, 94]
+      }
+    }
+  }
+}
+";
+
+/// Overlay for the vice mapping profile
+///
+/// The pound sign, up arrow, left arrow and π decode to the ASCII
+/// characters that took their places, backslash, caret, underscore
+/// and tilde, for text exchanged with VICE and its tools.  Both the
+/// ASCII and the Unicode characters encode to the PETSCII codes.
+pub static VICE_PROFILE_OVERLAY: &str = "
+{
+  \"petscii\": {
+    \"character_set_map\":
+    {
+      \"c64_screen_codes_set_1_to_unicode_codes\":
+      {
+        \"28\": 92,
+        \"30\": 94,
+        \"31\": 95,
+        \"94\": 126
+      },
+      \"unicode_codes_to_c64_screen_codes\":
+      {
+        \"92\": [1, 28],
+        \"94\": [1, 30],
+        \"95\": [1, 31],
+        \"126\": [1, 94],
+        \"960\": [1, 94]
+      }
+    }
+  }
+}
+";
+
+/// Overlay for the cbmconvert mapping profile
+///
+/// For text exchanged with cbmconvert.  The pound sign, which Latin-1
+/// text can hold, is kept, the arrows become caret and underscore,
+/// and π is U+03C0.
+pub static CBMCONVERT_PROFILE_OVERLAY: &str = "
+{
+  \"petscii\": {
+    \"character_set_map\":
+    {
+      \"c64_screen_codes_set_1_to_unicode_codes\":
+      {
+        \"30\": 94,
+        \"31\": 95,
+        \"94\": 960
+      },
+      \"unicode_codes_to_c64_screen_codes\":
+      {
+        \"94\": [1, 30],
+        \"95\": [1, 31],
+        \"960\": [1, 94]
+      }
+    }
+  }
+}
+";
+
+/// ASCII approximations of graphics characters
+///
+/// Maps box drawing, block element, geometric shape, card suit and
+/// Symbols for Legacy Computing characters to the ASCII character
+/// that looks the most like them.  Lines become -, | and +, and
+/// blocks and shades become # and the pound sign becomes L.
+/// Characters that aren't in the map don't have a useful
+/// approximation.  The ascii-approximation configuration is built by
+/// applying this map to the PETSCII screen codes.
+pub static ASCII_APPROXIMATION_MAP: &str = "
+{
+  \"version\": \"0.1.0\",
+
+  \"unicode_codes_to_ascii_codes\":
+  {
+    \"163\": 76,
+    \"8212\": 45,
+    \"8214\": 124,
+    \"8592\": 60,
+    \"8593\": 94,
+    \"8594\": 62,
+    \"8595\": 118,
+    \"8626\": 60,
+    \"8987\": 88,
+    \"8990\": 76,
+    \"9472\": 45,
+    \"9473\": 45,
+    \"9474\": 124,
+    \"9475\": 124,
+    \"9476\": 45,
+    \"9477\": 45,
+    \"9478\": 124,
+    \"9479\": 124,
+    \"9480\": 45,
+    \"9481\": 45,
+    \"9482\": 124,
+    \"9483\": 124,
+    \"9484\": 43,
+    \"9485\": 43,
+    \"9486\": 43,
+    \"9487\": 43,
+    \"9488\": 43,
+    \"9489\": 43,
+    \"9490\": 43,
+    \"9491\": 43,
+    \"9492\": 43,
+    \"9493\": 43,
+    \"9494\": 43,
+    \"9495\": 43,
+    \"9496\": 43,
+    \"9497\": 43,
+    \"9498\": 43,
+    \"9499\": 43,
+    \"9500\": 43,
+    \"9501\": 43,
+    \"9502\": 43,
+    \"9503\": 43,
+    \"9504\": 43,
+    \"9505\": 43,
+    \"9506\": 43,
+    \"9507\": 43,
+    \"9508\": 43,
+    \"9509\": 43,
+    \"9510\": 43,
+    \"9511\": 43,
+    \"9512\": 43,
+    \"9513\": 43,
+    \"9514\": 43,
+    \"9515\": 43,
+    \"9516\": 43,
+    \"9517\": 43,
+    \"9518\": 43,
+    \"9519\": 43,
+    \"9520\": 43,
+    \"9521\": 43,
+    \"9522\": 43,
+    \"9523\": 43,
+    \"9524\": 43,
+    \"9525\": 43,
+    \"9526\": 43,
+    \"9527\": 43,
+    \"9528\": 43,
+    \"9529\": 43,
+    \"9530\": 43,
+    \"9531\": 43,
+    \"9532\": 43,
+    \"9533\": 43,
+    \"9534\": 43,
+    \"9535\": 43,
+    \"9536\": 43,
+    \"9537\": 43,
+    \"9538\": 43,
+    \"9539\": 43,
+    \"9540\": 43,
+    \"9541\": 43,
+    \"9542\": 43,
+    \"9543\": 43,
+    \"9544\": 43,
+    \"9545\": 43,
+    \"9546\": 43,
+    \"9547\": 43,
+    \"9548\": 45,
+    \"9549\": 45,
+    \"9550\": 124,
+    \"9551\": 124,
+    \"9552\": 61,
+    \"9553\": 124,
+    \"9554\": 43,
+    \"9555\": 43,
+    \"9556\": 43,
+    \"9557\": 43,
+    \"9558\": 43,
+    \"9559\": 43,
+    \"9560\": 43,
+    \"9561\": 43,
+    \"9562\": 43,
+    \"9563\": 43,
+    \"9564\": 43,
+    \"9565\": 43,
+    \"9566\": 43,
+    \"9567\": 43,
+    \"9568\": 43,
+    \"9569\": 43,
+    \"9570\": 43,
+    \"9571\": 43,
+    \"9572\": 43,
+    \"9573\": 43,
+    \"9574\": 43,
+    \"9575\": 43,
+    \"9576\": 43,
+    \"9577\": 43,
+    \"9578\": 43,
+    \"9579\": 43,
+    \"9580\": 43,
+    \"9581\": 43,
+    \"9582\": 43,
+    \"9583\": 43,
+    \"9584\": 43,
+    \"9585\": 47,
+    \"9586\": 92,
+    \"9587\": 88,
+    \"9588\": 45,
+    \"9589\": 124,
+    \"9590\": 45,
+    \"9591\": 124,
+    \"9592\": 45,
+    \"9593\": 124,
+    \"9594\": 45,
+    \"9595\": 124,
+    \"9596\": 45,
+    \"9597\": 124,
+    \"9598\": 45,
+    \"9599\": 124,
+    \"9600\": 35,
+    \"9601\": 95,
+    \"9602\": 95,
+    \"9603\": 95,
+    \"9604\": 35,
+    \"9605\": 35,
+    \"9606\": 35,
+    \"9607\": 35,
+    \"9608\": 35,
+    \"9609\": 35,
+    \"9610\": 35,
+    \"9611\": 35,
+    \"9612\": 35,
+    \"9613\": 124,
+    \"9614\": 124,
+    \"9615\": 124,
+    \"9616\": 35,
+    \"9617\": 46,
+    \"9618\": 35,
+    \"9619\": 35,
+    \"9620\": 45,
+    \"9621\": 124,
+    \"9622\": 35,
+    \"9623\": 35,
+    \"9624\": 35,
+    \"9625\": 35,
+    \"9626\": 35,
+    \"9627\": 35,
+    \"9628\": 35,
+    \"9629\": 35,
+    \"9630\": 35,
+    \"9631\": 35,
+    \"9632\": 35,
+    \"9642\": 35,
+    \"9644\": 35,
+    \"9646\": 35,
+    \"9650\": 35,
+    \"9652\": 35,
+    \"9654\": 35,
+    \"9656\": 35,
+    \"9660\": 35,
+    \"9662\": 35,
+    \"9664\": 35,
+    \"9666\": 35,
+    \"9670\": 42,
+    \"9671\": 42,
+    \"9672\": 42,
+    \"9675\": 111,
+    \"9679\": 79,
+    \"9689\": 111,
+    \"9690\": 111,
+    \"9691\": 111,
+    \"9698\": 35,
+    \"9699\": 35,
+    \"9700\": 35,
+    \"9701\": 35,
+    \"9724\": 35,
+    \"9726\": 35,
+    \"9824\": 42,
+    \"9825\": 42,
+    \"9826\": 42,
+    \"9827\": 42,
+    \"9828\": 42,
+    \"9829\": 42,
+    \"9830\": 42,
+    \"9831\": 42,
+    \"10003\": 118,
+    \"129792\": 35,
+    \"129793\": 35,
+    \"129794\": 35,
+    \"129795\": 35,
+    \"129796\": 35,
+    \"129797\": 35,
+    \"129798\": 35,
+    \"129799\": 35,
+    \"129800\": 35,
+    \"129801\": 35,
+    \"129802\": 35,
+    \"129803\": 35,
+    \"129804\": 35,
+    \"129805\": 35,
+    \"129806\": 35,
+    \"129807\": 35,
+    \"129808\": 35,
+    \"129809\": 35,
+    \"129810\": 35,
+    \"129811\": 35,
+    \"129812\": 35,
+    \"129813\": 35,
+    \"129814\": 35,
+    \"129815\": 35,
+    \"129816\": 35,
+    \"129817\": 35,
+    \"129818\": 35,
+    \"129819\": 35,
+    \"129820\": 35,
+    \"129821\": 35,
+    \"129822\": 35,
+    \"129823\": 35,
+    \"129824\": 35,
+    \"129825\": 35,
+    \"129826\": 35,
+    \"129827\": 35,
+    \"129828\": 35,
+    \"129829\": 35,
+    \"129830\": 35,
+    \"129831\": 35,
+    \"129832\": 35,
+    \"129833\": 35,
+    \"129834\": 35,
+    \"129835\": 35,
+    \"129836\": 35,
+    \"129837\": 35,
+    \"129838\": 35,
+    \"129839\": 35,
+    \"129840\": 35,
+    \"129841\": 35,
+    \"129842\": 35,
+    \"129843\": 35,
+    \"129844\": 35,
+    \"129845\": 35,
+    \"129846\": 35,
+    \"129847\": 35,
+    \"129848\": 35,
+    \"129849\": 35,
+    \"129850\": 35,
+    \"129851\": 35,
+    \"129852\": 35,
+    \"129853\": 35,
+    \"129854\": 35,
+    \"129855\": 35,
+    \"129856\": 35,
+    \"129857\": 35,
+    \"129858\": 35,
+    \"129859\": 35,
+    \"129860\": 35,
+    \"129861\": 35,
+    \"129862\": 35,
+    \"129863\": 35,
+    \"129864\": 35,
+    \"129865\": 35,
+    \"129866\": 35,
+    \"129867\": 35,
+    \"129868\": 35,
+    \"129869\": 35,
+    \"129870\": 35,
+    \"129871\": 35,
+    \"129872\": 35,
+    \"129873\": 35,
+    \"129874\": 35,
+    \"129875\": 35,
+    \"129876\": 35,
+    \"129877\": 35,
+    \"129878\": 35,
+    \"129879\": 35,
+    \"129880\": 35,
+    \"129881\": 35,
+    \"129882\": 35,
+    \"129883\": 35,
+    \"129884\": 35,
+    \"129885\": 35,
+    \"129886\": 35,
+    \"129887\": 35,
+    \"129888\": 35,
+    \"129889\": 35,
+    \"129890\": 35,
+    \"129891\": 35,
+    \"129892\": 35,
+    \"129893\": 35,
+    \"129894\": 35,
+    \"129895\": 35,
+    \"129896\": 35,
+    \"129897\": 35,
+    \"129898\": 35,
+    \"129899\": 35,
+    \"129900\": 35,
+    \"129901\": 35,
+    \"129902\": 35,
+    \"129903\": 35,
+    \"129904\": 124,
+    \"129905\": 124,
+    \"129906\": 124,
+    \"129907\": 124,
+    \"129908\": 124,
+    \"129909\": 124,
+    \"129910\": 45,
+    \"129911\": 45,
+    \"129912\": 45,
+    \"129913\": 45,
+    \"129914\": 45,
+    \"129915\": 45,
+    \"129916\": 43,
+    \"129917\": 43,
+    \"129918\": 43,
+    \"129919\": 43,
+    \"129920\": 61,
+    \"129921\": 45,
+    \"129922\": 35,
+    \"129923\": 35,
+    \"129924\": 35,
+    \"129925\": 35,
+    \"129926\": 35,
+    \"129927\": 35,
+    \"129928\": 35,
+    \"129929\": 35,
+    \"129930\": 35,
+    \"129931\": 35,
+    \"129932\": 35,
+    \"129933\": 35,
+    \"129934\": 35,
+    \"129935\": 35,
+    \"129936\": 35,
+    \"129937\": 35,
+    \"129938\": 35,
+    \"129939\": 35,
+    \"129940\": 35,
+    \"129941\": 35,
+    \"129942\": 35,
+    \"129943\": 35,
+    \"129944\": 35,
+    \"129945\": 35,
+    \"129946\": 35,
+    \"129947\": 35,
+    \"129948\": 35,
+    \"129949\": 35,
+    \"129950\": 35,
+    \"129951\": 35,
+    \"129952\": 47,
+    \"129953\": 92,
+    \"129954\": 92,
+    \"129955\": 47,
+    \"129956\": 60,
+    \"129957\": 62,
+    \"129958\": 118,
+    \"129959\": 94,
+    \"129960\": 88,
+    \"129961\": 88,
+    \"129962\": 88,
+    \"129963\": 88,
+    \"129964\": 88,
+    \"129965\": 88,
+    \"129966\": 88,
+    \"129967\": 43,
+    \"129968\": 62,
+    \"129969\": 118,
+    \"129970\": 35,
+    \"129971\": 35,
+    \"129972\": 118,
+    \"129973\": 60,
+    \"129974\": 62,
+    \"129975\": 118,
+    \"129976\": 94,
+    \"129977\": 35,
+    \"129978\": 35,
+    \"129979\": 43,
+    \"129980\": 111,
+    \"129981\": 88,
+    \"129982\": 35,
+    \"129983\": 88,
+    \"129984\": 35,
+    \"129985\": 35,
+    \"129986\": 35,
+    \"129987\": 35,
+    \"129988\": 35,
+    \"129989\": 35,
+    \"129990\": 35,
+    \"129991\": 35,
+    \"129992\": 35,
+    \"129993\": 35,
+    \"129994\": 35,
+    \"130032\": 48,
+    \"130033\": 49,
+    \"130034\": 50,
+    \"130035\": 51,
+    \"130036\": 52,
+    \"130037\": 53,
+    \"130038\": 54,
+    \"130039\": 55,
+    \"130040\": 56,
+    \"130041\": 57
+  }
+}
+";
+
+/// BASIC keyword tables
+///
+/// v2 is the Commodore BASIC V2 table, and the other dialects list
+/// the keywords an extension adds to it.  tokens are single byte
+/// tokens, and prefixed_tokens are two byte tokens keyed by their
+/// first byte.  Codes are written in hex.
+///
+/// The Simons' BASIC table follows the one in VICE's petcat, leaving
+/// out the codes it lists as >>.  BASIC 7.0 is the C128 BASIC, with
+/// the 0xCE and 0xFE prefixes.
+pub static BASIC_KEYWORDS: &str = "
+{
+    \"version\": \"0.2.0\",
+    \"dialects\": {
+	\"v2\": {
+	    \"name\": \"BASIC V2\",
+	    \"tokens\": {
+	  \"0x80\": \"END\",
+	  \"0x81\": \"FOR\",
+	  \"0x82\": \"NEXT\",
+	  \"0x83\": \"DATA\",
+	  \"0x84\": \"INPUT#\",
+	  \"0x85\": \"INPUT\",
+	  \"0x86\": \"DIM\",
+	  \"0x87\": \"READ\",
+	  \"0x88\": \"LET\",
+	  \"0x89\": \"GOTO\",
+	  \"0x8A\": \"RUN\",
+	  \"0x8B\": \"IF\",
+	  \"0x8C\": \"RESTORE\",
+	  \"0x8D\": \"GOSUB\",
+	  \"0x8E\": \"RETURN\",
+	  \"0x8F\": \"REM\",
+	  \"0x90\": \"STOP\",
+	  \"0x91\": \"ON\",
+	  \"0x92\": \"WAIT\",
+	  \"0x93\": \"LOAD\",
+	  \"0x94\": \"SAVE\",
+	  \"0x95\": \"VERIFY\",
+	  \"0x96\": \"DEF\",
+	  \"0x97\": \"POKE\",
+	  \"0x98\": \"PRINT#\",
+	  \"0x99\": \"PRINT\",
+	  \"0x9A\": \"CONT\",
+	  \"0x9B\": \"LIST\",
+	  \"0x9C\": \"CLR\",
+	  \"0x9D\": \"CMD\",
+	  \"0x9E\": \"SYS\",
+	  \"0x9F\": \"OPEN\",
+	  \"0xA0\": \"CLOSE\",
+	  \"0xA1\": \"GET\",
+	  \"0xA2\": \"NEW\",
+	  \"0xA3\": \"TAB(\",
+	  \"0xA4\": \"TO\",
+	  \"0xA5\": \"FN\",
+	  \"0xA6\": \"SPC(\",
+	  \"0xA7\": \"THEN\",
+	  \"0xA8\": \"NOT\",
+	  \"0xA9\": \"STEP\",
+	  \"0xAA\": \"+\",
+	  \"0xAB\": \"-\",
+	  \"0xAC\": \"*\",
+	  \"0xAD\": \"/\",
+	  \"0xAE\": \"^\",
+	  \"0xAF\": \"AND\",
+	  \"0xB0\": \"OR\",
+	  \"0xB1\": \">\",
+	  \"0xB2\": \"=\",
+	  \"0xB3\": \"<\",
+	  \"0xB4\": \"SGN\",
+	  \"0xB5\": \"INT\",
+	  \"0xB6\": \"ABS\",
+	  \"0xB7\": \"USR\",
+	  \"0xB8\": \"FRE\",
+	  \"0xB9\": \"POS\",
+	  \"0xBA\": \"SQR\",
+	  \"0xBB\": \"RND\",
+	  \"0xBC\": \"LOG\",
+	  \"0xBD\": \"EXP\",
+	  \"0xBE\": \"COS\",
+	  \"0xBF\": \"SIN\",
+	  \"0xC0\": \"TAN\",
+	  \"0xC1\": \"ATN\",
+	  \"0xC2\": \"PEEK\",
+	  \"0xC3\": \"LEN\",
+	  \"0xC4\": \"STR$\",
+	  \"0xC5\": \"VAL\",
+	  \"0xC6\": \"ASC\",
+	  \"0xC7\": \"CHR$\",
+	  \"0xC8\": \"LEFT$\",
+	  \"0xC9\": \"RIGHT$\",
+	  \"0xCA\": \"MID$\",
+	  \"0xCB\": \"GO\"
+	}
+	},
+	\"simons\": {
+	    \"name\": \"Simons' BASIC\",
+	    \"prefixed_tokens\": {
+		\"0x64\": {
+		  \"0x01\": \"HIRES\",
+		  \"0x02\": \"PLOT\",
+		  \"0x03\": \"LINE\",
+		  \"0x04\": \"BLOCK\",
+		  \"0x05\": \"FCHR\",
+		  \"0x06\": \"FCOL\",
+		  \"0x07\": \"FILL\",
+		  \"0x08\": \"REC\",
+		  \"0x09\": \"ROT\",
+		  \"0x0A\": \"DRAW\",
+		  \"0x0B\": \"CHAR\",
+		  \"0x0C\": \"HI COL\",
+		  \"0x0D\": \"INV\",
+		  \"0x0E\": \"FRAC\",
+		  \"0x0F\": \"MOVE\",
+		  \"0x10\": \"PLACE\",
+		  \"0x11\": \"UPB\",
+		  \"0x12\": \"UPW\",
+		  \"0x13\": \"LEFTW\",
+		  \"0x14\": \"LEFTB\",
+		  \"0x15\": \"DOWNB\",
+		  \"0x16\": \"DOWNW\",
+		  \"0x17\": \"RIGHTB\",
+		  \"0x18\": \"RIGHTW\",
+		  \"0x19\": \"MULTI\",
+		  \"0x1A\": \"COLOUR\",
+		  \"0x1B\": \"MMOB\",
+		  \"0x1C\": \"BFLASH\",
+		  \"0x1D\": \"MOB SET\",
+		  \"0x1E\": \"MUSIC\",
+		  \"0x1F\": \"FLASH\",
+		  \"0x20\": \"REPEAT\",
+		  \"0x21\": \"PLAY\",
+		  \"0x23\": \"CENTRE\",
+		  \"0x24\": \"ENVELOPE\",
+		  \"0x25\": \"CGOTO\",
+		  \"0x26\": \"WAVE\",
+		  \"0x27\": \"FETCH\",
+		  \"0x28\": \"AT(\",
+		  \"0x29\": \"UNTIL\",
+		  \"0x2C\": \"USE\",
+		  \"0x2E\": \"GLOBAL\",
+		  \"0x30\": \"RESET\",
+		  \"0x31\": \"PROC\",
+		  \"0x32\": \"CALL\",
+		  \"0x33\": \"EXEC\",
+		  \"0x34\": \"END PROC\",
+		  \"0x35\": \"EXIT\",
+		  \"0x36\": \"END LOOP\",
+		  \"0x37\": \"ON KEY\",
+		  \"0x38\": \"DISABLE\",
+		  \"0x39\": \"RESUME\",
+		  \"0x3A\": \"LOOP\",
+		  \"0x3B\": \"DELAY\",
+		  \"0x40\": \"SECURE\",
+		  \"0x41\": \"DISAPA\",
+		  \"0x42\": \"CIRCLE\",
+		  \"0x43\": \"ON ERROR\",
+		  \"0x44\": \"NO ERROR\",
+		  \"0x45\": \"LOCAL\",
+		  \"0x46\": \"RCOMP\",
+		  \"0x47\": \"ELSE\",
+		  \"0x48\": \"RETRACE\",
+		  \"0x49\": \"TRACE\",
+		  \"0x4A\": \"DIR\",
+		  \"0x4B\": \"PAGE\",
+		  \"0x4C\": \"DUMP\",
+		  \"0x4D\": \"FIND\",
+		  \"0x4E\": \"OPTION\",
+		  \"0x4F\": \"AUTO\",
+		  \"0x50\": \"OLD\",
+		  \"0x51\": \"JOY\",
+		  \"0x52\": \"MOD\",
+		  \"0x53\": \"DIV\",
+		  \"0x55\": \"DUP\",
+		  \"0x56\": \"INKEY\",
+		  \"0x57\": \"INST\",
+		  \"0x58\": \"TEST\",
+		  \"0x59\": \"LIN\",
+		  \"0x5A\": \"EXOR\",
+		  \"0x5B\": \"INSERT\",
+		  \"0x5C\": \"POT\",
+		  \"0x5D\": \"PENX\",
+		  \"0x5F\": \"PENY\",
+		  \"0x60\": \"SOUND\",
+		  \"0x61\": \"GRAPHICS\",
+		  \"0x62\": \"DESIGN\",
+		  \"0x63\": \"RLOCMOB\",
+		  \"0x64\": \"CMOB\",
+		  \"0x65\": \"BCKGNDS\",
+		  \"0x66\": \"PAUSE\",
+		  \"0x67\": \"NRM\",
+		  \"0x68\": \"MOB OFF\",
+		  \"0x69\": \"OFF\",
+		  \"0x6A\": \"ANGL\",
+		  \"0x6B\": \"ARC\",
+		  \"0x6C\": \"COLD\",
+		  \"0x6D\": \"SCRSV\",
+		  \"0x6E\": \"SCRLD\",
+		  \"0x6F\": \"TEXT\",
+		  \"0x70\": \"CSET\",
+		  \"0x71\": \"VOL\",
+		  \"0x72\": \"DISK\",
+		  \"0x73\": \"HRDCPY\",
+		  \"0x74\": \"KEY\",
+		  \"0x75\": \"PAINT\",
+		  \"0x76\": \"LOW COL\",
+		  \"0x77\": \"COPY\",
+		  \"0x78\": \"MERGE\",
+		  \"0x79\": \"RENUMBER\",
+		  \"0x7A\": \"MEM\",
+		  \"0x7B\": \"DETECT\",
+		  \"0x7C\": \"CHECK\",
+		  \"0x7D\": \"DISPLAY\",
+		  \"0x7E\": \"ERR\",
+		  \"0x7F\": \"OUT\"
+		}
+	    }
+	},
+	\"basic7\": {
+	    \"name\": \"BASIC 7.0\",
+	    \"tokens\": {
+	  \"0xCC\": \"RGR\",
+	  \"0xCD\": \"RCLR\",
+	  \"0xCF\": \"JOY\",
+	  \"0xD0\": \"RDOT\",
+	  \"0xD1\": \"DEC\",
+	  \"0xD2\": \"HEX$\",
+	  \"0xD3\": \"ERR$\",
+	  \"0xD4\": \"INSTR\",
+	  \"0xD5\": \"ELSE\",
+	  \"0xD6\": \"RESUME\",
+	  \"0xD7\": \"TRAP\",
+	  \"0xD8\": \"TRON\",
+	  \"0xD9\": \"TROFF\",
+	  \"0xDA\": \"SOUND\",
+	  \"0xDB\": \"VOL\",
+	  \"0xDC\": \"AUTO\",
+	  \"0xDD\": \"PUDEF\",
+	  \"0xDE\": \"GRAPHIC\",
+	  \"0xDF\": \"PAINT\",
+	  \"0xE0\": \"CHAR\",
+	  \"0xE1\": \"BOX\",
+	  \"0xE2\": \"CIRCLE\",
+	  \"0xE3\": \"GSHAPE\",
+	  \"0xE4\": \"SSHAPE\",
+	  \"0xE5\": \"DRAW\",
+	  \"0xE6\": \"LOCATE\",
+	  \"0xE7\": \"COLOR\",
+	  \"0xE8\": \"SCNCLR\",
+	  \"0xE9\": \"SCALE\",
+	  \"0xEA\": \"HELP\",
+	  \"0xEB\": \"DO\",
+	  \"0xEC\": \"LOOP\",
+	  \"0xED\": \"EXIT\",
+	  \"0xEE\": \"DIRECTORY\",
+	  \"0xEF\": \"DSAVE\",
+	  \"0xF0\": \"DLOAD\",
+	  \"0xF1\": \"HEADER\",
+	  \"0xF2\": \"SCRATCH\",
+	  \"0xF3\": \"COLLECT\",
+	  \"0xF4\": \"COPY\",
+	  \"0xF5\": \"RENAME\",
+	  \"0xF6\": \"BACKUP\",
+	  \"0xF7\": \"DELETE\",
+	  \"0xF8\": \"RENUMBER\",
+	  \"0xF9\": \"KEY\",
+	  \"0xFA\": \"MONITOR\",
+	  \"0xFB\": \"USING\",
+	  \"0xFC\": \"UNTIL\",
+	  \"0xFD\": \"WHILE\"
+	},
+	    \"prefixed_tokens\": {
+		\"0xCE\": {
+		  \"0x02\": \"POT\",
+		  \"0x03\": \"BUMP\",
+		  \"0x04\": \"PEN\",
+		  \"0x05\": \"RSPPOS\",
+		  \"0x06\": \"RSPRITE\",
+		  \"0x07\": \"RSPCOLOR\",
+		  \"0x08\": \"XOR\",
+		  \"0x09\": \"RWINDOW\",
+		  \"0x0A\": \"POINTER\"
+		},
+		\"0xFE\": {
+		  \"0x02\": \"BANK\",
+		  \"0x03\": \"FILTER\",
+		  \"0x04\": \"PLAY\",
+		  \"0x05\": \"TEMPO\",
+		  \"0x06\": \"MOVSPR\",
+		  \"0x07\": \"SPRITE\",
+		  \"0x08\": \"SPRCOLOR\",
+		  \"0x09\": \"RREG\",
+		  \"0x0A\": \"ENVELOPE\",
+		  \"0x0B\": \"SLEEP\",
+		  \"0x0C\": \"CATALOG\",
+		  \"0x0D\": \"DOPEN\",
+		  \"0x0E\": \"APPEND\",
+		  \"0x0F\": \"DCLOSE\",
+		  \"0x10\": \"BSAVE\",
+		  \"0x11\": \"BLOAD\",
+		  \"0x12\": \"RECORD\",
+		  \"0x13\": \"CONCAT\",
+		  \"0x14\": \"DVERIFY\",
+		  \"0x15\": \"DCLEAR\",
+		  \"0x16\": \"SPRSAV\",
+		  \"0x17\": \"COLLISION\",
+		  \"0x18\": \"BEGIN\",
+		  \"0x19\": \"BEND\",
+		  \"0x1A\": \"WINDOW\",
+		  \"0x1B\": \"BOOT\",
+		  \"0x1C\": \"WIDTH\",
+		  \"0x1D\": \"SPRDEF\",
+		  \"0x1E\": \"QUIT\",
+		  \"0x1F\": \"STASH\",
+		  \"0x21\": \"FETCH\",
+		  \"0x23\": \"SWAP\",
+		  \"0x24\": \"OFF\",
+		  \"0x25\": \"FAST\",
+		  \"0x26\": \"SLOW\"
+		}
+	    }
+	}
+    }
+}
+";
@@ -0,0 +1,249 @@
+//!
+//! Golden file tests for character sets
+//!
+//! A corpus is a directory of cases.  Each case is a few files that
+//! share a name:
+//!
+//! * NAME.bin, the bytes to decode
+//! * NAME.txt, the UTF-8 text they should decode to, exactly, so
+//!   without a newline at the end unless the bytes decode to one
+//! * NAME.encoded.bin, the bytes the text should encode back to.
+//!   This one is optional, and without it the text should encode back
+//!   to NAME.bin.  It's for cases where the round trip isn't exact,
+//!   like control codes that decoding drops.
+//!
+//! Other files in the directory are ignored, so a README can explain
+//! the cases.  run_corpus checks every case with a character set and
+//! returns the ones that failed, so a new system module can be
+//! checked against a directory of samples in one test:
+//!
+//! ```no_run
+//! use forbidden_bands::{corpus::run_corpus, petscii::PetsciiConfig, Configuration, SystemConfig};
+//!
+//! let config = PetsciiConfig::load().expect("Error loading config");
+//! let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+//!
+//! let failures = run_corpus("corpus/petscii", &petscii).expect("Error reading corpus");
+//! for failure in &failures {
+//!     eprintln!("{failure}");
+//! }
+//! assert!(failures.is_empty());
+//! ```
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::Path,
+};
+
+use crate::{error::Error, CharacterSet};
+
+/// The extension of the input files
+const INPUT_EXTENSION: &str = "bin";
+
+/// The suffix of the optional expected encoding files
+const ENCODED_SUFFIX: &str = ".encoded.bin";
+
+/// A case in a corpus
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoldenCase {
+    /// The name the case's files share
+    pub name: String,
+    /// The bytes to decode
+    pub input: Vec<u8>,
+    /// The text the bytes decode to
+    pub expected_text: String,
+    /// The bytes the text encodes to
+    pub expected_bytes: Vec<u8>,
+}
+
+/// How a case failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The bytes decoded to different text
+    Decode {
+        /// The text in the case
+        expected: String,
+        /// The text the character set decoded
+        actual: String,
+    },
+    /// The text encoded to different bytes
+    Encode {
+        /// The bytes in the case
+        expected: Vec<u8>,
+        /// The bytes the character set encoded
+        actual: Vec<u8>,
+    },
+}
+
+/// A case that failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// The name of the case
+    pub name: String,
+    /// How it failed
+    pub mismatch: Mismatch,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.mismatch {
+            Mismatch::Decode { expected, actual } => write!(
+                f,
+                "{}: decoded to {:?}, expected {:?}",
+                self.name, actual, expected
+            ),
+            Mismatch::Encode { expected, actual } => write!(
+                f,
+                "{}: encoded to {:02X?}, expected {:02X?}",
+                self.name, actual, expected
+            ),
+        }
+    }
+}
+
+impl GoldenCase {
+    /// Check the case with a character set
+    ///
+    /// The decode and the encode are checked separately, so a case
+    /// can fail both ways.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     corpus::{GoldenCase, Mismatch},
+    ///     petscii::PetsciiConfig,
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let case = GoldenCase {
+    ///     name: String::from("hi"),
+    ///     input: b"HI".to_vec(),
+    ///     expected_text: String::from("HI"),
+    ///     expected_bytes: b"HI".to_vec(),
+    /// };
+    /// assert!(case.check(&petscii).is_empty());
+    ///
+    /// let case = GoldenCase {
+    ///     expected_text: String::from("HO"),
+    ///     ..case
+    /// };
+    /// assert!(matches!(case.check(&petscii)[0], Mismatch::Decode { .. }));
+    /// ```
+    pub fn check<C: CharacterSet + ?Sized>(&self, charset: &C) -> Vec<Mismatch> {
+        let mut mismatches = Vec::new();
+
+        let actual = charset.decode(&self.input);
+        if actual != self.expected_text {
+            mismatches.push(Mismatch::Decode {
+                expected: self.expected_text.clone(),
+                actual,
+            });
+        }
+
+        let actual = charset.encode(&self.expected_text);
+        if actual != self.expected_bytes {
+            mismatches.push(Mismatch::Encode {
+                expected: self.expected_bytes.clone(),
+                actual,
+            });
+        }
+
+        mismatches
+    }
+}
+
+/// Read the cases in a corpus directory
+///
+/// The cases are sorted by name.  Returns an error if a directory or
+/// file can't be read, or if a case's text isn't UTF-8 or is missing.
+pub fn load_corpus<P: AsRef<Path>>(dir: P) -> std::result::Result<Vec<GoldenCase>, Error> {
+    let dir = dir.as_ref();
+    let mut cases = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if file_name.ends_with(ENCODED_SUFFIX)
+            || path.extension().and_then(|e| e.to_str()) != Some(INPUT_EXTENSION)
+        {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let input = fs::read(&path)?;
+        let expected_text = fs::read_to_string(dir.join(format!("{name}.txt")))?;
+        let encoded = dir.join(format!("{name}{ENCODED_SUFFIX}"));
+        let expected_bytes = if encoded.exists() {
+            fs::read(encoded)?
+        } else {
+            input.clone()
+        };
+
+        cases.push(GoldenCase {
+            name: String::from(name),
+            input,
+            expected_text,
+            expected_bytes,
+        });
+    }
+
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(cases)
+}
+
+/// Check every case in a corpus directory with a character set
+///
+/// Returns the failures, an empty list if every case passed.
+pub fn run_corpus<P: AsRef<Path>, C: CharacterSet + ?Sized>(
+    dir: P,
+    charset: &C,
+) -> std::result::Result<Vec<Failure>, Error> {
+    Ok(load_corpus(dir)?
+        .into_iter()
+        .flat_map(|case| {
+            case.check(charset)
+                .into_iter()
+                .map(move |mismatch| Failure {
+                    name: case.name.clone(),
+                    mismatch,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        corpus::{load_corpus, run_corpus},
+        petscii::PetsciiConfig,
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn corpus_petscii_cases_pass() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let cases = load_corpus("corpus/petscii").unwrap();
+        assert!(cases.iter().any(|case| case.name == "control_codes"));
+        assert!(cases.iter().all(|case| !case.name.ends_with(".encoded")));
+
+        let failures = run_corpus("corpus/petscii", &petscii).unwrap();
+        let report: Vec<String> = failures.iter().map(|f| f.to_string()).collect();
+        assert!(failures.is_empty(), "{report:#?}");
+
+        assert!(load_corpus("corpus/missing").is_err());
+    }
+}
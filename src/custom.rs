@@ -0,0 +1,280 @@
+//!
+//! User-defined 8-bit character sets
+//!
+//! Many machines only need a flat table from each 8-bit code to a
+//! Unicode character.  TableEncoding builds a character set from
+//! a table like that, so new machines can be supported without
+//! adding a module to this crate.
+//!
+//! Tables can be built in code or loaded from a definition file.  A
+//! definition has a name and a map from character codes to Unicode
+//! characters.  Codes can be written in decimal or in hexadecimal
+//! with a 0x prefix.  Characters can be written as code points or as
+//! strings with a single character.  Codes that aren't in the map
+//! aren't part of the character set.
+//!
+//! ```json
+//! {
+//!   "name": "example",
+//!   "characters": {
+//!     "65": 65,
+//!     "0x42": "B",
+//!     "92": "£"
+//!   }
+//! }
+//! ```
+//!
+//! With the toml feature, the same definition can be written in TOML:
+//!
+//! ```toml
+//! name = "example"
+//!
+//! [characters]
+//! 65 = 65
+//! 0x42 = "B"
+//! 92 = "£"
+//! ```
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, ErrorKind},
+    CharacterSet,
+};
+
+/// A Unicode character in a table definition
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum TableEntry {
+    /// A Unicode code point
+    CodePoint(u32),
+    /// A string containing a single character
+    Character(String),
+}
+
+impl TableEntry {
+    /// The character for this entry
+    pub fn to_char(&self) -> std::result::Result<char, Error> {
+        match self {
+            TableEntry::CodePoint(c) => char::from_u32(*c)
+                .ok_or_else(|| Error::new(ErrorKind::Message(format!("Invalid code point {c}")))),
+            TableEntry::Character(s) => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err(Error::new(ErrorKind::Message(format!(
+                        "Table entry {s:?} isn't a single character"
+                    )))),
+                }
+            }
+        }
+    }
+}
+
+/// A character set table definition, as stored in a file
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TableDefinition {
+    /// The name of the character set
+    pub name: String,
+    /// Character codes to Unicode characters
+    pub characters: BTreeMap<String, TableEntry>,
+}
+
+/// Parse a character code written in decimal or 0x-prefixed hex
+pub(crate) fn parse_code(key: &str) -> std::result::Result<u8, Error> {
+    let parsed = match key.strip_prefix("0x").or_else(|| key.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => key.parse::<u8>(),
+    };
+    parsed.map_err(|_| {
+        Error::new(ErrorKind::Message(format!(
+            "Invalid character code {key:?}"
+        )))
+    })
+}
+
+/// A character set defined by a table of 256 entries
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{custom::TableEncoding, CharacterSet};
+///
+/// let mut table = [None; 256];
+/// for code in 0x20..0x7F {
+///     table[code] = char::from_u32(code as u32);
+/// }
+/// table[0x5C] = Some('¥');
+///
+/// let encoding = TableEncoding::new("ascii-yen", table);
+///
+/// assert_eq!(encoding.decode(b"\\100"), "¥100");
+/// assert_eq!(encoding.encode("¥100"), b"\\100");
+/// ```
+#[derive(Clone, Debug)]
+pub struct TableEncoding {
+    name: String,
+    to_unicode: [Option<char>; 256],
+    from_unicode: BTreeMap<char, u8>,
+}
+
+impl TableEncoding {
+    /// Create a character set from a table of 256 entries
+    ///
+    /// If more than one code maps to the same character, the lowest
+    /// code is used when encoding.
+    pub fn new(name: &str, table: [Option<char>; 256]) -> TableEncoding {
+        let mut from_unicode: BTreeMap<char, u8> = BTreeMap::new();
+        for (code, c) in table.iter().enumerate() {
+            if let Some(c) = c {
+                from_unicode.entry(*c).or_insert(code as u8);
+            }
+        }
+
+        TableEncoding {
+            name: String::from(name),
+            to_unicode: table,
+            from_unicode,
+        }
+    }
+
+    /// Create a character set from a table definition
+    pub fn from_definition(
+        definition: &TableDefinition,
+    ) -> std::result::Result<TableEncoding, Error> {
+        let mut table: [Option<char>; 256] = [None; 256];
+
+        for (key, entry) in &definition.characters {
+            table[usize::from(parse_code(key)?)] = Some(entry.to_char()?);
+        }
+
+        Ok(TableEncoding::new(&definition.name, table))
+    }
+
+    /// Load a character set from a JSON table definition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{custom::TableEncoding, CharacterSet};
+    ///
+    /// let json = r#"{ "name": "tiny", "characters": { "0x01": "A", "2": 66 } }"#;
+    /// let encoding = TableEncoding::from_json(json).expect("Error loading table");
+    ///
+    /// assert_eq!(encoding.name(), "tiny");
+    /// assert_eq!(encoding.decode(&[1, 2, 3]), "AB");
+    /// ```
+    pub fn from_json(json_str: &str) -> std::result::Result<TableEncoding, Error> {
+        let definition: TableDefinition = serde_json::from_str(json_str)?;
+        TableEncoding::from_definition(&definition)
+    }
+
+    /// Load a character set from a TOML table definition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{custom::TableEncoding, CharacterSet};
+    ///
+    /// let toml_str = "name = \"tiny\"\n[characters]\n0x01 = \"A\"\n2 = 66\n";
+    /// let encoding = TableEncoding::from_toml(toml_str).expect("Error loading table");
+    ///
+    /// assert_eq!(encoding.decode(&[1, 2]), "AB");
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml_str: &str) -> std::result::Result<TableEncoding, Error> {
+        let definition: TableDefinition = toml::from_str(toml_str)?;
+        TableEncoding::from_definition(&definition)
+    }
+
+    /// The name of the character set
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The Unicode character for a character code
+    pub fn to_unicode(&self, code: u8) -> Option<char> {
+        self.to_unicode[usize::from(code)]
+    }
+
+    /// The character code for a Unicode character
+    pub fn from_unicode(&self, c: char) -> Option<u8> {
+        self.from_unicode.get(&c).copied()
+    }
+
+    /// Create a table definition for this character set
+    ///
+    /// The definition can be saved and loaded again later.
+    pub fn to_definition(&self) -> TableDefinition {
+        TableDefinition {
+            name: self.name.clone(),
+            characters: self
+                .to_unicode
+                .iter()
+                .enumerate()
+                .filter_map(|(code, c)| {
+                    c.map(|c| (code.to_string(), TableEntry::CodePoint(u32::from(c))))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CharacterSet for TableEncoding {
+    /// Decode a sequence of character codes into a Unicode string
+    ///
+    /// Codes that aren't in the table are dropped.
+    fn decode(&self, bytes: &[u8]) -> String {
+        bytes.iter().filter_map(|b| self.to_unicode(*b)).collect()
+    }
+
+    /// Encode a Unicode string into a sequence of character codes
+    fn encode(&self, s: &str) -> Vec<u8> {
+        s.chars().filter_map(|c| self.from_unicode(c)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{custom::TableEncoding, CharacterSet};
+
+    #[test]
+    fn table_encoding_duplicate_characters_encode_to_lowest_code() {
+        let mut table = [None; 256];
+        table[0x41] = Some('A');
+        table[0xC1] = Some('A');
+
+        let encoding = TableEncoding::new("duplicates", table);
+
+        assert_eq!(encoding.decode(&[0x41, 0xC1]), "AA");
+        assert_eq!(encoding.encode("A"), vec![0x41]);
+    }
+
+    #[test]
+    fn table_encoding_invalid_definitions_fail() {
+        let bad_code = r#"{ "name": "bad", "characters": { "256": 65 } }"#;
+        assert!(TableEncoding::from_json(bad_code).is_err());
+
+        let bad_character = r#"{ "name": "bad", "characters": { "1": "AB" } }"#;
+        assert!(TableEncoding::from_json(bad_character).is_err());
+
+        let bad_code_point = r#"{ "name": "bad", "characters": { "1": 55296 } }"#;
+        assert!(TableEncoding::from_json(bad_code_point).is_err());
+    }
+
+    #[test]
+    fn table_encoding_definition_round_trip_works() {
+        let json = r#"{ "name": "pound", "characters": { "0x23": "£", "65": "A" } }"#;
+        let encoding = TableEncoding::from_json(json).unwrap();
+
+        let definition = encoding.to_definition();
+        let copy = TableEncoding::from_definition(&definition).unwrap();
+
+        assert_eq!(copy.name(), "pound");
+        assert_eq!(copy.decode(&[0x23, 0x41]), "£A");
+    }
+}
@@ -0,0 +1,184 @@
+//!
+//! Documents that mix character sets
+//!
+//! A capture from a BBS that speaks more than one protocol switches
+//! character sets partway through: the ASCII login banner, then a
+//! PETSCII menu, then ASCII again after a file transfer.  Decoding
+//! the whole capture as one character set garbles the other parts.
+//!
+//! A Document is a list of runs, each tagged with the character set
+//! its bytes are in.  Document::decode decodes each run with its own
+//! table and joins the text.  Each run is decoded from the start
+//! state of its character set, so a PETSCII run doesn't inherit a
+//! shift or reverse code from an earlier one.
+//!
+//! Finding where the character set changes is up to the caller, who
+//! knows the protocol.  from_boundaries splits a capture at those
+//! offsets.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    error::{Error, ErrorKind},
+    Encoding,
+};
+
+/// The character set of a run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunCharset {
+    /// Plain ASCII.  Printable characters, tabs, carriage returns
+    /// and line feeds are kept, and other bytes decode to U+FFFD.
+    Ascii,
+    /// One of the character sets in this crate
+    Encoding(&'static Encoding),
+}
+
+impl RunCharset {
+    /// Decode bytes in the character set
+    fn decode(self, bytes: &[u8]) -> String {
+        match self {
+            RunCharset::Ascii => bytes
+                .iter()
+                .map(|b| match b {
+                    b'\t' | b'\n' | b'\r' | 0x20..=0x7E => char::from(*b),
+                    _ => char::REPLACEMENT_CHARACTER,
+                })
+                .collect(),
+            RunCharset::Encoding(encoding) => encoding.decode_lossy_cow(bytes).into_owned(),
+        }
+    }
+}
+
+/// A run of bytes in one character set
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Run {
+    /// The character set of the bytes
+    pub charset: RunCharset,
+    /// The bytes
+    pub bytes: Vec<u8>,
+}
+
+impl Run {
+    /// Decode the run
+    pub fn decode(&self) -> String {
+        self.charset.decode(&self.bytes)
+    }
+}
+
+/// A document made of runs in different character sets
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Document {
+    /// The runs, in order
+    pub runs: Vec<Run>,
+}
+
+impl Document {
+    /// Create an empty document
+    pub fn new() -> Document {
+        Document::default()
+    }
+
+    /// Add bytes to the end of the document
+    ///
+    /// Bytes in the same character set as the last run are added to
+    /// it.  Empty runs are skipped.
+    pub fn push(&mut self, charset: RunCharset, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        match self.runs.last_mut() {
+            Some(run) if run.charset == charset => run.bytes.extend_from_slice(bytes),
+            _ => self.runs.push(Run {
+                charset,
+                bytes: bytes.to_vec(),
+            }),
+        }
+    }
+
+    /// Split a capture into runs where the character set changes
+    ///
+    /// Each boundary is the offset a character set starts at.  The
+    /// offsets must be in order and inside the capture, and the first
+    /// run starts at the first boundary, so bytes before it are left
+    /// out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     document::{Document, RunCharset},
+    ///     PETSCII_UNSHIFTED,
+    /// };
+    ///
+    /// let capture = b"LOGIN OK\r\n\x93\x1cMENU\x0d";
+    /// let document = Document::from_boundaries(
+    ///     capture,
+    ///     &[
+    ///         (0, RunCharset::Ascii),
+    ///         (10, RunCharset::Encoding(PETSCII_UNSHIFTED)),
+    ///     ],
+    /// )
+    /// .expect("Error splitting capture");
+    ///
+    /// assert_eq!(document.runs.len(), 2);
+    /// assert_eq!(document.decode(), "LOGIN OK\r\nMENU\r");
+    /// ```
+    pub fn from_boundaries(
+        bytes: &[u8],
+        boundaries: &[(usize, RunCharset)],
+    ) -> std::result::Result<Document, Error> {
+        let mut document = Document::new();
+
+        for (i, (start, charset)) in boundaries.iter().enumerate() {
+            let end = boundaries.get(i + 1).map_or(bytes.len(), |(end, _)| *end);
+            if *start > end || end > bytes.len() {
+                return Err(Error::new(ErrorKind::Message(format!(
+                    "Run boundary {} to {} is out of order or past the end of {} bytes",
+                    start,
+                    end,
+                    bytes.len()
+                ))));
+            }
+            document.push(*charset, &bytes[*start..end]);
+        }
+
+        Ok(document)
+    }
+
+    /// Decode the document, each run with its own character set
+    pub fn decode(&self) -> String {
+        self.runs.iter().map(Run::decode).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        document::{Document, RunCharset},
+        PETSCII_SHIFTED, PETSCII_UNSHIFTED,
+    };
+
+    #[test]
+    fn document_decodes_each_run_with_its_charset() {
+        let mut document = Document::new();
+        document.push(RunCharset::Ascii, b"hi ");
+        document.push(RunCharset::Ascii, b"there\x01");
+        document.push(RunCharset::Encoding(PETSCII_UNSHIFTED), b"\x0eHI");
+        document.push(RunCharset::Encoding(PETSCII_UNSHIFTED), b"");
+        document.push(RunCharset::Ascii, b" ");
+        document.push(RunCharset::Encoding(PETSCII_UNSHIFTED), b"HI");
+        document.push(RunCharset::Encoding(PETSCII_SHIFTED), b"HI");
+
+        assert_eq!(document.runs.len(), 5);
+        assert_eq!(document.runs[0].bytes, b"hi there\x01");
+        // The shift code in the first PETSCII run doesn't carry over
+        assert_eq!(document.decode(), "hi there\u{fffd}hi HIhi");
+
+        let runs = [
+            (2, RunCharset::Ascii),
+            (1, RunCharset::Encoding(PETSCII_UNSHIFTED)),
+        ];
+        assert!(Document::from_boundaries(b"abc", &runs).is_err());
+        assert!(Document::from_boundaries(b"abc", &[(4, RunCharset::Ascii)]).is_err());
+    }
+}
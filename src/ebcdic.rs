@@ -0,0 +1,248 @@
+//!
+//! EBCDIC string library
+//!
+//! EBCDIC is the family of character sets used by IBM mainframes and
+//! midrange systems.  It isn't an 8-bit home computer character set,
+//! but a lot of archives of old software and data contain EBCDIC
+//! text, so it's handy to have it next to the others.
+//!
+//! Unlike ASCII, the letters aren't contiguous.  They're split into
+//! three groups (A-I, J-R and S-Z) with gaps between them, a legacy
+//! of punched card encodings.
+//!
+//! This module supports code page 037 (US/Canada) and code page 500
+//! (International).  They contain the same characters and differ
+//! only in where a few punctuation characters are placed.
+//!
+//! Mainframe text files are usually stored as fixed-length records,
+//! such as 80-column card images, without line separators.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{collections::BTreeMap, sync::RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config_data,
+    error::{Error, ErrorKind},
+    load_cached, CharacterSet, SystemModule,
+};
+
+/// The supported EBCDIC code pages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodePageId {
+    /// Code page 037, US and Canada
+    Cp037,
+    /// Code page 500, International
+    Cp500,
+}
+
+/// Configuration data including code page maps for EBCDIC
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EbcdicConfig {
+    /// Version of the EBCDIC config
+    pub version: String,
+
+    /// Code page 037 to Unicode codes
+    pub cp037_to_unicode_codes: Vec<u32>,
+
+    /// Code page 500 to Unicode codes
+    pub cp500_to_unicode_codes: Vec<u32>,
+}
+
+/// Configuration data for the EBCDIC module
+pub static CONFIG: RwLock<Option<EbcdicConfig>> = RwLock::new(None);
+
+impl SystemModule for EbcdicConfig {
+    const NAMESPACE: &'static str = "ebcdic";
+}
+
+impl EbcdicConfig {
+    /// Load the embedded EBCDIC configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::ebcdic::EbcdicConfig;
+    ///
+    /// let config = EbcdicConfig::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.cp037_to_unicode_codes.len(), 256);
+    /// ```
+    pub fn load() -> std::result::Result<EbcdicConfig, Error> {
+        load_cached(&CONFIG, config_data::EBCDIC_CHARACTER_MAP)
+    }
+
+    /// Build a code page from the configuration tables
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     ebcdic::{CodePageId, EbcdicConfig},
+    ///     CharacterSet,
+    /// };
+    ///
+    /// let config = EbcdicConfig::load().expect("Error loading config");
+    /// let cp037 = config.code_page(CodePageId::Cp037).expect("Error building code page");
+    ///
+    /// assert_eq!(cp037.decode(&[0xC8, 0x85, 0x93, 0x93, 0x96]), "Hello");
+    /// ```
+    pub fn code_page(&self, id: CodePageId) -> std::result::Result<CodePage, Error> {
+        match id {
+            CodePageId::Cp037 => CodePage::new(&self.cp037_to_unicode_codes),
+            CodePageId::Cp500 => CodePage::new(&self.cp500_to_unicode_codes),
+        }
+    }
+}
+
+/// An EBCDIC code page
+pub struct CodePage {
+    to_unicode: Vec<char>,
+    from_unicode: BTreeMap<char, u8>,
+}
+
+impl CodePage {
+    /// Create a code page from a table of 256 Unicode code points
+    pub fn new(table: &[u32]) -> std::result::Result<CodePage, Error> {
+        if table.len() != 256 {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "Code page table has {} entries instead of 256",
+                table.len()
+            ))));
+        }
+
+        let to_unicode = table
+            .iter()
+            .map(|c| {
+                char::from_u32(*c).ok_or_else(|| {
+                    Error::new(ErrorKind::Message(format!("Invalid code point {c}")))
+                })
+            })
+            .collect::<std::result::Result<Vec<char>, Error>>()?;
+
+        let from_unicode = to_unicode
+            .iter()
+            .enumerate()
+            .map(|(code, c)| (*c, code as u8))
+            .collect();
+
+        Ok(CodePage {
+            to_unicode,
+            from_unicode,
+        })
+    }
+
+    /// Decode fixed-length records, such as 80-column card images
+    ///
+    /// Trailing spaces are removed from each record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::ebcdic::{CodePageId, EbcdicConfig};
+    ///
+    /// let config = EbcdicConfig::load().expect("Error loading config");
+    /// let cp037 = config.code_page(CodePageId::Cp037).expect("Error building code page");
+    ///
+    /// let data = [0xC1, 0x40, 0x40, 0xC2, 0xC3, 0x40];
+    ///
+    /// assert_eq!(cp037.decode_fixed_records(&data, 3), vec!["A", "BC"]);
+    /// ```
+    pub fn decode_fixed_records(&self, data: &[u8], record_length: usize) -> Vec<String> {
+        if record_length == 0 {
+            return Vec::new();
+        }
+        data.chunks(record_length)
+            .map(|record| self.decode(record).trim_end_matches(' ').to_string())
+            .collect()
+    }
+}
+
+impl CharacterSet for CodePage {
+    /// Decode a sequence of EBCDIC bytes into a Unicode string
+    ///
+    /// Every EBCDIC code maps to a Unicode character.  The EBCDIC new
+    /// line control (0x15) is decoded as U+0085 NEXT LINE.
+    fn decode(&self, bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .map(|b| self.to_unicode[usize::from(*b)])
+            .collect()
+    }
+
+    /// Encode a Unicode string into a sequence of EBCDIC bytes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     ebcdic::{CodePageId, EbcdicConfig},
+    ///     CharacterSet,
+    /// };
+    ///
+    /// let config = EbcdicConfig::load().expect("Error loading config");
+    /// let cp500 = config.code_page(CodePageId::Cp500).expect("Error building code page");
+    ///
+    /// assert_eq!(cp500.encode("A[1]"), vec![0xC1, 0x4A, 0xF1, 0x5A]);
+    /// ```
+    fn encode(&self, s: &str) -> Vec<u8> {
+        s.chars()
+            .filter_map(|c| self.from_unicode.get(&c).copied())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ebcdic::{CodePage, CodePageId, EbcdicConfig},
+        CharacterSet,
+    };
+
+    #[test]
+    fn ebcdic_letters_and_digits_work() {
+        let config = EbcdicConfig::load().expect("Error loading config");
+        let cp037 = config.code_page(CodePageId::Cp037).unwrap();
+
+        let data: Vec<u8> = (0xC1..=0xC9)
+            .chain(0xD1..=0xD9)
+            .chain(0xE2..=0xE9)
+            .chain(0xF0..=0xF9)
+            .collect();
+
+        assert_eq!(cp037.decode(&data), "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789");
+    }
+
+    #[test]
+    fn ebcdic_code_pages_differ_in_brackets() {
+        let config = EbcdicConfig::load().expect("Error loading config");
+        let cp037 = config.code_page(CodePageId::Cp037).unwrap();
+        let cp500 = config.code_page(CodePageId::Cp500).unwrap();
+
+        assert_eq!(cp037.decode(&[0xBA, 0xBB]), "[]");
+        assert_eq!(cp500.decode(&[0x4A, 0x5A]), "[]");
+        assert_eq!(cp037.encode("[]"), vec![0xBA, 0xBB]);
+    }
+
+    #[test]
+    fn ebcdic_round_trip_all_codes_works() {
+        let config = EbcdicConfig::load().expect("Error loading config");
+        let data: Vec<u8> = (0..=255).collect();
+
+        for id in [CodePageId::Cp037, CodePageId::Cp500] {
+            let code_page = config.code_page(id).unwrap();
+            assert_eq!(code_page.encode(&code_page.decode(&data)), data);
+        }
+    }
+
+    #[test]
+    fn ebcdic_invalid_table_fails() {
+        assert!(CodePage::new(&[0x41; 255]).is_err());
+
+        let mut table = [0x41u32; 256];
+        table[0] = 0xD800;
+        assert!(CodePage::new(&table).is_err());
+    }
+}
@@ -0,0 +1,533 @@
+//!
+//! An encoding_rs style interface to the character sets
+//!
+//! Applications that already handle text through encoding_rs pass
+//! around `&'static Encoding` values and call decode and encode on
+//! them.  This module gives the character sets in this crate the
+//! same shape, so they can be added to those applications with a
+//! little glue instead of a separate code path:
+//!
+//! ```
+//! use forbidden_bands::PETSCII_UNSHIFTED;
+//!
+//! let (text, encoding, had_errors) = PETSCII_UNSHIFTED.decode(b"HELLO\x5c");
+//! assert_eq!(text, "HELLO£");
+//! assert_eq!(encoding.name(), "PETSCII");
+//! assert!(!had_errors);
+//! ```
+//!
+//! Like encoding_rs, decode and encode return the input unchanged,
+//! without copying it, when converting it wouldn't change any bytes.
+//!
+//! Encodings can also be looked up by label, so a charset named in
+//! file metadata can pick the decoder:
+//!
+//! ```
+//! use forbidden_bands::{Encoding, PETSCII_SHIFTED};
+//!
+//! assert_eq!(Encoding::for_label("PETSCII-Shifted"), Some(PETSCII_SHIFTED));
+//! assert_eq!(Encoding::for_label("ebcdic-cp-us").map(|e| e.name()), Some("IBM037"));
+//! assert_eq!(Encoding::for_label("utf-8"), None);
+//! ```
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{
+    borrow::Cow,
+    fmt::{Debug, Formatter},
+    sync::OnceLock,
+};
+
+use crate::{
+    ansi::AnsiConfig,
+    apple2::Apple2Config,
+    bbc::BbcConfig,
+    coco::CocoConfig,
+    ebcdic::{CodePage, CodePageId, EbcdicConfig},
+    error::Error,
+    petscii::{
+        decoder::{DecodeEvent, PetsciiDecoderState},
+        encode_from_state, PetsciiConfig,
+    },
+    ti99::Ti99Config,
+    CharacterSet, Configuration, SystemConfig,
+};
+
+/// A character set that can be decoded and encoded
+///
+/// Encodings are compared by identity, there is only one of each.
+pub struct Encoding {
+    name: &'static str,
+    kind: EncodingKind,
+    /// The printable ASCII bytes that decode to themselves, worked
+    /// out on first use
+    ascii_identical: OnceLock<[bool; 128]>,
+}
+
+/// The character set behind an encoding
+#[derive(Clone, Copy)]
+enum EncodingKind {
+    /// PETSCII, starting in the shifted, lowercase, character set or
+    /// not
+    Petscii {
+        shifted: bool,
+    },
+    Apple2,
+    Bbc,
+    Coco,
+    Ti99,
+    Ebcdic(CodePageId),
+    Cp437,
+}
+
+/// The unshifted PETSCII character set, with uppercase letters and
+/// graphics characters
+pub static PETSCII_UNSHIFTED_INIT: Encoding = Encoding {
+    name: "PETSCII",
+    kind: EncodingKind::Petscii { shifted: false },
+    ascii_identical: OnceLock::new(),
+};
+
+/// The unshifted PETSCII character set
+///
+/// Decoding and encoding start in the uppercase character set, and
+/// encoded text shifts back to it at the end.
+pub static PETSCII_UNSHIFTED: &Encoding = &PETSCII_UNSHIFTED_INIT;
+
+/// The shifted PETSCII character set, with lowercase and uppercase
+/// letters
+pub static PETSCII_SHIFTED_INIT: Encoding = Encoding {
+    name: "PETSCII-shifted",
+    kind: EncodingKind::Petscii { shifted: true },
+    ascii_identical: OnceLock::new(),
+};
+
+/// The shifted PETSCII character set
+///
+/// Decoding and encoding start in the lowercase character set, the
+/// one the C64 shows after pressing Commodore and Shift.
+pub static PETSCII_SHIFTED: &Encoding = &PETSCII_SHIFTED_INIT;
+
+/// Apple II high-bit ASCII
+pub static APPLE2_INIT: Encoding = Encoding {
+    name: "Apple-II",
+    kind: EncodingKind::Apple2,
+    ascii_identical: OnceLock::new(),
+};
+
+/// Apple II high-bit ASCII, as stored in DOS 3.3 and ProDOS text
+/// files
+pub static APPLE2: &Encoding = &APPLE2_INIT;
+
+/// BBC Micro text
+pub static BBC_MICRO_INIT: Encoding = Encoding {
+    name: "BBC-Micro",
+    kind: EncodingKind::Bbc,
+    ascii_identical: OnceLock::new(),
+};
+
+/// BBC Micro text, with VDU commands skipped when decoding
+pub static BBC_MICRO: &Encoding = &BBC_MICRO_INIT;
+
+/// TRS-80 Color Computer text
+pub static COCO_INIT: Encoding = Encoding {
+    name: "CoCo",
+    kind: EncodingKind::Coco,
+    ascii_identical: OnceLock::new(),
+};
+
+/// TRS-80 Color Computer text, decoded to the characters the VDG
+/// shows
+pub static COCO: &Encoding = &COCO_INIT;
+
+/// TI-99/4A character codes
+pub static TI99_INIT: Encoding = Encoding {
+    name: "TI-99",
+    kind: EncodingKind::Ti99,
+    ascii_identical: OnceLock::new(),
+};
+
+/// TI-99/4A character codes
+pub static TI99: &Encoding = &TI99_INIT;
+
+/// EBCDIC code page 037
+pub static IBM037_INIT: Encoding = Encoding {
+    name: "IBM037",
+    kind: EncodingKind::Ebcdic(CodePageId::Cp037),
+    ascii_identical: OnceLock::new(),
+};
+
+/// EBCDIC code page 037, US and Canada
+pub static IBM037: &Encoding = &IBM037_INIT;
+
+/// EBCDIC code page 500
+pub static IBM500_INIT: Encoding = Encoding {
+    name: "IBM500",
+    kind: EncodingKind::Ebcdic(CodePageId::Cp500),
+    ascii_identical: OnceLock::new(),
+};
+
+/// EBCDIC code page 500, International
+pub static IBM500: &Encoding = &IBM500_INIT;
+
+/// IBM PC code page 437
+pub static IBM437_INIT: Encoding = Encoding {
+    name: "IBM437",
+    kind: EncodingKind::Cp437,
+    ascii_identical: OnceLock::new(),
+};
+
+/// IBM PC code page 437, the character set of DOS and ANSI art
+pub static IBM437: &Encoding = &IBM437_INIT;
+
+/// Labels and the encodings they name, in lowercase
+///
+/// The EBCDIC and code page 437 labels are the IANA names and
+/// aliases.
+const LABELS: [(&str, &Encoding); 32] = [
+    ("petscii", &PETSCII_UNSHIFTED_INIT),
+    ("petscii-unshifted", &PETSCII_UNSHIFTED_INIT),
+    ("petscii-uppercase", &PETSCII_UNSHIFTED_INIT),
+    ("cbm", &PETSCII_UNSHIFTED_INIT),
+    ("commodore", &PETSCII_UNSHIFTED_INIT),
+    ("petscii-shifted", &PETSCII_SHIFTED_INIT),
+    ("petscii-lowercase", &PETSCII_SHIFTED_INIT),
+    ("apple-ii", &APPLE2_INIT),
+    ("apple2", &APPLE2_INIT),
+    ("bbc-micro", &BBC_MICRO_INIT),
+    ("bbc", &BBC_MICRO_INIT),
+    ("acorn", &BBC_MICRO_INIT),
+    ("coco", &COCO_INIT),
+    ("trs-80-coco", &COCO_INIT),
+    ("ti-99", &TI99_INIT),
+    ("ti99", &TI99_INIT),
+    ("ti-99/4a", &TI99_INIT),
+    ("ibm037", &IBM037_INIT),
+    ("cp037", &IBM037_INIT),
+    ("ebcdic-cp-us", &IBM037_INIT),
+    ("ebcdic-cp-ca", &IBM037_INIT),
+    ("csibm037", &IBM037_INIT),
+    ("ebcdic-cp-wt", &IBM037_INIT),
+    ("ibm500", &IBM500_INIT),
+    ("cp500", &IBM500_INIT),
+    ("ebcdic-cp-be", &IBM500_INIT),
+    ("ebcdic-cp-ch", &IBM500_INIT),
+    ("csibm500", &IBM500_INIT),
+    ("ibm437", &IBM437_INIT),
+    ("cp437", &IBM437_INIT),
+    ("437", &IBM437_INIT),
+    ("cspc8codepage437", &IBM437_INIT),
+];
+
+// Configurations for the encodings, loaded on first use
+static PETSCII: OnceLock<SystemConfig> = OnceLock::new();
+static APPLE2_CONFIG: OnceLock<Apple2Config> = OnceLock::new();
+static BBC_CONFIG: OnceLock<BbcConfig> = OnceLock::new();
+static COCO_CONFIG: OnceLock<CocoConfig> = OnceLock::new();
+static TI99_CONFIG: OnceLock<Ti99Config> = OnceLock::new();
+static CP037: OnceLock<CodePage> = OnceLock::new();
+static CP500: OnceLock<CodePage> = OnceLock::new();
+static CP437: OnceLock<CodePage> = OnceLock::new();
+
+/// Get a configuration, loading it on first use
+///
+/// The embedded configurations always load, so failing to is a bug.
+fn cached<T>(cell: &'static OnceLock<T>, load: fn() -> Result<T, Error>) -> &'static T {
+    cell.get_or_init(|| load().expect("Error loading config"))
+}
+
+/// Get the PETSCII configuration, loading it on first use
+fn petscii() -> &'static SystemConfig {
+    cached(&PETSCII, || PetsciiConfig::load()?.system::<SystemConfig>())
+}
+
+impl Encoding {
+    /// Look up an encoding by one of its labels
+    ///
+    /// Labels are matched ignoring case and surrounding whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{encoding::APPLE2, Encoding, PETSCII_UNSHIFTED};
+    ///
+    /// assert_eq!(Encoding::for_label(" petscii "), Some(PETSCII_UNSHIFTED));
+    /// assert_eq!(Encoding::for_label("Apple2"), Some(APPLE2));
+    /// ```
+    pub fn for_label(label: &str) -> Option<&'static Encoding> {
+        let label = label.trim().to_ascii_lowercase();
+        LABELS
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map(|(_, encoding)| *encoding)
+    }
+
+    /// The canonical name of the encoding
+    pub fn name(&'static self) -> &'static str {
+        self.name
+    }
+
+    /// The character set behind the encoding
+    fn character_set(&'static self) -> &'static dyn CharacterSet {
+        match self.kind {
+            EncodingKind::Petscii { .. } => petscii(),
+            EncodingKind::Apple2 => cached::<Apple2Config>(&APPLE2_CONFIG, Apple2Config::load),
+            EncodingKind::Bbc => cached::<BbcConfig>(&BBC_CONFIG, BbcConfig::load),
+            EncodingKind::Coco => cached::<CocoConfig>(&COCO_CONFIG, CocoConfig::load),
+            EncodingKind::Ti99 => cached::<Ti99Config>(&TI99_CONFIG, Ti99Config::load),
+            EncodingKind::Ebcdic(id) => {
+                let cell = match id {
+                    CodePageId::Cp037 => &CP037,
+                    CodePageId::Cp500 => &CP500,
+                };
+                cell.get_or_init(|| {
+                    EbcdicConfig::load()
+                        .and_then(|config| config.code_page(id))
+                        .expect("Error loading config")
+                })
+            }
+            EncodingKind::Cp437 => cached::<CodePage>(&CP437, || AnsiConfig::load()?.cp437()),
+        }
+    }
+
+    /// Decode bytes into a string
+    ///
+    /// Returns the string, the encoding used and whether any bytes
+    /// had no character.  Control codes aren't errors, they change
+    /// the decoder state or are skipped.  Only PETSCII has bytes
+    /// without a character, the other character sets drop control
+    /// codes and never report errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{PETSCII_SHIFTED, PETSCII_UNSHIFTED};
+    ///
+    /// assert_eq!(PETSCII_UNSHIFTED.decode(b"\x41\xc1").0, "A♠");
+    /// assert_eq!(PETSCII_SHIFTED.decode(b"\x41\x42").0, "ab");
+    /// ```
+    pub fn decode<'a>(&'static self, bytes: &'a [u8]) -> (Cow<'a, str>, &'static Encoding, bool) {
+        let (s, had_errors) = self.decode_without_bom_handling(bytes);
+        (s, self, had_errors)
+    }
+
+    /// Decode bytes into a string
+    ///
+    /// None of the character sets have byte order marks, so this is
+    /// the same as decode without the encoding in the result.
+    pub fn decode_without_bom_handling<'a>(&'static self, bytes: &'a [u8]) -> (Cow<'a, str>, bool) {
+        if let Some(s) = self.borrow_ascii_identical(bytes) {
+            return (Cow::Borrowed(s), false);
+        }
+        let (s, had_errors) = self.decode_to_string(bytes);
+
+        if s.as_bytes() == bytes {
+            // Only ASCII decodes to itself, so the bytes are UTF-8
+            let s = std::str::from_utf8(bytes).expect("Decoded text is valid UTF-8");
+            return (Cow::Borrowed(s), had_errors);
+        }
+        (Cow::Owned(s), had_errors)
+    }
+
+    /// Decode bytes into a string, dropping bytes without a character
+    ///
+    /// Printable ASCII that decodes to the same characters, like most
+    /// file names, is returned as it is without decoding or copying
+    /// it, so indexing directories doesn't allocate for each name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use forbidden_bands::PETSCII_UNSHIFTED;
+    ///
+    /// assert!(matches!(
+    ///     PETSCII_UNSHIFTED.decode_lossy_cow(b"GAME 2"),
+    ///     Cow::Borrowed("GAME 2")
+    /// ));
+    /// assert_eq!(PETSCII_UNSHIFTED.decode_lossy_cow(b"GAME\x5c"), "GAME£");
+    /// ```
+    pub fn decode_lossy_cow<'a>(&'static self, bytes: &'a [u8]) -> Cow<'a, str> {
+        self.decode_without_bom_handling(bytes).0
+    }
+
+    /// The bytes as a string, if they're all printable ASCII that
+    /// decodes to itself
+    fn borrow_ascii_identical<'a>(&'static self, bytes: &'a [u8]) -> Option<&'a str> {
+        let identical = self.ascii_identical.get_or_init(|| {
+            let mut identical = [false; 128];
+            for b in 0x20..0x7F_u8 {
+                identical[usize::from(b)] =
+                    self.decode_to_string(&[b]).0 == char::from(b).to_string();
+            }
+            identical
+        });
+
+        bytes
+            .iter()
+            .all(|b| identical.get(usize::from(*b)) == Some(&true))
+            .then(|| std::str::from_utf8(bytes).expect("Printable ASCII is valid UTF-8"))
+    }
+
+    /// Decode bytes into a new string, and whether any bytes had no
+    /// character
+    fn decode_to_string(&'static self, bytes: &[u8]) -> (String, bool) {
+        match self.kind {
+            EncodingKind::Petscii { shifted } => decode_petscii(bytes, shifted),
+            _ => (self.character_set().decode(bytes), false),
+        }
+    }
+
+    /// Encode a string into bytes
+    ///
+    /// Returns the bytes, the encoding used and whether any
+    /// characters couldn't be encoded.  Characters that can't be
+    /// encoded are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{PETSCII_SHIFTED, PETSCII_UNSHIFTED};
+    ///
+    /// assert_eq!(&PETSCII_UNSHIFTED.encode("Ab").0[..], [0x41, 0x0E, 0x42, 0x8E]);
+    /// assert_eq!(&PETSCII_SHIFTED.encode("aB").0[..], [0x41, 0x8E, 0x42, 0x0E]);
+    ///
+    /// let (_, _, had_errors) = PETSCII_UNSHIFTED.encode("€");
+    /// assert!(had_errors);
+    /// ```
+    pub fn encode<'a>(&'static self, s: &'a str) -> (Cow<'a, [u8]>, &'static Encoding, bool) {
+        let character_set = self.character_set();
+        let had_errors = s
+            .chars()
+            .any(|c| character_set.encode(c.encode_utf8(&mut [0; 4])).is_empty());
+
+        let bytes = match self.kind {
+            EncodingKind::Petscii { shifted } => encode_petscii(s, shifted),
+            _ => character_set.encode(s),
+        };
+
+        if bytes == s.as_bytes() {
+            return (Cow::Borrowed(s.as_bytes()), self, had_errors);
+        }
+        (Cow::Owned(bytes), self, had_errors)
+    }
+}
+
+/// Decode PETSCII starting in a character set
+fn decode_petscii(bytes: &[u8], shifted: bool) -> (String, bool) {
+    let mut state = PetsciiDecoderState::new(Some(petscii()));
+    if shifted {
+        state.feed(0x0E);
+    }
+
+    let mut had_errors = false;
+    let s = bytes
+        .iter()
+        .filter_map(|b| match state.feed(*b) {
+            DecodeEvent::Char(c) => Some(c),
+            DecodeEvent::Nothing => {
+                had_errors = true;
+                None
+            }
+            _ => None,
+        })
+        .collect();
+    (s, had_errors)
+}
+
+/// Encode PETSCII starting in a character set, and shift back to it
+/// at the end
+fn encode_petscii(s: &str, shifted: bool) -> Vec<u8> {
+    let mut state = shifted;
+    let mut bytes = encode_from_state(s, &petscii().character_set_map, &mut state);
+    if state != shifted {
+        bytes.push(if shifted { 0x0E } else { 0x8E });
+    }
+    bytes
+}
+
+impl PartialEq for Encoding {
+    fn eq(&self, other: &Encoding) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for Encoding {}
+
+impl Debug for Encoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Encoding {{ {} }}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::encoding::{Encoding, IBM437, IBM500, PETSCII_SHIFTED, PETSCII_UNSHIFTED};
+
+    #[test]
+    fn encoding_borrows_unchanged_input() {
+        let (s, encoding, had_errors) = PETSCII_UNSHIFTED.decode(b"LOAD\"*\",8,1");
+        assert!(matches!(s, Cow::Borrowed("LOAD\"*\",8,1")));
+        assert_eq!(encoding, PETSCII_UNSHIFTED);
+        assert!(!had_errors);
+
+        let (bytes, _, _) = PETSCII_UNSHIFTED.encode("RUN");
+        assert!(matches!(bytes, Cow::Borrowed(b"RUN")));
+
+        // The same bytes are lowercase in the shifted character set
+        let (s, encoding, _) = PETSCII_SHIFTED.decode(b"RUN");
+        assert!(matches!(s, Cow::Owned(_)));
+        assert_eq!(s, "run");
+        assert_ne!(encoding, PETSCII_UNSHIFTED);
+
+        // Only the bytes that decode to themselves are borrowed
+        assert!(matches!(
+            PETSCII_SHIFTED.decode_lossy_cow(b"[1541]"),
+            Cow::Borrowed("[1541]")
+        ));
+        assert!(matches!(
+            PETSCII_UNSHIFTED.decode_lossy_cow(b"A_B"),
+            Cow::Owned(_)
+        ));
+    }
+
+    #[test]
+    fn encoding_labels_find_other_character_sets() {
+        let cp500 = Encoding::for_label("CP500").unwrap();
+        assert_eq!(cp500, IBM500);
+        assert_eq!(cp500.decode(&[0xC1, 0x4A]).0, "A[");
+        assert_eq!(&cp500.encode("A[").0[..], [0xC1, 0x4A]);
+
+        // Lowercase ASCII is the same in CoCo text
+        let (bytes, encoding, had_errors) = Encoding::for_label("coco").unwrap().encode("run");
+        assert!(matches!(bytes, Cow::Borrowed(b"run")));
+        assert_eq!(encoding.name(), "CoCo");
+        assert!(!had_errors);
+
+        assert_eq!(Encoding::for_label("atascii"), None);
+    }
+
+    #[test]
+    fn encoding_labels_find_code_page_437() {
+        for label in ["cp437", "IBM437", " 437 ", "csPC8CodePage437"] {
+            assert_eq!(Encoding::for_label(label), Some(IBM437));
+        }
+
+        let (s, encoding, had_errors) = IBM437.decode(&[0xC9, 0xCD, 0xBB, 0xDB]);
+        assert_eq!(s, "╔═╗█");
+        assert_eq!(encoding.name(), "IBM437");
+        assert!(!had_errors);
+        assert_eq!(&IBM437.encode("╚═╝").0[..], [0xC8, 0xCD, 0xBC]);
+    }
+
+    #[test]
+    fn encoding_round_trips_shifted_text() {
+        let (bytes, _, had_errors) = PETSCII_SHIFTED.encode("Hello, World ♠");
+        assert!(!had_errors);
+        assert_eq!(PETSCII_SHIFTED.decode(&bytes).0, "Hello, World ♠");
+        assert_eq!(bytes.last(), Some(&0x0E));
+    }
+}
@@ -9,6 +9,13 @@ pub enum ErrorKind {
     /// Generic error type
     // TODO: More error types
     Message(String),
+    /// A grapheme cluster couldn't be encoded
+    Unencodable {
+        /// The grapheme cluster
+        grapheme: String,
+        /// The byte offset of the grapheme cluster in the string
+        offset: usize,
+    },
 }
 
 /// It's an error type, with tons of info
@@ -16,10 +23,25 @@ pub struct Error {
     kind: ErrorKind,
 }
 
+impl Error {
+    /// Create a new error with the given kind
+    pub fn new(kind: ErrorKind) -> Error {
+        Error { kind }
+    }
+
+    /// The kind of error that occurred
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match &self.kind {
             ErrorKind::Message(m) => write!(f, "Some error occurred: {:?}", m),
+            ErrorKind::Unencodable { grapheme, offset } => {
+                write!(f, "Can't encode {:?} at byte {}", grapheme, offset)
+            }
         }
     }
 }
@@ -45,3 +67,21 @@ impl From<std::io::Error> for Error {
         }
     }
 }
+
+#[cfg(feature = "gif")]
+impl From<gif::EncodingError> for Error {
+    fn from(e: gif::EncodingError) -> Self {
+        Error {
+            kind: ErrorKind::Message(e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error {
+            kind: ErrorKind::Message(e.to_string()),
+        }
+    }
+}
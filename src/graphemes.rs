@@ -0,0 +1,384 @@
+//!
+//! Grapheme-aware encoding
+//!
+//! A user-perceived character can be made of more than one Unicode
+//! code point.  An accented letter can be written as the base letter
+//! followed by a combining mark, for example e followed by U+0301
+//! COMBINING ACUTE ACCENT.  Encoding code point by code point maps
+//! the e and silently drops the accent.
+//!
+//! This module encodes a string one grapheme cluster at a time.  A
+//! cluster is either encoded as a whole, transliterated to its base
+//! character, or reported as an error with the position of the
+//! cluster in the string.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    error::{Error, ErrorKind},
+    CharacterSet, EncodeOptions,
+};
+
+/// Precomposed Latin-1 Supplement and Latin Extended-A letters and
+/// their base letters
+///
+/// Without the unicode-normalization feature this is how a
+/// precomposed letter like É falls back to E, the same as the
+/// decomposed E followed by a combining acute accent.  Letters with
+/// a stroke, like Ø and Ł, fall back to the letter without it.
+const LATIN_BASE_LETTERS: [(char, char); 172] = [
+    ('À', 'A'),
+    ('Á', 'A'),
+    ('Â', 'A'),
+    ('Ã', 'A'),
+    ('Ä', 'A'),
+    ('Å', 'A'),
+    ('Ç', 'C'),
+    ('È', 'E'),
+    ('É', 'E'),
+    ('Ê', 'E'),
+    ('Ë', 'E'),
+    ('Ì', 'I'),
+    ('Í', 'I'),
+    ('Î', 'I'),
+    ('Ï', 'I'),
+    ('Ñ', 'N'),
+    ('Ò', 'O'),
+    ('Ó', 'O'),
+    ('Ô', 'O'),
+    ('Õ', 'O'),
+    ('Ö', 'O'),
+    ('Ø', 'O'),
+    ('Ù', 'U'),
+    ('Ú', 'U'),
+    ('Û', 'U'),
+    ('Ü', 'U'),
+    ('Ý', 'Y'),
+    ('à', 'a'),
+    ('á', 'a'),
+    ('â', 'a'),
+    ('ã', 'a'),
+    ('ä', 'a'),
+    ('å', 'a'),
+    ('ç', 'c'),
+    ('è', 'e'),
+    ('é', 'e'),
+    ('ê', 'e'),
+    ('ë', 'e'),
+    ('ì', 'i'),
+    ('í', 'i'),
+    ('î', 'i'),
+    ('ï', 'i'),
+    ('ñ', 'n'),
+    ('ò', 'o'),
+    ('ó', 'o'),
+    ('ô', 'o'),
+    ('õ', 'o'),
+    ('ö', 'o'),
+    ('ø', 'o'),
+    ('ù', 'u'),
+    ('ú', 'u'),
+    ('û', 'u'),
+    ('ü', 'u'),
+    ('ý', 'y'),
+    ('ÿ', 'y'),
+    ('Ā', 'A'),
+    ('ā', 'a'),
+    ('Ă', 'A'),
+    ('ă', 'a'),
+    ('Ą', 'A'),
+    ('ą', 'a'),
+    ('Ć', 'C'),
+    ('ć', 'c'),
+    ('Ĉ', 'C'),
+    ('ĉ', 'c'),
+    ('Ċ', 'C'),
+    ('ċ', 'c'),
+    ('Č', 'C'),
+    ('č', 'c'),
+    ('Ď', 'D'),
+    ('ď', 'd'),
+    ('Đ', 'D'),
+    ('đ', 'd'),
+    ('Ē', 'E'),
+    ('ē', 'e'),
+    ('Ĕ', 'E'),
+    ('ĕ', 'e'),
+    ('Ė', 'E'),
+    ('ė', 'e'),
+    ('Ę', 'E'),
+    ('ę', 'e'),
+    ('Ě', 'E'),
+    ('ě', 'e'),
+    ('Ĝ', 'G'),
+    ('ĝ', 'g'),
+    ('Ğ', 'G'),
+    ('ğ', 'g'),
+    ('Ġ', 'G'),
+    ('ġ', 'g'),
+    ('Ģ', 'G'),
+    ('ģ', 'g'),
+    ('Ĥ', 'H'),
+    ('ĥ', 'h'),
+    ('Ħ', 'H'),
+    ('ħ', 'h'),
+    ('Ĩ', 'I'),
+    ('ĩ', 'i'),
+    ('Ī', 'I'),
+    ('ī', 'i'),
+    ('Ĭ', 'I'),
+    ('ĭ', 'i'),
+    ('Į', 'I'),
+    ('į', 'i'),
+    ('İ', 'I'),
+    ('ı', 'i'),
+    ('Ĵ', 'J'),
+    ('ĵ', 'j'),
+    ('Ķ', 'K'),
+    ('ķ', 'k'),
+    ('Ĺ', 'L'),
+    ('ĺ', 'l'),
+    ('Ļ', 'L'),
+    ('ļ', 'l'),
+    ('Ľ', 'L'),
+    ('ľ', 'l'),
+    ('Ł', 'L'),
+    ('ł', 'l'),
+    ('Ń', 'N'),
+    ('ń', 'n'),
+    ('Ņ', 'N'),
+    ('ņ', 'n'),
+    ('Ň', 'N'),
+    ('ň', 'n'),
+    ('Ō', 'O'),
+    ('ō', 'o'),
+    ('Ŏ', 'O'),
+    ('ŏ', 'o'),
+    ('Ő', 'O'),
+    ('ő', 'o'),
+    ('Ŕ', 'R'),
+    ('ŕ', 'r'),
+    ('Ŗ', 'R'),
+    ('ŗ', 'r'),
+    ('Ř', 'R'),
+    ('ř', 'r'),
+    ('Ś', 'S'),
+    ('ś', 's'),
+    ('Ŝ', 'S'),
+    ('ŝ', 's'),
+    ('Ş', 'S'),
+    ('ş', 's'),
+    ('Š', 'S'),
+    ('š', 's'),
+    ('Ţ', 'T'),
+    ('ţ', 't'),
+    ('Ť', 'T'),
+    ('ť', 't'),
+    ('Ŧ', 'T'),
+    ('ŧ', 't'),
+    ('Ũ', 'U'),
+    ('ũ', 'u'),
+    ('Ū', 'U'),
+    ('ū', 'u'),
+    ('Ŭ', 'U'),
+    ('ŭ', 'u'),
+    ('Ů', 'U'),
+    ('ů', 'u'),
+    ('Ű', 'U'),
+    ('ű', 'u'),
+    ('Ų', 'U'),
+    ('ų', 'u'),
+    ('Ŵ', 'W'),
+    ('ŵ', 'w'),
+    ('Ŷ', 'Y'),
+    ('ŷ', 'y'),
+    ('Ÿ', 'Y'),
+    ('Ź', 'Z'),
+    ('ź', 'z'),
+    ('Ż', 'Z'),
+    ('ż', 'z'),
+    ('Ž', 'Z'),
+    ('ž', 'z'),
+];
+
+/// The base letter of a precomposed Latin letter
+fn base_letter(c: char) -> Option<char> {
+    LATIN_BASE_LETTERS
+        .iter()
+        .find(|(letter, _)| *letter == c)
+        .map(|(_, base)| *base)
+}
+
+/// Check if every character in a string can be encoded
+fn all_encodable<C: CharacterSet + ?Sized>(charset: &C, s: &str) -> bool {
+    s.chars()
+        .all(|c| !charset.encode(c.encode_utf8(&mut [0; 4])).is_empty())
+}
+
+/// Find the text to encode for a grapheme cluster
+///
+/// Returns None if there isn't any way to encode the cluster.
+fn encodable_cluster<C: CharacterSet + ?Sized>(
+    charset: &C,
+    cluster: &str,
+    options: &EncodeOptions,
+) -> Option<String> {
+    if all_encodable(charset, cluster) {
+        return Some(String::from(cluster));
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    if options.normalize {
+        let normalized = crate::normalize::normalize_for_encoding(cluster, |c| {
+            !charset.encode(c.encode_utf8(&mut [0; 4])).is_empty()
+        });
+        if all_encodable(charset, &normalized) {
+            return Some(normalized);
+        }
+    }
+
+    // Without any features enabled there aren't any options
+    let _ = options;
+
+    // Fall back to the base character, dropping the combining marks
+    // and other extending characters, and then to the base letter of
+    // a precomposed letter, so both forms of a letter encode the same
+    let mut chars = cluster.chars();
+    let first = chars.next()?;
+    let has_marks = chars.next().is_some();
+    [has_marks.then_some(first), base_letter(first)]
+        .into_iter()
+        .flatten()
+        .find(|c| all_encodable(charset, c.encode_utf8(&mut [0; 4])))
+        .map(String::from)
+}
+
+/// Encode a string one grapheme cluster at a time
+///
+/// Clusters that can't be encoded as they are, with normalization if
+/// it's enabled in the options, are transliterated to their base
+/// character.  Precomposed Latin-1 and Latin Extended-A letters are
+/// transliterated to their base letter too, even without the
+/// unicode-normalization feature.  If the base character can't be encoded either, an
+/// Unencodable error is returned with the first cluster that
+/// couldn't be encoded and its byte offset in the string.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     error::ErrorKind, graphemes::encode_graphemes, petscii::PetsciiConfig, CharacterSet,
+///     Configuration, EncodeOptions, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let options = EncodeOptions::default();
+///
+/// let bytes = encode_graphemes(&petscii, "CAFE\u{301}", &options).expect("Error encoding");
+/// assert_eq!(bytes, petscii.encode("CAFE"));
+/// let bytes = encode_graphemes(&petscii, "CAFÉ", &options).expect("Error encoding");
+/// assert_eq!(bytes, petscii.encode("CAFE"));
+///
+/// let error = encode_graphemes(&petscii, "CAFÉ 𝄞", &options).unwrap_err();
+/// assert!(matches!(
+///     error.kind(),
+///     ErrorKind::Unencodable { grapheme, offset: 6 } if grapheme == "𝄞"
+/// ));
+/// ```
+pub fn encode_graphemes<C: CharacterSet + ?Sized>(
+    charset: &C,
+    s: &str,
+    options: &EncodeOptions,
+) -> std::result::Result<Vec<u8>, Error> {
+    let mut text = String::with_capacity(s.len());
+
+    for (offset, cluster) in s.grapheme_indices(true) {
+        match encodable_cluster(charset, cluster, options) {
+            Some(encodable) => text.push_str(&encodable),
+            None => {
+                return Err(Error::new(ErrorKind::Unencodable {
+                    grapheme: String::from(cluster),
+                    offset,
+                }))
+            }
+        }
+    }
+
+    // Encode the whole string at once, so character sets that keep
+    // state between characters, like the PETSCII shift state, work
+    Ok(charset.encode(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        custom::TableEncoding, error::ErrorKind, graphemes::encode_graphemes, CharacterSet,
+        EncodeOptions,
+    };
+
+    fn ascii_table() -> TableEncoding {
+        let table = std::array::from_fn(|code| {
+            Some(char::from(code as u8)).filter(|c| c.is_ascii_graphic() || *c == ' ')
+        });
+        TableEncoding::new("ascii", table)
+    }
+
+    #[test]
+    fn graphemes_combining_marks_are_transliterated() {
+        let ascii = ascii_table();
+
+        let bytes = encode_graphemes(&ascii, "nai\u{308}ve", &EncodeOptions::default()).unwrap();
+
+        assert_eq!(bytes, b"naive");
+    }
+
+    #[test]
+    fn graphemes_precomposed_and_decomposed_letters_match() {
+        let ascii = ascii_table();
+        let options = EncodeOptions::default();
+
+        assert_eq!(
+            encode_graphemes(&ascii, "CAF\u{c9}", &options).unwrap(),
+            b"CAFE"
+        );
+        assert_eq!(
+            encode_graphemes(&ascii, "CAFE\u{301}", &options).unwrap(),
+            b"CAFE"
+        );
+        assert_eq!(
+            encode_graphemes(&ascii, "\u{141}\u{f3}d\u{17a}", &options).unwrap(),
+            b"Lodz"
+        );
+    }
+
+    #[test]
+    fn graphemes_error_covers_whole_cluster() {
+        let ascii = ascii_table();
+
+        // A flag is two regional indicators in one cluster
+        let error = encode_graphemes(&ascii, "go 🇫🇮!", &EncodeOptions::default()).unwrap_err();
+
+        match error.kind() {
+            ErrorKind::Unencodable { grapheme, offset } => {
+                assert_eq!(grapheme, "🇫🇮");
+                assert_eq!(*offset, 3);
+            }
+            _ => panic!("Expected an Unencodable error"),
+        }
+    }
+
+    #[test]
+    fn graphemes_encodable_text_matches_encode() {
+        let ascii = ascii_table();
+        let s = "10 PRINT \"HELLO\"";
+
+        assert_eq!(
+            encode_graphemes(&ascii, s, &EncodeOptions::default()).unwrap(),
+            ascii.encode(s)
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//!
+//! Drawing the screen in GUI frontends
+//!
+//! Emulator frontends written with egui or iced draw the screen in a
+//! custom widget, a character cell at a time.  GlyphGrid is the
+//! screen with everything such a widget needs already worked out:
+//! the character in each cell and the RGB colors to draw it and its
+//! background in.  Reversed cells have their colors swapped, so a
+//! widget doesn't need to know about reverse video.
+//!
+//! With the egui feature, ScreenWidget draws a GlyphGrid in an egui
+//! user interface.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    palette::{Palette, Rgb},
+    petscii::screen::Screen,
+    SystemConfig,
+};
+
+/// A character cell, ready to draw
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    /// The character
+    pub c: char,
+    /// The color to draw the character in
+    pub foreground: Rgb,
+    /// The color to fill the cell with
+    pub background: Rgb,
+}
+
+/// The character cells of a screen, ready to draw
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlyphGrid {
+    /// The number of columns
+    pub columns: usize,
+    /// The number of rows
+    pub rows: usize,
+    /// The glyphs, a row at a time
+    pub glyphs: Vec<Glyph>,
+    /// The border color
+    pub border: Rgb,
+}
+
+impl GlyphGrid {
+    /// The glyph at a column and row, or None if it's off the grid
+    pub fn glyph(&self, column: usize, row: usize) -> Option<&Glyph> {
+        if column >= self.columns || row >= self.rows {
+            return None;
+        }
+        self.glyphs.get(row * self.columns + column)
+    }
+
+    /// The glyphs in a row
+    pub fn row(&self, row: usize) -> &[Glyph] {
+        &self.glyphs[row * self.columns..(row + 1) * self.columns]
+    }
+}
+
+impl Screen {
+    /// The screen as glyphs, with the colors from a palette
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     palette::Palette,
+    ///     petscii::{screen::Screen, Color, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut screen = Screen::c64();
+    /// screen.set(0, 0, 0x88, Color::White);
+    ///
+    /// let palette = Palette::COLODORE;
+    /// let grid = screen.to_glyph_grid(&petscii, &palette);
+    /// let glyph = grid.glyph(0, 0).expect("The cell is on the screen");
+    ///
+    /// assert_eq!(glyph.c, 'H');
+    /// assert_eq!(glyph.foreground, palette.rgb(Color::Blue));
+    /// assert_eq!(glyph.background, palette.rgb(Color::White));
+    /// ```
+    pub fn to_glyph_grid(&self, petscii: &SystemConfig, palette: &Palette) -> GlyphGrid {
+        let background = palette.rgb(self.background);
+
+        let glyphs = self
+            .cells
+            .iter()
+            .map(|cell| {
+                let color = palette.rgb(cell.color);
+                let (foreground, background) = if cell.is_reversed() {
+                    (background, color)
+                } else {
+                    (color, background)
+                };
+                Glyph {
+                    c: self.char_for(cell.screen_code & 0x7F, petscii),
+                    foreground,
+                    background,
+                }
+            })
+            .collect();
+
+        GlyphGrid {
+            columns: self.columns,
+            rows: self.rows,
+            glyphs,
+            border: palette.rgb(self.border),
+        }
+    }
+}
+
+/// An egui widget that draws a glyph grid
+///
+/// Each cell is drawn as a square, like the 8 by 8 pixel characters
+/// of the C64, with the border around the grid.
+///
+/// # Examples
+///
+/// ```no_run
+/// use forbidden_bands::gui::{GlyphGrid, ScreenWidget};
+///
+/// fn show(ui: &mut egui::Ui, grid: &GlyphGrid) {
+///     ui.add(ScreenWidget::new(grid).cell_size(16.0));
+/// }
+/// ```
+#[cfg(feature = "egui")]
+pub struct ScreenWidget<'a> {
+    grid: &'a GlyphGrid,
+    cell_size: f32,
+    border_size: f32,
+}
+
+#[cfg(feature = "egui")]
+impl<'a> ScreenWidget<'a> {
+    /// Create a widget drawing the grid with 8 point cells
+    pub fn new(grid: &'a GlyphGrid) -> ScreenWidget<'a> {
+        ScreenWidget {
+            grid,
+            cell_size: 8.0,
+            border_size: 32.0,
+        }
+    }
+
+    /// Set the width and height of a cell
+    pub fn cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Set the width of the border, 0.0 to leave it out
+    pub fn border_size(mut self, border_size: f32) -> Self {
+        self.border_size = border_size;
+        self
+    }
+}
+
+/// The egui color for an RGB color
+#[cfg(feature = "egui")]
+fn egui_color(rgb: Rgb) -> egui::Color32 {
+    egui::Color32::from_rgb(rgb.r, rgb.g, rgb.b)
+}
+
+#[cfg(feature = "egui")]
+impl egui::Widget for ScreenWidget<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let size = egui::vec2(
+            self.grid.columns as f32 * self.cell_size + 2.0 * self.border_size,
+            self.grid.rows as f32 * self.cell_size + 2.0 * self.border_size,
+        );
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+        if !ui.is_rect_visible(rect) {
+            return response;
+        }
+
+        let painter = ui.painter();
+        painter.rect_filled(rect, 0.0, egui_color(self.grid.border));
+
+        let font = egui::FontId::monospace(self.cell_size);
+        let origin = rect.min + egui::vec2(self.border_size, self.border_size);
+        for row in 0..self.grid.rows {
+            for (column, glyph) in self.grid.row(row).iter().enumerate() {
+                let cell = egui::Rect::from_min_size(
+                    origin + egui::vec2(column as f32, row as f32) * self.cell_size,
+                    egui::vec2(self.cell_size, self.cell_size),
+                );
+                painter.rect_filled(cell, 0.0, egui_color(glyph.background));
+                if glyph.c != ' ' {
+                    painter.text(
+                        cell.center(),
+                        egui::Align2::CENTER_CENTER,
+                        glyph.c,
+                        font.clone(),
+                        egui_color(glyph.foreground),
+                    );
+                }
+            }
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        palette::Palette,
+        petscii::{screen::Screen, Color, PetsciiConfig},
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn gui_glyph_grid_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let palette = Palette::PEPTO;
+
+        let mut screen = Screen::new(3, 2);
+        screen.set(2, 1, 0x41, Color::Yellow);
+        let grid = screen.to_glyph_grid(&petscii, &palette);
+
+        assert_eq!(grid.glyphs.len(), 6);
+        assert_eq!(grid.row(1)[2].c, '♠');
+        assert_eq!(grid.row(1)[2].foreground, palette.rgb(Color::Yellow));
+        assert_eq!(grid.border, palette.rgb(Color::LightBlue));
+        assert_eq!(grid.glyph(3, 0), None);
+    }
+}
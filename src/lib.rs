@@ -2,17 +2,56 @@
 #![warn(missing_docs)]
 #![warn(unsafe_code)]
 
-use std::{fs::File, io::BufReader, path::Path, sync::RwLock};
+use std::{
+    any::{Any, TypeId},
+    collections::BTreeMap,
+    fs::File,
+    io::BufReader,
+    path::Path,
+    sync::{Arc, Mutex, RwLock},
+};
 
 // See the notes about optional JSON support in the Cargo.toml file
 // #[cfg(feature = "json")]
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 // #[cfg(feature = "json")]
-// use serde_json::{Map, Value};
+use serde_json::{Map, Value};
 
+pub mod amiga;
+pub mod analysis;
+pub mod ansi;
+pub mod apple2;
+pub mod ascii;
+pub mod bbc;
+pub mod bridge;
+#[cfg(feature = "rayon")]
+pub mod bulk;
+pub mod cbm;
+pub mod coco;
 pub mod config_data;
+pub mod corpus;
+pub mod custom;
+pub mod document;
+pub mod ebcdic;
+pub mod encoding;
 pub mod error;
+#[cfg(feature = "unicode-segmentation")]
+pub mod graphemes;
+#[cfg(feature = "gui")]
+pub mod gui;
+pub mod manager;
+#[cfg(feature = "unicode-normalization")]
+pub mod normalize;
+pub mod palette;
 pub mod petscii;
+pub mod search;
+#[cfg(feature = "terminal")]
+pub mod terminal;
+pub mod ti99;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+pub use encoding::{Encoding, PETSCII_SHIFTED, PETSCII_UNSHIFTED};
 
 /// An individual system config
 /// Contains character set mappings
@@ -31,33 +70,332 @@ pub struct SystemConfig {
     /// tables that hold information about the in-memory values of
     /// characters on the screen.
     ///
-    /// TODO: I want to get dynamic loading and unloading of modules
-    /// working.  It will require some refactoring with dyn traits and
-    /// serialization / deserialization to make sure everything works.
+    /// A configuration can be swapped out at runtime with
+    /// [manager::ConfigManager].
+    ///
+    /// TODO: Loading and unloading individual modules will require
+    /// some refactoring with dyn traits and serialization /
+    /// deserialization to make sure everything works.
     pub character_set_map: petscii::PetsciiConfig,
 }
 
 /// Configuration format
+///
+/// Each system module stores its configuration under its own key,
+/// its namespace, next to the version.  The root configuration
+/// doesn't need to know about the modules, so new modules can be
+/// added without changing the format.
 // #[cfg(feature = "json")]
-#[derive(Serialize, Deserialize)]
-// TODO: system should be dynamic
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Version of the configuration root
     pub version: String,
-    /// A mapping for PETSCII systems
-    /// TODO: Remove this, individual modules should create their own
-    /// keys, in an approved namespace like good little modules.
-    pub petscii: SystemConfig,
+    /// System configurations, keyed by the namespace of the module
+    /// that owns them
+    #[serde(flatten)]
+    pub systems: BTreeMap<String, Value>,
+    /// The system configurations cached_system has deserialized
+    #[serde(skip)]
+    cache: SystemCache,
+}
+
+/// Deserialized system configurations, by type
+///
+/// Cloning a configuration doesn't clone the cache, so a clone that's
+/// changed doesn't return the systems of the original.
+#[derive(Default)]
+struct SystemCache(Mutex<BTreeMap<TypeId, Arc<dyn Any + Send + Sync>>>);
+
+impl Clone for SystemCache {
+    fn clone(&self) -> Self {
+        SystemCache::default()
+    }
+}
+
+/// Trait for system modules that store their configuration in the
+/// root configuration
+pub trait SystemModule: Serialize + DeserializeOwned {
+    /// The key the module configuration is stored under
+    const NAMESPACE: &'static str;
+}
+
+/// The names of the embedded configurations
+///
+/// strict-legacy-unicode is the default configuration, the same one
+/// [Configuration::load] loads.  It maps the graphics characters to
+/// the Symbols for Legacy Computing block.
+///
+/// ascii-approximation maps the graphics characters to similar
+/// looking ASCII characters, for terminals and fonts without the
+/// Legacy Computing glyphs.
+///
+/// lossless-escape maps characters that don't have a distinct
+/// Unicode character to the Private Use Area, so every screen code
+/// decodes to a different character and encodes back to a PETSCII
+/// code that shows it.  PETSCII codes that duplicate another code,
+/// like 0xDE for 0x7E, show the same screen code and are encoded as
+/// the code they duplicate.
+pub const CONFIG_NAMES: [&str; 3] = [
+    "strict-legacy-unicode",
+    "ascii-approximation",
+    "lossless-escape",
+];
+
+impl Config {
+    /// Load one of the embedded configurations by name
+    ///
+    /// See [CONFIG_NAMES] for the available configurations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiString, Config, SystemConfig};
+    ///
+    /// let config = Config::load_named("ascii-approximation").expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let ps = PetsciiString::new_with_config(5, [0xb0, 0x60, 0x60, 0xae, 0x5c], &petscii);
+    ///
+    /// assert_eq!(String::from(ps), "+--+L");
+    /// ```
+    pub fn load_named(name: &str) -> std::result::Result<Config, error::Error> {
+        let mut value: Value = serde_json::from_str(config_data::CONFIG_DATA)?;
+        match name {
+            "strict-legacy-unicode" => (),
+            "ascii-approximation" => {
+                approximate_screen_codes(&mut value, &ascii::AsciiConfig::load()?)
+            }
+            "lossless-escape" => merge_json(
+                &mut value,
+                serde_json::from_str(config_data::LOSSLESS_ESCAPE_OVERLAY)?,
+            ),
+            _ => {
+                return Err(error::Error::new(error::ErrorKind::Message(format!(
+                    "No embedded configuration named {name}"
+                ))))
+            }
+        }
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Check that the PETSCII maps in this configuration are
+    /// bijective
+    ///
+    /// See [SystemConfig::check_bijectivity].  Returns an error if a
+    /// PETSCII to screen code entry isn't a screen code, see
+    /// [petscii::PetsciiConfig::validate].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{Config, Configuration};
+    ///
+    /// let config = Config::load().expect("Error loading config");
+    /// let report = config.check_bijectivity().expect("Error checking config");
+    ///
+    /// for mismatch in &report.petscii_mismatches {
+    ///     assert_ne!(mismatch.encoded, vec![mismatch.code]);
+    /// }
+    /// ```
+    pub fn check_bijectivity(
+        &self,
+    ) -> std::result::Result<petscii::BijectivityReport, error::Error> {
+        let petscii = self.system::<SystemConfig>()?;
+        petscii.character_set_map.validate()?;
+        Ok(petscii.check_bijectivity())
+    }
+
+    /// How full each PETSCII table is
+    ///
+    /// See [petscii::PetsciiConfig::coverage].
+    pub fn coverage(
+        &self,
+    ) -> std::result::Result<Vec<petscii::coverage::TableCoverage>, error::Error> {
+        Ok(self.system::<SystemConfig>()?.character_set_map.coverage())
+    }
+
+    /// The namespaces of the system modules with a configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{Config, Configuration};
+    ///
+    /// let config = Config::load().expect("Error loading config");
+    ///
+    /// assert!(config.system_names().any(|name| name == "petscii"));
+    /// ```
+    pub fn system_names(&self) -> impl Iterator<Item = &str> {
+        self.systems
+            .iter()
+            .filter(|(_, value)| value.is_object())
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Get the configuration for a system module
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{Config, Configuration, SystemConfig};
+    ///
+    /// let config = Config::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert_eq!(petscii.version, "0.2.0");
+    /// ```
+    pub fn system<T: SystemModule>(&self) -> std::result::Result<T, error::Error> {
+        let value = self.systems.get(T::NAMESPACE).ok_or_else(|| {
+            error::Error::new(error::ErrorKind::Message(format!(
+                "No configuration for system {}",
+                T::NAMESPACE
+            )))
+        })?;
+
+        Ok(T::deserialize(value)?)
+    }
+
+    /// Get the configuration for a system module, deserializing it
+    /// only the first time
+    ///
+    /// The shared snapshots from [Config::current] use this, so code
+    /// that needs a system configuration for every conversion doesn't
+    /// deserialize it each time.  The cache is cleared by
+    /// [Config::set_system], but not by changing systems directly, so
+    /// change a configuration before calling this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use forbidden_bands::{Config, Configuration, SystemConfig};
+    ///
+    /// let config = Config::current().expect("Error loading config");
+    /// let first = config.cached_system::<SystemConfig>().expect("Error loading PETSCII config");
+    /// let second = config.cached_system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert!(Arc::ptr_eq(&first, &second));
+    /// ```
+    pub fn cached_system<T: SystemModule + Send + Sync + 'static>(
+        &self,
+    ) -> std::result::Result<Arc<T>, error::Error> {
+        let mut cache = self
+            .cache
+            .0
+            .lock()
+            .expect("Should be able to lock system cache");
+        if let Some(system) = cache.get(&TypeId::of::<T>()) {
+            if let Ok(system) = system.clone().downcast::<T>() {
+                return Ok(system);
+            }
+        }
+
+        let system = Arc::new(self.system::<T>()?);
+        cache.insert(TypeId::of::<T>(), system.clone());
+        Ok(system)
+    }
+
+    /// A snapshot of the global configuration
+    ///
+    /// The default configuration is loaded the first time this is
+    /// called if one hasn't been set with [Config::set_current].  A
+    /// [manager::ConfigOverrideGuard] on the current thread takes
+    /// precedence over the global configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{Config, SystemConfig};
+    ///
+    /// let config = Config::current().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert!(!petscii.character_set_map.version.is_empty());
+    /// ```
+    pub fn current() -> std::result::Result<Arc<Config>, error::Error> {
+        if let Some(config) = manager::thread_override() {
+            return Ok(config);
+        }
+
+        if let Some(config) = CONFIG
+            .read()
+            .expect("Should be able to get reader lock")
+            .as_ref()
+        {
+            return Ok(config.clone());
+        }
+
+        let config = Arc::new(Config::load()?);
+
+        // Another thread may have set the config while this one was
+        // loading it, and that one wins
+        let mut lock_res = CONFIG
+            .write()
+            .expect("Should be able to acquire config lock");
+        Ok(lock_res.get_or_insert(config).clone())
+    }
+
+    /// Replace the global configuration
+    ///
+    /// Returns the previous configuration, if there was one.
+    /// Snapshots returned by [Config::current] before the replacement
+    /// aren't changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use forbidden_bands::{Config, Configuration};
+    ///
+    /// let before = Config::current().expect("Error loading config");
+    ///
+    /// let mut config = Config::load().expect("Error loading config");
+    /// config.version = String::from("fixed");
+    /// let previous = Config::set_current(config).expect("A config was loaded");
+    ///
+    /// assert!(Arc::ptr_eq(&before, &previous));
+    /// assert_eq!(Config::current().expect("Error loading config").version, "fixed");
+    /// assert_ne!(before.version, "fixed");
+    /// ```
+    pub fn set_current(config: Config) -> Option<Arc<Config>> {
+        publish_current(Arc::new(config))
+    }
+
+    /// Set the configuration for a system module, replacing any
+    /// existing configuration in its namespace
+    pub fn set_system<T: SystemModule>(
+        &mut self,
+        system: &T,
+    ) -> std::result::Result<(), error::Error> {
+        self.systems
+            .insert(String::from(T::NAMESPACE), serde_json::to_value(system)?);
+        self.cache = SystemCache::default();
+        Ok(())
+    }
 }
 
 /// The global configuration settings
 /// This is used by default if a custom configuration isn't used
 /// when creating a string.
-// Each string with configuration is a "reader" on the config data
-// structure.  There may be hundreds or thousands floating around.
-// Use a reader-writer lock type to keep track of them.  When the lock
-// count reaches zero, we can modify the config.
-pub static CONFIG: RwLock<Option<Config>> = RwLock::new(None);
+///
+/// The lock is only held long enough to clone or replace the Arc.
+/// Code that needs the configuration gets a snapshot with
+/// [Config::current] and keeps it for as long as it likes, so
+/// replacing the global configuration never waits for strings that
+/// borrow from an older snapshot.
+pub static CONFIG: RwLock<Option<Arc<Config>>> = RwLock::new(None);
+
+/// Make a snapshot the global configuration, returning the previous
+/// one
+pub(crate) fn publish_current(config: Arc<Config>) -> Option<Arc<Config>> {
+    CONFIG
+        .write()
+        .expect("Should be able to acquire config lock")
+        .replace(config)
+}
 
 /// Trait that defines a set of methods that allow loading and
 /// unloading configuration data
@@ -68,6 +406,392 @@ pub trait Configuration {
 
     /// Load configuration from a file
     fn load_from_file(filename: &str) -> std::result::Result<Config, error::Error>;
+
+    /// Load configuration from an asynchronous reader
+    ///
+    /// The JSON is read without blocking and parsed on tokio's
+    /// blocking thread pool, so it has to be called from inside a
+    /// tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{config_data, Config, Configuration};
+    ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread()
+    ///     .build()
+    ///     .expect("Error creating runtime");
+    ///
+    /// let config = runtime
+    ///     .block_on(Config::load_from_reader_async(config_data::CONFIG_DATA.as_bytes()))
+    ///     .expect("Error loading config");
+    ///
+    /// assert_eq!(config.version, "0.2.0");
+    /// ```
+    #[cfg(feature = "tokio")]
+    fn load_from_reader_async<R>(
+        reader: R,
+    ) -> impl std::future::Future<Output = std::result::Result<Config, error::Error>> + Send
+    where
+        R: tokio::io::AsyncRead + Unpin + Send,
+    {
+        load_config_from_reader_async(reader)
+    }
+}
+
+/// Read and parse a configuration without blocking the executor
+#[cfg(feature = "tokio")]
+async fn load_config_from_reader_async<R>(
+    mut reader: R,
+) -> std::result::Result<Config, error::Error>
+where
+    R: tokio::io::AsyncRead + Unpin + Send,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut json = Vec::new();
+    reader.read_to_end(&mut json).await?;
+
+    tokio::task::spawn_blocking(move || serde_json::from_slice::<Config>(&json))
+        .await
+        .map_err(|e| error::Error::new(error::ErrorKind::Message(e.to_string())))?
+        .map_err(error::Error::from)
+}
+
+/// Trait for 8-bit character sets that can be converted to and from
+/// Unicode strings
+///
+/// Each system module implements this on its character map
+/// configuration, so code that doesn't care which system the data
+/// came from can work with any of them.
+pub trait CharacterSet {
+    /// Decode a sequence of 8-bit character codes into a Unicode
+    /// string
+    fn decode(&self, bytes: &[u8]) -> String;
+
+    /// Encode a Unicode string into a sequence of 8-bit character
+    /// codes
+    ///
+    /// Characters that don't exist in the character set are dropped.
+    fn encode(&self, s: &str) -> Vec<u8>;
+
+    /// Decode a sequence of 8-bit character codes into a Unicode
+    /// string using decoding options
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::PetsciiConfig, CharacterSet, Configuration, DecodeOptions, DecodeTarget,
+    ///     SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let options = DecodeOptions::default().with_target(DecodeTarget::AsciiApproximation);
+    ///
+    /// assert_eq!(petscii.decode_with_options(&[0x41, 0xa6, 0xdb], &options), "A#+");
+    /// ```
+    fn decode_with_options(&self, bytes: &[u8], options: &DecodeOptions) -> String {
+        options.target.apply(self.decode(bytes))
+    }
+
+    /// Encode a Unicode string into a sequence of 8-bit character
+    /// codes using encoding options
+    fn encode_with_options(&self, s: &str, options: &EncodeOptions) -> Vec<u8> {
+        #[cfg(feature = "unicode-normalization")]
+        if options.normalize {
+            let normalized = normalize::normalize_for_encoding(s, |c| {
+                !self.encode(c.encode_utf8(&mut [0; 4])).is_empty()
+            });
+            return self.encode(&normalized);
+        }
+
+        // Without any features enabled there aren't any options
+        let _ = options;
+        self.encode(s)
+    }
+}
+
+/// The kind of text to decode character codes to
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeTarget {
+    /// The closest Unicode characters, including the Symbols for
+    /// Legacy Computing block
+    #[default]
+    Unicode,
+    /// Graphics characters are replaced with similar looking ASCII
+    /// characters
+    AsciiApproximation,
+}
+
+impl DecodeTarget {
+    /// Convert decoded Unicode text to the target
+    pub(crate) fn apply(self, s: String) -> String {
+        match self {
+            DecodeTarget::Unicode => s,
+            DecodeTarget::AsciiApproximation => ascii::AsciiConfig::load()
+                .expect("Error loading config")
+                .approximate(&s),
+        }
+    }
+}
+
+/// The version of Unicode that decoded text has to display in
+///
+/// The graphics characters of the 8-bit systems were added to Unicode
+/// in stages.  Fonts and terminals often lag behind, so decoding to
+/// the newest characters gives the most faithful text but needs a
+/// recent font, while an older version displays almost anywhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UnicodeVersion {
+    /// Unicode 12 or earlier, before Symbols for Legacy Computing
+    Unicode12,
+    /// Unicode 13, which added the Symbols for Legacy Computing block
+    Unicode13,
+    /// Unicode 16, which added more characters to Symbols for Legacy
+    /// Computing and the Symbols for Legacy Computing Supplement block
+    #[default]
+    Unicode16,
+}
+
+impl UnicodeVersion {
+    /// The version a character was added in, for the characters the
+    /// 8-bit character sets use
+    ///
+    /// Characters outside the legacy computing blocks are older than
+    /// all of the versions, so they're reported as Unicode12.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::UnicodeVersion;
+    ///
+    /// assert_eq!(UnicodeVersion::of('▌'), UnicodeVersion::Unicode12);
+    /// assert_eq!(UnicodeVersion::of('\u{1fb70}'), UnicodeVersion::Unicode13);
+    /// assert_eq!(UnicodeVersion::of('\u{1fbce}'), UnicodeVersion::Unicode16);
+    /// ```
+    pub fn of(c: char) -> UnicodeVersion {
+        match u32::from(c) {
+            0x1CC00..=0x1CEBF | 0x1FBCB..=0x1FBEF => UnicodeVersion::Unicode16,
+            0x1FB00..=0x1FBFF => UnicodeVersion::Unicode13,
+            _ => UnicodeVersion::Unicode12,
+        }
+    }
+}
+
+/// Options for encoding Unicode strings
+///
+/// The options depend on the enabled features, so they're built from
+/// the defaults with the with_ methods instead of a struct literal.
+/// That keeps code that compiles without a feature compiling with it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EncodeOptions {
+    /// Normalize the string before encoding it
+    ///
+    /// Characters that can't be encoded are replaced with their
+    /// compatibility decompositions, so fullwidth Ａ is encoded as A
+    /// and é as e.  See the [normalize] module.
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize: bool,
+}
+
+impl EncodeOptions {
+    /// Set whether to normalize the string before encoding it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::PetsciiConfig, CharacterSet, Configuration, EncodeOptions, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let options = EncodeOptions::default().with_normalize(true);
+    ///
+    /// assert_eq!(petscii.encode_with_options("Ａ½", &options), petscii.encode("A1/2"));
+    /// ```
+    #[cfg(feature = "unicode-normalization")]
+    pub fn with_normalize(mut self, normalize: bool) -> EncodeOptions {
+        self.normalize = normalize;
+        self
+    }
+}
+
+/// Options for decoding character codes
+///
+/// Like EncodeOptions, the options are built from the defaults with
+/// the with_ methods instead of a struct literal, so adding an option
+/// doesn't break code that sets the others.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DecodeOptions {
+    /// The kind of text to decode to
+    pub target: DecodeTarget,
+
+    /// Start in the shifted (lowercase) character set
+    ///
+    /// Many SEQ files and BBS captures assume the viewer already
+    /// switched to lowercase and never send the shift code.  Only
+    /// PETSCII uses this.
+    pub initial_shifted: bool,
+
+    /// Start with reverse video on
+    ///
+    /// Only PETSCII uses this.
+    pub initial_reversed: bool,
+
+    /// The text color to start with, instead of the light blue the
+    /// C64 starts with
+    ///
+    /// Only decoders that keep colors, like
+    /// [SystemConfig::decode_rich_with_options], use this.
+    pub initial_color: Option<petscii::Color>,
+
+    /// Keep runs of more than this many codes that don't decode to a
+    /// character as hex escapes, instead of dropping them
+    ///
+    /// Only PETSCII uses this, see the [petscii::binary] module.
+    pub hex_escape_runs: Option<usize>,
+}
+
+impl DecodeOptions {
+    /// Set the kind of text to decode to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{DecodeOptions, DecodeTarget};
+    ///
+    /// let options = DecodeOptions::default().with_target(DecodeTarget::AsciiApproximation);
+    ///
+    /// assert_eq!(options.target, DecodeTarget::AsciiApproximation);
+    /// ```
+    pub fn with_target(mut self, target: DecodeTarget) -> DecodeOptions {
+        self.target = target;
+        self
+    }
+
+    /// Set whether to start in the shifted (lowercase) character set
+    pub fn with_initial_shifted(mut self, initial_shifted: bool) -> DecodeOptions {
+        self.initial_shifted = initial_shifted;
+        self
+    }
+
+    /// Set whether to start with reverse video on
+    pub fn with_initial_reversed(mut self, initial_reversed: bool) -> DecodeOptions {
+        self.initial_reversed = initial_reversed;
+        self
+    }
+
+    /// Set the text color to start with
+    pub fn with_initial_color(mut self, initial_color: Option<petscii::Color>) -> DecodeOptions {
+        self.initial_color = initial_color;
+        self
+    }
+
+    /// Set the length of the runs of undecodable codes kept as hex
+    /// escapes
+    pub fn with_hex_escape_runs(mut self, hex_escape_runs: Option<usize>) -> DecodeOptions {
+        self.hex_escape_runs = hex_escape_runs;
+        self
+    }
+}
+
+/// Load a system configuration from an embedded JSON string
+///
+/// The parsed configuration is saved in cache and later loads return
+/// a copy of the cached value.
+pub(crate) fn load_cached<T: Clone + DeserializeOwned>(
+    cache: &RwLock<Option<T>>,
+    json_str: &str,
+) -> std::result::Result<T, error::Error> {
+    {
+        let binding = cache.read().expect("Should be able to get reader lock");
+        if let Some(config) = binding.as_ref() {
+            return Ok(config.clone());
+        }
+    }
+
+    let config: T = serde_json::from_str(json_str)?;
+
+    let mut lock_res = cache
+        .write()
+        .expect("Should be able to acquire config lock");
+    *lock_res = Some(config.clone());
+
+    Ok(config)
+}
+
+/// Replace the characters the PETSCII screen codes decode to with
+/// their ASCII approximations
+///
+/// Characters without an approximation, like letters and digits,
+/// aren't changed.
+fn approximate_screen_codes(value: &mut Value, ascii: &ascii::AsciiConfig) {
+    for set in [1, 2] {
+        let pointer =
+            format!("/petscii/character_set_map/c64_screen_codes_set_{set}_to_unicode_codes");
+        let Some(Value::Object(map)) = value.pointer_mut(&pointer) else {
+            continue;
+        };
+        for code in map.values_mut() {
+            let approximation = code
+                .as_u64()
+                .and_then(|c| char::from_u32(u32::try_from(c).ok()?))
+                .and_then(|c| ascii.approximate_char(c));
+            if let Some(approximation) = approximation {
+                *code = Value::from(u32::from(approximation));
+            }
+        }
+    }
+}
+
+/// Merge a JSON overlay into a JSON value
+///
+/// Objects are merged key by key and any other overlay value
+/// replaces the value in the base.
+pub(crate) fn merge_json(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Log a conversion decision, like a dropped byte or a fallback
+/// character, as a tracing debug event
+///
+/// Without the tracing feature this expands to nothing.
+macro_rules! trace_decision {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+pub(crate) use trace_decision;
+
+/// Look up the Unicode character for a character code in one of the
+/// configuration maps
+pub(crate) fn lookup_char(map: &Map<String, Value>, code: u8) -> Option<char> {
+    match map.get(&code.to_string()) {
+        Some(Value::Number(v)) => v.as_u64().and_then(|v| char::from_u32(v as u32)),
+        _ => None,
+    }
 }
 
 impl Configuration for Config {
@@ -93,16 +817,24 @@ impl Configuration for Config {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Config, Configuration};
+    use crate::{
+        ebcdic::EbcdicConfig,
+        petscii::{PetsciiConfig, PetsciiString},
+        ti99::Ti99Config,
+        CharacterSet, Config, Configuration, DecodeOptions, DecodeTarget, SystemConfig,
+        CONFIG_NAMES,
+    };
 
     #[test]
     fn config_works() {
         let config = Config::load().expect("Error loading config");
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
 
         // Test config
         let key: String = 167.to_string();
-        let res: Option<&serde_json::Value> = config
-            .petscii
+        let res: Option<&serde_json::Value> = petscii
             .character_set_map
             .c64_petscii_unshifted_codes_to_screen_codes
             .get(&key);
@@ -112,17 +844,17 @@ mod tests {
                 assert_eq!(v.get(1).unwrap().as_u64().unwrap(), 103);
             }
             _ => {
-                assert!(false);
+                panic!("Expected an array of screen code values");
             }
         }
 
         let key: String = 103.to_string();
-        let res = config
-            .petscii
+        let res = petscii
             .character_set_map
             .c64_screen_codes_set_1_to_unicode_codes
             .get(&key);
-        assert!(res.is_none());
+        // RIGHT ONE EIGHTH BLOCK
+        assert_eq!(res.unwrap().as_u64(), Some(9621));
 
         // let key: String = 92.to_string();
         // let res = config.petscii.character_set_map.get(&key);
@@ -146,4 +878,117 @@ mod tests {
         // let res = config.petscii.character_set_map.get(&key);
         // assert_eq!(res.unwrap(), 163);
     }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn config_load_from_reader_async_works() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let json = std::fs::read("data/config.json").unwrap();
+        let config = runtime
+            .block_on(Config::load_from_reader_async(json.as_slice()))
+            .expect("Error loading config");
+        assert!(config.system::<SystemConfig>().is_ok());
+
+        let result = runtime.block_on(Config::load_from_reader_async(&b"{ \"version\": "[..]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn config_systems_are_namespaced() {
+        let mut config = Config::load().expect("Error loading config");
+
+        assert!(config.systems.contains_key("petscii"));
+        assert!(config.system::<Ti99Config>().is_err());
+
+        let ti99 = Ti99Config::load().expect("Error loading config");
+        config.set_system(&ti99).expect("Error setting config");
+
+        // Other namespaces aren't affected and unknown keys survive a
+        // round trip through the root configuration
+        let json = serde_json::to_string(&config).unwrap();
+        let copy: Config = serde_json::from_str(&json).unwrap();
+        assert!(copy.system::<SystemConfig>().is_ok());
+        assert!(copy.system::<Ti99Config>().is_ok());
+        assert!(copy.system::<EbcdicConfig>().is_err());
+
+        let petscii: PetsciiConfig = copy.system::<SystemConfig>().unwrap().character_set_map;
+        assert_eq!(petscii.version, "0.2.0");
+    }
+
+    #[test]
+    fn config_load_named_works() {
+        for name in CONFIG_NAMES {
+            assert!(Config::load_named(name).is_ok(), "{name}");
+        }
+        assert!(Config::load_named("no-such-config").is_err());
+
+        let strict = Config::load_named("strict-legacy-unicode").unwrap();
+        let strict = strict.system::<SystemConfig>().unwrap();
+        let default = Config::load().unwrap().system::<SystemConfig>().unwrap();
+        assert_eq!(strict.decode(&[0x61, 0xa6]), default.decode(&[0x61, 0xa6]));
+    }
+
+    #[test]
+    fn config_cached_system_is_cleared_by_set_system() {
+        let mut config = Config::load().unwrap();
+        let cached = config.cached_system::<SystemConfig>().unwrap();
+        assert!(std::sync::Arc::ptr_eq(
+            &cached,
+            &config.cached_system::<SystemConfig>().unwrap()
+        ));
+
+        let mut petscii = (*cached).clone();
+        petscii.version = String::from("9.9.9");
+
+        // A clone doesn't share the cache
+        let mut copy = config.clone();
+        copy.systems.remove("petscii");
+        assert!(copy.cached_system::<SystemConfig>().is_err());
+
+        config.set_system(&petscii).unwrap();
+        assert_eq!(
+            config.cached_system::<SystemConfig>().unwrap().version,
+            "9.9.9"
+        );
+    }
+
+    #[test]
+    fn config_ascii_approximation_is_ascii() {
+        let config = Config::load_named("ascii-approximation").unwrap();
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let data: Vec<u8> = (0x20..=0xff).collect();
+        let s = petscii.decode(&data);
+
+        assert!(!s.is_empty());
+        assert!(s.is_ascii());
+        // Letters and digits aren't changed
+        assert_eq!(petscii.decode(b"ABC123"), "ABC123");
+
+        // The configuration agrees with the decode target
+        let default = Config::load().unwrap().system::<SystemConfig>().unwrap();
+        let options = DecodeOptions::default().with_target(DecodeTarget::AsciiApproximation);
+        assert_eq!(default.decode_with_options(&data, &options), s);
+    }
+
+    #[test]
+    fn config_lossless_escape_round_trips_screen_codes() {
+        let config = Config::load_named("lossless-escape").unwrap();
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // 0xde and 0xa6 are medium shades, 0xa5 and 0xb4 are left
+        // blocks and 0xdf is the upper right triangle
+        let data = [0x41, 0xde, 0xa6, 0xa5, 0xb4, 0xdf];
+        let ps = PetsciiString::new_with_config(6, data, &petscii);
+        let s = String::from(ps);
+
+        assert_eq!(s.chars().count(), 6);
+        assert_eq!(s.chars().last(), Some('◥'));
+        // 0xde and 0xdf show the same screen codes as 0x7e and 0x7f,
+        // so they're encoded as the codes they duplicate
+        assert_eq!(petscii.encode(&s), vec![0x41, 0x7e, 0xa6, 0xa5, 0xb4, 0x7f]);
+    }
 }
@@ -0,0 +1,482 @@
+//!
+//! Replacing the configuration while a program is running
+//!
+//! Long-running programs may want to pick up fixes to the character
+//! maps without restarting.  A ConfigManager holds the current
+//! configuration as a shared snapshot.  Replacing it swaps in a new
+//! snapshot atomically and notifies the subscribers.
+//!
+//! Code that got a snapshot before the swap keeps using it until it
+//! asks for the current configuration again, so strings created with
+//! the old character maps stay consistent.
+//!
+//! A manager created with publish_global also makes every
+//! configuration it installs the global configuration, so code that
+//! uses Config::current, like PetsciiString::try_from, sees the reloads.
+//!
+//! Tests and benchmarks can use a ConfigOverrideGuard to change the
+//! global configuration returned by Config::current for the current
+//! thread only, without affecting tests running on other threads.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+
+use crate::{error::Error, Config, Configuration};
+
+/// A function called with the new configuration when it's replaced
+type Subscriber = Arc<dyn Fn(&Arc<Config>) + Send + Sync>;
+
+/// Identifies a subscriber, so it can be removed later
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SubscriptionId(u64);
+
+/// The current configuration and the number of times it's been
+/// replaced
+struct Snapshot {
+    config: Arc<Config>,
+    generation: u64,
+}
+
+/// Holds a configuration that can be replaced at runtime
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicU64, Ordering},
+///     Arc,
+/// };
+///
+/// use forbidden_bands::{manager::ConfigManager, Config, Configuration};
+///
+/// let manager = ConfigManager::load().expect("Error loading config");
+/// let old = manager.current();
+///
+/// let seen = Arc::new(AtomicU64::new(0));
+/// let seen_by_subscriber = seen.clone();
+/// manager.subscribe(move |_config| {
+///     seen_by_subscriber.fetch_add(1, Ordering::SeqCst);
+/// });
+///
+/// manager.replace(Config::load_named("ascii-approximation").expect("Error loading config"));
+///
+/// assert_eq!(seen.load(Ordering::SeqCst), 1);
+/// assert_eq!(manager.generation(), 1);
+/// // The old snapshot is still usable
+/// assert!(!Arc::ptr_eq(&old, &manager.current()));
+/// ```
+pub struct ConfigManager {
+    snapshot: RwLock<Snapshot>,
+    subscribers: Mutex<BTreeMap<SubscriptionId, Subscriber>>,
+    /// True while a thread is notifying the subscribers
+    notifying: AtomicBool,
+    /// The generation the subscribers were last notified of
+    notified: AtomicU64,
+    /// Whether installed configurations become the global one
+    global: bool,
+    next_id: AtomicU64,
+}
+
+impl ConfigManager {
+    /// Create a manager holding a configuration
+    pub fn new(config: Config) -> ConfigManager {
+        ConfigManager {
+            snapshot: RwLock::new(Snapshot {
+                config: Arc::new(config),
+                generation: 0,
+            }),
+            subscribers: Mutex::new(BTreeMap::new()),
+            notifying: AtomicBool::new(false),
+            notified: AtomicU64::new(0),
+            global: false,
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Make the manager's configuration the global configuration
+    ///
+    /// The current configuration is published with
+    /// Config::set_current now, and every configuration installed
+    /// later is published the same way, so Config::current and the
+    /// conversions that use it see the reloads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use forbidden_bands::{manager::ConfigManager, Config, Configuration};
+    ///
+    /// let manager = ConfigManager::load().expect("Error loading config").publish_global();
+    ///
+    /// let installed = manager
+    ///     .reload_from_file("data/config.json")
+    ///     .expect("Error loading config");
+    ///
+    /// assert!(Arc::ptr_eq(&installed, &Config::current().expect("Error loading config")));
+    /// ```
+    pub fn publish_global(mut self) -> ConfigManager {
+        self.global = true;
+        crate::publish_current(self.current());
+        self
+    }
+
+    /// Create a manager holding the default configuration
+    pub fn load() -> std::result::Result<ConfigManager, Error> {
+        Ok(ConfigManager::new(Config::load()?))
+    }
+
+    /// A snapshot of the current configuration
+    pub fn current(&self) -> Arc<Config> {
+        self.snapshot
+            .read()
+            .expect("Should be able to get reader lock")
+            .config
+            .clone()
+    }
+
+    /// The number of times the configuration has been replaced
+    pub fn generation(&self) -> u64 {
+        self.snapshot
+            .read()
+            .expect("Should be able to get reader lock")
+            .generation
+    }
+
+    /// Replace the configuration and notify the subscribers
+    ///
+    /// Returns the previous configuration.  The subscribers are called
+    /// after the new configuration is visible to current.
+    ///
+    /// Subscribers are only called by one thread at a time.  If the
+    /// configuration is replaced while they're being notified, by
+    /// another thread or by a subscriber, the thread notifying them
+    /// calls them again with the newest configuration once it's done.
+    /// Configurations replaced before the subscribers got to them are
+    /// skipped, so the last configuration a subscriber is called with
+    /// is always the one current returns.
+    pub fn replace(&self, config: Config) -> Arc<Config> {
+        self.install(Arc::new(config))
+    }
+
+    /// Install a configuration and notify the subscribers, returning
+    /// the previous configuration
+    fn install(&self, config: Arc<Config>) -> Arc<Config> {
+        let previous = {
+            let mut snapshot = self
+                .snapshot
+                .write()
+                .expect("Should be able to acquire config lock");
+            snapshot.generation += 1;
+            if self.global {
+                crate::publish_current(config.clone());
+            }
+            std::mem::replace(&mut snapshot.config, config)
+        };
+
+        self.notify();
+
+        previous
+    }
+
+    /// Notify the subscribers of the newest configuration, unless
+    /// another thread is already notifying them
+    fn notify(&self) {
+        while !self.notifying.swap(true, Ordering::SeqCst) {
+            let (config, generation) = {
+                let snapshot = self
+                    .snapshot
+                    .read()
+                    .expect("Should be able to get reader lock");
+                (snapshot.config.clone(), snapshot.generation)
+            };
+
+            if generation > self.notified.load(Ordering::SeqCst) {
+                // Copy the subscribers so they can subscribe,
+                // unsubscribe or replace the configuration while
+                // being notified
+                let subscribers: Vec<Subscriber> = self
+                    .subscribers
+                    .lock()
+                    .expect("Should be able to lock subscribers")
+                    .values()
+                    .cloned()
+                    .collect();
+                for subscriber in subscribers {
+                    subscriber(&config);
+                }
+                self.notified.store(generation, Ordering::SeqCst);
+            }
+
+            self.notifying.store(false, Ordering::SeqCst);
+
+            // A configuration installed while the subscribers were
+            // being notified is picked up by this thread, unless
+            // another one has started notifying
+            if self.generation() == self.notified.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+    }
+
+    /// Load a configuration file and replace the current configuration
+    /// with it
+    ///
+    /// If the file can't be loaded, the current configuration is kept
+    /// and the subscribers aren't notified.  Returns the configuration
+    /// this call installed, even if another thread has replaced it
+    /// since.
+    pub fn reload_from_file(&self, filename: &str) -> std::result::Result<Arc<Config>, Error> {
+        let config = Arc::new(Config::load_from_file(filename)?);
+        self.install(config.clone());
+        Ok(config)
+    }
+
+    /// Call a function every time the configuration is replaced
+    ///
+    /// To receive the configurations on another thread, send them on a
+    /// channel from the function.
+    pub fn subscribe<F>(&self, subscriber: F) -> SubscriptionId
+    where
+        F: Fn(&Arc<Config>) + Send + Sync + 'static,
+    {
+        let id = SubscriptionId(self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+
+        self.subscribers
+            .lock()
+            .expect("Should be able to lock subscribers")
+            .insert(id, Arc::new(subscriber));
+
+        id
+    }
+
+    /// Stop calling a subscriber
+    ///
+    /// Returns false if the subscriber was already removed.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.subscribers
+            .lock()
+            .expect("Should be able to lock subscribers")
+            .remove(&id)
+            .is_some()
+    }
+}
+
+thread_local! {
+    /// The configuration installed by the innermost override guard on
+    /// this thread
+    static OVERRIDE: RefCell<Option<Arc<Config>>> = const { RefCell::new(None) };
+}
+
+/// The configuration overriding the global configuration on this
+/// thread, if there is one
+pub(crate) fn thread_override() -> Option<Arc<Config>> {
+    OVERRIDE.with(|config| config.borrow().clone())
+}
+
+/// Overrides the global configuration on the current thread until
+/// it's dropped
+///
+/// While the guard is alive, Config::current returns its
+/// configuration on this thread.  Other threads still see the global
+/// configuration.  When the guard is dropped the previous override,
+/// if any, is restored, so guards can be nested.  Guards should be
+/// dropped in the reverse order they were created, which happens
+/// naturally when they're scoped to blocks.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{manager::ConfigOverrideGuard, Config, Configuration};
+///
+/// let mut config = Config::load().expect("Error loading config");
+/// config.version = String::from("test");
+///
+/// {
+///     let _guard = ConfigOverrideGuard::new(config);
+///     assert_eq!(Config::current().expect("Error loading config").version, "test");
+/// }
+///
+/// assert_ne!(Config::current().expect("Error loading config").version, "test");
+/// ```
+pub struct ConfigOverrideGuard {
+    previous: Option<Arc<Config>>,
+    // The override is thread-local, so the guard has to be dropped on
+    // the thread that created it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ConfigOverrideGuard {
+    /// Override the global configuration on this thread
+    pub fn new(config: Config) -> ConfigOverrideGuard {
+        ConfigOverrideGuard::from_snapshot(Arc::new(config))
+    }
+
+    /// Override the global configuration on this thread with a
+    /// snapshot, like one from a ConfigManager
+    pub fn from_snapshot(config: Arc<Config>) -> ConfigOverrideGuard {
+        let previous = OVERRIDE.with(|current| current.borrow_mut().replace(config));
+
+        ConfigOverrideGuard {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for ConfigOverrideGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        OVERRIDE.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{mpsc, Arc};
+
+    use crate::{
+        manager::{ConfigManager, ConfigOverrideGuard},
+        Config, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn manager_snapshots_outlive_replace() {
+        let manager = ConfigManager::load().expect("Error loading config");
+
+        let snapshot = manager.current();
+        let petscii = snapshot.system::<SystemConfig>().unwrap();
+
+        let mut config = Config::load().unwrap();
+        config.version = String::from("9.9.9");
+        let previous = manager.replace(config);
+
+        assert!(Arc::ptr_eq(&snapshot, &previous));
+        assert_eq!(snapshot.version, "0.2.0");
+        assert_eq!(manager.current().version, "9.9.9");
+        assert!(!petscii.character_set_map.version.is_empty());
+    }
+
+    #[test]
+    fn manager_channel_subscriber_works() {
+        let manager = ConfigManager::load().expect("Error loading config");
+
+        let (sender, receiver) = mpsc::channel();
+        let sender = std::sync::Mutex::new(sender);
+        let id = manager.subscribe(move |config| {
+            sender.lock().unwrap().send(config.clone()).unwrap();
+        });
+
+        manager.replace(Config::load().unwrap());
+        let received = receiver.recv().unwrap();
+        assert!(Arc::ptr_eq(&received, &manager.current()));
+
+        assert!(manager.unsubscribe(id));
+        assert!(!manager.unsubscribe(id));
+        manager.replace(Config::load().unwrap());
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn manager_failed_reload_keeps_config() {
+        let manager = ConfigManager::load().expect("Error loading config");
+        let snapshot = manager.current();
+
+        assert!(manager.reload_from_file("data/missing.json").is_err());
+        assert!(Arc::ptr_eq(&snapshot, &manager.current()));
+        assert_eq!(manager.generation(), 0);
+
+        let reloaded = manager.reload_from_file("data/config.json").unwrap();
+        assert!(Arc::ptr_eq(&reloaded, &manager.current()));
+        assert_eq!(manager.generation(), 1);
+    }
+
+    #[test]
+    fn manager_subscriber_can_replace() {
+        let manager = Arc::new(ConfigManager::load().expect("Error loading config"));
+
+        let (sender, receiver) = mpsc::channel();
+        let sender = std::sync::Mutex::new(sender);
+        let weak = Arc::downgrade(&manager);
+        manager.subscribe(move |config| {
+            sender.lock().unwrap().send(config.version.clone()).unwrap();
+            // Fix up the version of the first configuration
+            if config.version == "bad" {
+                let mut fixed = Config::load().unwrap();
+                fixed.version = String::from("fixed");
+                weak.upgrade().unwrap().replace(fixed);
+            }
+        });
+
+        let mut bad = Config::load().unwrap();
+        bad.version = String::from("bad");
+        manager.replace(bad);
+
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), ["bad", "fixed"]);
+        assert_eq!(manager.current().version, "fixed");
+        assert_eq!(manager.generation(), 2);
+    }
+
+    #[test]
+    fn manager_concurrent_replace_notifies_in_order() {
+        let manager = Arc::new(ConfigManager::load().expect("Error loading config"));
+
+        let last = Arc::new(std::sync::Mutex::new(None));
+        let last_seen = last.clone();
+        manager.subscribe(move |config| {
+            *last_seen.lock().unwrap() = Some(config.clone());
+        });
+
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let manager = manager.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        let mut config = Config::load().unwrap();
+                        config.version = format!("{i}");
+                        manager.replace(config);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let last = last.lock().unwrap().clone().unwrap();
+        assert!(Arc::ptr_eq(&last, &manager.current()));
+        assert_eq!(manager.generation(), 160);
+    }
+
+    #[test]
+    fn manager_override_guards_nest() {
+        let global = Config::current().unwrap();
+
+        let manager = ConfigManager::load().unwrap();
+        let outer = manager.current();
+        {
+            let _outer_guard = ConfigOverrideGuard::from_snapshot(outer.clone());
+            {
+                let _inner_guard = ConfigOverrideGuard::new(Config::load().unwrap());
+                assert!(!Arc::ptr_eq(&Config::current().unwrap(), &outer));
+            }
+            assert!(Arc::ptr_eq(&Config::current().unwrap(), &outer));
+
+            // Other threads still see the global configuration
+            let other = std::thread::spawn(|| Config::current().unwrap().version.clone())
+                .join()
+                .unwrap();
+            assert_eq!(other, global.version);
+        }
+        assert!(Arc::ptr_eq(&Config::current().unwrap(), &global));
+    }
+}
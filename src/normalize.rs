@@ -0,0 +1,118 @@
+//!
+//! Unicode normalization before encoding
+//!
+//! Real-world text often contains characters that an 8-bit character
+//! set can't encode directly, but that have a simple equivalent it
+//! can.  Text copied from web pages may use decomposed accents
+//! (e followed by U+0301 COMBINING ACUTE ACCENT), fullwidth forms
+//! (Ａ) or compatibility characters like ½ and ﬁ.
+//!
+//! The string is first composed with NFC.  Characters that the target
+//! character set still can't encode are replaced with their NFKD
+//! compatibility decomposition with the combining marks removed, so
+//! Ａ becomes A, é becomes e and ½ becomes 1/2.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Fold a character to its compatibility decomposition without
+/// combining marks
+///
+/// Returns None if the character doesn't have a decomposition that
+/// is different from the character itself.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::normalize::fold_compatibility;
+///
+/// assert_eq!(fold_compatibility('Ａ'), Some(String::from("A")));
+/// assert_eq!(fold_compatibility('é'), Some(String::from("e")));
+/// assert_eq!(fold_compatibility('½'), Some(String::from("1/2")));
+/// assert_eq!(fold_compatibility('A'), None);
+/// ```
+pub fn fold_compatibility(c: char) -> Option<String> {
+    let folded: String = std::iter::once(c)
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        // The fraction slash from vulgar fractions like ½
+        .map(|c| if c == '\u{2044}' { '/' } else { c })
+        .collect();
+
+    if folded.is_empty() || folded.chars().eq(std::iter::once(c)) {
+        None
+    } else {
+        Some(folded)
+    }
+}
+
+/// Normalize a string so more of it can be encoded
+///
+/// encodable should return true if the target character set can
+/// encode a character.  Characters it can encode are left alone,
+/// even if they have a compatibility decomposition.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::normalize::normalize_for_encoding;
+///
+/// let s = normalize_for_encoding("Cafe\u{301} ＡＢＣ", |c| c.is_ascii());
+///
+/// assert_eq!(s, "Cafe ABC");
+/// ```
+pub fn normalize_for_encoding<F: Fn(char) -> bool>(s: &str, encodable: F) -> String {
+    s.nfc()
+        .fold(String::with_capacity(s.len()), |mut normalized, c| {
+            match fold_compatibility(c) {
+                Some(folded) if !encodable(c) => normalized.push_str(&folded),
+                _ => normalized.push(c),
+            }
+            normalized
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        normalize::normalize_for_encoding, petscii::PetsciiConfig, CharacterSet, Configuration,
+        EncodeOptions, SystemConfig,
+    };
+
+    #[test]
+    fn normalize_keeps_encodable_characters() {
+        // £ has no decomposition and ² would fold to 2, but both are
+        // kept when they can be encoded
+        let s = normalize_for_encoding("£²", |_| true);
+
+        assert_eq!(s, "£²");
+    }
+
+    #[test]
+    fn normalize_composes_before_folding() {
+        // e with a combining acute accent is composed to é, which is
+        // encodable here
+        let s = normalize_for_encoding("e\u{301}", |c| c == 'é');
+
+        assert_eq!(s, "é");
+    }
+
+    #[test]
+    fn normalize_encode_options_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let options = EncodeOptions::default().with_normalize(true);
+
+        assert_eq!(
+            petscii.encode_with_options("ＨＩ ½", &options),
+            petscii.encode("HI 1/2")
+        );
+        // Without normalization the fullwidth letters and ½ are dropped
+        assert_eq!(
+            petscii.encode_with_options("ＨＩ ½", &EncodeOptions::default()),
+            petscii.encode(" ")
+        );
+    }
+}
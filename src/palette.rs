@@ -0,0 +1,282 @@
+//!
+//! Commodore 64 color palettes
+//!
+//! The VIC-II generates its 16 colors as an analog video signal, so
+//! there isn't one correct set of RGB values for them.  Emulators and
+//! measurements of real machines disagree, and people have strong
+//! opinions about which set looks right.  A few well known sets are
+//! included here:
+//!
+//! * Colodore, measured by Philip "Pepto" Timmermann in 2017
+//! * Pepto, his earlier 2001 PAL measurements
+//! * VICE, the saturated palette older VICE versions used by default
+//!
+//! The palettes are in sRGB.  Matching an arbitrary color to the
+//! nearest C64 color is done in the CIELAB color space, which is
+//! closer to how different colors look than distances in sRGB.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Display, Formatter};
+
+use crate::petscii::Color;
+
+/// An sRGB color with 8 bits per channel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb {
+    /// Red
+    pub r: u8,
+    /// Green
+    pub g: u8,
+    /// Blue
+    pub b: u8,
+}
+
+impl Rgb {
+    /// Create a color from its channels
+    pub const fn new(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb { r, g, b }
+    }
+
+    /// Create a color from a 24-bit 0xRRGGBB value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::palette::Rgb;
+    ///
+    /// assert_eq!(Rgb::from_hex(0x813338), Rgb::new(0x81, 0x33, 0x38));
+    /// assert_eq!(Rgb::from_hex(0x813338).to_string(), "#813338");
+    /// ```
+    pub const fn from_hex(hex: u32) -> Rgb {
+        Rgb {
+            r: (hex >> 16) as u8,
+            g: (hex >> 8) as u8,
+            b: hex as u8,
+        }
+    }
+
+    /// The color as linear light values from 0.0 to 1.0, undoing the
+    /// sRGB transfer function
+    pub fn to_linear(self) -> [f64; 3] {
+        [self.r, self.g, self.b].map(|channel| {
+            let v = f64::from(channel) / 255.0;
+            if v <= 0.04045 {
+                v / 12.92
+            } else {
+                ((v + 0.055) / 1.055).powf(2.4)
+            }
+        })
+    }
+
+    /// Create a color from linear light values, applying the sRGB
+    /// transfer function
+    ///
+    /// Values outside 0.0 to 1.0 are clamped.
+    pub fn from_linear(linear: [f64; 3]) -> Rgb {
+        let [r, g, b] = linear.map(|v| {
+            let v = v.clamp(0.0, 1.0);
+            let v = if v <= 0.0031308 {
+                v * 12.92
+            } else {
+                1.055 * v.powf(1.0 / 2.4) - 0.055
+            };
+            (v * 255.0).round() as u8
+        });
+        Rgb { r, g, b }
+    }
+
+    /// The color in the CIELAB color space, with a D65 white point
+    fn to_lab(self) -> [f64; 3] {
+        let [r, g, b] = self.to_linear();
+        let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+        let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+
+        let [fx, fy, fz] = [x, y, z].map(|t| {
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                (24389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        });
+
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+}
+
+impl Display for Rgb {
+    /// Format the color the way HTML and CSS write it
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// A set of RGB values for the 16 C64 colors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    /// The name of the palette
+    pub name: &'static str,
+    /// The colors, in color number order
+    pub colors: [Rgb; 16],
+}
+
+impl Palette {
+    /// The Colodore palette
+    pub const COLODORE: Palette = Palette {
+        name: "Colodore",
+        colors: [
+            Rgb::from_hex(0x000000),
+            Rgb::from_hex(0xffffff),
+            Rgb::from_hex(0x813338),
+            Rgb::from_hex(0x75cec8),
+            Rgb::from_hex(0x8e3c97),
+            Rgb::from_hex(0x56ac4d),
+            Rgb::from_hex(0x2e2c9b),
+            Rgb::from_hex(0xedf171),
+            Rgb::from_hex(0x8e5029),
+            Rgb::from_hex(0x553800),
+            Rgb::from_hex(0xc46c71),
+            Rgb::from_hex(0x4a4a4a),
+            Rgb::from_hex(0x7b7b7b),
+            Rgb::from_hex(0xa9ff9f),
+            Rgb::from_hex(0x706deb),
+            Rgb::from_hex(0xb2b2b2),
+        ],
+    };
+
+    /// The Pepto PAL palette
+    pub const PEPTO: Palette = Palette {
+        name: "Pepto",
+        colors: [
+            Rgb::from_hex(0x000000),
+            Rgb::from_hex(0xffffff),
+            Rgb::from_hex(0x68372b),
+            Rgb::from_hex(0x70a4b2),
+            Rgb::from_hex(0x6f3d86),
+            Rgb::from_hex(0x588d43),
+            Rgb::from_hex(0x352879),
+            Rgb::from_hex(0xb8c76f),
+            Rgb::from_hex(0x6f4f25),
+            Rgb::from_hex(0x433900),
+            Rgb::from_hex(0x9a6759),
+            Rgb::from_hex(0x444444),
+            Rgb::from_hex(0x6c6c6c),
+            Rgb::from_hex(0x9ad284),
+            Rgb::from_hex(0x6c5eb5),
+            Rgb::from_hex(0x959595),
+        ],
+    };
+
+    /// The palette older versions of VICE used by default
+    pub const VICE: Palette = Palette {
+        name: "VICE",
+        colors: [
+            Rgb::from_hex(0x000000),
+            Rgb::from_hex(0xfdfefc),
+            Rgb::from_hex(0xbe1a24),
+            Rgb::from_hex(0x30e6c6),
+            Rgb::from_hex(0xb41ae2),
+            Rgb::from_hex(0x1fd21e),
+            Rgb::from_hex(0x211bae),
+            Rgb::from_hex(0xdff60a),
+            Rgb::from_hex(0xb84104),
+            Rgb::from_hex(0x6a3304),
+            Rgb::from_hex(0xfe4a57),
+            Rgb::from_hex(0x424540),
+            Rgb::from_hex(0x70746f),
+            Rgb::from_hex(0x59fe59),
+            Rgb::from_hex(0x5f53fe),
+            Rgb::from_hex(0xa4a7a2),
+        ],
+    };
+
+    /// All of the built-in palettes
+    pub const ALL: [Palette; 3] = [Palette::COLODORE, Palette::PEPTO, Palette::VICE];
+
+    /// Find a built-in palette by name, ignoring case
+    pub fn named(name: &str) -> Option<Palette> {
+        Palette::ALL
+            .into_iter()
+            .find(|palette| palette.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The RGB value of a color
+    pub fn rgb(&self, color: Color) -> Rgb {
+        self.colors[usize::from(color.index())]
+    }
+
+    /// The C64 color that looks the most like an RGB color
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     palette::{Palette, Rgb},
+    ///     petscii::Color,
+    /// };
+    ///
+    /// let palette = Palette::COLODORE;
+    ///
+    /// assert_eq!(palette.nearest(Rgb::new(0x80, 0x30, 0x30)), Color::Red);
+    /// assert_eq!(palette.nearest(Rgb::new(0x80, 0x80, 0x80)), Color::Grey);
+    /// assert_eq!(palette.nearest(palette.rgb(Color::Orange)), Color::Orange);
+    /// ```
+    pub fn nearest(&self, rgb: Rgb) -> Color {
+        let [l, a, b] = rgb.to_lab();
+
+        Color::ALL
+            .into_iter()
+            .map(|color| {
+                let [cl, ca, cb] = self.rgb(color).to_lab();
+                let distance = (l - cl).powi(2) + (a - ca).powi(2) + (b - cb).powi(2);
+                (color, distance)
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(color, _)| color)
+            .expect("There are always 16 colors")
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::COLODORE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        palette::{Palette, Rgb},
+        petscii::Color,
+    };
+
+    #[test]
+    fn palette_colors_are_their_own_nearest() {
+        for palette in Palette::ALL {
+            for color in Color::ALL {
+                assert_eq!(
+                    palette.nearest(palette.rgb(color)),
+                    color,
+                    "{}",
+                    palette.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn palette_linear_round_trip_works() {
+        for channel in [0, 1, 10, 11, 128, 254, 255] {
+            let rgb = Rgb::new(channel, 255 - channel, channel / 2);
+            assert_eq!(Rgb::from_linear(rgb.to_linear()), rgb);
+        }
+    }
+
+    #[test]
+    fn palette_named_works() {
+        assert_eq!(Palette::named("vice"), Some(Palette::VICE));
+        assert_eq!(Palette::named("colodore"), Some(Palette::default()));
+        assert_eq!(Palette::named("ntsc"), None);
+    }
+}
@@ -46,6 +46,7 @@
 
 use enumset::{EnumSet, EnumSetType};
 use std::{
+    borrow::Borrow,
     fmt::{Debug, Display, Formatter, Result},
     sync::RwLock,
 };
@@ -56,7 +57,171 @@ use serde::{Deserialize, Serialize};
 // #[cfg(feature = "json")]
 use serde_json::{Map, Value};
 
-use crate::{config_data, Configuration, SystemConfig};
+use crate::{
+    ascii::AsciiConfig,
+    config_data, load_cached, lookup_char,
+    petscii::decoder::{DecodeEvent, PetsciiDecoderState},
+    trace_decision, CharacterSet, Config, Configuration, DecodeOptions, SystemConfig, SystemModule,
+    UnicodeVersion,
+};
+
+pub mod animation;
+pub mod art;
+#[cfg(feature = "tokio")]
+pub mod async_io;
+pub mod banner;
+pub mod binary;
+pub mod bitmap;
+pub mod canonical;
+pub mod coverage;
+pub mod decoder;
+pub mod describe;
+pub mod diff;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+pub mod fuzzy;
+#[cfg(feature = "legacy-sources")]
+pub mod legacy_sources;
+pub mod markdown;
+pub mod marks;
+pub mod profile;
+pub mod provenance;
+pub mod report;
+pub mod resync;
+pub mod rich;
+pub mod screen;
+pub mod slice;
+pub mod svg;
+pub mod template;
+pub mod terminal;
+#[cfg(feature = "zeroize")]
+pub mod wipe;
+pub mod wrap;
+pub mod writer;
+
+pub use slice::PetsciiStr;
+
+/// The screen code sets in the configuration
+///
+/// Set 1 is the uppercase and graphics character set and set 2 is the
+/// lowercase and uppercase set.  Set 3 doesn't exist on the C64.  It
+/// holds control codes like carriage return that have a Unicode
+/// equivalent but no character on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum ScreenSet {
+    /// The uppercase and graphics character set
+    Set1,
+    /// The lowercase and uppercase character set
+    Set2,
+    /// The virtual set of control codes
+    Set3,
+}
+
+impl TryFrom<u8> for ScreenSet {
+    type Error = crate::error::Error;
+
+    fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
+        match value {
+            1 => Ok(ScreenSet::Set1),
+            2 => Ok(ScreenSet::Set2),
+            3 => Ok(ScreenSet::Set3),
+            _ => Err(crate::error::Error::new(crate::error::ErrorKind::Message(
+                format!("There is no screen code set {}", value),
+            ))),
+        }
+    }
+}
+
+impl From<ScreenSet> for u8 {
+    /// The number of the set in the configuration
+    fn from(set: ScreenSet) -> u8 {
+        match set {
+            ScreenSet::Set1 => 1,
+            ScreenSet::Set2 => 2,
+            ScreenSet::Set3 => 3,
+        }
+    }
+}
+
+/// A PETSCII code
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{PetsciiCode, PetsciiConfig, ScreenCode, ScreenSet},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+/// let map = &petscii.character_set_map;
+///
+/// let screen_code = PetsciiCode(0x41).to_screen_code(true, map).expect("No screen code");
+///
+/// assert_eq!(screen_code, ScreenCode { set: ScreenSet::Set2, value: 1 });
+/// assert_eq!(screen_code.to_unicode(map), Some('a'));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct PetsciiCode(pub u8);
+
+impl PetsciiCode {
+    /// True for the codes that change the cursor, colors or modes
+    /// instead of printing a character
+    pub fn is_control(self) -> bool {
+        matches!(self.0, 0x00..=0x1F | 0x80..=0x9F)
+    }
+
+    /// The screen code this code prints, in the shifted (lowercase)
+    /// or unshifted character set
+    ///
+    /// The tables have an entry for every code that prints something,
+    /// duplicates included.
+    pub fn to_screen_code(self, shifted: bool, map: &PetsciiConfig) -> Option<ScreenCode> {
+        self.try_to_screen_code(shifted, map).ok().flatten()
+    }
+
+    /// The screen code this code prints, None if it doesn't print
+    /// one, or an error if the configuration entry for it isn't a
+    /// screen code
+    pub fn try_to_screen_code(
+        self,
+        shifted: bool,
+        map: &PetsciiConfig,
+    ) -> std::result::Result<Option<ScreenCode>, crate::error::Error> {
+        let petscii_to_screen_codes = if shifted {
+            &map.c64_petscii_shifted_codes_to_screen_codes
+        } else {
+            &map.c64_petscii_unshifted_codes_to_screen_codes
+        };
+
+        petscii_to_screen_codes
+            .get(&self.0.to_string())
+            .map(|value| {
+                ScreenCode::deserialize(value).map_err(|e| {
+                    crate::error::Error::new(crate::error::ErrorKind::Message(format!(
+                        "PETSCII code {:#04x} has a bad screen code {}: {}",
+                        self.0, value, e
+                    )))
+                })
+            })
+            .transpose()
+    }
+}
+
+impl From<u8> for PetsciiCode {
+    fn from(code: u8) -> Self {
+        PetsciiCode(code)
+    }
+}
+
+impl From<PetsciiCode> for u8 {
+    fn from(code: PetsciiCode) -> u8 {
+        code.0
+    }
+}
 
 /// A Commodore screen code value and the screen set it is in
 ///
@@ -64,14 +229,175 @@ use crate::{config_data, Configuration, SystemConfig};
 /// the set and value fields.  The Serde and Serde JSON serializer
 /// automatically support deserializing from a tuple into a struct.
 /// This may be confusing so this note is here to let people know.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
-pub struct ScreenCodeValue {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub struct ScreenCode {
     /// The screen set this code is in
-    pub set: u8,
+    pub set: ScreenSet,
     /// The screen code value
     pub value: u8,
 }
 
+/// The old name of ScreenCode
+#[deprecated(note = "Use ScreenCode, which has a ScreenSet instead of a set number")]
+pub type ScreenCodeValue = ScreenCode;
+
+impl ScreenCode {
+    /// The screen code for a Unicode character
+    pub fn from_unicode(c: char, map: &PetsciiConfig) -> Option<ScreenCode> {
+        map.unicode_codes_to_c64_screen_codes
+            .get(&u32::from(c).to_string())
+            .and_then(|value| ScreenCode::deserialize(value).ok())
+    }
+
+    /// The Unicode character for the screen code
+    ///
+    /// Reversed screen codes in set 1 have their own characters when
+    /// Unicode has one.
+    pub fn to_unicode(self, map: &PetsciiConfig) -> Option<char> {
+        let screen_codes_to_unicode = match self.set {
+            ScreenSet::Set1 => &map.c64_screen_codes_set_1_to_unicode_codes,
+            ScreenSet::Set2 => &map.c64_screen_codes_set_2_to_unicode_codes,
+            ScreenSet::Set3 => &map.c64_screen_codes_set_3_to_unicode_codes,
+        };
+        lookup_char(screen_codes_to_unicode, self.value)
+    }
+
+    /// True if the screen code is a reversed character
+    ///
+    /// The second half of the set 1 and set 2 screen codes are the
+    /// reversed versions of the first half.
+    pub fn is_reversed(self) -> bool {
+        self.set != ScreenSet::Set3 && self.value > 127
+    }
+
+    /// The reversed version of the screen code
+    ///
+    /// Reversed screen codes are already reversed, and the virtual
+    /// set 3 control codes don't have reversed versions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::{ScreenCode, ScreenSet};
+    ///
+    /// let a = ScreenCode { set: ScreenSet::Set1, value: 1 };
+    ///
+    /// assert_eq!(a.reversed(), ScreenCode { set: ScreenSet::Set1, value: 129 });
+    /// assert_eq!(a.reversed().reversed(), a.reversed());
+    /// ```
+    pub fn reversed(self) -> ScreenCode {
+        match self.set {
+            ScreenSet::Set3 => self,
+            _ => ScreenCode {
+                value: self.value | 0x80,
+                ..self
+            },
+        }
+    }
+
+    /// The PETSCII code that prints the screen code, and whether it
+    /// needs the shifted character set
+    ///
+    /// The set 2 table only lists the letters.  Other set 2 screen
+    /// codes are printed by the same PETSCII code as in set 1, with
+    /// the shifted character set selected.
+    pub fn to_petscii(self, map: &PetsciiConfig) -> Option<PetsciiCodeValue> {
+        let screen_codes_to_petscii = match self.set {
+            ScreenSet::Set1 => &map.c64_screen_codes_set_1_to_petscii_codes,
+            ScreenSet::Set2 => &map.c64_screen_codes_set_2_to_petscii_codes,
+            ScreenSet::Set3 => &map.c64_screen_codes_set_3_to_petscii_codes,
+        };
+        let petscii_code = screen_codes_to_petscii
+            .get(&self.value.to_string())
+            .and_then(|value| PetsciiCodeValue::deserialize(value).ok());
+
+        match (petscii_code, self.set) {
+            (None, ScreenSet::Set2) => ScreenCode {
+                set: ScreenSet::Set1,
+                ..self
+            }
+            .to_petscii(map)
+            .map(|mut petscii_code| {
+                petscii_code
+                    .attributes
+                    .insert(PetsciiCharacterAttributes::Shifted);
+                petscii_code
+            }),
+            (petscii_code, _) => petscii_code,
+        }
+    }
+}
+
+/// Convert a PETSCII code to the screen code it prints
+///
+/// This is the first step of decoding.  shifted selects the lowercase
+/// character set, the state after 0x0E.  Returns None for codes that
+/// don't print a character, like colors and cursor movement.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{petscii_to_screen_code, screen_code_to_unicode, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+/// let map = &petscii.character_set_map;
+///
+/// let screen_code = petscii_to_screen_code(0x41, false, map).expect("No screen code");
+///
+/// assert_eq!(screen_code.value, 1);
+/// assert_eq!(screen_code_to_unicode(screen_code, map), Some('A'));
+/// assert_eq!(petscii_to_screen_code(0x93, false, map), None);
+/// ```
+pub fn petscii_to_screen_code(code: u8, shifted: bool, map: &PetsciiConfig) -> Option<ScreenCode> {
+    PetsciiCode(code).to_screen_code(shifted, map)
+}
+
+/// Convert a screen code to the Unicode character shown for it
+pub fn screen_code_to_unicode(screen_code: ScreenCode, map: &PetsciiConfig) -> Option<char> {
+    screen_code.to_unicode(map)
+}
+
+/// Convert a Unicode character to the screen code that shows it
+///
+/// This is the first step of encoding.  Programs that write screen
+/// memory directly can stop here, without going through PETSCII.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{unicode_to_screen_code, screen_code_to_petscii, PetsciiConfig, ScreenSet},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+/// let map = &petscii.character_set_map;
+///
+/// let screen_code = unicode_to_screen_code('b', map).expect("No screen code");
+/// assert_eq!((screen_code.set, screen_code.value), (ScreenSet::Set2, 2));
+///
+/// let petscii_code = screen_code_to_petscii(screen_code, map).expect("No PETSCII code");
+/// assert!(petscii_code.is_shifted());
+/// assert_eq!(u8::from(petscii_code.value), 0x42);
+/// ```
+pub fn unicode_to_screen_code(c: char, map: &PetsciiConfig) -> Option<ScreenCode> {
+    ScreenCode::from_unicode(c, map)
+}
+
+/// Convert a screen code to the PETSCII code that prints it, and
+/// whether the code needs the shifted character set
+pub fn screen_code_to_petscii(
+    screen_code: ScreenCode,
+    map: &PetsciiConfig,
+) -> Option<PetsciiCodeValue> {
+    screen_code.to_petscii(map)
+}
+
 /// Commodore 64 character attributes
 #[derive(Debug, EnumSetType, Serialize, Deserialize)]
 #[enumset(serialize_repr = "u8", repr = "u8")]
@@ -83,21 +409,49 @@ pub enum PetsciiCharacterAttributes {
 /// The Petscii Code along with whether it's the "shifted" table
 /// The unshifted table contains uppercase and graphics characters
 /// The shifted table contains lowercase and uppercase characters.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PetsciiCodeValue {
     /// Whether the value is shifted and other attributes
-    pub attributes: u8,
+    pub attributes: EnumSet<PetsciiCharacterAttributes>,
     /// The PETSCII code value
-    pub value: u8,
+    pub value: PetsciiCode,
+}
+
+impl PetsciiCodeValue {
+    /// True if the code is in the shifted character set
+    pub fn is_shifted(&self) -> bool {
+        self.attributes
+            .contains(PetsciiCharacterAttributes::Shifted)
+    }
 }
 
 /// Configuration data including character maps for the PETSCII crate
 // #[cfg(feature = "json")]
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PetsciiConfig {
     /// Version of the PETSCII config
     pub version: String,
 
+    /// PETSCII codes that are duplicates of other codes, mapped to
+    /// the code they duplicate
+    ///
+    /// On the C64, 192-223 are duplicates of 96-127, 224-254 are
+    /// duplicates of 160-190 and 255 is a duplicate of 126.  The
+    /// screen code tables list the duplicates too, so decoding only
+    /// uses this table to pick a character for codes without one.
+    /// Configurations without this table get the C64 duplicates.
+    #[serde(default = "default_code_aliases")]
+    pub c64_petscii_code_aliases: Map<String, Value>,
+
+    /// Names of the unshifted PETSCII codes, for tools and debuggers
+    #[serde(default)]
+    pub c64_petscii_unshifted_code_names: Map<String, Value>,
+
+    /// Names of the shifted PETSCII codes that print something
+    /// different from the unshifted code
+    #[serde(default)]
+    pub c64_petscii_shifted_code_names: Map<String, Value>,
+
     /// shifted PETSCII codes to screen codes
     pub c64_petscii_shifted_codes_to_screen_codes: Map<String, Value>,
 
@@ -130,58 +484,157 @@ pub struct PetsciiConfig {
     pub c64_screen_codes_set_3_to_petscii_codes: Map<String, Value>,
 }
 
-/// Configuration data for the PETSCII crate
+/// The C64 PETSCII code duplicates, for configurations that don't
+/// have an alias table
+fn default_code_aliases() -> Map<String, Value> {
+    (192..=255u8)
+        .map(|code| {
+            let canonical = match code {
+                192..=223 => code - 96,
+                224..=254 => code - 64,
+                _ => 126,
+            };
+            (code.to_string(), Value::from(canonical))
+        })
+        .collect()
+}
+
+impl PetsciiConfig {
+    /// The code a PETSCII code is a duplicate of
+    ///
+    /// Codes that aren't duplicates are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{Configuration, SystemConfig, petscii::PetsciiConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    /// let map = &petscii.character_set_map;
+    ///
+    /// assert_eq!(map.canonicalize_code(0xC1), 0x61);
+    /// assert_eq!(map.canonicalize_code(0xFF), 0x7E);
+    /// assert_eq!(map.canonicalize_code(0x41), 0x41);
+    /// ```
+    pub fn canonicalize_code(&self, code: u8) -> u8 {
+        self.c64_petscii_code_aliases
+            .get(&code.to_string())
+            .and_then(Value::as_u64)
+            .and_then(|canonical| u8::try_from(canonical).ok())
+            .unwrap_or(code)
+    }
+
+    /// Check that every PETSCII to screen code entry is a screen code
+    ///
+    /// Entries for screen codes above 127, the reversed characters,
+    /// are fine.  Decoding skips entries that aren't screen codes, so
+    /// a configuration with a typo in one decodes without it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{Configuration, SystemConfig, petscii::PetsciiConfig};
+    /// use serde_json::json;
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let mut petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    /// assert!(petscii.character_set_map.validate().is_ok());
+    ///
+    /// petscii
+    ///     .character_set_map
+    ///     .c64_petscii_shifted_codes_to_screen_codes
+    ///     .insert(String::from("65"), json!([2, 256]));
+    /// assert!(petscii.character_set_map.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> std::result::Result<(), crate::error::Error> {
+        for shifted in [false, true] {
+            for code in 0..=255u8 {
+                PetsciiCode(code).try_to_screen_code(shifted, self)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A copy of the configuration that only decodes to characters
+    /// in a version of Unicode
+    ///
+    /// Characters added after the version are replaced with their
+    /// ASCII approximations, or U+FFFD REPLACEMENT CHARACTER if they
+    /// don't have one.  The configuration can still encode them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{PetsciiConfig, PetsciiString},
+    ///     Configuration, SystemConfig, UnicodeVersion,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let mut petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// // 0xBA is a lower right corner, from Symbols for Legacy Computing
+    /// let ps = PetsciiString::new_with_config(2, [0x41, 0xBA], &petscii);
+    /// assert_eq!(String::from(ps), "A\u{1fb7f}");
+    ///
+    /// petscii.character_set_map = petscii
+    ///     .character_set_map
+    ///     .for_unicode_version(UnicodeVersion::Unicode12)
+    ///     .expect("Error loading config");
+    /// let ps = PetsciiString::new_with_config(2, [0x41, 0xBA], &petscii);
+    /// assert_eq!(String::from(ps), "A+");
+    /// ```
+    pub fn for_unicode_version(
+        &self,
+        version: UnicodeVersion,
+    ) -> std::result::Result<PetsciiConfig, crate::error::Error> {
+        let ascii = AsciiConfig::load()?;
+        let mut config = self.clone();
+
+        for table in [
+            &mut config.c64_screen_codes_set_1_to_unicode_codes,
+            &mut config.c64_screen_codes_set_2_to_unicode_codes,
+            &mut config.c64_screen_codes_set_3_to_unicode_codes,
+        ] {
+            for value in table.values_mut() {
+                let Some(c) = value.as_u64().and_then(|v| char::from_u32(v as u32)) else {
+                    continue;
+                };
+                if UnicodeVersion::of(c) > version {
+                    let fallback = ascii.approximate_char(c).unwrap_or('\u{fffd}');
+                    *value = Value::from(u32::from(fallback));
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// The parsed PETSCII character map
 ///
-/// We try to load this once on first use and then only read from it
-/// There is an overhead creating each PetsciiString getting a read
-/// lock on the config variable.
+/// PetsciiConfig::load parses the embedded map once and copies it
+/// from here after that.  This is only a cache: conversions that
+/// aren't given a configuration use [crate::Config::current].
 pub static CONFIG: RwLock<Option<PetsciiConfig>> = RwLock::new(None);
 
+impl SystemModule for SystemConfig {
+    const NAMESPACE: &'static str = "petscii";
+}
+
 /// Load the configuration data from the PETSCII configuration string
 impl Configuration for PetsciiConfig {
     fn load() -> std::result::Result<crate::Config, crate::error::Error> {
-        let crate_config = crate::Config::load()?;
-
-        // First see if the configuration is already loaded
-        {
-            let binding = CONFIG.read().expect("Should be able to get reader lock");
-
-            let test = binding.as_ref();
-            // This pattern has a code smell
-            // I don't have a good RAII replacement for it.
-            // I'm rust.try_once_into_and_or_expect_better_ergonomics_from_compiler_not_speed(|e| { yoda_is_in_lispland(e) });
-            if test.is_some() {
-                let petscii_config = test.expect("Should be set at this point");
-
-                return Ok(crate::Config {
-                    version: crate_config.version,
-                    petscii: crate::SystemConfig {
-                        version: crate_config.petscii.version,
-                        character_set_map: petscii_config.clone(),
-                    },
-                });
-            }
-        }
+        let mut crate_config = crate::Config::load()?;
 
-        // If the configuration is not loaded, load it and save it
-        let json_str = config_data::C64_PETSCII_MAP;
-        let petscii_config: PetsciiConfig =
-            serde_json::from_str(json_str).expect("Couldn't load embedded config");
+        // The character map is parsed once and then shared by every
+        // configuration loaded after that
+        let mut petscii: SystemConfig = crate_config.system()?;
+        petscii.character_set_map = load_cached(&CONFIG, config_data::C64_PETSCII_MAP)?;
+        crate_config.set_system(&petscii)?;
 
-        {
-            let mut lock_res = CONFIG
-                .write()
-                .expect("Should be able to acquire config lock");
-            *lock_res = Some(petscii_config.clone());
-        }
-
-        Ok(crate::Config {
-            version: crate_config.version,
-            petscii: crate::SystemConfig {
-                version: crate_config.petscii.version,
-                character_set_map: petscii_config.clone(),
-            },
-        })
+        Ok(crate_config)
     }
 
     fn load_from_file(filename: &str) -> std::result::Result<crate::Config, crate::error::Error> {
@@ -214,7 +667,109 @@ pub struct PetsciiCharacter {
     /// The attributes of this character
     pub attributes: CharacterAttributes,
     /// The character PETSCII code
-    pub character: u8,
+    pub character: PetsciiCode,
+}
+
+/// The 16 Commodore 64 colors, in the order of the VIC-II color
+/// numbers
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Color {
+    /// Color 0
+    Black,
+    /// Color 1
+    White,
+    /// Color 2
+    Red,
+    /// Color 3
+    Cyan,
+    /// Color 4
+    Purple,
+    /// Color 5
+    Green,
+    /// Color 6
+    Blue,
+    /// Color 7
+    Yellow,
+    /// Color 8
+    Orange,
+    /// Color 9
+    Brown,
+    /// Color 10
+    LightRed,
+    /// Color 11
+    DarkGrey,
+    /// Color 12
+    Grey,
+    /// Color 13
+    LightGreen,
+    /// Color 14
+    LightBlue,
+    /// Color 15
+    LightGrey,
+}
+
+/// The PETSCII control codes that select each color, in color number
+/// order
+const COLOR_CONTROL_CODES: [u8; 16] = [
+    0x90, 0x05, 0x1C, 0x9F, 0x9C, 0x1E, 0x1F, 0x9E, 0x81, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0x9B,
+];
+
+impl Color {
+    /// All of the colors, in color number order
+    pub const ALL: [Color; 16] = [
+        Color::Black,
+        Color::White,
+        Color::Red,
+        Color::Cyan,
+        Color::Purple,
+        Color::Green,
+        Color::Blue,
+        Color::Yellow,
+        Color::Orange,
+        Color::Brown,
+        Color::LightRed,
+        Color::DarkGrey,
+        Color::Grey,
+        Color::LightGreen,
+        Color::LightBlue,
+        Color::LightGrey,
+    ];
+
+    /// The color with a VIC-II color number
+    ///
+    /// Only the low four bits are used, like the VIC-II does.
+    pub fn from_index(index: u8) -> Color {
+        Color::ALL[usize::from(index & 0x0F)]
+    }
+
+    /// The VIC-II color number
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// The color selected by a PETSCII control code
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::Color;
+    ///
+    /// assert_eq!(Color::from_control_code(0x1C), Some(Color::Red));
+    /// assert_eq!(Color::from_control_code(0x41), None);
+    /// assert_eq!(Color::White.control_code(), 0x05);
+    /// ```
+    pub fn from_control_code(code: u8) -> Option<Color> {
+        COLOR_CONTROL_CODES
+            .iter()
+            .position(|c| *c == code)
+            .map(|index| Color::ALL[index])
+    }
+
+    /// The PETSCII control code that selects the color
+    pub fn control_code(self) -> u8 {
+        COLOR_CONTROL_CODES[usize::from(self.index())]
+    }
 }
 
 /// A PETSCII string
@@ -241,6 +796,17 @@ pub struct PetsciiString<'a, const L: usize> {
     pub strip_shifted_space: bool,
 }
 
+/// What PetsciiString::resize does with a string longer than the new
+/// width
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Return an error
+    #[default]
+    Error,
+    /// Keep the bytes that fit and drop the rest
+    Truncate,
+}
+
 impl<'a, const L: usize> Debug for PetsciiString<'a, L> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "length: {:?}, ", self.len)?;
@@ -258,8 +824,11 @@ impl<'a, const L: usize> Display for PetsciiString<'a, L> {
 /// An IntoIter structure for PetsciiStrings
 /// We need to keep track of the index of the current element, along
 /// with the data.
+/// The end index is tracked too, so the iterator can be consumed
+/// from the back.
 pub struct IntoIter<'a, const L: usize> {
     index: usize,
+    end: usize,
     data: PetsciiString<'a, L>,
 }
 
@@ -269,6 +838,7 @@ impl<'a, const L: usize> IntoIterator for PetsciiString<'a, L> {
     fn into_iter(self) -> IntoIter<'a, L> {
         IntoIter {
             index: 0,
+            end: self.byte_len(),
             data: self,
         }
     }
@@ -277,35 +847,72 @@ impl<'a, const L: usize> IntoIterator for PetsciiString<'a, L> {
 impl<'a, const L: usize> Iterator for IntoIter<'a, L> {
     type Item = u8;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.data.len.try_into().unwrap() {
+        if self.index < self.end {
             self.index += 1;
             Some(self.data.data[self.index - 1])
         } else {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.index;
+        (remaining, Some(remaining))
+    }
 }
 
-impl<'a, const L: usize> From<&'a [u8]> for PetsciiString<'a, L> {
-    fn from(s: &'a [u8]) -> PetsciiString<L> {
-        let mut bytes: [u8; L] = [0; L];
-        if s.len() > L {
-            panic!("u8 slice is too large");
+impl<'a, const L: usize> DoubleEndedIterator for IntoIter<'a, L> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.end {
+            self.end -= 1;
+            Some(self.data.data[self.end])
+        } else {
+            None
         }
+    }
+}
 
-        // Replacing the below manual copy loop between slices with
-        // the following recomendation from clippy
-        // for i in 0..L {
-        //     bytes[i] = s[i];
-        // }
-        bytes[..L].copy_from_slice(&s[..L]);
+impl<'a, const L: usize> ExactSizeIterator for IntoIter<'a, L> {}
 
-        PetsciiString {
-            len: L as u32,
-            data: bytes,
-            character_map: None,
-            strip_shifted_space: false,
-        }
+impl<'a, const L: usize> TryFrom<&[u8]> for PetsciiString<'a, L> {
+    type Error = crate::error::Error;
+
+    /// Copy the bytes into a string, or return an error if there are
+    /// more than L of them
+    fn try_from(s: &[u8]) -> std::result::Result<Self, Self::Error> {
+        PetsciiString::try_from_slice(s)
+    }
+}
+
+impl<'a, const L: usize> Default for PetsciiString<'a, L> {
+    /// An empty string without a character map
+    fn default() -> Self {
+        PetsciiString::new(0, [0; L])
+    }
+}
+
+impl<'a, const L: usize> TryFrom<Vec<u8>> for PetsciiString<'a, L> {
+    type Error = crate::error::Error;
+
+    /// Copy the bytes into a string, or return an error if there are
+    /// more than L of them
+    fn try_from(bytes: Vec<u8>) -> std::result::Result<Self, Self::Error> {
+        PetsciiString::try_from_slice(&bytes)
+    }
+}
+
+impl<'a, const L: usize> AsRef<[u8]> for PetsciiString<'a, L> {
+    /// The first byte_len bytes of the data
+    fn as_ref(&self) -> &[u8] {
+        &self.data[..self.byte_len()]
+    }
+}
+
+impl<'a, const L: usize> Borrow<[u8]> for PetsciiString<'a, L> {
+    /// The same bytes as as_ref, so the string can be looked up by
+    /// its bytes
+    fn borrow(&self) -> &[u8] {
+        self.as_ref()
     }
 }
 
@@ -326,103 +933,9 @@ impl<'a, const L: usize> From<&'a [u8]> for PetsciiString<'a, L> {
 ///
 /// If there are other common uses cases, this could be made a
 /// parameter or the default changed.
-fn unicode_to_petscii_bytes(s: &str) -> Vec<u8> {
-    let mut attributes = EnumSet::new();
+fn unicode_to_petscii_bytes(s: &str, character_map: &PetsciiConfig) -> Vec<u8> {
     let mut shifted = false;
-
-    let config = PetsciiConfig::load().expect("Error loading config");
-
-    let uc_map = config
-        .petscii
-        .character_set_map
-        .unicode_codes_to_c64_screen_codes;
-    let sc1_map = config
-        .petscii
-        .character_set_map
-        .c64_screen_codes_set_1_to_petscii_codes;
-    let sc2_map = config
-        .petscii
-        .character_set_map
-        .c64_screen_codes_set_2_to_petscii_codes;
-    let sc3_map = config
-        .petscii
-        .character_set_map
-        .c64_screen_codes_set_3_to_petscii_codes;
-
-    attributes.insert(CharacterAttributes::Normal);
-
-    let mut bytes: Vec<u8> = s
-        .chars()
-        .filter_map(|c| {
-            let key = u32::from(c).to_string();
-
-            let screen_code_opt = uc_map.get(&key);
-
-            let screen_code_value = match screen_code_opt {
-                Some(s) => s,
-                None => {
-                    return None;
-                }
-            };
-
-            let screen_code_res = ScreenCodeValue::deserialize(screen_code_value);
-            let screen_code = match screen_code_res {
-                Ok(s) => s,
-                Err(_) => {
-                    return None;
-                }
-            };
-
-            let key = screen_code.value.to_string();
-            let petscii_code_opt = if screen_code.set == 1 {
-                sc1_map.get(&key)
-            } else if screen_code.set == 2 {
-                sc2_map.get(&key)
-            } else if screen_code.set == 3 {
-                // Screen code set 3 is a "virtual" screen code set
-                // It's used to transform control characters like line feed
-                // and carriage return
-                sc3_map.get(&key)
-            } else {
-                return None;
-            };
-            let petscii_code_value = match petscii_code_opt {
-                Some(s) => s,
-                None => {
-                    return None;
-                }
-            };
-
-            let petscii_code_res = PetsciiCodeValue::deserialize(petscii_code_value);
-            let petscii_code = match petscii_code_res {
-                Ok(s) => s,
-                Err(_) => {
-                    return None;
-                }
-            };
-
-            Some(petscii_code)
-        })
-        .flat_map(|petscii_code| {
-            let mut codes: Vec<u8> = Vec::new();
-            let eset: EnumSet<PetsciiCharacterAttributes> =
-                EnumSet::from_repr(petscii_code.attributes);
-
-            if eset.contains(PetsciiCharacterAttributes::Shifted) {
-                if !shifted {
-                    // Output a new shift in character
-                    codes.push(0x0E);
-                    shifted = true;
-                }
-            } else if shifted {
-                // Output a new shift out character
-                codes.push(0x8E);
-                shifted = false;
-            }
-            codes.push(petscii_code.value);
-            codes
-        })
-        .collect();
+    let mut bytes = encode_from_state(s, character_map, &mut shifted);
 
     // Shift out if we're still shifted at the end of a string
     if shifted {
@@ -432,25 +945,97 @@ fn unicode_to_petscii_bytes(s: &str) -> Vec<u8> {
     bytes
 }
 
-impl<'a, const L: usize> From<&str> for PetsciiString<'a, L> {
-    fn from(s: &str) -> PetsciiString<'a, L> {
-        let mut final_bytes: [u8; L] = [0; L];
+/// Convert a Unicode string slice to PETSCII bytes, starting in a
+/// shift state
+///
+/// The shift state is updated and the bytes are left in it, so
+/// strings can be encoded one after another without shifting in and
+/// out between them.  Characters that only exist as reversed screen
+/// codes are wrapped in reverse on and off codes.
+pub(crate) fn encode_from_state(
+    s: &str,
+    character_map: &PetsciiConfig,
+    shifted: &mut bool,
+) -> Vec<u8> {
+    encode_reversed_from_state(s.chars().map(|c| (c, false)), character_map, shifted)
+}
 
-        let bytes = unicode_to_petscii_bytes(s);
+/// Encode characters into PETSCII, each with whether it's shown in
+/// reverse video
+///
+/// Works like encode_from_state, with reverse on and off codes around
+/// the runs of reversed characters as well.
+pub(crate) fn encode_reversed_from_state(
+    chars: impl IntoIterator<Item = (char, bool)>,
+    character_map: &PetsciiConfig,
+    shifted: &mut bool,
+) -> Vec<u8> {
+    let mut codes: Vec<u8> = Vec::new();
+    let mut reversed = false;
+
+    for (c, reverse) in chars {
+        let Some(screen_code) = ScreenCode::from_unicode(c, character_map) else {
+            trace_decision!(character = ?c, "dropping character without a screen code");
+            continue;
+        };
+        let Some(petscii_code) = screen_code.to_petscii(character_map) else {
+            trace_decision!(
+                character = ?c,
+                screen_code = ?screen_code,
+                "dropping character whose screen code has no PETSCII code"
+            );
+            continue;
+        };
 
-        if bytes.len() > L {
-            panic!("u8 slice is too large");
+        if petscii_code.is_shifted() {
+            if !*shifted {
+                // Output a new shift in character
+                codes.push(0x0E);
+                *shifted = true;
+            }
+        } else if *shifted {
+            // Output a new shift out character
+            codes.push(0x8E);
+            *shifted = false;
         }
-        let b = bytes.as_slice();
-
-        final_bytes[..b.len()].copy_from_slice(&b[..b.len()]);
 
-        PetsciiString {
-            len: b.len() as u32,
-            data: final_bytes,
-            character_map: None,
-            strip_shifted_space: false,
+        let is_reversed = reverse || screen_code.is_reversed();
+        if is_reversed != reversed {
+            codes.push(if is_reversed { 0x12 } else { 0x92 });
+            reversed = is_reversed;
         }
+        codes.push(petscii_code.value.into());
+    }
+
+    if reversed {
+        codes.push(0x92);
+    }
+    codes
+}
+
+impl<'a, const L: usize> TryFrom<&str> for PetsciiString<'a, L> {
+    type Error = crate::error::Error;
+
+    /// Encode a string with the configuration from [Config::current]
+    ///
+    /// Returns an error if the current configuration has no PETSCII
+    /// tables or the encoded string is longer than L bytes.  Use
+    /// [PetsciiString::from_str_truncated] to cut long strings
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// let ps = PetsciiString::<16>::try_from("GAME").expect("Error encoding string");
+    /// assert_eq!(ps.as_ref(), b"GAME");
+    ///
+    /// assert!(PetsciiString::<2>::try_from("GAME").is_err());
+    /// ```
+    fn try_from(s: &str) -> std::result::Result<Self, Self::Error> {
+        let petscii = Config::current()?.cached_system::<SystemConfig>()?;
+        PetsciiString::try_from_slice(&unicode_to_petscii_bytes(s, &petscii.character_set_map))
     }
 }
 
@@ -460,274 +1045,756 @@ impl<'a, const L: usize> From<PetsciiString<'a, L>> for String {
     /// # Examples
     ///
     /// ```
-    /// use forbidden_bands::{
-    ///     petscii::{PetsciiConfig, PetsciiString},
-    ///     Config,
-    ///     Configuration,
-    /// };
-    ///
-    /// let config = PetsciiConfig::load().expect("Error loading config file");
+    /// use forbidden_bands::{
+    ///     petscii::{PetsciiConfig, PetsciiString},
+    ///     Config,
+    ///     Configuration,
+    ///     SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config file");
+    ///
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    /// let ps = PetsciiString::new_with_config(6, [0x41, 0x42, 0x43, 0x5c, 0x5e, 0x5f], &petscii);
+    /// let mut s: String = String::from(ps);
+    ///
+    /// assert_eq!(s.pop().unwrap(), '←');
+    /// assert_eq!(s.pop().unwrap(), '↑');
+    /// assert_eq!(s.pop().unwrap(), '£');
+    /// assert_eq!(s.pop().unwrap(), 'C');
+    /// assert_eq!(s.pop().unwrap(), 'B');
+    /// assert_eq!(s.pop().unwrap(), 'A');
+    /// ```
+    fn from(s: PetsciiString<L>) -> String {
+        String::from(&s)
+    }
+}
+
+impl<'a, const L: usize> From<&PetsciiString<'a, L>> for String {
+    /// Create a String from a reference to a PetsciiString
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{PetsciiConfig, PetsciiString},
+    ///     Config,
+    ///     Configuration,
+    ///     SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config file");
+    ///
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    /// let ps = PetsciiString::new_with_config(6, [0x41, 0x42, 0x43, 0x5c, 0x5e, 0x5f], &petscii);
+    /// let mut s: String = String::from(&ps);
+    ///
+    /// assert_eq!(s.pop().unwrap(), '←');
+    /// assert_eq!(s.pop().unwrap(), '↑');
+    /// assert_eq!(s.pop().unwrap(), '£');
+    /// assert_eq!(s.pop().unwrap(), 'C');
+    /// assert_eq!(s.pop().unwrap(), 'B');
+    /// assert_eq!(s.pop().unwrap(), 'A');
+    /// ```
+    // TODO: Unicode 13 now has "Legacy Computing Sources"
+    // (Unicode 13 was released around March 10, 2020).
+    fn from(s: &PetsciiString<L>) -> String {
+        petscii_bytes_to_unicode(s.into_iter(), s.strip_shifted_space, s.character_map)
+    }
+}
+
+/// Convert a sequence of PETSCII bytes to a Unicode String
+///
+/// If strip_shifted_space is true, shifted spaces (0xA0) are removed
+/// from the output.  If no character map is given, the bytes are
+/// converted directly to the Unicode characters with the same value.
+fn petscii_bytes_to_unicode<I: Iterator<Item = u8>>(
+    bytes: I,
+    strip_shifted_space: bool,
+    character_map: Option<&SystemConfig>,
+) -> String {
+    decode_from_state(
+        bytes,
+        PetsciiDecoderState::new(character_map).with_strip_shifted_space(strip_shifted_space),
+    )
+}
+
+/// Convert a sequence of PETSCII bytes to a Unicode String, starting
+/// in a decoder state
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn decode_from_state<I: Iterator<Item = u8>>(bytes: I, mut state: PetsciiDecoderState) -> String {
+    bytes
+        .filter_map(|c| match state.feed(c) {
+            DecodeEvent::Char(c) => Some(c),
+            DecodeEvent::Control { code, .. } => {
+                trace_decision!(code, "dropping control code");
+                None
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+impl CharacterSet for SystemConfig {
+    /// Decode a sequence of PETSCII bytes into a Unicode string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, CharacterSet, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    ///
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert_eq!(petscii.decode(&[0x41, 0x42, 0x5c]), "AB£");
+    /// ```
+    fn decode(&self, bytes: &[u8]) -> String {
+        petscii_bytes_to_unicode(bytes.iter().copied(), false, Some(self))
+    }
+
+    /// Decode a sequence of PETSCII bytes into a Unicode string,
+    /// starting in the character set and reverse video state of the
+    /// options
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::PetsciiConfig, CharacterSet, Configuration, DecodeOptions, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// // Art saved without the shift code at the start
+    /// let options = DecodeOptions::default().with_initial_shifted(true);
+    ///
+    /// assert_eq!(petscii.decode_with_options(&[0x48, 0x49], &options), "hi");
+    /// ```
+    fn decode_with_options(&self, bytes: &[u8], options: &DecodeOptions) -> String {
+        let state = PetsciiDecoderState::new(Some(self))
+            .with_shifted(options.initial_shifted)
+            .with_reversed(options.initial_reversed);
+        let decoded = match options.hex_escape_runs {
+            Some(min_run) => binary::decode_escaping(bytes, state, min_run),
+            None => decode_from_state(bytes.iter().copied(), state),
+        };
+        options.target.apply(decoded)
+    }
+
+    /// Encode a Unicode string into a sequence of PETSCII bytes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, CharacterSet, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    ///
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert_eq!(petscii.encode("ABc"), vec![0x41, 0x42, 0x0e, 0x43, 0x8e]);
+    /// ```
+    fn encode(&self, s: &str) -> Vec<u8> {
+        unicode_to_petscii_bytes(s, &self.character_set_map)
+    }
+}
+
+/// A PETSCII code that doesn't encode back to itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PetsciiMismatch {
+    /// True if the code is in the shifted (lowercase) character set
+    pub shifted: bool,
+    /// The PETSCII code
+    pub code: u8,
+    /// The character the code decodes to
+    pub character: char,
+    /// The PETSCII codes the character encodes to, without shift
+    /// codes.  This is empty if the character can't be encoded.
+    pub encoded: Vec<u8>,
+}
+
+/// A Unicode character that doesn't decode back to itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnicodeMismatch {
+    /// The Unicode character
+    pub character: char,
+    /// The PETSCII codes the character encodes to
+    pub encoded: Vec<u8>,
+    /// The string the PETSCII codes decode to
+    pub decoded: String,
+}
+
+/// Report of the asymmetries between the PETSCII to Unicode maps
+/// and the Unicode to PETSCII maps
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BijectivityReport {
+    /// PETSCII codes whose Unicode character maps back to a
+    /// different code
+    pub petscii_mismatches: Vec<PetsciiMismatch>,
+    /// Unicode characters whose PETSCII codes map back to a
+    /// different character
+    pub unicode_mismatches: Vec<UnicodeMismatch>,
+}
+
+impl BijectivityReport {
+    /// True if every mapping has an inverse
+    pub fn is_bijective(&self) -> bool {
+        self.petscii_mismatches.is_empty() && self.unicode_mismatches.is_empty()
+    }
+}
+
+impl Display for BijectivityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for m in &self.petscii_mismatches {
+            let set = if m.shifted { "shifted" } else { "unshifted" };
+            if m.encoded.is_empty() {
+                writeln!(
+                    f,
+                    "{set} PETSCII {:#04x} decodes to {:?} (U+{:04X}), which can't be encoded",
+                    m.code,
+                    m.character,
+                    u32::from(m.character)
+                )?;
+            } else {
+                writeln!(
+                    f,
+                    "{set} PETSCII {:#04x} decodes to {:?} (U+{:04X}), which encodes to {:02x?}",
+                    m.code,
+                    m.character,
+                    u32::from(m.character),
+                    m.encoded
+                )?;
+            }
+        }
+        for m in &self.unicode_mismatches {
+            writeln!(
+                f,
+                "{:?} (U+{:04X}) encodes to {:02x?}, which decodes to {:?}",
+                m.character,
+                u32::from(m.character),
+                m.encoded,
+                m.decoded
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl SystemConfig {
+    /// Check that the PETSCII to Unicode maps and the Unicode to
+    /// PETSCII maps are inverses of each other
+    ///
+    /// Every PETSCII code from 0 to 191, in both the unshifted and
+    /// shifted character sets, is decoded and encoded again.  Codes
+    /// 192 to 255 are copies of lower codes and the shift and reverse
+    /// video control codes don't decode to characters, so they aren't
+    /// checked.  Then every character in the Unicode to screen code
+    /// map is encoded and decoded again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{Config, SystemConfig};
+    ///
+    /// let config = Config::load_named("lossless-escape").expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let report = petscii.check_bijectivity();
+    ///
+    /// // Carriage return decodes to \r, which isn't in the map
+    /// assert!(report
+    ///     .petscii_mismatches
+    ///     .iter()
+    ///     .all(|m| m.code == 0x0d && m.encoded.is_empty()));
+    /// ```
+    pub fn check_bijectivity(&self) -> BijectivityReport {
+        let mut report = BijectivityReport::default();
+
+        for shifted in [false, true] {
+            for code in 0..=191u8 {
+                if matches!(code, 0x0E | 0x12 | 0x8E | 0x92) {
+                    continue;
+                }
+
+                let bytes = if shifted {
+                    vec![0x0E, code]
+                } else {
+                    vec![code]
+                };
+                let decoded = self.decode(&bytes);
+                let mut chars = decoded.chars();
+                let character = match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => continue,
+                };
+
+                let encoded: Vec<u8> = self
+                    .encode(character.encode_utf8(&mut [0; 4]))
+                    .into_iter()
+                    .filter(|b| *b != 0x0E && *b != 0x8E)
+                    .collect();
+
+                if encoded != [code] {
+                    report.petscii_mismatches.push(PetsciiMismatch {
+                        shifted,
+                        code,
+                        character,
+                        encoded,
+                    });
+                }
+            }
+        }
+
+        let mut characters: Vec<char> = self
+            .character_set_map
+            .unicode_codes_to_c64_screen_codes
+            .keys()
+            .filter_map(|key| key.parse::<u32>().ok().and_then(char::from_u32))
+            .collect();
+        characters.sort_unstable();
+
+        for character in characters {
+            let encoded = self.encode(character.encode_utf8(&mut [0; 4]));
+            let decoded = self.decode(&encoded);
+
+            if !decoded.chars().eq(std::iter::once(character)) {
+                report.unicode_mismatches.push(UnicodeMismatch {
+                    character,
+                    encoded,
+                    decoded,
+                });
+            }
+        }
+
+        report
+    }
+
+    /// Decode PETSCII bytes that may come from anywhere, with a
+    /// configuration that may come from anywhere
+    ///
+    /// This never panics, and is what the fuzz targets check.  Bytes
+    /// the configuration maps to something that isn't a screen code
+    /// become U+FFFD REPLACEMENT CHARACTER, where decode drops them,
+    /// and configured Unicode values that aren't characters are
+    /// skipped.  Use it for files downloaded from BBSes and disk
+    /// images of unknown origin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, Configuration, SystemConfig};
+    /// use serde_json::json;
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let mut petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert_eq!(petscii.decode_untrusted(&[0x41, 0x42, 0x5c]), "AB£");
+    ///
+    /// petscii
+    ///     .character_set_map
+    ///     .c64_petscii_unshifted_codes_to_screen_codes
+    ///     .insert(String::from("65"), json!([9, 1]));
+    /// assert_eq!(petscii.decode_untrusted(&[0x41, 0x42]), "\u{fffd}B");
+    /// ```
+    pub fn decode_untrusted(&self, bytes: &[u8]) -> String {
+        let mut state = PetsciiDecoderState::new(Some(self));
+
+        bytes
+            .iter()
+            .filter_map(|c| match state.try_feed(*c) {
+                Ok(DecodeEvent::Char(c)) => Some(c),
+                Ok(_) => None,
+                Err(_) => Some(char::REPLACEMENT_CHARACTER),
+            })
+            .collect()
+    }
+}
+
+impl<'a, const L: usize> PetsciiString<'a, L> {
+    /// Create a new Petscii string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// let ps = PetsciiString::new(6, [0x41, 0x42, 0x43]);
+    ///
+    /// assert_eq!(ps.data[0], 0x41);
+    /// assert_eq!(ps.data[1], 0x42);
+    /// assert_eq!(ps.data[2], 0x43);
+    /// ```
+    pub fn new(len: u32, data: [u8; L]) -> Self {
+        PetsciiString {
+            len,
+            data,
+            character_map: None,
+            strip_shifted_space: false,
+        }
+    }
+
+    /// Create a new PETSCII string with a given character map
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{PetsciiConfig, PetsciiString},
+    ///     Config,
+    ///     Configuration,
+    ///     SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let ps = PetsciiString::new(6, [0x41, 0x42, 0x43]);
+    ///
+    /// assert_eq!(ps.data[0], 0x41);
+    /// assert_eq!(ps.data[1], 0x42);
+    /// assert_eq!(ps.data[2], 0x43);
+    /// ```
+    pub fn new_with_config(len: u32, data: [u8; L], character_map: &'a SystemConfig) -> Self {
+        PetsciiString {
+            len,
+            data,
+            character_map: Some(character_map),
+            strip_shifted_space: false,
+        }
+    }
+
+    /// Create a PETSCII string from a byte slice
+    ///
+    /// The length is the length of the slice and the rest of the data
+    /// is zeros.  A slice longer than L is an error.  The TryFrom
+    /// conversion from a slice does the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// let ps = PetsciiString::<16>::try_from_slice(b"GAME").expect("Error creating string");
+    /// assert_eq!(ps.as_ref(), b"GAME");
+    ///
+    /// assert!(PetsciiString::<2>::try_from_slice(b"GAME").is_err());
+    /// ```
+    pub fn try_from_slice(s: &[u8]) -> std::result::Result<Self, crate::error::Error> {
+        if s.len() > L {
+            return Err(crate::error::Error::new(crate::error::ErrorKind::Message(
+                format!("{} bytes don't fit in a {} byte string", s.len(), L),
+            )));
+        }
+
+        let mut data = [0; L];
+        data[..s.len()].copy_from_slice(s);
+        Ok(PetsciiString::new(s.len() as u32, data))
+    }
+
+    /// Get the length of the Petscii string
+    ///
+    /// This is the declared length, which counts control codes and
+    /// padding as well as the characters.  Use byte_len, char_count
+    /// or capacity for the length you mean.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// let ps = PetsciiString::new(3, [0x41, 0x42, 0x43]);
+    ///
+    /// #[allow(deprecated)]
+    /// let len = ps.len();
+    /// assert_eq!(len, 3);
+    /// ```
+    #[deprecated(note = "Use byte_len, char_count or capacity, which say which length they are")]
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// The number of bytes in the string
+    ///
+    /// Control codes and padding are counted.  A declared length past
+    /// the end of the data is cut to the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// // RED, then "HI" padded with shifted spaces
+    /// let ps = PetsciiString::new(5, [0x1c, 0x48, 0x49, 0xa0, 0xa0]);
+    ///
+    /// assert_eq!(ps.byte_len(), 5);
+    /// assert_eq!(PetsciiString::new(6, [0x41, 0x42]).byte_len(), 2);
+    /// ```
+    pub fn byte_len(&self) -> usize {
+        (self.len as usize).min(L)
+    }
+
+    /// The number of characters the string decodes to
+    ///
+    /// Control codes don't decode to characters, and neither does
+    /// shifted space padding when strip_shifted_space is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{PetsciiConfig, PetsciiString},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut ps = PetsciiString::new_with_config(5, [0x1c, 0x48, 0x49, 0xa0, 0xa0], &petscii);
+    /// ps.strip_shifted_space = true;
+    ///
+    /// assert_eq!(ps.char_count(), 2);
+    /// ```
+    pub fn char_count(&self) -> usize {
+        String::from(self).chars().count()
+    }
+
+    /// The number of bytes the string can hold, L
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// let ps = PetsciiString::<16>::try_from_slice(b"GAME").expect("Error creating string");
+    ///
+    /// assert_eq!(ps.capacity(), 16);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        L
+    }
+
+    /// Copy the string into a string of another width
+    ///
+    /// With a padding byte, the old padding (shifted spaces and zeros)
+    /// is removed and the name is padded to the new width with the
+    /// byte, the way a name is re-packed from one field into another.
+    /// Without one, the bytes are copied as they are and the length
+    /// stays the same.  A string that's still too long is cut to fit
+    /// or is an error, depending on overflow.
+    ///
+    /// The character map and strip_shifted_space are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     cbm::{DISK_PADDING, TAPE_PADDING},
+    ///     petscii::{Overflow, PetsciiString},
+    /// };
+    ///
+    /// let name = PetsciiString::<16>::from_byte_slice_strip_shifted_space(
+    ///     b"GAME\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0\xa0",
+    /// );
+    ///
+    /// let comment = name
+    ///     .resize::<24>(Some(TAPE_PADDING), Overflow::Error)
+    ///     .expect("Error resizing name");
+    /// assert_eq!(comment.as_ref(), b"GAME                    ");
+    ///
+    /// let short = name.resize::<2>(Some(DISK_PADDING), Overflow::Truncate).unwrap();
+    /// assert_eq!(short.as_ref(), b"GA");
+    /// assert!(name.resize::<2>(None, Overflow::Error).is_err());
+    /// ```
+    pub fn resize<const M: usize>(
+        &self,
+        padding: Option<u8>,
+        overflow: Overflow,
+    ) -> std::result::Result<PetsciiString<'a, M>, crate::error::Error> {
+        let bytes = match padding {
+            Some(_) => self.as_trimmed_bytes(),
+            None => self.as_ref(),
+        };
+        let bytes = match overflow {
+            Overflow::Truncate => &bytes[..bytes.len().min(M)],
+            Overflow::Error => bytes,
+        };
+
+        let mut resized = match padding {
+            Some(padding) => {
+                PetsciiString::new(M as u32, crate::cbm::write::pad_field(bytes, padding)?)
+            }
+            None => PetsciiString::try_from_slice(bytes)?,
+        };
+        resized.character_map = self.character_map;
+        resized.strip_shifted_space = self.strip_shifted_space;
+        Ok(resized)
+    }
+
+    /// Copy the string into a wider string
+    ///
+    /// The bytes and length are kept, and the new bytes are zeros.
+    /// Widening to a narrower width doesn't compile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// let name = PetsciiString::<16>::try_from_slice(b"GAME").expect("Error creating string");
+    /// let wide: PetsciiString<24> = name.widen();
+    ///
+    /// assert_eq!(wide.as_ref(), b"GAME");
+    /// assert_eq!(wide.capacity(), 24);
+    /// ```
+    pub fn widen<const M: usize>(&self) -> PetsciiString<'a, M> {
+        const { assert!(M >= L, "widen can't make a string narrower") };
+
+        let mut data = [0; M];
+        data[..L].copy_from_slice(&self.data);
+        PetsciiString {
+            len: self.len,
+            data,
+            character_map: self.character_map,
+            strip_shifted_space: self.strip_shifted_space,
+        }
+    }
+
+    /// Return true if the string is empty
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
     ///
-    /// let ps = PetsciiString::new_with_config(6, [0x41, 0x42, 0x43, 0x5c, 0x5e, 0x5f], &config.petscii);
-    /// let mut s: String = String::from(ps);
+    /// let ps = PetsciiString::new(0, []);
     ///
-    /// assert_eq!(s.pop().unwrap(), '←');
-    /// assert_eq!(s.pop().unwrap(), '↑');
-    /// assert_eq!(s.pop().unwrap(), '£');
-    /// assert_eq!(s.pop().unwrap(), 'C');
-    /// assert_eq!(s.pop().unwrap(), 'B');
-    /// assert_eq!(s.pop().unwrap(), 'A');
+    /// assert!(ps.is_empty());
     /// ```
-    fn from(s: PetsciiString<L>) -> String {
-        String::from(&s)
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
-}
 
-impl<'a, const L: usize> From<&PetsciiString<'a, L>> for String {
-    /// Create a String from a reference to a PetsciiString
+    /// Iterate over the bytes of the string by reference
+    ///
+    /// Like the owning iterator, this only covers the first len bytes
+    /// of the data.
     ///
     /// # Examples
     ///
     /// ```
-    /// use forbidden_bands::{
-    ///     petscii::{PetsciiConfig, PetsciiString},
-    ///     Config,
-    ///     Configuration,
-    /// };
-    ///
-    /// let config = PetsciiConfig::load().expect("Error loading config file");
+    /// use forbidden_bands::petscii::PetsciiString;
     ///
-    /// let ps = PetsciiString::new_with_config(6, [0x41, 0x42, 0x43, 0x5c, 0x5e, 0x5f], &config.petscii);
-    /// let mut s: String = String::from(&ps);
+    /// let ps = PetsciiString::new(3, [0x41, 0x42, 0xA0, 0xA0]);
     ///
-    /// assert_eq!(s.pop().unwrap(), '←');
-    /// assert_eq!(s.pop().unwrap(), '↑');
-    /// assert_eq!(s.pop().unwrap(), '£');
-    /// assert_eq!(s.pop().unwrap(), 'C');
-    /// assert_eq!(s.pop().unwrap(), 'B');
-    /// assert_eq!(s.pop().unwrap(), 'A');
+    /// assert_eq!(ps.iter().len(), 3);
+    /// assert_eq!(ps.iter().rposition(|b| *b != 0xA0), Some(1));
     /// ```
-    // TODO: Unicode 13 now has "Legacy Computing Sources"
-    // (Unicode 13 was released around March 10, 2020).
-    fn from(s: &PetsciiString<L>) -> String {
-        let mut attributes = EnumSet::new();
-        let mut shifted = false;
-
-        attributes.insert(CharacterAttributes::Normal);
-        s.into_iter()
-            .filter(|c| !s.strip_shifted_space || (*c != 0xA0))
-            .filter_map(|c| {
-		// TODO: refactor this into another function.
-		//
-		// It's a good opportunity to learn State patterns and
-		// integrate that into this code.
-		match c {
-		    0x0E => {
-			// Switch to lowercase / shifted
-			// This is the "shifted" state on the C64
-			// Unshifted is an uppercase and graphic
-			// character set
-			shifted = true;
-			return None;
-		    },
-		    0x12 => {
-			attributes.remove(CharacterAttributes::Normal);
-			attributes.insert(CharacterAttributes::Reversed);
-			return None;
-		    },
-		    0x8E => {
-			// Switch to uppercase / unshifted
-			// This is the "unshifted" state on the C64
-			// shifted is a lowercase and uppercase
-			// character set (business mode)
-			shifted = false;
-			return None;
-		    },
-		    0x92 => {
-			attributes.remove(CharacterAttributes::Reversed);
-			attributes.insert(CharacterAttributes::Normal);
-			return None;
-		    },
-		    _ => {}
-		}
-
-		let cm = match &s.character_map {
-		    Some(s) => s,
-		    None => { return Some(char::from_u32(c as u32).unwrap()); },
-		};
-
-		// There are three sets of code that are duplicated in
-		// PETSCII
-		// They're duplicated in both the PETSCII unshifted
-		// and shifted character sets.
-		//
-		// 192-223 are duplicates of 96-127
-		// 224-254 are duplicates of 160-190
-		// 255 is a duplicate of 126
-		//
-		// These should probably be explicity added to the
-		// configuration data instead of transformed here.
-		let c = match c {
-		    0..=191 => c,
-		    192..=223 => c - 96,
-		    224..=254 => c - 64,
-		    255 => 126,
-		};
-
-		// Map from PETSCII to screen codes
-		let petscii_to_screen_codes = if !shifted {
-		    &cm.character_set_map.c64_petscii_unshifted_codes_to_screen_codes
-		} else {
-		    &cm.character_set_map.c64_petscii_shifted_codes_to_screen_codes
-		};
-		let key = c.to_string();
-
-		let screen_code_opt: Option<ScreenCodeValue> =
-		    petscii_to_screen_codes
-		    .get(&key)
-		    .and_then(|screen_code_value| {
-			ScreenCodeValue::deserialize(screen_code_value).ok()
-		    });
-
-		// This chaining of None options is tricky.  return
-		// None doesn't always return to the filter_map
-		// context in an closure context, but it does in a
-		// match context
-		let screen_code = match screen_code_opt {
-		    Some(s) => s,
-		    None => return None,
-		};
-
-		// TODO This test may be removed as we implement the full
-		// block character graphics set
-		if screen_code.value > 127 {
-		    panic!("Should not have a screen code greater than 127 before applying reverse video transform");
-		}
-
-		let screen_code_value: u32 =
-		    if attributes.contains(CharacterAttributes::Reversed) {
-			(screen_code.value as u32) + 128
-		    } else {
-			screen_code.value.into()
-		    };
-
-		// Now map from screen codes to Unicode
-		let screen_codes_to_unicode = match screen_code.set {
-		    1 =>
-			&cm.character_set_map.c64_screen_codes_set_1_to_unicode_codes,
-		    2 =>
-			&cm.character_set_map.c64_screen_codes_set_2_to_unicode_codes,
-		    3 =>
-			&cm.character_set_map.c64_screen_codes_set_3_to_unicode_codes,
-		    _ => {
-			panic!("Invalid screen code set");
-		    }
-		};
-
-		let key = screen_code_value.to_string();
-                let d = if screen_codes_to_unicode.contains_key(&key) {
-                    match screen_codes_to_unicode.get(&key).unwrap() {
-                        serde_json::Value::Number(v) => v.as_u64().unwrap() as u32,
-                        _ => 0,
-                    }
-                } else {
-                    c as u32
-                };
-
-                Some(char::from_u32(d).unwrap())
-            })
-            .collect()
+    pub fn iter(&self) -> std::slice::Iter<'_, u8> {
+        self.data[..self.byte_len()].iter()
     }
-}
 
-impl<'a, const L: usize> PetsciiString<'a, L> {
-    /// Create a new Petscii string
+    /// The number of shifted spaces (0xA0) at the end of the string
+    ///
+    /// These are the padding bytes left out of the display when
+    /// strip_shifted_space is set.  Keep the count alongside the
+    /// displayed name to restore the padding when the name is written
+    /// back.
     ///
     /// # Examples
     ///
     /// ```
     /// use forbidden_bands::petscii::PetsciiString;
     ///
-    /// let ps = PetsciiString::new(6, [0x41, 0x42, 0x43]);
+    /// let field = [0x41, 0x42, 0xA0, 0xA0];
+    /// let ps = PetsciiString::<4>::from_byte_slice_strip_shifted_space(&field);
     ///
-    /// assert_eq!(ps.data[0], 0x41);
-    /// assert_eq!(ps.data[1], 0x42);
-    /// assert_eq!(ps.data[2], 0x43);
+    /// assert_eq!(ps.padding_len(), 2);
     /// ```
-    pub fn new(len: u32, data: [u8; L]) -> Self {
-        PetsciiString {
-            len,
-            data,
-            character_map: None,
-            strip_shifted_space: false,
-        }
+    pub fn padding_len(&self) -> usize {
+        self.iter().rev().take_while(|b| **b == 0xA0).count()
     }
 
-    /// Create a new PETSCII string with a given character map
+    /// True if the strings are the same apart from the case of their
+    /// letters
+    ///
+    /// Letters typed in the lowercase character set are stored as the
+    /// same codes as uppercase letters in the uppercase set, and the
+    /// shifted letters (0x61-0x7A and 0xC1-0xDA) are the other case.
+    /// Those are folded together, and shift codes (0x0E and 0x8E) are
+    /// ignored.
     ///
     /// # Examples
     ///
     /// ```
-    /// use forbidden_bands::{
-    ///     petscii::{PetsciiConfig, PetsciiString},
-    ///     Config,
-    ///     Configuration,
-    /// };
+    /// use forbidden_bands::petscii::PetsciiString;
     ///
-    /// let config = PetsciiConfig::load().expect("Error loading config");
-    /// let ps = PetsciiString::new(6, [0x41, 0x42, 0x43]);
+    /// let upper = PetsciiString::new(4, [0x47, 0x41, 0x4D, 0x45]);
+    /// let shifted = PetsciiString::new(4, [0xC7, 0xC1, 0xCD, 0xC5]);
+    /// let other = PetsciiString::new(4, [0x47, 0x41, 0x4D, 0x53]);
     ///
-    /// assert_eq!(ps.data[0], 0x41);
-    /// assert_eq!(ps.data[1], 0x42);
-    /// assert_eq!(ps.data[2], 0x43);
+    /// assert!(upper.eq_ignore_case(&shifted));
+    /// assert!(!upper.eq_ignore_case(&other));
     /// ```
-    pub fn new_with_config(len: u32, data: [u8; L], character_map: &'a SystemConfig) -> Self {
-        PetsciiString {
-            len,
-            data,
-            character_map: Some(character_map),
-            strip_shifted_space: false,
-        }
+    pub fn eq_ignore_case<const M: usize>(&self, other: &PetsciiString<'_, M>) -> bool {
+        self.as_petscii_str().eq_ignore_case(other.as_petscii_str())
     }
 
-    /// Get the length of the Petscii string
+    /// True if the strings are the same apart from shifted space
+    /// padding at the end
     ///
-    /// TODO: More details on what length means
-    /// TODO: Add example about getting number of characters
+    /// CBM DOS pads file and disk names to their field width with
+    /// shifted spaces (0xA0), so "GAME" and a padded "GAME" name the
+    /// same file.
     ///
     /// # Examples
     ///
     /// ```
     /// use forbidden_bands::petscii::PetsciiString;
     ///
-    /// let ps = PetsciiString::new(3, [0x41, 0x42, 0x43]);
+    /// let name = PetsciiString::new(4, [0x47, 0x41, 0x4D, 0x45]);
+    /// let field = [0x47, 0x41, 0x4D, 0x45, 0xA0, 0xA0, 0xA0, 0xA0];
+    /// let padded = PetsciiString::<8>::from_byte_slice_strip_shifted_space(&field);
     ///
-    /// assert_eq!(ps.len(), 3);
+    /// assert!(name.eq_ignore_padding(&padded));
+    /// assert_ne!(name.byte_len(), padded.byte_len());
     /// ```
-    pub fn len(&self) -> usize {
-        self.len as usize
+    pub fn eq_ignore_padding<const M: usize>(&self, other: &PetsciiString<'_, M>) -> bool {
+        self.as_petscii_str()
+            .eq_ignore_padding(other.as_petscii_str())
     }
 
-    /// Return true if the string is empty
+    /// True if the string matches a CBM DOS filename pattern, like
+    /// the "GAME*" in LOAD"GAME*",8
+    ///
+    /// See cbm::matches_pattern for the rules.
+    ///
     /// # Examples
     ///
     /// ```
     /// use forbidden_bands::petscii::PetsciiString;
     ///
-    /// let ps = PetsciiString::new(0, []);
+    /// let field = [0x47, 0x41, 0x4D, 0x45, 0xA0, 0xA0];
+    /// let name = PetsciiString::<6>::from_byte_slice_strip_shifted_space(&field);
     ///
-    /// assert!(ps.is_empty());
+    /// assert!(name.matches_cbm_pattern(b"GAME*"));
+    /// assert!(name.matches_cbm_pattern(b"?AME"));
+    /// assert!(!name.matches_cbm_pattern(b"GAMES"));
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.len == 0
+    pub fn matches_cbm_pattern(&self, pattern: &[u8]) -> bool {
+        self.as_petscii_str().matches_cbm_pattern(pattern)
     }
 
-    /// This function is the same as the From implementation for byte
-    /// slices but it strips any shifted spaces (0xA0) from the end.
+    /// This function copies the bytes like the TryFrom implementation
+    /// for byte slices and [PetsciiString::try_from_slice] but it
+    /// strips any shifted spaces (0xA0) from the end.
     ///
-    /// Shifted spaces are used to pad out filenames and disk namss in
+    /// Shifted spaces are used to pad out filenames and disk names in
     /// CBM DOS
-    pub fn from_byte_slice_strip_shifted_space(s: &'a [u8]) -> PetsciiString<L> {
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice is longer than L.  TryFrom returns an error
+    /// instead.
+    pub fn from_byte_slice_strip_shifted_space(s: &'a [u8]) -> PetsciiString<'a, L> {
         let mut bytes: [u8; L] = [0; L];
         if s.len() > L {
             panic!("u8 slice is too large");
@@ -748,6 +1815,39 @@ impl<'a, const L: usize> PetsciiString<'a, L> {
         }
     }
 
+    /// Encode a string with the configuration from [Config::current],
+    /// keeping the first L bytes
+    ///
+    /// A string cut short can end in the lowercase character set or
+    /// reverse video without the codes that switch them off.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current configuration has no PETSCII tables.
+    /// The TryFrom conversion returns an error instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// let ps = PetsciiString::<2>::from_str_truncated("GAME");
+    ///
+    /// assert_eq!(ps.as_ref(), b"GA");
+    /// ```
+    pub fn from_str_truncated(s: &str) -> PetsciiString<'a, L> {
+        let config = Config::current().expect("Error loading config");
+        let petscii = config
+            .cached_system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+        let bytes = unicode_to_petscii_bytes(s, &petscii.character_set_map);
+
+        let len = bytes.len().min(L);
+        let mut data = [0; L];
+        data[..len].copy_from_slice(&bytes[..len]);
+        PetsciiString::new(len as u32, data)
+    }
+
     /// Create a PetsciiString from a string slice
     ///
     /// I think I'm going to have to decide on what to do about
@@ -759,7 +1859,7 @@ impl<'a, const L: usize> PetsciiString<'a, L> {
     pub fn from_str_with_config(s: &str, character_map: &'a SystemConfig) -> PetsciiString<'a, L> {
         let mut final_bytes: [u8; L] = [0; L];
 
-        let bytes = unicode_to_petscii_bytes(s);
+        let bytes = unicode_to_petscii_bytes(s, &character_map.character_set_map);
 
         if bytes.len() > L {
             panic!("u8 vector is too large");
@@ -809,91 +1909,122 @@ mod tests {
     use std::fmt::Write;
 
     use crate::{
-        petscii::{PetsciiConfig, PetsciiString, CONFIG},
-        Config, Configuration,
+        manager::ConfigOverrideGuard,
+        petscii::{
+            default_code_aliases, petscii_to_screen_code, screen_code_to_petscii,
+            screen_code_to_unicode, unicode_to_screen_code, Overflow, PetsciiCode,
+            PetsciiCodeValue, PetsciiConfig, PetsciiMismatch, PetsciiString, ScreenCode, ScreenSet,
+            CONFIG,
+        },
+        CharacterSet, Config, Configuration, DecodeOptions, DecodeTarget, SystemConfig,
+        UnicodeVersion,
     };
+    use serde_json::json;
 
     // #[cfg(feature = "external-json")]
     // use crate::load_config_from_file;
 
     /// Test loading the configuration works
     ///
-    /// There are two tricky problems with testing the config and
-    /// static RWLock.  First, tests may be run out-of-order.
-    /// Normally they are run alphabetically, but I don't want to play
-    /// Yellow Pages games with test names.  I'm sure there are test
-    /// crates out there that augment the test framework with
-    /// deterministic ordering, but it should be in the core Rust
-    /// library.
-    ///
-    /// Clearing the config in this test and resetting it works
-    /// around that issue.
-    ///
-    /// The other issue is that tests may be run concurrently in
-    /// future versions of Rust or custom configurations.
-    ///
-    /// In this case, there is a small window where a newer config may
-    /// be replaced by an older config.
-    ///
-    /// We have version strings in configurations.  We could add a new
-    /// runtime version counter and only accept configurations that
-    /// match the expected version or a greater version.
-    ///
-    /// But that's not implemented yet.  If you are hit by a
-    /// concurrency bug in tests in the future, I'm sorry.
+    /// Tests may run concurrently, so this doesn't clear the cached
+    /// configuration.  Once loaded the cache is never emptied, so it
+    /// can be checked without racing other tests.
     #[test]
     fn petscii_load_config_works() {
-        // This test creates a race condition, where another test
-        // may try loading config while this test is running.
-        //
-        // Since tests can be run out-of-order we can't assume that
-        // the configuration is uninitialized.
-        //
-        // This test function acquires a write-lock for the duration of the test
-        // Then it saves the old config, replacing it with None.
-        // It tests this default value
-        // Then it calls load_config normally, tests that it was
-        // successful, and then swaps in the original value.
-        let mut saved_config: Option<PetsciiConfig> = None;
-
-        // Just read from saved_config so we don't get unused
-        // assignment warnings
-        // I had an ignore(unused_assignments) which will be a hard
-        // error soon!  For now just test it to prevent an unused
-        // assignments clippy warning.
-        assert!(saved_config.is_none());
-
-        {
-            let mut lock_res = CONFIG
-                .write()
-                .expect("Should be able to acquire config lock");
-            // *lock_res = Some(config);
-            saved_config = lock_res.take();
-        }
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
 
-        {
-            // Now test that a "first" read of the config fails.
-            let binding = CONFIG.read().expect("Should be able to get reader lock");
-            // Reading an unloaded config should fail
-            assert!(binding.as_ref().is_none());
-        }
+        let binding = CONFIG.read().expect("Should be able to get reader lock");
+        let cached = binding.as_ref().expect("The config should be cached");
+        assert_eq!(cached.version, petscii.character_set_map.version);
+    }
 
-        // Now call load_config and test for a good result
-        let config_result = PetsciiConfig::load();
-        assert!(config_result.is_ok());
+    /// Snapshots of the configuration outlive replacing it
+    #[test]
+    fn petscii_config_snapshots_outlive_overrides() {
+        let petscii = {
+            let _guard = ConfigOverrideGuard::new(Config::load().unwrap());
+            let snapshot = Config::current().expect("Error loading config");
+            snapshot.system::<SystemConfig>().unwrap()
+        };
 
-        // Now we should have a Some value in the Option
-        {
-            let binding = CONFIG.read().expect("Should be able to get reader lock");
-            // Reading an loaded config should work
-            assert!(binding.as_ref().is_some());
-        }
+        let ps = PetsciiString::new_with_config(3, [0x41, 0x42, 0x43], &petscii);
+        assert_eq!(String::from(ps), "ABC");
+    }
+
+    /// Strings encoded without a configuration use the override
+    #[test]
+    fn petscii_string_from_str_uses_current_config() {
+        // Only lossless-escape has a character for 0x20
+        let escaped = "\u{e020}";
+        assert!(PetsciiString::<4>::try_from(escaped).unwrap().is_empty());
+
+        let _guard = ConfigOverrideGuard::new(Config::load_named("lossless-escape").unwrap());
+        assert_eq!(
+            PetsciiString::<4>::try_from(escaped).unwrap().as_ref(),
+            [0x20]
+        );
+
+        // Too long for the string, or no PETSCII tables
+        assert!(PetsciiString::<1>::try_from("AB").is_err());
+        assert_eq!(PetsciiString::<1>::from_str_truncated("AB").as_ref(), b"A");
+        let mut config = Config::load().unwrap();
+        config.systems.clear();
+        let _guard = ConfigOverrideGuard::new(config);
+        assert!(PetsciiString::<4>::try_from("AB").is_err());
+    }
+
+    #[test]
+    fn petscii_string_std_traits_work() {
+        use std::{borrow::Borrow, collections::BTreeSet};
+
+        let empty = PetsciiString::<16>::default();
+        assert!(empty.is_empty());
+        assert!(empty.as_ref().is_empty());
+
+        let ps = PetsciiString::<16>::try_from(b"DISK".to_vec()).unwrap();
+        assert_eq!(ps.byte_len(), 4);
+        assert_eq!(ps.as_ref(), b"DISK");
+        assert!(PetsciiString::<3>::try_from(b"DISK".to_vec()).is_err());
+
+        // Slices shorter than L are fine, longer ones are errors
+        let game = PetsciiString::<16>::try_from(&b"GAME"[..]).unwrap();
+        assert_eq!(game.as_ref(), b"GAME");
+        assert!(PetsciiString::<3>::try_from(&b"GAME"[..]).is_err());
+
+        // Generic byte code sees only the first len bytes
+        let names: BTreeSet<Vec<u8>> = [b"DISK".to_vec()].into();
+        let borrowed: &[u8] = ps.borrow();
+        assert!(names.contains(borrowed));
+
+        // A length past the end of the data is cut to the data
+        assert_eq!(PetsciiString::new(6, [0x41, 0x42]).as_ref(), [0x41, 0x42]);
+    }
+
+    #[test]
+    fn petscii_string_resize_repacks_names() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let field = [b"DEMO".as_slice(), &[0xA0; 12]].concat();
+        let name =
+            PetsciiString::<16>::from_byte_slice_strip_shifted_space_with_config(&field, &petscii);
+
+        let comment = name.resize::<24>(Some(0xA0), Overflow::Error).unwrap();
+        assert_eq!(comment.byte_len(), 24);
+        assert_eq!(String::from(&comment), "DEMO");
 
-        // Now swap back in the original value
-        let mut lock_res = CONFIG
-            .write()
-            .expect("Should be able to acquire config lock");
-        *lock_res = saved_config.take();
+        let back = comment.resize::<16>(Some(0xA0), Overflow::Error).unwrap();
+        assert_eq!(back.as_ref(), field);
+
+        // Without padding the bytes and length are kept as they are
+        let unpadded = name.resize::<20>(None, Overflow::Error).unwrap();
+        assert_eq!(unpadded.as_ref(), field);
+        assert_eq!(
+            name.resize::<3>(None, Overflow::Truncate).unwrap().as_ref(),
+            b"DEM"
+        );
+        assert_eq!(name.widen::<16>().as_ref(), field);
     }
 
     #[test]
@@ -906,11 +2037,11 @@ mod tests {
     #[test]
     fn petscii_with_config_works() {
         let config = PetsciiConfig::load().expect("Error loading config file");
-        let ps = PetsciiString::new_with_config(
-            6,
-            [0x41, 0x42, 0x43, 0x5c, 0x5e, 0x5f],
-            &config.petscii,
-        );
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(6, [0x41, 0x42, 0x43, 0x5c, 0x5e, 0x5f], &petscii);
         let mut s: String = String::from(ps);
         assert_eq!(s.pop().unwrap(), '←');
         assert_eq!(s.pop().unwrap(), '↑');
@@ -929,7 +2060,11 @@ mod tests {
                 panic!("Error loading config file: {e}");
             }
         };
-        let ps = PetsciiString::new_with_config(2, [0x41, 0xb2], &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(2, [0x41, 0xb2], &petscii);
         let mut s: String = String::from(ps);
         assert_eq!(s.pop().unwrap(), '┬');
         assert_eq!(s.pop().unwrap(), 'A');
@@ -948,24 +2083,28 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn petscii_len_unfilled_works() {
         let ps = PetsciiString::new(6, [0x41, 0x42, 0x43]);
 
         assert_eq!(ps.len(), 6);
+        // byte_len counts the bytes there are
+        assert_eq!(ps.byte_len(), 3);
+        assert_eq!(ps.capacity(), 3);
     }
 
     #[test]
     fn petscii_len_7bit_characters_works() {
         let ps = PetsciiString::new(6, [0x41, 0x42, 0x43, 0x41, 0x42, 0x43]);
 
-        assert_eq!(ps.len(), 6);
+        assert_eq!(ps.byte_len(), 6);
     }
 
     #[test]
     fn petscii_len_8bit_characters_works() {
         let ps = PetsciiString::new(7, [0xa5, 0x74, 0x67, 0x7d, 0x68, 0x79, 0xa7]);
 
-        assert_eq!(ps.len(), 7);
+        assert_eq!(ps.byte_len(), 7);
     }
 
     #[test]
@@ -984,26 +2123,18 @@ mod tests {
             PetsciiConfig::load_from_file(&config_fn).expect("Error loading config file")
         };
 
-        // This should be called at a higher level than when creating
-        // strings usually.  Possibly only once at library
-        // initialization.
-        {
-            let mut lock_res = crate::CONFIG
-                .write()
-                .expect("Should be able to acquire config lock");
-            *lock_res = Some(config);
-        }
+        // Programs would call Config::set_current once at
+        // initialization.  Tests override the config on their own
+        // thread so they don't change it for other tests.
+        let _guard = ConfigOverrideGuard::new(config);
 
-        let binding = crate::CONFIG
-            .read()
-            .expect("Should be able to get reader lock");
-        let config = binding.as_ref().unwrap();
+        let config = Config::current().expect("Error loading config");
 
-        let ps = PetsciiString::new_with_config(
-            6,
-            [0x74, 0x67, 0x62, 0x7d, 0x68, 0x79],
-            &config.petscii,
-        );
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(6, [0x74, 0x67, 0x62, 0x7d, 0x68, 0x79], &petscii);
         let s: String = String::from(ps);
 
         // All six charactes are mapped to 32-bit unicode characters
@@ -1037,13 +2168,17 @@ mod tests {
             129913, 129913, 129913, 9496, 13, 10,
         ];
 
-        let ps = PetsciiString::new_with_config(61, hello_world_data, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(61, hello_world_data, &petscii);
 
         let mut string_buf = String::new();
 
         write!(string_buf, "{}", ps).unwrap();
 
-        let bytes: Vec<u32> = string_buf.chars().map(|c| u32::from(c)).collect();
+        let bytes: Vec<u32> = string_buf.chars().map(u32::from).collect();
 
         assert_eq!(Vec::from(expected_unicode), bytes);
     }
@@ -1074,9 +2209,13 @@ mod tests {
             PetsciiConfig::load_from_file(&config_fn).expect("Error loading config file")
         };
 
-        let ps = PetsciiString::new_with_config(28, data, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
 
-        assert_eq!(ps.len(), 28);
+        let ps = PetsciiString::new_with_config(28, data, &petscii);
+
+        assert_eq!(ps.byte_len(), 28);
 
         let s: String = String::from(ps);
         let expected = "abcdefghijklmnopqrstuvwxyz";
@@ -1108,10 +2247,14 @@ mod tests {
             PetsciiConfig::load_from_file(&config_fn).expect("Error loading config file")
         };
 
-        let ps = PetsciiString::new_with_config(1, data, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(1, data, &petscii);
 
         let s: String = String::from(ps);
-        let c = s.chars().nth(0).unwrap();
+        let c = s.chars().next().unwrap();
         let expected: char = char::from_u32(0x2583).unwrap();
 
         assert_eq!(c, expected);
@@ -1173,7 +2316,11 @@ mod tests {
 
         let config = PetsciiConfig::load().expect("Error loading config file");
 
-        let ps = PetsciiString::new_with_config(4, data, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(4, data, &petscii);
         let s: String = String::from(ps);
         let expected = "♠♥♣♦";
 
@@ -1190,7 +2337,11 @@ mod tests {
         let data: [u8; 4] = [0xc1, 0xd3, 0xd8, 0xda];
 
         let config = PetsciiConfig::load().expect("Error loading config");
-        let ps = PetsciiString::new_with_config(4, data, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(4, data, &petscii);
         let s: String = String::from(ps);
         let expected = "♠♥♣♦";
 
@@ -1207,7 +2358,11 @@ mod tests {
         let data: [u8; 6] = [0x12, 0x61, 0x73, 0x78, 0x7a, 0x92];
 
         let config = PetsciiConfig::load().expect("Error loading config");
-        let ps = PetsciiString::new_with_config(6, data, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(6, data, &petscii);
         let s: String = String::from(ps);
         let expected = "♤♡♧♢";
 
@@ -1224,7 +2379,11 @@ mod tests {
         let data: [u8; 6] = [0x12, 0xc1, 0xd3, 0xd8, 0xda, 0x92];
 
         let config = PetsciiConfig::load().expect("Error loading config");
-        let ps = PetsciiString::new_with_config(6, data, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(6, data, &petscii);
         let s: String = String::from(ps);
         let expected = "♤♡♧♢";
 
@@ -1241,7 +2400,11 @@ mod tests {
             PetsciiConfig::load_from_file(&config_fn).expect("Error loading config file")
         };
 
-        let ps = PetsciiString::new_with_config(3, [0x41, 0x42, 0x43], &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::new_with_config(3, [0x41, 0x42, 0x43], &petscii);
 
         let mut iter = ps.into_iter();
 
@@ -1266,9 +2429,13 @@ mod tests {
 
         let config = PetsciiConfig::load().expect("Error loading config");
 
-        let ps = PetsciiString::<26>::from_str_with_config(&s, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
+
+        let ps = PetsciiString::<26>::from_str_with_config(&s, &petscii);
 
-        assert_eq!(ps.len(), 26);
+        assert_eq!(ps.byte_len(), 26);
         assert_eq!(ps.data, expected);
 
         let s: String = String::from(ps);
@@ -1308,13 +2475,346 @@ mod tests {
 
         let config = PetsciiConfig::load().expect("Error loading config");
 
-        let ps = PetsciiString::<28>::from_str_with_config(&s, &config.petscii);
+        let petscii = config
+            .system::<SystemConfig>()
+            .expect("Error loading PETSCII config");
 
-        assert_eq!(ps.len(), 28);
+        let ps = PetsciiString::<28>::from_str_with_config(&s, &petscii);
+
+        assert_eq!(ps.byte_len(), 28);
         assert_eq!(ps.data, expected);
 
         let s: String = String::from(ps);
 
         assert_eq!(s, lowercase);
     }
+
+    #[test]
+    fn check_bijectivity_finds_shared_characters() {
+        let config = Config::load().expect("Error loading config");
+        let report = config.check_bijectivity().unwrap();
+
+        // PETSCII 0x7E and 0xA6 both decode to a medium shade
+        assert!(report.petscii_mismatches.contains(&PetsciiMismatch {
+            shifted: false,
+            code: 0x7e,
+            character: '▒',
+            encoded: vec![0xa6],
+        }));
+        assert!(!report.is_bijective());
+    }
+
+    #[test]
+    fn check_bijectivity_lossless_escape_has_fewer_mismatches() {
+        let default_report = Config::load().unwrap().check_bijectivity().unwrap();
+        let lossless_report = Config::load_named("lossless-escape")
+            .unwrap()
+            .check_bijectivity()
+            .unwrap();
+
+        assert!(lossless_report.petscii_mismatches.len() < default_report.petscii_mismatches.len());
+    }
+
+    #[test]
+    fn check_bijectivity_finds_edited_map() {
+        let config = Config::load().expect("Error loading config");
+        let mut petscii = config.system::<SystemConfig>().unwrap();
+
+        // Map A to the screen code for B
+        let b = petscii.character_set_map.unicode_codes_to_c64_screen_codes["66"].clone();
+        petscii
+            .character_set_map
+            .unicode_codes_to_c64_screen_codes
+            .insert(String::from("65"), b);
+
+        let report = petscii.check_bijectivity();
+
+        assert!(report
+            .unicode_mismatches
+            .iter()
+            .any(|m| m.character == 'A' && m.decoded == "B"));
+        assert!(report
+            .petscii_mismatches
+            .iter()
+            .any(|m| !m.shifted && m.code == 0x41 && m.encoded == [0x42]));
+    }
+
+    #[test]
+    fn petscii_duplicate_codes_match_tables() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let map = &petscii.character_set_map;
+
+        assert_eq!(map.c64_petscii_code_aliases.len(), 64);
+        for (code, canonical) in &map.c64_petscii_code_aliases {
+            let code = PetsciiCode(code.parse().unwrap());
+            let canonical = PetsciiCode(canonical.as_u64().unwrap() as u8);
+            for shifted in [false, true] {
+                assert!(code.to_screen_code(shifted, map).is_some(), "{code:?}");
+                assert_eq!(
+                    code.to_screen_code(shifted, map),
+                    canonical.to_screen_code(shifted, map)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn petscii_code_aliases_match_default() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        assert_eq!(
+            petscii.character_set_map.c64_petscii_code_aliases,
+            default_code_aliases()
+        );
+
+        // A configuration without the table gets the C64 duplicates
+        let mut value = serde_json::to_value(&petscii.character_set_map).unwrap();
+        value
+            .as_object_mut()
+            .unwrap()
+            .remove("c64_petscii_code_aliases");
+        let map: PetsciiConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(map.canonicalize_code(0xFF), 0x7E);
+    }
+
+    #[test]
+    fn petscii_tables_cover_every_code() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let map = &petscii.character_set_map;
+
+        for code in (0..=255u8).map(PetsciiCode).filter(|c| !c.is_control()) {
+            assert!(code.to_screen_code(false, map).is_some(), "{code:?}");
+            assert!(code.to_screen_code(true, map).is_some(), "{code:?}");
+        }
+
+        // Control codes are left out, except line feed and carriage
+        // return in the virtual set 3
+        for code in (0..=255u8).map(PetsciiCode).filter(|c| c.is_control()) {
+            let expected = matches!(code.0, 0x0A | 0x0D);
+            assert_eq!(
+                code.to_screen_code(false, map).is_some(),
+                expected,
+                "{code:?}"
+            );
+            assert_eq!(
+                code.to_screen_code(true, map).is_some(),
+                expected,
+                "{code:?}"
+            );
+        }
+
+        // Uppercase and graphics in the lowercase character set
+        assert_eq!(
+            petscii.decode(&[0x0E, 0x41, 0x61, 0xC1, 0x5B, 0xA6, 0x40]),
+            "aAA[▒@"
+        );
+    }
+
+    #[test]
+    fn petscii_duplicate_codes_are_data() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let mut petscii = config.system::<SystemConfig>().unwrap();
+
+        // Make 0xC1 a duplicate of A instead of the spade
+        petscii
+            .character_set_map
+            .c64_petscii_unshifted_codes_to_screen_codes
+            .insert(String::from("193"), serde_json::json!([1, 1]));
+
+        let ps = PetsciiString::new_with_config(2, [0xC1, 0xC2], &petscii);
+        assert_eq!(String::from(ps), "A🭲");
+    }
+
+    #[test]
+    fn petscii_into_iter_double_ended_works() {
+        let ps = PetsciiString::new(3, [0x41, 0x42, 0x43, 0x44]);
+
+        let mut iter = ps.into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(0x43));
+        assert_eq!(iter.next(), Some(0x41));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back(), Some(0x42));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        let reversed: Vec<u8> = ps.into_iter().rev().collect();
+        assert_eq!(reversed, vec![0x43, 0x42, 0x41]);
+        assert_eq!(
+            ps.iter().copied().collect::<Vec<u8>>(),
+            vec![0x41, 0x42, 0x43]
+        );
+    }
+
+    #[test]
+    fn petscii_code_types_reject_invalid_values() {
+        assert!(serde_json::from_str::<ScreenCode>("[4, 1]").is_err());
+        assert_eq!(
+            serde_json::from_str::<ScreenCode>("[2, 1]").unwrap(),
+            ScreenCode {
+                set: ScreenSet::Set2,
+                value: 1
+            }
+        );
+
+        let code: PetsciiCodeValue = serde_json::from_str("[1, 65]").unwrap();
+        assert!(code.is_shifted());
+        assert_eq!(code.value, PetsciiCode(0x41));
+        assert_eq!(
+            serde_json::from_value::<PetsciiCodeValue>(serde_json::to_value(code).unwrap())
+                .unwrap(),
+            code
+        );
+    }
+
+    #[test]
+    fn petscii_screen_code_round_trips() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let map = &petscii.character_set_map;
+
+        let screen_code = ScreenCode::from_unicode('♠', map).unwrap();
+        assert_eq!(screen_code.set, ScreenSet::Set1);
+        let petscii_code = screen_code.to_petscii(map).unwrap();
+        assert!(!petscii_code.is_shifted());
+        assert_eq!(
+            petscii_code.value.to_screen_code(false, map),
+            Some(screen_code)
+        );
+        assert!(PetsciiCode(0x93).is_control());
+    }
+
+    #[test]
+    fn petscii_single_character_functions_match_decode() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let map = &petscii.character_set_map;
+
+        for (code, shifted) in [(0x41, false), (0x41, true), (0x5C, false), (0x5A, true)] {
+            let screen_code = petscii_to_screen_code(code, shifted, map).unwrap();
+            let c = screen_code_to_unicode(screen_code, map).unwrap();
+
+            let bytes = if shifted {
+                vec![0x0E, code]
+            } else {
+                vec![code]
+            };
+            assert_eq!(petscii.decode(&bytes), c.to_string());
+
+            let back = unicode_to_screen_code(c, map).unwrap();
+            assert_eq!(
+                screen_code_to_petscii(back, map).map(|code| code.is_shifted()),
+                Some(back.set == ScreenSet::Set2)
+            );
+        }
+    }
+
+    #[test]
+    fn petscii_for_unicode_version_keeps_older_characters() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let map = &petscii.character_set_map;
+
+        let latest = map.for_unicode_version(UnicodeVersion::Unicode16).unwrap();
+        assert_eq!(
+            latest.mapped_characters(),
+            map.mapped_characters(),
+            "The embedded tables don't use anything newer than Unicode 16"
+        );
+
+        let old = map.for_unicode_version(UnicodeVersion::Unicode12).unwrap();
+        assert!(old
+            .mapped_characters()
+            .iter()
+            .all(|c| UnicodeVersion::of(*c) == UnicodeVersion::Unicode12));
+        // Box drawing and block elements are old enough to keep
+        assert!(old.mapped_characters().contains(&'▌'));
+        // The configuration can still encode the newer characters
+        assert!(unicode_to_screen_code('\u{1fb7f}', &old).is_some());
+    }
+
+    #[test]
+    fn petscii_legacy_computing_characters_round_trip() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Including the ones that only exist in reverse video
+        for c in petscii.character_set_map.mapped_characters() {
+            let encoded = petscii.encode(&c.to_string());
+            assert_eq!(
+                petscii.decode(&encoded),
+                c.to_string(),
+                "{c:?} {encoded:x?}"
+            );
+        }
+
+        assert_eq!(petscii.encode("\u{1fb99}"), [0x12, 0x60, 0x92]);
+    }
+
+    #[test]
+    fn petscii_string_eq_ignoring_case_and_padding_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Encoding lowercase adds shift codes around the letters
+        let lower = PetsciiString::<8>::from_str_with_config("game", &petscii);
+        let upper = PetsciiString::<8>::from_str_with_config("GAME", &petscii);
+        assert_ne!(lower.byte_len(), upper.byte_len());
+        assert!(lower.eq_ignore_case(&upper));
+        assert!(!lower.eq_ignore_padding(&upper));
+
+        let field = [0x47, 0x41, 0x4D, 0x45, 0xA0, 0xA0];
+        let padded = PetsciiString::<6>::from_byte_slice_strip_shifted_space(&field);
+        assert!(upper.eq_ignore_padding(&padded));
+        assert!(!upper.eq_ignore_case(&padded));
+    }
+
+    #[test]
+    fn petscii_decode_untrusted_survives_bad_config() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let mut petscii = config.system::<SystemConfig>().unwrap();
+
+        // Reversed screen codes, unknown sets, surrogates and values
+        // past the end of Unicode
+        let map = &mut petscii.character_set_map;
+        for code in 0..=255u8 {
+            let key = code.to_string();
+            map.c64_petscii_unshifted_codes_to_screen_codes
+                .insert(key.clone(), json!([1, 128 + code / 2]));
+            map.c64_petscii_shifted_codes_to_screen_codes
+                .insert(key.clone(), json!([7, code]));
+            map.c64_screen_codes_set_1_to_unicode_codes
+                .insert(key, json!(0xD800 + u32::from(code) * 0x2000));
+        }
+        map.c64_petscii_code_aliases
+            .insert(String::from("65"), json!(-1));
+
+        let bytes: Vec<u8> = (0..=255u8).chain([0x0E, 0x41, 0x12, 0x42]).collect();
+        let decoded = petscii.decode_untrusted(&bytes);
+        assert!(decoded.contains(char::REPLACEMENT_CHARACTER));
+    }
+
+    #[test]
+    fn petscii_decode_with_initial_state_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let options = DecodeOptions::default()
+            .with_initial_shifted(true)
+            .with_initial_reversed(true);
+        // Reverse off and shift out still work
+        let bytes = [0x41, 0x92, 0x41, 0x8e, 0x41];
+        let mut unshifted = vec![0x0e, 0x12];
+        unshifted.extend(bytes);
+        assert_eq!(
+            petscii.decode_with_options(&bytes, &options),
+            petscii.decode(&unshifted)
+        );
+
+        let ascii = options.with_target(DecodeTarget::AsciiApproximation);
+        assert_eq!(petscii.decode_with_options(&[0xb0, 0x41], &ascii), "+a");
+    }
 }
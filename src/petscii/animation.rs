@@ -0,0 +1,431 @@
+//!
+//! Playing PETSCII animations
+//!
+//! PETSCII animations are usually distributed as a stream of
+//! characters and control codes that draws each frame over the last
+//! one, starting each frame by clearing the screen or moving the
+//! cursor home.  Player prints the stream on a virtual terminal and
+//! returns a snapshot of the screen for each frame.
+//!
+//! BBS art was drawn at the speed of the modem, and some of it is
+//! made to be watched as it arrives.  A Timing gives each frame the
+//! time it's complete, from the bytes per second of a modem or a
+//! fixed delay between frames, so a frontend can show the frames on
+//! its own clock.
+//!
+//! With the gif feature, write_gif draws the frames with a character
+//! ROM and writes them as an animated GIF.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+#[cfg(feature = "gif")]
+use std::io::Write;
+use std::time::Duration;
+
+use crate::petscii::{screen::Screen, terminal::PetsciiTerminal};
+#[cfg(feature = "gif")]
+use crate::{
+    error::{Error, ErrorKind},
+    palette::Palette,
+    petscii::bitmap::CharacterRom,
+};
+
+/// Where one frame ends and the next starts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameBoundary {
+    /// Before each clear screen (0x93) or home (0x13) code
+    Home,
+    /// After every this many bytes
+    Bytes(usize),
+}
+
+/// How fast the stream is played
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Timing {
+    /// All at once, every frame at time zero
+    #[default]
+    Instant,
+    /// At the speed of a modem with this many bits per second, like
+    /// 300, 1200 or 2400
+    ///
+    /// Each byte takes ten bits, a start bit, eight data bits and a
+    /// stop bit.  A rate of zero is the same as Instant.
+    Baud(u32),
+    /// With this delay between frames
+    FrameDelay(Duration),
+}
+
+impl Timing {
+    /// The bytes per second a modem sends, or None if the timing
+    /// isn't a modem speed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::animation::Timing;
+    ///
+    /// assert_eq!(Timing::Baud(1200).bytes_per_second(), Some(120));
+    /// assert_eq!(Timing::Instant.bytes_per_second(), None);
+    /// ```
+    pub fn bytes_per_second(&self) -> Option<u32> {
+        match self {
+            Timing::Baud(baud) if *baud > 0 => Some(baud / 10),
+            _ => None,
+        }
+    }
+
+    /// The time the byte at an offset in the stream has arrived
+    ///
+    /// Frontends that print a stream a byte at a time can print each
+    /// byte at this time.  Only modem speeds spread the bytes out,
+    /// with other timings every byte arrives at time zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use forbidden_bands::petscii::animation::Timing;
+    ///
+    /// // 30 bytes a second
+    /// assert_eq!(Timing::Baud(300).byte_time(14), Duration::from_millis(500));
+    /// assert_eq!(Timing::Baud(300).byte_time(29), Duration::from_secs(1));
+    /// assert_eq!(Timing::FrameDelay(Duration::from_secs(1)).byte_time(29), Duration::ZERO);
+    /// ```
+    pub fn byte_time(&self, offset: usize) -> Duration {
+        match self {
+            Timing::Baud(baud) if *baud > 0 => {
+                let bits = (offset as u128 + 1) * 10;
+                let nanos = bits * 1_000_000_000 / u128::from(*baud);
+                Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// The time a frame is complete, from the number of frames
+    /// before it and the offset of the first byte after it
+    pub fn frame_time(&self, index: usize, offset: usize) -> Duration {
+        match self {
+            Timing::FrameDelay(delay) => {
+                delay.saturating_mul(u32::try_from(index).unwrap_or(u32::MAX))
+            }
+            _ => offset
+                .checked_sub(1)
+                .map_or(Duration::ZERO, |last| self.byte_time(last)),
+        }
+    }
+}
+
+/// A snapshot of the screen
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// The screen
+    pub screen: Screen,
+    /// The offset in the stream of the first byte after the frame
+    pub offset: usize,
+    /// The time the frame is complete, from the start of the stream
+    pub time: Duration,
+}
+
+/// Plays a PETSCII stream a frame at a time
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{
+///         animation::{FrameBoundary, Player},
+///         PetsciiConfig,
+///     },
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let frames: Vec<String> = Player::new(b"\x93ONE\x13TWO\x13\x1d\x1d\x1dEE", FrameBoundary::Home)
+///     .map(|frame| frame.screen.to_text(&petscii))
+///     .collect();
+///
+/// assert_eq!(frames, ["ONE", "TWO", "TWOEE"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Player<'a> {
+    terminal: PetsciiTerminal,
+    bytes: &'a [u8],
+    position: usize,
+    boundary: FrameBoundary,
+    timing: Timing,
+    frames: usize,
+}
+
+impl<'a> Player<'a> {
+    /// Create a player that prints onto a blank C64 screen
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current configuration doesn't have PETSCII
+    /// tables, see PetsciiTerminal::with_screen.  Use with_terminal
+    /// and PetsciiTerminal::with_config to get an error instead.
+    pub fn new(bytes: &'a [u8], boundary: FrameBoundary) -> Player<'a> {
+        Player::with_terminal(PetsciiTerminal::new(), bytes, boundary)
+    }
+
+    /// Create a player that prints onto a terminal, like one with a
+    /// screen of another size
+    pub fn with_terminal(
+        terminal: PetsciiTerminal,
+        bytes: &'a [u8],
+        boundary: FrameBoundary,
+    ) -> Player<'a> {
+        Player {
+            terminal,
+            bytes,
+            position: 0,
+            boundary,
+            timing: Timing::Instant,
+            frames: 0,
+        }
+    }
+
+    /// Play the stream with a timing
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use forbidden_bands::petscii::animation::{FrameBoundary, Player, Timing};
+    ///
+    /// // The second frame finishes arriving after 8 bytes at 2400 baud
+    /// let times: Vec<Duration> = Player::new(b"\x93ONE\x93TWO", FrameBoundary::Home)
+    ///     .with_timing(Timing::Baud(2400))
+    ///     .map(|frame| frame.time)
+    ///     .collect();
+    ///
+    /// assert_eq!(times, [Duration::from_nanos(16_666_666), Duration::from_nanos(33_333_333)]);
+    /// ```
+    pub fn with_timing(mut self, timing: Timing) -> Player<'a> {
+        self.timing = timing;
+        self
+    }
+
+    /// The terminal the stream is printed on
+    pub fn terminal(&self) -> &PetsciiTerminal {
+        &self.terminal
+    }
+
+    /// The timing the stream is played with
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+}
+
+impl Iterator for Player<'_> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        let start = self.position;
+        while let Some(b) = self.bytes.get(self.position) {
+            let ends_frame = match self.boundary {
+                FrameBoundary::Home => matches!(b, 0x13 | 0x93) && self.position > start,
+                FrameBoundary::Bytes(len) => self.position - start >= len.max(1),
+            };
+            if ends_frame {
+                break;
+            }
+            self.terminal.print_byte(*b);
+            self.position += 1;
+        }
+
+        if self.position == start {
+            return None;
+        }
+        let time = self.timing.frame_time(self.frames, self.position);
+        self.frames += 1;
+        Some(Frame {
+            screen: self.terminal.screen().clone(),
+            offset: self.position,
+            time,
+        })
+    }
+}
+
+/// Write frames as an animated GIF that loops forever
+///
+/// Each frame is shown for the delay, in hundredths of a second, and
+/// drawn with the character ROM and the colors of the palette.  The
+/// frames must all be the same size.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     palette::Palette,
+///     petscii::{
+///         animation::{write_gif, FrameBoundary, Player},
+///         bitmap::CharacterRom,
+///     },
+/// };
+///
+/// let rom = CharacterRom::from_bytes(&[0x18; CharacterRom::LEN]).expect("The ROM is 4K");
+/// let player = Player::new(b"\x93A\x93B", FrameBoundary::Home);
+///
+/// let mut gif = Vec::new();
+/// write_gif(&mut gif, player, &rom, &Palette::PEPTO, 50).expect("Error writing GIF");
+///
+/// assert!(gif.starts_with(b"GIF89a"));
+/// ```
+#[cfg(feature = "gif")]
+pub fn write_gif<W: Write>(
+    writer: W,
+    frames: impl IntoIterator<Item = Frame>,
+    rom: &CharacterRom,
+    palette: &Palette,
+    delay: u16,
+) -> std::result::Result<(), Error> {
+    write_frames(
+        writer,
+        frames.into_iter().map(|frame| (frame, delay)),
+        rom,
+        palette,
+    )
+}
+
+/// Write frames as an animated GIF that plays at the times of the
+/// frames
+///
+/// Each frame is shown until the time of the next one, and the last
+/// frame for the hold time, in hundredths of a second, before the
+/// animation starts again.  GIF delays are in hundredths of a second,
+/// so the frames are shown at their times rounded down to them.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     palette::Palette,
+///     petscii::{
+///         animation::{write_timed_gif, FrameBoundary, Player, Timing},
+///         bitmap::CharacterRom,
+///     },
+/// };
+///
+/// let rom = CharacterRom::from_bytes(&[0x18; CharacterRom::LEN]).expect("The ROM is 4K");
+/// let player = Player::new(b"READY.\r", FrameBoundary::Bytes(1)).with_timing(Timing::Baud(300));
+///
+/// let mut gif = Vec::new();
+/// write_timed_gif(&mut gif, player, &rom, &Palette::PEPTO, 200).expect("Error writing GIF");
+///
+/// assert!(gif.starts_with(b"GIF89a"));
+/// ```
+#[cfg(feature = "gif")]
+pub fn write_timed_gif<W: Write>(
+    writer: W,
+    frames: impl IntoIterator<Item = Frame>,
+    rom: &CharacterRom,
+    palette: &Palette,
+    hold: u16,
+) -> std::result::Result<(), Error> {
+    let mut frames = frames.into_iter().peekable();
+    let delayed = std::iter::from_fn(|| {
+        let frame = frames.next()?;
+        let delay = match frames.peek() {
+            Some(next) => {
+                // Rounding the times, not the differences, keeps the
+                // rounding from adding up over a long animation
+                let centis =
+                    (next.time.as_millis() / 10).saturating_sub(frame.time.as_millis() / 10);
+                u16::try_from(centis).unwrap_or(u16::MAX)
+            }
+            None => hold,
+        };
+        Some((frame, delay))
+    });
+    write_frames(writer, delayed, rom, palette)
+}
+
+/// Write frames, each with its delay, as an animated GIF
+#[cfg(feature = "gif")]
+fn write_frames<W: Write>(
+    writer: W,
+    frames: impl Iterator<Item = (Frame, u16)>,
+    rom: &CharacterRom,
+    palette: &Palette,
+) -> std::result::Result<(), Error> {
+    let global_palette: Vec<u8> = palette
+        .colors
+        .iter()
+        .flat_map(|rgb| [rgb.r, rgb.g, rgb.b])
+        .collect();
+
+    let mut images = frames
+        .map(|(frame, delay)| (frame.screen.to_indexed_image(rom), delay))
+        .peekable();
+    let Some((first, _)) = images.peek() else {
+        return Err(Error::new(ErrorKind::Message(String::from(
+            "A GIF needs at least one frame",
+        ))));
+    };
+    // The image size comes from the first frame
+    let (Ok(width), Ok(height)) = (u16::try_from(first.width), u16::try_from(first.height)) else {
+        return Err(Error::new(ErrorKind::Message(format!(
+            "A {}x{} image is too large for a GIF",
+            first.width, first.height
+        ))));
+    };
+
+    let mut encoder = gif::Encoder::new(writer, width, height, &global_palette)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    for (image, delay) in images {
+        if (image.width, image.height) != (usize::from(width), usize::from(height)) {
+            return Err(Error::new(ErrorKind::Message(String::from(
+                "The frames of a GIF must be the same size",
+            ))));
+        }
+        let mut frame = gif::Frame::from_indexed_pixels(width, height, image.pixels, None);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::petscii::animation::{FrameBoundary, Player, Timing};
+
+    #[test]
+    fn animation_frames_split_on_bytes() {
+        let frames: Vec<(usize, u8)> = Player::new(b"\x93ABCDE", FrameBoundary::Bytes(2))
+            .map(|frame| (frame.offset, frame.screen.row(0).unwrap()[2].screen_code))
+            .collect();
+
+        assert_eq!(frames, [(2, 0x20), (4, 0x03), (6, 0x03)]);
+    }
+
+    #[test]
+    fn animation_frames_are_timed() {
+        // Three bytes take a tenth of a second at 300 baud
+        let stream = b"\x93AB\x93CD\x93EF";
+        let times = |timing| -> Vec<Duration> {
+            Player::new(stream, FrameBoundary::Home)
+                .with_timing(timing)
+                .map(|frame| frame.time)
+                .collect()
+        };
+
+        assert_eq!(times(Timing::Instant), [Duration::ZERO; 3]);
+        assert_eq!(times(Timing::Baud(0)), [Duration::ZERO; 3]);
+        assert_eq!(
+            times(Timing::Baud(300)),
+            [100, 200, 300].map(Duration::from_millis)
+        );
+        assert_eq!(
+            times(Timing::FrameDelay(Duration::from_millis(250))),
+            [0, 250, 500].map(Duration::from_millis)
+        );
+    }
+}
@@ -0,0 +1,460 @@
+//!
+//! Readers for PETSCII art files
+//!
+//! PETSCII art is distributed in a few formats:
+//!
+//! * SEQ files, a stream of PETSCII characters and control codes
+//!   that draws the picture when it's printed
+//! * Petmate JSON exports, with the screen codes and colors of each
+//!   screen
+//! * Petmate C exports, the same data as C arrays
+//! * .pet files from Marq's PETSCII editor, the screen and color
+//!   memory with a short header
+//!
+//! Each reader builds the screens in the file.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorKind},
+    petscii::{
+        screen::{Charset, Screen},
+        terminal::PetsciiTerminal,
+        Color,
+    },
+    SystemConfig,
+};
+
+/// Print a PETSCII stream onto a screen
+///
+/// Printing starts in the home position with light blue text, the
+/// way the C64 starts.  Printing past the bottom of the screen
+/// scrolls it up.  The screen codes come from the PETSCII tables of
+/// petscii, and it's an error if they aren't valid, see
+/// PetsciiTerminal::with_config.  The screen isn't changed then.
+pub fn print_seq(
+    screen: &mut Screen,
+    bytes: &[u8],
+    petscii: &SystemConfig,
+) -> std::result::Result<(), Error> {
+    let mut terminal = PetsciiTerminal::with_config(screen.clone(), petscii)?;
+    terminal.print(bytes);
+    *screen = terminal.into_screen();
+    Ok(())
+}
+
+/// Read a SEQ art file onto a C64 screen
+///
+/// Returns the same errors as print_seq.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{art::read_seq, Color, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// // Clear the screen, a white H and a reversed red I
+/// let screen = read_seq(&[0x93, 0x05, 0x48, 0x1c, 0x12, 0x49], &petscii)
+///     .expect("Error reading SEQ file");
+///
+/// assert_eq!(screen.screen_codes()[..2], [0x08, 0x89]);
+/// assert_eq!(screen.cell(1, 0).map(|cell| cell.color), Some(Color::Red));
+/// ```
+pub fn read_seq(bytes: &[u8], petscii: &SystemConfig) -> std::result::Result<Screen, Error> {
+    let mut screen = Screen::c64();
+    print_seq(&mut screen, bytes, petscii)?;
+    Ok(screen)
+}
+
+/// The number of cells on a screen
+///
+/// The size comes from the file, so it's an error if the number
+/// doesn't fit in a usize.
+fn screen_len(columns: usize, rows: usize) -> std::result::Result<usize, Error> {
+    columns.checked_mul(rows).ok_or_else(|| {
+        Error::new(ErrorKind::Message(format!(
+            "A {}x{} screen is too large",
+            columns, rows
+        )))
+    })
+}
+
+/// Build a screen from screen codes and color numbers
+///
+/// The screen is only allocated once there's enough data for it, so
+/// a size in a file can't make it larger than the file.
+fn screen_from_memory(
+    columns: usize,
+    rows: usize,
+    screen_codes: &[u8],
+    colors: &[u8],
+) -> std::result::Result<Screen, Error> {
+    let len = screen_len(columns, rows)?;
+    if screen_codes.len() < len || colors.len() < len {
+        return Err(Error::new(ErrorKind::Message(format!(
+            "A {}x{} screen needs {} screen codes and colors, found {} and {}",
+            columns,
+            rows,
+            len,
+            screen_codes.len(),
+            colors.len()
+        ))));
+    }
+
+    let mut screen = Screen::new(columns, rows);
+    for (i, (screen_code, color)) in screen_codes.iter().zip(colors).take(len).enumerate() {
+        screen.set(
+            i % columns,
+            i / columns,
+            *screen_code,
+            Color::from_index(*color),
+        );
+    }
+    Ok(screen)
+}
+
+/// The character set named in a Petmate file
+fn petmate_charset(name: Option<&str>) -> Charset {
+    match name {
+        Some(name) if name.starts_with("lower") => Charset::Lowercase,
+        _ => Charset::Uppercase,
+    }
+}
+
+/// Read a number from a JSON object
+fn json_number(object: &Value, key: &str) -> std::result::Result<u64, Error> {
+    object.get(key).and_then(Value::as_u64).ok_or_else(|| {
+        Error::new(ErrorKind::Message(format!(
+            "Petmate screen is missing {}",
+            key
+        )))
+    })
+}
+
+/// Read a screen dimension from a JSON object
+fn json_size(object: &Value, key: &str) -> std::result::Result<usize, Error> {
+    let size = json_number(object, key)?;
+    usize::try_from(size).map_err(|_| {
+        Error::new(ErrorKind::Message(format!(
+            "Petmate screen {} of {} is too large",
+            key, size
+        )))
+    })
+}
+
+/// Read a list of bytes from a JSON value
+fn json_bytes(value: Option<&Value>) -> Vec<u8> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .map(|v| v.as_u64().unwrap_or(0) as u8)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Read the screens in a Petmate JSON export or .petmate file
+///
+/// JSON exports list the screen codes and colors of each screen in
+/// screencodes and colors arrays.  Petmate's own files have a
+/// framebuf array of rows of cells instead.  Both are read.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::{art::read_petmate_json, screen::Charset, Color};
+///
+/// let json = r#"{
+///   "version": 1,
+///   "framebufs": [{
+///     "width": 2, "height": 1,
+///     "backgroundColor": 0, "borderColor": 14,
+///     "charset": "lower",
+///     "screencodes": [8, 9], "colors": [1, 2]
+///   }]
+/// }"#;
+///
+/// let screens = read_petmate_json(json).expect("Error reading Petmate file");
+///
+/// assert_eq!(screens[0].screen_codes(), vec![8, 9]);
+/// assert_eq!(screens[0].background, Color::Black);
+/// assert_eq!(screens[0].charset, Charset::Lowercase);
+/// ```
+pub fn read_petmate_json(json: &str) -> std::result::Result<Vec<Screen>, Error> {
+    let root: Value = serde_json::from_str(json)?;
+    let framebufs = root
+        .get("framebufs")
+        .and_then(Value::as_array)
+        .ok_or_else(|| Error::new(ErrorKind::Message(String::from("No framebufs in file"))))?;
+
+    framebufs
+        .iter()
+        .map(|framebuf| {
+            let columns = json_size(framebuf, "width")?;
+            let rows = json_size(framebuf, "height")?;
+
+            let (screen_codes, colors) = match framebuf.get("framebuf").and_then(Value::as_array) {
+                Some(cell_rows) => cell_rows
+                    .iter()
+                    .flat_map(|row| row.as_array().cloned().unwrap_or_default())
+                    .map(|cell| {
+                        let code = cell.get("code").and_then(Value::as_u64).unwrap_or(0);
+                        let color = cell.get("color").and_then(Value::as_u64).unwrap_or(0);
+                        (code as u8, color as u8)
+                    })
+                    .unzip(),
+                None => (
+                    json_bytes(framebuf.get("screencodes")),
+                    json_bytes(framebuf.get("colors")),
+                ),
+            };
+
+            let mut screen = screen_from_memory(columns, rows, &screen_codes, &colors)?;
+            screen.background = Color::from_index(json_number(framebuf, "backgroundColor")? as u8);
+            screen.border = Color::from_index(json_number(framebuf, "borderColor")? as u8);
+            screen.charset = petmate_charset(framebuf.get("charset").and_then(Value::as_str));
+            Ok(screen)
+        })
+        .collect()
+}
+
+/// Read the screens in a Petmate C export
+///
+/// Each screen is an array holding the border color, the background
+/// color, the screen codes and the colors.  The size and character
+/// set come from the META comment, and default to a 40 by 25
+/// uppercase screen.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::{art::read_petmate_c, Color};
+///
+/// let source = "unsigned char frame0000[]={// border,bg,chars,colors
+/// 14,6,
+/// 8,9,
+/// 1,1,
+/// };
+/// // META: 2 1 C64 upper
+/// ";
+///
+/// let screens = read_petmate_c(source).expect("Error reading C export");
+///
+/// assert_eq!(screens[0].screen_codes(), vec![8, 9]);
+/// assert_eq!(screens[0].border, Color::LightBlue);
+/// ```
+pub fn read_petmate_c(source: &str) -> std::result::Result<Vec<Screen>, Error> {
+    let mut columns = 40;
+    let mut rows = 25;
+    let mut charset = Charset::Uppercase;
+    let mut arrays: Vec<Vec<u8>> = Vec::new();
+    let mut current: Option<Vec<u8>> = None;
+
+    for line in source.lines() {
+        let (code, comment) = match line.find("//") {
+            Some(position) => (&line[..position], Some(&line[position + 2..])),
+            None => (line, None),
+        };
+
+        if let Some(meta) = comment.and_then(|comment| comment.trim().strip_prefix("META:")) {
+            let fields: Vec<&str> = meta.split_whitespace().collect();
+            if let [width, height, ..] = fields[..] {
+                columns = width.parse().unwrap_or(columns);
+                rows = height.parse().unwrap_or(rows);
+            }
+            charset = petmate_charset(fields.get(3).copied());
+        }
+
+        let mut code = code;
+        if let Some(position) = code.find('{') {
+            current = Some(Vec::new());
+            code = &code[position + 1..];
+        }
+        let end = code.find('}');
+        if let Some(position) = end {
+            code = &code[..position];
+        }
+
+        if let Some(values) = current.as_mut() {
+            for value in code.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                let value = match value.strip_prefix("0x") {
+                    Some(hex) => u8::from_str_radix(hex, 16),
+                    None => value.parse(),
+                }
+                .map_err(|e| Error::new(ErrorKind::Message(format!("{}: {}", value, e))))?;
+                values.push(value);
+            }
+        }
+
+        if end.is_some() {
+            if let Some(values) = current.take() {
+                arrays.push(values);
+            }
+        }
+    }
+
+    arrays
+        .iter()
+        .map(|values| {
+            let len = screen_len(columns, rows)?;
+            let needed = len
+                .checked_mul(2)
+                .and_then(|cells| cells.checked_add(2))
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::Message(format!(
+                        "A {}x{} screen is too large",
+                        columns, rows
+                    )))
+                })?;
+            if values.len() < needed {
+                return Err(Error::new(ErrorKind::Message(format!(
+                    "A {}x{} screen needs {} values, found {}",
+                    columns,
+                    rows,
+                    needed,
+                    values.len()
+                ))));
+            }
+
+            let mut screen = screen_from_memory(
+                columns,
+                rows,
+                &values[2..2 + len],
+                &values[2 + len..2 + 2 * len],
+            )?;
+            screen.border = Color::from_index(values[0]);
+            screen.background = Color::from_index(values[1]);
+            screen.charset = charset;
+            Ok(screen)
+        })
+        .collect()
+}
+
+/// Read a .pet file from Marq's PETSCII editor
+///
+/// The file starts with the width, height, border color, background
+/// color and character set (0 for uppercase, 1 for lowercase),
+/// followed by the screen codes and the colors.
+pub fn read_pet(bytes: &[u8]) -> std::result::Result<Screen, Error> {
+    let [columns, rows, border, background, charset, ..] = bytes[..] else {
+        return Err(Error::new(ErrorKind::Message(String::from(
+            ".pet file is too short for its header",
+        ))));
+    };
+    let (columns, rows) = (usize::from(columns), usize::from(rows));
+    let len = screen_len(columns, rows)?;
+    let data = &bytes[5..];
+
+    let mut screen = screen_from_memory(columns, rows, data, data.get(len..).unwrap_or_default())?;
+    screen.border = Color::from_index(border);
+    screen.background = Color::from_index(background);
+    screen.charset = if charset == 0 {
+        Charset::Uppercase
+    } else {
+        Charset::Lowercase
+    };
+    Ok(screen)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            art::{read_pet, read_petmate_c, read_petmate_json, read_seq},
+            screen::Charset,
+            Color, PetsciiConfig,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn art_seq_wraps_and_scrolls() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut seq = vec![0x93, 0x0e];
+        // A full line wraps, then 24 returns scroll it off the top
+        seq.extend([0x41; 41]);
+        seq.extend([0x0d; 24]);
+        seq.push(0x42);
+
+        let screen = read_seq(&seq, &petscii).unwrap();
+        assert_eq!(screen.charset, Charset::Lowercase);
+        assert_eq!(screen.row_text(0, &petscii), "a");
+        assert_eq!(screen.row_text(24, &petscii), "b");
+
+        // Tables with a bad screen code are an error
+        let mut bad = petscii.clone();
+        bad.character_set_map
+            .c64_petscii_unshifted_codes_to_screen_codes
+            .insert(String::from("65"), "A".into());
+        assert!(read_seq(&seq, &bad).is_err());
+    }
+
+    #[test]
+    fn art_petmate_framebuf_works() {
+        let json = r#"{"version": 1, "screens": [0], "framebufs": [{
+            "width": 1, "height": 2, "backgroundColor": 6, "borderColor": 14,
+            "charset": "upper",
+            "framebuf": [[{"code": 1, "color": 7}], [{"code": 160, "color": 2}]]
+        }]}"#;
+
+        let screens = read_petmate_json(json).unwrap();
+        assert_eq!(screens.len(), 1);
+        assert_eq!(screens[0].screen_codes(), vec![1, 160]);
+        assert_eq!(screens[0].color_codes(), vec![7, 2]);
+        assert_eq!(screens[0].background, Color::Blue);
+
+        assert!(read_petmate_json(r#"{"framebufs": [{"width": 1}]}"#).is_err());
+    }
+
+    #[test]
+    fn art_petmate_c_short_array_fails() {
+        let source = "unsigned char frame0000[]={14,6,1,2,3};\n";
+        assert!(read_petmate_c(source).is_err());
+
+        let source = "unsigned char a[]={0,0,0x01,1};\nunsigned char b[]={0,0,2,2};\n// META: 1 1 C64 lower\n";
+        let screens = read_petmate_c(source).unwrap();
+        assert_eq!(screens.len(), 2);
+        assert_eq!(screens[1].screen_codes(), vec![2]);
+        assert_eq!(screens[1].charset, Charset::Lowercase);
+    }
+
+    #[test]
+    fn art_oversized_headers_fail() {
+        // Sizes whose number of cells, or twice it, overflow
+        let max = usize::MAX;
+        for (columns, rows) in [(max, 2), (max / 2, 1)] {
+            let source =
+                format!("unsigned char a[]={{0,0,1,1}};\n// META: {columns} {rows} C64 upper\n");
+            assert!(read_petmate_c(&source).is_err());
+        }
+
+        for (width, height) in [(u64::MAX, 2), (u64::MAX, u64::MAX), (1 << 40, 1 << 40)] {
+            let json = format!(
+                r#"{{"framebufs": [{{"width": {width}, "height": {height},
+                "backgroundColor": 0, "borderColor": 0, "screencodes": [1], "colors": [1]}}]}}"#
+            );
+            assert!(read_petmate_json(&json).is_err());
+        }
+    }
+
+    #[test]
+    fn art_pet_works() {
+        let screen = read_pet(&[2, 1, 0, 11, 1, 0x08, 0x09, 5, 5]).unwrap();
+
+        assert_eq!(screen.background, Color::DarkGrey);
+        assert_eq!(screen.charset, Charset::Lowercase);
+        assert_eq!(screen.color_codes(), vec![5, 5]);
+        assert!(read_pet(&[2, 1, 0, 11, 1, 0x08]).is_err());
+    }
+}
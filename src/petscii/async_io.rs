@@ -0,0 +1,302 @@
+//!
+//! Transcoding tokio streams
+//!
+//! A BBS bridge reads PETSCII from one socket and writes text to
+//! another.  These adapters wrap a tokio reader or writer and
+//! transcode as the bytes pass through:
+//!
+//! * PetsciiReadAdapter reads PETSCII from the stream it wraps and
+//!   returns UTF-8
+//! * PetsciiWriteAdapter takes UTF-8 and writes PETSCII to the stream
+//!   it wraps
+//!
+//! Network reads and writes can end anywhere, so the decoder state
+//! and any partial UTF-8 character are kept between calls.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{
+    petscii::{
+        decoder::{DecodeEvent, PetsciiDecoderState},
+        encode_from_state,
+    },
+    SystemConfig,
+};
+
+/// The number of bytes read from the wrapped stream at a time
+const READ_CHUNK_LEN: usize = 512;
+
+/// Decodes PETSCII read from a stream into UTF-8
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{async_io::PetsciiReadAdapter, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+/// use tokio::io::AsyncReadExt;
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread()
+///     .build()
+///     .expect("Error creating runtime");
+///
+/// let mut reader = PetsciiReadAdapter::new(&b"\x48\x49\x5c"[..], petscii);
+/// let mut text = String::new();
+/// runtime
+///     .block_on(reader.read_to_string(&mut text))
+///     .expect("Error reading");
+///
+/// assert_eq!(text, "HI£");
+/// ```
+pub struct PetsciiReadAdapter<R> {
+    inner: R,
+    petscii: SystemConfig,
+    /// The decoder state, kept without its character map between
+    /// reads
+    state: PetsciiDecoderState<'static>,
+    /// Decoded UTF-8 that hasn't been returned yet
+    decoded: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> PetsciiReadAdapter<R> {
+    /// Wrap a PETSCII stream
+    pub fn new(inner: R, petscii: SystemConfig) -> PetsciiReadAdapter<R> {
+        PetsciiReadAdapter {
+            inner,
+            petscii,
+            state: PetsciiDecoderState::default(),
+            decoded: Vec::new(),
+        }
+    }
+
+    /// The wrapped stream
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Decode PETSCII bytes into the pending UTF-8
+    fn decode(&mut self, bytes: &[u8]) {
+        let mut state = self.state.with_character_map(Some(&self.petscii));
+        for b in bytes {
+            if let DecodeEvent::Char(c) = state.feed(*b) {
+                self.decoded
+                    .extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+        }
+        self.state = state.with_character_map(None);
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for PetsciiReadAdapter<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        // Control codes don't decode to anything, so keep reading
+        // until there's text or the stream ends
+        while this.decoded.is_empty() {
+            let mut chunk = [0; READ_CHUNK_LEN];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+
+            if chunk_buf.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            let filled = chunk_buf.filled().to_vec();
+            this.decode(&filled);
+        }
+
+        let len = this.decoded.len().min(buf.remaining());
+        buf.put_slice(&this.decoded[..len]);
+        this.decoded.drain(..len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Encodes UTF-8 written to it into PETSCII on a stream
+///
+/// Shutting the adapter down shifts back to the uppercase character
+/// set if the text ended in lowercase, like a complete encode does.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{async_io::PetsciiWriteAdapter, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+/// use tokio::io::AsyncWriteExt;
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread()
+///     .build()
+///     .expect("Error creating runtime");
+///
+/// let mut writer = PetsciiWriteAdapter::new(Vec::new(), petscii);
+/// runtime.block_on(async {
+///     writer.write_all("Hi".as_bytes()).await?;
+///     writer.shutdown().await
+/// })
+/// .expect("Error writing");
+///
+/// assert_eq!(writer.into_inner(), [0x48, 0x0E, 0x49, 0x8E]);
+/// ```
+pub struct PetsciiWriteAdapter<W> {
+    inner: W,
+    petscii: SystemConfig,
+    shifted: bool,
+    /// The start of a UTF-8 character split across writes
+    partial: Vec<u8>,
+    /// Encoded bytes the wrapped stream hasn't accepted yet
+    encoded: Vec<u8>,
+}
+
+impl<W: AsyncWrite + Unpin> PetsciiWriteAdapter<W> {
+    /// Wrap a stream that takes PETSCII
+    pub fn new(inner: W, petscii: SystemConfig) -> PetsciiWriteAdapter<W> {
+        PetsciiWriteAdapter {
+            inner,
+            petscii,
+            shifted: false,
+            partial: Vec::new(),
+            encoded: Vec::new(),
+        }
+    }
+
+    /// The wrapped stream
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Write the encoded bytes to the wrapped stream
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.encoded.is_empty() {
+            let written = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.encoded))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.encoded.drain(..written);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for PetsciiWriteAdapter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+
+        this.partial.extend_from_slice(buf);
+        let valid_len = match std::str::from_utf8(&this.partial) {
+            Ok(s) => s.len(),
+            // An incomplete character at the end is kept for the
+            // next write
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => {
+                this.partial.clear();
+                return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidData, e)));
+            }
+        };
+
+        let text: Vec<u8> = this.partial.drain(..valid_len).collect();
+        let s = std::str::from_utf8(&text).expect("Checked the text is valid UTF-8");
+        let bytes = encode_from_state(s, &this.petscii.character_set_map, &mut this.shifted);
+        this.encoded.extend(bytes);
+
+        // Start writing now, the rest goes out on the next call
+        if let Poll::Ready(Err(e)) = this.poll_drain(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.shifted {
+            this.encoded.push(0x8E);
+            this.shifted = false;
+        }
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::{
+        petscii::{
+            async_io::{PetsciiReadAdapter, PetsciiWriteAdapter},
+            PetsciiConfig,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn async_io_read_keeps_state_between_reads() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        // The shift code and the letter arrive in separate reads
+        let stream = (&b"\x0e"[..]).chain(&b"\x41\x93"[..]);
+        let mut reader = PetsciiReadAdapter::new(stream, petscii);
+        let mut text = String::new();
+        runtime.block_on(reader.read_to_string(&mut text)).unwrap();
+
+        assert_eq!(text, "a");
+    }
+
+    #[test]
+    fn async_io_write_keeps_partial_characters() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let mut writer = PetsciiWriteAdapter::new(Vec::new(), petscii);
+        runtime
+            .block_on(async {
+                writer.write_all(b"ab\xc2").await?;
+                writer.write_all(b"\xa3").await?;
+                writer.shutdown().await
+            })
+            .unwrap();
+        assert_eq!(writer.into_inner(), [0x0E, 0x41, 0x42, 0x8E, 0x5C]);
+
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let mut writer = PetsciiWriteAdapter::new(Vec::new(), petscii);
+        assert!(runtime.block_on(writer.write_all(b"\xff")).is_err());
+    }
+}
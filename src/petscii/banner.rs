@@ -0,0 +1,565 @@
+//!
+//! Large banner letters from block graphics
+//!
+//! BBS logon screens and demo scrollers spell out titles in letters
+//! several characters tall.  Banner draws text in a 5 by 7 pixel font
+//! embedded in this module, with one pixel of space after each letter
+//! and one below, and packs the pixels into the C64's quadrant block
+//! characters, two by two pixels in each character cell.  Each letter
+//! is three cells wide and four tall, so a 40 column screen fits
+//! thirteen letters on a line.
+//!
+//! PETSCII only has half of the sixteen quadrant patterns, like the
+//! upper left quadrant ▘ and the left half ▌.  The other half are the
+//! same characters in reverse video, so the banner switches reverse
+//! video on and off as it goes.  The codes are the C64's, the same on
+//! every configuration.
+//!
+//! The font has the letters, the digits, the space and some
+//! punctuation.  Lowercase letters are drawn as uppercase.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::error::{Error, ErrorKind};
+
+/// The width of a glyph in pixels
+const GLYPH_WIDTH: usize = 5;
+
+/// The height of a glyph in pixels
+const GLYPH_HEIGHT: usize = 7;
+
+/// The width of a letter in character cells, with its space
+const LETTER_COLUMNS: usize = 3;
+
+/// The height of a letter in character cells, with its space
+const LETTER_ROWS: usize = 4;
+
+/// Carriage return
+const RETURN: u8 = 0x0D;
+/// Turn reverse video on
+const REVERSE_ON: u8 = 0x12;
+/// Turn reverse video off
+const REVERSE_OFF: u8 = 0x92;
+
+/// The font, a row of pixels in each byte with the leftmost pixel in
+/// bit 4
+const FONT: [(char, [u8; GLYPH_HEIGHT]); 52] = [
+    (
+        ' ',
+        [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '!',
+        [
+            0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100,
+        ],
+    ),
+    (
+        '"',
+        [
+            0b01010, 0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '#',
+        [
+            0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010,
+        ],
+    ),
+    (
+        '\'',
+        [
+            0b01100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '(',
+        [
+            0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010,
+        ],
+    ),
+    (
+        ')',
+        [
+            0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        '*',
+        [
+            0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000,
+        ],
+    ),
+    (
+        '+',
+        [
+            0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000,
+        ],
+    ),
+    (
+        ',',
+        [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        '-',
+        [
+            0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '.',
+        [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100,
+        ],
+    ),
+    (
+        '/',
+        [
+            0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000,
+        ],
+    ),
+    (
+        '0',
+        [
+            0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '1',
+        [
+            0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+    ),
+    (
+        '2',
+        [
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111,
+        ],
+    ),
+    (
+        '3',
+        [
+            0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '4',
+        [
+            0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010,
+        ],
+    ),
+    (
+        '5',
+        [
+            0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '6',
+        [
+            0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '7',
+        [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000,
+        ],
+    ),
+    (
+        '8',
+        [
+            0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '9',
+        [
+            0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100,
+        ],
+    ),
+    (
+        ':',
+        [
+            0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000,
+        ],
+    ),
+    (
+        '=',
+        [
+            0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '?',
+        [
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100,
+        ],
+    ),
+    (
+        'A',
+        [
+            0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'B',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110,
+        ],
+    ),
+    (
+        'C',
+        [
+            0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'D',
+        [
+            0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110,
+        ],
+    ),
+    (
+        'E',
+        [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        'F',
+        [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+    ),
+    (
+        'G',
+        [
+            0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111,
+        ],
+    ),
+    (
+        'H',
+        [
+            0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'I',
+        [
+            0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+    ),
+    (
+        'J',
+        [
+            0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100,
+        ],
+    ),
+    (
+        'K',
+        [
+            0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001,
+        ],
+    ),
+    (
+        'L',
+        [
+            0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        'M',
+        [
+            0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'N',
+        [
+            0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'O',
+        [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'P',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+    ),
+    (
+        'Q',
+        [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101,
+        ],
+    ),
+    (
+        'R',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001,
+        ],
+    ),
+    (
+        'S',
+        [
+            0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110,
+        ],
+    ),
+    (
+        'T',
+        [
+            0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
+        ],
+    ),
+    (
+        'U',
+        [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'V',
+        [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100,
+        ],
+    ),
+    (
+        'W',
+        [
+            0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010,
+        ],
+    ),
+    (
+        'X',
+        [
+            0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'Y',
+        [
+            0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100,
+        ],
+    ),
+    (
+        'Z',
+        [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111,
+        ],
+    ),
+];
+
+/// The PETSCII code and reverse video for each quadrant pattern
+///
+/// Bit 0 of the pattern is the upper left quadrant, bit 1 the upper
+/// right, bit 2 the lower left and bit 3 the lower right.
+const QUADRANT_CODES: [(u8, bool); 16] = [
+    (0x20, false),
+    (0xBE, false),
+    (0xBC, false),
+    (0xA2, true),
+    (0xBB, false),
+    (0xA1, false),
+    (0xBF, true),
+    (0xAC, true),
+    (0xAC, false),
+    (0xBF, false),
+    (0xA1, true),
+    (0xBB, true),
+    (0xA2, false),
+    (0xBC, true),
+    (0xBE, true),
+    (0x20, true),
+];
+
+/// The Unicode character for each quadrant pattern
+const QUADRANT_CHARS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// Text drawn in large letters
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::banner::Banner;
+///
+/// let banner = Banner::new("Hi").expect("Error drawing banner");
+///
+/// assert_eq!((banner.columns(), banner.rows()), (6, 4));
+/// assert_eq!(banner.to_unicode(), "▌ ▌▝▛\n▙▄▌ ▌\n▌ ▌ ▌\n▘ ▘▝▀\n");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Banner {
+    /// The glyph of each letter
+    glyphs: Vec<[u8; GLYPH_HEIGHT]>,
+}
+
+impl Banner {
+    /// Draw text in the banner font
+    ///
+    /// Returns an error for a character the font doesn't have.
+    pub fn new(text: &str) -> std::result::Result<Banner, Error> {
+        let glyphs = text
+            .char_indices()
+            .map(|(offset, c)| {
+                FONT.iter()
+                    .find(|(f, _)| *f == c.to_ascii_uppercase())
+                    .map(|(_, glyph)| *glyph)
+                    .ok_or_else(|| {
+                        Error::new(ErrorKind::Unencodable {
+                            grapheme: String::from(c),
+                            offset,
+                        })
+                    })
+            })
+            .collect::<std::result::Result<_, _>>()?;
+
+        Ok(Banner { glyphs })
+    }
+
+    /// The width of the banner in character cells
+    pub fn columns(&self) -> usize {
+        self.glyphs.len() * LETTER_COLUMNS
+    }
+
+    /// The height of the banner in character cells, zero if there's
+    /// no text
+    pub fn rows(&self) -> usize {
+        if self.glyphs.is_empty() {
+            0
+        } else {
+            LETTER_ROWS
+        }
+    }
+
+    /// True if the pixel is set
+    fn pixel(&self, x: usize, y: usize) -> bool {
+        let column = x % (GLYPH_WIDTH + 1);
+        y < GLYPH_HEIGHT
+            && column < GLYPH_WIDTH
+            && self.glyphs[x / (GLYPH_WIDTH + 1)][y] & (1 << (GLYPH_WIDTH - 1 - column)) != 0
+    }
+
+    /// The quadrant pattern of a character cell
+    fn pattern(&self, column: usize, row: usize) -> usize {
+        let (x, y) = (column * 2, row * 2);
+        [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)]
+            .iter()
+            .enumerate()
+            .filter(|(_, (x, y))| self.pixel(*x, *y))
+            .map(|(bit, _)| 1 << bit)
+            .sum()
+    }
+
+    /// The banner as PETSCII
+    ///
+    /// Each row ends with a carriage return, which also turns reverse
+    /// video off.  Spaces at the ends of rows are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::banner::Banner;
+    ///
+    /// let banner = Banner::new("-").expect("Error drawing banner");
+    ///
+    /// assert_eq!(banner.to_petscii(), b"\r\xa2\xa2\xbb\r\r\r");
+    /// ```
+    pub fn to_petscii(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for row in 0..self.rows() {
+            let end = (0..self.columns())
+                .rposition(|column| self.pattern(column, row) != 0)
+                .map_or(0, |column| column + 1);
+            let mut reversed = false;
+            for column in 0..end {
+                let (code, reverse) = QUADRANT_CODES[self.pattern(column, row)];
+                if reverse != reversed {
+                    bytes.push(if reverse { REVERSE_ON } else { REVERSE_OFF });
+                    reversed = reverse;
+                }
+                bytes.push(code);
+            }
+            bytes.push(RETURN);
+        }
+
+        bytes
+    }
+
+    /// The banner as Unicode block elements, a line for each row
+    pub fn to_unicode(&self) -> String {
+        let mut s = String::new();
+
+        for row in 0..self.rows() {
+            let line: String = (0..self.columns())
+                .map(|column| QUADRANT_CHARS[self.pattern(column, row)])
+                .collect();
+            s.push_str(line.trim_end_matches(' '));
+            s.push('\n');
+        }
+
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            banner::{Banner, QUADRANT_CHARS, QUADRANT_CODES},
+            petscii_to_screen_code,
+            terminal::PetsciiTerminal,
+            PetsciiConfig,
+        },
+        CharacterSet, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn banner_quadrants_match_the_character_set() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Each code shows its pattern, or the opposite one reversed
+        for (pattern, (code, reversed)) in QUADRANT_CODES.iter().enumerate() {
+            let shown = if *reversed { pattern ^ 0b1111 } else { pattern };
+            let c = QUADRANT_CHARS[shown].to_string();
+            assert_eq!(petscii.decode(&[*code]), c, "pattern {pattern:04b}");
+        }
+
+        let banner = Banner::new("Ok 1").unwrap();
+        assert_eq!(banner.columns(), 12);
+
+        // Printed, every cell holds the screen code of its pattern
+        let mut terminal = PetsciiTerminal::new().with_character_map(&petscii);
+        terminal.print(&banner.to_petscii());
+        let screen = terminal.into_screen();
+        let map = &petscii.character_set_map;
+        for row in 0..banner.rows() {
+            for column in 0..banner.columns() {
+                let (code, reversed) = QUADRANT_CODES[banner.pattern(column, row)];
+                let screen_code = petscii_to_screen_code(code, false, map).unwrap();
+                let expected = if reversed {
+                    screen_code.reversed()
+                } else {
+                    screen_code
+                };
+                let cell = screen.cell(column, row).unwrap();
+                assert_eq!(cell.screen_code, expected.value, "cell {column}, {row}");
+            }
+        }
+
+        assert!(Banner::new("").unwrap().to_petscii().is_empty());
+        assert!(Banner::new("£").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//!
+//! Text mixed with binary data
+//!
+//! Some files interleave PETSCII text with bytes that aren't text at
+//! all, like a BASIC program with machine code after it, or a
+//! capture with sprite data in the middle.  Decoding them drops the
+//! codes that don't show a character, so the binary parts vanish and
+//! the bytes can't be recovered from the text.
+//!
+//! With [crate::DecodeOptions::hex_escape_runs] set, runs of those
+//! codes longer than the limit are kept as hex escapes like \x00.
+//! Shorter runs, usually the color and cursor codes in the text, are
+//! still dropped.  encode_with_hex_escapes turns the escapes back
+//! into the bytes, so with a limit of zero a decoded buffer encodes
+//! back to the same bytes wherever its characters do.
+//!
+//! PETSCII doesn't have a backslash, so an escape can't be mistaken
+//! for decoded text unless a profile maps the pound sign to one.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::Write;
+
+use crate::{
+    petscii::{
+        decoder::{DecodeEvent, PetsciiDecoderState},
+        encode_from_state,
+    },
+    SystemConfig,
+};
+
+/// Decode bytes, writing runs of more than min_run codes without a
+/// character as hex escapes
+pub(crate) fn decode_escaping(
+    bytes: &[u8],
+    mut state: PetsciiDecoderState,
+    min_run: usize,
+) -> String {
+    let mut s = String::new();
+    let mut run: Vec<u8> = Vec::new();
+
+    let flush = |s: &mut String, run: &mut Vec<u8>| {
+        if run.len() > min_run {
+            run.iter()
+                .for_each(|b| write!(s, "\\x{b:02X}").expect("Should write to a String"));
+        }
+        run.clear();
+    };
+
+    for b in bytes {
+        match state.feed(*b) {
+            DecodeEvent::Char(c) => {
+                flush(&mut s, &mut run);
+                s.push(c);
+            }
+            _ => run.push(*b),
+        }
+    }
+    flush(&mut s, &mut run);
+
+    s
+}
+
+/// Find the first hex escape in a string, returning where it starts
+/// and the byte
+fn find_escape(s: &str) -> Option<(usize, u8)> {
+    s.match_indices("\\x").find_map(|(i, _)| {
+        let digits = s.get(i + 2..i + 4)?;
+        u8::from_str_radix(digits, 16)
+            .ok()
+            .filter(|_| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .map(|b| (i, b))
+    })
+}
+
+impl SystemConfig {
+    /// Encode a string, turning hex escapes like \x00 back into the
+    /// bytes they stand for
+    ///
+    /// The escaped bytes are written as they are.  A shift in or out
+    /// code among them changes the character set the text after them
+    /// is encoded in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::PetsciiConfig, CharacterSet, Configuration, DecodeOptions, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let bytes = [0x48, 0x49, 0x00, 0x00, 0x00, 0x4f, 0x1c, 0x4b];
+    /// let options = DecodeOptions::default().with_hex_escape_runs(Some(2));
+    ///
+    /// let text = petscii.decode_with_options(&bytes, &options);
+    /// assert_eq!(text, "HI\\x00\\x00\\x00OK");
+    ///
+    /// assert_eq!(
+    ///     petscii.encode_with_hex_escapes(&text),
+    ///     [0x48, 0x49, 0x00, 0x00, 0x00, 0x4f, 0x4b]
+    /// );
+    /// ```
+    pub fn encode_with_hex_escapes(&self, s: &str) -> Vec<u8> {
+        let map = &self.character_set_map;
+        let mut shifted = false;
+        let mut bytes = Vec::new();
+        let mut rest = s;
+
+        while let Some((i, b)) = find_escape(rest) {
+            bytes.extend(encode_from_state(&rest[..i], map, &mut shifted));
+            match b {
+                0x0E => shifted = true,
+                0x8E => shifted = false,
+                _ => {}
+            }
+            bytes.push(b);
+            rest = &rest[i + 4..];
+        }
+        bytes.extend(encode_from_state(rest, map, &mut shifted));
+
+        if shifted {
+            bytes.push(0x8E);
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{petscii::PetsciiConfig, CharacterSet, Configuration, DecodeOptions, SystemConfig};
+
+    #[test]
+    fn binary_round_trips_machine_code_after_text() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let options = DecodeOptions::default().with_hex_escape_runs(Some(0));
+
+        // "ok" in lowercase, then data that's all control codes
+        let bytes = [0x0e, 0x4f, 0x4b, 0x8e, 0x00, 0x80, 0x03, 0x1f, 0x41];
+        let text = petscii.decode_with_options(&bytes, &options);
+        assert!(text.starts_with("\\x0Eok\\x8E"), "{text}");
+        assert_eq!(petscii.encode_with_hex_escapes(&text), bytes);
+
+        // Without a limit the codes are dropped like before
+        assert!(!petscii.decode(&bytes).contains('\\'));
+
+        // Only complete escapes are bytes
+        assert_eq!(
+            petscii.encode_with_hex_escapes("\\xZ1\\x4"),
+            petscii.encode("\\xZ1\\x4")
+        );
+    }
+}
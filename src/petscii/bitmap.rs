@@ -0,0 +1,164 @@
+//!
+//! Drawing screens as pixels
+//!
+//! The C64 draws each character cell from an 8 by 8 pixel bitmap in
+//! its character ROM.  The ROM isn't part of this crate, so drawing a
+//! screen pixel for pixel needs a copy of it, the 4K chargen file
+//! that comes with emulators like VICE.  The VIC-20 character ROM has
+//! the same layout.
+//!
+//! The pixels are C64 color numbers, so the same image can be shown
+//! with any palette or written to an indexed image format like GIF.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    error::{Error, ErrorKind},
+    petscii::screen::{Charset, Screen},
+};
+
+/// The width and height of a character in pixels
+pub const CHARACTER_SIZE: usize = 8;
+
+/// The width of the border around the screen in pixels
+pub const BORDER_SIZE: usize = 32;
+
+/// A character ROM with the bitmaps of both character sets
+///
+/// The first 2K is the uppercase and graphics set and the second 2K
+/// the lowercase set.  Each set has the 128 characters followed by
+/// their reversed copies, 8 bytes per character, one byte per row
+/// with the leftmost pixel in bit 7.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CharacterRom {
+    data: Vec<u8>,
+}
+
+impl CharacterRom {
+    /// The size of a character ROM in bytes
+    pub const LEN: usize = 4096;
+
+    /// Create a character ROM from the contents of a ROM file
+    pub fn from_bytes(bytes: &[u8]) -> std::result::Result<CharacterRom, Error> {
+        if bytes.len() != CharacterRom::LEN {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "A character ROM is {} bytes, found {}",
+                CharacterRom::LEN,
+                bytes.len()
+            ))));
+        }
+        Ok(CharacterRom {
+            data: bytes.to_vec(),
+        })
+    }
+
+    /// The bitmap of a screen code in a character set, a byte for
+    /// each row
+    pub fn glyph(&self, screen_code: u8, charset: Charset) -> &[u8] {
+        let set = match charset {
+            Charset::Uppercase => 0,
+            Charset::Lowercase => 2048,
+        };
+        let start = set + usize::from(screen_code) * CHARACTER_SIZE;
+        &self.data[start..start + CHARACTER_SIZE]
+    }
+}
+
+/// An image with a color number for each pixel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedImage {
+    /// The width in pixels
+    pub width: usize,
+    /// The height in pixels
+    pub height: usize,
+    /// The color numbers of the pixels, a row at a time
+    pub pixels: Vec<u8>,
+}
+
+impl Screen {
+    /// Draw the screen and its border with the bitmaps in a
+    /// character ROM
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::{bitmap::CharacterRom, screen::Screen, Color};
+    ///
+    /// // A ROM where the top row of every character is set
+    /// let mut data = vec![0; CharacterRom::LEN];
+    /// for glyph in data.chunks_mut(8) {
+    ///     glyph[0] = 0xFF;
+    /// }
+    /// let rom = CharacterRom::from_bytes(&data).expect("The ROM is 4K");
+    ///
+    /// let mut screen = Screen::new(1, 1);
+    /// screen.set(0, 0, 0x01, Color::White);
+    /// let image = screen.to_indexed_image(&rom);
+    ///
+    /// assert_eq!((image.width, image.height), (72, 72));
+    /// // The border, the top row of the character and the background
+    /// assert_eq!(image.pixels[0], Color::LightBlue.index());
+    /// assert_eq!(image.pixels[32 * 72 + 32], Color::White.index());
+    /// assert_eq!(image.pixels[33 * 72 + 32], Color::Blue.index());
+    /// ```
+    pub fn to_indexed_image(&self, rom: &CharacterRom) -> IndexedImage {
+        let width = self.columns * CHARACTER_SIZE + 2 * BORDER_SIZE;
+        let height = self.rows * CHARACTER_SIZE + 2 * BORDER_SIZE;
+        let mut pixels = vec![self.border.index(); width * height];
+
+        for row in 0..self.rows {
+            for (column, cell) in self.row(row).unwrap_or_default().iter().enumerate() {
+                let glyph = rom.glyph(cell.screen_code, self.charset);
+                for (y, bits) in glyph.iter().enumerate() {
+                    let start = (BORDER_SIZE + row * CHARACTER_SIZE + y) * width
+                        + BORDER_SIZE
+                        + column * CHARACTER_SIZE;
+                    for (x, pixel) in pixels[start..start + CHARACTER_SIZE].iter_mut().enumerate() {
+                        *pixel = if bits & (0x80 >> x) != 0 {
+                            cell.color.index()
+                        } else {
+                            self.background.index()
+                        };
+                    }
+                }
+            }
+        }
+
+        IndexedImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::petscii::{
+        bitmap::CharacterRom,
+        screen::{Charset, Screen},
+        Color,
+    };
+
+    #[test]
+    fn bitmap_uses_the_charset_and_reverse_bit() {
+        // Each character's first byte is its set and screen code
+        let mut data = vec![0; CharacterRom::LEN];
+        for (i, glyph) in data.chunks_mut(8).enumerate() {
+            glyph[0] = (i % 256) as u8;
+        }
+        data[2048 + 8] = 0x40;
+        let rom = CharacterRom::from_bytes(&data).unwrap();
+        assert_eq!(rom.glyph(0x81, Charset::Uppercase)[0], 0x81);
+
+        let mut screen = Screen::new(1, 1);
+        screen.set(0, 0, 0x01, Color::Red);
+        screen.charset = Charset::Lowercase;
+        let image = screen.to_indexed_image(&rom);
+        let top_row = &image.pixels[32 * image.width + 32..][..8];
+        assert_eq!(top_row[1], Color::Red.index());
+        assert_eq!(top_row[7], Color::Blue.index());
+
+        assert!(CharacterRom::from_bytes(&data[..2048]).is_err());
+    }
+}
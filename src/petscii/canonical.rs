@@ -0,0 +1,202 @@
+//!
+//! Canonical PETSCII byte sequences
+//!
+//! The same screen output can be written with different bytes.  A
+//! shift code can be sent when the character set is already
+//! selected, reverse video can be turned on and straight back off,
+//! and the C64 has two codes for most graphics characters.  Two
+//! copies of a file that differ only in these ways hash and diff as
+//! different.
+//!
+//! normalize rewrites PETSCII into one canonical form:
+//!
+//! * A shift code is only kept if the character set it selects isn't
+//!   already selected when the next other code comes, so runs of
+//!   toggles collapse to the last one and redundant codes go.
+//! * Reverse on and off codes are handled the same way, and a
+//!   carriage return turns reverse video off like it does on the C64,
+//!   so a reverse code with nothing after it before the end of the
+//!   line goes too.
+//! * The duplicate codes are replaced by the code they duplicate,
+//!   from the code alias table of the configuration.  On the C64
+//!   that's 0xC0 to 0xDF by 0x60 to 0x7F, 0xE0 to 0xFE by 0xA0 to
+//!   0xBE, and 0xFF by 0x7E.
+//! * Optionally, the shifted space padding at the end is removed.
+//!
+//! This works on the stream of codes as it's printed.  It doesn't
+//! know about quote mode or BASIC tokens, so normalize the string
+//! literals of a program rather than the tokenized program.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::SystemConfig;
+
+/// Switch to the shifted (lowercase and uppercase) character set
+const SHIFT_ON: u8 = 0x0E;
+/// Switch to the unshifted (uppercase and graphics) character set
+const SHIFT_OFF: u8 = 0x8E;
+/// Turn reverse video on
+const REVERSE_ON: u8 = 0x12;
+/// Turn reverse video off
+const REVERSE_OFF: u8 = 0x92;
+/// Carriage return, which also turns reverse video off
+const RETURN: u8 = 0x0D;
+
+/// Options for normalizing PETSCII
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    /// The data starts in the shifted (lowercase) character set, so
+    /// a shift on code at the start is redundant
+    pub initial_shifted: bool,
+
+    /// Remove shifted spaces (0xA0 and its duplicates) at the end
+    pub strip_padding: bool,
+}
+
+/// Rewrite PETSCII bytes into their canonical form
+///
+/// Normalizing twice gives the same bytes as normalizing once.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{
+///         canonical::{normalize, NormalizeOptions},
+///         PetsciiConfig,
+///     },
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let options = NormalizeOptions {
+///     strip_padding: true,
+///     ..NormalizeOptions::default()
+/// };
+///
+/// // A redundant unshift, a toggle pair and an empty reverse pair
+/// let bytes = [0x8E, 0x41, 0x0E, 0x8E, 0x12, 0x92, 0xC1, 0xA0, 0xA0];
+///
+/// assert_eq!(normalize(&bytes, options, &petscii), [0x41, 0x61]);
+/// ```
+pub fn normalize(bytes: &[u8], options: NormalizeOptions, petscii: &SystemConfig) -> Vec<u8> {
+    let map = &petscii.character_set_map;
+    let bytes = if options.strip_padding {
+        let len = bytes
+            .iter()
+            .rposition(|code| map.canonicalize_code(*code) != 0xA0)
+            .map_or(0, |position| position + 1);
+        &bytes[..len]
+    } else {
+        bytes
+    };
+
+    let mut normalized = Vec::with_capacity(bytes.len());
+    let mut current = Attributes {
+        shifted: options.initial_shifted,
+        reversed: false,
+    };
+    let mut pending = current;
+
+    for code in bytes {
+        match *code {
+            SHIFT_ON => pending.shifted = true,
+            SHIFT_OFF => pending.shifted = false,
+            REVERSE_ON => pending.reversed = true,
+            REVERSE_OFF => pending.reversed = false,
+            RETURN => {
+                // The return turns reverse video off itself
+                pending.reversed = current.reversed;
+                current.flush(pending, &mut normalized);
+                normalized.push(RETURN);
+                current.reversed = false;
+                pending.reversed = false;
+            }
+            code => {
+                current.flush(pending, &mut normalized);
+                normalized.push(map.canonicalize_code(code));
+            }
+        }
+    }
+
+    // Codes at the end still set the state for whatever comes next
+    current.flush(pending, &mut normalized);
+
+    normalized
+}
+
+/// The character set and reverse video state
+#[derive(Clone, Copy)]
+struct Attributes {
+    shifted: bool,
+    reversed: bool,
+}
+
+impl Attributes {
+    /// Write the codes that change this state to the pending one
+    fn flush(&mut self, pending: Attributes, normalized: &mut Vec<u8>) {
+        if pending.shifted != self.shifted {
+            normalized.push(if pending.shifted { SHIFT_ON } else { SHIFT_OFF });
+        }
+        if pending.reversed != self.reversed {
+            normalized.push(if pending.reversed {
+                REVERSE_ON
+            } else {
+                REVERSE_OFF
+            });
+        }
+        *self = pending;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            canonical::{normalize, NormalizeOptions},
+            PetsciiConfig,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn canonical_normalize_is_idempotent() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let options = NormalizeOptions::default();
+        let samples: [&[u8]; 5] = [
+            &[0x0E, 0x0E, 0x48, 0x8E, 0x0E, 0x49],
+            &[0x12, 0x41, 0x0D, 0x92, 0x42],
+            &[0x12, 0x92, 0x12, 0x0D, 0x41],
+            &[0xE0, 0xFF, 0xDA, 0x0E],
+            &[0x12, 0x0E, 0x41, 0x92, 0xA0],
+        ];
+        let expected: [&[u8]; 5] = [
+            &[0x0E, 0x48, 0x49],
+            &[0x12, 0x41, 0x0D, 0x42],
+            &[0x0D, 0x41],
+            &[0xA0, 0x7E, 0x7A, 0x0E],
+            &[0x0E, 0x12, 0x41, 0x92, 0xA0],
+        ];
+
+        for (sample, expected) in samples.iter().zip(expected) {
+            let normalized = normalize(sample, options, &petscii);
+            assert_eq!(normalized, expected);
+            assert_eq!(normalize(&normalized, options, &petscii), normalized);
+        }
+
+        let shifted = NormalizeOptions {
+            initial_shifted: true,
+            strip_padding: true,
+        };
+        assert_eq!(
+            normalize(&[0x0E, 0x41, 0xA0, 0xE0], shifted, &petscii),
+            [0x41]
+        );
+        let options = NormalizeOptions::default();
+        assert!(normalize(&[0x12, 0x92, 0x8E], options, &petscii).is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//!
+//! Looking inside a PETSCII configuration
+//!
+//! Documentation generators and configuration editors want to know
+//! what a loaded configuration covers without parsing the JSON again:
+//! which tables it has, how full each one is, which characters it
+//! decodes to and which Unicode blocks those characters come from.
+//! A configuration using the lossless-escape overlay, for example,
+//! shows up with characters in the Private Use Area.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{Map, Value};
+
+use crate::petscii::PetsciiConfig;
+
+/// The Unicode blocks the 8-bit character sets map to, with their
+/// first and last code points
+const UNICODE_BLOCKS: [(u32, u32, &str); 15] = [
+    (0x0000, 0x007F, "Basic Latin"),
+    (0x0080, 0x00FF, "Latin-1 Supplement"),
+    (0x0370, 0x03FF, "Greek and Coptic"),
+    (0x2000, 0x206F, "General Punctuation"),
+    (0x2190, 0x21FF, "Arrows"),
+    (0x2200, 0x22FF, "Mathematical Operators"),
+    (0x2500, 0x257F, "Box Drawing"),
+    (0x2580, 0x259F, "Block Elements"),
+    (0x25A0, 0x25FF, "Geometric Shapes"),
+    (0x2600, 0x26FF, "Miscellaneous Symbols"),
+    (0xE000, 0xF8FF, "Private Use Area"),
+    (0x1CC00, 0x1CEBF, "Symbols for Legacy Computing Supplement"),
+    (0x1FB00, 0x1FBFF, "Symbols for Legacy Computing"),
+    (0xF0000, 0xFFFFF, "Supplementary Private Use Area-A"),
+    (0x100000, 0x10FFFF, "Supplementary Private Use Area-B"),
+];
+
+/// The name of the Unicode block a character is in
+///
+/// Only the blocks that the character sets in this crate map to are
+/// known.  Other characters return None.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::coverage::unicode_block;
+///
+/// assert_eq!(unicode_block('A'), Some("Basic Latin"));
+/// assert_eq!(unicode_block('🭰'), Some("Symbols for Legacy Computing"));
+/// assert_eq!(unicode_block('あ'), None);
+/// ```
+pub fn unicode_block(c: char) -> Option<&'static str> {
+    let c = u32::from(c);
+    UNICODE_BLOCKS
+        .iter()
+        .find(|(first, last, _)| (*first..=*last).contains(&c))
+        .map(|(_, _, name)| *name)
+}
+
+/// How much of a table is filled in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableCoverage {
+    /// The name of the table, as it appears in the configuration
+    pub name: &'static str,
+    /// The number of entries in the table
+    pub entries: usize,
+    /// The number of entries the table could have, for tables keyed
+    /// by a byte
+    ///
+    /// The Unicode to screen code table is keyed by a character, so
+    /// it doesn't have a limit.
+    pub possible: Option<usize>,
+}
+
+impl TableCoverage {
+    /// The fraction of possible entries that are filled in
+    pub fn ratio(&self) -> Option<f64> {
+        self.possible
+            .map(|possible| self.entries.min(possible) as f64 / possible as f64)
+    }
+}
+
+impl PetsciiConfig {
+    /// The tables in the configuration, by name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let names: Vec<&str> = petscii.character_set_map.tables().map(|(name, _)| name).collect();
+    /// assert!(names.contains(&"c64_screen_codes_set_1_to_unicode_codes"));
+    /// ```
+    pub fn tables(&self) -> impl Iterator<Item = (&'static str, &Map<String, Value>)> {
+        [
+            ("c64_petscii_code_aliases", &self.c64_petscii_code_aliases),
+            (
+                "c64_petscii_unshifted_code_names",
+                &self.c64_petscii_unshifted_code_names,
+            ),
+            (
+                "c64_petscii_shifted_code_names",
+                &self.c64_petscii_shifted_code_names,
+            ),
+            (
+                "c64_petscii_shifted_codes_to_screen_codes",
+                &self.c64_petscii_shifted_codes_to_screen_codes,
+            ),
+            (
+                "c64_petscii_unshifted_codes_to_screen_codes",
+                &self.c64_petscii_unshifted_codes_to_screen_codes,
+            ),
+            (
+                "c64_screen_codes_set_1_to_unicode_codes",
+                &self.c64_screen_codes_set_1_to_unicode_codes,
+            ),
+            (
+                "c64_screen_codes_set_2_to_unicode_codes",
+                &self.c64_screen_codes_set_2_to_unicode_codes,
+            ),
+            (
+                "c64_screen_codes_set_3_to_unicode_codes",
+                &self.c64_screen_codes_set_3_to_unicode_codes,
+            ),
+            (
+                "unicode_codes_to_c64_screen_codes",
+                &self.unicode_codes_to_c64_screen_codes,
+            ),
+            (
+                "c64_screen_codes_set_1_to_petscii_codes",
+                &self.c64_screen_codes_set_1_to_petscii_codes,
+            ),
+            (
+                "c64_screen_codes_set_2_to_petscii_codes",
+                &self.c64_screen_codes_set_2_to_petscii_codes,
+            ),
+            (
+                "c64_screen_codes_set_3_to_petscii_codes",
+                &self.c64_screen_codes_set_3_to_petscii_codes,
+            ),
+        ]
+        .into_iter()
+    }
+
+    /// How full each table is
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// for table in petscii.character_set_map.coverage() {
+    ///     println!("{}: {} entries", table.name, table.entries);
+    /// }
+    /// ```
+    pub fn coverage(&self) -> Vec<TableCoverage> {
+        self.tables()
+            .map(|(name, table)| TableCoverage {
+                name,
+                entries: table.len(),
+                possible: (!name.starts_with("unicode_codes")).then_some(256),
+            })
+            .collect()
+    }
+
+    /// The characters the screen code tables decode to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let characters = petscii.character_set_map.mapped_characters();
+    /// assert!(characters.contains(&'£'));
+    /// assert!(characters.contains(&'♠'));
+    /// ```
+    pub fn mapped_characters(&self) -> BTreeSet<char> {
+        [
+            &self.c64_screen_codes_set_1_to_unicode_codes,
+            &self.c64_screen_codes_set_2_to_unicode_codes,
+            &self.c64_screen_codes_set_3_to_unicode_codes,
+        ]
+        .into_iter()
+        .flat_map(|table| table.values())
+        .filter_map(|v| v.as_u64().and_then(|v| char::from_u32(v as u32)))
+        .collect()
+    }
+
+    /// The number of mapped characters in each Unicode block
+    ///
+    /// Characters outside the known blocks are counted under "Other".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let blocks = petscii.character_set_map.unicode_blocks();
+    /// assert!(blocks["Symbols for Legacy Computing"] > 0);
+    /// assert!(!blocks.contains_key("Private Use Area"));
+    /// ```
+    pub fn unicode_blocks(&self) -> BTreeMap<&'static str, usize> {
+        let mut blocks = BTreeMap::new();
+        for c in self.mapped_characters() {
+            *blocks
+                .entry(unicode_block(c).unwrap_or("Other"))
+                .or_insert(0) += 1;
+        }
+        blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::coverage::{unicode_block, TableCoverage},
+        Config, SystemConfig,
+    };
+
+    #[test]
+    fn coverage_counts_tables() {
+        let config = Config::load_named("lossless-escape").expect("Error loading config");
+        let coverage = config.coverage().unwrap();
+
+        let aliases = coverage
+            .iter()
+            .find(|table| table.name == "c64_petscii_code_aliases")
+            .unwrap();
+        assert_eq!(aliases.entries, 64);
+        assert_eq!(aliases.ratio(), Some(0.25));
+
+        // The escaped characters are in the Private Use Area
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let blocks = petscii.character_set_map.unicode_blocks();
+        assert!(blocks["Private Use Area"] > 0);
+
+        let unicode = TableCoverage {
+            name: "unicode_codes_to_c64_screen_codes",
+            entries: 10,
+            possible: None,
+        };
+        assert_eq!(unicode.ratio(), None);
+    }
+
+    #[test]
+    fn coverage_finds_blocks() {
+        assert_eq!(unicode_block('┼'), Some("Box Drawing"));
+        assert_eq!(unicode_block('\u{e000}'), Some("Private Use Area"));
+        assert_eq!(
+            unicode_block('\u{1cc00}'),
+            Some("Symbols for Legacy Computing Supplement")
+        );
+    }
+}
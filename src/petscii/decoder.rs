@@ -0,0 +1,914 @@
+//!
+//! The PETSCII decoder state machine
+//!
+//! Decoding PETSCII depends on the codes that came before.  0x0E and
+//! 0x8E switch between the uppercase and lowercase character sets,
+//! 0x12 and 0x92 turn reverse video on and off, and a quote puts the
+//! screen editor in quote mode, where cursor and color codes are
+//! shown instead of acted on.  A carriage return ends reverse video
+//! and quote mode.
+//!
+//! PetsciiDecoderState keeps track of this state and turns each byte
+//! into an event.  Collecting the characters gives the same string
+//! as decoding the bytes, and the other events are there for
+//! terminals and renderers that need to act on them.
+//!
+//! decode_events goes a step further and says what each control code
+//! does, tracking the text color as well.
+//!
+//! Every byte is a complete code, so a stream split into chunks
+//! anywhere, even between a reverse on code and the character it
+//! reverses, decodes the same as the whole stream as long as the
+//! same state is fed all of the chunks.  At the end of the stream,
+//! finish reports the state that was left on.
+//!
+//! PETSCII written out as text by petcat or CBM prg Studio has escapes
+//! like {CLEAR} for the codes without a printable character.  With
+//! with_escapes the decoder reads them as the codes they name.  An
+//! escape is held back until its closing brace arrives, so it can be
+//! split between chunks too, and finish returns an escape the stream
+//! ended in the middle of.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Debug, Formatter};
+
+use crate::{
+    error::{Error, ErrorKind},
+    petscii::{petscii_to_screen_code, screen::Charset, Color, PetsciiCode, PetsciiConfig},
+    trace_decision, DecodeOptions, SystemConfig,
+};
+
+/// A change to the decoder state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeChange {
+    /// The character set changed, true for the lowercase set
+    Shifted(bool),
+    /// Reverse video was turned on or off
+    Reversed(bool),
+}
+
+/// What a byte did when it was fed to the decoder
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeEvent {
+    /// A character to show
+    Char(char),
+    /// The character set or reverse video changed
+    AttributeChange(AttributeChange),
+    /// A control code without a character, like a color or cursor
+    /// movement
+    Control {
+        /// The PETSCII code
+        code: u8,
+        /// True if the code was fed in quote mode, where the C64
+        /// shows control codes as reversed characters instead of
+        /// acting on them
+        quoted: bool,
+    },
+    /// A code that doesn't show anything, like a stripped shifted
+    /// space or a code missing from the character map
+    Nothing,
+}
+
+/// The state left at the end of a stream
+///
+/// None of it is an error by itself, plenty of files end in lowercase,
+/// but a stream that was cut short often ends in reverse video or
+/// quote mode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dangling {
+    /// The lowercase character set is still selected
+    pub shifted: bool,
+    /// Reverse video is still on
+    pub reversed: bool,
+    /// A quoted string wasn't closed
+    pub quoted: bool,
+    /// Input at the end that wasn't a complete character or sequence
+    pub incomplete: Vec<u8>,
+}
+
+impl Dangling {
+    /// True if the stream ended in the start state with nothing left
+    /// over
+    pub fn is_clean(&self) -> bool {
+        *self == Dangling::default()
+    }
+}
+
+/// The state of a PETSCII decoder
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{
+///         decoder::{AttributeChange, DecodeEvent, PetsciiDecoderState},
+///         PetsciiConfig,
+///     },
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let mut state = PetsciiDecoderState::new(Some(&petscii));
+///
+/// assert_eq!(
+///     state.feed(0x0E),
+///     DecodeEvent::AttributeChange(AttributeChange::Shifted(true))
+/// );
+/// assert_eq!(state.feed(0x41), DecodeEvent::Char('a'));
+/// assert_eq!(state.feed(0x1C), DecodeEvent::Control { code: 0x1C, quoted: false });
+/// assert!(state.is_shifted());
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct PetsciiDecoderState<'a> {
+    character_map: Option<&'a SystemConfig>,
+    strip_shifted_space: bool,
+    shifted: bool,
+    reversed: bool,
+    quoted: bool,
+    substituted: bool,
+    escapes: bool,
+    /// The escape read so far, from the opening brace
+    escape: [u8; MAX_ESCAPE_LEN],
+    /// The length of the escape read so far, if inside one
+    escape_len: Option<usize>,
+}
+
+/// The longest escape, with its braces, the decoder reads
+pub const MAX_ESCAPE_LEN: usize = 32;
+
+impl<'a> Debug for PetsciiDecoderState<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PetsciiDecoderState")
+            .field("shifted", &self.shifted)
+            .field("reversed", &self.reversed)
+            .field("quoted", &self.quoted)
+            .field("substituted", &self.substituted)
+            .field("escape", &self.pending_escape())
+            .finish()
+    }
+}
+
+impl<'a> PetsciiDecoderState<'a> {
+    /// Create a decoder in the unshifted, unreversed state
+    ///
+    /// Without a character map, bytes are decoded to the Unicode
+    /// characters with the same value.
+    pub fn new(character_map: Option<&'a SystemConfig>) -> Self {
+        PetsciiDecoderState {
+            character_map,
+            ..PetsciiDecoderState::default()
+        }
+    }
+
+    /// Set whether shifted spaces (0xA0) are dropped, the way padding
+    /// in directory names is
+    pub fn with_strip_shifted_space(mut self, strip_shifted_space: bool) -> Self {
+        self.strip_shifted_space = strip_shifted_space;
+        self
+    }
+
+    /// Start in the shifted (lowercase) or unshifted character set
+    pub fn with_shifted(mut self, shifted: bool) -> Self {
+        self.shifted = shifted;
+        self
+    }
+
+    /// Start with reverse video on or off
+    pub fn with_reversed(mut self, reversed: bool) -> Self {
+        self.reversed = reversed;
+        self
+    }
+
+    /// Set whether escapes in braces, like {CLEAR} or {CBM-A}, are read
+    /// as the codes they name
+    ///
+    /// The names are the ones cbm::prg_studio reads.  An escape with a
+    /// name that isn't known, or longer than MAX_ESCAPE_LEN, decodes
+    /// to nothing.  Escapes need a character map for the names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{decoder::PetsciiDecoderState, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut state = PetsciiDecoderState::new(Some(&petscii)).with_escapes(true);
+    /// let mut text = state.decode_chunk(b"{LOWER");
+    /// text += &state.decode_chunk(b"CASE}HI{CBM-");
+    ///
+    /// assert_eq!(text, "hi");
+    /// assert_eq!(state.finish().incomplete, b"{CBM-");
+    /// ```
+    pub fn with_escapes(mut self, escapes: bool) -> Self {
+        self.escapes = escapes;
+        self
+    }
+
+    /// The same state decoding with another character map
+    ///
+    /// This lets a decoder that owns its configuration keep the state
+    /// between uses without borrowing from itself.
+    pub fn with_character_map<'b>(
+        self,
+        character_map: Option<&'b SystemConfig>,
+    ) -> PetsciiDecoderState<'b> {
+        PetsciiDecoderState {
+            character_map,
+            strip_shifted_space: self.strip_shifted_space,
+            shifted: self.shifted,
+            reversed: self.reversed,
+            quoted: self.quoted,
+            substituted: self.substituted,
+            escapes: self.escapes,
+            escape: self.escape,
+            escape_len: self.escape_len,
+        }
+    }
+
+    /// True if the lowercase character set is selected
+    pub fn is_shifted(&self) -> bool {
+        self.shifted
+    }
+
+    /// True if reverse video is on
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    /// True if the last character decoded is a stand-in for the one
+    /// the screen code shows
+    ///
+    /// Reversed characters without a Unicode character of their own
+    /// are decoded as the unreversed character, and screen codes
+    /// without a character as the code they duplicate.
+    pub fn substituted(&self) -> bool {
+        self.substituted
+    }
+
+    /// True if the decoder is inside a quoted string
+    pub fn in_quote_mode(&self) -> bool {
+        self.quoted
+    }
+
+    /// The part of an escape read so far, if the decoder is inside
+    /// one
+    pub fn pending_escape(&self) -> Option<&[u8]> {
+        self.escape_len.map(|len| &self.escape[..len])
+    }
+
+    /// Go back to the unshifted, unreversed state outside quotes,
+    /// dropping any partial escape
+    pub fn reset(&mut self) {
+        self.shifted = false;
+        self.reversed = false;
+        self.quoted = false;
+        self.escape_len = None;
+    }
+
+    /// Decode a chunk of a stream, keeping the state for the next one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{decoder::PetsciiDecoderState, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut state = PetsciiDecoderState::new(Some(&petscii));
+    /// let mut text = state.decode_chunk(&[0x48, 0x0e]);
+    /// text += &state.decode_chunk(&[0x49]);
+    ///
+    /// assert_eq!(text, "Hi");
+    /// assert!(state.finish().shifted);
+    /// ```
+    pub fn decode_chunk(&mut self, bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .filter_map(|b| match self.feed(*b) {
+                DecodeEvent::Char(c) => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// End the stream, returning the state that was left on and
+    /// going back to the start state
+    pub fn finish(&mut self) -> Dangling {
+        let dangling = Dangling {
+            shifted: self.shifted,
+            reversed: self.reversed,
+            quoted: self.quoted,
+            incomplete: self.pending_escape().unwrap_or_default().to_vec(),
+        };
+        self.reset();
+        dangling
+    }
+
+    /// Decode a byte, updating the state
+    ///
+    /// Codes with a configuration entry that isn't a screen code
+    /// decode to nothing.  try_feed returns an error for them instead.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn feed(&mut self, c: u8) -> DecodeEvent {
+        match self.try_feed(c) {
+            Ok(event) => event,
+            Err(e) => {
+                trace_decision!(code = c, error = %e, "dropping code with a bad configuration entry");
+                DecodeEvent::Nothing
+            }
+        }
+    }
+
+    /// Decode a byte, updating the state, or return an error if the
+    /// character map has an entry for it that isn't a screen code
+    ///
+    /// Screen codes above 127 are reversed characters.  A character
+    /// map can give them for a PETSCII code, and they stay reversed in
+    /// reverse video mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{decoder::{DecodeEvent, PetsciiDecoderState}, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    /// use serde_json::json;
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let mut petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    /// let map = &mut petscii.character_set_map;
+    /// map.c64_petscii_unshifted_codes_to_screen_codes
+    ///     .insert(String::from("65"), json!([9, 1]));
+    /// map.c64_petscii_unshifted_codes_to_screen_codes
+    ///     .insert(String::from("66"), json!([1, 220]));
+    ///
+    /// let mut state = PetsciiDecoderState::new(Some(&petscii));
+    /// assert!(state.try_feed(0x41).is_err());
+    /// assert_eq!(state.try_feed(0x42).ok(), Some(DecodeEvent::Char('\u{1fb94}')));
+    /// ```
+    pub fn try_feed(&mut self, c: u8) -> std::result::Result<DecodeEvent, Error> {
+        self.substituted = false;
+
+        if let Some(len) = self.escape_len {
+            return self.feed_escape(c, len);
+        }
+        if self.escapes && c == b'{' && self.character_map.is_some() {
+            self.escape[0] = c;
+            self.escape_len = Some(1);
+            return Ok(DecodeEvent::Nothing);
+        }
+
+        self.decode_code(c)
+    }
+
+    /// Add a byte to the escape being read, decoding the code it
+    /// names when it's closed
+    fn feed_escape(&mut self, c: u8, len: usize) -> std::result::Result<DecodeEvent, Error> {
+        if c != b'}' {
+            if len + 1 == MAX_ESCAPE_LEN {
+                self.escape_len = None;
+                return Err(Error::new(ErrorKind::Message(format!(
+                    "Escape longer than {MAX_ESCAPE_LEN} bytes"
+                ))));
+            }
+            self.escape[len] = c;
+            self.escape_len = Some(len + 1);
+            return Ok(DecodeEvent::Nothing);
+        }
+
+        self.escape_len = None;
+        let name = String::from_utf8_lossy(&self.escape[1..len]).into_owned();
+        let code = self
+            .character_map
+            .and_then(|cm| crate::cbm::prg_studio::escape_code(&name, cm))
+            .ok_or_else(|| Error::new(ErrorKind::Message(format!("Unknown escape {{{name}}}"))))?;
+        self.decode_code(code)
+    }
+
+    /// Decode a PETSCII code, updating the state
+    fn decode_code(&mut self, c: u8) -> std::result::Result<DecodeEvent, Error> {
+        if self.strip_shifted_space && c == 0xA0 {
+            trace_decision!("stripping shifted space");
+            return Ok(DecodeEvent::Nothing);
+        }
+
+        match c {
+            // Switch to lowercase / shifted.  This is the "shifted"
+            // state on the C64.  Unshifted is an uppercase and
+            // graphic character set.
+            0x0E => {
+                self.shifted = true;
+                return Ok(DecodeEvent::AttributeChange(AttributeChange::Shifted(true)));
+            }
+            0x12 => {
+                self.reversed = true;
+                return Ok(DecodeEvent::AttributeChange(AttributeChange::Reversed(
+                    true,
+                )));
+            }
+            // Switch to uppercase / unshifted.  Shifted is a
+            // lowercase and uppercase character set (business mode).
+            0x8E => {
+                self.shifted = false;
+                return Ok(DecodeEvent::AttributeChange(AttributeChange::Shifted(
+                    false,
+                )));
+            }
+            0x92 => {
+                self.reversed = false;
+                return Ok(DecodeEvent::AttributeChange(AttributeChange::Reversed(
+                    false,
+                )));
+            }
+            0x22 => self.quoted = !self.quoted,
+            0x0D | 0x8D => {
+                self.reversed = false;
+                self.quoted = false;
+            }
+            _ => {}
+        }
+
+        let cm = match self.character_map {
+            Some(cm) => cm,
+            None => return Ok(DecodeEvent::Char(char::from(c))),
+        };
+
+        // Map from PETSCII to screen codes, in the unshifted or
+        // shifted character set
+        let screen_code =
+            match PetsciiCode(c).try_to_screen_code(self.shifted, &cm.character_set_map)? {
+                Some(screen_code) => screen_code,
+                None if PetsciiCode(c).is_control() => {
+                    return Ok(DecodeEvent::Control {
+                        code: c,
+                        quoted: self.quoted,
+                    })
+                }
+                None => {
+                    trace_decision!(
+                        code = c,
+                        shifted = self.shifted,
+                        "dropping code without a screen code"
+                    );
+                    return Ok(DecodeEvent::Nothing);
+                }
+            };
+
+        let reversed_screen_code = self.reversed.then(|| screen_code.reversed());
+
+        // Now map from screen codes to Unicode.  Reversed codes
+        // without a character of their own are shown as the
+        // unreversed character, and codes without a character are
+        // shown as the PETSCII code they duplicate.
+        let map = &cm.character_set_map;
+        let reversed_char = reversed_screen_code.and_then(|reversed| reversed.to_unicode(map));
+        if reversed_screen_code.is_some() && reversed_char.is_none() {
+            trace_decision!(code = c, "no reversed character, decoding as unreversed");
+            self.substituted = true;
+        }
+        let d = match reversed_char.or_else(|| screen_code.to_unicode(map)) {
+            Some(d) => u32::from(d),
+            None => {
+                let canonical = map.canonicalize_code(c);
+                trace_decision!(
+                    code = c,
+                    canonical,
+                    "screen code without a character, decoding as the code it duplicates"
+                );
+                self.substituted = true;
+                canonical as u32
+            }
+        };
+
+        Ok(char::from_u32(d).map_or(DecodeEvent::Nothing, DecodeEvent::Char))
+    }
+}
+
+/// What a PETSCII stream does to the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PetsciiEvent {
+    /// Print a character at the cursor
+    PrintChar {
+        /// The character
+        c: char,
+        /// The text color
+        color: Color,
+        /// True if reverse video is on.  The character is already the
+        /// reversed one when Unicode has it.
+        reversed: bool,
+    },
+    /// Change the text color
+    SetColor(Color),
+    /// Clear the screen and move the cursor home
+    ClearScreen,
+    /// Move the cursor to the top left corner
+    Home,
+    /// Move the cursor
+    CursorMove {
+        /// Columns to the right
+        dx: i8,
+        /// Rows down
+        dy: i8,
+    },
+    /// Move to the start of the next line, ending reverse video
+    NewLine,
+    /// Switch the character set shown on the whole screen
+    CharsetSwitch(Charset),
+    /// Turn reverse video on or off
+    Reverse(bool),
+    /// Delete the character to the left of the cursor
+    Delete,
+    /// Insert a space at the cursor
+    Insert,
+    /// Any other control code, like the bell or disabling the
+    /// character set switch
+    Control(u8),
+}
+
+/// The reversed character the screen editor shows for a control code
+/// in quote mode
+pub(crate) fn quoted_char(code: u8, shifted: bool, map: &PetsciiConfig) -> Option<char> {
+    // Control codes show the character 0x40 above them
+    let screen_code = petscii_to_screen_code(code.wrapping_add(0x40), shifted, map)?;
+
+    screen_code
+        .reversed()
+        .to_unicode(map)
+        .or_else(|| screen_code.to_unicode(map))
+}
+
+/// Decode PETSCII bytes into the events a screen would act on
+///
+/// Decoding starts in the uppercase character set with light blue
+/// text, the state the C64 starts in.  In quote mode, control codes
+/// other than carriage return are printed as reversed characters
+/// like the screen editor shows them.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{
+///         decoder::{decode_events, PetsciiEvent},
+///         Color, PetsciiConfig,
+///     },
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let events = decode_events(&[0x93, 0x1C, 0x48, 0x11], &petscii);
+///
+/// assert_eq!(
+///     events,
+///     vec![
+///         PetsciiEvent::ClearScreen,
+///         PetsciiEvent::SetColor(Color::Red),
+///         PetsciiEvent::PrintChar { c: 'H', color: Color::Red, reversed: false },
+///         PetsciiEvent::CursorMove { dx: 0, dy: 1 },
+///     ]
+/// );
+/// ```
+pub fn decode_events(bytes: &[u8], character_map: &SystemConfig) -> Vec<PetsciiEvent> {
+    let mut decoder = PetsciiEventDecoder::new(character_map);
+    bytes.iter().filter_map(|b| decoder.feed(*b)).collect()
+}
+
+/// Decodes PETSCII into screen events a byte at a time
+///
+/// The state is kept between bytes, so a stream can be decoded in
+/// pieces as it arrives.  decode_events decodes a whole buffer.
+#[derive(Clone, Copy, Debug)]
+pub struct PetsciiEventDecoder<'a> {
+    state: PetsciiDecoderState<'a>,
+    color: Color,
+}
+
+impl<'a> PetsciiEventDecoder<'a> {
+    /// Create a decoder in the state the C64 starts in
+    pub fn new(character_map: &'a SystemConfig) -> PetsciiEventDecoder<'a> {
+        PetsciiEventDecoder {
+            state: PetsciiDecoderState::new(Some(character_map)),
+            color: Color::LightBlue,
+        }
+    }
+
+    /// Create a decoder in the initial state of decoding options
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{decoder::{PetsciiEvent, PetsciiEventDecoder}, Color, PetsciiConfig},
+    ///     Configuration, DecodeOptions, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let options = DecodeOptions::default()
+    ///     .with_initial_shifted(true)
+    ///     .with_initial_color(Some(Color::White));
+    /// let mut decoder = PetsciiEventDecoder::with_options(&petscii, &options);
+    ///
+    /// assert_eq!(
+    ///     decoder.feed(0x41),
+    ///     Some(PetsciiEvent::PrintChar { c: 'a', color: Color::White, reversed: false })
+    /// );
+    /// ```
+    pub fn with_options(
+        character_map: &'a SystemConfig,
+        options: &DecodeOptions,
+    ) -> PetsciiEventDecoder<'a> {
+        PetsciiEventDecoder {
+            state: PetsciiDecoderState::new(Some(character_map))
+                .with_shifted(options.initial_shifted)
+                .with_reversed(options.initial_reversed),
+            color: options.initial_color.unwrap_or(Color::LightBlue),
+        }
+    }
+
+    /// The character set, reverse video and quote mode state
+    pub fn state(&self) -> &PetsciiDecoderState<'a> {
+        &self.state
+    }
+
+    /// The current text color
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// End the stream, returning the state that was left on and
+    /// going back to the start state
+    ///
+    /// The text color isn't reported or reset, every color is a
+    /// normal place for a stream to end.
+    pub fn finish(&mut self) -> Dangling {
+        self.state.finish()
+    }
+
+    /// Decode a byte, returning None for bytes that don't do anything
+    pub fn feed(&mut self, b: u8) -> Option<PetsciiEvent> {
+        let event = match (b, self.state.feed(b)) {
+            (0x0D | 0x8D, _) => PetsciiEvent::NewLine,
+            (0x0A, _) => PetsciiEvent::Control(0x0A),
+            (_, DecodeEvent::Char(c)) => PetsciiEvent::PrintChar {
+                c,
+                color: self.color,
+                reversed: self.state.is_reversed(),
+            },
+            (_, DecodeEvent::AttributeChange(AttributeChange::Shifted(shifted))) => {
+                PetsciiEvent::CharsetSwitch(if shifted {
+                    Charset::Lowercase
+                } else {
+                    Charset::Uppercase
+                })
+            }
+            (_, DecodeEvent::AttributeChange(AttributeChange::Reversed(reversed))) => {
+                PetsciiEvent::Reverse(reversed)
+            }
+            (_, DecodeEvent::Control { code, quoted: true }) => PetsciiEvent::PrintChar {
+                c: quoted_char(
+                    code,
+                    self.state.shifted,
+                    &self.state.character_map?.character_set_map,
+                )?,
+                color: self.color,
+                reversed: true,
+            },
+            (_, DecodeEvent::Control { code, .. }) => match code {
+                0x93 => PetsciiEvent::ClearScreen,
+                0x13 => PetsciiEvent::Home,
+                0x11 => PetsciiEvent::CursorMove { dx: 0, dy: 1 },
+                0x91 => PetsciiEvent::CursorMove { dx: 0, dy: -1 },
+                0x1D => PetsciiEvent::CursorMove { dx: 1, dy: 0 },
+                0x9D => PetsciiEvent::CursorMove { dx: -1, dy: 0 },
+                0x14 => PetsciiEvent::Delete,
+                0x94 => PetsciiEvent::Insert,
+                code => match Color::from_control_code(code) {
+                    Some(new_color) => {
+                        self.color = new_color;
+                        PetsciiEvent::SetColor(new_color)
+                    }
+                    None => PetsciiEvent::Control(code),
+                },
+            },
+            (_, DecodeEvent::Nothing) => return None,
+        };
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            decoder::{
+                decode_events, AttributeChange, DecodeEvent, PetsciiDecoderState, PetsciiEvent,
+                MAX_ESCAPE_LEN,
+            },
+            screen::Charset,
+            Color, PetsciiConfig,
+        },
+        Configuration, SystemConfig,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn decoder_quote_mode_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut state = PetsciiDecoderState::new(Some(&petscii));
+        state.feed(0x22);
+        assert!(state.in_quote_mode());
+        assert_eq!(
+            state.feed(0x93),
+            DecodeEvent::Control {
+                code: 0x93,
+                quoted: true
+            }
+        );
+        state.feed(0x12);
+        assert!(state.is_reversed());
+
+        // A carriage return ends quote mode and reverse video
+        state.feed(0x0D);
+        assert!(!state.in_quote_mode());
+        assert!(!state.is_reversed());
+    }
+
+    #[test]
+    fn decoder_strip_shifted_space_works() {
+        let mut state = PetsciiDecoderState::new(None).with_strip_shifted_space(true);
+
+        assert_eq!(state.feed(0xA0), DecodeEvent::Nothing);
+        assert_eq!(state.feed(0x41), DecodeEvent::Char('A'));
+        assert_eq!(
+            state.feed(0x92),
+            DecodeEvent::AttributeChange(AttributeChange::Reversed(false))
+        );
+    }
+
+    #[test]
+    fn decoder_events_quote_mode_prints_controls() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // PRINT "<clear>" followed by a lowercase line
+        let events = decode_events(&[0x22, 0x93, 0x22, 0x0D, 0x0E, 0x05, 0x41], &petscii);
+        assert!(matches!(
+            events[1],
+            PetsciiEvent::PrintChar { reversed: true, .. }
+        ));
+        assert_eq!(events[3], PetsciiEvent::NewLine);
+        assert_eq!(events[4], PetsciiEvent::CharsetSwitch(Charset::Lowercase));
+        assert_eq!(
+            events[6],
+            PetsciiEvent::PrintChar {
+                c: 'a',
+                color: Color::White,
+                reversed: false
+            }
+        );
+    }
+
+    #[test]
+    fn decoder_high_screen_codes_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let mut petscii = config.system::<SystemConfig>().unwrap();
+
+        // A table with reversed screen codes in it, like a full 256
+        // entry table
+        petscii
+            .character_set_map
+            .c64_petscii_unshifted_codes_to_screen_codes
+            .insert(String::from("66"), json!([1, 220]));
+        assert!(petscii.character_set_map.validate().is_ok());
+
+        let mut state = PetsciiDecoderState::new(Some(&petscii));
+        assert_eq!(state.feed(0x42), DecodeEvent::Char('\u{1fb94}'));
+        state.feed(0x12);
+        assert_eq!(state.feed(0x42), DecodeEvent::Char('\u{1fb94}'));
+        assert_eq!(state.feed(0x7C), DecodeEvent::Char('\u{1fb94}'));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn decoder_traces_dropped_codes() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        use tracing::{span, Event, Metadata, Subscriber};
+
+        use crate::CharacterSet;
+
+        /// Counts the events and ignores spans
+        struct CountEvents(Arc<AtomicUsize>);
+
+        impl Subscriber for CountEvents {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, _: &Event<'_>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let events = Arc::new(AtomicUsize::new(0));
+        tracing::subscriber::with_default(CountEvents(events.clone()), || {
+            // The clear screen and bell control codes are dropped
+            assert_eq!(petscii.decode(&[0x93, 0x41, 0x07]), "A");
+        });
+        assert_eq!(events.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn decoder_chunks_split_anywhere() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let bytes = [0x12, 0x41, 0x0e, 0x22, 0x42, 0x92, 0x43, 0x0d, 0x12, 0x44];
+        let whole = PetsciiDecoderState::new(Some(&petscii)).decode_chunk(&bytes);
+        for split in 0..=bytes.len() {
+            let mut state = PetsciiDecoderState::new(Some(&petscii));
+            let mut text = state.decode_chunk(&bytes[..split]);
+            text += &state.decode_chunk(&bytes[split..]);
+            assert_eq!(text, whole, "{split}");
+
+            let dangling = state.finish();
+            assert!(dangling.shifted && dangling.reversed && !dangling.quoted);
+            assert!(state.finish().is_clean());
+        }
+    }
+
+    #[test]
+    fn decoder_escapes_split_anywhere() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let text = b"{CLEAR}{REVERSE ON}HI{LOWERCASE}A{CBM-A}";
+        let whole = PetsciiDecoderState::new(Some(&petscii))
+            .with_escapes(true)
+            .decode_chunk(text);
+        let bytes = PetsciiDecoderState::new(Some(&petscii)).decode_chunk(b"\x93\x12HI\x0eA\xb0");
+        assert_eq!(whole, bytes);
+
+        for split in 0..=text.len() {
+            let mut state = PetsciiDecoderState::new(Some(&petscii)).with_escapes(true);
+            let mut decoded = state.decode_chunk(&text[..split]);
+
+            // A split inside an escape holds back the part read so far
+            let open = text[..split].iter().rposition(|b| *b == b'{');
+            let close = text[..split].iter().rposition(|b| *b == b'}');
+            let pending = match (open, close) {
+                (Some(open), Some(close)) if open < close => None,
+                (Some(open), _) => Some(&text[open..split]),
+                _ => None,
+            };
+            assert_eq!(state.pending_escape(), pending, "{split}");
+            assert_eq!(
+                state.clone().finish().incomplete,
+                pending.unwrap_or_default()
+            );
+
+            decoded += &state.decode_chunk(&text[split..]);
+            assert_eq!(decoded, whole, "{split}");
+            assert!(state.finish().incomplete.is_empty());
+        }
+
+        // Unknown and unfinished escapes
+        let mut state = PetsciiDecoderState::new(Some(&petscii)).with_escapes(true);
+        assert!(state.try_feed(b'{').is_ok());
+        assert!(state.try_feed(b'X').is_ok());
+        assert!(state.try_feed(b'}').is_err());
+        assert_eq!(state.decode_chunk(&[b'{'; MAX_ESCAPE_LEN]), "");
+        assert!(state.pending_escape().is_none());
+    }
+}
@@ -0,0 +1,150 @@
+//!
+//! Describing PETSCII codes
+//!
+//! Debuggers, hex viewers and configuration editors show what a byte
+//! is, not just the character it decodes to.  describe_code collects
+//! everything the configuration knows about a code: its name, the
+//! screen code it prints, the Unicode character for that, the
+//! reversed character the screen editor shows for it in quote mode
+//! and the code it duplicates.
+//!
+//! The names come from the c64_petscii_unshifted_code_names and
+//! c64_petscii_shifted_code_names tables.  Printable codes are named
+//! after their Unicode character, with "(CBM)" after the graphics
+//! characters, and control codes after their key or function.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Display, Formatter};
+
+use crate::petscii::{decoder::quoted_char, PetsciiCode, PetsciiConfig, ScreenCode};
+
+/// What the configuration knows about a PETSCII code
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeInfo {
+    /// The code
+    pub code: PetsciiCode,
+    /// True if this describes the code in the shifted (lowercase)
+    /// character set
+    pub shifted: bool,
+    /// The name of the code, if it has one
+    pub name: Option<String>,
+    /// The Unicode character the code decodes to
+    pub unicode: Option<char>,
+    /// The screen code the code prints
+    pub screen_code: Option<ScreenCode>,
+    /// The reversed character shown for a control code in quote mode
+    pub quoted_glyph: Option<char>,
+    /// The code this code is a duplicate of
+    pub duplicate_of: Option<PetsciiCode>,
+}
+
+impl Display for CodeInfo {
+    /// The code in hex, its character and its name
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${:02X}", self.code.0)?;
+        match self.unicode {
+            Some(c) if !c.is_control() => write!(f, " {c}")?,
+            _ => {}
+        }
+        if let Some(name) = &self.name {
+            write!(f, " {name}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PetsciiConfig {
+    /// The name of a PETSCII code in the shifted or unshifted
+    /// character set
+    ///
+    /// Shifted codes without a name of their own print the same as
+    /// in the unshifted set and have its name.
+    pub fn code_name(&self, code: u8, shifted: bool) -> Option<&str> {
+        let key = code.to_string();
+        shifted
+            .then(|| self.c64_petscii_shifted_code_names.get(&key))
+            .flatten()
+            .or_else(|| self.c64_petscii_unshifted_code_names.get(&key))
+            .and_then(|name| name.as_str())
+    }
+
+    /// Describe a PETSCII code in the shifted or unshifted character
+    /// set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{PetsciiCode, PetsciiConfig, ScreenCode, ScreenSet},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    /// let map = &petscii.character_set_map;
+    ///
+    /// let info = map.describe_code(0xB0, false);
+    /// assert_eq!(
+    ///     info.name.as_deref(),
+    ///     Some("BOX DRAWINGS LIGHT DOWN AND RIGHT (CBM)")
+    /// );
+    /// assert_eq!(info.unicode, Some('┌'));
+    /// assert_eq!(info.screen_code, Some(ScreenCode { set: ScreenSet::Set1, value: 112 }));
+    ///
+    /// let info = map.describe_code(0x93, false);
+    /// assert_eq!(info.name.as_deref(), Some("CLEAR"));
+    /// assert_eq!(info.unicode, None);
+    /// assert_eq!(info.quoted_glyph, Some('♡'));
+    ///
+    /// let info = map.describe_code(0xC1, true);
+    /// assert_eq!(info.to_string(), "$C1 A LATIN CAPITAL LETTER A");
+    /// assert_eq!(info.duplicate_of, Some(PetsciiCode(0x61)));
+    /// ```
+    pub fn describe_code(&self, code: u8, shifted: bool) -> CodeInfo {
+        let screen_code = PetsciiCode(code).to_screen_code(shifted, self);
+        let canonical = self.canonicalize_code(code);
+
+        CodeInfo {
+            code: PetsciiCode(code),
+            shifted,
+            name: self.code_name(code, shifted).map(String::from),
+            unicode: screen_code.and_then(|screen_code| screen_code.to_unicode(self)),
+            screen_code,
+            quoted_glyph: (PetsciiCode(code).is_control() && screen_code.is_none())
+                .then(|| quoted_char(code, shifted, self))
+                .flatten(),
+            duplicate_of: (canonical != code).then_some(PetsciiCode(canonical)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{PetsciiConfig, ScreenSet},
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn describe_names_every_printable_code() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let map = &petscii.character_set_map;
+
+        for code in 0..=255u8 {
+            for shifted in [false, true] {
+                let info = map.describe_code(code, shifted);
+                if info.unicode.is_some() {
+                    assert!(info.name.is_some(), "{code:#04x} {shifted}");
+                }
+            }
+        }
+
+        let info = map.describe_code(0x41, true);
+        assert_eq!(info.name.as_deref(), Some("LATIN SMALL LETTER A"));
+        assert_eq!(info.screen_code.map(|s| s.set), Some(ScreenSet::Set2));
+        assert_eq!(map.code_name(0x30, true), Some("DIGIT ZERO"));
+        assert_eq!(map.code_name(0x00, false), None);
+    }
+}
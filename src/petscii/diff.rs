@@ -0,0 +1,307 @@
+//!
+//! Comparing PETSCII buffers
+//!
+//! A hex dump diff shows which bytes changed but not what they mean,
+//! and a diff of the decoded text hides control codes and the
+//! character set each byte was read in.  diff compares two buffers
+//! byte by byte and reports each run of changes with both: the code
+//! in hex, the character or control code name it decodes to, and
+//! whether the decoder was shifted, reversed or in quote mode when it
+//! got there.
+//!
+//! ```text
+//! - $0003 $41 a [shifted]
+//! + $0003 $C1 A [shifted]
+//! ```
+//!
+//! It's meant for checking that an encode and decode round trip, or
+//! a repair to a disk image, changed only what it was meant to.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    petscii::decoder::{DecodeEvent, PetsciiDecoderState},
+    SystemConfig,
+};
+
+/// A byte in one of the compared buffers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffByte {
+    /// The offset of the byte in its buffer
+    pub offset: usize,
+    /// The PETSCII code
+    pub code: u8,
+    /// The character the code decodes to
+    pub character: Option<char>,
+    /// The name of a code that doesn't decode to a character, like
+    /// "CLEAR"
+    pub name: Option<String>,
+    /// The shifted (lowercase) character set was selected
+    pub shifted: bool,
+    /// Reverse video was on
+    pub reversed: bool,
+    /// The byte was inside a quoted string
+    pub quoted: bool,
+}
+
+impl Display for DiffByte {
+    /// The offset and code in hex, then the character and the state
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${:04X} ${:02X} ", self.offset, self.code)?;
+        match (self.character, &self.name) {
+            (Some(c), _) => write!(f, "{c}")?,
+            (None, Some(name)) => write!(f, "{{{name}}}")?,
+            (None, None) => write!(f, "{{${:02X}}}", self.code)?,
+        }
+
+        let state: Vec<&str> = [
+            (self.shifted, "shifted"),
+            (self.reversed, "reversed"),
+            (self.quoted, "quoted"),
+        ]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+        if !state.is_empty() {
+            write!(f, " [{}]", state.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// A run of bytes that differ between the buffers
+///
+/// Bytes only in the old buffer were removed, bytes only in the new
+/// one were added, and a run with both was changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference {
+    /// Where the run starts in the old buffer
+    pub old_offset: usize,
+    /// Where the run starts in the new buffer
+    pub new_offset: usize,
+    /// The bytes from the old buffer
+    pub old: Vec<DiffByte>,
+    /// The bytes from the new buffer
+    pub new: Vec<DiffByte>,
+}
+
+impl Display for Difference {
+    /// A line for each byte, starting with - for the old bytes and +
+    /// for the new ones
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let old = self.old.iter().map(|b| ('-', b));
+        let new = self.new.iter().map(|b| ('+', b));
+        for (i, (sign, b)) in old.chain(new).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{sign} {b}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two PETSCII buffers
+///
+/// The buffers are aligned on their longest common sequence of bytes,
+/// so an added shift code only shows up once instead of moving every
+/// byte after it.  Both buffers are decoded from the start state.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{petscii::{diff::diff, PetsciiConfig}, Configuration, SystemConfig};
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let differences = diff(&[0x0e, 0x48, 0x49, 0x93], &[0x0e, 0xc8, 0x49, 0x93], &petscii);
+///
+/// assert_eq!(differences.len(), 1);
+/// assert_eq!(
+///     differences[0].to_string(),
+///     "- $0001 $48 h [shifted]\n+ $0001 $C8 H [shifted]"
+/// );
+///
+/// let differences = diff(&[0x41], &[0x41, 0x93], &petscii);
+/// assert_eq!(differences[0].to_string(), "+ $0001 $93 {CLEAR}");
+/// ```
+pub fn diff(old: &[u8], new: &[u8], petscii: &SystemConfig) -> Vec<Difference> {
+    let old_bytes = describe_bytes(old, petscii);
+    let new_bytes = describe_bytes(new, petscii);
+
+    let mut differences = Vec::new();
+    let mut current: Option<Difference> = None;
+    let (mut i, mut j) = (0, 0);
+
+    for edit in edits(old, new) {
+        if edit == Edit::Equal {
+            differences.extend(current.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        let difference = current.get_or_insert_with(|| Difference {
+            old_offset: i,
+            new_offset: j,
+            old: Vec::new(),
+            new: Vec::new(),
+        });
+        if edit == Edit::Remove {
+            difference.old.push(old_bytes[i].clone());
+            i += 1;
+        } else {
+            difference.new.push(new_bytes[j].clone());
+            j += 1;
+        }
+    }
+    differences.extend(current);
+
+    differences
+}
+
+/// Decode a buffer, keeping the state each byte was read in
+fn describe_bytes(bytes: &[u8], petscii: &SystemConfig) -> Vec<DiffByte> {
+    let mut state = PetsciiDecoderState::new(Some(petscii));
+
+    bytes
+        .iter()
+        .enumerate()
+        .map(|(offset, code)| {
+            let (shifted, reversed, quoted) = (
+                state.is_shifted(),
+                state.is_reversed(),
+                state.in_quote_mode(),
+            );
+            let character = match state.feed(*code) {
+                DecodeEvent::Char(c) => Some(c),
+                _ => None,
+            };
+            DiffByte {
+                offset,
+                code: *code,
+                character,
+                name: character
+                    .is_none()
+                    .then(|| petscii.character_set_map.code_name(*code, shifted))
+                    .flatten()
+                    .map(String::from),
+                shifted,
+                reversed,
+                quoted,
+            }
+        })
+        .collect()
+}
+
+/// A step from the old buffer to the new one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    Equal,
+    Remove,
+    Insert,
+}
+
+/// The shortest list of edits turning old into new
+///
+/// This is Myers' algorithm.  Only the diagonals reached at each
+/// step are saved for the backtrack, so memory grows with the square
+/// of the number of differences rather than with the buffer sizes.
+fn edits(old: &[u8], new: &[u8]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let index = |k: isize| (k + max + 1) as usize;
+
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(v[index(-d - 1)..=index(d + 1)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, saved) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let get = |k: isize| saved[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == prev_x {
+                Edit::Insert
+            } else {
+                Edit::Remove
+            });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{diff::diff, PetsciiConfig},
+        CharacterSet, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn diff_aligns_inserted_and_removed_bytes() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let old = petscii.encode("HELLO WORLD");
+        assert!(diff(&old, &old, &petscii).is_empty());
+
+        // An extra reverse on code, and a letter dropped
+        let new = petscii.encode("HELLO WRLD");
+        let new = [&[0x12][..], &new].concat();
+        let differences = diff(&old, &new, &petscii);
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].to_string(), "+ $0000 $12 {REVERSE ON}");
+        assert_eq!(differences[1].old_offset, 7);
+        assert_eq!(differences[1].new_offset, 8);
+        assert_eq!(differences[1].to_string(), "- $0007 $4F O");
+
+        assert_eq!(
+            diff(&[0x12, 0x41], &[0x12, 0x42], &petscii)[0].to_string(),
+            "- $0001 $41 A [reversed]\n+ $0001 $42 B [reversed]"
+        );
+
+        assert_eq!(diff(&[], &old, &petscii)[0].new.len(), old.len());
+    }
+}
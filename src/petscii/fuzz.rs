@@ -0,0 +1,83 @@
+//!
+//! Generating inputs for fuzzers and property tests
+//!
+//! With the arbitrary feature, the types a caller hands to the
+//! decoder implement Arbitrary: DecodeOptions, the colors, and the
+//! screen model.  Most of them derive it, and the two with invariants
+//! are implemented here so every generated value is one the library
+//! could have made:
+//!
+//! * A PetsciiString's length is never past the end of its data, and
+//!   it has no character map, since a map can't be generated
+//! * A Screen has a cell for every position, and is at most 80
+//!   columns by 50 rows so inputs stay small
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::petscii::{
+    screen::{Cell, Screen},
+    PetsciiString,
+};
+
+/// The most columns in a generated screen, the width of the C128's
+/// 80 column display
+const MAX_COLUMNS: usize = 80;
+
+/// The most rows in a generated screen
+const MAX_ROWS: usize = 50;
+
+impl<'a, const L: usize> Arbitrary<'a> for PetsciiString<'a, L> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let data = <[u8; L]>::arbitrary(u)?;
+        let len = u.int_in_range(0..=L)?;
+        let mut string = PetsciiString::new(len as u32, data);
+        string.strip_shifted_space = bool::arbitrary(u)?;
+        Ok(string)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Screen {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let columns = u.int_in_range(1..=MAX_COLUMNS)?;
+        let rows = u.int_in_range(1..=MAX_ROWS)?;
+
+        let mut screen = Screen::new(columns, rows);
+        for cell in screen.cells.iter_mut() {
+            *cell = Cell::arbitrary(u)?;
+        }
+        screen.background = Arbitrary::arbitrary(u)?;
+        screen.border = Arbitrary::arbitrary(u)?;
+        screen.charset = Arbitrary::arbitrary(u)?;
+        Ok(screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::{
+        petscii::{screen::Screen, PetsciiString},
+        DecodeOptions,
+    };
+
+    #[test]
+    fn fuzz_generates_valid_values() {
+        let bytes: Vec<u8> = (0..4096u32).map(|i| (i * 7919 % 251) as u8).collect();
+        let mut u = Unstructured::new(&bytes);
+
+        for _ in 0..8 {
+            let string = PetsciiString::<16>::arbitrary(&mut u).unwrap();
+            assert!(string.byte_len() <= 16);
+            assert!(string.character_map.is_none());
+
+            let screen = Screen::arbitrary(&mut u).unwrap();
+            assert_eq!(screen.cells.len(), screen.columns * screen.rows);
+            assert!(screen.columns <= 80 && screen.rows <= 50);
+
+            DecodeOptions::arbitrary(&mut u).unwrap();
+        }
+    }
+}
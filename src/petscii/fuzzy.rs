@@ -0,0 +1,189 @@
+//!
+//! Fuzzy matching of PETSCII names
+//!
+//! Filenames read from damaged disks or typed in from a screenshot
+//! rarely match a catalog exactly.  These functions compare names by
+//! edit distance after removing the differences that don't change
+//! what's shown on screen:
+//!
+//! * The case-shift codes 0x0E and 0x8E
+//! * Trailing spaces and shifted spaces used as padding
+//! * The duplicate codes, like 0xC0 to 0xDF, which print the same
+//!   characters as 0x60 to 0x7F.  They're read from the code alias
+//!   table of the configuration.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    cbm::{trim_padding, DISK_PADDING, TAPE_PADDING},
+    SystemConfig,
+};
+
+/// The bytes of a name that matter when comparing it
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{fuzzy::comparable_bytes, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// assert_eq!(comparable_bytes(b"\x0eGAME\x8e\xa0\xa0", &petscii), b"GAME");
+/// assert_eq!(comparable_bytes(&[0xC1, 0xE0, 0xFF], &petscii), [0x61, 0xA0, 0x7E]);
+/// ```
+pub fn comparable_bytes(name: &[u8], petscii: &SystemConfig) -> Vec<u8> {
+    trim_padding(name, &[DISK_PADDING, TAPE_PADDING])
+        .iter()
+        .filter(|b| **b != 0x0E && **b != 0x8E)
+        .map(|b| petscii.character_set_map.canonicalize_code(*b))
+        .collect()
+}
+
+/// The Levenshtein distance between two names
+///
+/// This is the number of bytes that have to be inserted, removed or
+/// replaced to turn one name into the other, after both are reduced
+/// to their comparable bytes.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{fuzzy::edit_distance, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// assert_eq!(edit_distance(b"GAME\xa0\xa0", b"GAME", &petscii), 0);
+/// assert_eq!(edit_distance(b"GAMF", b"GAME", &petscii), 1);
+/// assert_eq!(edit_distance(b"GME", b"GAME 2", &petscii), 3);
+/// ```
+pub fn edit_distance(a: &[u8], b: &[u8], petscii: &SystemConfig) -> usize {
+    let a = comparable_bytes(a, petscii);
+    let b = comparable_bytes(b, petscii);
+
+    // Keep one row of the distance matrix at a time
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let replace = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = replace.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// How alike two names are, from 0.0 for nothing in common to 1.0
+/// for the same name
+pub fn similarity(a: &[u8], b: &[u8], petscii: &SystemConfig) -> f64 {
+    let len = comparable_bytes(a, petscii)
+        .len()
+        .max(comparable_bytes(b, petscii).len());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b, petscii) as f64 / len as f64
+}
+
+/// A name in a catalog that matched
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch<'c> {
+    /// The position of the name in the catalog
+    pub index: usize,
+    /// The name
+    pub name: &'c [u8],
+    /// The edit distance to the name being matched
+    pub distance: usize,
+}
+
+/// Find the closest name in a catalog
+///
+/// Names further than max_distance edits away aren't matched.  When
+/// names are equally close the first one wins.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{fuzzy::best_match, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let catalog: [&[u8]; 3] = [b"LODE RUNNER", b"BOULDER DASH", b"IMPOSSIBLE MISSION"];
+///
+/// let found = best_match(b"B0ULDER DASH\xa0\xa0\xa0\xa0", catalog, 2, &petscii)
+///     .expect("No match");
+/// assert_eq!(found.index, 1);
+/// assert_eq!(found.distance, 1);
+///
+/// assert_eq!(best_match(b"ELITE", catalog, 2, &petscii), None);
+/// ```
+pub fn best_match<'c, I>(
+    name: &[u8],
+    catalog: I,
+    max_distance: usize,
+    petscii: &SystemConfig,
+) -> Option<FuzzyMatch<'c>>
+where
+    I: IntoIterator<Item = &'c [u8]>,
+{
+    catalog
+        .into_iter()
+        .enumerate()
+        .map(|(index, candidate)| FuzzyMatch {
+            index,
+            name: candidate,
+            distance: edit_distance(name, candidate, petscii),
+        })
+        .filter(|found| found.distance <= max_distance)
+        .min_by_key(|found| found.distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            fuzzy::{best_match, edit_distance, similarity},
+            PetsciiConfig,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn fuzzy_ignores_shifts_and_padding() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        assert_eq!(edit_distance(b"\x0eGAME\x8e", b"GAME    ", &petscii), 0);
+        // Duplicate codes print the same character
+        assert_eq!(edit_distance(&[0x41, 0x61], &[0x41, 0xC1], &petscii), 0);
+        assert_eq!(edit_distance(&[0x7E], &[0xFF], &petscii), 0);
+        assert_eq!(edit_distance(b"", b"ABC", &petscii), 3);
+        assert_eq!(similarity(b"", b"\xa0", &petscii), 1.0);
+        assert_eq!(similarity(b"ABCD", b"ABXY", &petscii), 0.5);
+    }
+
+    #[test]
+    fn fuzzy_best_match_prefers_first() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let catalog: Vec<&[u8]> = vec![b"DISK 1", b"DISK 2"];
+        let found = best_match(b"DISK 3", catalog.iter().copied(), 1, &petscii).unwrap();
+        assert_eq!(found.index, 0);
+        assert_eq!(found.name, b"DISK 1");
+    }
+}
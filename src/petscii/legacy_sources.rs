@@ -0,0 +1,246 @@
+//!
+//! Importing the Legacy Computing Sources data
+//!
+//! The Unicode proposal for Symbols for Legacy Computing came with an
+//! auxiliary file, 18235-aux-LegacyComputingSources, listing the
+//! characters of each legacy system that every new code point was
+//! added for.  The C64 screen code tables in the configuration were
+//! transcribed from it by hand.  This module reads the data from its
+//! text form so the tables can be checked against it and corrected.
+//!
+//! The text form has one mapping per line: the code point in hex,
+//! the source system and the source character, separated by
+//! semicolons.  Commodore characters are given as a screen code
+//! followed by the character set, G0 for set 1 and G1 for set 2.
+//! Screen codes can be hex, with a 0x or $ prefix, or decimal.
+//! Comments start with #.
+//!
+//! ```text
+//! # Code point; source; character
+//! 1FB70; C64; 0x74 (G0)
+//! 1FB82; PET; $77 (G0)
+//! ```
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorKind},
+    petscii::{PetsciiConfig, ScreenCode, ScreenSet},
+};
+
+/// A character a code point was added for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceMapping {
+    /// The Unicode character
+    pub code_point: char,
+    /// The legacy system, like C64, PET or VIC20
+    pub system: String,
+    /// The screen code of the character on that system
+    pub screen_code: ScreenCode,
+}
+
+impl SourceMapping {
+    /// True if the mapping is for a C64 or C128 character
+    pub fn is_c64(&self) -> bool {
+        let system = self.system.to_ascii_uppercase();
+        system.starts_with("C64") || system.starts_with("C128")
+    }
+}
+
+/// A screen code where the configuration and the sources disagree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The screen code
+    pub screen_code: ScreenCode,
+    /// The character in the sources
+    pub expected: char,
+    /// The character in the configuration
+    pub found: Option<char>,
+}
+
+/// Build an error for a line in the sources
+fn line_error(line_number: usize, message: &str) -> Error {
+    Error::new(ErrorKind::Message(format!("Line {line_number}: {message}")))
+}
+
+/// Parse a screen code and character set like "0x74 (G0)"
+fn parse_screen_code(field: &str) -> Option<ScreenCode> {
+    let (value, set) = field.split_once('(')?;
+    let set = match set.trim().trim_end_matches(')').trim() {
+        "G0" => ScreenSet::Set1,
+        "G1" => ScreenSet::Set2,
+        _ => return None,
+    };
+
+    let value = value.trim();
+    let value = if let Some(hex) = value.strip_prefix("0x").or_else(|| value.strip_prefix('$')) {
+        u8::from_str_radix(hex, 16).ok()?
+    } else {
+        value.parse().ok()?
+    };
+
+    Some(ScreenCode { set, value })
+}
+
+/// Parse the text form of the Legacy Computing Sources data
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::{legacy_sources::parse_sources, ScreenSet};
+///
+/// let mappings = parse_sources("1FB70; C64; 0x74 (G0)  # From the C64 table")
+///     .expect("Error parsing sources");
+///
+/// assert_eq!(mappings[0].code_point, '\u{1fb70}');
+/// assert_eq!(mappings[0].screen_code.set, ScreenSet::Set1);
+/// assert_eq!(mappings[0].screen_code.value, 0x74);
+/// ```
+pub fn parse_sources(text: &str) -> std::result::Result<Vec<SourceMapping>, Error> {
+    let mut mappings = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        let [code_point, system, character] = fields[..] else {
+            return Err(line_error(i + 1, "Expected three fields"));
+        };
+
+        let code_point = u32::from_str_radix(code_point.trim_start_matches("U+"), 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| line_error(i + 1, "Invalid code point"))?;
+        let screen_code =
+            parse_screen_code(character).ok_or_else(|| line_error(i + 1, "Invalid screen code"))?;
+
+        mappings.push(SourceMapping {
+            code_point,
+            system: system.to_string(),
+            screen_code,
+        });
+    }
+
+    Ok(mappings)
+}
+
+impl PetsciiConfig {
+    /// Compare the C64 screen code tables with the sources
+    ///
+    /// Mappings for other systems are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{legacy_sources::parse_sources, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mappings = parse_sources("2660; C64; 0x41 (G0)").expect("Error parsing sources");
+    /// assert!(petscii.character_set_map.cross_check(&mappings).is_empty());
+    /// ```
+    pub fn cross_check(&self, mappings: &[SourceMapping]) -> Vec<Divergence> {
+        mappings
+            .iter()
+            .filter(|mapping| mapping.is_c64())
+            .filter_map(|mapping| {
+                let found = mapping.screen_code.to_unicode(self);
+                (found != Some(mapping.code_point)).then_some(Divergence {
+                    screen_code: mapping.screen_code,
+                    expected: mapping.code_point,
+                    found,
+                })
+            })
+            .collect()
+    }
+
+    /// Correct the C64 screen code tables from the sources
+    ///
+    /// The screen code to Unicode tables take the character from the
+    /// sources.  The character is also added to the Unicode to screen
+    /// code table if it isn't there, and the character it replaced is
+    /// removed if it was encoded as that screen code.  Returns the
+    /// corrections that were made.
+    pub fn apply_sources(&mut self, mappings: &[SourceMapping]) -> Vec<Divergence> {
+        let divergences = self.cross_check(mappings);
+
+        for divergence in &divergences {
+            let ScreenCode { set, value } = divergence.screen_code;
+            let table = match set {
+                ScreenSet::Set1 => &mut self.c64_screen_codes_set_1_to_unicode_codes,
+                ScreenSet::Set2 => &mut self.c64_screen_codes_set_2_to_unicode_codes,
+                ScreenSet::Set3 => &mut self.c64_screen_codes_set_3_to_unicode_codes,
+            };
+            table.insert(
+                value.to_string(),
+                Value::from(u32::from(divergence.expected)),
+            );
+
+            let screen_code = Value::from(vec![u8::from(set), value]);
+            if let Some(old) = divergence.found {
+                let key = u32::from(old).to_string();
+                if self.unicode_codes_to_c64_screen_codes.get(&key) == Some(&screen_code) {
+                    self.unicode_codes_to_c64_screen_codes.remove(&key);
+                }
+            }
+            self.unicode_codes_to_c64_screen_codes
+                .entry(u32::from(divergence.expected).to_string())
+                .or_insert(screen_code);
+        }
+
+        divergences
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            legacy_sources::parse_sources, unicode_to_screen_code, PetsciiConfig, ScreenCode,
+            ScreenSet,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn legacy_sources_reports_bad_lines() {
+        let error = parse_sources("# Header\n\n1FB70; C64\n").unwrap_err();
+        assert!(error.to_string().contains("Line 3"));
+
+        assert!(parse_sources("1FB70; C64; 0x74 (G2)").is_err());
+        assert!(parse_sources("D800; C64; 0x74 (G0)").is_err());
+
+        let mappings = parse_sources("U+1FB70;VIC20;116 (G0)").unwrap();
+        assert!(!mappings[0].is_c64());
+    }
+
+    #[test]
+    fn legacy_sources_corrects_tables() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let mut map = petscii.character_set_map;
+
+        // Pretend the sources put a different character on the spade
+        let mappings = parse_sources("1FBAF; C64; $41 (G0)\n2660; PET; $41 (G0)").unwrap();
+        let divergences = map.apply_sources(&mappings);
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].found, Some('♠'));
+
+        let screen_code = ScreenCode {
+            set: ScreenSet::Set1,
+            value: 0x41,
+        };
+        assert_eq!(screen_code.to_unicode(&map), Some('\u{1fbaf}'));
+        assert_eq!(unicode_to_screen_code('\u{1fbaf}', &map), Some(screen_code));
+        assert!(map.cross_check(&mappings).is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+//!
+//! Exporting PETSCII to Markdown
+//!
+//! Documentation and issue trackers take Markdown, not HTML, and
+//! pasting a screen or listing into them needs the text in a fenced
+//! code block so the graphics characters line up.  Screen readers
+//! and people without a font that has the graphics characters can't
+//! make much of the graphics, so the block is followed by a list
+//! describing each run of graphics characters by name.
+//!
+//! The names come from the PETSCII code name tables, without the
+//! "(CBM)" suffix, and repeated characters are counted, like
+//! "4 × MEDIUM SHADE".
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::Write;
+
+use crate::{
+    petscii::{screen::Screen, PetsciiConfig, ScreenCode},
+    CharacterSet, SystemConfig,
+};
+
+/// True if a character is drawn with the graphics characters
+///
+/// Everything outside ASCII except letters is, so £ and the arrows
+/// are described too.
+fn is_graphics(c: char) -> bool {
+    !c.is_ascii() && !c.is_alphabetic()
+}
+
+/// The name of a character, from the code it encodes to
+fn char_name(c: char, map: &PetsciiConfig) -> String {
+    let name = ScreenCode::from_unicode(c, map).and_then(|screen_code| {
+        let petscii_code = screen_code.to_petscii(map)?;
+        let name = map.code_name(petscii_code.value.0, petscii_code.is_shifted())?;
+        let name = name.trim_end_matches(" (CBM)");
+        Some(if screen_code.is_reversed() {
+            format!("REVERSED {name}")
+        } else {
+            name.to_string()
+        })
+    });
+    name.unwrap_or_else(|| format!("U+{:04X}", u32::from(c)))
+}
+
+/// Describe a run of graphics characters
+fn describe_run(run: &[char], map: &PetsciiConfig) -> String {
+    let mut parts = Vec::new();
+    let mut rest = run;
+    while let Some(c) = rest.first() {
+        let count = rest.iter().take_while(|r| *r == c).count();
+        let name = char_name(*c, map);
+        if count > 1 {
+            parts.push(format!("{count} × {name}"));
+        } else {
+            parts.push(name);
+        }
+        rest = &rest[count..];
+    }
+    parts.join(", ")
+}
+
+/// Format text as a fenced code block followed by descriptions of
+/// its graphics
+///
+/// The fence is longer than any run of backticks in the text, so the
+/// text can't end the block early.  A newline at the end of the text
+/// is left out of the block.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{markdown::to_markdown, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// assert_eq!(
+///     to_markdown("HI\n▒▒▒▒ ┌", &petscii),
+///     "```text\nHI\n▒▒▒▒ ┌\n```\n\n\
+///      * Line 2, column 1: 4 × MEDIUM SHADE\n\
+///      * Line 2, column 6: BOX DRAWINGS LIGHT DOWN AND RIGHT\n"
+/// );
+/// ```
+pub fn to_markdown(text: &str, petscii: &SystemConfig) -> String {
+    let map = &petscii.character_set_map;
+    let text = text.strip_suffix('\n').unwrap_or(text);
+
+    let longest_backticks = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_backticks.max(2) + 1);
+
+    let mut markdown = format!("{fence}text\n{text}\n{fence}\n");
+    let mut described = false;
+    for (line_number, line) in text.lines().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let mut column = 0;
+        while column < chars.len() {
+            let len = chars[column..]
+                .iter()
+                .take_while(|c| is_graphics(**c))
+                .count();
+            if len == 0 {
+                column += 1;
+                continue;
+            }
+
+            if !described {
+                markdown.push('\n');
+                described = true;
+            }
+            let _ = writeln!(
+                markdown,
+                "* Line {}, column {}: {}",
+                line_number + 1,
+                column + 1,
+                describe_run(&chars[column..column + len], map)
+            );
+            column += len;
+        }
+    }
+    markdown
+}
+
+impl SystemConfig {
+    /// Decode PETSCII bytes, like a listing or a message, into
+    /// Markdown
+    ///
+    /// Carriage returns end the lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert_eq!(
+    ///     petscii.decode_markdown(b"10 PRINT\"\xd3\"\r"),
+    ///     "```text\n10 PRINT\"♥\"\n```\n\n* Line 1, column 10: BLACK HEART SUIT\n"
+    /// );
+    /// ```
+    pub fn decode_markdown(&self, bytes: &[u8]) -> String {
+        to_markdown(&self.decode(bytes).replace('\r', "\n"), self)
+    }
+}
+
+impl Screen {
+    /// The text on the screen as Markdown
+    ///
+    /// Reversed characters without a character of their own are
+    /// written as the character, like to_text does.
+    pub fn to_markdown(&self, petscii: &SystemConfig) -> String {
+        to_markdown(&self.to_text(petscii), petscii)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{markdown::to_markdown, screen::Screen, Color, PetsciiConfig},
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn markdown_fences_and_describes() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Backticks in the text get a longer fence
+        assert!(to_markdown("A ``` B", &petscii).starts_with("````text\n"));
+        assert_eq!(to_markdown("READY.", &petscii), "```text\nREADY.\n```\n");
+
+        let mut screen = Screen::new(3, 1);
+        screen.set(1, 0, 0x66, Color::White);
+        screen.set(2, 0, 0x66, Color::White);
+        assert!(screen
+            .to_markdown(&petscii)
+            .ends_with("* Line 1, column 2: 2 × MEDIUM SHADE\n"));
+    }
+}
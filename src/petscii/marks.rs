@@ -0,0 +1,336 @@
+//!
+//! Keeping attributes in the decoded text
+//!
+//! Plain Unicode text has no reverse video or colors, so decoding
+//! PETSCII to a string loses them.  For reversed characters without a
+//! reversed Unicode character of their own, and for colored text,
+//! the attributes can be carried in the text itself:
+//!
+//! * AttributeMarks::EnclosingSquare follows each reversed character
+//!   with U+20DE COMBINING ENCLOSING SQUARE.  Colors are dropped.
+//! * AttributeMarks::Annotation wraps each run of reversed or colored
+//!   text in the interlinear annotation characters, U+FFF9 before the
+//!   text, U+FFFA between the text and the annotation and U+FFFB
+//!   after it.  The annotation is the color name, followed by
+//!   "reversed" for reversed text.
+//!
+//! Text in the C64 start state, light blue and not reversed, isn't
+//! marked.  encode_with_marks reads both kinds of marks back into
+//! reverse video and color codes.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::iter::Peekable;
+
+use crate::{
+    petscii::{
+        decoder::{PetsciiEvent, PetsciiEventDecoder},
+        encode_reversed_from_state, unicode_to_screen_code, Color,
+    },
+    SystemConfig,
+};
+
+/// U+20DE COMBINING ENCLOSING SQUARE
+const ENCLOSING_SQUARE: char = '\u{20de}';
+/// U+FFF9 INTERLINEAR ANNOTATION ANCHOR
+const ANNOTATION_ANCHOR: char = '\u{fff9}';
+/// U+FFFA INTERLINEAR ANNOTATION SEPARATOR
+const ANNOTATION_SEPARATOR: char = '\u{fffa}';
+/// U+FFFB INTERLINEAR ANNOTATION TERMINATOR
+const ANNOTATION_TERMINATOR: char = '\u{fffb}';
+
+/// The color text starts in on a C64
+const START_COLOR: Color = Color::LightBlue;
+
+/// How attributes are kept in decoded text
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttributeMarks {
+    /// Follow reversed characters with a combining enclosing square
+    #[default]
+    EnclosingSquare,
+    /// Wrap reversed and colored text in interlinear annotations
+    Annotation,
+}
+
+/// A decoded character and its attributes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MarkedChar {
+    c: char,
+    color: Color,
+    /// True if the character is reversed and isn't a reversed
+    /// character already
+    reversed: bool,
+}
+
+/// The annotation for a run of text
+fn annotation(color: Color, reversed: bool) -> String {
+    if reversed {
+        format!("{color:?} reversed")
+    } else {
+        format!("{color:?}")
+    }
+}
+
+/// Read an annotation back into a color and reverse video
+///
+/// Unknown words are ignored, so a missing color leaves the text in
+/// the start color.
+fn parse_annotation(annotation: &str) -> (Color, bool) {
+    let mut color = START_COLOR;
+    let mut reversed = false;
+
+    for word in annotation.split_whitespace() {
+        if word.eq_ignore_ascii_case("reversed") {
+            reversed = true;
+        } else if let Some(c) = Color::ALL
+            .iter()
+            .find(|c| format!("{c:?}").eq_ignore_ascii_case(word))
+        {
+            color = *c;
+        }
+    }
+    (color, reversed)
+}
+
+/// Decode PETSCII, keeping reverse video and colors as marks in the
+/// text
+///
+/// Cursor movement and other control codes are dropped like they are
+/// by a plain decode.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{
+///         marks::{decode_with_marks, AttributeMarks},
+///         PetsciiConfig,
+///     },
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let bytes = [0x12, 0x48, 0x92, 0x1C, 0x49];
+///
+/// assert_eq!(
+///     decode_with_marks(&bytes, &petscii, AttributeMarks::EnclosingSquare),
+///     "H\u{20de}I"
+/// );
+/// assert_eq!(
+///     decode_with_marks(&bytes, &petscii, AttributeMarks::Annotation),
+///     "\u{fff9}H\u{fffa}LightBlue reversed\u{fffb}\u{fff9}I\u{fffa}Red\u{fffb}"
+/// );
+/// ```
+pub fn decode_with_marks(
+    bytes: &[u8],
+    character_map: &SystemConfig,
+    marks: AttributeMarks,
+) -> String {
+    let map = &character_map.character_set_map;
+    let mut decoder = PetsciiEventDecoder::new(character_map);
+
+    let chars = bytes.iter().filter_map(|b| match decoder.feed(*b)? {
+        PetsciiEvent::PrintChar { c, color, reversed } => Some(MarkedChar {
+            c,
+            color,
+            reversed: reversed
+                && !unicode_to_screen_code(c, map).is_some_and(|code| code.is_reversed()),
+        }),
+        // Line breaks end reverse video and aren't colored
+        PetsciiEvent::NewLine => Some(MarkedChar {
+            c: '\r',
+            color: START_COLOR,
+            reversed: false,
+        }),
+        PetsciiEvent::Control(0x0A) => Some(MarkedChar {
+            c: '\n',
+            color: START_COLOR,
+            reversed: false,
+        }),
+        _ => None,
+    });
+
+    let mut s = String::new();
+    match marks {
+        AttributeMarks::EnclosingSquare => {
+            for marked in chars {
+                s.push(marked.c);
+                if marked.reversed {
+                    s.push(ENCLOSING_SQUARE);
+                }
+            }
+        }
+        AttributeMarks::Annotation => {
+            let mut chars = chars.peekable();
+            while let Some(marked) = chars.next() {
+                if (marked.color, marked.reversed) == (START_COLOR, false) {
+                    s.push(marked.c);
+                    continue;
+                }
+
+                s.push(ANNOTATION_ANCHOR);
+                s.push(marked.c);
+                while let Some(next) =
+                    chars.next_if(|n| (n.color, n.reversed) == (marked.color, marked.reversed))
+                {
+                    s.push(next.c);
+                }
+                s.push(ANNOTATION_SEPARATOR);
+                s.push_str(&annotation(marked.color, marked.reversed));
+                s.push(ANNOTATION_TERMINATOR);
+            }
+        }
+    }
+    s
+}
+
+/// Read characters up to an annotation character, with whether each
+/// one is followed by an enclosing square
+fn read_marked_run(chars: &mut Peekable<impl Iterator<Item = char>>) -> Vec<(char, bool)> {
+    let mut run = Vec::new();
+    while let Some(c) = chars.next_if(|c| {
+        !matches!(
+            *c,
+            ANNOTATION_ANCHOR | ANNOTATION_SEPARATOR | ANNOTATION_TERMINATOR
+        )
+    }) {
+        let enclosed = chars.next_if_eq(&ENCLOSING_SQUARE).is_some();
+        run.push((c, enclosed));
+    }
+    run
+}
+
+/// Encode text with attribute marks into PETSCII
+///
+/// Both kinds of marks are read, characters followed by an enclosing
+/// square are reversed and annotated text gets the color and reverse
+/// video in its annotation.  Like encode, the text ends in the
+/// uppercase character set.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{marks::encode_with_marks, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// assert_eq!(
+///     encode_with_marks("H\u{20de}I", &petscii),
+///     [0x12, 0x48, 0x92, 0x49]
+/// );
+/// assert_eq!(
+///     encode_with_marks("\u{fff9}HI\u{fffa}Red\u{fffb}!", &petscii),
+///     [0x1C, 0x48, 0x49, 0x9A, 0x21]
+/// );
+/// ```
+pub fn encode_with_marks(s: &str, character_map: &SystemConfig) -> Vec<u8> {
+    let map = &character_map.character_set_map;
+    let mut chars = s.chars().peekable();
+    let mut codes = Vec::new();
+    let mut shifted = false;
+    let mut color = START_COLOR;
+
+    while chars.peek().is_some() {
+        let (run, run_color) = if chars.next_if_eq(&ANNOTATION_ANCHOR).is_some() {
+            let mut run = read_marked_run(&mut chars);
+            chars.next_if_eq(&ANNOTATION_SEPARATOR);
+            let annotation: String = chars
+                .by_ref()
+                .take_while(|c| *c != ANNOTATION_TERMINATOR)
+                .collect();
+            let (run_color, reversed) = parse_annotation(&annotation);
+            for (_, reverse) in run.iter_mut() {
+                *reverse |= reversed;
+            }
+            (run, run_color)
+        } else {
+            // Stray separators and terminators are skipped
+            chars.next_if(|c| matches!(*c, ANNOTATION_SEPARATOR | ANNOTATION_TERMINATOR));
+            (read_marked_run(&mut chars), START_COLOR)
+        };
+
+        if run_color != color && !run.is_empty() {
+            codes.push(run_color.control_code());
+            color = run_color;
+        }
+        codes.extend(encode_reversed_from_state(run, map, &mut shifted));
+    }
+
+    if shifted {
+        codes.push(0x8E);
+    }
+    codes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            marks::{decode_with_marks, encode_with_marks, AttributeMarks},
+            PetsciiConfig,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn marks_round_trip_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Red "HI", then a reversed white lowercase "ok" and a light
+        // blue line
+        let bytes = [
+            0x1C, 0x48, 0x49, 0x0D, 0x05, 0x12, 0x0E, 0x4F, 0x4B, 0x92, 0x8E, 0x9A, 0x41,
+        ];
+
+        let s = decode_with_marks(&bytes, &petscii, AttributeMarks::Annotation);
+        assert_eq!(
+            s,
+            "\u{fff9}HI\u{fffa}Red\u{fffb}\r\u{fff9}ok\u{fffa}White reversed\u{fffb}A"
+        );
+        assert_eq!(
+            decode_with_marks(
+                &encode_with_marks(&s, &petscii),
+                &petscii,
+                AttributeMarks::Annotation
+            ),
+            s
+        );
+
+        let s = decode_with_marks(&bytes, &petscii, AttributeMarks::EnclosingSquare);
+        assert_eq!(s, "HI\ro\u{20de}k\u{20de}A");
+        assert_eq!(
+            decode_with_marks(
+                &encode_with_marks(&s, &petscii),
+                &petscii,
+                AttributeMarks::EnclosingSquare
+            ),
+            s
+        );
+    }
+
+    #[test]
+    fn marks_skip_reversed_characters() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Reversed 0x7C has its own Unicode character, so it doesn't
+        // need a mark
+        let s = decode_with_marks(
+            &[0x12, 0x7C, 0x92],
+            &petscii,
+            AttributeMarks::EnclosingSquare,
+        );
+        assert_eq!(s, "\u{1fb94}");
+        assert_eq!(encode_with_marks(&s, &petscii), [0x12, 0x7C, 0x92]);
+
+        // Marks without a character are dropped
+        assert_eq!(encode_with_marks("\u{fffa}\u{fffb}A", &petscii), [0x41]);
+    }
+}
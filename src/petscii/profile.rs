@@ -0,0 +1,388 @@
+//!
+//! Mapping profiles
+//!
+//! Emulators and conversion tools don't agree on a handful of
+//! PETSCII characters.  The pound sign, the up and left arrows and π
+//! took the places of backslash, caret, underscore and tilde in
+//! ASCII, and tools decode them as either.  A mapping profile
+//! changes just those characters, so decoded text matches the tools
+//! it's exchanged with:
+//!
+//! | Profile        | £   | ↑   | ←   | π   |
+//! |----------------|-----|-----|-----|-----|
+//! | strict-unicode | £   | ↑   | ←   | π   |
+//! | vice           | \\  | ^   | _   | ~   |
+//! | cbmconvert     | £   | ^   | _   | π   |
+//!
+//! Each profile is an overlay, like the embedded configurations in
+//! [crate::CONFIG_NAMES], merged over the tables of a loaded
+//! configuration.
+//!
+//! To choose the characters for a single conversion without building
+//! another configuration, pass AmbiguousCharacters to
+//! decode_with_choices and encode_with_choices.  Each of the four
+//! characters can be chosen on its own.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use serde_json::Value;
+
+use crate::{
+    config_data,
+    error::{Error, ErrorKind},
+    merge_json,
+    petscii::{
+        decoder::{DecodeEvent, PetsciiDecoderState},
+        encode_from_state,
+    },
+    CharacterSet, SystemConfig,
+};
+
+/// A set of mappings for the characters tools disagree on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MappingProfile {
+    /// The Unicode characters the C64 draws
+    StrictUnicode,
+    /// The ASCII characters VICE and its tools use
+    Vice,
+    /// The characters cbmconvert uses
+    Cbmconvert,
+}
+
+impl MappingProfile {
+    /// All the profiles
+    pub const ALL: [MappingProfile; 3] = [
+        MappingProfile::StrictUnicode,
+        MappingProfile::Vice,
+        MappingProfile::Cbmconvert,
+    ];
+
+    /// Look up a profile by its name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::profile::MappingProfile;
+    ///
+    /// assert_eq!(MappingProfile::for_name("vice"), Some(MappingProfile::Vice));
+    /// assert_eq!(MappingProfile::for_name("petcat"), None);
+    /// ```
+    pub fn for_name(name: &str) -> Option<MappingProfile> {
+        MappingProfile::ALL
+            .into_iter()
+            .find(|profile| profile.name() == name)
+    }
+
+    /// The name of the profile
+    pub fn name(self) -> &'static str {
+        match self {
+            MappingProfile::StrictUnicode => "strict-unicode",
+            MappingProfile::Vice => "vice",
+            MappingProfile::Cbmconvert => "cbmconvert",
+        }
+    }
+
+    /// The overlay with the profile's mappings
+    pub fn overlay(self) -> &'static str {
+        match self {
+            MappingProfile::StrictUnicode => config_data::STRICT_UNICODE_PROFILE_OVERLAY,
+            MappingProfile::Vice => config_data::VICE_PROFILE_OVERLAY,
+            MappingProfile::Cbmconvert => config_data::CBMCONVERT_PROFILE_OVERLAY,
+        }
+    }
+}
+
+impl SystemConfig {
+    /// A copy of the configuration with a profile's mappings
+    ///
+    /// Keep the copy for as long as the profile is used, building it
+    /// parses the overlay and copies the tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{profile::MappingProfile, PetsciiConfig},
+    ///     CharacterSet, Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let vice = petscii.with_profile(MappingProfile::Vice).expect("Error applying profile");
+    ///
+    /// assert_eq!(vice.decode(b"\x5c\x5e\x5f\xff"), "\\^_~");
+    /// assert_eq!(vice.encode("^£"), [0x5e, 0x5c]);
+    /// ```
+    pub fn with_profile(
+        &self,
+        profile: MappingProfile,
+    ) -> std::result::Result<SystemConfig, Error> {
+        let overlay: Value = serde_json::from_str(profile.overlay())?;
+        let Some(overlay) = overlay.get("petscii").cloned() else {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "The {} profile has no PETSCII mappings",
+                profile.name()
+            ))));
+        };
+
+        let mut value = serde_json::to_value(self)?;
+        merge_json(&mut value, overlay);
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Decode bytes with a profile's mappings
+    ///
+    /// This builds the profile's configuration for every call, use
+    /// with_profile to decode more than a few strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{profile::MappingProfile, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let pi = petscii
+    ///     .decode_with_profile(&[0x7e], MappingProfile::StrictUnicode)
+    ///     .expect("Error applying profile");
+    ///
+    /// assert_eq!(pi, "π");
+    /// ```
+    pub fn decode_with_profile(
+        &self,
+        bytes: &[u8],
+        profile: MappingProfile,
+    ) -> std::result::Result<String, Error> {
+        Ok(self.with_profile(profile)?.decode(bytes))
+    }
+}
+
+/// The characters the ambiguous PETSCII codes decode to
+///
+/// The pound sign is 0x5C, the up arrow 0x5E and the left arrow 0x5F
+/// in both character sets.  π is 0x7E, and its duplicates 0xDE and
+/// 0xFF, in the uppercase set only, in the lowercase set the codes
+/// print a checkerboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmbiguousCharacters {
+    /// The character for 0x5C, £ or backslash
+    pub pound: char,
+    /// The character for 0x5E, ↑ or caret
+    pub up_arrow: char,
+    /// The character for 0x5F, ← or underscore
+    pub left_arrow: char,
+    /// The character for π, π or tilde
+    pub pi: char,
+}
+
+impl AmbiguousCharacters {
+    /// The characters the C64 draws
+    pub const UNICODE: AmbiguousCharacters = AmbiguousCharacters {
+        pound: '£',
+        up_arrow: '↑',
+        left_arrow: '←',
+        pi: 'π',
+    };
+
+    /// The ASCII characters in the same places
+    pub const ASCII: AmbiguousCharacters = AmbiguousCharacters {
+        pound: '\\',
+        up_arrow: '^',
+        left_arrow: '_',
+        pi: '~',
+    };
+
+    /// The characters a mapping profile decodes to
+    pub fn for_profile(profile: MappingProfile) -> AmbiguousCharacters {
+        match profile {
+            MappingProfile::StrictUnicode => AmbiguousCharacters::UNICODE,
+            MappingProfile::Vice => AmbiguousCharacters::ASCII,
+            MappingProfile::Cbmconvert => AmbiguousCharacters {
+                pound: '£',
+                up_arrow: '^',
+                left_arrow: '_',
+                pi: 'π',
+            },
+        }
+    }
+
+    /// The chosen character for a PETSCII code, if it's ambiguous
+    fn for_code(&self, code: u8, shifted: bool) -> Option<char> {
+        match code {
+            0x5C => Some(self.pound),
+            0x5E => Some(self.up_arrow),
+            0x5F => Some(self.left_arrow),
+            0x7E | 0xDE | 0xFF if !shifted => Some(self.pi),
+            _ => None,
+        }
+    }
+
+    /// The PETSCII code for a chosen character, and whether it needs
+    /// the uppercase set
+    fn code_for(&self, c: char) -> Option<(u8, bool)> {
+        if c == self.pound {
+            Some((0x5C, false))
+        } else if c == self.up_arrow {
+            Some((0x5E, false))
+        } else if c == self.left_arrow {
+            Some((0x5F, false))
+        } else if c == self.pi {
+            Some((0x7E, true))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for AmbiguousCharacters {
+    fn default() -> AmbiguousCharacters {
+        AmbiguousCharacters::UNICODE
+    }
+}
+
+impl SystemConfig {
+    /// Decode bytes, with chosen characters for the ambiguous codes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{profile::AmbiguousCharacters, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// // Keep the pound sign, but use ASCII for the arrows
+    /// let choices = AmbiguousCharacters {
+    ///     pound: '£',
+    ///     ..AmbiguousCharacters::ASCII
+    /// };
+    ///
+    /// assert_eq!(petscii.decode_with_choices(b"\x5c\x5e\x5f\x7e", &choices), "£^_~");
+    /// ```
+    pub fn decode_with_choices(&self, bytes: &[u8], choices: &AmbiguousCharacters) -> String {
+        let mut state = PetsciiDecoderState::new(Some(self));
+        bytes
+            .iter()
+            .filter_map(|b| match state.feed(*b) {
+                DecodeEvent::Char(c) => Some(choices.for_code(*b, state.is_shifted()).unwrap_or(c)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Encode a string, with chosen characters for the ambiguous
+    /// codes
+    ///
+    /// The chosen characters encode to the ambiguous codes even if
+    /// the configuration maps them to something else.  π switches to
+    /// the uppercase set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{profile::AmbiguousCharacters, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert_eq!(
+    ///     petscii.encode_with_choices("a~^", &AmbiguousCharacters::ASCII),
+    ///     [0x0e, 0x41, 0x8e, 0x7e, 0x5e]
+    /// );
+    /// ```
+    pub fn encode_with_choices(&self, s: &str, choices: &AmbiguousCharacters) -> Vec<u8> {
+        let map = &self.character_set_map;
+        let mut shifted = false;
+        let mut bytes = Vec::new();
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            let (run, code) = match rest
+                .char_indices()
+                .find_map(|(i, c)| choices.code_for(c).map(|code| (i, c.len_utf8(), code)))
+            {
+                Some((i, len, code)) => {
+                    let run = &rest[..i];
+                    rest = &rest[i + len..];
+                    (run, Some(code))
+                }
+                None => (std::mem::take(&mut rest), None),
+            };
+
+            bytes.extend(encode_from_state(run, map, &mut shifted));
+            if let Some((code, unshifted)) = code {
+                if unshifted && shifted {
+                    bytes.push(0x8E);
+                    shifted = false;
+                }
+                bytes.push(code);
+            }
+        }
+
+        if shifted {
+            bytes.push(0x8E);
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            profile::{AmbiguousCharacters, MappingProfile},
+            PetsciiConfig,
+        },
+        CharacterSet, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn profile_round_trips_the_disputed_codes() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let disputed = [0x5c, 0x5e, 0x5f, 0x7e];
+
+        for profile in MappingProfile::ALL {
+            let profiled = petscii.with_profile(profile).unwrap();
+            let decoded = profiled.decode(&disputed);
+            assert_eq!(profiled.encode(&decoded), disputed, "{}", profile.name());
+        }
+
+        let cbmconvert = petscii.with_profile(MappingProfile::Cbmconvert).unwrap();
+        assert_eq!(cbmconvert.decode(&disputed), "£^_π");
+        // Shifted, 0x7E is a checkerboard in every profile
+        assert_eq!(
+            cbmconvert.decode(&[0x0e, 0x7e]),
+            petscii.decode(&[0x0e, 0x7e])
+        );
+    }
+
+    #[test]
+    fn profile_choices_match_the_profiles() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let bytes = b"\x5c\x5e\x5f\x7e\xff\x0e\x5c\xff";
+
+        for profile in MappingProfile::ALL {
+            let choices = AmbiguousCharacters::for_profile(profile);
+            let profiled = petscii.with_profile(profile).unwrap();
+            let decoded = petscii.decode_with_choices(bytes, &choices);
+            assert_eq!(decoded, profiled.decode(bytes), "{}", profile.name());
+            assert_eq!(
+                petscii.encode_with_choices(&decoded, &choices),
+                profiled.encode(&decoded)
+            );
+        }
+    }
+}
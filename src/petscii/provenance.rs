@@ -0,0 +1,268 @@
+//!
+//! Where each decoded character came from
+//!
+//! Archival metadata records how faithful a conversion is, and a
+//! single lossless flag isn't enough for that: a reversed character
+//! shown unreversed, a graphics character read through one of its
+//! duplicate codes and a letter whose character set had to be guessed
+//! are different kinds of doubt.  decode_with_provenance returns
+//! every character with its offset in the input and a Provenance
+//! saying which kind applies.
+//!
+//! decode_resynchronizing_with_provenance does the same for damaged
+//! streams, splitting them the way resync::decode_resynchronizing
+//! does.  The character set a text run starts in after a gap is
+//! carried over from the text before the gap, so until the run has
+//! its own shift code, letters that differ between the two character
+//! sets are marked as guessed.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    petscii::{
+        decoder::{DecodeEvent, PetsciiDecoderState},
+        petscii_to_screen_code,
+        resync::{split_runs, Span},
+        ScreenCode,
+    },
+    SystemConfig,
+};
+
+/// How a decoded character relates to the input
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Provenance {
+    /// The code maps to exactly this character
+    Exact,
+    /// The character stands in for one Unicode doesn't have, like a
+    /// reversed character decoded as the unreversed character
+    Approximated,
+    /// The code is a duplicate of another code that shows the same
+    /// character, like 0x7E for 0xA6, and encoding the character
+    /// gives the other code
+    Aliased,
+    /// The character depends on a character set that was guessed
+    /// after a gap in a damaged stream
+    Guessed,
+    /// A U+FFFD REPLACEMENT CHARACTER standing in for a gap in a
+    /// damaged stream
+    Unrecoverable,
+}
+
+/// A decoded character and where it came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnnotatedChar {
+    /// The character
+    pub character: char,
+    /// The offset of the code in the input, or of the start of the
+    /// gap for an unrecoverable character
+    pub offset: usize,
+    /// How the character relates to the code
+    pub provenance: Provenance,
+}
+
+/// True if a character encodes to a different code than the one it
+/// was decoded from
+fn is_alias(code: u8, character: char, character_map: &SystemConfig) -> bool {
+    let map = &character_map.character_set_map;
+    ScreenCode::from_unicode(character, map)
+        .and_then(|screen_code| screen_code.to_petscii(map))
+        .is_some_and(|canonical| u8::from(canonical.value) != code)
+}
+
+/// True if a code shows a different character in the other
+/// character set
+fn depends_on_charset(code: u8, character_map: &SystemConfig) -> bool {
+    let map = &character_map.character_set_map;
+    let [unshifted, shifted] = [false, true].map(|shifted| {
+        petscii_to_screen_code(code, shifted, map)
+            .and_then(|screen_code| screen_code.to_unicode(map))
+    });
+    unshifted != shifted
+}
+
+/// Decode bytes from a state, annotating each character
+///
+/// While guessing is true, characters that depend on the character
+/// set are marked as guessed, until a shift code sets it.
+fn annotate(
+    bytes: &[u8],
+    start: usize,
+    mut state: PetsciiDecoderState<'_>,
+    mut guessing: bool,
+    character_map: &SystemConfig,
+    chars: &mut Vec<AnnotatedChar>,
+) {
+    for (i, code) in bytes.iter().enumerate() {
+        let character = match state.feed(*code) {
+            DecodeEvent::Char(c) => c,
+            _ => {
+                guessing &= !matches!(*code, 0x0E | 0x8E);
+                continue;
+            }
+        };
+
+        let provenance = if guessing && depends_on_charset(*code, character_map) {
+            Provenance::Guessed
+        } else if state.substituted() {
+            Provenance::Approximated
+        } else if is_alias(*code, character, character_map) {
+            Provenance::Aliased
+        } else {
+            Provenance::Exact
+        };
+        chars.push(AnnotatedChar {
+            character,
+            offset: start + i,
+            provenance,
+        });
+    }
+}
+
+impl SystemConfig {
+    /// Decode PETSCII bytes, with the provenance of each character
+    ///
+    /// The characters are the ones decode returns.  A character that
+    /// is both approximated and aliased is marked as approximated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{provenance::Provenance, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// // A letter, a reversed letter and a duplicate code for 0xA6
+    /// let chars = petscii.decode_with_provenance(&[0x41, 0x12, 0x42, 0x92, 0x7E]);
+    /// let provenance: Vec<Provenance> = chars.iter().map(|c| c.provenance).collect();
+    ///
+    /// assert_eq!(
+    ///     provenance,
+    ///     [Provenance::Exact, Provenance::Approximated, Provenance::Aliased]
+    /// );
+    /// assert_eq!(chars[2].offset, 4);
+    /// ```
+    pub fn decode_with_provenance(&self, bytes: &[u8]) -> Vec<AnnotatedChar> {
+        let mut chars = Vec::new();
+        annotate(
+            bytes,
+            0,
+            PetsciiDecoderState::new(Some(self)),
+            false,
+            self,
+            &mut chars,
+        );
+        chars
+    }
+
+    /// Decode a damaged stream, with the provenance of each character
+    ///
+    /// The stream is split into text runs and gaps like
+    /// decode_resynchronizing.  Each gap is one unrecoverable
+    /// replacement character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{provenance::Provenance, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let chars = petscii.decode_resynchronizing_with_provenance(b"OK\x00\xffOK", 2);
+    /// let text: String = chars.iter().map(|c| c.character).collect();
+    ///
+    /// assert_eq!(text, "OK\u{fffd}OK");
+    /// assert_eq!(chars[2].provenance, Provenance::Unrecoverable);
+    /// // Whether these are uppercase or lowercase letters was guessed
+    /// assert_eq!(chars[3].provenance, Provenance::Guessed);
+    /// ```
+    pub fn decode_resynchronizing_with_provenance(
+        &self,
+        bytes: &[u8],
+        min_run: usize,
+    ) -> Vec<AnnotatedChar> {
+        let mut chars = Vec::new();
+        let mut after_gap = false;
+
+        for span in split_runs(bytes, min_run) {
+            match span {
+                Span::Text { range, shifted } => {
+                    let state = PetsciiDecoderState::new(Some(self)).with_shifted(shifted);
+                    annotate(
+                        &bytes[range.clone()],
+                        range.start,
+                        state,
+                        after_gap,
+                        self,
+                        &mut chars,
+                    );
+                }
+                Span::Gap(range) => {
+                    after_gap = true;
+                    chars.push(AnnotatedChar {
+                        character: char::REPLACEMENT_CHARACTER,
+                        offset: range.start,
+                        provenance: Provenance::Unrecoverable,
+                    });
+                }
+            }
+        }
+
+        chars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{provenance::Provenance, PetsciiConfig},
+        CharacterSet, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn provenance_annotates_every_character() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let bytes = [
+            0x05, 0x48, 0x49, 0x0D, 0x12, 0x41, 0x92, 0x7E, 0xA6, 0xFF, 0x5C,
+        ];
+        let chars = petscii.decode_with_provenance(&bytes);
+        let text: String = chars.iter().map(|c| c.character).collect();
+        assert_eq!(text, petscii.decode(&bytes));
+        assert!(chars
+            .iter()
+            .all(|c| c.provenance != Provenance::Guessed && bytes.get(c.offset).is_some()));
+        // 0x7E and 0xFF are duplicates of 0xA6 and 0xDE
+        let aliased: Vec<usize> = chars
+            .iter()
+            .filter(|c| c.provenance == Provenance::Aliased)
+            .map(|c| c.offset)
+            .collect();
+        assert_eq!(aliased, [7, 9]);
+
+        // A shift code in the run after the gap settles the guess
+        let chars = petscii.decode_resynchronizing_with_provenance(b"\x0eHI\x00\x00HI\x8eHI", 2);
+        let provenance: Vec<Provenance> = chars.iter().map(|c| c.provenance).collect();
+        assert_eq!(
+            provenance,
+            [
+                Provenance::Exact,
+                Provenance::Exact,
+                Provenance::Unrecoverable,
+                Provenance::Guessed,
+                Provenance::Guessed,
+                Provenance::Exact,
+                Provenance::Exact,
+            ]
+        );
+        assert_eq!(chars[2].offset, 3);
+    }
+}
@@ -0,0 +1,221 @@
+//!
+//! Measuring how much a conversion loses
+//!
+//! decode and encode quietly drop what they can't convert, which is
+//! right for showing text but makes it hard to tell whether a file
+//! came through intact.  decode_with_report and encode_with_report
+//! return the same output along with a ConversionReport: how many
+//! characters were dropped or replaced by a stand-in, how many
+//! control codes were consumed, and where the first few problems
+//! are.
+//!
+//! Control codes aren't problems.  Colors, cursor movement and shift
+//! codes are how PETSCII works, they just don't have a Unicode
+//! character, so they're counted separately.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    petscii::{decoder::DecodeEvent, decoder::PetsciiDecoderState, ScreenCode},
+    CharacterSet, SystemConfig,
+};
+
+/// The number of problems a report keeps the offsets of
+pub const MAX_PROBLEMS: usize = 16;
+
+/// What went wrong converting a character
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// The character was left out of the output
+    Dropped,
+    /// The character was replaced by a different one, like a
+    /// reversed character decoded as the unreversed character
+    Substituted,
+}
+
+/// A character that didn't convert cleanly
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// The offset of the character in the input, in bytes
+    pub offset: usize,
+    /// What went wrong
+    pub kind: ProblemKind,
+}
+
+/// How lossy a conversion was
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// The number of characters left out of the output
+    pub dropped: usize,
+    /// The number of characters replaced by a different one
+    pub substitutions: usize,
+    /// The number of control codes consumed when decoding, or
+    /// output when encoding
+    pub control_codes: usize,
+    /// The first MAX_PROBLEMS problems, in order
+    pub problems: Vec<Problem>,
+}
+
+impl ConversionReport {
+    /// True if nothing was dropped or substituted
+    pub fn is_lossless(&self) -> bool {
+        self.dropped == 0 && self.substitutions == 0
+    }
+
+    /// Count a problem, keeping its offset if there's room
+    fn record(&mut self, offset: usize, kind: ProblemKind) {
+        match kind {
+            ProblemKind::Dropped => self.dropped += 1,
+            ProblemKind::Substituted => self.substitutions += 1,
+        }
+        if self.problems.len() < MAX_PROBLEMS {
+            self.problems.push(Problem { offset, kind });
+        }
+    }
+}
+
+impl SystemConfig {
+    /// Decode PETSCII bytes, with a report of what was lost
+    ///
+    /// The string is the same one decode returns.  Codes without a
+    /// character are dropped, and reversed characters without a
+    /// reversed Unicode character of their own are substituted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{
+    ///         report::{Problem, ProblemKind},
+    ///         PetsciiConfig,
+    ///     },
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let (s, report) = petscii.decode_with_report(&[0x48, 0x49, 0x12, 0x41]);
+    ///
+    /// assert_eq!(s, "HIA");
+    /// assert_eq!(report.control_codes, 1);
+    /// assert_eq!(report.substitutions, 1);
+    /// assert_eq!(
+    ///     report.problems,
+    ///     [Problem {
+    ///         offset: 3,
+    ///         kind: ProblemKind::Substituted
+    ///     }]
+    /// );
+    /// ```
+    pub fn decode_with_report(&self, bytes: &[u8]) -> (String, ConversionReport) {
+        let mut state = PetsciiDecoderState::new(Some(self));
+        let mut report = ConversionReport::default();
+        let mut s = String::new();
+
+        for (offset, b) in bytes.iter().enumerate() {
+            match state.feed(*b) {
+                DecodeEvent::Char(c) => {
+                    if state.substituted() {
+                        report.record(offset, ProblemKind::Substituted);
+                    }
+                    s.push(c);
+                }
+                DecodeEvent::AttributeChange(_) | DecodeEvent::Control { .. } => {
+                    report.control_codes += 1
+                }
+                DecodeEvent::Nothing => report.record(offset, ProblemKind::Dropped),
+            }
+        }
+        (s, report)
+    }
+
+    /// Encode a string into PETSCII bytes, with a report of what was
+    /// lost
+    ///
+    /// The bytes are the same ones encode returns.  Characters without
+    /// a PETSCII code are dropped, and characters that decode back to
+    /// a different character are substituted.  The control codes are
+    /// the shift and reverse video codes added to the output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{petscii::PetsciiConfig, Configuration, SystemConfig};
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let (bytes, report) = petscii.encode_with_report("Ab€");
+    ///
+    /// assert_eq!(bytes, [0x41, 0x0e, 0x42, 0x8e]);
+    /// assert_eq!(report.control_codes, 2);
+    /// assert_eq!(report.dropped, 1);
+    /// assert_eq!(report.problems[0].offset, 2);
+    /// ```
+    pub fn encode_with_report(&self, s: &str) -> (Vec<u8>, ConversionReport) {
+        let map = &self.character_set_map;
+        let mut report = ConversionReport::default();
+        let mut encoded = 0;
+
+        for (offset, c) in s.char_indices() {
+            let screen_code = ScreenCode::from_unicode(c, map);
+            match screen_code.and_then(|code| code.to_petscii(map)) {
+                None => report.record(offset, ProblemKind::Dropped),
+                Some(_) => {
+                    encoded += 1;
+                    if screen_code.and_then(|code| code.to_unicode(map)) != Some(c) {
+                        report.record(offset, ProblemKind::Substituted);
+                    }
+                }
+            }
+        }
+
+        let bytes = self.encode(s);
+        report.control_codes = bytes.len() - encoded;
+        (bytes, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            report::{ProblemKind, MAX_PROBLEMS},
+            PetsciiConfig,
+        },
+        CharacterSet, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn report_matches_plain_conversions() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let bytes = [0x05, 0x0E, 0x48, 0x49, 0x0D, 0x12, 0x41, 0x92, 0x8E, 0x5C];
+        let (s, report) = petscii.decode_with_report(&bytes);
+        assert_eq!(s, petscii.decode(&bytes));
+        assert_eq!(report.control_codes, 5);
+
+        let (encoded, report) = petscii.encode_with_report("hi £");
+        assert_eq!(encoded, petscii.encode("hi £"));
+        assert!(report.is_lossless());
+    }
+
+    #[test]
+    fn report_keeps_the_first_problems() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let s = "€".repeat(MAX_PROBLEMS + 4);
+        let (bytes, report) = petscii.encode_with_report(&s);
+        assert!(bytes.is_empty());
+        assert_eq!(report.dropped, MAX_PROBLEMS + 4);
+        assert_eq!(report.problems.len(), MAX_PROBLEMS);
+        assert_eq!(report.problems[1].offset, '€'.len_utf8());
+        assert!(report
+            .problems
+            .iter()
+            .all(|p| p.kind == ProblemKind::Dropped));
+    }
+}
@@ -0,0 +1,214 @@
+//!
+//! Decoding damaged PETSCII streams
+//!
+//! A file recovered from a worn tape or a disk with bad sectors has
+//! stretches of noise between the text.  Decoding it straight through
+//! lets the noise leave the decoder in the wrong state: a stray 0x0E
+//! switches the rest of the file to lowercase, and a stray quote or
+//! reverse on code changes how everything after it is shown.
+//!
+//! decode_resynchronizing splits the stream into text and gaps
+//! instead.  A run of bytes is taken as text when every byte is a
+//! letter, digit, punctuation character or one of the control codes
+//! text uses (carriage returns, the character set and reverse video
+//! switches and the colors), and at least min_run of them are
+//! characters, classified with analysis::classify.  Anything else is
+//! a gap.  Each text run is decoded from a fresh decoder state in the
+//! character set the last text run left, so codes in a gap don't
+//! change how the text after it decodes.
+//!
+//! Graphics characters end a run, so PETSCII art comes out as gaps.
+//! This is for recovering text, not art.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::ops::Range;
+
+use crate::{
+    analysis::{classify, CodeClass},
+    petscii::{decoder::PetsciiDecoderState, Color},
+    SystemConfig,
+};
+
+/// A part of a damaged stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Recovered {
+    /// A run of bytes that decoded as text
+    Text {
+        /// The offset of the run in the stream
+        offset: usize,
+        /// The decoded text
+        text: String,
+    },
+    /// Bytes that didn't look like text
+    Gap(Range<usize>),
+}
+
+/// True for the control codes that show up in text
+fn is_text_control(code: u8) -> bool {
+    matches!(code, 0x0D | 0x0E | 0x8E | 0x12 | 0x92) || Color::from_control_code(code).is_some()
+}
+
+/// Decode a damaged stream into runs of text and the gaps between them
+///
+/// Text runs need at least min_run characters, not counting control
+/// codes, so shorter runs of plausible bytes in the noise are left in
+/// the gaps.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{
+///         resync::{decode_resynchronizing, Recovered},
+///         PetsciiConfig,
+///     },
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// // The 0x0E in the noise doesn't switch WORLD to lowercase
+/// let parts = decode_resynchronizing(b"HELLO\x02\xff\x0e\x03WORLD", 4, &petscii);
+///
+/// assert_eq!(
+///     parts,
+///     vec![
+///         Recovered::Text { offset: 0, text: String::from("HELLO") },
+///         Recovered::Gap(5..9),
+///         Recovered::Text { offset: 9, text: String::from("WORLD") },
+///     ]
+/// );
+/// ```
+pub fn decode_resynchronizing(
+    bytes: &[u8],
+    min_run: usize,
+    character_map: &SystemConfig,
+) -> Vec<Recovered> {
+    split_runs(bytes, min_run)
+        .into_iter()
+        .map(|span| match span {
+            Span::Text { range, shifted } => Recovered::Text {
+                offset: range.start,
+                text: PetsciiDecoderState::new(Some(character_map))
+                    .with_shifted(shifted)
+                    .decode_chunk(&bytes[range]),
+            },
+            Span::Gap(range) => Recovered::Gap(range),
+        })
+        .collect()
+}
+
+/// A part of a damaged stream before it's decoded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Span {
+    /// Bytes that look like text
+    Text {
+        /// Where the bytes are in the stream
+        range: Range<usize>,
+        /// True if the run starts in the shifted character set
+        shifted: bool,
+    },
+    /// Bytes that don't
+    Gap(Range<usize>),
+}
+
+/// Split a damaged stream into text runs and gaps
+pub(crate) fn split_runs(bytes: &[u8], min_run: usize) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut shifted = false;
+    let mut gap_start = None;
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        // Scan the longest run of plausible bytes from here
+        let mut run_shifted = shifted;
+        let mut characters = 0;
+        let mut end = offset;
+        while let Some(code) = bytes.get(end) {
+            match classify(*code, run_shifted) {
+                CodeClass::Text => characters += 1,
+                CodeClass::Control if is_text_control(*code) => match *code {
+                    0x0E => run_shifted = true,
+                    0x8E => run_shifted = false,
+                    _ => (),
+                },
+                _ => break,
+            }
+            end += 1;
+        }
+
+        if end > offset && characters >= min_run.max(1) {
+            if let Some(start) = gap_start.take() {
+                spans.push(Span::Gap(start..offset));
+            }
+            spans.push(Span::Text {
+                range: offset..end,
+                shifted,
+            });
+            shifted = run_shifted;
+            offset = end;
+        } else {
+            // The byte that ended the run may start one in the
+            // character set from before the run, so only the run is
+            // skipped
+            gap_start.get_or_insert(offset);
+            offset = end.max(offset + 1);
+        }
+    }
+
+    if let Some(start) = gap_start {
+        spans.push(Span::Gap(start..bytes.len()));
+    }
+
+    spans
+}
+
+/// Join recovered parts into one string, with a U+FFFD REPLACEMENT
+/// CHARACTER for each gap
+pub fn join_recovered(parts: &[Recovered]) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            Recovered::Text { text, .. } => text.as_str(),
+            Recovered::Gap(_) => "\u{fffd}",
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            resync::{decode_resynchronizing, join_recovered, Recovered},
+            PetsciiConfig,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn resync_marks_gaps_and_keeps_shift_state() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Lowercase text, noise with a quote, reverse on and an
+        // unshift, lowercase text again, and a short run in the noise
+        // at the end
+        let mut bytes = b"\x0eHI \xd4HERE".to_vec();
+        bytes.extend_from_slice(b"\x00\xff\x8e\x22\x12\x01");
+        bytes.extend_from_slice(b"\x05\xc2YE\x0d");
+        bytes.extend_from_slice(b"\x02OK\xf0");
+
+        let parts = decode_resynchronizing(&bytes, 3, &petscii);
+        assert_eq!(parts[1], Recovered::Gap(9..15));
+        assert_eq!(parts[3], Recovered::Gap(20..24));
+        assert_eq!(join_recovered(&parts), "hi There\u{fffd}Bye\r\u{fffd}");
+
+        assert!(decode_resynchronizing(&[], 3, &petscii).is_empty());
+        assert_eq!(
+            decode_resynchronizing(b"\x0e\x8e", 0, &petscii),
+            vec![Recovered::Gap(0..2)]
+        );
+    }
+}
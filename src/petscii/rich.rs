@@ -0,0 +1,272 @@
+//!
+//! Decoding PETSCII into styled spans
+//!
+//! Terminal frontends and HTML exporters draw text with colors and
+//! need to know where each style starts and ends, which a decoded
+//! string doesn't say.  decode_rich returns RichText instead, a list
+//! of spans of text that share the same colors and flags.
+//!
+//! A PETSCII stream can't change the background color, so spans
+//! decoded from one don't have a background.  Blinking is the flash
+//! attribute of the C128 80 column screen, turned on by 0x0F and off
+//! by 0x8F.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    petscii::{
+        decoder::{PetsciiEvent, PetsciiEventDecoder},
+        Color,
+    },
+    DecodeOptions, SystemConfig,
+};
+
+/// A run of text with the same style
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// The text
+    pub text: String,
+    /// The text color
+    pub foreground: Color,
+    /// The background color, None for the screen background
+    pub background: Option<Color>,
+    /// True if the text is in reverse video.  Characters that have a
+    /// reversed Unicode character are already that character.
+    pub reversed: bool,
+    /// True if the text blinks
+    pub blink: bool,
+}
+
+impl Span {
+    /// True if the other span has the same style
+    pub fn same_style(&self, other: &Span) -> bool {
+        (self.foreground, self.background, self.reversed, self.blink)
+            == (
+                other.foreground,
+                other.background,
+                other.reversed,
+                other.blink,
+            )
+    }
+}
+
+/// Styled text, as a list of spans
+///
+/// Line breaks are newline characters in the span text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RichText {
+    /// The spans, in order
+    pub spans: Vec<Span>,
+}
+
+impl RichText {
+    /// Add text to the end, joining it to the last span if it has the
+    /// same style
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::{
+    ///     rich::{RichText, Span},
+    ///     Color,
+    /// };
+    ///
+    /// let mut text = RichText::default();
+    /// for s in ["HELLO", " WORLD"] {
+    ///     text.push(Span {
+    ///         text: s.to_string(),
+    ///         foreground: Color::White,
+    ///         background: None,
+    ///         reversed: false,
+    ///         blink: false,
+    ///     });
+    /// }
+    ///
+    /// assert_eq!(text.spans.len(), 1);
+    /// assert_eq!(text.to_string(), "HELLO WORLD");
+    /// ```
+    pub fn push(&mut self, span: Span) {
+        match self.spans.last_mut() {
+            Some(last) if last.same_style(&span) => last.text.push_str(&span.text),
+            _ => self.spans.push(span),
+        }
+    }
+
+    /// True if there isn't any text
+    pub fn is_empty(&self) -> bool {
+        self.spans.iter().all(|span| span.text.is_empty())
+    }
+
+    /// The spans of each line, split at the newlines
+    ///
+    /// Spans that cross a line break are split into a span on each
+    /// line, and the newlines are left out.
+    pub fn lines(&self) -> Vec<Vec<Span>> {
+        let mut lines = vec![Vec::new()];
+        for span in &self.spans {
+            for (i, text) in span.text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Vec::new());
+                }
+                if !text.is_empty() {
+                    lines.last_mut().expect("There's always a line").push(Span {
+                        text: text.to_string(),
+                        ..span.clone()
+                    });
+                }
+            }
+        }
+        lines
+    }
+}
+
+impl Display for RichText {
+    /// The text without styles
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for span in &self.spans {
+            f.write_str(&span.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl SystemConfig {
+    /// Decode PETSCII bytes into styled text
+    ///
+    /// Decoding starts in the C64 start state, with light blue text.
+    /// Carriage returns become newlines.  Cursor movement and other
+    /// control codes are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{Color, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let text = petscii.decode_rich(&[0x1C, 0x48, 0x49, 0x0D, 0x12, 0x41]);
+    ///
+    /// assert_eq!(text.to_string(), "HI\nA");
+    /// assert_eq!(text.spans[0].text, "HI\n");
+    /// assert_eq!(text.spans[0].foreground, Color::Red);
+    /// assert!(text.spans[1].reversed);
+    /// ```
+    pub fn decode_rich(&self, bytes: &[u8]) -> RichText {
+        self.decode_rich_with_options(bytes, &DecodeOptions::default())
+    }
+
+    /// Decode PETSCII bytes into styled text, starting in the state
+    /// of the options
+    ///
+    /// The decoding target isn't used, the spans keep the Unicode
+    /// characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{Color, PetsciiConfig},
+    ///     Configuration, DecodeOptions, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let options = DecodeOptions::default()
+    ///     .with_initial_shifted(true)
+    ///     .with_initial_color(Some(Color::Green));
+    /// let text = petscii.decode_rich_with_options(&[0x48, 0x49], &options);
+    ///
+    /// assert_eq!(text.spans[0].text, "hi");
+    /// assert_eq!(text.spans[0].foreground, Color::Green);
+    /// ```
+    pub fn decode_rich_with_options(&self, bytes: &[u8], options: &DecodeOptions) -> RichText {
+        let mut decoder = PetsciiEventDecoder::with_options(self, options);
+        let mut text = RichText::default();
+        let mut blink = false;
+
+        for b in bytes {
+            let (c, reversed) = match decoder.feed(*b) {
+                Some(PetsciiEvent::PrintChar { c, reversed, .. }) => (c, reversed),
+                Some(PetsciiEvent::NewLine) => ('\n', false),
+                Some(PetsciiEvent::Control(0x0F)) => {
+                    blink = true;
+                    continue;
+                }
+                Some(PetsciiEvent::Control(0x8F)) => {
+                    blink = false;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            text.push(Span {
+                text: c.to_string(),
+                foreground: decoder.color(),
+                background: None,
+                reversed,
+                blink,
+            });
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{rich::Span, Color, PetsciiConfig},
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn rich_spans_follow_attributes() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // White, flashing white, then a red line
+        let text = petscii.decode_rich(&[0x05, 0x41, 0x0F, 0x42, 0x8F, 0x0D, 0x1C, 0x43]);
+        let styles: Vec<(Color, bool)> = text
+            .spans
+            .iter()
+            .map(|span| (span.foreground, span.blink))
+            .collect();
+        assert_eq!(
+            styles,
+            [
+                (Color::White, false),
+                (Color::White, true),
+                (Color::White, false),
+                (Color::Red, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn rich_lines_split_spans() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let text = petscii.decode_rich(b"AB\rCD\r");
+        let lines = text.lines();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            [Span {
+                text: String::from("CD"),
+                foreground: Color::LightBlue,
+                background: None,
+                reversed: false,
+                blink: false,
+            }]
+        );
+        assert!(lines[2].is_empty());
+    }
+}
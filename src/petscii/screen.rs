@@ -0,0 +1,713 @@
+//!
+//! A model of the C64 text screen
+//!
+//! The C64 keeps the characters on the screen in screen memory as
+//! screen codes, one byte per cell, and the color of each cell in
+//! color RAM.  Screen codes aren't PETSCII codes.  They're the order
+//! of the characters in the character ROM, with bit 7 selecting the
+//! reversed copy of each character.
+//!
+//! The whole screen shows either the uppercase and graphics character
+//! set or the lowercase and uppercase set, and has one background
+//! and one border color.
+//!
+//! Screen can have any size, chosen when it's created.  FixedScreen
+//! has its size in its type, so the screen and color memory of each
+//! machine are arrays whose sizes are checked when compiling.
+//! C64Screen, PetScreen, Vic20Screen and C128Screen are the 40 and 80
+//! column screens of the Commodore machines.  They convert to and
+//! from Screen, which the terminal and renderers work on.
+//!
+//! VIC-20 screens are read from dumps of screen memory and color RAM
+//! with from_vic20_memory.  The VIC-20 has the same character ROM
+//! layout, but chooses the character set and reverse video with its
+//! video chip registers, and has fewer character colors.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::Write;
+
+use crate::{
+    error::{Error, ErrorKind},
+    lookup_char,
+    palette::Palette,
+    petscii::Color,
+    SystemConfig,
+};
+
+/// The width of the C64 screen
+pub const C64_COLUMNS: usize = 40;
+
+/// The height of the C64 screen
+pub const C64_ROWS: usize = 25;
+
+/// The screen code of a space
+pub const SPACE: u8 = 0x20;
+
+/// A blank cell in the C64 power-on text color
+const BLANK: Cell = Cell {
+    screen_code: SPACE,
+    color: Color::LightBlue,
+};
+
+/// The character set shown on the screen
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Charset {
+    /// Uppercase letters and graphics characters, the set the C64
+    /// starts in
+    #[default]
+    Uppercase,
+    /// Lowercase and uppercase letters
+    Lowercase,
+}
+
+/// A character cell on the screen
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    /// The screen code, with bit 7 set for reversed characters
+    pub screen_code: u8,
+    /// The color of the character
+    pub color: Color,
+}
+
+impl Cell {
+    /// True if the character is reversed
+    pub fn is_reversed(&self) -> bool {
+        self.screen_code & 0x80 != 0
+    }
+}
+
+/// The text screen of a C64, or a screen of another size
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{screen::Screen, Color, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let mut screen = Screen::c64();
+/// screen.set(0, 0, 0x08, Color::White);
+/// screen.set(1, 0, 0x09, Color::White);
+///
+/// assert_eq!(screen.row_text(0, &petscii), "HI");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Screen {
+    /// The number of columns
+    pub columns: usize,
+    /// The number of rows
+    pub rows: usize,
+    /// The cells, a row at a time
+    pub cells: Vec<Cell>,
+    /// The background color
+    pub background: Color,
+    /// The border color
+    pub border: Color,
+    /// The character set shown
+    pub charset: Charset,
+}
+
+impl Screen {
+    /// Create a blank screen in the C64 power-on colors
+    pub fn new(columns: usize, rows: usize) -> Screen {
+        Screen {
+            columns,
+            rows,
+            cells: vec![BLANK; columns * rows],
+            background: Color::Blue,
+            border: Color::LightBlue,
+            charset: Charset::Uppercase,
+        }
+    }
+
+    /// Create a blank 40 by 25 C64 screen
+    pub fn c64() -> Screen {
+        Screen::new(C64_COLUMNS, C64_ROWS)
+    }
+
+    /// The cell at a column and row
+    pub fn cell(&self, column: usize, row: usize) -> Option<&Cell> {
+        if column < self.columns && row < self.rows {
+            self.cells.get(row * self.columns + column)
+        } else {
+            None
+        }
+    }
+
+    /// Set the cell at a column and row
+    ///
+    /// Positions outside the screen are ignored.
+    pub fn set(&mut self, column: usize, row: usize, screen_code: u8, color: Color) {
+        if column < self.columns && row < self.rows {
+            self.cells[row * self.columns + column] = Cell { screen_code, color };
+        }
+    }
+
+    /// The cells in a row
+    ///
+    /// Returns None for a row outside the screen, or if there aren't
+    /// enough cells for it.
+    pub fn row(&self, row: usize) -> Option<&[Cell]> {
+        if row < self.rows {
+            self.cells
+                .get(row * self.columns..(row + 1).checked_mul(self.columns)?)
+        } else {
+            None
+        }
+    }
+
+    /// Fill the screen with spaces in a color
+    pub fn clear(&mut self, color: Color) {
+        self.cells.fill(Cell {
+            screen_code: SPACE,
+            color,
+        });
+    }
+
+    /// Move every row up one row, leaving a blank row at the bottom
+    pub fn scroll_up(&mut self, color: Color) {
+        if self.rows == 0 {
+            return;
+        }
+        self.cells.drain(..self.columns);
+        self.cells.extend(std::iter::repeat_n(
+            Cell {
+                screen_code: SPACE,
+                color,
+            },
+            self.columns,
+        ));
+    }
+
+    /// The screen codes of the cells, the contents of screen memory
+    pub fn screen_codes(&self) -> Vec<u8> {
+        self.cells.iter().map(|cell| cell.screen_code).collect()
+    }
+
+    /// The color numbers of the cells, the contents of color RAM
+    pub fn color_codes(&self) -> Vec<u8> {
+        self.cells.iter().map(|cell| cell.color.index()).collect()
+    }
+
+    /// The Unicode character shown by a screen code in the screen's
+    /// character set
+    pub fn char_for(&self, screen_code: u8, petscii: &SystemConfig) -> char {
+        let map = &petscii.character_set_map;
+        let lookup = |code| {
+            let lowercase = match self.charset {
+                // The set 2 map only has the codes that differ from set
+                // 1, and leaves out the uppercase letters that replace
+                // the graphics characters
+                Charset::Lowercase => {
+                    lookup_char(&map.c64_screen_codes_set_2_to_unicode_codes, code).or(match code {
+                        0x41..=0x5A => Some(char::from(code)),
+                        _ => None,
+                    })
+                }
+                Charset::Uppercase => None,
+            };
+            lowercase.or_else(|| lookup_char(&map.c64_screen_codes_set_1_to_unicode_codes, code))
+        };
+
+        // Reversed characters without a Unicode character of their
+        // own are shown as the character
+        lookup(screen_code)
+            .or_else(|| lookup(screen_code & 0x7F))
+            .unwrap_or(' ')
+    }
+
+    /// The text in a row, without trailing spaces
+    pub fn row_text(&self, row: usize, petscii: &SystemConfig) -> String {
+        let text: String = self
+            .row(row)
+            .unwrap_or_default()
+            .iter()
+            .map(|cell| self.char_for(cell.screen_code, petscii))
+            .collect();
+        text.trim_end_matches(' ').to_string()
+    }
+
+    /// The text on the screen, a line for each row
+    ///
+    /// Trailing spaces and empty rows at the bottom are left out.
+    pub fn to_text(&self, petscii: &SystemConfig) -> String {
+        let lines: Vec<String> = (0..self.rows)
+            .map(|row| self.row_text(row, petscii))
+            .collect();
+        let len = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |position| position + 1);
+        lines[..len].join("\n")
+    }
+
+    /// The screen as an HTML pre element, with the colors from a
+    /// palette
+    ///
+    /// Reversed characters are drawn as the character with the text
+    /// and background colors swapped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     palette::Palette,
+    ///     petscii::{screen::Screen, Color, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut screen = Screen::new(2, 1);
+    /// screen.set(0, 0, 0x3C, Color::White);
+    ///
+    /// assert_eq!(
+    ///     screen.to_html(&petscii, &Palette::COLODORE),
+    ///     "<pre style=\"background-color: #2e2c9b; border: 1em solid #706deb\">\
+    ///      <span style=\"color: #ffffff\">&lt;</span>\
+    ///      <span style=\"color: #706deb\"> </span>\n</pre>"
+    /// );
+    /// ```
+    pub fn to_html(&self, petscii: &SystemConfig, palette: &Palette) -> String {
+        let background = palette.rgb(self.background);
+        let mut html = format!(
+            "<pre style=\"background-color: {}; border: 1em solid {}\">",
+            background,
+            palette.rgb(self.border)
+        );
+
+        for row in 0..self.rows {
+            let mut run = String::new();
+            let mut run_style = String::new();
+
+            for cell in self.row(row).unwrap_or_default() {
+                let color = palette.rgb(cell.color);
+                let style = if cell.is_reversed() {
+                    format!("color: {}; background-color: {}", background, color)
+                } else {
+                    format!("color: {}", color)
+                };
+                if style != run_style && !run.is_empty() {
+                    let _ = write!(html, "<span style=\"{}\">{}</span>", run_style, run);
+                    run.clear();
+                }
+                run_style = style;
+
+                match self.char_for(cell.screen_code & 0x7F, petscii) {
+                    '<' => run.push_str("&lt;"),
+                    '>' => run.push_str("&gt;"),
+                    '&' => run.push_str("&amp;"),
+                    c => run.push(c),
+                }
+            }
+            if !run.is_empty() {
+                let _ = write!(html, "<span style=\"{}\">{}</span>", run_style, run);
+            }
+            html.push('\n');
+        }
+
+        html.push_str("</pre>");
+        html
+    }
+}
+
+/// A screen with its size in its type
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{
+///         screen::{Screen, Vic20Screen},
+///         terminal::PetsciiTerminal,
+///         PetsciiConfig,
+///     },
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// // Print onto a VIC-20 screen, where lines wrap after 22 columns
+/// let mut terminal = PetsciiTerminal::with_screen(Vic20Screen::new().into());
+/// terminal.print(b"**** CBM BASIC V2 ****");
+/// terminal.print(b"3583 BYTES FREE");
+///
+/// let screen = Vic20Screen::try_from(terminal.into_screen()).expect("The size is the same");
+/// assert_eq!(screen.screen_memory()[1][..4], [0x33, 0x35, 0x38, 0x33]);
+/// assert_eq!(Screen::from(screen).row_text(1, &petscii), "3583 BYTES FREE");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedScreen<const W: usize, const H: usize> {
+    /// The cells, a row at a time
+    pub cells: [[Cell; W]; H],
+    /// The background color
+    pub background: Color,
+    /// The border color
+    pub border: Color,
+    /// The character set shown
+    pub charset: Charset,
+}
+
+/// The 40 by 25 screen of the C64
+pub type C64Screen = FixedScreen<40, 25>;
+
+/// The 40 by 25 screen of the 40 column PETs
+pub type PetScreen = FixedScreen<40, 25>;
+
+/// The 22 by 23 screen of the VIC-20
+pub type Vic20Screen = FixedScreen<22, 23>;
+
+/// The 80 by 25 screen of the C128 in 80 column mode
+pub type C128Screen = FixedScreen<80, 25>;
+
+impl<const W: usize, const H: usize> FixedScreen<W, H> {
+    /// The number of columns
+    pub const COLUMNS: usize = W;
+
+    /// The number of rows
+    pub const ROWS: usize = H;
+
+    /// Create a blank screen in the C64 power-on colors
+    pub fn new() -> FixedScreen<W, H> {
+        FixedScreen {
+            cells: [[BLANK; W]; H],
+            background: Color::Blue,
+            border: Color::LightBlue,
+            charset: Charset::Uppercase,
+        }
+    }
+
+    /// Build a screen from the contents of screen memory and color
+    /// RAM
+    ///
+    /// Only the low four bits of the colors are used, like the C64
+    /// does.
+    pub fn from_memory(screen_codes: &[[u8; W]; H], colors: &[[u8; W]; H]) -> FixedScreen<W, H> {
+        let mut screen = FixedScreen::new();
+        for (row, (codes, colors)) in screen_codes.iter().zip(colors).enumerate() {
+            for (column, (screen_code, color)) in codes.iter().zip(colors).enumerate() {
+                screen.cells[row][column] = Cell {
+                    screen_code: *screen_code,
+                    color: Color::from_index(*color),
+                };
+            }
+        }
+        screen
+    }
+
+    /// The cell at a column and row
+    pub fn cell(&self, column: usize, row: usize) -> Option<&Cell> {
+        self.cells.get(row)?.get(column)
+    }
+
+    /// Set the cell at a column and row
+    ///
+    /// Positions outside the screen are ignored.
+    pub fn set(&mut self, column: usize, row: usize, screen_code: u8, color: Color) {
+        if let Some(cell) = self.cells.get_mut(row).and_then(|r| r.get_mut(column)) {
+            *cell = Cell { screen_code, color };
+        }
+    }
+
+    /// The cells in a row, or None if the row is outside the screen
+    pub fn row(&self, row: usize) -> Option<&[Cell; W]> {
+        self.cells.get(row)
+    }
+
+    /// The contents of screen memory
+    pub fn screen_memory(&self) -> [[u8; W]; H] {
+        self.cells.map(|row| row.map(|cell| cell.screen_code))
+    }
+
+    /// The contents of color RAM
+    pub fn color_memory(&self) -> [[u8; W]; H] {
+        self.cells.map(|row| row.map(|cell| cell.color.index()))
+    }
+}
+
+impl<const W: usize, const H: usize> Default for FixedScreen<W, H> {
+    fn default() -> Self {
+        FixedScreen::new()
+    }
+}
+
+impl<const W: usize, const H: usize> From<FixedScreen<W, H>> for Screen {
+    fn from(screen: FixedScreen<W, H>) -> Screen {
+        Screen {
+            columns: W,
+            rows: H,
+            cells: screen.cells.iter().flatten().copied().collect(),
+            background: screen.background,
+            border: screen.border,
+            charset: screen.charset,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> TryFrom<Screen> for FixedScreen<W, H> {
+    type Error = Error;
+
+    /// Convert a screen of the same size, or return an error
+    fn try_from(screen: Screen) -> std::result::Result<FixedScreen<W, H>, Error> {
+        if (screen.columns, screen.rows) != (W, H) || screen.cells.len() != W * H {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "A {}x{} screen can't be converted to a {}x{} screen",
+                screen.columns, screen.rows, W, H
+            ))));
+        }
+
+        let mut fixed = FixedScreen::new();
+        for (row, cells) in fixed.cells.iter_mut().zip(screen.cells.chunks_exact(W)) {
+            row.copy_from_slice(cells);
+        }
+        fixed.background = screen.background;
+        fixed.border = screen.border;
+        fixed.charset = screen.charset;
+        Ok(fixed)
+    }
+}
+
+/// The number of cells on the VIC-20 screen
+pub const VIC20_CELLS: usize = 22 * 23;
+
+/// The nearest C64 color for each of the 16 VIC-20 colors
+///
+/// The first 8 are the same.  The light colors that only the VIC-20
+/// has become the nearest C64 color.
+const VIC20_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::White,
+    Color::Red,
+    Color::Cyan,
+    Color::Purple,
+    Color::Green,
+    Color::Blue,
+    Color::Yellow,
+    // Orange, light orange, pink and light cyan
+    Color::Orange,
+    Color::Orange,
+    Color::LightRed,
+    Color::Cyan,
+    // Light purple, light green, light blue and light yellow
+    Color::Purple,
+    Color::LightGreen,
+    Color::LightBlue,
+    Color::Yellow,
+];
+
+/// The registers of the VIC-20 video chip that change how the text
+/// screen looks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vic20Registers {
+    /// Register $9005.  The low four bits select the character
+    /// memory.
+    pub character_memory: u8,
+    /// Register $900F.  The border color is in bits 0 to 2, bit 3 is
+    /// clear in inverse mode and the background color is in bits 4
+    /// to 7.
+    pub screen_colors: u8,
+}
+
+impl Default for Vic20Registers {
+    /// The registers of an unexpanded VIC-20 after power on, with the
+    /// uppercase character set and a cyan border around white
+    fn default() -> Self {
+        Vic20Registers {
+            character_memory: 0xF0,
+            screen_colors: 0x1B,
+        }
+    }
+}
+
+impl FixedScreen<22, 23> {
+    /// Build a VIC-20 screen from dumps of its screen memory and
+    /// color RAM
+    ///
+    /// The VIC-20 keeps its screen in different places depending on
+    /// the memory expansion, so the dumps are the memory starting at
+    /// the screen, $1E00 on an unexpanded machine and $1000 on an
+    /// expanded one, and the color RAM starting at $9600 or $9400.
+    /// Longer dumps are fine, only the first 506 bytes are used.
+    ///
+    /// The character set comes from the character memory register.
+    /// The ROM has the uppercase set at $8000 and the lowercase set at
+    /// $8800, with a reversed copy of each 1K after it.  Custom
+    /// character sets in RAM can't be decoded and are read as the
+    /// uppercase set.  In inverse mode every cell is reversed.
+    ///
+    /// Color RAM only has 8 character colors, and bit 3 is the
+    /// multicolor flag, which is ignored.  The VIC-20 colors above 7
+    /// only exist as background colors and become the nearest C64
+    /// color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{
+    ///         screen::{Screen, Vic20Registers, Vic20Screen},
+    ///         Color, PetsciiConfig,
+    ///     },
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut screen_ram = [0x20; 512];
+    /// screen_ram[23..28].copy_from_slice(&[0x12, 0x05, 0x01, 0x04, 0x19]);
+    /// let color_ram = [0xF6; 512];
+    ///
+    /// let screen = Vic20Screen::from_vic20_memory(&screen_ram, &color_ram, Vic20Registers::default())
+    ///     .expect("Error reading the dumps");
+    ///
+    /// assert_eq!(screen.background, Color::White);
+    /// assert_eq!(screen.border, Color::Cyan);
+    /// assert_eq!(screen.cell(1, 1).map(|cell| cell.color), Some(Color::Blue));
+    /// assert_eq!(Screen::from(screen).to_text(&petscii), "\n READY");
+    /// ```
+    pub fn from_vic20_memory(
+        screen_ram: &[u8],
+        color_ram: &[u8],
+        registers: Vic20Registers,
+    ) -> std::result::Result<Vic20Screen, Error> {
+        if screen_ram.len() < VIC20_CELLS || color_ram.len() < VIC20_CELLS {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "A VIC-20 screen needs {} bytes of screen memory and color RAM, found {} and {}",
+                VIC20_CELLS,
+                screen_ram.len(),
+                color_ram.len()
+            ))));
+        }
+
+        // The 1K blocks at $8400 and $8C00 are the reversed sets
+        let (charset, reversed_set) = match registers.character_memory & 0x0F {
+            0x1 => (Charset::Uppercase, true),
+            0x2 => (Charset::Lowercase, false),
+            0x3 => (Charset::Lowercase, true),
+            _ => (Charset::Uppercase, false),
+        };
+        let inverse_mode = registers.screen_colors & 0x08 == 0;
+        let reverse_bit = if reversed_set != inverse_mode {
+            0x80
+        } else {
+            0
+        };
+
+        let mut screen = FixedScreen::new();
+        for (i, (screen_code, color)) in screen_ram.iter().zip(color_ram).enumerate() {
+            screen.set(
+                i % 22,
+                i / 22,
+                screen_code ^ reverse_bit,
+                VIC20_COLORS[usize::from(color & 0x07)],
+            );
+        }
+        screen.background = VIC20_COLORS[usize::from(registers.screen_colors >> 4)];
+        screen.border = VIC20_COLORS[usize::from(registers.screen_colors & 0x07)];
+        screen.charset = charset;
+        Ok(screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            screen::{C128Screen, C64Screen, Charset, Screen, Vic20Registers, Vic20Screen},
+            Color, PetsciiConfig,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn screen_charsets_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut screen = Screen::new(2, 2);
+        screen.set(0, 1, 0x01, Color::White);
+        screen.set(1, 1, 0x41, Color::White);
+        assert_eq!(screen.to_text(&petscii), "\nA♠");
+
+        screen.charset = Charset::Lowercase;
+        assert_eq!(screen.to_text(&petscii), "\naA");
+    }
+
+    #[test]
+    fn screen_scroll_up_works() {
+        let mut screen = Screen::new(1, 2);
+        screen.set(0, 1, 0x01, Color::Red);
+        screen.scroll_up(Color::Black);
+
+        assert_eq!(screen.screen_codes(), vec![0x01, 0x20]);
+        assert_eq!(screen.color_codes(), vec![2, 0]);
+    }
+
+    #[test]
+    fn screen_row_out_of_range_is_none() {
+        let mut screen = Screen::new(2, 2);
+        assert_eq!(screen.row(1).map(|row| row.len()), Some(2));
+        assert!(screen.row(2).is_none());
+
+        // The fields can disagree about the size of the screen
+        screen.cells.truncate(3);
+        assert!(screen.row(1).is_none());
+        assert_eq!(
+            screen.row_text(1, &PetsciiConfig::load().unwrap().system().unwrap()),
+            ""
+        );
+    }
+
+    #[test]
+    fn screen_fixed_size_round_trips() {
+        let mut codes = [[0x20; 40]; 25];
+        codes[24][39] = 0x01;
+        let colors = [[0x11; 40]; 25];
+        let fixed = C64Screen::from_memory(&codes, &colors);
+        assert_eq!(
+            fixed.cell(39, 24).map(|cell| cell.color),
+            Some(Color::White)
+        );
+        assert_eq!(fixed.color_memory()[0][0], 1);
+
+        let screen = Screen::from(fixed.clone());
+        assert_eq!(screen.cell(39, 24).map(|cell| cell.screen_code), Some(0x01));
+        assert_eq!(C64Screen::try_from(screen.clone()).unwrap(), fixed);
+        assert!(C128Screen::try_from(screen).is_err());
+    }
+
+    #[test]
+    fn screen_vic20_registers_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        // Lowercase, in inverse mode, with a black border around a
+        // light blue background
+        let registers = Vic20Registers {
+            character_memory: 0xF2,
+            screen_colors: 0xE0,
+        };
+        let screen = Vic20Screen::from_vic20_memory(&[0x01; 506], &[0x0A; 506], registers).unwrap();
+        assert_eq!(screen.charset, Charset::Lowercase);
+        assert_eq!(screen.row(22).map(|row| row[21].screen_code), Some(0x81));
+        assert_eq!(screen.row(0).map(|row| row[0].color), Some(Color::Red));
+        assert!(screen.row(23).is_none());
+        assert_eq!(
+            (screen.background, screen.border),
+            (Color::LightBlue, Color::Black)
+        );
+        assert_eq!(Screen::from(screen).row_text(0, &petscii), "a".repeat(22));
+
+        assert!(Vic20Screen::from_vic20_memory(&[0x01; 400], &[0; 506], registers).is_err());
+    }
+}
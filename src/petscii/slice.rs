@@ -0,0 +1,247 @@
+//!
+//! Borrowed PETSCII strings of any length
+//!
+//! PetsciiString carries its field width in its type, which suits
+//! the fixed-length names it was made for but makes every function
+//! that takes one generic over the width.  PetsciiStr is to
+//! PetsciiString what str is to String: an unsized view of the bytes,
+//! so a function taking &PetsciiStr accepts a name of any width, or a
+//! plain byte slice.
+//!
+//! The comparisons and pattern matching on PetsciiString work on the
+//! view, and decode turns it into text with a character set.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{
+    fmt::{Debug, Formatter, Result},
+    ops::Deref,
+};
+
+use crate::{
+    cbm::{matches_pattern, trim_padding, DISK_PADDING},
+    petscii::{petscii_bytes_to_unicode, PetsciiString},
+    SystemConfig,
+};
+
+/// A borrowed PETSCII string
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::{PetsciiStr, PetsciiString};
+///
+/// fn is_game(name: &PetsciiStr) -> bool {
+///     name.matches_cbm_pattern(b"GAME*")
+/// }
+///
+/// let field = PetsciiString::<8>::from_byte_slice_strip_shifted_space(b"GAME 1\xa0\xa0");
+///
+/// assert!(is_game(field.as_petscii_str()));
+/// assert!(is_game(PetsciiStr::new(b"GAMES")));
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct PetsciiStr([u8]);
+
+impl PetsciiStr {
+    /// View a byte slice as a PETSCII string
+    #[allow(unsafe_code)]
+    pub fn new(bytes: &[u8]) -> &PetsciiStr {
+        // SAFETY: PetsciiStr is a transparent wrapper around [u8], so
+        // the pointer casts keep the slice's address, length and
+        // lifetime.
+        unsafe { &*(bytes as *const [u8] as *const PetsciiStr) }
+    }
+
+    /// The bytes of the string
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The number of bytes in the string
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// True if the string is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the bytes of the string
+    pub fn iter(&self) -> std::slice::Iter<'_, u8> {
+        self.0.iter()
+    }
+
+    /// The number of shifted spaces (0xA0) at the end of the string
+    pub fn padding_len(&self) -> usize {
+        self.len() - self.trim_padding().len()
+    }
+
+    /// The string without the shifted space padding at the end
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiStr;
+    ///
+    /// let name = PetsciiStr::new(b"GAME\xa0\xa0");
+    ///
+    /// assert_eq!(name.trim_padding().as_bytes(), b"GAME");
+    /// assert_eq!(name.padding_len(), 2);
+    /// ```
+    pub fn trim_padding(&self) -> &PetsciiStr {
+        PetsciiStr::new(trim_padding(&self.0, &[DISK_PADDING]))
+    }
+
+    /// The bytes without the padding at the end
+    ///
+    /// Shifted spaces and zeros are both removed, since names read
+    /// into a PetsciiString that's wider than the name end in zeros
+    /// after the shifted space padding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::petscii::PetsciiString;
+    ///
+    /// let mut data = [0; 20];
+    /// data[..6].copy_from_slice(b"GAME\xa0\xa0");
+    /// let name = PetsciiString::<20>::try_from(&data[..]).expect("Name too long");
+    ///
+    /// assert_eq!(name.as_trimmed_bytes(), b"GAME");
+    /// ```
+    pub fn as_trimmed_bytes(&self) -> &[u8] {
+        trim_padding(&self.0, &[DISK_PADDING, 0])
+    }
+
+    /// True if the strings are the same apart from the case of their
+    /// letters
+    ///
+    /// See PetsciiString::eq_ignore_case for how letters are folded.
+    pub fn eq_ignore_case(&self, other: &PetsciiStr) -> bool {
+        fn folded(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+            bytes
+                .iter()
+                .filter(|b| !matches!(**b, 0x0E | 0x8E))
+                .map(|b| match *b {
+                    0x61..=0x7A => b - 0x20,
+                    0xC1..=0xDA => b - 0x80,
+                    b => b,
+                })
+        }
+
+        folded(&self.0).eq(folded(&other.0))
+    }
+
+    /// True if the strings are the same apart from shifted space
+    /// padding at the end
+    pub fn eq_ignore_padding(&self, other: &PetsciiStr) -> bool {
+        self.trim_padding() == other.trim_padding()
+    }
+
+    /// True if the string matches a CBM DOS filename pattern
+    ///
+    /// See cbm::matches_pattern for the rules.
+    pub fn matches_cbm_pattern(&self, pattern: &[u8]) -> bool {
+        matches_pattern(&self.0, pattern)
+    }
+
+    /// Decode the string with a character set
+    ///
+    /// Without a character set the bytes are converted to the Unicode
+    /// characters with the same values, like a PetsciiString without
+    /// a character map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{PetsciiConfig, PetsciiStr},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// assert_eq!(PetsciiStr::new(b"HI\x5c").decode(Some(&petscii)), "HI£");
+    /// ```
+    pub fn decode(&self, character_map: Option<&SystemConfig>) -> String {
+        petscii_bytes_to_unicode(self.iter().copied(), false, character_map)
+    }
+}
+
+impl Debug for PetsciiStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "PetsciiStr({:02X?})", &self.0)
+    }
+}
+
+impl Default for &PetsciiStr {
+    fn default() -> Self {
+        PetsciiStr::new(&[])
+    }
+}
+
+impl AsRef<[u8]> for PetsciiStr {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a [u8]> for &'a PetsciiStr {
+    fn from(bytes: &'a [u8]) -> &'a PetsciiStr {
+        PetsciiStr::new(bytes)
+    }
+}
+
+impl<'a, const L: usize> PetsciiString<'a, L> {
+    /// View the first len bytes of the string as a PetsciiStr
+    pub fn as_petscii_str(&self) -> &PetsciiStr {
+        PetsciiStr::new(AsRef::<[u8]>::as_ref(self))
+    }
+}
+
+impl<'a, const L: usize> Deref for PetsciiString<'a, L> {
+    type Target = PetsciiStr;
+
+    /// The first len bytes, so the PetsciiStr methods that
+    /// PetsciiString doesn't have itself work on it too
+    fn deref(&self) -> &PetsciiStr {
+        self.as_petscii_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::petscii::{PetsciiStr, PetsciiString};
+
+    #[test]
+    fn slice_views_strings_of_any_width() {
+        let short = PetsciiString::<4>::from_byte_slice_strip_shifted_space(b"GAME");
+        let long = PetsciiString::<8>::from_byte_slice_strip_shifted_space(
+            b"\xc7\xc1\xcd\xc5\xa0\xa0\xa0\xa0",
+        );
+
+        let names: [&PetsciiStr; 2] = [short.as_petscii_str(), long.as_petscii_str()];
+        assert_eq!(names[1].len(), 8);
+        assert!(names[0].eq_ignore_case(names[1].trim_padding()));
+        assert!(!names[0].eq_ignore_padding(names[1]));
+
+        // Views compare and hash by their bytes
+        let set: HashSet<&PetsciiStr> = names.into_iter().collect();
+        assert!(set.contains(PetsciiStr::new(b"GAME")));
+        assert_eq!(format!("{:?}", names[0]), "PetsciiStr([47, 41, 4D, 45])");
+
+        // Through Deref, the trimmed bytes are one call away
+        let fields = [&b"GAME\xa0\x00"[..], b"GAME\x00\xa0", b"GAME"];
+        for field in fields {
+            let name = PetsciiString::<6>::try_from_slice(field).unwrap();
+            assert_eq!(name.as_trimmed_bytes(), b"GAME");
+        }
+        assert_eq!(long.trim_padding().len(), 4);
+    }
+}
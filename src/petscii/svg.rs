@@ -0,0 +1,202 @@
+//!
+//! Drawing screens as SVG
+//!
+//! SVG scales to any size without the blur of a scaled bitmap, which
+//! makes it a good format for printing and archiving PETSCII art.
+//! Each character cell is 8 units square, like the 8 by 8 pixel
+//! characters of the C64, inside a 32 unit border.
+//!
+//! Block graphics, the half, quarter and eighth blocks that most
+//! PETSCII art is drawn with, are drawn as rectangles so they line up
+//! exactly.  Other characters are drawn as text in the viewer's
+//! monospace font.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::Write;
+
+use crate::{palette::Palette, petscii::screen::Screen, SystemConfig};
+
+/// The width and height of a cell
+const CELL_SIZE: usize = 8;
+
+/// The width of the border
+const BORDER_SIZE: usize = 32;
+
+/// The rectangles that draw a block element, in eighths of a cell,
+/// as x, y, width and height
+///
+/// Returns None for characters that aren't block elements.
+fn block_rects(c: char) -> Option<Vec<(usize, usize, usize, usize)>> {
+    // Quadrants, upper left, upper right, lower left and lower right
+    let quadrants = |bits: u8| {
+        [(0, 0), (4, 0), (0, 4), (4, 4)]
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| bits & (1 << i) != 0)
+            .map(|(_, (x, y))| (x, y, 4, 4))
+            .collect()
+    };
+
+    let rects = match u32::from(c) {
+        0x2580 => vec![(0, 0, 8, 4)],
+        // Lower one eighth block to full block
+        code @ 0x2581..=0x2588 => {
+            let height = (code - 0x2580) as usize;
+            vec![(0, 8 - height, 8, height)]
+        }
+        // Left seven eighths block to left one eighth block
+        code @ 0x2589..=0x258F => vec![(0, 0, (0x2590 - code) as usize, 8)],
+        0x2590 => vec![(4, 0, 4, 8)],
+        0x2594 => vec![(0, 0, 8, 1)],
+        0x2595 => vec![(7, 0, 1, 8)],
+        0x2596 => quadrants(0b0100),
+        0x2597 => quadrants(0b1000),
+        0x2598 => quadrants(0b0001),
+        0x2599 => quadrants(0b1101),
+        0x259A => quadrants(0b1001),
+        0x259B => quadrants(0b0111),
+        0x259C => quadrants(0b1011),
+        0x259D => quadrants(0b0010),
+        0x259E => quadrants(0b0110),
+        0x259F => quadrants(0b1110),
+        _ => return None,
+    };
+    Some(rects)
+}
+
+impl Screen {
+    /// The screen as an SVG image, with the colors from a palette
+    ///
+    /// Like to_html, reversed characters are drawn with the text and
+    /// background colors swapped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     palette::Palette,
+    ///     petscii::{screen::Screen, Color, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut screen = Screen::new(2, 1);
+    /// screen.set(0, 0, 0x08, Color::White);
+    /// screen.set(1, 0, 0x62, Color::Red);
+    ///
+    /// let svg = screen.to_svg(&petscii, &Palette::COLODORE);
+    ///
+    /// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 80 72\""));
+    /// assert!(svg.contains(">H</text>"));
+    /// // The lower half block is a rectangle
+    /// assert!(svg.contains("<rect x=\"40\" y=\"36\" width=\"8\" height=\"4\" fill=\"#813338\"/>"));
+    /// ```
+    pub fn to_svg(&self, petscii: &SystemConfig, palette: &Palette) -> String {
+        let width = self.columns * CELL_SIZE + 2 * BORDER_SIZE;
+        let height = self.rows * CELL_SIZE + 2 * BORDER_SIZE;
+        let background = palette.rgb(self.background);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {width} {height}\" \
+             width=\"{width}\" height=\"{height}\" shape-rendering=\"crispEdges\">\n"
+        );
+        let _ = writeln!(
+            svg,
+            "<rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>",
+            palette.rgb(self.border)
+        );
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{BORDER_SIZE}\" y=\"{BORDER_SIZE}\" width=\"{}\" height=\"{}\" fill=\"{background}\"/>",
+            self.columns * CELL_SIZE,
+            self.rows * CELL_SIZE
+        );
+        let _ = writeln!(
+            svg,
+            "<g font-family=\"monospace\" font-size=\"{CELL_SIZE}\" text-anchor=\"middle\" \
+             dominant-baseline=\"central\">"
+        );
+
+        for row in 0..self.rows {
+            for (column, cell) in self.row(row).unwrap_or_default().iter().enumerate() {
+                let x = BORDER_SIZE + column * CELL_SIZE;
+                let y = BORDER_SIZE + row * CELL_SIZE;
+                let color = palette.rgb(cell.color);
+                let foreground = if cell.is_reversed() {
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{x}\" y=\"{y}\" width=\"{CELL_SIZE}\" height=\"{CELL_SIZE}\" fill=\"{color}\"/>"
+                    );
+                    background
+                } else {
+                    color
+                };
+
+                let c = self.char_for(cell.screen_code & 0x7F, petscii);
+                if let Some(rects) = block_rects(c) {
+                    for (rx, ry, rw, rh) in rects {
+                        let _ = writeln!(
+                            svg,
+                            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{foreground}\"/>",
+                            x + rx * CELL_SIZE / 8,
+                            y + ry * CELL_SIZE / 8,
+                            rw * CELL_SIZE / 8,
+                            rh * CELL_SIZE / 8
+                        );
+                    }
+                    continue;
+                }
+
+                let text = match c {
+                    ' ' => continue,
+                    '<' => String::from("&lt;"),
+                    '>' => String::from("&gt;"),
+                    '&' => String::from("&amp;"),
+                    c => c.to_string(),
+                };
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" fill=\"{foreground}\">{text}</text>",
+                    x + CELL_SIZE / 2,
+                    y + CELL_SIZE / 2
+                );
+            }
+        }
+
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        palette::Palette,
+        petscii::{screen::Screen, Color, PetsciiConfig},
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn svg_draws_reversed_blocks() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let palette = Palette::PEPTO;
+
+        // A reversed upper left quadrant is the cell color with the
+        // quadrant in the background color
+        let mut screen = Screen::new(1, 1);
+        screen.set(0, 0, 0xFE, Color::Yellow);
+        let svg = screen.to_svg(&petscii, &palette);
+
+        assert!(svg.contains(&format!(
+            "<rect x=\"32\" y=\"32\" width=\"8\" height=\"8\" fill=\"{}\"/>\n\
+             <rect x=\"32\" y=\"32\" width=\"4\" height=\"4\" fill=\"{}\"/>",
+            palette.rgb(Color::Yellow),
+            palette.rgb(Color::Blue)
+        )));
+        assert!(!svg.contains("<text"));
+    }
+}
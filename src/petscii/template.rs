@@ -0,0 +1,438 @@
+//!
+//! Screen templates
+//!
+//! Menu and status screens are mostly fixed text with a few fields
+//! that change, like a file name or a block count.  A ScreenTemplate
+//! is the layout of a 40 column screen written as text, one line per
+//! row, with placeholders for the fields:
+//!
+//! ```text
+//! ┌────────────────────────┐
+//! │ {name:16} {blocks:>4}  │
+//! └────────────────────────┘
+//! ```
+//!
+//! A placeholder is a name in braces.  A width after a colon pads the
+//! value with spaces or cuts it to that many columns, on the right by
+//! default or on the left after a >.  PETSCII doesn't have braces, so
+//! they're only used for placeholders.  The text is encoded with the
+//! lowest code that shows each character in the character set the
+//! template is for, so every character has to be in that set.
+//!
+//! The template is filled with PETSCII values, any PetsciiStr or
+//! PetsciiString, with their padding removed.  A value can have
+//! colors and reverse video codes in it.  fill_stream returns the
+//! PETSCII that prints the screen: a clear screen code, a shift code
+//! for the lowercase set, then the rows with returns between them.  A
+//! row that uses all 40 columns isn't followed by a return, since the
+//! cursor has already moved to the next row, so reverse video and
+//! quote mode carry on into it.
+//! fill_screen returns the screen that printing the stream shows, as
+//! petscii::terminal draws it, ready to be copied into screen memory.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{
+    error::{Error, ErrorKind},
+    petscii::{
+        petscii_to_screen_code,
+        screen::{C64Screen, Charset, Screen, C64_COLUMNS, C64_ROWS},
+        terminal::PetsciiTerminal,
+        PetsciiStr,
+    },
+    SystemConfig,
+};
+
+/// Clear the screen and move the cursor home
+const CLEAR: u8 = 0x93;
+/// Switch to the shifted (lowercase and uppercase) character set
+const SHIFT_ON: u8 = 0x0E;
+/// Carriage return
+const RETURN: u8 = 0x0D;
+/// Space, used to pad fields
+const SPACE: u8 = 0x20;
+/// Quote, which starts and ends quote mode
+const QUOTE: u8 = 0x22;
+
+/// Where a value goes in a field wider than it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    /// At the start, padded on the right
+    #[default]
+    Left,
+    /// At the end, padded on the left
+    Right,
+}
+
+/// A part of a template row
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    /// Fixed PETSCII codes
+    Literal(Vec<u8>),
+    /// A placeholder
+    Field {
+        /// The name of the value
+        name: String,
+        /// The number of columns, or None for the width of the value
+        width: Option<usize>,
+        /// Where the value goes in the columns
+        align: Align,
+    },
+}
+
+/// A 40 column screen layout with placeholders
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::{
+///         screen::Charset, template::ScreenTemplate, PetsciiConfig, PetsciiStr,
+///     },
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let template = ScreenTemplate::parse("FILE {name:8}={blocks:>3}", Charset::Uppercase, &petscii)
+///     .expect("Error parsing template");
+/// let values = [
+///     ("name", PetsciiStr::new(b"GAME\xa0\xa0")),
+///     ("blocks", PetsciiStr::new(b"12")),
+/// ];
+///
+/// let stream = template.fill_stream(&values).expect("Error filling template");
+/// assert_eq!(stream, b"\x93FILE GAME    = 12");
+///
+/// let screen = template.fill_screen(&values).expect("Error filling template");
+/// assert_eq!(screen.screen_memory()[0][..9], [6, 9, 12, 5, 32, 7, 1, 13, 5]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScreenTemplate {
+    /// The character set the text is encoded in
+    charset: Charset,
+    /// The rows
+    rows: Vec<Vec<Segment>>,
+}
+
+/// The lowest code that shows a character in a character set
+fn code_for(c: char, shifted: bool, petscii: &SystemConfig) -> Option<u8> {
+    let map = &petscii.character_set_map;
+    (0x20..=0xFF).find(|code| {
+        petscii_to_screen_code(*code, shifted, map)
+            .and_then(|screen_code| screen_code.to_unicode(map))
+            == Some(c)
+    })
+}
+
+/// Parse a placeholder, the text between the braces
+fn parse_field(field: &str, offset: usize) -> std::result::Result<Segment, Error> {
+    let (name, format) = field.split_once(':').unwrap_or((field, ""));
+    let (align, width) = match format.strip_prefix('>') {
+        Some(width) => (Align::Right, width),
+        None => (Align::Left, format.strip_prefix('<').unwrap_or(format)),
+    };
+    let width = if width.is_empty() {
+        None
+    } else {
+        Some(width.parse::<usize>().map_err(|_| {
+            Error::new(ErrorKind::Message(format!(
+                "Placeholder at byte {offset} has a bad width {width:?}"
+            )))
+        })?)
+    };
+
+    if name.is_empty() {
+        return Err(Error::new(ErrorKind::Message(format!(
+            "Placeholder at byte {offset} has no name"
+        ))));
+    }
+
+    Ok(Segment::Field {
+        name: String::from(name),
+        width,
+        align,
+    })
+}
+
+/// The number of columns PETSCII codes take up when printed
+///
+/// Returns an error for codes that move the cursor, since a row can't
+/// be laid out with them.
+fn columns(bytes: &[u8], quoted: &mut bool) -> std::result::Result<usize, Error> {
+    let mut columns = 0;
+    for code in bytes {
+        match *code {
+            0x0D | 0x8D | 0x11 | 0x91 | 0x1D | 0x9D | 0x13 | 0x93 | 0x14 | 0x94 => {
+                return Err(Error::new(ErrorKind::Message(format!(
+                    "Code {code:#04x} moves the cursor and can't be in a template row"
+                ))));
+            }
+            // In quote mode the other control codes are drawn
+            0x00..=0x1F | 0x80..=0x9F if !*quoted => (),
+            code => {
+                if code == QUOTE {
+                    *quoted = !*quoted;
+                }
+                columns += 1;
+            }
+        }
+    }
+    Ok(columns)
+}
+
+impl ScreenTemplate {
+    /// Parse a template for a character set
+    ///
+    /// Returns an error if there are more rows than the screen has, a
+    /// placeholder isn't closed or has a bad width, or a character
+    /// isn't in the character set.
+    pub fn parse(
+        layout: &str,
+        charset: Charset,
+        petscii: &SystemConfig,
+    ) -> std::result::Result<ScreenTemplate, Error> {
+        let shifted = charset == Charset::Lowercase;
+        let mut rows = Vec::new();
+        let mut line_offset = 0;
+
+        for line in layout.lines() {
+            let mut segments = Vec::new();
+            let mut literal = Vec::new();
+            let mut chars = line.char_indices().peekable();
+
+            while let Some((i, c)) = chars.next() {
+                let offset = line_offset + i;
+                match c {
+                    '{' => {
+                        let end = line[i..].find('}').map(|end| i + end).ok_or_else(|| {
+                            Error::new(ErrorKind::Message(format!(
+                                "Unclosed placeholder at byte {offset}"
+                            )))
+                        })?;
+                        if !literal.is_empty() {
+                            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                        }
+                        segments.push(parse_field(&line[i + 1..end], offset)?);
+                        while chars.peek().is_some_and(|(j, _)| *j <= end) {
+                            chars.next();
+                        }
+                    }
+                    c => literal.push(code_for(c, shifted, petscii).ok_or_else(|| {
+                        Error::new(ErrorKind::Unencodable {
+                            grapheme: String::from(c),
+                            offset,
+                        })
+                    })?),
+                }
+            }
+
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(literal));
+            }
+            rows.push(segments);
+            line_offset += line.len() + 1;
+        }
+
+        if rows.len() > C64_ROWS {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "The template has {} rows, more than the {} on the screen",
+                rows.len(),
+                C64_ROWS
+            ))));
+        }
+
+        Ok(ScreenTemplate { charset, rows })
+    }
+
+    /// The names of the placeholders, in the order they're used
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.rows
+            .iter()
+            .flatten()
+            .filter_map(|segment| match segment {
+                Segment::Field { name, .. } => Some(name.as_str()),
+                Segment::Literal(_) => None,
+            })
+    }
+
+    /// Fill in a row, returning its codes and the columns they take up
+    fn fill_row(
+        segments: &[Segment],
+        values: &[(&str, &PetsciiStr)],
+    ) -> std::result::Result<(Vec<u8>, usize), Error> {
+        let mut row = Vec::new();
+        let mut width = 0;
+        let mut quoted = false;
+
+        for segment in segments {
+            let (name, field_width, align) = match segment {
+                Segment::Literal(codes) => {
+                    width += columns(codes, &mut quoted)?;
+                    row.extend_from_slice(codes);
+                    continue;
+                }
+                Segment::Field { name, width, align } => (name, width, align),
+            };
+
+            let value = values
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| value.as_trimmed_bytes())
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::Message(format!(
+                        "No value for the placeholder {name}"
+                    )))
+                })?;
+
+            let mut value_quoted = quoted;
+            let value_width = columns(value, &mut value_quoted)?;
+            let Some(field_width) = *field_width else {
+                width += value_width;
+                quoted = value_quoted;
+                row.extend_from_slice(value);
+                continue;
+            };
+
+            // Cut the value to the field, a code at a time, so the
+            // control codes in it count the same as when printed
+            let (mut value, mut value_width) = (value, value_width);
+            while value_width > field_width {
+                value = &value[..value.len() - 1];
+                value_quoted = quoted;
+                value_width = columns(value, &mut value_quoted)?;
+            }
+            let padding = field_width - value_width;
+            if *align == Align::Right {
+                row.extend(std::iter::repeat_n(SPACE, padding));
+            }
+            row.extend_from_slice(value);
+            if *align == Align::Left {
+                row.extend(std::iter::repeat_n(SPACE, padding));
+            }
+            quoted = value_quoted;
+            width += field_width;
+        }
+
+        if width > C64_COLUMNS {
+            return Err(Error::new(ErrorKind::Message(format!(
+                "A row is {width} columns wide, more than the {C64_COLUMNS} on the screen"
+            ))));
+        }
+        Ok((row, width))
+    }
+
+    /// Fill in the template, returning the PETSCII that prints it
+    ///
+    /// values has the value for each placeholder name.  Returns an
+    /// error if a placeholder has no value, a row is wider than the
+    /// screen, a row has cursor movement codes in it, or the last row
+    /// fills the bottom line, since printing its last column scrolls
+    /// the screen.
+    pub fn fill_stream(
+        &self,
+        values: &[(&str, &PetsciiStr)],
+    ) -> std::result::Result<Vec<u8>, Error> {
+        let mut stream = vec![CLEAR];
+        if self.charset == Charset::Lowercase {
+            stream.push(SHIFT_ON);
+        }
+
+        for (i, segments) in self.rows.iter().enumerate() {
+            let (row, width) = ScreenTemplate::fill_row(segments, values)?;
+            stream.extend(row);
+
+            let last = i + 1 == self.rows.len();
+            if width == C64_COLUMNS && i + 1 == C64_ROWS {
+                return Err(Error::new(ErrorKind::Message(String::from(
+                    "The last row can't fill the bottom line without scrolling the screen",
+                ))));
+            } else if width < C64_COLUMNS && !last {
+                stream.push(RETURN);
+            }
+        }
+
+        Ok(stream)
+    }
+
+    /// Fill in the template, returning the screen it shows
+    ///
+    /// Returns the same errors as fill_stream, and an error if the
+    /// current configuration doesn't have PETSCII tables.
+    pub fn fill_screen(
+        &self,
+        values: &[(&str, &PetsciiStr)],
+    ) -> std::result::Result<C64Screen, Error> {
+        let stream = self.fill_stream(values)?;
+
+        // A spare row keeps the cursor wrapping off the last row from
+        // scrolling the screen
+        let mut terminal = PetsciiTerminal::current_config(Screen::new(C64_COLUMNS, C64_ROWS + 1))?;
+        terminal.print(&stream);
+
+        let mut screen = terminal.into_screen();
+        screen.rows = C64_ROWS;
+        screen.cells.truncate(C64_COLUMNS * C64_ROWS);
+        C64Screen::try_from(screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        petscii::{
+            screen::{Charset, C64_COLUMNS},
+            template::ScreenTemplate,
+            terminal::PetsciiTerminal,
+            Color, PetsciiConfig, PetsciiStr,
+        },
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn template_fills_screens_and_streams() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let full_row = "=".repeat(C64_COLUMNS);
+        let layout = format!("Menu {{title}}\n{full_row}\n{{item:>6}} {{item:<3}}.");
+        let template = ScreenTemplate::parse(&layout, Charset::Lowercase, &petscii).unwrap();
+        assert_eq!(
+            template.field_names().collect::<Vec<_>>(),
+            ["title", "item", "item"]
+        );
+
+        let values = [
+            ("title", PetsciiStr::new(b"\x05\x12MAIN\x92\x9a")),
+            ("item", PetsciiStr::new(b"LOAD\xa0")),
+        ];
+        let stream = template.fill_stream(&values).unwrap();
+        let screen = template.fill_screen(&values).unwrap();
+
+        // The row that fills the screen isn't followed by a return
+        assert!(stream.starts_with(b"\x93\x0e\x6d\x45\x4e\x55 "));
+        assert!(stream.ends_with(b"===  LOAD LOA."));
+
+        let mut terminal = PetsciiTerminal::new();
+        terminal.print(&stream);
+        let screen_codes: Vec<u8> = screen.screen_memory().into_iter().flatten().collect();
+        assert_eq!(terminal.screen_codes(), screen_codes);
+        assert_eq!(screen.cells[0][6].color, Color::White);
+        assert!(screen.cells[0][6].is_reversed());
+        assert_eq!(screen.charset, Charset::Lowercase);
+
+        // Errors for missing values, wide rows and characters the
+        // character set doesn't have
+        assert!(template.fill_stream(&values[1..]).is_err());
+        let wide = ScreenTemplate::parse("{a:41}", Charset::Uppercase, &petscii).unwrap();
+        assert!(wide.fill_stream(&values).is_err());
+        assert!(ScreenTemplate::parse("{a", Charset::Uppercase, &petscii).is_err());
+        assert!(ScreenTemplate::parse("{a:x}", Charset::Uppercase, &petscii).is_err());
+        assert!(ScreenTemplate::parse("a", Charset::Uppercase, &petscii).is_err());
+        assert!(ScreenTemplate::parse("{a}}", Charset::Uppercase, &petscii).is_err());
+        let bottom = format!("{}{full_row}", "\n".repeat(24));
+        let bottom = ScreenTemplate::parse(&bottom, Charset::Uppercase, &petscii).unwrap();
+        assert!(bottom.fill_stream(&[]).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//!
+//! A virtual C64 screen that PETSCII can be written to
+//!
+//! PetsciiTerminal acts on PETSCII the way the C64 screen editor
+//! does when a program prints to the screen: characters are drawn at
+//! the cursor, colors and reverse video apply to the characters that
+//! follow, the cursor wraps at the end of a line and the screen
+//! scrolls when the cursor moves past the bottom.  Printing a quote
+//! starts quote mode, where control codes are drawn as reversed
+//! characters instead of being acted on.  Quote mode follows the
+//! same rules as the decoder: the character set, reverse video and
+//! carriage return codes still work in it.  Delete still deletes in
+//! quote mode, like in the screen editor.  Control codes printed into
+//! the spaces opened by insert are drawn as reversed characters too,
+//! delete included.
+//!
+//! The screen codes come from the PETSCII tables of a configuration,
+//! the current one from Config::current unless the terminal is created
+//! with with_config or given another with with_character_map.
+//!
+//! The screen can be read back as text, HTML or screen codes at any
+//! time, which makes the terminal useful for BBS clients and for
+//! checking the output of C64 programs in tests.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::io::Write;
+
+use crate::{
+    error::Error,
+    palette::Palette,
+    petscii::{
+        petscii_to_screen_code,
+        screen::{Charset, Screen, SPACE},
+        Color, PetsciiCode, PetsciiConfig, ScreenSet,
+    },
+    Config, SystemConfig,
+};
+
+/// A virtual C64 screen
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+///
+/// use forbidden_bands::{
+///     petscii::{terminal::PetsciiTerminal, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let mut terminal = PetsciiTerminal::new();
+/// terminal.write_all(b"\x93READY.\rLOAD\x9d\x9d\x9d\x9dLIST").expect("Error writing");
+///
+/// assert_eq!(terminal.to_text(&petscii), "READY.\nLIST");
+/// assert_eq!(terminal.cursor(), (4, 1));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PetsciiTerminal {
+    screen: Screen,
+    column: usize,
+    row: usize,
+    color: Color,
+    reversed: bool,
+    quoted: bool,
+    /// The number of spaces opened by insert that haven't been
+    /// printed into yet
+    inserts: usize,
+    charset_locked: bool,
+    /// The tables PETSCII codes are looked up in
+    map: PetsciiConfig,
+}
+
+impl Default for PetsciiTerminal {
+    fn default() -> Self {
+        PetsciiTerminal::new()
+    }
+}
+
+impl PetsciiTerminal {
+    /// Create a terminal with a blank 40 by 25 screen, in the colors
+    /// the C64 starts with
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current configuration doesn't have PETSCII
+    /// tables, see with_screen.
+    pub fn new() -> PetsciiTerminal {
+        PetsciiTerminal::with_screen(Screen::c64())
+    }
+
+    /// Create a terminal that prints onto a screen, starting at the
+    /// top left corner in light blue
+    ///
+    /// The terminal uses the configuration from Config::current.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current configuration can't be loaded or doesn't
+    /// have valid PETSCII tables.  Use current_config or with_config
+    /// to get an error instead.
+    pub fn with_screen(screen: Screen) -> PetsciiTerminal {
+        PetsciiTerminal::current_config(screen).expect("Error loading PETSCII config")
+    }
+
+    /// Create a terminal that prints onto a screen with the
+    /// configuration from Config::current
+    ///
+    /// Returns an error if the current configuration can't be loaded
+    /// or doesn't have valid PETSCII tables.
+    pub fn current_config(screen: Screen) -> std::result::Result<PetsciiTerminal, Error> {
+        let petscii = Config::current()?.cached_system::<SystemConfig>()?;
+        PetsciiTerminal::with_config(screen, &petscii)
+    }
+
+    /// Create a terminal that prints onto a screen with the PETSCII
+    /// tables of a configuration
+    ///
+    /// Returns an error if a PETSCII code in the tables doesn't map to
+    /// a screen code, see PetsciiConfig::validate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     petscii::{screen::Screen, terminal::PetsciiTerminal, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut terminal =
+    ///     PetsciiTerminal::with_config(Screen::c64(), &petscii).expect("Error creating terminal");
+    /// terminal.print(b"HI");
+    ///
+    /// assert_eq!(terminal.to_text(&petscii), "HI");
+    /// ```
+    pub fn with_config(
+        screen: Screen,
+        petscii: &SystemConfig,
+    ) -> std::result::Result<PetsciiTerminal, Error> {
+        petscii.character_set_map.validate()?;
+
+        Ok(PetsciiTerminal {
+            screen,
+            column: 0,
+            row: 0,
+            color: Color::LightBlue,
+            reversed: false,
+            quoted: false,
+            inserts: 0,
+            charset_locked: false,
+            map: petscii.character_set_map.clone(),
+        })
+    }
+
+    /// Use the PETSCII tables of another configuration
+    pub fn with_character_map(mut self, petscii: &SystemConfig) -> PetsciiTerminal {
+        self.map = petscii.character_set_map.clone();
+        self
+    }
+
+    /// The screen
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    /// Take the screen out of the terminal
+    pub fn into_screen(self) -> Screen {
+        self.screen
+    }
+
+    /// The column and row of the cursor
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.column, self.row)
+    }
+
+    /// The color characters are printed in
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// The text on the screen, a line for each row
+    pub fn to_text(&self, petscii: &SystemConfig) -> String {
+        self.screen.to_text(petscii)
+    }
+
+    /// The screen as an HTML pre element
+    pub fn to_html(&self, petscii: &SystemConfig, palette: &Palette) -> String {
+        self.screen.to_html(petscii, palette)
+    }
+
+    /// The contents of screen memory
+    pub fn screen_codes(&self) -> Vec<u8> {
+        self.screen.screen_codes()
+    }
+
+    /// Move to the next row, scrolling if the cursor is on the last
+    /// row
+    fn next_row(&mut self) {
+        self.column = 0;
+        if self.row + 1 >= self.screen.rows {
+            self.screen.scroll_up(self.color);
+        } else {
+            self.row += 1;
+        }
+    }
+
+    /// Draw a screen code at the cursor and move right
+    fn draw(&mut self, screen_code: u8) {
+        self.screen
+            .set(self.column, self.row, screen_code, self.color);
+        self.inserts = self.inserts.saturating_sub(1);
+        self.column += 1;
+        if self.column >= self.screen.columns {
+            self.next_row();
+        }
+    }
+
+    /// Delete the character left of the cursor, pulling the rest of
+    /// the line left
+    fn delete(&mut self) {
+        if self.column == 0 {
+            return;
+        }
+        self.column -= 1;
+        for column in self.column..self.screen.columns - 1 {
+            if let Some(cell) = self.screen.cell(column + 1, self.row).copied() {
+                self.screen
+                    .set(column, self.row, cell.screen_code, cell.color);
+            }
+        }
+        let last = self.screen.columns - 1;
+        self.screen.set(last, self.row, SPACE, self.color);
+    }
+
+    /// Insert a space at the cursor, pushing the rest of the line
+    /// right
+    fn insert(&mut self) {
+        for column in (self.column + 1..self.screen.columns).rev() {
+            if let Some(cell) = self.screen.cell(column - 1, self.row).copied() {
+                self.screen
+                    .set(column, self.row, cell.screen_code, cell.color);
+            }
+        }
+        self.screen.set(self.column, self.row, SPACE, self.color);
+    }
+
+    /// Act on a PETSCII code
+    pub fn print_byte(&mut self, code: u8) {
+        let shifted = self.screen.charset == Charset::Lowercase;
+        let screen_code = petscii_to_screen_code(code, shifted, &self.map)
+            .filter(|screen_code| screen_code.set != ScreenSet::Set3);
+
+        // Other control codes are drawn as the reversed character
+        // 0x40 above them.  The screen editor deletes in quote mode
+        // and only draws delete after an insert.
+        let acts_in_quotes = matches!(code, 0x0D | 0x8D | 0x0E | 0x8E | 0x12 | 0x92);
+        let drawn = self.inserts > 0 || (self.quoted && code != 0x14);
+        if drawn && screen_code.is_none() && PetsciiCode(code).is_control() && !acts_in_quotes {
+            if let Some(quoted) =
+                petscii_to_screen_code(code.wrapping_add(0x40), shifted, &self.map)
+            {
+                self.draw(quoted.reversed().value);
+            }
+            return;
+        }
+
+        if let Some(color) = Color::from_control_code(code) {
+            self.color = color;
+            return;
+        }
+
+        match code {
+            0x0D | 0x8D => {
+                self.reversed = false;
+                self.quoted = false;
+                self.inserts = 0;
+                self.next_row();
+            }
+            0x08 => self.charset_locked = true,
+            0x09 => self.charset_locked = false,
+            0x0E if !self.charset_locked => self.screen.charset = Charset::Lowercase,
+            0x8E if !self.charset_locked => self.screen.charset = Charset::Uppercase,
+            0x11 => {
+                let column = self.column;
+                self.next_row();
+                self.column = column;
+            }
+            0x91 => self.row = self.row.saturating_sub(1),
+            0x12 => self.reversed = true,
+            0x92 => self.reversed = false,
+            0x13 => (self.column, self.row) = (0, 0),
+            0x93 => {
+                self.screen.clear(self.color);
+                (self.column, self.row) = (0, 0);
+            }
+            0x14 => self.delete(),
+            0x94 => {
+                self.insert();
+                self.inserts += 1;
+            }
+            0x1D => {
+                self.column += 1;
+                if self.column >= self.screen.columns {
+                    self.next_row();
+                }
+            }
+            0x9D => {
+                if self.column > 0 {
+                    self.column -= 1;
+                } else if self.row > 0 {
+                    self.row -= 1;
+                    self.column = self.screen.columns.saturating_sub(1);
+                }
+            }
+            code => {
+                if let Some(screen_code) = screen_code {
+                    if code == 0x22 {
+                        self.quoted = !self.quoted;
+                    }
+                    let screen_code = if self.reversed {
+                        screen_code.reversed()
+                    } else {
+                        screen_code
+                    };
+                    self.draw(screen_code.value);
+                }
+            }
+        }
+    }
+
+    /// Act on PETSCII codes
+    pub fn print(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.print_byte(*b);
+        }
+    }
+}
+
+impl Write for PetsciiTerminal {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.print(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::{
+        manager::ConfigOverrideGuard,
+        palette::Palette,
+        petscii::{
+            screen::{Charset, Screen},
+            terminal::PetsciiTerminal,
+            Color, PetsciiConfig,
+        },
+        Config, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn terminal_quote_mode_draws_controls() {
+        let mut terminal = PetsciiTerminal::new();
+        terminal.write_all(b"\"\x93\x1c\"\x1cX").unwrap();
+
+        // The quoted clear and red are drawn reversed, the second red
+        // changes the color
+        assert_eq!(terminal.screen_codes()[..5], [0x22, 0xD3, 0x9C, 0x22, 0x18]);
+        assert_eq!(terminal.color(), Color::Red);
+
+        // Reverse video and the character set still work in quotes,
+        // like in the decoder, and delete still deletes
+        let mut terminal = PetsciiTerminal::new();
+        terminal.write_all(b"\"\x12A\x92\x0eA\x14\x60\xc0").unwrap();
+        assert_eq!(terminal.screen_codes()[..5], [0x22, 0x81, 0x40, 0x40, 0x20]);
+        assert_eq!(terminal.screen().charset, Charset::Lowercase);
+    }
+
+    #[test]
+    fn terminal_insert_mode_draws_delete() {
+        // Delete printed into an inserted space is drawn reversed, the
+        // next delete deletes
+        let mut terminal = PetsciiTerminal::new();
+        terminal.write_all(b"A\x9d\x94\x14").unwrap();
+        assert_eq!(terminal.screen_codes()[..2], [0x94, 0x01]);
+        assert_eq!(terminal.cursor(), (1, 0));
+
+        terminal.write_all(b"\x14").unwrap();
+        assert_eq!(terminal.screen_codes()[..2], [0x01, 0x20]);
+    }
+
+    #[test]
+    fn terminal_insert_delete_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut terminal = PetsciiTerminal::new();
+        terminal.write_all(b"ABC\x9d\x9d\x94X\x14\x14").unwrap();
+        assert_eq!(terminal.to_text(&petscii), "BC");
+
+        // Locking the character set ignores the shift codes
+        terminal.write_all(b"\x08\x0e").unwrap();
+        assert_eq!(terminal.screen().charset, Charset::Uppercase);
+        let html = terminal.to_html(&petscii, &Palette::COLODORE);
+        assert!(html.contains(">BC "));
+    }
+
+    #[test]
+    fn terminal_config_errors_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let mut petscii = config.system::<SystemConfig>().unwrap();
+        assert!(PetsciiTerminal::with_config(Screen::c64(), &petscii).is_ok());
+
+        // A PETSCII code with a bad screen code
+        petscii
+            .character_set_map
+            .c64_petscii_unshifted_codes_to_screen_codes
+            .insert(String::from("65"), "A".into());
+        assert!(PetsciiTerminal::with_config(Screen::c64(), &petscii).is_err());
+
+        // A current configuration without PETSCII tables
+        let mut config = Config::load().unwrap();
+        config.systems.clear();
+        let _guard = ConfigOverrideGuard::new(config);
+        assert!(PetsciiTerminal::current_config(Screen::c64()).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//!
+//! Wiping decoded secrets
+//!
+//! Password files and keys recovered from old disks end up in the
+//! same buffers as any other text.  With the zeroize feature, the
+//! owned types that hold PETSCII or the text decoded from it
+//! implement Zeroize, so a caller can clear them once they're done:
+//!
+//! * PetsciiString clears its data and length and drops its
+//!   character map
+//! * RichText clears the text of each span and then the spans
+//! * Dangling clears the bytes left over at the end of a stream
+//!
+//! None of them wipe themselves on drop.  PetsciiString is Copy, and
+//! every copy holds the bytes, so each one has to be cleared.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use zeroize::Zeroize;
+
+use crate::petscii::{decoder::Dangling, rich::RichText, PetsciiString};
+
+impl<'a, const L: usize> Zeroize for PetsciiString<'a, L> {
+    fn zeroize(&mut self) {
+        self.data.zeroize();
+        self.len.zeroize();
+        self.character_map = None;
+        self.strip_shifted_space = false;
+    }
+}
+
+impl Zeroize for RichText {
+    fn zeroize(&mut self) {
+        self.spans.iter_mut().for_each(|span| span.text.zeroize());
+        self.spans.clear();
+    }
+}
+
+impl Zeroize for Dangling {
+    fn zeroize(&mut self) {
+        self.incomplete.zeroize();
+        self.shifted = false;
+        self.reversed = false;
+        self.quoted = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zeroize::Zeroize;
+
+    use crate::{
+        petscii::{rich::RichText, PetsciiConfig, PetsciiString},
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn wipe_clears_strings() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut password = PetsciiString::new_with_config(6, *b"SECRET", &petscii);
+        password.zeroize();
+        assert_eq!(password.data, [0; 6]);
+        assert!(password.is_empty());
+        assert!(password.character_map.is_none());
+
+        let mut text = petscii.decode_rich(b"\x1cSECRET");
+        assert!(!text.spans.is_empty());
+        text.zeroize();
+        assert_eq!(text, RichText::default());
+    }
+}
@@ -0,0 +1,279 @@
+//!
+//! Word wrapping PETSCII text
+//!
+//! Text written for an 80 column terminal or a text editor needs
+//! reflowing before it reads well on a 40 column screen.  The screen
+//! editor wraps long lines wherever the column runs out, in the middle
+//! of words.
+//!
+//! The functions here wrap at spaces instead.  Control codes take no
+//! columns and stay with the word they're in, so a color change at
+//! the start of a word moves to the next line with it.  A carriage
+//! return (0x0D or the shifted return 0x8D) always ends a line, and
+//! shifted spaces (0xA0) don't break, like a no-break space.  Words
+//! aren't hyphenated: a word longer than a line is split where the
+//! line runs out.
+//!
+//! A carriage return also turns reverse video off, so when wrap ends
+//! a line inside reversed text it turns reverse video back on at the
+//! start of the next line.  wrap_lines returns slices of the input and
+//! can't add codes, so callers printing its lines have to do that
+//! themselves.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+/// Carriage return
+const RETURN: u8 = 0x0D;
+/// Shifted carriage return, which also ends a line
+const SHIFTED_RETURN: u8 = 0x8D;
+/// Space, where lines can be broken
+const SPACE: u8 = 0x20;
+/// Turn reverse video on
+const REVERSE_ON: u8 = 0x12;
+/// Turn reverse video off
+const REVERSE_OFF: u8 = 0x92;
+
+/// True for codes that end a line
+fn is_return(code: u8) -> bool {
+    code == RETURN || code == SHIFTED_RETURN
+}
+
+/// True for codes that take up a column
+fn is_visible(code: u8) -> bool {
+    !matches!(code, 0x00..=0x1F | 0x80..=0x9F)
+}
+
+/// The number of columns the codes take up
+fn columns(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|code| is_visible(**code)).count()
+}
+
+/// Wrap one line without returns in it
+fn wrap_line<'a>(line: &'a [u8], width: usize, lines: &mut Vec<&'a [u8]>) {
+    let mut start = 0;
+    let mut column = 0;
+    // The end of the text before the last run of spaces and the start
+    // of the word after it
+    let mut last_break: Option<(usize, usize)> = None;
+    let mut i = 0;
+
+    while i < line.len() {
+        let code = line[i];
+        if code == SPACE {
+            let end = line[i..]
+                .iter()
+                .position(|code| *code != SPACE)
+                .map_or(line.len(), |n| i + n);
+            if i > start {
+                last_break = Some((i, end));
+            }
+            column += end - i;
+            i = end;
+            continue;
+        }
+
+        if is_visible(code) {
+            if column >= width {
+                match last_break.take() {
+                    Some((end, next)) => {
+                        lines.push(&line[start..end]);
+                        start = next;
+                    }
+                    None => {
+                        // No space to break at, so split the word
+                        let end = line[start..i]
+                            .iter()
+                            .rposition(|code| *code != SPACE)
+                            .map_or(start, |n| start + n + 1);
+                        lines.push(&line[start..end]);
+                        start = i;
+                    }
+                }
+                column = columns(&line[start..i]);
+            }
+            column += 1;
+        }
+        i += 1;
+    }
+
+    // Spaces at the end don't need to fit
+    let mut end = line.len();
+    while end > start && line[end - 1] == SPACE && columns(&line[start..end]) > width {
+        end -= 1;
+    }
+    lines.push(&line[start..end]);
+}
+
+/// Wrap PETSCII text into lines of at most width columns
+///
+/// The lines are slices of the input without the returns and the
+/// spaces they were broken at.  Text ending in a return ends with an
+/// empty line.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::wrap::wrap_lines;
+///
+/// let lines = wrap_lines(b"THE \x1cQUICK BROWN FOX\rJUMPS", 10);
+///
+/// assert_eq!(lines, [&b"THE \x1cQUICK"[..], b"BROWN FOX", b"JUMPS"]);
+/// ```
+pub fn wrap_lines(bytes: &[u8], width: usize) -> Vec<&[u8]> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+
+    for line in bytes.split(|code| is_return(*code)) {
+        wrap_line(line, width, &mut lines);
+    }
+
+    lines
+}
+
+/// Wrap PETSCII text to width columns, adding carriage returns
+///
+/// The returns in the text are kept.  Reverse video is turned back on
+/// after an added return if it was on where the line was broken.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::wrap::wrap;
+///
+/// assert_eq!(wrap(b"HELLO THERE", 8), b"HELLO\rTHERE");
+/// assert_eq!(wrap(b"\x12HELLO THERE", 8), b"\x12HELLO\r\x12THERE");
+/// ```
+pub fn wrap(bytes: &[u8], width: usize) -> Vec<u8> {
+    let width = width.max(1);
+    let mut wrapped = Vec::with_capacity(bytes.len());
+    let mut offset = 0;
+
+    for line in bytes.split(|code| is_return(*code)) {
+        let mut lines = Vec::new();
+        wrap_line(line, width, &mut lines);
+
+        let mut reversed = false;
+        for (i, part) in lines.iter().enumerate() {
+            if i > 0 {
+                wrapped.push(RETURN);
+                if reversed {
+                    wrapped.push(REVERSE_ON);
+                }
+            }
+            wrapped.extend_from_slice(part);
+            for code in *part {
+                match *code {
+                    REVERSE_ON => reversed = true,
+                    REVERSE_OFF => reversed = false,
+                    _ => (),
+                }
+            }
+        }
+
+        // Keep the return that ended the line
+        offset += line.len();
+        if let Some(code) = bytes.get(offset) {
+            wrapped.push(*code);
+        }
+        offset += 1;
+    }
+
+    wrapped
+}
+
+/// Reflow paragraphs of PETSCII text to width columns
+///
+/// Paragraphs are separated by blank lines.  The returns inside a
+/// paragraph become spaces before it's wrapped, and paragraphs are
+/// separated by one blank line in the result.  If reverse video was
+/// on at a return that became a space, it's turned off there, since
+/// the return used to turn it off.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::petscii::wrap::reflow;
+///
+/// let text = b"ONE TWO\rTHREE\r\r\rFOUR";
+///
+/// assert_eq!(reflow(text, 9), b"ONE TWO\rTHREE\r\rFOUR");
+/// assert_eq!(reflow(text, 40), b"ONE TWO THREE\r\rFOUR");
+/// ```
+pub fn reflow(bytes: &[u8], width: usize) -> Vec<u8> {
+    let mut reflowed = Vec::with_capacity(bytes.len());
+    let mut paragraph = Vec::new();
+    let mut reversed = false;
+    let mut returns = 0;
+
+    for code in bytes.iter().copied() {
+        if is_return(code) {
+            returns += 1;
+            continue;
+        }
+
+        if returns == 1 && !paragraph.is_empty() {
+            if reversed {
+                paragraph.push(REVERSE_OFF);
+            }
+            paragraph.push(SPACE);
+        } else if returns > 1 && !paragraph.is_empty() {
+            if !reflowed.is_empty() {
+                reflowed.extend_from_slice(&[RETURN, RETURN]);
+            }
+            reflowed.extend(wrap(&paragraph, width));
+            paragraph.clear();
+        }
+        if returns > 0 {
+            reversed = false;
+        }
+        returns = 0;
+
+        match code {
+            REVERSE_ON => reversed = true,
+            REVERSE_OFF => reversed = false,
+            _ => (),
+        }
+        paragraph.push(code);
+    }
+
+    if !paragraph.is_empty() {
+        if !reflowed.is_empty() {
+            reflowed.extend_from_slice(&[RETURN, RETURN]);
+        }
+        reflowed.extend(wrap(&paragraph, width));
+    }
+
+    reflowed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::petscii::wrap::{reflow, wrap, wrap_lines};
+
+    #[test]
+    fn wrap_counts_columns_without_control_codes() {
+        // Colors take no columns, so this fits in 5
+        assert_eq!(
+            wrap_lines(b"\x05AB\x1cCDE FG", 5),
+            [&b"\x05AB\x1cCDE"[..], b"FG"]
+        );
+
+        // Long words are split, shifted spaces don't break and spaces
+        // at a break are dropped
+        assert_eq!(wrap_lines(b"ABCDEFG", 3), [&b"ABC"[..], b"DEF", b"G"]);
+        assert_eq!(wrap_lines(b"A\xa0B C", 3), [&b"A\xa0B"[..], b"C"]);
+        assert_eq!(wrap_lines(b"ABC   DEF   ", 3), [&b"ABC"[..], b"DEF"]);
+        assert_eq!(wrap_lines(b"AB\r", 3), [&b"AB"[..], b""]);
+
+        assert_eq!(wrap(b"AB CD\x8dEF GH", 2), b"AB\rCD\x8dEF\rGH");
+        assert_eq!(wrap(b"\x12AB\x92 CD", 3), b"\x12AB\x92\rCD");
+    }
+
+    #[test]
+    fn wrap_reflow_joins_paragraph_lines() {
+        let text = b"\x12ONE\rTWO\r\rTHREE FOUR";
+        assert_eq!(reflow(text, 40), b"\x12ONE\x92 TWO\r\rTHREE FOUR");
+        assert_eq!(reflow(text, 5), b"\x12ONE\x92\rTWO\r\rTHREE\rFOUR");
+        assert!(reflow(b"\r\r", 5).is_empty());
+    }
+}
@@ -0,0 +1,326 @@
+//!
+//! Composing PETSCII output with write!
+//!
+//! A PetsciiWriter collects encoded PETSCII bytes.  It implements
+//! std::fmt::Write, so menus and BBS screens can be built with write!
+//! and writeln! instead of concatenating byte vectors.
+//!
+//! The writer remembers the shift state between writes.  Writing
+//! "ab" and then "cd" gives one shift in and one shift out, not a
+//! pair around each piece.  Control codes like colors and cursor
+//! movement don't have Unicode characters, so they're added with
+//! write_raw.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::fmt::{Alignment, Display, Formatter};
+
+use crate::{petscii::encode_from_state, CharacterSet, SystemConfig};
+
+/// Collects PETSCII bytes encoded from formatted text
+///
+/// Characters that don't exist in PETSCII are dropped, like
+/// CharacterSet::encode does.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Write;
+///
+/// use forbidden_bands::{
+///     petscii::{writer::PetsciiWriter, PetsciiConfig},
+///     Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let mut writer = PetsciiWriter::new(&petscii);
+/// // Clear the screen
+/// writer.write_raw(&[0x93]);
+/// write!(writer, "{}) Pl", 1).expect("Error writing");
+/// write!(writer, "ay").expect("Error writing");
+///
+/// assert_eq!(
+///     writer.finish(),
+///     vec![0x93, 0x31, 0x29, 0xa0, 0x50, 0x0e, 0x4c, 0x41, 0x59, 0x8e]
+/// );
+/// ```
+#[derive(Clone)]
+pub struct PetsciiWriter<'a> {
+    character_map: &'a SystemConfig,
+    bytes: Vec<u8>,
+    shifted: bool,
+}
+
+impl<'a> PetsciiWriter<'a> {
+    /// Create an empty writer
+    pub fn new(character_map: &'a SystemConfig) -> PetsciiWriter<'a> {
+        PetsciiWriter {
+            character_map,
+            bytes: Vec::new(),
+            shifted: false,
+        }
+    }
+
+    /// Encode a string and add it to the output
+    pub fn push_str(&mut self, s: &str) {
+        let bytes = encode_from_state(s, &self.character_map.character_set_map, &mut self.shifted);
+        self.bytes.extend(bytes);
+    }
+
+    /// Add bytes to the output without encoding them
+    ///
+    /// Shift in (0x0E) and shift out (0x8E) codes in the bytes change
+    /// the shift state of the writer.
+    pub fn write_raw(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            match b {
+                0x0E => self.shifted = true,
+                0x8E => self.shifted = false,
+                _ => (),
+            }
+        }
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// True if the output so far ends in the shifted character set
+    pub fn is_shifted(&self) -> bool {
+        self.shifted
+    }
+
+    /// The bytes written so far
+    ///
+    /// These may end in the shifted character set, see finish.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Finish writing and return the bytes
+    ///
+    /// If the output ends in the shifted character set a shift out is
+    /// added, the same as encoding a single string.
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.shifted {
+            self.bytes.push(0x8E);
+        }
+        self.bytes
+    }
+}
+
+impl<'a> std::fmt::Write for PetsciiWriter<'a> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+/// Format a value with the width measured in PETSCII display cells
+///
+/// Rust pads to a width in chars, but characters that can't be
+/// encoded don't take up a cell on the screen and shift codes aren't
+/// characters at all.  Cells counts the cells the encoded value will
+/// use and pads it to the width itself.
+///
+/// The width, fill, alignment, precision, + sign and 0 flags of the
+/// format spec are used.  Values are aligned the way Rust aligns
+/// them, so numbers are right-aligned by default, and zero padding
+/// goes after the sign.  The # flag isn't passed on to the value.
+/// petscii_format! wraps its arguments in Cells.
+pub struct Cells<'a, T: ?Sized> {
+    value: &'a T,
+    character_map: &'a SystemConfig,
+}
+
+impl<'a, T: Display + ?Sized> Cells<'a, T> {
+    /// Wrap a value to format for a character set
+    pub fn new(value: &'a T, character_map: &'a SystemConfig) -> Cells<'a, T> {
+        Cells {
+            value,
+            character_map,
+        }
+    }
+}
+
+impl<'a, T: Display + ?Sized> Display for Cells<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match (f.sign_plus(), f.precision()) {
+            (true, Some(precision)) => format!("{:+.*}", precision, self.value),
+            (true, None) => format!("{:+}", self.value),
+            (false, Some(precision)) => format!("{:.*}", precision, self.value),
+            (false, None) => self.value.to_string(),
+        };
+
+        let width = match f.width() {
+            Some(width) => width,
+            None => return f.write_str(&s),
+        };
+
+        let cells = self
+            .character_map
+            .encode(&s)
+            .iter()
+            .filter(|b| **b != 0x0E && **b != 0x8E)
+            .count();
+
+        if f.sign_aware_zero_pad() {
+            // Zeros go after the sign, so let Rust pad the value,
+            // widened by the chars that don't take up a cell
+            let width = (width + s.chars().count()).saturating_sub(cells);
+            let padded = match (f.sign_plus(), f.precision()) {
+                (true, Some(precision)) => format!("{:+0width$.precision$}", self.value),
+                (true, None) => format!("{:+0width$}", self.value),
+                (false, Some(precision)) => format!("{:0width$.precision$}", self.value),
+                (false, None) => format!("{:0width$}", self.value),
+            };
+            return f.write_str(&padded);
+        }
+
+        let padding = width.saturating_sub(cells);
+
+        let align = f.align().unwrap_or_else(|| {
+            // Find out which way Rust aligns this kind of value by
+            // padding it by one column
+            let width = s.chars().count() + 1;
+            let probe = match f.precision() {
+                Some(precision) => format!("{:width$.precision$}", self.value),
+                None => format!("{:width$}", self.value),
+            };
+            if probe.strip_prefix(' ') == Some(s.as_str()) {
+                Alignment::Right
+            } else {
+                Alignment::Left
+            }
+        });
+        let (before, after) = match align {
+            Alignment::Left => (0, padding),
+            Alignment::Right => (padding, 0),
+            Alignment::Center => (padding / 2, padding - padding / 2),
+        };
+
+        let fill = f.fill().to_string();
+        f.write_str(&fill.repeat(before))?;
+        f.write_str(&s)?;
+        f.write_str(&fill.repeat(after))
+    }
+}
+
+/// Format text into PETSCII bytes
+///
+/// Works like format!, but the first argument is the character set to
+/// encode with and the result is a Vec of PETSCII bytes.  Widths are
+/// counted in display cells, see Cells.  Arguments are passed by
+/// position and formatted with Display.  Variables captured in the
+/// format string are padded by chars, like format! does.
+///
+/// Every argument is wrapped in Cells, so a width or precision can't
+/// be taken from an argument: "{:>1$}" and "{:.*}" don't compile.
+/// Capture a usize variable for them instead, like "{:>width$}".
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     petscii::PetsciiConfig, petscii_format, CharacterSet, Configuration, SystemConfig,
+/// };
+///
+/// let config = PetsciiConfig::load().expect("Error loading config");
+/// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+///
+/// let bytes = petscii_format!(&petscii, "{:<6}{:>4}", "Bob", 100);
+///
+/// assert_eq!(petscii.decode(&bytes), "Bob    100");
+///
+/// // The é can't be encoded, so it isn't counted
+/// let bytes = petscii_format!(&petscii, "[{:>5}]", "CAFé");
+///
+/// assert_eq!(petscii.decode(&bytes), "[  CAF]");
+///
+/// // Widths from variables are captured in the format string
+/// let width = 5;
+/// let bytes = petscii_format!(&petscii, "[{:>width$}]", "CAFé");
+///
+/// assert_eq!(petscii.decode(&bytes), "[  CAF]");
+/// ```
+#[macro_export]
+macro_rules! petscii_format {
+    ($charset:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {{
+        let charset: &$crate::SystemConfig = $charset;
+        let mut writer = $crate::petscii::writer::PetsciiWriter::new(charset);
+        ::std::fmt::Write::write_fmt(
+            &mut writer,
+            ::std::format_args!($fmt $(, $crate::petscii::writer::Cells::new(&$arg, charset))*),
+        )
+        .expect("Writing to a PetsciiWriter can't fail");
+        writer.finish()
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use crate::{
+        petscii::{writer::PetsciiWriter, PetsciiConfig},
+        CharacterSet, Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn writer_matches_encode() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut writer = PetsciiWriter::new(&petscii);
+        for piece in ["Hel", "lo, ", "World", "!"] {
+            writer.write_str(piece).unwrap();
+        }
+
+        assert_eq!(writer.finish(), petscii.encode("Hello, World!"));
+    }
+
+    #[test]
+    fn writer_raw_shift_codes_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let mut writer = PetsciiWriter::new(&petscii);
+        writer.write_raw(&[0x0E]);
+        assert!(writer.is_shifted());
+        writer.push_str("a");
+        writer.write_raw(&[0x8E]);
+        writer.push_str("a");
+
+        assert_eq!(writer.as_bytes(), &[0x0e, 0x41, 0x8e, 0x0e, 0x41]);
+        assert_eq!(writer.finish(), vec![0x0e, 0x41, 0x8e, 0x0e, 0x41, 0x8e]);
+    }
+
+    #[test]
+    fn writer_format_alignment_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let bytes = petscii_format!(&petscii, "{:*^7}:{:5.1}:{:<3}:", "MENU", 2.25, 7);
+        assert_eq!(petscii.decode(&bytes), "*MENU**:  2.2:7  :");
+
+        // Shift codes don't take up cells
+        let bytes = petscii_format!(&petscii, "{:>4}", "ab");
+        assert_eq!(bytes, vec![0xa0, 0xa0, 0x0e, 0x41, 0x42, 0x8e]);
+    }
+
+    #[test]
+    fn writer_format_sign_and_zero_flags_work() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let bytes = petscii_format!(&petscii, "{:+}:{:05}:{:+06.1}", 7, -42, 2.25);
+        assert_eq!(
+            petscii.decode(&bytes),
+            format!("{:+}:{:05}:{:+06.1}", 7, -42, 2.25)
+        );
+
+        // Strings aren't zero padded, and shift codes don't take up
+        // cells here either
+        let bytes = petscii_format!(&petscii, "{:04}", "ab");
+        assert_eq!(bytes, vec![0x0e, 0x41, 0x42, 0x8e, 0xa0, 0xa0]);
+    }
+}
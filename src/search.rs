@@ -0,0 +1,147 @@
+//!
+//! Folding text for search indexes
+//!
+//! A search index over the catalogs of thousands of disk images
+//! needs one simple key per name, so "GAMES  #1", "games #1" with
+//! shifted space padding and a name with a heart in it all match the
+//! same query.  fold_for_search makes that key from decoded text, and
+//! fold_petscii_for_search makes it straight from PETSCII bytes.
+//!
+//! The rules are fixed, so an index built with one release of this
+//! crate can be searched with keys from another.  If they ever have
+//! to change, FOLD_VERSION changes with them, and an index should
+//! store the version it was built with.
+//!
+//! fold_for_search applies these rules, in order, to each character:
+//!
+//! 1. ASCII letters are lowercased.
+//! 2. The accented Latin-1 letters (U+00C0 to U+00FF) become their
+//!    base letters, lowercased, so É becomes e.  Æ becomes ae, ß
+//!    becomes ss and Þ becomes th.
+//! 3. ASCII digits and punctuation are kept.
+//! 4. Everything else, including whitespace, U+00A0 NO-BREAK SPACE,
+//!    control characters, graphics characters and symbols like £,
+//!    separates words.
+//!
+//! Then runs of separators become one space, and separators at the
+//! start and end are removed.
+//!
+//! fold_petscii_for_search doesn't use a configuration.  It follows
+//! the shift codes, reads letters, digits and punctuation as ASCII,
+//! treats every other code as a separator, and folds the result.
+//! That makes shifted space padding a separator at the end, which is
+//! removed.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+/// The version of the folding rules
+///
+/// This only changes if the rules do.
+pub const FOLD_VERSION: u32 = 1;
+
+/// The ASCII letters for an accented Latin-1 letter, lowercased
+fn fold_latin1(c: char) -> Option<&'static str> {
+    Some(match c {
+        'À'..='Å' | 'à'..='å' => "a",
+        'Æ' | 'æ' => "ae",
+        'Ç' | 'ç' => "c",
+        'È'..='Ë' | 'è'..='ë' => "e",
+        'Ì'..='Ï' | 'ì'..='ï' => "i",
+        'Ð' | 'ð' => "d",
+        'Ñ' | 'ñ' => "n",
+        'Ò'..='Ö' | 'Ø' | 'ò'..='ö' | 'ø' => "o",
+        'Ù'..='Ü' | 'ù'..='ü' => "u",
+        'Ý' | 'ý' | 'ÿ' => "y",
+        'Þ' | 'þ' => "th",
+        'ß' => "ss",
+        _ => return None,
+    })
+}
+
+/// Fold text into a search key
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::search::fold_for_search;
+///
+/// assert_eq!(fold_for_search("  GAMES\u{a0}#1 "), "games #1");
+/// assert_eq!(fold_for_search("Café ♥ Crêpes"), "cafe crepes");
+/// assert_eq!(fold_for_search("£5"), "5");
+/// ```
+pub fn fold_for_search(s: &str) -> String {
+    let mut folded = String::with_capacity(s.len());
+    let mut separated = false;
+
+    for c in s.chars() {
+        let kept = c.is_ascii_alphanumeric() || c.is_ascii_punctuation();
+        let letters = fold_latin1(c);
+        if !kept && letters.is_none() {
+            separated = true;
+            continue;
+        }
+
+        if separated && !folded.is_empty() {
+            folded.push(' ');
+        }
+        separated = false;
+        match letters {
+            Some(letters) => folded.push_str(letters),
+            None => folded.push(c.to_ascii_lowercase()),
+        }
+    }
+
+    folded
+}
+
+/// Fold PETSCII bytes into a search key
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::search::fold_petscii_for_search;
+///
+/// // A disk name with a heart and shifted space padding
+/// assert_eq!(fold_petscii_for_search(b"MY\xd3GAMES\xa0\xa0"), "my games");
+///
+/// // In the shifted character set, 0xC1 is a letter
+/// assert_eq!(fold_petscii_for_search(b"\x0e\xc7AME"), "game");
+/// ```
+pub fn fold_petscii_for_search(bytes: &[u8]) -> String {
+    let mut shifted = false;
+    let s: String = bytes
+        .iter()
+        .filter_map(|code| match *code {
+            0x0E | 0x8E => {
+                shifted = *code == 0x0E;
+                None
+            }
+            0x20..=0x5B | 0x5D => Some(char::from(*code)),
+            0x61..=0x7A if shifted => Some(char::from(*code - 0x20)),
+            0xC1..=0xDA if shifted => Some(char::from(*code - 0x80)),
+            _ => Some(' '),
+        })
+        .collect();
+
+    fold_for_search(&s)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::{fold_for_search, fold_petscii_for_search};
+
+    #[test]
+    fn search_folding_is_stable() {
+        // These keys must not change without a new FOLD_VERSION
+        assert_eq!(fold_for_search("ÆTHER Straße Þorn"), "aether strasse thorn");
+        assert_eq!(fold_for_search("\u{1fb8c}\u{1fb8c}"), "");
+        assert_eq!(fold_for_search("A-B,  C\r\nD"), "a-b, c d");
+
+        assert_eq!(
+            fold_petscii_for_search(b"\x12DISK\x92 \xb0 1\x0d"),
+            "disk 1"
+        );
+        assert_eq!(fold_petscii_for_search(b"\x0e\x61\x5c\xdaz"), "a zz");
+        assert_eq!(fold_petscii_for_search(b"\x61\xc1"), "");
+    }
+}
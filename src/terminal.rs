@@ -0,0 +1,203 @@
+//!
+//! Terminal capability probing
+//!
+//! The full-fidelity decoding of graphics characters uses the Symbols
+//! for Legacy Computing block.  Only some terminals and fonts can
+//! display it, and the rest show empty boxes instead.
+//!
+//! There isn't a reliable way to ask a terminal which glyphs its font
+//! has, so this module guesses from the environment: the locale has
+//! to use UTF-8 and the terminal has to be one known to draw the
+//! Legacy Computing characters itself or ship a font with them.
+//! Unknown terminals are assumed not to support them.
+//!
+//! The guess can be overridden with the FORBIDDEN_BANDS_DECODE_TARGET
+//! environment variable, set to "unicode" or "ascii".
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use crate::{DecodeOptions, DecodeTarget};
+
+/// The environment variable that overrides the detected decode target
+pub const DECODE_TARGET_VARIABLE: &str = "FORBIDDEN_BANDS_DECODE_TARGET";
+
+/// Values of TERM for terminals that draw the Legacy Computing
+/// characters
+const LEGACY_COMPUTING_TERMS: [&str; 5] = [
+    "xterm-kitty",
+    "foot",
+    "foot-extra",
+    "wezterm",
+    "xterm-ghostty",
+];
+
+/// Values of TERM_PROGRAM for terminals that draw the Legacy
+/// Computing characters
+const LEGACY_COMPUTING_TERM_PROGRAMS: [&str; 3] = ["WezTerm", "ghostty", "iTerm.app"];
+
+/// Environment variables only set by terminals that draw the Legacy
+/// Computing characters
+const LEGACY_COMPUTING_VARIABLES: [&str; 3] =
+    ["KITTY_WINDOW_ID", "WEZTERM_EXECUTABLE", "WT_SESSION"];
+
+/// What the terminal is likely able to display
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TerminalCapabilities {
+    /// The terminal uses UTF-8
+    pub unicode: bool,
+    /// The terminal can display the Symbols for Legacy Computing
+    /// characters
+    pub legacy_computing: bool,
+    /// The decode target set with the override variable
+    pub decode_target_override: Option<DecodeTarget>,
+}
+
+impl TerminalCapabilities {
+    /// Probe the capabilities of the current terminal from the
+    /// environment
+    pub fn from_env() -> TerminalCapabilities {
+        TerminalCapabilities::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Probe terminal capabilities using a function that looks up
+    /// environment variables
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::terminal::TerminalCapabilities;
+    ///
+    /// let capabilities = TerminalCapabilities::from_lookup(|name| match name {
+    ///     "LANG" => Some(String::from("en_US.UTF-8")),
+    ///     "TERM" => Some(String::from("xterm-kitty")),
+    ///     _ => None,
+    /// });
+    ///
+    /// assert!(capabilities.unicode);
+    /// assert!(capabilities.legacy_computing);
+    /// ```
+    pub fn from_lookup<F: Fn(&str) -> Option<String>>(lookup: F) -> TerminalCapabilities {
+        // The first locale variable that's set wins, like in setlocale
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|name| lookup(name))
+            .find(|value| !value.is_empty())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        // Windows Terminal doesn't set a locale, but it's always UTF-8
+        let unicode =
+            locale.contains("utf-8") || locale.contains("utf8") || lookup("WT_SESSION").is_some();
+
+        let term = lookup("TERM").unwrap_or_default();
+        let term_program = lookup("TERM_PROGRAM").unwrap_or_default();
+
+        let known_terminal = LEGACY_COMPUTING_TERMS.contains(&term.as_str())
+            || LEGACY_COMPUTING_TERM_PROGRAMS.contains(&term_program.as_str())
+            || LEGACY_COMPUTING_VARIABLES
+                .iter()
+                .any(|name| lookup(name).is_some());
+
+        let decode_target_override = match lookup(DECODE_TARGET_VARIABLE).as_deref() {
+            Some("unicode") => Some(DecodeTarget::Unicode),
+            Some("ascii") => Some(DecodeTarget::AsciiApproximation),
+            _ => None,
+        };
+
+        TerminalCapabilities {
+            unicode,
+            legacy_computing: unicode && term != "dumb" && term != "linux" && known_terminal,
+            decode_target_override,
+        }
+    }
+
+    /// The decode target to use for this terminal
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{terminal::TerminalCapabilities, DecodeTarget};
+    ///
+    /// let capabilities = TerminalCapabilities::from_lookup(|name| match name {
+    ///     "LANG" => Some(String::from("C")),
+    ///     _ => None,
+    /// });
+    ///
+    /// assert_eq!(capabilities.decode_target(), DecodeTarget::AsciiApproximation);
+    /// ```
+    pub fn decode_target(&self) -> DecodeTarget {
+        match self.decode_target_override {
+            Some(target) => target,
+            None if self.legacy_computing => DecodeTarget::Unicode,
+            None => DecodeTarget::AsciiApproximation,
+        }
+    }
+}
+
+/// Decoding options for the current terminal
+///
+/// Uses the full-fidelity Unicode target if the terminal likely
+/// supports the Symbols for Legacy Computing and the ASCII
+/// approximation target otherwise.
+pub fn detect_decode_options() -> DecodeOptions {
+    DecodeOptions::default().with_target(TerminalCapabilities::from_env().decode_target())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{terminal::TerminalCapabilities, DecodeTarget};
+
+    fn lookup_from<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| String::from(*value))
+        }
+    }
+
+    #[test]
+    fn terminal_locale_precedence_works() {
+        let vars = [("LC_ALL", "C"), ("LANG", "en_US.UTF-8"), ("TERM", "foot")];
+        let capabilities = TerminalCapabilities::from_lookup(lookup_from(&vars));
+
+        assert!(!capabilities.unicode);
+        assert!(!capabilities.legacy_computing);
+        assert_eq!(
+            capabilities.decode_target(),
+            DecodeTarget::AsciiApproximation
+        );
+    }
+
+    #[test]
+    fn terminal_unknown_terminal_uses_ascii() {
+        let vars = [("LANG", "en_US.UTF-8"), ("TERM", "xterm-256color")];
+        let capabilities = TerminalCapabilities::from_lookup(lookup_from(&vars));
+
+        assert!(capabilities.unicode);
+        assert!(!capabilities.legacy_computing);
+    }
+
+    #[test]
+    fn terminal_windows_terminal_works() {
+        let vars = [("WT_SESSION", "3a1b")];
+        let capabilities = TerminalCapabilities::from_lookup(lookup_from(&vars));
+
+        assert_eq!(capabilities.decode_target(), DecodeTarget::Unicode);
+    }
+
+    #[test]
+    fn terminal_override_works() {
+        let vars = [
+            ("LANG", "en_US.UTF-8"),
+            ("TERM_PROGRAM", "WezTerm"),
+            ("FORBIDDEN_BANDS_DECODE_TARGET", "ascii"),
+        ];
+        let capabilities = TerminalCapabilities::from_lookup(lookup_from(&vars));
+
+        assert!(capabilities.legacy_computing);
+        assert_eq!(
+            capabilities.decode_target(),
+            DecodeTarget::AsciiApproximation
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//!
+//! TI-99/4A string library
+//!
+//! The Texas Instruments TI-99/4A uses a character set based on
+//! ASCII.  Character codes 32 through 126 display the same characters
+//! as ASCII.  Code 30 is the cursor and code 31 is the "edge"
+//! character used to fill the area around the screen.
+//!
+//! Programs define their own characters with CALL CHAR.  TI BASIC
+//! allows redefining character codes 32 through 159, with codes 128
+//! through 159 making up character sets 13 through 16.  Extended
+//! BASIC allows codes 32 through 143.  Codes 32 through 126 are
+//! decoded as their built-in characters.  Codes above 127 (>7F in TI
+//! notation) have no built-in glyphs, so their meaning depends on the
+//! program.  All of them, up to 255, are mapped to the Unicode Private
+//! Use Area.  This keeps the conversion reversible and lets
+//! applications substitute their own glyphs.
+//!
+//! Text files on the TI-99/4A are usually stored as DISPLAY/VARIABLE
+//! 80 (DIS/VAR 80) files.  Each record is a length byte followed by
+//! the record data, and a length byte of 0xFF marks the end of the
+//! records in a 256-byte sector.  Files transferred to other systems
+//! are usually wrapped in a 128-byte TIFILES header.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    config_data,
+    error::{Error, ErrorKind},
+    load_cached, CharacterSet, SystemModule,
+};
+
+/// The size of a TI-99/4A disk sector
+pub const SECTOR_SIZE: usize = 256;
+
+/// The size of a TIFILES header
+pub const TIFILES_HEADER_SIZE: usize = 128;
+
+/// The TIFILES header signature, a length byte followed by "TIFILES"
+pub const TIFILES_SIGNATURE: [u8; 8] = [0x07, b'T', b'I', b'F', b'I', b'L', b'E', b'S'];
+
+/// The range of user-definable character codes and where they are
+/// placed in the Unicode Private Use Area
+///
+/// Like the PETSCII ScreenCode, this is stored in the
+/// configuration file as a list and deserialized into a struct.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct CustomCharacterArea {
+    /// The first user-definable character code
+    pub first: u8,
+    /// The last user-definable character code
+    pub last: u8,
+    /// The Unicode code point the first character code maps to
+    pub unicode_base: u32,
+}
+
+impl CustomCharacterArea {
+    /// Return true if the character code is in the custom character
+    /// area
+    pub fn contains(&self, code: u8) -> bool {
+        (self.first..=self.last).contains(&code)
+    }
+
+    /// Map a custom character code to its Private Use Area character
+    pub fn to_unicode(&self, code: u8) -> Option<char> {
+        if !self.contains(code) {
+            return None;
+        }
+        char::from_u32(self.unicode_base + u32::from(code - self.first))
+    }
+
+    /// Map a Private Use Area character back to a custom character
+    /// code
+    pub fn from_unicode(&self, c: char) -> Option<u8> {
+        let offset = u32::from(c).checked_sub(self.unicode_base)?;
+        let code = u32::from(self.first) + offset;
+        if code > u32::from(self.last) {
+            return None;
+        }
+        u8::try_from(code).ok()
+    }
+}
+
+/// Configuration data including character maps for the TI-99/4A
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ti99Config {
+    /// Version of the TI-99/4A config
+    pub version: String,
+
+    /// TI-99/4A character codes to Unicode codes
+    pub ti99_character_codes_to_unicode_codes: Map<String, Value>,
+
+    /// Unicode codes to TI-99/4A character codes
+    pub unicode_codes_to_ti99_character_codes: Map<String, Value>,
+
+    /// The user-definable character codes
+    pub custom_character_area: CustomCharacterArea,
+}
+
+/// Configuration data for the TI-99/4A module
+pub static CONFIG: RwLock<Option<Ti99Config>> = RwLock::new(None);
+
+impl SystemModule for Ti99Config {
+    const NAMESPACE: &'static str = "ti99";
+}
+
+impl Ti99Config {
+    /// Load the embedded TI-99/4A configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::ti99::Ti99Config;
+    ///
+    /// let config = Ti99Config::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.custom_character_area.first, 128);
+    /// ```
+    pub fn load() -> std::result::Result<Ti99Config, Error> {
+        load_cached(&CONFIG, config_data::TI99_CHARACTER_MAP)
+    }
+}
+
+impl CharacterSet for Ti99Config {
+    /// Decode a sequence of TI-99/4A character codes into a Unicode
+    /// string
+    ///
+    /// Character codes in the custom character area are decoded to
+    /// the Unicode Private Use Area.  Control codes without a
+    /// character are decoded as U+FFFD REPLACEMENT CHARACTER, which
+    /// doesn't encode back to anything.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{ti99::Ti99Config, CharacterSet};
+    ///
+    /// let config = Ti99Config::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.decode(&[0x48, 0x49, 0x80]), "HI\u{e000}");
+    /// ```
+    fn decode(&self, bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .filter_map(|c| {
+                let key = c.to_string();
+                match self.ti99_character_codes_to_unicode_codes.get(&key) {
+                    Some(Value::Number(v)) => v.as_u64().and_then(|v| char::from_u32(v as u32)),
+                    Some(_) => None,
+                    None => Some(
+                        self.custom_character_area
+                            .to_unicode(*c)
+                            .unwrap_or(char::REPLACEMENT_CHARACTER),
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    /// Encode a Unicode string into a sequence of TI-99/4A character
+    /// codes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{ti99::Ti99Config, CharacterSet};
+    ///
+    /// let config = Ti99Config::load().expect("Error loading config");
+    ///
+    /// assert_eq!(config.encode("HI\u{e000}"), vec![0x48, 0x49, 0x80]);
+    /// ```
+    fn encode(&self, s: &str) -> Vec<u8> {
+        s.chars()
+            .filter_map(|c| {
+                let key = u32::from(c).to_string();
+                match self.unicode_codes_to_ti99_character_codes.get(&key) {
+                    Some(Value::Number(v)) => v.as_u64().and_then(|v| u8::try_from(v).ok()),
+                    Some(_) => None,
+                    None => self.custom_character_area.from_unicode(c),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A TIFILES header
+///
+/// Only the fields needed to read the file records are parsed.
+#[derive(Clone, Copy, Debug)]
+pub struct TifilesHeader {
+    /// The number of 256-byte sectors in the file
+    pub sectors: u16,
+    /// The file status flags
+    pub flags: u8,
+    /// The number of records in each sector
+    pub records_per_sector: u8,
+    /// The number of bytes used in the last sector
+    pub eof_offset: u8,
+    /// The record length, the maximum length for variable records
+    pub record_length: u8,
+    /// The number of records for fixed files or sectors for variable
+    /// files
+    pub records: u16,
+}
+
+impl TifilesHeader {
+    /// Program file flag
+    pub const PROGRAM: u8 = 0x01;
+    /// INTERNAL data type flag, DISPLAY if not set
+    pub const INTERNAL: u8 = 0x02;
+    /// VARIABLE length records flag, FIXED if not set
+    pub const VARIABLE: u8 = 0x80;
+
+    /// Parse a TIFILES header from the start of a file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::ti99::{TifilesHeader, TIFILES_SIGNATURE};
+    ///
+    /// let mut data = [0u8; 128];
+    /// data[..8].copy_from_slice(&TIFILES_SIGNATURE);
+    /// data[9] = 1;
+    /// data[10] = TifilesHeader::VARIABLE;
+    /// data[13] = 80;
+    ///
+    /// let header = TifilesHeader::parse(&data).expect("Error parsing header");
+    ///
+    /// assert_eq!(header.sectors, 1);
+    /// assert!(header.is_variable());
+    /// assert_eq!(header.record_length, 80);
+    /// ```
+    pub fn parse(data: &[u8]) -> std::result::Result<TifilesHeader, Error> {
+        if data.len() < TIFILES_HEADER_SIZE || data[..8] != TIFILES_SIGNATURE {
+            return Err(Error::new(ErrorKind::Message(String::from(
+                "Missing TIFILES header",
+            ))));
+        }
+
+        Ok(TifilesHeader {
+            sectors: u16::from_be_bytes([data[8], data[9]]),
+            flags: data[10],
+            records_per_sector: data[11],
+            eof_offset: data[12],
+            record_length: data[13],
+            records: u16::from_le_bytes([data[14], data[15]]),
+        })
+    }
+
+    /// Return true if this is a program file
+    pub fn is_program(&self) -> bool {
+        self.flags & Self::PROGRAM != 0
+    }
+
+    /// Return true if the records are INTERNAL rather than DISPLAY
+    pub fn is_internal(&self) -> bool {
+        self.flags & Self::INTERNAL != 0
+    }
+
+    /// Return true if the file has variable length records
+    pub fn is_variable(&self) -> bool {
+        self.flags & Self::VARIABLE != 0
+    }
+}
+
+/// An iterator over the records in variable-length record sectors
+///
+/// Each record is returned as a slice of the record data without the
+/// length byte.
+pub struct VariableRecords<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> VariableRecords<'a> {
+    /// Iterate over the records in a sequence of 256-byte sectors
+    pub fn new(data: &'a [u8]) -> VariableRecords<'a> {
+        VariableRecords { data, position: 0 }
+    }
+}
+
+impl<'a> Iterator for VariableRecords<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let length = *self.data.get(self.position)?;
+
+            if length == 0xFF {
+                // End of the records in this sector, skip to the
+                // start of the next one
+                self.position = (self.position / SECTOR_SIZE + 1) * SECTOR_SIZE;
+                continue;
+            }
+
+            let start = self.position + 1;
+            let end = start + usize::from(length);
+            if end > self.data.len() {
+                self.position = self.data.len();
+                return None;
+            }
+            self.position = end;
+
+            return Some(&self.data[start..end]);
+        }
+    }
+}
+
+/// Decode the text records in a TI-99/4A DISPLAY file
+///
+/// The data may start with a TIFILES header, otherwise it's treated
+/// as DIS/VAR sectors.  Fixed length records have trailing spaces
+/// removed.
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::ti99::{decode_text_records, Ti99Config};
+///
+/// let config = Ti99Config::load().expect("Error loading config");
+///
+/// let mut sector = vec![0u8; 256];
+/// sector[..9].copy_from_slice(&[0x02, b'H', b'I', 0x04, b'T', b'I', b'9', b'9', 0xFF]);
+///
+/// let records = decode_text_records(&sector, &config).expect("Error decoding records");
+///
+/// assert_eq!(records, vec!["HI", "TI99"]);
+/// ```
+pub fn decode_text_records(
+    data: &[u8],
+    config: &Ti99Config,
+) -> std::result::Result<Vec<String>, Error> {
+    let header = if data.starts_with(&TIFILES_SIGNATURE) {
+        Some(TifilesHeader::parse(data)?)
+    } else {
+        None
+    };
+
+    let header = match header {
+        Some(header) => {
+            if header.is_program() || header.is_internal() {
+                return Err(Error::new(ErrorKind::Message(String::from(
+                    "Not a DISPLAY data file",
+                ))));
+            }
+            header
+        }
+        None => {
+            return Ok(VariableRecords::new(data)
+                .map(|r| config.decode(r))
+                .collect());
+        }
+    };
+
+    let body = &data[TIFILES_HEADER_SIZE..];
+
+    if header.is_variable() {
+        return Ok(VariableRecords::new(body)
+            .map(|r| config.decode(r))
+            .collect());
+    }
+
+    let record_length = usize::from(header.record_length);
+    let records_per_sector = usize::from(header.records_per_sector);
+    if record_length == 0 || records_per_sector == 0 {
+        return Err(Error::new(ErrorKind::Message(String::from(
+            "Invalid fixed record layout",
+        ))));
+    }
+
+    Ok(body
+        .chunks(SECTOR_SIZE)
+        .flat_map(|sector| sector.chunks_exact(record_length).take(records_per_sector))
+        .take(usize::from(header.records))
+        .map(|r| config.decode(r).trim_end_matches(' ').to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ti99::{
+            decode_text_records, Ti99Config, TifilesHeader, VariableRecords, TIFILES_SIGNATURE,
+        },
+        CharacterSet,
+    };
+
+    #[test]
+    fn ti99_decode_ascii_works() {
+        let config = Ti99Config::load().expect("Error loading config");
+
+        assert_eq!(config.decode(b"HELLO, world! {~}"), "HELLO, world! {~}");
+    }
+
+    #[test]
+    fn ti99_cursor_and_edge_characters_work() {
+        let config = Ti99Config::load().expect("Error loading config");
+
+        assert_eq!(config.decode(&[0x1E, 0x1F]), "█ ");
+    }
+
+    #[test]
+    fn ti99_custom_characters_round_trip() {
+        let config = Ti99Config::load().expect("Error loading config");
+        let data: [u8; 4] = [0x41, 0x80, 0x8F, 0x9F];
+
+        let s = config.decode(&data);
+        assert_eq!(s, "A\u{e000}\u{e00f}\u{e01f}");
+
+        assert_eq!(config.encode(&s), data);
+    }
+
+    #[test]
+    fn ti99_decoded_characters_round_trip() {
+        let config = Ti99Config::load().expect("Error loading config");
+
+        // Every character code decodes to a character that encodes
+        // back to it, or to U+FFFD.  The edge character looks like a
+        // space and encodes as one.
+        for code in (0..=255).filter(|code| *code != 0x1F) {
+            let s = config.decode(&[code]);
+            if s == "\u{fffd}" {
+                assert!(config.encode(&s).is_empty());
+            } else {
+                assert_eq!(config.encode(&s), [code], "{code:#04x} decoded to {s:?}");
+            }
+        }
+        assert_eq!(config.decode(&[0x01, 0x7F]), "\u{fffd}\u{fffd}");
+    }
+
+    #[test]
+    fn ti99_encode_drops_unmapped_characters() {
+        let config = Ti99Config::load().expect("Error loading config");
+
+        assert_eq!(config.encode("A£B"), vec![0x41, 0x42]);
+    }
+
+    #[test]
+    fn variable_records_skip_to_next_sector_works() {
+        let mut data = vec![0u8; 512];
+        data[..4].copy_from_slice(&[0x01, b'A', 0x00, 0xFF]);
+        data[256..260].copy_from_slice(&[0x02, b'B', b'C', 0xFF]);
+
+        let records: Vec<&[u8]> = VariableRecords::new(&data).collect();
+
+        assert_eq!(records, vec![&b"A"[..], &b""[..], &b"BC"[..]]);
+    }
+
+    #[test]
+    fn decode_tifiles_fixed_records_works() {
+        let config = Ti99Config::load().expect("Error loading config");
+
+        let mut data = vec![0u8; 128 + 256];
+        data[..8].copy_from_slice(&TIFILES_SIGNATURE);
+        data[9] = 1;
+        data[11] = 32;
+        data[13] = 8;
+        data[14] = 2;
+        data[128..144].copy_from_slice(b"LINE 1  LINE 2  ");
+
+        let header = TifilesHeader::parse(&data).expect("Error parsing header");
+        assert!(!header.is_variable());
+
+        let records = decode_text_records(&data, &config).expect("Error decoding records");
+
+        assert_eq!(records, vec!["LINE 1", "LINE 2"]);
+    }
+
+    #[test]
+    fn decode_tifiles_program_fails() {
+        let config = Ti99Config::load().expect("Error loading config");
+
+        let mut data = vec![0u8; 128];
+        data[..8].copy_from_slice(&TIFILES_SIGNATURE);
+        data[10] = TifilesHeader::PROGRAM;
+
+        assert!(decode_text_records(&data, &config).is_err());
+    }
+}
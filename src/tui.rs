@@ -0,0 +1,303 @@
+//!
+//! Drawing decoded PETSCII in terminal frontends
+//!
+//! Conversions from RichText and the screen model to ratatui text,
+//! and crossterm commands that print RichText with its colors, so a
+//! terminal program can show PETSCII art and directory listings in
+//! color without working out the styles itself.
+//!
+//! The C64 colors are drawn as 24-bit colors from a palette.
+//! Terminals without 24-bit color approximate them.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::io::{self, Write};
+
+use crossterm::{
+    style::{Attribute, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor},
+    QueueableCommand,
+};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Text},
+};
+
+use crate::{
+    palette::Palette,
+    petscii::{
+        rich::{RichText, Span},
+        screen::Screen,
+        Color,
+    },
+    SystemConfig,
+};
+
+/// The ratatui color for a C64 color
+pub fn ratatui_color(color: Color, palette: &Palette) -> ratatui::style::Color {
+    let rgb = palette.rgb(color);
+    ratatui::style::Color::Rgb(rgb.r, rgb.g, rgb.b)
+}
+
+/// The crossterm color for a C64 color
+pub fn crossterm_color(color: Color, palette: &Palette) -> crossterm::style::Color {
+    let rgb = palette.rgb(color);
+    crossterm::style::Color::Rgb {
+        r: rgb.r,
+        g: rgb.g,
+        b: rgb.b,
+    }
+}
+
+/// The ratatui style of a span
+///
+/// # Examples
+///
+/// ```
+/// use forbidden_bands::{
+///     palette::Palette,
+///     petscii::{rich::Span, Color},
+///     tui::span_style,
+/// };
+/// use ratatui::style::Modifier;
+///
+/// let span = Span {
+///     text: String::from("HI"),
+///     foreground: Color::White,
+///     background: None,
+///     reversed: true,
+///     blink: false,
+/// };
+/// let style = span_style(&span, &Palette::COLODORE);
+///
+/// assert_eq!(style.fg, Some(ratatui::style::Color::Rgb(0xff, 0xff, 0xff)));
+/// assert_eq!(style.bg, None);
+/// assert!(style.add_modifier.contains(Modifier::REVERSED));
+/// ```
+pub fn span_style(span: &Span, palette: &Palette) -> Style {
+    let mut style = Style::new().fg(ratatui_color(span.foreground, palette));
+    if let Some(background) = span.background {
+        style = style.bg(ratatui_color(background, palette));
+    }
+    if span.reversed {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    if span.blink {
+        style = style.add_modifier(Modifier::SLOW_BLINK);
+    }
+    style
+}
+
+impl RichText {
+    /// The text as ratatui Text, a line for each line of the text
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     palette::Palette,
+    ///     petscii::PetsciiConfig,
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let text = petscii
+    ///     .decode_rich(&[0x1C, 0x48, 0x49, 0x0D, 0x05, 0x41])
+    ///     .to_ratatui_text(&Palette::COLODORE);
+    ///
+    /// assert_eq!(text.lines.len(), 2);
+    /// assert_eq!(text.lines[1].spans[0].content, "A");
+    /// ```
+    pub fn to_ratatui_text(&self, palette: &Palette) -> Text<'static> {
+        self.lines()
+            .into_iter()
+            .map(|spans| {
+                Line::from(
+                    spans
+                        .iter()
+                        .map(|span| {
+                            ratatui::text::Span::styled(
+                                span.text.clone(),
+                                span_style(span, palette),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect()
+    }
+
+    /// Queue crossterm commands that print the text in its colors
+    ///
+    /// Line breaks are printed as carriage return and line feed, so
+    /// they work in raw mode too.  The colors and attributes are
+    /// reset at the end.  Call flush on the writer to print it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     palette::Palette,
+    ///     petscii::PetsciiConfig,
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut out = Vec::new();
+    /// petscii
+    ///     .decode_rich(&[0x1C, 0x48, 0x49])
+    ///     .queue_crossterm(&mut out, &Palette::COLODORE)
+    ///     .expect("Error writing");
+    ///
+    /// assert!(String::from_utf8(out).unwrap().contains("HI"));
+    /// ```
+    pub fn queue_crossterm<W: Write>(&self, writer: &mut W, palette: &Palette) -> io::Result<()> {
+        for (i, line) in self.lines().iter().enumerate() {
+            if i > 0 {
+                writer.queue(Print("\r\n"))?;
+            }
+            for span in line {
+                writer.queue(SetAttribute(Attribute::Reset))?;
+                writer.queue(SetForegroundColor(crossterm_color(
+                    span.foreground,
+                    palette,
+                )))?;
+                if let Some(background) = span.background {
+                    writer.queue(SetBackgroundColor(crossterm_color(background, palette)))?;
+                }
+                if span.reversed {
+                    writer.queue(SetAttribute(Attribute::Reverse))?;
+                }
+                if span.blink {
+                    writer.queue(SetAttribute(Attribute::SlowBlink))?;
+                }
+                writer.queue(Print(&span.text))?;
+            }
+        }
+
+        writer.queue(SetAttribute(Attribute::Reset))?;
+        writer.queue(ResetColor)?;
+        Ok(())
+    }
+}
+
+impl Screen {
+    /// The screen as ratatui lines, with the screen background
+    ///
+    /// Like to_html, reversed characters are drawn with the text and
+    /// background colors swapped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use forbidden_bands::{
+    ///     palette::Palette,
+    ///     petscii::{screen::Screen, Color, PetsciiConfig},
+    ///     Configuration, SystemConfig,
+    /// };
+    ///
+    /// let config = PetsciiConfig::load().expect("Error loading config");
+    /// let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+    ///
+    /// let mut screen = Screen::new(2, 1);
+    /// screen.set(0, 0, 0x08, Color::White);
+    ///
+    /// let lines = screen.to_ratatui_lines(&petscii, &Palette::COLODORE);
+    /// assert_eq!(lines[0].spans[0].content, "H");
+    /// assert_eq!(lines[0].spans[1].content, " ");
+    /// ```
+    pub fn to_ratatui_lines(
+        &self,
+        petscii: &SystemConfig,
+        palette: &Palette,
+    ) -> Vec<Line<'static>> {
+        let background = ratatui_color(self.background, palette);
+
+        (0..self.rows)
+            .map(|row| {
+                let mut spans: Vec<ratatui::text::Span<'static>> = Vec::new();
+                for cell in self.row(row).unwrap_or_default() {
+                    let color = ratatui_color(cell.color, palette);
+                    let style = if cell.is_reversed() {
+                        Style::new().fg(background).bg(color)
+                    } else {
+                        Style::new().fg(color).bg(background)
+                    };
+                    let c = self.char_for(cell.screen_code & 0x7F, petscii);
+
+                    match spans.last_mut() {
+                        Some(last) if last.style == style => last.content.to_mut().push(c),
+                        _ => spans.push(ratatui::text::Span::styled(c.to_string(), style)),
+                    }
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Modifier;
+
+    use crate::{
+        cbm::{
+            directory::{DirectoryEntry, DirectoryListing, FileType},
+            write::DiskHeader,
+        },
+        palette::Palette,
+        petscii::{screen::Screen, Color, PetsciiConfig},
+        tui::ratatui_color,
+        Configuration, SystemConfig,
+    };
+
+    #[test]
+    fn tui_screen_swaps_reversed_colors() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+        let palette = Palette::COLODORE;
+
+        let mut screen = Screen::new(2, 1);
+        screen.set(0, 0, 0x81, Color::Red);
+        screen.set(1, 0, 0x81, Color::Red);
+
+        let lines = screen.to_ratatui_lines(&petscii, &palette);
+        assert_eq!(lines[0].spans.len(), 1);
+        assert_eq!(lines[0].spans[0].content, "AA");
+        assert_eq!(
+            lines[0].spans[0].style.fg,
+            Some(ratatui_color(Color::Blue, &palette))
+        );
+        assert_eq!(
+            lines[0].spans[0].style.bg,
+            Some(ratatui_color(Color::Red, &palette))
+        );
+    }
+
+    #[test]
+    fn tui_directory_listing_works() {
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().unwrap();
+
+        let listing = DirectoryListing {
+            header: DiskHeader::new("DISK", "01"),
+            entries: vec![DirectoryEntry::new("GAME", 12, FileType::Prg)],
+            blocks_free: 652,
+        };
+        let bytes = listing.to_petscii(&petscii).unwrap();
+        let text = petscii
+            .decode_rich(&bytes)
+            .to_ratatui_text(&Palette::COLODORE);
+
+        // The header is reversed after the drive number
+        assert_eq!(text.lines[0].spans[0].content, "0 ");
+        assert!(text.lines[0].spans[1]
+            .style
+            .add_modifier
+            .contains(Modifier::REVERSED));
+        assert_eq!(text.lines[1].to_string(), "12   \"GAME\"             PRG");
+    }
+}
@@ -0,0 +1,189 @@
+//!
+//! Differential tests against petcat
+//!
+//! petcat, the BASIC lister that comes with VICE, is what a lot of
+//! community tooling uses to list programs.  These tests generate
+//! random BASIC V2 programs, list them with petcat and with this
+//! crate, and compare the listings.  They only run with the
+//! petcat-tests feature, and pass without checking anything when
+//! petcat isn't on the PATH:
+//!
+//! ```text
+//! cargo test --features petcat-tests --test petcat
+//! ```
+//!
+//! Each divergence is written as a line of JSON to
+//! petcat-divergences.jsonl in Cargo's temporary directory for
+//! integration tests, with the program in hex and both listings, so
+//! a configuration author can see which codes disagree.
+//!
+//! The programs use keywords, letters, digits and punctuation, but no
+//! quotes, remarks or control codes.  petcat writes those as its own
+//! brace mnemonics, which aren't this crate's names, so they'd all be
+//! reported.  cbmconvert isn't covered: it converts disk and archive
+//! images, and this crate doesn't write them.
+#![warn(missing_docs)]
+#![warn(unsafe_code)]
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    process::Command,
+};
+
+use forbidden_bands::{
+    cbm::{
+        basic::{basic_lines, FIRST_TOKEN, REM_TOKEN},
+        prg::{Prg, C64_BASIC_START},
+    },
+    petscii::PetsciiConfig,
+    CharacterSet, Configuration, SystemConfig,
+};
+use proptest::prelude::*;
+use serde::Serialize;
+
+/// The characters in the generated programs besides keywords
+const CHARACTERS: &[u8] = b" ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789+-*/=<>(),;:$%.";
+
+/// The power token, which petcat writes as an arrow mnemonic
+const POWER_TOKEN: u8 = 0xAE;
+
+/// A program the listings disagree on
+#[derive(Debug, Serialize)]
+struct Divergence {
+    /// The tool the listing was compared with
+    tool: &'static str,
+    /// The PRG file, in hex
+    program: String,
+    /// This crate's listing
+    ours: Vec<String>,
+    /// The tool's listing
+    theirs: Vec<String>,
+}
+
+/// True if petcat can be run
+fn petcat_installed() -> bool {
+    Command::new("petcat").arg("-h").output().is_ok()
+}
+
+/// A path in Cargo's temporary directory for integration tests
+fn temporary_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+/// Build a PRG at the C64 BASIC start from lines of tokens
+fn program(lines: &[(u16, Vec<u8>)]) -> Vec<u8> {
+    let mut bytes = C64_BASIC_START.to_le_bytes().to_vec();
+    let mut address = C64_BASIC_START;
+    for (line_number, tokens) in lines {
+        address += 4 + tokens.len() as u16 + 1;
+        bytes.extend_from_slice(&address.to_le_bytes());
+        bytes.extend_from_slice(&line_number.to_le_bytes());
+        bytes.extend_from_slice(tokens);
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(&[0, 0]);
+    bytes
+}
+
+/// The lines of a listing, compared without regard to case
+///
+/// petcat lists unshifted letters as lowercase ASCII, and this crate
+/// decodes them as uppercase.
+fn normalize(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .map(|line| line.trim_end().to_ascii_lowercase())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// List a program with this crate
+fn our_listing(prg: &[u8], petscii: &SystemConfig) -> Vec<String> {
+    let prg = Prg::parse(prg).expect("Error parsing generated program");
+    let lines = basic_lines(prg.data).expect("Error reading generated program");
+    let listing: String = lines
+        .iter()
+        .map(|line| {
+            format!(
+                "{} {}\n",
+                line.line_number,
+                petscii.decode(&line.detokenize())
+            )
+        })
+        .collect();
+    normalize(&listing)
+}
+
+/// List a program with petcat
+fn petcat_listing(prg: &[u8], name: &str) -> Vec<String> {
+    let input = temporary_path(&format!("{name}.prg"));
+    let output = temporary_path(&format!("{name}.txt"));
+    fs::write(&input, prg).expect("Error writing program");
+
+    let status = Command::new("petcat")
+        .args(["-2", "-nh", "-o"])
+        .arg(&output)
+        .arg("--")
+        .arg(&input)
+        .status()
+        .expect("Error running petcat");
+    assert!(status.success(), "petcat failed on {}", input.display());
+
+    let listing = fs::read(&output).expect("Error reading petcat output");
+    normalize(&String::from_utf8_lossy(&listing))
+}
+
+/// Append a divergence to the report
+fn report(divergence: &Divergence) {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(temporary_path("petcat-divergences.jsonl"))
+        .expect("Error opening divergence report");
+    let json = serde_json::to_string(divergence).expect("Error writing divergence");
+    writeln!(file, "{json}").expect("Error writing divergence report");
+}
+
+/// A tokenized line of keywords and plain characters
+fn line_tokens() -> impl Strategy<Value = Vec<u8>> {
+    let keyword = (FIRST_TOKEN..=0xCB).prop_filter("no remarks or powers", |token| {
+        *token != REM_TOKEN && *token != POWER_TOKEN
+    });
+    let character = proptest::sample::select(CHARACTERS);
+    proptest::collection::vec(prop_oneof![keyword, character], 1..40)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn petcat_lists_programs_the_same(
+        lines in proptest::collection::btree_map(0..64000u16, line_tokens(), 1..8)
+    ) {
+        if !petcat_installed() {
+            eprintln!("petcat isn't installed, skipping");
+            return Ok(());
+        }
+
+        let config = PetsciiConfig::load().expect("Error loading config");
+        let petscii = config.system::<SystemConfig>().expect("Error loading PETSCII config");
+
+        let lines: Vec<(u16, Vec<u8>)> = lines.into_iter().collect();
+        let prg = program(&lines);
+        let ours = our_listing(&prg, &petscii);
+        let theirs = petcat_listing(&prg, "petcat-differential");
+
+        if ours != theirs {
+            let divergence = Divergence {
+                tool: "petcat",
+                program: prg.iter().map(|b| format!("{b:02x}")).collect(),
+                ours,
+                theirs,
+            };
+            report(&divergence);
+            prop_assert!(false, "Listings differ: {:?}", divergence);
+        }
+    }
+}